@@ -0,0 +1,155 @@
+//! Micro-benchmark per i percorsi caldi del motore (movegen, make/unmake,
+//! eval, TT, attacchi) e ricerche a profondità fissa. Le posizioni sono
+//! fisse per rendere i numeri confrontabili tra una revisione e l'altra.
+//!
+//! Eseguire con `cargo bench --bench micro`.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use scacchista::board::{Board, Color, Move};
+use scacchista::eval::evaluate;
+use scacchista::search::tt::{NodeType, TranspositionTable};
+use scacchista::search::{Search, SearchParams};
+
+/// Profondità delle ricerche a profondità fissa
+const SEARCH_DEPTH: u8 = 5;
+
+const POSITIONS: &[(&str, &str)] = &[
+    (
+        "startpos",
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    ),
+    (
+        "kiwipete",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    ),
+    ("endgame", "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"),
+];
+
+fn board_from_fen(fen: &str) -> Board {
+    scacchista::init();
+    let mut board = Board::new();
+    board.set_from_fen(fen).expect("valid benchmark FEN");
+    board
+}
+
+fn bench_generate_moves(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate_moves");
+    for (name, fen) in POSITIONS {
+        let mut board = board_from_fen(fen);
+        group.bench_function(*name, |b| b.iter(|| black_box(board.generate_moves())));
+    }
+    group.finish();
+}
+
+fn bench_make_unmake(c: &mut Criterion) {
+    let mut group = c.benchmark_group("make_unmake_all");
+    for (name, fen) in POSITIONS {
+        let mut board = board_from_fen(fen);
+        let moves = board.generate_moves();
+        group.bench_function(*name, |b| {
+            b.iter(|| {
+                for &mv in &moves {
+                    let undo = board.make_move(black_box(mv));
+                    board.unmake_move(undo);
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_evaluate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("evaluate");
+    for (name, fen) in POSITIONS {
+        let board = board_from_fen(fen);
+        group.bench_function(*name, |b| b.iter(|| black_box(evaluate(black_box(&board)))));
+    }
+    group.finish();
+}
+
+fn bench_is_square_attacked(c: &mut Criterion) {
+    let board = board_from_fen(POSITIONS[1].1);
+    c.bench_function("is_square_attacked/all_squares", |b| {
+        b.iter(|| {
+            let mut count = 0u32;
+            for sq in 0..64 {
+                if board.is_square_attacked(black_box(sq), Color::Black) {
+                    count += 1;
+                }
+            }
+            count
+        })
+    });
+}
+
+fn bench_tt(c: &mut Criterion) {
+    let tt = TranspositionTable::new(16);
+    // Chiavi pseudo-casuali deterministiche (xorshift) per coprire tutta la tabella
+    let keys: Vec<u64> = {
+        let mut x = 0x9E37_79B9_7F4A_7C15u64;
+        (0..1024)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                x
+            })
+            .collect()
+    };
+
+    c.bench_function("tt/store_1024", |b| {
+        b.iter(|| {
+            for (i, &key) in keys.iter().enumerate() {
+                tt.store(key, i as i16, 8, NodeType::Exact, Move::NONE);
+            }
+        })
+    });
+
+    for (i, &key) in keys.iter().enumerate() {
+        tt.store(key, i as i16, 8, NodeType::Exact, Move::NONE);
+    }
+    c.bench_function("tt/probe_1024", |b| {
+        b.iter(|| {
+            let mut hits = 0u32;
+            for &key in &keys {
+                if tt.probe(black_box(key)).is_some() {
+                    hits += 1;
+                }
+            }
+            hits
+        })
+    });
+}
+
+fn bench_search(c: &mut Criterion) {
+    let mut group = c.benchmark_group("search_depth_5");
+    // Una ricerca dura fino a qualche centinaio di millisecondi: bastano pochi campioni
+    group.sample_size(10);
+    for (name, fen) in POSITIONS {
+        let board = board_from_fen(fen);
+        group.bench_function(*name, |b| {
+            // Ricerca nuova (TT vuota) a ogni iterazione; l'allocazione della
+            // TT resta fuori dalla misura
+            b.iter_batched(
+                || {
+                    let params = SearchParams::new().max_depth(SEARCH_DEPTH).time_limit(0);
+                    Search::new(board.clone(), 16, params)
+                },
+                |mut search| black_box(search.search(Some(SEARCH_DEPTH))),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_generate_moves,
+    bench_make_unmake,
+    bench_evaluate,
+    bench_is_square_attacked,
+    bench_tt,
+    bench_search
+);
+criterion_main!(benches);
@@ -1,5 +1,6 @@
 use clap::Parser;
-use scacchista::board::{Board, START_FEN};
+use scacchista::board::{move_to_uci, Board, START_FEN};
+use scacchista::perft::oracle;
 use shakmaty::fen::Fen;
 use shakmaty::{Chess, Position};
 
@@ -22,30 +23,7 @@ fn perft_debug(board: &mut Board, depth: u8) -> (u64, Vec<(String, u64)>) {
     let mut move_counts = Vec::new();
 
     for mv in moves {
-        // Save board state and convert to notation after undo
-        let from = scacchista::board::move_from_sq(mv);
-        let to = scacchista::board::move_to_sq(mv);
-        let piece = scacchista::board::move_piece(mv);
-
-        let files = ['a', 'b', 'c', 'd', 'e', 'f', 'g', 'h'];
-        let ranks = ['1', '2', '3', '4', '5', '6', '7', '8'];
-
-        let piece_char = match piece {
-            scacchista::board::PieceKind::Pawn => "",
-            scacchista::board::PieceKind::Knight => "N",
-            scacchista::board::PieceKind::Bishop => "B",
-            scacchista::board::PieceKind::Rook => "R",
-            scacchista::board::PieceKind::Queen => "Q",
-            scacchista::board::PieceKind::King => "K",
-        };
-
-        let mv_str = format!(
-            "{}{}{}{}",
-            piece_char,
-            files[from % 8],
-            ranks[7 - (from / 8)],
-            files[to % 8]
-        );
+        let mv_str = move_to_uci(mv);
         let undo = board.make_move(mv);
         let (nodes, _) = perft_debug(board, depth - 1);
         board.unmake_move(undo);
@@ -66,7 +44,7 @@ fn perft_debug_shakmaty(pos: &Chess, depth: u8) -> (u64, Vec<(String, u64)>) {
     let mut move_counts = Vec::new();
 
     for m in pos.legal_moves() {
-        let mv_str = format!("{}", m);
+        let mv_str = m.to_uci(shakmaty::CastlingMode::Standard).to_string();
         let mut new_pos = pos.clone();
         new_pos.play_unchecked(&m);
         let (nodes, _) = perft_debug_shakmaty(&new_pos, depth - 1);
@@ -146,5 +124,11 @@ fn main() {
                 println!("  {}: Shakmaty={}, Scacchista=missing", mv, sh_count);
             }
         }
+
+        match oracle::find_divergence(&args.fen, args.depth) {
+            Ok(Some(divergence)) => println!("\nFirst divergence {}", divergence),
+            Ok(None) => println!("\nNo diverging position found"),
+            Err(e) => println!("\nOracle error: {}", e),
+        }
     }
 }
@@ -0,0 +1,197 @@
+//! Static position analysis for teaching frontends.
+//!
+//! [`hotspots`] reports, for each side, the pieces with the fewest safe squares
+//! and the captures that win material according to SEE. It needs no search:
+//! everything is built from attack bitboards and [`Board::see`], so tutoring
+//! UIs can call it after every move.
+
+use crate::board::{Board, Color, Move, PieceKind};
+
+/// A piece with little room to move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstrainedPiece {
+    pub square: usize,
+    pub kind: PieceKind,
+    /// Squares the piece attacks that are not occupied by its own pieces
+    pub mobility: u32,
+    /// Of those, squares not attacked by an enemy pawn
+    pub safe_mobility: u32,
+}
+
+/// A legal capture that wins material after the whole exchange
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Threat {
+    pub mv: Move,
+    /// SEE of the capture, always positive
+    pub gain: i16,
+}
+
+/// Hotspots of one side: its own constrained pieces and the threats it makes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SideHotspots {
+    /// Most constrained first (least safe mobility, then most valuable)
+    pub constrained: Vec<ConstrainedPiece>,
+    /// Biggest gain first
+    pub threats: Vec<Threat>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Hotspots {
+    pub white: SideHotspots,
+    pub black: SideHotspots,
+}
+
+impl Hotspots {
+    pub fn side(&self, color: Color) -> &SideHotspots {
+        match color {
+            Color::White => &self.white,
+            Color::Black => &self.black,
+        }
+    }
+}
+
+/// Up to `n` constrained pieces and `n` SEE-winning captures for each side.
+///
+/// Threats are computed for both sides regardless of the side to move: for the
+/// side not on move they are what it threatens to take next turn.
+pub fn hotspots(board: &Board, n: usize) -> Hotspots {
+    Hotspots {
+        white: side_hotspots(board, Color::White, n),
+        black: side_hotspots(board, Color::Black, n),
+    }
+}
+
+fn side_hotspots(board: &Board, color: Color, n: usize) -> SideHotspots {
+    let mut constrained = constrained_pieces(board, color);
+    constrained.truncate(n);
+    let mut threats = threats(board, color);
+    threats.truncate(n);
+    SideHotspots {
+        constrained,
+        threats,
+    }
+}
+
+/// Knights, bishops, rooks and queens of `color`, most constrained first
+pub fn constrained_pieces(board: &Board, color: Color) -> Vec<ConstrainedPiece> {
+    let (own_occ, enemy) = match color {
+        Color::White => (board.white_occ, Color::Black),
+        Color::Black => (board.black_occ, Color::White),
+    };
+    let unsafe_squares = pawn_attacks(board.piece_bb(PieceKind::Pawn, enemy), enemy);
+
+    let mut pieces = Vec::new();
+    for kind in [
+        PieceKind::Knight,
+        PieceKind::Bishop,
+        PieceKind::Rook,
+        PieceKind::Queen,
+    ] {
+        let mut bb = board.piece_bb(kind, color);
+        while bb != 0 {
+            let sq = bb.trailing_zeros() as usize;
+            bb &= bb - 1;
+            let attacks = match kind {
+                PieceKind::Knight => crate::utils::knight_attacks(sq),
+                PieceKind::Bishop => crate::magic::bishop_attacks(sq, board.occ),
+                PieceKind::Rook => crate::magic::rook_attacks(sq, board.occ),
+                _ => crate::magic::queen_attacks(sq, board.occ),
+            } & !own_occ;
+            pieces.push(ConstrainedPiece {
+                square: sq,
+                kind,
+                mobility: attacks.count_ones(),
+                safe_mobility: (attacks & !unsafe_squares).count_ones(),
+            });
+        }
+    }
+    pieces.sort_by_key(|p| (p.safe_mobility, std::cmp::Reverse(p.kind as u8), p.square));
+    pieces
+}
+
+/// Legal captures by `color` with a positive SEE, biggest gain first
+pub fn threats(board: &Board, color: Color) -> Vec<Threat> {
+    let mut board = board.clone();
+    if board.side != color {
+        // Seen from the side not to move: no en passant right, the king is
+        // never a capture target
+        board.side = color;
+        board.ep = None;
+    }
+    let mut captures = Vec::new();
+    board.generate_captures(&mut captures);
+    let mut threats: Vec<Threat> = captures
+        .into_iter()
+        .filter(|&mv| matches!(mv.captured(), Some(kind) if kind != PieceKind::King))
+        .filter_map(|mv| {
+            let gain = board.see(mv);
+            (gain > 0).then_some(Threat { mv, gain })
+        })
+        .collect();
+    threats.sort_by_key(|t| (std::cmp::Reverse(t.gain), t.mv));
+    threats
+}
+
+/// Squares attacked by the pawns in `pawns` of `color`
+fn pawn_attacks(pawns: u64, color: Color) -> u64 {
+    match color {
+        Color::White => {
+            ((pawns & crate::utils::NOT_FILE_A) << 7) | ((pawns & crate::utils::NOT_FILE_H) << 9)
+        }
+        Color::Black => {
+            ((pawns & crate::utils::NOT_FILE_A) >> 9) | ((pawns & crate::utils::NOT_FILE_H) >> 7)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{move_to_uci, START_FEN};
+
+    fn board(fen: &str) -> Board {
+        crate::init();
+        let mut board = Board::new();
+        board.set_from_fen(fen).unwrap();
+        board
+    }
+
+    #[test]
+    fn test_start_position_has_no_threats() {
+        let hs = hotspots(&board(START_FEN), 3);
+        assert!(hs.white.threats.is_empty());
+        assert!(hs.black.threats.is_empty());
+        // Bishops, rooks and queen are boxed in; the queen is listed first
+        assert_eq!(hs.white.constrained.len(), 3);
+        assert!(hs.white.constrained.iter().all(|p| p.safe_mobility == 0));
+        assert_eq!(hs.white.constrained[0].kind, PieceKind::Queen);
+    }
+
+    #[test]
+    fn test_threats_are_see_verified_for_both_sides() {
+        // White to move: Nxe5 wins a knight, Qxd5 loses the queen to exd5.
+        // Black (not to move) threatens to take the undefended knight on f3
+        let b = board("4k3/8/4p3/3pn3/6b1/5N2/3Q4/4K3 w - - 0 1");
+        let hs = hotspots(&b, 5);
+        let uci = |threats: &[Threat]| -> Vec<String> {
+            let mut moves: Vec<String> = threats.iter().map(|t| move_to_uci(t.mv)).collect();
+            moves.sort();
+            moves
+        };
+        assert_eq!(uci(&hs.white.threats), vec!["f3e5"]);
+        assert_eq!(hs.white.threats[0].gain, 320);
+        assert_eq!(uci(&hs.black.threats), vec!["e5f3", "g4f3"]);
+        assert!(hs.black.threats.iter().all(|t| t.gain == 320));
+    }
+
+    #[test]
+    fn test_boxed_in_piece_is_most_constrained() {
+        // The knight on h1 is hemmed in by its own pawns, the rook on d1 is free
+        let b = board("4k3/8/8/8/8/6P1/5P2/3RK2N w - - 0 1");
+        let pieces = constrained_pieces(&b, Color::White);
+        assert_eq!(pieces[0].kind, PieceKind::Knight);
+        assert_eq!(pieces[0].square, 7);
+        assert_eq!(pieces[0].mobility, 0);
+        assert!(pieces[1].safe_mobility > 0);
+    }
+}
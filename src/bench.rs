@@ -0,0 +1,103 @@
+//! Reproducible node-count benchmark (`bench`).
+//!
+//! Searches a fixed set of positions to a fixed depth, single-threaded, with a
+//! fresh transposition table and no time limit. The total node count is the
+//! bench *signature*: it only changes when search or evaluation behavior
+//! changes, so a refactor that claims to be functionally neutral must leave it
+//! untouched. NPS is reported too but depends on the machine.
+
+use crate::board::Board;
+use crate::search::{Search, SearchParams};
+use std::time::{Duration, Instant};
+
+/// Default bench depth (`bench` without arguments)
+pub const BENCH_DEPTH: u8 = 8;
+
+/// Transposition table size used by the bench; fixed because the node count
+/// depends on it
+pub const BENCH_HASH_MB: usize = 16;
+
+/// Opening, middlegame, tactical and endgame positions searched by the bench
+pub const BENCH_POSITIONS: [&str; 8] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "2r3k1/pp3ppp/2n1b3/q2pP3/3P4/P1PB1N2/5PPP/R2Q2K1 b - - 0 18",
+    "r1b1k2r/ppppnppp/2n2q2/2b5/3NP3/2P1B3/PP3PPP/RN1QKB1R w KQkq - 0 7",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "8/8/4k3/3p4/3P4/4K3/8/8 w - - 0 1",
+];
+
+/// Nodes searched on one bench position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchPosition {
+    pub fen: &'static str,
+    pub nodes: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct BenchResult {
+    pub depth: u8,
+    pub positions: Vec<BenchPosition>,
+    pub elapsed: Duration,
+}
+
+impl BenchResult {
+    /// Total nodes: the bench signature
+    pub fn nodes(&self) -> u64 {
+        self.positions.iter().map(|p| p.nodes).sum()
+    }
+
+    pub fn nps(&self) -> u64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            (self.nodes() as f64 / secs) as u64
+        } else {
+            0
+        }
+    }
+}
+
+/// Search every bench position to `depth` and count the nodes
+pub fn run_bench(depth: u8) -> BenchResult {
+    let depth = depth.max(1);
+    let start = Instant::now();
+    let positions = BENCH_POSITIONS
+        .iter()
+        .map(|&fen| {
+            let mut board = Board::new();
+            board
+                .set_from_fen(fen)
+                .expect("bench positions are valid FENs");
+            let params = SearchParams::new().max_depth(depth).time_limit(0);
+            let mut search = Search::new(board, BENCH_HASH_MB, params);
+            search.search(Some(depth));
+            BenchPosition {
+                fen,
+                nodes: search.stats().nodes,
+            }
+        })
+        .collect();
+    BenchResult {
+        depth,
+        positions,
+        elapsed: start.elapsed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bench_signature_is_reproducible() {
+        crate::init();
+        let first = run_bench(3);
+        let second = run_bench(3);
+        assert_eq!(first.positions.len(), BENCH_POSITIONS.len());
+        assert!(first.positions.iter().all(|p| p.nodes > 0));
+        assert_eq!(first.positions, second.positions);
+        assert!(run_bench(4).nodes() > first.nodes());
+    }
+}
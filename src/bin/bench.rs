@@ -126,9 +126,9 @@ fn perft(board: &mut Board, depth: u8) -> u64 {
 
     let mut nodes = 0u64;
     for mv in moves {
-        let undo = board.make_move(mv);
+        board.push_move(mv);
         nodes += perft(board, depth - 1);
-        board.unmake_move(undo);
+        board.pop_move();
     }
     nodes
 }
@@ -0,0 +1,251 @@
+//! EPD test-suite runner (WAC, STS, Bratko-Kopec, ...)
+//!
+//! Loads an EPD file, searches every position that has a `bm` (best move) or
+//! `am` (avoid move) opcode for a fixed time and reports solved/total plus the
+//! average time-to-solution of the solved positions. Moves may be given in SAN
+//! (`Qg6`, `Rxb2+`, `O-O`) or UCI (`g3g6`).
+//!
+//! Usage: epdtest <file.epd> [--movetime ms] [--threads n] [--hash mb] [--limit n]
+
+use clap::Parser;
+use scacchista::board::{move_to_uci, parse_uci_move, Board, Move};
+use scacchista::search::thread_mgr::SearchJob;
+use scacchista::search::{IterationInfo, SearchObserver, SearchParams, ThreadManager};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[derive(Parser, Debug)]
+#[command(author, version, about = "Run an EPD test suite", long_about = None)]
+struct Args {
+    /// EPD file with `bm`/`am` opcodes
+    file: String,
+    /// Search time per position in milliseconds
+    #[arg(short, long, default_value_t = 1000)]
+    movetime: u64,
+    #[arg(short, long, default_value_t = 1)]
+    threads: usize,
+    /// Transposition table size in MB
+    #[arg(long, default_value_t = 64)]
+    hash: usize,
+    /// Only run the first N positions
+    #[arg(short, long)]
+    limit: Option<usize>,
+}
+
+/// One EPD record: position plus the opcodes the runner understands
+struct EpdPosition {
+    fen: String,
+    id: String,
+    best_moves: Vec<String>,
+    avoid_moves: Vec<String>,
+}
+
+/// Parse one EPD line. Accepts the 4-field EPD position as well as a full FEN
+/// (halfmove/fullmove counters) before the opcodes. Returns None for blank
+/// lines, comments and records without `bm`/`am`.
+fn parse_epd_line(line: &str) -> Option<EpdPosition> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    if tokens.len() < 4 {
+        return None;
+    }
+    let mut fen = tokens[..4].join(" ");
+    let mut rest = 4;
+    let is_counter = |i: usize| {
+        tokens
+            .get(i)
+            .is_some_and(|t| t.trim_end_matches(';').parse::<u32>().is_ok())
+    };
+    if is_counter(4) && is_counter(5) {
+        fen = format!("{} {} {}", fen, tokens[4], tokens[5].trim_end_matches(';'));
+        rest = 6;
+    } else {
+        fen.push_str(" 0 1");
+    }
+
+    let mut position = EpdPosition {
+        fen,
+        id: String::new(),
+        best_moves: Vec::new(),
+        avoid_moves: Vec::new(),
+    };
+    let opcodes = tokens[rest..].join(" ");
+    for operation in opcodes.split(';') {
+        let mut parts = operation.split_whitespace();
+        match parts.next() {
+            Some("bm") => position.best_moves.extend(parts.map(str::to_string)),
+            Some("am") => position.avoid_moves.extend(parts.map(str::to_string)),
+            Some("id") => {
+                position.id = parts
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    .trim_matches('"')
+                    .to_string()
+            }
+            _ => {}
+        }
+    }
+    if position.best_moves.is_empty() && position.avoid_moves.is_empty() {
+        return None;
+    }
+    Some(position)
+}
+
+/// Resolve a SAN or UCI move in `fen`; None if it is not legal there
+fn resolve_move(fen: &str, text: &str) -> Option<Move> {
+    let mut board = Board::new();
+    board.set_from_fen(fen).ok()?;
+    if let Ok(mv) = parse_uci_move(&mut board, text) {
+        return Some(mv);
+    }
+
+    use shakmaty::{fen::Fen, san::San, CastlingMode, Chess};
+    let pos: Chess = fen
+        .parse::<Fen>()
+        .ok()?
+        .into_position(CastlingMode::Standard)
+        .ok()?;
+    let san: San = text.trim_end_matches(['+', '#', '!', '?']).parse().ok()?;
+    let uci = san.to_move(&pos).ok()?.to_uci(CastlingMode::Standard);
+    parse_uci_move(&mut board, &uci.to_string()).ok()
+}
+
+/// Records the best move and elapsed time of every completed iteration
+#[derive(Default)]
+struct IterationLog(Mutex<Vec<(Move, Duration)>>);
+
+impl SearchObserver for IterationLog {
+    fn on_iteration(&self, info: &IterationInfo) {
+        if let Some(&mv) = info.pv.first() {
+            self.0.lock().unwrap().push((mv, info.elapsed));
+        }
+    }
+}
+
+/// Time at which the search settled on a solution for good: the first
+/// iteration after the last one whose move was not a solution (`total` if the
+/// move only changed in the final, interrupted iteration). None if the final
+/// move is not a solution.
+fn time_to_solution(
+    iterations: &[(Move, Duration)],
+    final_move: Move,
+    total: Duration,
+    solves: impl Fn(Move) -> bool,
+) -> Option<Duration> {
+    if !solves(final_move) {
+        return None;
+    }
+    let settled = iterations
+        .iter()
+        .rposition(|&(mv, _)| !solves(mv))
+        .map_or(0, |i| i + 1);
+    Some(
+        iterations
+            .get(settled)
+            .map_or(total, |&(_, elapsed)| elapsed),
+    )
+}
+
+fn main() {
+    scacchista::init();
+    let args = Args::parse();
+
+    let content = match std::fs::read_to_string(&args.file) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Cannot read {}: {}", args.file, e);
+            std::process::exit(1);
+        }
+    };
+    let mut positions: Vec<EpdPosition> = content.lines().filter_map(parse_epd_line).collect();
+    if let Some(limit) = args.limit {
+        positions.truncate(limit);
+    }
+
+    println!(
+        "EPD suite {}: {} positions, {} ms/position, {} thread(s)",
+        args.file,
+        positions.len(),
+        args.movetime,
+        args.threads
+    );
+    println!();
+
+    let mut solved = 0usize;
+    let mut total_time = Duration::ZERO;
+    for (i, pos) in positions.iter().enumerate() {
+        let best: Vec<Move> = pos
+            .best_moves
+            .iter()
+            .filter_map(|m| resolve_move(&pos.fen, m))
+            .collect();
+        let avoid: Vec<Move> = pos
+            .avoid_moves
+            .iter()
+            .filter_map(|m| resolve_move(&pos.fen, m))
+            .collect();
+        if best.len() != pos.best_moves.len() || avoid.len() != pos.avoid_moves.len() {
+            println!("{:>4} {:<24} SKIP  (unparsable bm/am)", i + 1, pos.id);
+            continue;
+        }
+        let mut board = Board::new();
+        if board.set_from_fen(&pos.fen).is_err() {
+            println!("{:>4} {:<24} SKIP  (bad FEN)", i + 1, pos.id);
+            continue;
+        }
+
+        // Fresh engine per position, as after `ucinewgame`
+        let tm = ThreadManager::new(args.threads, args.hash);
+        let log = Arc::new(IterationLog::default());
+        tm.set_observer(Some(log.clone()));
+        let start = Instant::now();
+        let result = tm.submit_job(SearchJob {
+            board,
+            params: SearchParams::new().max_depth(99).time_limit(args.movetime),
+        });
+        let total = start.elapsed();
+        tm.stop();
+
+        let solves = |mv: Move| (best.is_empty() || best.contains(&mv)) && !avoid.contains(&mv);
+        let iterations = log.0.lock().unwrap().clone();
+        let outcome = time_to_solution(&iterations, result.best_move, total, solves);
+        let expected = if pos.best_moves.is_empty() {
+            format!("am {}", pos.avoid_moves.join(" "))
+        } else {
+            format!("bm {}", pos.best_moves.join(" "))
+        };
+        match outcome {
+            Some(t) => {
+                solved += 1;
+                total_time += t;
+                println!(
+                    "{:>4} {:<24} OK    {:<6} ({}) in {} ms",
+                    i + 1,
+                    pos.id,
+                    move_to_uci(result.best_move),
+                    expected,
+                    t.as_millis()
+                );
+            }
+            None => println!(
+                "{:>4} {:<24} FAIL  {:<6} ({})",
+                i + 1,
+                pos.id,
+                move_to_uci(result.best_move),
+                expected
+            ),
+        }
+    }
+
+    println!();
+    println!("Solved: {}/{}", solved, positions.len());
+    if solved > 0 {
+        println!(
+            "Average time to solution: {} ms",
+            total_time.as_millis() / solved as u128
+        );
+    }
+}
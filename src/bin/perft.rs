@@ -1,13 +1,17 @@
-use clap::Parser;
-use scacchista::board::Move as MoveType;
-use scacchista::board::{move_from_sq, move_piece, move_to_sq, Board, Color, PieceKind, START_FEN}; // explicit alias for type in function signatures
-
-use shakmaty::fen::Fen;
-use shakmaty::{Chess, Position}; // used when parsing non-start FEN
-
-use std::vec::Vec; // ensure Vec<Move> available in signature
+//! Perft with shakmaty as the oracle
+//!
+//! By default the node count is checked against shakmaty; on a mismatch the
+//! tree is walked down to the first position where the two move generators
+//! disagree, printed as FEN plus the moves leading to it. `--divide`,
+//! `--breakdown`, `--threads` and `--hash` skip the check.
+//!
+//! `--random N` checks N positions reached by random games instead of
+//! `--fen` (on `--threads` threads, reproducible with `--seed`) and prints
+//! each failure shrunk to as few pieces as possible.
 
-// (move type is u32 alias in crate)
+use clap::Parser;
+use scacchista::board::{Board, START_FEN};
+use scacchista::perft::oracle;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -18,134 +22,22 @@ struct Args {
     depth: u8,
     #[arg(long, default_value_t = false)]
     divide: bool,
+    /// Print captures/e.p./castles/promotions/checks/mates for every depth up to `depth`
+    #[arg(long, default_value_t = false)]
+    breakdown: bool,
+    /// Split the root moves over this many threads (fast mode, no shakmaty check)
+    #[arg(short, long, default_value_t = 1)]
+    threads: usize,
+    /// Perft hash table size in MB, 0 = off (fast mode, no shakmaty check)
+    #[arg(long, default_value_t = 0)]
+    hash: usize,
+    /// Compare this many random positions with shakmaty instead of `--fen`
+    #[arg(long)]
+    random: Option<usize>,
+    /// Seed of the random positions
+    #[arg(long, default_value_t = 1)]
+    seed: u64,
 }
-fn perft_scacchista(board: &mut Board, depth: u8, path: &mut Vec<MoveType>) -> u64 {
-    if depth == 0 {
-        return 1;
-    }
-    let moves = board.generate_moves();
-    let mut nodes = 0u64;
-    for mv in moves {
-        path.push(mv);
-        let undo = board.make_move(mv);
-
-        // After make: inspect pseudo and legal moves from this position
-        let mut pseudo: Vec<scacchista::board::Move> = Vec::with_capacity(256);
-        board.generate_pseudo_moves(&mut pseudo);
-        let pseudo_len = pseudo.len();
-        let legal_len = board.generate_moves().len();
-
-        let child = perft_scacchista(board, depth - 1, path);
-        nodes += child;
-        if depth == 4 {
-            let from = move_from_sq(mv);
-            let to = move_to_sq(mv);
-            let piece = move_piece(mv);
-            if child == 0 {
-                eprintln!("==== ROOT MOVE {}→{} ({:?}) -> {} nodes (pseudo={}, legal={}) - DETAILED DUMP ====", from, to, piece, child, pseudo_len, legal_len);
-                // Print path
-                eprintln!("Path (root->current):");
-                for (i, pm) in path.iter().enumerate() {
-                    eprintln!(
-                        "  {}: {}->{} ({:?})",
-                        i + 1,
-                        move_from_sq(*pm),
-                        move_to_sq(*pm),
-                        move_piece(*pm)
-                    );
-                }
-                // Print board position after make
-                eprintln!("FEN-like board:\n{}", board);
-                eprintln!("Zobrist: 0x{:x}", board.recalc_zobrist());
-                eprintln!(
-                    "white_occ={:x} black_occ={:x} occ={:x}",
-                    board.white_occ, board.black_occ, board.occ
-                );
-
-                // Dump pseudo moves
-                eprintln!("Pseudo moves (count={}):", pseudo_len);
-                for (i, pmv) in pseudo.iter().enumerate() {
-                    let pf = move_from_sq(*pmv);
-                    let pt = move_to_sq(*pmv);
-                    let pp = move_piece(*pmv);
-                    eprintln!("  {}: {}->{} ({:?})", i + 1, pf, pt, pp);
-                }
-
-                // Dump legal moves from this position
-                let legal_moves = board.generate_moves();
-                eprintln!("Legal moves (count={}):", legal_moves.len());
-                for (i, lm) in legal_moves.iter().enumerate() {
-                    let lf = move_from_sq(*lm);
-                    let lt = move_to_sq(*lm);
-                    let lp = move_piece(*lm);
-                    eprintln!("  {}: {}->{} ({:?})", i + 1, lf, lt, lp);
-                }
-
-                // Additional invariants: recompute occupancy from piece bitboards
-                let mut recomputed_white = 0u64;
-                let mut recomputed_black = 0u64;
-                for kind in 0..6 {
-                    recomputed_white |= board.piece_bb_raw(kind);
-                    recomputed_black |= board.piece_bb_raw(kind + 6);
-                }
-                let recomputed_all = recomputed_white | recomputed_black;
-                eprintln!(
-                    "Recomputed occ white={:x} black={:x} all={:x}",
-                    recomputed_white, recomputed_black, recomputed_all
-                );
-                if recomputed_white != board.white_occ
-                    || recomputed_black != board.black_occ
-                    || recomputed_all != board.occ
-                {
-                    eprintln!("OCCUPANCY MISMATCH detected!");
-                }
-
-                // Check king squares and presence
-                let wking_sq = board.white_king_sq as usize;
-                let bking_sq = board.black_king_sq as usize;
-                eprintln!(
-                    "White king sq: {} piece_on: {:?}",
-                    wking_sq,
-                    board.piece_on(wking_sq)
-                );
-                eprintln!(
-                    "Black king sq: {} piece_on: {:?}",
-                    bking_sq,
-                    board.piece_on(bking_sq)
-                );
-
-                eprintln!("==== END DUMP ====");
-
-                // Stop early so we can inspect the output
-                std::process::exit(1);
-            } else {
-                eprintln!(
-                    "ROOT MOVE {}->{} ({:?}) -> {} nodes (pseudo={}, legal={})",
-                    from, to, piece, child, pseudo_len, legal_len
-                );
-            }
-        }
-        board.unmake_move(undo);
-        path.pop();
-    }
-    if depth == 4 {
-        eprintln!("TOTAL depth {}: {} nodes", depth, nodes);
-    }
-    nodes
-}
-fn perft_shakmaty(pos: &Chess, depth: u8) -> u64 {
-    if depth == 0 {
-        return 1;
-    }
-    let mut nodes = 0;
-    for m in pos.legal_moves() {
-        let mut new_pos = pos.clone();
-        new_pos.play_unchecked(&m);
-        nodes += perft_shakmaty(&new_pos, depth - 1);
-    }
-    nodes
-}
-
 fn perft_simple(board: &mut Board, depth: u8) -> u64 {
     if depth == 0 {
         return 1;
@@ -153,9 +45,9 @@ fn perft_simple(board: &mut Board, depth: u8) -> u64 {
     let moves = board.generate_moves();
     let mut nodes = 0u64;
     for mv in moves {
-        let undo = board.make_move(mv);
+        board.push_move(mv);
         nodes += perft_simple(board, depth - 1);
-        board.unmake_move(undo);
+        board.pop_move();
     }
     nodes
 }
@@ -171,13 +63,13 @@ fn perft_divide(board: &mut Board, depth: u8) {
 
     let mut total = 0u64;
     for mv in moves {
-        let undo = board.make_move(mv);
+        board.push_move(mv);
         let count = if depth > 1 {
             perft_simple(board, depth - 1)
         } else {
             1
         };
-        board.unmake_move(undo);
+        board.pop_move();
 
         println!("{:<10} : {}", move_to_uci(mv), count);
         total += count;
@@ -186,6 +78,77 @@ fn perft_divide(board: &mut Board, depth: u8) {
     println!("\nTotal nodes: {}", total);
 }
 
+fn perft_breakdown(board: &mut Board, depth: u8) {
+    println!(
+        "{:>5} | {:>12} | {:>10} | {:>8} | {:>8} | {:>10} | {:>10} | {:>10}",
+        "Depth", "Nodes", "Captures", "E.p.", "Castles", "Promotions", "Checks", "Mates"
+    );
+    for d in 1..=depth {
+        let c = board.perft_breakdown(d);
+        println!(
+            "{:>5} | {:>12} | {:>10} | {:>8} | {:>8} | {:>10} | {:>10} | {:>10}",
+            d, c.nodes, c.captures, c.en_passant, c.castles, c.promotions, c.checks, c.checkmates
+        );
+    }
+}
+
+/// Perft of random positions against shakmaty; exits with 1 on a failure
+fn perft_random(positions: usize, depth: u8, threads: usize, seed: u64) {
+    let config = oracle::RandomPerft {
+        positions,
+        depth,
+        threads,
+        seed,
+        ..oracle::RandomPerft::default()
+    };
+    println!(
+        "Checking {} random positions at depth {} (seed {}, {} thread(s))",
+        positions, depth, seed, threads
+    );
+    let start = std::time::Instant::now();
+    let failures = oracle::check_random_positions(&config);
+    for failure in &failures {
+        println!("\n{}", failure);
+    }
+    println!(
+        "{} of {} positions fail ({} ms)",
+        failures.len(),
+        positions,
+        start.elapsed().as_millis()
+    );
+    if !failures.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+/// Bulk-counting perft with optional hash table and root splitting
+fn perft_fast(board: &Board, depth: u8, threads: usize, hash_mb: usize, divide: bool) {
+    use scacchista::board::move_to_uci;
+    use scacchista::perft::{perft_parallel, PerftTable};
+
+    let table = (hash_mb > 0).then(|| PerftTable::new(hash_mb));
+    let start = std::time::Instant::now();
+    let counts = perft_parallel(board, depth, threads, table.as_ref());
+    let elapsed = start.elapsed();
+
+    if divide {
+        for (mv, nodes) in &counts {
+            println!("{:<10} : {}", move_to_uci(*mv), nodes);
+        }
+        println!();
+    }
+    let nodes: u64 = counts.iter().map(|(_, n)| n).sum();
+    println!(
+        "perft({}) = {} nodes ({} ms, {:.2} Mnps, {} thread(s), hash {} MB)",
+        depth,
+        nodes,
+        elapsed.as_millis(),
+        nodes as f64 / elapsed.as_micros().max(1) as f64,
+        threads,
+        hash_mb
+    );
+}
+
 fn main() {
     scacchista::init();
     // Force initialization of attack tables
@@ -194,6 +157,19 @@ fn main() {
 
     let args = Args::parse();
 
+    if let Some(positions) = args.random {
+        perft_random(positions, args.depth, args.threads, args.seed);
+        return;
+    }
+
+    if args.threads > 1 || args.hash > 0 {
+        let mut board = Board::new();
+        board.set_from_fen(&args.fen).unwrap();
+        println!("Running fast perft on FEN: {}", args.fen);
+        perft_fast(&board, args.depth, args.threads, args.hash, args.divide);
+        return;
+    }
+
     if args.divide {
         // Divide mode: just show per-move breakdown
         let mut board = Board::new();
@@ -204,61 +180,48 @@ fn main() {
         return;
     }
 
-    println!("Running perft on FEN: {} at depth {}", args.fen, args.depth);
+    if args.breakdown {
+        let mut board = Board::new();
+        board.set_from_fen(&args.fen).unwrap();
+        println!("Running perft breakdown on FEN: {}", args.fen);
+        perft_breakdown(&mut board, args.depth);
+        return;
+    }
 
-    // Shakmaty
-    let pos: Chess = if args.fen != START_FEN {
-        let fen: Fen = args.fen.parse().unwrap();
-        fen.into_position(shakmaty::CastlingMode::Standard).unwrap()
-    } else {
-        Chess::default()
-    };
-    let start = std::time::Instant::now();
-    let nodes_sh = perft_shakmaty(&pos, args.depth);
-    let dur_sh = start.elapsed();
+    println!("Running perft on FEN: {} at depth {}", args.fen, args.depth);
 
-    // Scacchista
     let mut board = Board::new();
     board.set_from_fen(&args.fen).unwrap();
     let start = std::time::Instant::now();
-    // Debug: dump piece bitboards at root
-    for kind in &[
-        PieceKind::Pawn,
-        PieceKind::Knight,
-        PieceKind::Bishop,
-        PieceKind::Rook,
-        PieceKind::Queen,
-        PieceKind::King,
-    ] {
-        let wbb = board.piece_bb(*kind, Color::White);
-        let bbb = board.piece_bb(*kind, Color::Black);
-        eprintln!("piece {:?} white bb: {:x} black bb: {:x}", kind, wbb, bbb);
-    }
-    let mut path: Vec<MoveType> = Vec::new();
-    let nodes_sc = perft_scacchista(&mut board, args.depth, &mut path);
-
-    let dur_sc = start.elapsed();
-
-    println!(
-        "Shakmaty perft({}) = {} nodes ({} ms, {:.2} Mnps)",
-        args.depth,
-        nodes_sh,
-        dur_sh.as_millis(),
-        nodes_sh as f64 / (dur_sh.as_micros() as f64)
-    );
+    let nodes = perft_simple(&mut board, args.depth);
+    let elapsed = start.elapsed();
     println!(
         "Scacchista perft({}) = {} nodes ({} ms, {:.2} Mnps)",
         args.depth,
-        nodes_sc,
-        dur_sc.as_millis(),
-        nodes_sc as f64 / (dur_sc.as_micros() as f64)
+        nodes,
+        elapsed.as_millis(),
+        nodes as f64 / elapsed.as_micros().max(1) as f64
     );
-    if nodes_sh == nodes_sc {
+
+    let (_, oracle_nodes, divergence) = match oracle::compare(&args.fen, args.depth) {
+        Ok(result) => result,
+        Err(e) => {
+            eprintln!("Oracle cannot check this FEN: {}", e);
+            std::process::exit(2);
+        }
+    };
+    println!("Shakmaty perft({}) = {} nodes", args.depth, oracle_nodes);
+    if nodes == oracle_nodes {
         println!("✅ Counts match!");
-    } else {
-        println!(
-            "Mismatch difference = {}",
-            (nodes_sc as i64) - (nodes_sh as i64)
-        );
+        return;
+    }
+    println!(
+        "Mismatch difference = {}",
+        (nodes as i64) - (oracle_nodes as i64)
+    );
+    match divergence {
+        Some(divergence) => println!("First divergence {}", divergence),
+        None => println!("No diverging position found"),
     }
+    std::process::exit(1);
 }
@@ -1,25 +1,76 @@
-use std::io::{BufRead, BufReader, Write};
+//! Engine-vs-engine runner with real clocks (also used for self-play)
+//!
+//! Usage: `tournament [engine1] [engine2] [rounds] [base_ms] [inc_ms] [options]`
+//! (pass the same binary twice for self-play). Each side gets a simulated clock
+//! of `base_ms` plus `inc_ms` per move; the wall time between `go` and
+//! `bestmove` is charged to the mover, and running out of time loses the game.
+//! Every move's time usage is written to `tournament_times.csv` so
+//! time-manager changes can be compared under realistic conditions.
+//!
+//! Options:
+//! - `--openings FILE`: EPD or PGN opening suite; each opening is played by a
+//!   pair of games with colors swapped (default: the starting position)
+//! - `--resign SCORE MOVES`: a side reporting at most -SCORE cp for MOVES
+//!   consecutive moves loses
+//! - `--draw SCORE MOVES MINPLY`: after MINPLY plies, both sides reporting
+//!   within SCORE cp of zero for MOVES consecutive moves each is a draw
+
+use scacchista::board::Color;
+use scacchista::selfplay::{
+    load_openings, schedule, AdjudicationRules, Adjudicator, Opening, Verdict,
+};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
 use std::process::{Command, Stdio};
 use std::time::Instant;
 
+const TIMES_CSV: &str = "tournament_times.csv";
+
 fn main() {
-    let engine1_path = "./scacchista_v0.5";
-    let engine2_path = "./scacchista_v0.4";
-    let rounds = 10;
-    let time_ms = 3000; // 3 seconds (quick test)
-    let inc_ms = 50; // 0.05 seconds
+    scacchista::init();
+    let (args, openings, rules) = match parse_options(std::env::args().collect()) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let arg = |i: usize, default: &str| args.get(i).cloned().unwrap_or(default.to_string());
+    let engine1_path = arg(1, "./scacchista_v0.5");
+    let engine2_path = arg(2, "./scacchista_v0.4");
+    let rounds: u32 = arg(3, "10").parse().unwrap_or(10);
+    let time_ms: u64 = arg(4, "3000").parse().unwrap_or(3000); // 3 seconds (quick test)
+    let inc_ms: u64 = arg(5, "50").parse().unwrap_or(50); // 0.05 seconds
+    let (name1, name2) = ("engine1", "engine2");
 
     println!("Starting tournament: {} vs {}", engine1_path, engine2_path);
     println!("Rounds: {}, TC: {}ms + {}ms", rounds, time_ms, inc_ms);
+    println!("Openings: {}", openings.len());
+
+    let mut times_csv = match File::create(TIMES_CSV) {
+        Ok(f) => Some(BufWriter::new(f)),
+        Err(e) => {
+            println!("Cannot write {}: {}", TIMES_CSV, e);
+            None
+        }
+    };
+    if let Some(csv) = times_csv.as_mut() {
+        let _ = writeln!(csv, "game,ply,side,engine,move,elapsed_ms,remaining_ms");
+    }
 
     let mut score1 = 0.0;
     let mut score2 = 0.0;
+    let mut usage1 = TimeUsage::default();
+    let mut usage2 = TimeUsage::default();
 
     for i in 1..=rounds {
-        let (white_path, black_path, white_name, black_name) = if i % 2 != 0 {
-            (engine1_path, engine2_path, "v0.5", "v0.4")
+        let (opening, engine1_white) = schedule(i as usize - 1, openings.len());
+        let opening = &openings[opening];
+        let (white_path, black_path, white_name, black_name) = if engine1_white {
+            (&engine1_path, &engine2_path, name1, name2)
         } else {
-            (engine2_path, engine1_path, "v0.4", "v0.5")
+            (&engine2_path, &engine1_path, name2, name1)
         };
 
         print!(
@@ -28,10 +79,11 @@ fn main() {
         );
         std::io::stdout().flush().unwrap();
 
-        match play_game(white_path, black_path, time_ms, inc_ms) {
+        let record = play_game(white_path, black_path, opening, &rules, time_ms, inc_ms);
+        match record.result {
             GameResult::WhiteWin => {
                 println!("1-0 (White wins)");
-                if white_name == "v0.5" {
+                if white_name == name1 {
                     score1 += 1.0;
                 } else {
                     score2 += 1.0;
@@ -39,7 +91,7 @@ fn main() {
             }
             GameResult::BlackWin => {
                 println!("0-1 (Black wins)");
-                if black_name == "v0.5" {
+                if black_name == name1 {
                     score1 += 1.0;
                 } else {
                     score2 += 1.0;
@@ -50,22 +102,103 @@ fn main() {
                 score1 += 0.5;
                 score2 += 0.5;
             }
-            GameResult::Error(e) => {
+            GameResult::Error(ref e) => {
                 println!("Error: {}", e);
             }
         }
+
+        // Per-game time report and CSV rows
+        for (side, clock) in record.clocks.iter().enumerate() {
+            let (side_name, engine) = if side == 0 {
+                ("w", white_name)
+            } else {
+                ("b", black_name)
+            };
+            println!("  {}: {}", engine, clock.summary());
+            if engine == name1 {
+                usage1.add(clock);
+            } else {
+                usage2.add(clock);
+            }
+            if let Some(csv) = times_csv.as_mut() {
+                // Plies of the game, counted from the first move after the opening
+                let first = if opening.side_to_move == Color::White {
+                    side
+                } else {
+                    1 - side
+                };
+                for (n, t) in clock.moves.iter().enumerate() {
+                    let ply = 2 * n + first + 1;
+                    let mv = record.moves.get(ply - 1).map(String::as_str).unwrap_or("");
+                    let _ = writeln!(
+                        csv,
+                        "{},{},{},{},{},{},{}",
+                        i, ply, side_name, engine, mv, t.elapsed_ms, t.remaining_ms
+                    );
+                }
+            }
+        }
     }
 
     println!("\n--- Final Results ---");
-    println!("v0.5 Score: {}", score1);
-    println!("v0.4 Score: {}", score2);
+    println!("{} ({}) Score: {}", name1, engine1_path, score1);
+    println!("{} ({}) Score: {}", name2, engine2_path, score2);
 
     let total = score1 + score2;
     if total > 0.0 {
         let fraction = score1 / total;
         let elo_diff = -400.0 * (1.0f64 / fraction - 1.0f64).log10();
-        println!("Elo Difference (v0.5 - v0.4): {:+.1}", elo_diff);
+        println!("Elo Difference ({} - {}): {:+.1}", name1, name2, elo_diff);
+    }
+
+    println!("\n--- Time Usage ---");
+    println!("{}: {}", name1, usage1.summary());
+    println!("{}: {}", name2, usage2.summary());
+    if times_csv.is_some() {
+        println!("Per-move times written to {}", TIMES_CSV);
+    }
+}
+
+/// Split the command line into positional arguments, opening suite and
+/// adjudication rules
+fn parse_options(
+    args: Vec<String>,
+) -> Result<(Vec<String>, Vec<Opening>, AdjudicationRules), String> {
+    let mut positional = Vec::new();
+    let mut openings = None;
+    let mut rules = AdjudicationRules::default();
+    let mut iter = args.into_iter();
+    let value = |iter: &mut std::vec::IntoIter<String>, option: &str| {
+        iter.next()
+            .ok_or_else(|| format!("{}: missing value", option))
+    };
+    let number = |text: String, option: &str| {
+        text.parse::<u32>()
+            .map_err(|_| format!("{}: invalid number '{}'", option, text))
+    };
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--openings" => {
+                let path = value(&mut iter, &arg)?;
+                openings = Some(load_openings(Path::new(&path))?);
+            }
+            "--resign" => {
+                rules.resign_score = number(value(&mut iter, &arg)?, &arg)? as i32;
+                rules.resign_moves = number(value(&mut iter, &arg)?, &arg)?;
+            }
+            "--draw" => {
+                rules.draw_score = number(value(&mut iter, &arg)?, &arg)? as i32;
+                rules.draw_moves = number(value(&mut iter, &arg)?, &arg)?;
+                rules.draw_min_ply = number(value(&mut iter, &arg)?, &arg)?;
+            }
+            _ => positional.push(arg),
+        }
     }
+    let openings = match openings {
+        Some(openings) => openings,
+        None => vec![Opening::new(None, &[])?],
+    };
+    Ok((positional, openings, rules))
 }
 
 enum GameResult {
@@ -75,14 +208,130 @@ enum GameResult {
     Error(String),
 }
 
-fn play_game(white_path: &str, black_path: &str, time_ms: u64, inc_ms: u64) -> GameResult {
+/// Time spent on one move and the clock left afterwards (increment included)
+struct MoveTime {
+    elapsed_ms: u64,
+    remaining_ms: u64,
+}
+
+/// Simulated chess clock for one side: base time plus a fixed increment
+struct Clock {
+    remaining_ms: u64,
+    inc_ms: u64,
+    flagged: bool,
+    moves: Vec<MoveTime>,
+}
+
+impl Clock {
+    fn new(base_ms: u64, inc_ms: u64) -> Self {
+        Self {
+            remaining_ms: base_ms,
+            inc_ms,
+            flagged: false,
+            moves: Vec::new(),
+        }
+    }
+
+    /// Charge `elapsed_ms` for a move and add the increment.
+    /// Returns false (and marks the clock as flagged) if time ran out.
+    fn punch(&mut self, elapsed_ms: u64) -> bool {
+        if elapsed_ms > self.remaining_ms {
+            self.remaining_ms = 0;
+            self.flagged = true;
+        } else {
+            self.remaining_ms = self.remaining_ms - elapsed_ms + self.inc_ms;
+        }
+        self.moves.push(MoveTime {
+            elapsed_ms,
+            remaining_ms: self.remaining_ms,
+        });
+        !self.flagged
+    }
+
+    fn summary(&self) -> String {
+        let total: u64 = self.moves.iter().map(|m| m.elapsed_ms).sum();
+        let max = self.moves.iter().map(|m| m.elapsed_ms).max().unwrap_or(0);
+        format!(
+            "{} moves, avg {}ms, max {}ms, left {}ms{}",
+            self.moves.len(),
+            total.checked_div(self.moves.len() as u64).unwrap_or(0),
+            max,
+            self.remaining_ms,
+            if self.flagged { " (flagged)" } else { "" }
+        )
+    }
+}
+
+/// Time usage of one engine accumulated over the whole match
+#[derive(Default)]
+struct TimeUsage {
+    moves: u64,
+    total_ms: u64,
+    max_ms: u64,
+    min_left_ms: Option<u64>,
+    flags: u32,
+}
+
+impl TimeUsage {
+    fn add(&mut self, clock: &Clock) {
+        for m in &clock.moves {
+            self.moves += 1;
+            self.total_ms += m.elapsed_ms;
+            self.max_ms = self.max_ms.max(m.elapsed_ms);
+        }
+        self.min_left_ms = Some(
+            self.min_left_ms
+                .map_or(clock.remaining_ms, |left| left.min(clock.remaining_ms)),
+        );
+        if clock.flagged {
+            self.flags += 1;
+        }
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "{} moves, avg {}ms, max {}ms, lowest clock {}ms, {} time losses",
+            self.moves,
+            self.total_ms.checked_div(self.moves).unwrap_or(0),
+            self.max_ms,
+            self.min_left_ms.unwrap_or(0),
+            self.flags
+        )
+    }
+}
+
+/// Outcome of a game with both clocks and the moves played after the opening
+struct GameRecord {
+    result: GameResult,
+    /// [white, black]
+    clocks: [Clock; 2],
+    moves: Vec<String>,
+}
+
+fn play_game(
+    white_path: &str,
+    black_path: &str,
+    opening: &Opening,
+    rules: &AdjudicationRules,
+    time_ms: u64,
+    inc_ms: u64,
+) -> GameRecord {
+    let mut record = GameRecord {
+        result: GameResult::Draw,
+        clocks: [Clock::new(time_ms, inc_ms), Clock::new(time_ms, inc_ms)],
+        moves: Vec::new(),
+    };
+
     let mut white_proc = match Command::new(white_path)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .spawn()
     {
         Ok(p) => p,
-        Err(e) => return GameResult::Error(format!("Failed to start white: {}", e)),
+        Err(e) => {
+            record.result = GameResult::Error(format!("Failed to start white: {}", e));
+            return record;
+        }
     };
 
     let mut black_proc = match Command::new(black_path)
@@ -91,7 +340,11 @@ fn play_game(white_path: &str, black_path: &str, time_ms: u64, inc_ms: u64) -> G
         .spawn()
     {
         Ok(p) => p,
-        Err(e) => return GameResult::Error(format!("Failed to start black: {}", e)),
+        Err(e) => {
+            let _ = white_proc.kill();
+            record.result = GameResult::Error(format!("Failed to start black: {}", e));
+            return record;
+        }
     };
 
     let mut w_in = white_proc.stdin.take().unwrap();
@@ -115,89 +368,73 @@ fn play_game(white_path: &str, black_path: &str, time_ms: u64, inc_ms: u64) -> G
     writeln!(w_in, "ucinewgame").unwrap();
     writeln!(b_in, "ucinewgame").unwrap();
 
-    let mut moves = Vec::new();
-    let mut turn = 0; // 0=White, 1=Black
-    let mut wtime = time_ms;
-    let mut btime = time_ms;
+    let mut adjudicator = Adjudicator::new(*rules);
 
     // Game loop (limited to 200 moves to prevent infinite games)
-    for _ in 0..200 {
-        let position_cmd = if moves.is_empty() {
-            "position startpos".to_string()
-        } else {
-            format!("position startpos moves {}", moves.join(" "))
-        };
+    for ply in 0..200 {
+        let turn = (ply + opening.side_to_move as usize) % 2; // 0=White, 1=Black
+        let position_cmd = opening.position_command(&record.moves);
 
         let go_cmd = format!(
             "go wtime {} btime {} winc {} binc {}",
-            wtime, btime, inc_ms, inc_ms
+            record.clocks[0].remaining_ms, record.clocks[1].remaining_ms, inc_ms, inc_ms
         );
 
+        let (input, output) = if turn == 0 {
+            (&mut w_in, &mut w_out as &mut dyn BufRead)
+        } else {
+            (&mut b_in, &mut b_out as &mut dyn BufRead)
+        };
+
         let start = Instant::now();
-        if turn == 0 {
-            // White
-            writeln!(w_in, "{}", position_cmd).unwrap();
-            writeln!(w_in, "{}", go_cmd).unwrap();
-            w_in.flush().unwrap();
-
-            let (mv, score) = read_bestmove(&mut w_out);
-            print!(" W:{} ({})", mv, score);
-            std::io::stdout().flush().unwrap();
-
-            let real_elapsed = start.elapsed().as_millis() as u64;
-            // Adjust time tracking roughly
-            if wtime > real_elapsed {
-                wtime -= real_elapsed;
-            } else {
-                wtime = 0;
-            }
-            wtime += inc_ms;
+        writeln!(input, "{}", position_cmd).unwrap();
+        writeln!(input, "{}", go_cmd).unwrap();
+        input.flush().unwrap();
 
-            if mv == "0000" || mv == "(none)" {
-                return GameResult::BlackWin;
-            }
-            // Check for mate announcement
-            if score.abs() >= 29000 {
-                println!(" [Mate detected]");
-                if score > 0 {
-                    return GameResult::WhiteWin;
-                } else {
-                    return GameResult::BlackWin;
-                }
-            }
-            moves.push(mv.clone());
-            turn = 1;
+        let (mv, score) = read_bestmove(output);
+        let elapsed = start.elapsed().as_millis() as u64;
+        let side = if turn == 0 { "W" } else { "B" };
+        print!(" {}:{} ({}) [{}ms]", side, mv, score, elapsed);
+        std::io::stdout().flush().unwrap();
+
+        // The mover loses on time, whatever move it found
+        let (win, loss) = if turn == 0 {
+            (GameResult::WhiteWin, GameResult::BlackWin)
         } else {
-            // Black
-            writeln!(b_in, "{}", position_cmd).unwrap();
-            writeln!(b_in, "{}", go_cmd).unwrap();
-            b_in.flush().unwrap();
-
-            let (mv, score) = read_bestmove(&mut b_out);
-            print!(" B:{} ({})", mv, score);
-            std::io::stdout().flush().unwrap();
-
-            let real_elapsed = start.elapsed().as_millis() as u64;
-            if btime > real_elapsed {
-                btime -= real_elapsed;
-            } else {
-                btime = 0;
-            }
-            btime += inc_ms;
+            (GameResult::BlackWin, GameResult::WhiteWin)
+        };
+        if !record.clocks[turn].punch(elapsed) {
+            println!(" [{} flagged]", side);
+            record.result = loss;
+            break;
+        }
 
-            if mv == "0000" || mv == "(none)" {
-                return GameResult::WhiteWin;
-            }
-            if score.abs() >= 29000 {
-                println!(" [Mate detected]");
-                if score > 0 {
-                    return GameResult::BlackWin;
-                } else {
-                    return GameResult::WhiteWin;
-                }
-            }
-            moves.push(mv.clone());
-            turn = 0;
+        if mv == "0000" || mv == "(none)" {
+            record.result = loss;
+            break;
+        }
+        // Check for mate announcement
+        if score.abs() >= 29000 {
+            println!(" [Mate detected]");
+            record.result = if score > 0 { win } else { loss };
+            break;
+        }
+        record.moves.push(mv);
+
+        let mover = if turn == 0 {
+            Color::White
+        } else {
+            Color::Black
+        };
+        let game_ply = (opening.moves.len() + ply + 1) as u32;
+        if let Some(verdict) = adjudicator.record(game_ply, mover, score) {
+            println!(" [Adjudicated]");
+            record.result = match verdict {
+                Verdict::WhiteWins => GameResult::WhiteWin,
+                Verdict::BlackWins => GameResult::BlackWin,
+                Verdict::Draw => GameResult::Draw,
+            };
+            break;
         }
     }
 
@@ -205,7 +442,7 @@ fn play_game(white_path: &str, black_path: &str, time_ms: u64, inc_ms: u64) -> G
     let _ = white_proc.kill();
     let _ = black_proc.kill();
 
-    GameResult::Draw // Limit reached
+    record // Draw if the move limit was reached
 }
 
 fn wait_for_uciok<R: BufRead>(reader: &mut R) {
@@ -228,7 +465,7 @@ fn wait_for_readyok<R: BufRead>(reader: &mut R) {
     }
 }
 
-fn read_bestmove<R: BufRead>(reader: &mut R) -> (String, i32) {
+fn read_bestmove<R: BufRead + ?Sized>(reader: &mut R) -> (String, i32) {
     let mut line = String::new();
     let mut score_cp = 0;
     while reader.read_line(&mut line).unwrap() > 0 {
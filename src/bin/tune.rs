@@ -0,0 +1,211 @@
+//! Texel tuning of the evaluation constants
+//!
+//! Reads positions labeled with the result of the game they come from, fits
+//! the scaling constant K of the logistic model
+//! `expected = 1 / (1 + 10^(-K * eval / 400))` and then runs Texel's local
+//! search over `EvalParams`: every parameter is moved one step up or down and
+//! the change is kept while the mean squared error against the results drops.
+//!
+//! Each input line is a FEN (4 or 6 fields) followed by the result, as
+//! `1-0`/`0-1`/`1/2-1/2` (optionally quoted, e.g. `c9 "1-0";`) or as a score
+//! for White in brackets (`[1.0]`, `[0.5]`, `[0.0]`).
+//!
+//! The tuned set is printed as a Rust `EvalParams` literal; with `--output`
+//! it is also written as `setoption` lines that `scacchista tune` accepts.
+//!
+//! Usage: tune <positions> [--iterations n] [--step n] [--threads n] [--limit n] [--output file]
+
+use clap::Parser;
+use scacchista::board::{Board, Color};
+use scacchista::eval::{evaluate_with, EvalParams, EvalWeights, EVAL_PARAM_SPINS};
+use std::io::Write;
+
+#[derive(Parser, Debug)]
+#[command(author, version, about = "Texel tuning of the evaluation", long_about = None)]
+struct Args {
+    /// File of FEN + game result lines
+    file: String,
+    /// Maximum number of local search passes over all parameters
+    #[arg(short, long, default_value_t = 50)]
+    iterations: usize,
+    /// Step tried for each parameter
+    #[arg(short, long, default_value_t = 1)]
+    step: i32,
+    #[arg(short, long, default_value_t = 1)]
+    threads: usize,
+    /// Only use the first N positions
+    #[arg(short, long)]
+    limit: Option<usize>,
+    /// Write the tuned parameters as `setoption` lines to this file
+    #[arg(short, long)]
+    output: Option<String>,
+}
+
+/// Parse one labeled position: the board and the result for White (1, 0.5,
+/// 0). None for blank lines, comments and unparsable records.
+fn parse_position(line: &str) -> Option<(Board, f64)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    if tokens.len() < 5 {
+        return None;
+    }
+    let is_counter = |i: usize| tokens.get(i).is_some_and(|t| t.parse::<u32>().is_ok());
+    let (fen, rest) = if is_counter(4) && is_counter(5) {
+        (tokens[..6].join(" "), &tokens[6..])
+    } else {
+        (format!("{} 0 1", tokens[..4].join(" ")), &tokens[4..])
+    };
+
+    let result = rest.iter().find_map(|t| {
+        match t.trim_matches(|c| matches!(c, '"' | ';' | '[' | ']' | ',')) {
+            "1-0" | "1.0" | "1" => Some(1.0),
+            "0-1" | "0.0" | "0" => Some(0.0),
+            "1/2-1/2" | "0.5" => Some(0.5),
+            _ => None,
+        }
+    })?;
+
+    let mut board = Board::new();
+    board.set_from_fen(&fen).ok()?;
+    Some((board, result))
+}
+
+/// Static evaluation for White
+fn white_eval(board: &Board, params: &EvalParams) -> f64 {
+    let score = evaluate_with(board, params, &EvalWeights::NORMAL) as f64;
+    if board.side == Color::Black {
+        -score
+    } else {
+        score
+    }
+}
+
+/// Expected result for White of a position evaluated `eval` centipawns
+fn sigmoid(eval: f64, k: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * eval / 400.0))
+}
+
+/// Mean squared error of the model over all positions, split across threads
+fn mean_error(positions: &[(Board, f64)], params: &EvalParams, k: f64, threads: usize) -> f64 {
+    if positions.is_empty() {
+        return 0.0;
+    }
+    let chunk = positions.len().div_ceil(threads.max(1));
+    let total: f64 = std::thread::scope(|scope| {
+        let handles: Vec<_> = positions
+            .chunks(chunk)
+            .map(|part| {
+                scope.spawn(move || {
+                    part.iter()
+                        .map(|(board, result)| {
+                            let error = result - sigmoid(white_eval(board, params), k);
+                            error * error
+                        })
+                        .sum::<f64>()
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).sum()
+    });
+    total / positions.len() as f64
+}
+
+/// K minimizing the error of the default parameters: coarse-to-fine scan
+fn fit_k(positions: &[(Board, f64)], params: &EvalParams, threads: usize) -> f64 {
+    let (mut best_k, mut step) = (1.0, 1.0);
+    let mut best_error = mean_error(positions, params, best_k, threads);
+    for _ in 0..6 {
+        let center = best_k;
+        for i in -10..=10 {
+            let k = center + i as f64 * step / 10.0;
+            if k <= 0.0 {
+                continue;
+            }
+            let error = mean_error(positions, params, k, threads);
+            if error < best_error {
+                best_error = error;
+                best_k = k;
+            }
+        }
+        step /= 10.0;
+    }
+    best_k
+}
+
+fn main() {
+    scacchista::init();
+    let args = Args::parse();
+
+    let content = match std::fs::read_to_string(&args.file) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Cannot read {}: {}", args.file, e);
+            std::process::exit(1);
+        }
+    };
+    let mut positions: Vec<(Board, f64)> = content.lines().filter_map(parse_position).collect();
+    if let Some(limit) = args.limit {
+        positions.truncate(limit);
+    }
+    if positions.is_empty() {
+        eprintln!("No labeled positions in {}", args.file);
+        std::process::exit(1);
+    }
+
+    let mut params = EvalParams::DEFAULT;
+    let k = fit_k(&positions, &params, args.threads);
+    let mut best_error = mean_error(&positions, &params, k, args.threads);
+    println!(
+        "Texel tuning on {} positions, K = {:.3}, initial error {:.6}",
+        positions.len(),
+        k,
+        best_error
+    );
+
+    for iteration in 1..=args.iterations {
+        let mut improved = false;
+        for spin in &EVAL_PARAM_SPINS {
+            let current = params.get(spin.name).expect("spin names are parameters");
+            for delta in [args.step, -args.step] {
+                let mut candidate = params;
+                candidate.set(spin.name, current + delta);
+                if candidate == params {
+                    continue; // Clamped at the edge of the range
+                }
+                let error = mean_error(&positions, &candidate, k, args.threads);
+                if error < best_error {
+                    best_error = error;
+                    params = candidate;
+                    improved = true;
+                    break;
+                }
+            }
+        }
+        println!("Iteration {}: error {:.6}", iteration, best_error);
+        if !improved {
+            break;
+        }
+    }
+
+    println!();
+    println!("pub const DEFAULT: EvalParams = {:#?};", params);
+
+    if let Some(path) = args.output {
+        let lines: String = EVAL_PARAM_SPINS
+            .iter()
+            .map(|spin| {
+                let value = params.get(spin.name).expect("spin names are parameters");
+                format!("setoption name {} value {}\n", spin.name, value)
+            })
+            .collect();
+        if let Err(e) = std::fs::File::create(&path).and_then(|mut f| f.write_all(lines.as_bytes()))
+        {
+            eprintln!("Cannot write {}: {}", path, e);
+            std::process::exit(1);
+        }
+        println!("Parameters written to {}", path);
+    }
+}
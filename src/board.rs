@@ -23,14 +23,12 @@ fn piece_index(kind: PieceKind, color: Color) -> usize {
     (color as usize) * 6 + (kind as usize)
 }
 
-pub type Move = u32;
-
 // Codifica mosse: 32-bit layout
 // Bits 0-5: from (0-63)
 // Bits 6-11: to (0-63)
 // Bits 12-15: piece (0-5)
-// Bits 16-19: captured (0-5, 0xFF = none)
-// Bits 20-23: promotion (0-5, 0xFF = none)
+// Bits 16-19: captured (0-5, 0xF = none)
+// Bits 20-23: promotion (0-5, 0xF = none)
 // Bits 24-31: flags
 pub const FLAG_NONE: u32 = 0;
 pub const FLAG_EN_PASSANT: u32 = 1 << 24;
@@ -39,61 +37,97 @@ pub const FLAG_CASTLE_QUEEN: u32 = 1 << 26;
 pub const FLAG_PROMOTION: u32 = 1 << 27;
 pub const FLAG_CAPTURE: u32 = 1 << 28;
 
-pub fn move_from_sq(m: Move) -> usize {
-    (m & 0x3F) as usize
-}
-pub fn move_to_sq(m: Move) -> usize {
-    ((m >> 6) & 0x3F) as usize
-}
-pub fn move_piece(m: Move) -> PieceKind {
-    match (m >> 12) & 0xF {
-        0 => PieceKind::Pawn,
-        1 => PieceKind::Knight,
-        2 => PieceKind::Bishop,
-        3 => PieceKind::Rook,
-        4 => PieceKind::Queen,
-        5 => PieceKind::King,
-        _ => panic!(),
+/// Mossa codificata nel layout a 32 bit sopra; [`Move::NONE`] (tutti zeri)
+/// indica l'assenza di mossa (mossa nulla, nessuna mossa migliore, ...)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct Move(u32);
+
+impl Move {
+    /// Nessuna mossa
+    pub const NONE: Move = Move(0);
+
+    /// Mossa dalla sua codifica a 32 bit (es. letta dalla TT o da un file)
+    pub const fn from_raw(raw: u32) -> Self {
+        Move(raw)
     }
-}
-pub fn move_captured(m: Move) -> Option<PieceKind> {
-    let v = (m >> 16) & 0xF;
-    if v == 0xF {
-        None
-    } else {
-        Some(match v {
-            0 => PieceKind::Pawn,
-            1 => PieceKind::Knight,
-            2 => PieceKind::Bishop,
-            3 => PieceKind::Rook,
-            4 => PieceKind::Queen,
-            5 => PieceKind::King,
-            _ => panic!(),
-        })
+
+    /// Codifica a 32 bit della mossa
+    pub const fn raw(self) -> u32 {
+        self.0
+    }
+
+    pub fn is_none(self) -> bool {
+        self == Move::NONE
+    }
+
+    /// Casella di partenza (0-63)
+    pub fn from(self) -> usize {
+        (self.0 & 0x3F) as usize
+    }
+
+    /// Casella di arrivo (0-63); per l'arrocco è la casella del Re
+    pub fn to(self) -> usize {
+        ((self.0 >> 6) & 0x3F) as usize
+    }
+
+    /// Pezzo che muove
+    pub fn piece(self) -> PieceKind {
+        piece_kind_from_bits((self.0 >> 12) & 0xF).expect("move without a moving piece")
+    }
+
+    /// Pezzo catturato (il pedone anche per la presa en passant)
+    pub fn captured(self) -> Option<PieceKind> {
+        piece_kind_from_bits((self.0 >> 16) & 0xF)
+    }
+
+    pub fn is_capture(self) -> bool {
+        self.captured().is_some()
+    }
+
+    /// Pezzo in cui promuove il pedone
+    pub fn promotion(self) -> Option<PieceKind> {
+        piece_kind_from_bits((self.0 >> 20) & 0xF)
+    }
+
+    pub fn is_promotion(self) -> bool {
+        self.has_flag(FLAG_PROMOTION)
+    }
+
+    /// Bit dei flag (`FLAG_*`) della mossa
+    pub fn flags(self) -> u32 {
+        self.0 & 0xFF00_0000
+    }
+
+    /// Vero se almeno uno dei flag in `flag` è impostato
+    pub fn has_flag(self, flag: u32) -> bool {
+        (self.0 & flag) != 0
     }
 }
-pub fn move_promotion(m: Move) -> Option<PieceKind> {
-    let v = (m >> 20) & 0xF;
-    if v == 0xF {
-        None
-    } else {
-        Some(match v {
-            0 => PieceKind::Pawn,
-            1 => PieceKind::Knight,
-            2 => PieceKind::Bishop,
-            3 => PieceKind::Rook,
-            4 => PieceKind::Queen,
-            5 => PieceKind::King,
-            _ => panic!(),
-        })
+
+/// Notazione UCI della mossa (es. "e2e4", "0000" per [`Move::NONE`])
+impl std::fmt::Display for Move {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&move_to_uci(*self))
     }
 }
-pub fn move_flag(m: Move, flag: u32) -> bool {
-    (m & flag) != 0
+
+/// Pezzo da un campo a 4 bit della codifica (0xF = nessuno)
+fn piece_kind_from_bits(v: u32) -> Option<PieceKind> {
+    match v {
+        0 => Some(PieceKind::Pawn),
+        1 => Some(PieceKind::Knight),
+        2 => Some(PieceKind::Bishop),
+        3 => Some(PieceKind::Rook),
+        4 => Some(PieceKind::Queen),
+        5 => Some(PieceKind::King),
+        0xF => None,
+        _ => panic!("invalid piece bits in move encoding"),
+    }
 }
 
 /// Convert a square index (0-63) to UCI notation (e.g., 0 -> "a1", 63 -> "h8")
-fn square_to_uci(sq: usize) -> String {
+pub fn square_to_uci(sq: usize) -> String {
     let file = (sq % 8) as u8;
     let rank = (sq / 8) as u8;
     let file_char = (b'a' + file) as char;
@@ -103,16 +137,39 @@ fn square_to_uci(sq: usize) -> String {
 
 /// Convert a Move to UCI notation (e.g., "e2e4" or "e7e8q")
 pub fn move_to_uci(m: Move) -> String {
-    if m == 0 {
+    move_to_uci_with(m, false)
+}
+
+/// UCI destination square of a castling move: the king's target square (e1g1)
+/// in standard chess, the castling rook's square (e1h1, king takes rook) in Chess960
+pub fn castling_uci_to_sq(m: Move, chess960: bool) -> usize {
+    let to = m.to();
+    if !chess960 {
+        return to;
+    }
+    let rank_start = to - to % 8;
+    if m.has_flag(FLAG_CASTLE_KING) {
+        rank_start + 7
+    } else if m.has_flag(FLAG_CASTLE_QUEEN) {
+        rank_start
+    } else {
+        to
+    }
+}
+
+/// Convert a Move to UCI notation, writing castling as king-takes-rook when
+/// `chess960` is set (what GUIs expect with `UCI_Chess960` on)
+pub fn move_to_uci_with(m: Move, chess960: bool) -> String {
+    if m.is_none() {
         return "0000".to_string();
     }
 
-    let from = move_from_sq(m);
-    let to = move_to_sq(m);
+    let from = m.from();
+    let to = castling_uci_to_sq(m, chess960);
     let mut uci = format!("{}{}", square_to_uci(from), square_to_uci(to));
 
     // Add promotion piece if applicable
-    if let Some(promo) = move_promotion(m) {
+    if let Some(promo) = m.promotion() {
         let promo_char = match promo {
             PieceKind::Knight => 'n',
             PieceKind::Bishop => 'b',
@@ -126,6 +183,23 @@ pub fn move_to_uci(m: Move) -> String {
     uci
 }
 
+/// Map a Chess960-style castling destination (king onto its own rook, e.g. e1h1)
+/// to the king's target square (e1g1); any other destination is returned as is
+fn castling_king_target(board: &Board, from: usize, to: usize) -> usize {
+    match (board.piece_on(from), board.piece_on(to)) {
+        (Some((PieceKind::King, king_color)), Some((PieceKind::Rook, rook_color)))
+            if king_color == rook_color && from / 8 == to / 8 =>
+        {
+            if to > from {
+                from + 2
+            } else {
+                from - 2
+            }
+        }
+        _ => to,
+    }
+}
+
 /// Convert UCI notation to a square index (e.g., "e2" -> 12, "a1" -> 0)
 fn uci_to_square(uci: &str) -> Result<usize, &'static str> {
     if uci.len() < 2 {
@@ -153,9 +227,9 @@ pub fn parse_uci_move(board: &mut Board, uci: &str) -> Result<Move, &'static str
         return Err("UCI move too short");
     }
 
-    // Parse from and to squares
+    // Parse from and to squares (king-takes-rook castling is accepted too)
     let from = uci_to_square(&uci[0..2])?;
-    let to = uci_to_square(&uci[2..4])?;
+    let to = castling_king_target(board, from, uci_to_square(&uci[2..4])?);
 
     // Parse promotion if present
     let promotion = if uci.len() >= 5 {
@@ -174,13 +248,13 @@ pub fn parse_uci_move(board: &mut Board, uci: &str) -> Result<Move, &'static str
     let legal_moves = board.generate_moves();
 
     for &mv in &legal_moves {
-        if move_from_sq(mv) == from && move_to_sq(mv) == to {
+        if mv.from() == from && mv.to() == to {
             // Check promotion matches if applicable
             if let Some(promo) = promotion {
-                if move_promotion(mv) == Some(promo) {
+                if mv.promotion() == Some(promo) {
                     return Ok(mv);
                 }
-            } else if move_promotion(mv).is_none() {
+            } else if mv.promotion().is_none() {
                 return Ok(mv);
             }
         }
@@ -200,12 +274,107 @@ pub fn new_move(
 ) -> Move {
     let cap = captured.map(|p| p as u32).unwrap_or(0xF);
     let prom = promotion.map(|p| p as u32).unwrap_or(0xF);
-    (from as u32 & 0x3F)
-        | ((to as u32 & 0x3F) << 6)
-        | ((piece as u32 & 0xF) << 12)
-        | ((cap & 0xF) << 16)
-        | ((prom & 0xF) << 20)
-        | flags
+    Move(
+        (from as u32 & 0x3F)
+            | ((to as u32 & 0x3F) << 6)
+            | ((piece as u32 & 0xF) << 12)
+            | ((cap & 0xF) << 16)
+            | ((prom & 0xF) << 20)
+            | flags,
+    )
+}
+
+/// Game phase of the starting material (see [`Board::phase`])
+pub const PHASE_MAX: u8 = 24;
+
+/// Weight of a piece in the game phase: 1 per minor piece, 2 per rook, 4 per
+/// queen (PeSTO), nothing for pawns and kings
+pub const fn phase_weight(kind: PieceKind) -> u8 {
+    match kind {
+        PieceKind::Knight | PieceKind::Bishop => 1,
+        PieceKind::Rook => 2,
+        PieceKind::Queen => 4,
+        PieceKind::Pawn | PieceKind::King => 0,
+    }
+}
+
+/// Material signature from piece counts `[pawns, knights, bishops, rooks, queens]`
+/// per side (kings excluded): 4 bits per count, white in bits 0-19, black in
+/// bits 20-39. Counts above 15 are clamped. See [`Board::material_key`].
+pub const fn material_key_from_counts(white: [u32; 5], black: [u32; 5]) -> u64 {
+    let mut key = 0u64;
+    let mut i = 0;
+    while i < 5 {
+        let w = if white[i] > 15 { 15 } else { white[i] };
+        let b = if black[i] > 15 { 15 } else { black[i] };
+        key |= (w as u64) << (4 * i);
+        key |= (b as u64) << (20 + 4 * i);
+        i += 1;
+    }
+    key
+}
+
+/// Leaf statistics of a perft run, in the layout of the standard perft tables
+///
+/// Every counter refers to the moves played at the last ply: `captures`
+/// includes en-passant captures, `promotions` counts each promotion piece
+/// separately, `checkmates` is a subset of `checks`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PerftCounts {
+    pub nodes: u64,
+    pub captures: u64,
+    pub en_passant: u64,
+    pub castles: u64,
+    pub promotions: u64,
+    pub checks: u64,
+    pub checkmates: u64,
+}
+
+impl std::ops::AddAssign for PerftCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.nodes += other.nodes;
+        self.captures += other.captures;
+        self.en_passant += other.en_passant;
+        self.castles += other.castles;
+        self.promotions += other.promotions;
+        self.checks += other.checks;
+        self.checkmates += other.checkmates;
+    }
+}
+
+/// How a game has ended (or may be ended by a claim), see [`Board::game_result`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameResult {
+    Checkmate { winner: Color },
+    Stalemate,
+    InsufficientMaterial,
+    FiftyMoveRule,
+    ThreefoldRepetition,
+}
+
+impl GameResult {
+    /// The side to move has no legal move: nothing can be played from here.
+    /// The other results are draws a GUI may still choose to play on from.
+    pub fn is_terminal(self) -> bool {
+        matches!(self, GameResult::Checkmate { .. } | GameResult::Stalemate)
+    }
+}
+
+impl std::fmt::Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameResult::Checkmate {
+                winner: Color::White,
+            } => write!(f, "checkmate 1-0"),
+            GameResult::Checkmate {
+                winner: Color::Black,
+            } => write!(f, "checkmate 0-1"),
+            GameResult::Stalemate => write!(f, "stalemate 1/2-1/2"),
+            GameResult::InsufficientMaterial => write!(f, "insufficient material 1/2-1/2"),
+            GameResult::FiftyMoveRule => write!(f, "fifty-move rule 1/2-1/2"),
+            GameResult::ThreefoldRepetition => write!(f, "threefold repetition 1/2-1/2"),
+        }
+    }
 }
 
 // Undo entry per rollback
@@ -243,10 +412,20 @@ pub struct Board {
     // King squares for fast king safety check
     pub white_king_sq: u8,
     pub black_king_sq: u8,
-    // Undo stack per unmake; capacità per centinaia di plies
-    _undo_stack: Vec<Undo>,
     // Position history for threefold repetition detection
     position_history: Vec<u64>,
+    // Moves played with push_move since the last set_from_fen, with their undo data
+    undo_stack: Vec<(Move, Undo)>,
+    // Accumulatori incrementali materiale+PSQT [color] (middlegame / endgame),
+    // aggiornati in set_piece/remove_piece e quindi anche da make/unmake
+    psqt_mg: [i32; 2],
+    psqt_eg: [i32; 2],
+    // Somma dei phase_weight dei pezzi sulla scacchiera (non limitata a
+    // PHASE_MAX: le promozioni possono superarla), aggiornata come i PSQT
+    phase_material: u8,
+    // Mailbox: pezzo su ogni casa, tenuto allineato ai bitboard da
+    // set_piece/remove_piece, così piece_on è una sola lettura
+    mailbox: [Option<(PieceKind, Color)>; 64],
 }
 
 impl Default for Board {
@@ -271,8 +450,12 @@ impl Board {
             zobrist: 0,
             white_king_sq: 0,
             black_king_sq: 0,
-            _undo_stack: Vec::with_capacity(1024),
             position_history: Vec::new(),
+            undo_stack: Vec::new(),
+            psqt_mg: [0; 2],
+            psqt_eg: [0; 2],
+            phase_material: 0,
+            mailbox: [None; 64],
         }
     }
 
@@ -288,29 +471,24 @@ impl Board {
 
     // Restituisce piece (kind,color) su square idx o None
     pub fn piece_on(&self, sq: usize) -> Option<(PieceKind, Color)> {
-        let mask = 1u64 << sq;
-        for i in 0..12 {
-            if self.piece_bb[i] & mask != 0 {
-                let kind = match i % 6 {
-                    0 => PieceKind::Pawn,
-                    1 => PieceKind::Knight,
-                    2 => PieceKind::Bishop,
-                    3 => PieceKind::Rook,
-                    4 => PieceKind::Queen,
-                    5 => PieceKind::King,
-                    _ => unreachable!(),
-                };
-                let color = if i < 6 { Color::White } else { Color::Black };
-                return Some((kind, color));
-            }
-        }
-        None
+        self.mailbox[sq]
     }
 
-    // Posiziona un pezzo; helper per FEN; NON aggiorna occupancy o Zobrist internamente (via set_from_fen)
+    // Posiziona un pezzo aggiornando occupancy, mailbox e accumulatori PSQT; NON aggiorna Zobrist
     pub fn set_piece(&mut self, sq: usize, kind: PieceKind, color: Color) {
         let i = piece_index(kind, color);
-        self.piece_bb[i] |= 1u64 << sq;
+        let bit = 1u64 << sq;
+        self.piece_bb[i] |= bit;
+        match color {
+            Color::White => self.white_occ |= bit,
+            Color::Black => self.black_occ |= bit,
+        }
+        self.occ |= bit;
+        self.mailbox[sq] = Some((kind, color));
+        let (mg, eg) = crate::eval::piece_square_value(kind, color, sq);
+        self.psqt_mg[color as usize] += mg;
+        self.psqt_eg[color as usize] += eg;
+        self.phase_material += phase_weight(kind);
         if kind == PieceKind::King {
             match color {
                 Color::White => self.white_king_sq = sq as u8,
@@ -322,14 +500,26 @@ impl Board {
     // Rimuovi pezzo; se re, memorizzo che è momentaneamente non sul board (make/unmake tracking)
     pub fn remove_piece(&mut self, sq: usize, kind: PieceKind, color: Color) {
         let i = piece_index(kind, color);
-        self.piece_bb[i] &= !(1u64 << sq);
+        let bit = 1u64 << sq;
+        self.piece_bb[i] &= !bit;
+        match color {
+            Color::White => self.white_occ &= !bit,
+            Color::Black => self.black_occ &= !bit,
+        }
+        self.occ &= !bit;
+        self.mailbox[sq] = None;
+        let (mg, eg) = crate::eval::piece_square_value(kind, color, sq);
+        self.psqt_mg[color as usize] -= mg;
+        self.psqt_eg[color as usize] -= eg;
+        self.phase_material -= phase_weight(kind);
         if kind == PieceKind::King {
             // In make/unmake tracking, la rimozione del re potrà avvenire per un attimo durante arrocco, ma lo riposizioniamo subito.
             // Non aggiorniamo king squares qui; lo farà make_move con le logiche ordinate.
         }
     }
 
-    // Refresh occupancy after bulk placement (usata in FEN setup)
+    // Ricostruisce l'occupancy dai bitboard dei pezzi (set_piece/remove_piece
+    // la mantengono già aggiornata; utile dopo modifiche dirette ai bitboard)
     pub fn refresh_occupancy(&mut self) {
         self.white_occ = 0;
         self.black_occ = 0;
@@ -355,12 +545,81 @@ impl Board {
         }
     }
 
+    // Il lato ha un re sulla scacchiera? (sempre vero tranne che in modalità studio)
+    pub fn has_king(&self, side: Color) -> bool {
+        self.piece_bb(PieceKind::King, side) != 0
+    }
+
+    /// Whether `side` has a piece besides its king and pawns. A side without
+    /// one can run out of moves while not in check (stalemate).
+    pub fn has_non_pawn_material(&self, side: Color) -> bool {
+        [
+            PieceKind::Knight,
+            PieceKind::Bishop,
+            PieceKind::Rook,
+            PieceKind::Queen,
+        ]
+        .iter()
+        .any(|&kind| self.piece_bb(kind, side) != 0)
+    }
+
+    /// Whether the king of `side` has a square to step to that holds no
+    /// piece of its own and is not attacked: a legal move, as long as `side`
+    /// is not in check (a checking slider still covers the square behind the
+    /// king). Cheaper than generating the moves.
+    pub fn king_has_safe_step(&self, side: Color) -> bool {
+        let (own, opponent) = match side {
+            Color::White => (self.white_occ, Color::Black),
+            Color::Black => (self.black_occ, Color::White),
+        };
+        let mut steps = crate::utils::king_attacks(self.king_sq(side)) & !own;
+        while let Some(sq) = crate::utils::pop_lsb(&mut steps) {
+            if !self.is_square_attacked(sq, opponent) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Material signature of the position (piece counts per side, kings
+    /// excluded), used to look up endgame-specific knowledge. Equal for every
+    /// position with the same material, whatever the squares.
+    pub fn material_key(&self) -> u64 {
+        let counts = |color| {
+            [
+                PieceKind::Pawn,
+                PieceKind::Knight,
+                PieceKind::Bishop,
+                PieceKind::Rook,
+                PieceKind::Queen,
+            ]
+            .map(|kind| self.piece_bb(kind, color).count_ones())
+        };
+        material_key_from_counts(counts(Color::White), counts(Color::Black))
+    }
+
+    /// Validate the kings on the board: at most one per side, and exactly one
+    /// unless `allow_missing` (study/puzzle mode) is set.
+    ///
+    /// Without a king a side can never be in check or mated, so such positions
+    /// are searched on material only.
+    pub fn validate_kings(&self, allow_missing: bool) -> Result<(), FenError> {
+        for color in [Color::White, Color::Black] {
+            match self.piece_bb(PieceKind::King, color).count_ones() {
+                0 if !allow_missing => return Err(FenError::MissingKing(color)),
+                0 | 1 => {}
+                _ => return Err(FenError::TooManyKings(color)),
+            }
+        }
+        Ok(())
+    }
+
     pub fn make_move(&mut self, mv: Move) -> Undo {
-        let from = move_from_sq(mv);
-        let to = move_to_sq(mv);
-        let piece = move_piece(mv);
-        let flags = mv & 0xFF000000u32;
-        let captured = move_captured(mv);
+        let from = mv.from();
+        let to = mv.to();
+        let piece = mv.piece();
+        let flags = mv.flags();
+        let captured = mv.captured();
         let ep_target = self.ep;
 
         // Store current position hash for threefold repetition detection
@@ -372,7 +631,7 @@ impl Board {
         } else {
             Color::Black
         };
-        let captured_sq = if move_flag(mv, FLAG_EN_PASSANT) {
+        let captured_sq = if mv.has_flag(FLAG_EN_PASSANT) {
             Some(if color == Color::White {
                 (ep_target.unwrap() as i32) - 8
             } else {
@@ -383,41 +642,13 @@ impl Board {
         } else {
             None
         };
-        let promoted_piece = if move_flag(mv, FLAG_PROMOTION) {
-            move_promotion(mv)
+        let promoted_piece = if mv.is_promotion() {
+            mv.promotion()
         } else {
             None
         };
 
         let double_pawn_move = piece == PieceKind::Pawn && to.abs_diff(from) == 16;
-        let new_ep_sq = if double_pawn_move {
-            let enemy_pawns = if color == Color::White {
-                self.piece_bb(PieceKind::Pawn, Color::Black)
-            } else {
-                self.piece_bb(PieceKind::Pawn, Color::White)
-            };
-            let file = to % 8;
-            let mut can_capture = false;
-            if file > 0 {
-                let sq = to - 1;
-                if (enemy_pawns >> sq) & 1 != 0 {
-                    can_capture = true;
-                }
-            }
-            if !can_capture && file < 7 {
-                let sq = to + 1;
-                if (enemy_pawns >> sq) & 1 != 0 {
-                    can_capture = true;
-                }
-            }
-            if can_capture {
-                Some(((from + to) / 2) as u8)
-            } else {
-                None
-            }
-        } else {
-            None
-        };
 
         let undo = Undo {
             from,
@@ -437,8 +668,8 @@ impl Board {
         };
         // Update Zobrist incrementally (undo still holds previous hash)
         self.zobrist ^= crate::zobrist::piece_key(piece, color, from);
-        let moved = if move_flag(mv, FLAG_PROMOTION) {
-            move_promotion(mv).unwrap()
+        let moved = if mv.is_promotion() {
+            mv.promotion().unwrap()
         } else {
             piece
         };
@@ -449,7 +680,7 @@ impl Board {
             } else {
                 Color::White
             };
-            let cap_sq = if move_flag(mv, FLAG_EN_PASSANT) {
+            let cap_sq = if mv.has_flag(FLAG_EN_PASSANT) {
                 captured_sq.unwrap()
             } else {
                 to
@@ -475,10 +706,6 @@ impl Board {
             let old_file = (old_ep_sq % 8) as usize;
             self.zobrist ^= crate::zobrist::ep_file_key(old_file);
         }
-        if let Some(ep_sq) = new_ep_sq {
-            let file = (ep_sq % 8) as usize;
-            self.zobrist ^= crate::zobrist::ep_file_key(file);
-        }
         // Update piece/occupancy fields
         if piece == PieceKind::King {
             if color == Color::White {
@@ -490,7 +717,7 @@ impl Board {
 
         self.remove_piece(from, piece, color);
         if let Some(capt) = captured {
-            if move_flag(mv, FLAG_EN_PASSANT) {
+            if mv.has_flag(FLAG_EN_PASSANT) {
                 self.remove_piece(
                     captured_sq.unwrap(),
                     capt,
@@ -512,15 +739,15 @@ impl Board {
                 );
             }
         }
-        let moved_piece = if move_flag(mv, FLAG_PROMOTION) {
-            move_promotion(mv).unwrap()
+        let moved_piece = if mv.is_promotion() {
+            mv.promotion().unwrap()
         } else {
             piece
         };
         self.set_piece(to, moved_piece, color);
 
         // Handle castling: move the rook as well
-        if move_flag(mv, FLAG_CASTLE_KING) {
+        if mv.has_flag(FLAG_CASTLE_KING) {
             // Kingside castle
             let (rook_from, rook_to) = if color == Color::White {
                 (7, 5) // h1 -> f1
@@ -532,7 +759,7 @@ impl Board {
             // Update Zobrist for rook move
             self.zobrist ^= crate::zobrist::piece_key(PieceKind::Rook, color, rook_from);
             self.zobrist ^= crate::zobrist::piece_key(PieceKind::Rook, color, rook_to);
-        } else if move_flag(mv, FLAG_CASTLE_QUEEN) {
+        } else if mv.has_flag(FLAG_CASTLE_QUEEN) {
             // Queenside castle
             let (rook_from, rook_to) = if color == Color::White {
                 (0, 3) // a1 -> d1
@@ -546,9 +773,24 @@ impl Board {
             self.zobrist ^= crate::zobrist::piece_key(PieceKind::Rook, color, rook_to);
         }
 
-        self.refresh_occupancy();
-
-        // Update en-passant flag
+        // En-passant square only if the opponent can legally take en passant
+        // (otherwise the same position would get two different hashes)
+        let opponent = if color == Color::White {
+            Color::Black
+        } else {
+            Color::White
+        };
+        let new_ep_sq = if double_pawn_move {
+            let ep_sq = (from + to) / 2;
+            self.ep_capture_is_legal(ep_sq, opponent)
+                .then_some(ep_sq as u8)
+        } else {
+            None
+        };
+        if let Some(ep_sq) = new_ep_sq {
+            let file = (ep_sq % 8) as usize;
+            self.zobrist ^= crate::zobrist::ep_file_key(file);
+        }
         self.ep = new_ep_sq;
         // Update move counters
         self.halfmove += 1;
@@ -566,6 +808,64 @@ impl Board {
         undo
     }
 
+    /// True if `side` has a legal en-passant capture onto `ep_sq`: an enemy
+    /// pawn that just passed it, and a pawn of `side` that can take it
+    /// without leaving its king in check (a pin of the capturing pawn, or a
+    /// rank pin opened by removing both pawns)
+    fn ep_capture_is_legal(&self, ep_sq: usize, side: Color) -> bool {
+        let (ep_rank, enemy) = match side {
+            Color::White => (5, Color::Black),
+            Color::Black => (2, Color::White),
+        };
+        if ep_sq / 8 != ep_rank {
+            return false;
+        }
+        let captured_sq = if side == Color::White {
+            ep_sq - 8
+        } else {
+            ep_sq + 8
+        };
+        let captured = 1u64 << captured_sq;
+        if self.piece_bb(PieceKind::Pawn, enemy) & captured == 0 || self.is_occupied(ep_sq) {
+            return false;
+        }
+        let mut capturers =
+            self.attackers_to_by(ep_sq, self.occ, side) & self.piece_bb(PieceKind::Pawn, side);
+        if !self.has_king(side) {
+            return capturers != 0;
+        }
+        let king_sq = self.king_sq(side);
+        while let Some(from) = crate::utils::pop_lsb(&mut capturers) {
+            let occ = (self.occ & !(1u64 << from) & !captured) | (1u64 << ep_sq);
+            if self.attackers_to_by(king_sq, occ, enemy) & !captured == 0 {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Play `mv` and keep its undo data on the board, to be taken back with
+    /// [`Board::pop_move`]. Moves played with `make_move` are not on the
+    /// stack: do not mix the two inside one push/pop pair.
+    pub fn push_move(&mut self, mv: Move) {
+        let undo = self.make_move(mv);
+        self.undo_stack.push((mv, undo));
+    }
+
+    /// Take back the last move played with [`Board::push_move`] and return it
+    /// (None if there is nothing to take back)
+    pub fn pop_move(&mut self) -> Option<Move> {
+        let (mv, undo) = self.undo_stack.pop()?;
+        self.unmake_move(undo);
+        Some(mv)
+    }
+
+    /// Moves on the undo stack: plies played with `push_move` since the
+    /// position was set up
+    pub fn ply(&self) -> usize {
+        self.undo_stack.len()
+    }
+
     pub fn unmake_move(&mut self, undo: Undo) {
         // Restore move counters/halfmove/fullmove/side first
         self.side = undo.prev_side;
@@ -608,7 +908,7 @@ impl Board {
         }
 
         // Handle castling: unmove the rook as well
-        if move_flag(undo.flags, FLAG_CASTLE_KING) {
+        if (undo.flags & FLAG_CASTLE_KING) != 0 {
             // Kingside castle - restore rook
             let (rook_from, rook_to) = if mover_color == Color::White {
                 (7, 5) // h1 -> f1 (during make), so restore f1 -> h1
@@ -617,7 +917,7 @@ impl Board {
             };
             self.remove_piece(rook_to, PieceKind::Rook, mover_color);
             self.set_piece(rook_from, PieceKind::Rook, mover_color);
-        } else if move_flag(undo.flags, FLAG_CASTLE_QUEEN) {
+        } else if (undo.flags & FLAG_CASTLE_QUEEN) != 0 {
             // Queenside castle - restore rook
             let (rook_from, rook_to) = if mover_color == Color::White {
                 (0, 3) // a1 -> d1 (during make), so restore d1 -> a1
@@ -628,9 +928,7 @@ impl Board {
             self.set_piece(rook_from, PieceKind::Rook, mover_color);
         }
 
-        self.refresh_occupancy();
-
-        // Restore occupancy and hash
+        // Restore hash (occupancy is kept up to date by set_piece/remove_piece)
         self.zobrist = undo.prev_zobrist;
 
         // Restore position history
@@ -701,6 +999,97 @@ impl Board {
         crate::zobrist::recalc_zobrist_full(self)
     }
 
+    /// Check that the incrementally updated `zobrist` matches a full recompute.
+    pub fn verify_zobrist(&self) -> bool {
+        self.zobrist == self.recalc_zobrist()
+    }
+
+    /// Incremental material + PSQT accumulator for `color` as `(middlegame, endgame)`.
+    ///
+    /// Maintained by `set_piece`/`remove_piece`, so it stays in sync through
+    /// `make_move`/`unmake_move` without a full board scan.
+    pub fn psqt_score(&self, color: Color) -> (i32, i32) {
+        (self.psqt_mg[color as usize], self.psqt_eg[color as usize])
+    }
+
+    /// Recompute the material + PSQT accumulators from scratch as
+    /// `[(white_mg, white_eg), (black_mg, black_eg)]`.
+    ///
+    /// Debug counterpart of [`Board::psqt_score`], analogous to `recalc_zobrist`.
+    pub fn recalc_psqt(&self) -> [(i32, i32); 2] {
+        let mut acc = [(0i32, 0i32); 2];
+        for i in 0..12 {
+            let kind = match i % 6 {
+                0 => PieceKind::Pawn,
+                1 => PieceKind::Knight,
+                2 => PieceKind::Bishop,
+                3 => PieceKind::Rook,
+                4 => PieceKind::Queen,
+                _ => PieceKind::King,
+            };
+            let color = if i < 6 { Color::White } else { Color::Black };
+            let mut bb = self.piece_bb[i];
+            while let Some(sq) = crate::utils::pop_lsb(&mut bb) {
+                let (mg, eg) = crate::eval::piece_square_value(kind, color, sq);
+                acc[color as usize].0 += mg;
+                acc[color as usize].1 += eg;
+            }
+        }
+        acc
+    }
+
+    /// Check that the incremental PSQT accumulators match a full recompute.
+    pub fn verify_psqt(&self) -> bool {
+        let full = self.recalc_psqt();
+        full[0] == self.psqt_score(Color::White) && full[1] == self.psqt_score(Color::Black)
+    }
+
+    /// Game phase from the non-pawn material on the board: [`PHASE_MAX`] with
+    /// every piece of the starting position (or more, after promotions),
+    /// down to 0 with only kings and pawns. Kept up to date by
+    /// `set_piece`/`remove_piece`, so reading it costs nothing.
+    pub fn phase(&self) -> u8 {
+        self.phase_material.min(PHASE_MAX)
+    }
+
+    /// Recompute the (unclamped) phase material from the piece bitboards.
+    ///
+    /// Debug counterpart of [`Board::phase`], analogous to `recalc_psqt`.
+    pub fn recalc_phase(&self) -> u8 {
+        [
+            PieceKind::Knight,
+            PieceKind::Bishop,
+            PieceKind::Rook,
+            PieceKind::Queen,
+        ]
+        .iter()
+        .map(|&kind| {
+            let count = self.piece_bb(kind, Color::White).count_ones()
+                + self.piece_bb(kind, Color::Black).count_ones();
+            count as u8 * phase_weight(kind)
+        })
+        .sum()
+    }
+
+    /// Check that the incremental phase matches a full recompute.
+    pub fn verify_phase(&self) -> bool {
+        self.phase_material == self.recalc_phase()
+    }
+
+    /// Check that the mailbox read by [`Board::piece_on`] matches the piece
+    /// bitboards on every square.
+    pub fn verify_mailbox(&self) -> bool {
+        (0..64).all(|sq| {
+            let mask = 1u64 << sq;
+            let on_bitboards = (0..12).find(|&i| self.piece_bb[i] & mask != 0);
+            match (self.mailbox[sq], on_bitboards) {
+                (Some((kind, color)), Some(i)) => piece_index(kind, color) == i,
+                (None, None) => true,
+                _ => false,
+            }
+        })
+    }
+
     /// Check if the position is a draw by 50-move rule
     pub fn is_50_move_draw(&self) -> bool {
         self.halfmove >= 100 // 50 moves by each side = 100 half-moves
@@ -727,8 +1116,58 @@ impl Board {
         false
     }
 
+    /// Check if the current position occurred among the first `len` entries
+    /// of the history
+    pub fn occurred_before(&self, len: usize) -> bool {
+        let len = len.min(self.position_history.len());
+        self.position_history[..len].contains(&self.zobrist)
+    }
+
+    /// Check if the position with hash `key` is in the history
+    pub fn in_history(&self, key: u64) -> bool {
+        self.position_history.contains(&key)
+    }
+
+    /// Number of earlier occurrences of the current position in the game/search history
+    ///
+    /// Only the last `halfmove` plies are scanned: an irreversible move (capture or
+    /// pawn push) makes any older position unreachable.
+    pub fn repetition_count(&self) -> usize {
+        let window = (self.halfmove as usize).min(self.position_history.len());
+        let start = self.position_history.len() - window;
+        self.position_history[start..]
+            .iter()
+            .filter(|&&hash| hash == self.zobrist)
+            .count()
+    }
+
+    /// Drop the position history from before the last irreversible move
+    ///
+    /// Positions preceding a capture or pawn move can never recur, so only the
+    /// last `halfmove` entries matter for repetition detection. Keeps memory
+    /// bounded over long games; call it between game moves (e.g. after a UCI
+    /// `position` command), never while `Undo`s from `make_move` are pending.
+    /// Moves played with [`Board::push_move`] can no longer be taken back.
+    pub fn prune_history(&mut self) {
+        let keep = (self.halfmove as usize).min(self.position_history.len());
+        let start = self.position_history.len() - keep;
+        self.position_history.drain(..start);
+        // Their `Undo`s would restore the history to its unpruned length
+        self.undo_stack.clear();
+    }
+
+    /// Number of earlier positions kept for repetition detection
+    pub fn history_len(&self) -> usize {
+        self.position_history.len()
+    }
+
     /// Check if the position is a draw by insufficient material
     pub fn is_insufficient_material(&self) -> bool {
+        // Without both kings (study mode) nobody can be mated: only material counts
+        if !self.has_king(Color::White) || !self.has_king(Color::Black) {
+            return false;
+        }
+
         // Count all pieces (including kings)
         let white_pieces = self.white_occ.count_ones();
         let black_pieces = self.black_occ.count_ones();
@@ -738,6 +1177,19 @@ impl Board {
             return true;
         }
 
+        // Only bishops left, all on squares of one color (KB vs K, KB vs KB
+        // same color, ...): no mate is possible at all
+        const DARK_SQUARES: u64 = 0xAA55_AA55_AA55_AA55;
+        let bishops = self.piece_bb(PieceKind::Bishop, Color::White)
+            | self.piece_bb(PieceKind::Bishop, Color::Black);
+        let kings = self.piece_bb(PieceKind::King, Color::White)
+            | self.piece_bb(PieceKind::King, Color::Black);
+        if self.occ == bishops | kings
+            && (bishops & DARK_SQUARES == 0 || bishops & !DARK_SQUARES == 0)
+        {
+            return true;
+        }
+
         let white_knights = self.piece_bb(PieceKind::Knight, Color::White).count_ones();
         let white_bishops = self.piece_bb(PieceKind::Bishop, Color::White).count_ones();
         let black_knights = self.piece_bb(PieceKind::Knight, Color::Black).count_ones();
@@ -760,24 +1212,7 @@ impl Board {
             return true;
         }
 
-        // King + Bishop vs King + Bishop (same color bishops)
         if white_pieces == 2 && black_pieces == 2 {
-            let white_bishop_bb = self.piece_bb(PieceKind::Bishop, Color::White);
-            let black_bishop_bb = self.piece_bb(PieceKind::Bishop, Color::Black);
-
-            if white_bishop_bb.count_ones() == 1 && black_bishop_bb.count_ones() == 1 {
-                // Check if bishops are on same color squares
-                let white_bishop_sq = white_bishop_bb.trailing_zeros() as usize;
-                let black_bishop_sq = black_bishop_bb.trailing_zeros() as usize;
-
-                let white_sq_color = (white_bishop_sq / 8 + white_bishop_sq % 8) % 2;
-                let black_sq_color = (black_bishop_sq / 8 + black_bishop_sq % 8) % 2;
-
-                if white_sq_color == black_sq_color {
-                    return true;
-                }
-            }
-
             // K+N vs K+N
             if white_knights == 1 && white_bishops == 0 && black_knights == 1 && black_bishops == 0
             {
@@ -821,9 +1256,40 @@ impl Board {
             || self.is_stalemate()
     }
 
+    /// Result of the game in this position, None while it goes on.
+    /// Checkmate and stalemate take precedence over the draw rules (a mate
+    /// delivered on the 100th half-move still wins).
+    pub fn game_result(&self) -> Option<GameResult> {
+        if self.clone().generate_moves().is_empty() {
+            return Some(if self.is_in_check(self.side) {
+                GameResult::Checkmate {
+                    winner: match self.side {
+                        Color::White => Color::Black,
+                        Color::Black => Color::White,
+                    },
+                }
+            } else {
+                GameResult::Stalemate
+            });
+        }
+        if self.is_insufficient_material() {
+            Some(GameResult::InsufficientMaterial)
+        } else if self.is_50_move_draw() {
+            Some(GameResult::FiftyMoveRule)
+        } else if self.is_threefold_repetition() {
+            Some(GameResult::ThreefoldRepetition)
+        } else {
+            None
+        }
+    }
+
     /// Check if square is attacked by given color (helper for is_in_check)
     /// Check if current side is in check
     pub fn is_in_check(&self, side: Color) -> bool {
+        // A side without a king (study mode) is never in check
+        if !self.has_king(side) {
+            return false;
+        }
         let king_sq = self.king_sq(side);
         let opponent = match side {
             Color::White => Color::Black,
@@ -834,97 +1300,301 @@ impl Board {
 
     // Legality helpers -------------------------------------------
     pub fn is_square_attacked(&self, sq: usize, by: Color) -> bool {
-        // Pawn attacks
-        if by == Color::White {
-            let white_pawns = self.piece_bb(PieceKind::Pawn, Color::White);
-            if ((white_pawns & crate::utils::NOT_FILE_A) << 7) & (1u64 << sq) != 0 {
-                return true;
+        self.attackers_to_by(sq, self.occ, by) != 0
+    }
+
+    /// All pieces of both colors attacking `sq`, with sliders computed on `occ`
+    ///
+    /// Passing a reduced occupancy reveals x-ray attackers (used by [`Board::see`]).
+    pub fn attackers_to(&self, sq: usize, occ: u64) -> u64 {
+        let target = 1u64 << sq;
+        // Squares from which a pawn of each color would attack `sq`
+        let white_pawn_from =
+            ((target >> 7) & crate::utils::NOT_FILE_A) | ((target >> 9) & crate::utils::NOT_FILE_H);
+        let black_pawn_from =
+            ((target << 7) & crate::utils::NOT_FILE_H) | ((target << 9) & crate::utils::NOT_FILE_A);
+
+        let knights = self.piece_bb(PieceKind::Knight, Color::White)
+            | self.piece_bb(PieceKind::Knight, Color::Black);
+        let kings = self.piece_bb(PieceKind::King, Color::White)
+            | self.piece_bb(PieceKind::King, Color::Black);
+        let queens = self.piece_bb(PieceKind::Queen, Color::White)
+            | self.piece_bb(PieceKind::Queen, Color::Black);
+        let diagonal = self.piece_bb(PieceKind::Bishop, Color::White)
+            | self.piece_bb(PieceKind::Bishop, Color::Black)
+            | queens;
+        let orthogonal = self.piece_bb(PieceKind::Rook, Color::White)
+            | self.piece_bb(PieceKind::Rook, Color::Black)
+            | queens;
+
+        (white_pawn_from & self.piece_bb(PieceKind::Pawn, Color::White))
+            | (black_pawn_from & self.piece_bb(PieceKind::Pawn, Color::Black))
+            | (crate::utils::knight_attacks(sq) & knights)
+            | (crate::utils::king_attacks(sq) & kings)
+            | (crate::magic::bishop_attacks(sq, occ) & diagonal)
+            | (crate::magic::rook_attacks(sq, occ) & orthogonal)
+    }
+
+    /// Pieces of color `by` attacking `sq`, with sliders computed on `occ`
+    ///
+    /// Sliders are only looked up when `by` has some.
+    pub fn attackers_to_by(&self, sq: usize, occ: u64, by: Color) -> u64 {
+        let target = 1u64 << sq;
+        // Squares from which a pawn of `by` would attack `sq`
+        let pawn_from = match by {
+            Color::White => {
+                ((target >> 7) & crate::utils::NOT_FILE_A)
+                    | ((target >> 9) & crate::utils::NOT_FILE_H)
             }
-            if ((white_pawns & crate::utils::NOT_FILE_H) << 9) & (1u64 << sq) != 0 {
-                return true;
+            Color::Black => {
+                ((target << 7) & crate::utils::NOT_FILE_H)
+                    | ((target << 9) & crate::utils::NOT_FILE_A)
             }
-        } else {
-            let black_pawns = self.piece_bb(PieceKind::Pawn, Color::Black);
-            if ((black_pawns & crate::utils::NOT_FILE_A) >> 9) & (1u64 << sq) != 0 {
-                return true;
+        };
+        let queens = self.piece_bb(PieceKind::Queen, by);
+        let diagonal = self.piece_bb(PieceKind::Bishop, by) | queens;
+        let orthogonal = self.piece_bb(PieceKind::Rook, by) | queens;
+
+        let mut attackers = (pawn_from & self.piece_bb(PieceKind::Pawn, by))
+            | (crate::utils::knight_attacks(sq) & self.piece_bb(PieceKind::Knight, by))
+            | (crate::utils::king_attacks(sq) & self.piece_bb(PieceKind::King, by));
+        if diagonal != 0 {
+            attackers |= crate::magic::bishop_attacks(sq, occ) & diagonal;
+        }
+        if orthogonal != 0 {
+            attackers |= crate::magic::rook_attacks(sq, occ) & orthogonal;
+        }
+        attackers
+    }
+
+    /// Static Exchange Evaluation of `mv` (swap algorithm)
+    ///
+    /// Plays out the whole capture sequence on the destination square, each side
+    /// recapturing with its least valuable attacker (x-rays included) and being
+    /// free to stop when continuing would lose material. Returns the material
+    /// balance in centipawns for the side making `mv`: negative means a losing
+    /// capture. Quiet moves return the (non-positive) cost of leaving the piece
+    /// en prise.
+    pub fn see(&self, mv: Move) -> i16 {
+        const SEE_VALUES: [i16; 6] = [100, 320, 330, 500, 900, 20000];
+
+        let from = mv.from();
+        let to = mv.to();
+        let mut occ = self.occ & !(1u64 << from);
+
+        let mut gain = [0i16; 32];
+        gain[0] = mv.captured().map_or(0, |k| SEE_VALUES[k as usize]);
+        // Piece standing on `to` after the move (what the opponent can win next)
+        let mut on_square = mv.piece();
+        if let Some(promo) = mv.promotion() {
+            gain[0] += SEE_VALUES[promo as usize] - SEE_VALUES[PieceKind::Pawn as usize];
+            on_square = promo;
+        }
+        if mv.has_flag(FLAG_EN_PASSANT) {
+            let captured_sq = if self.side == Color::White {
+                to - 8
+            } else {
+                to + 8
+            };
+            occ &= !(1u64 << captured_sq);
+        }
+
+        let mut attackers = self.attackers_to(to, occ) & occ;
+        let mut side = match self.side {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        };
+        let mut depth = 0;
+
+        loop {
+            let side_attackers = attackers
+                & match side {
+                    Color::White => self.white_occ,
+                    Color::Black => self.black_occ,
+                };
+            // Least valuable attacker of the side to capture
+            let lva = [
+                PieceKind::Pawn,
+                PieceKind::Knight,
+                PieceKind::Bishop,
+                PieceKind::Rook,
+                PieceKind::Queen,
+                PieceKind::King,
+            ]
+            .into_iter()
+            .find_map(|kind| {
+                let bb = side_attackers & self.piece_bb(kind, side);
+                (bb != 0).then(|| (kind, bb.trailing_zeros() as usize))
+            });
+            let Some((kind, sq)) = lva else { break };
+
+            // The king may only recapture if the square is no longer defended
+            if kind == PieceKind::King && attackers & !side_attackers != 0 {
+                break;
             }
-            if ((black_pawns & crate::utils::NOT_FILE_H) >> 7) & (1u64 << sq) != 0 {
-                return true;
+            if depth + 1 >= gain.len() {
+                break;
             }
+
+            depth += 1;
+            gain[depth] = SEE_VALUES[on_square as usize] - gain[depth - 1];
+            on_square = kind;
+
+            occ &= !(1u64 << sq);
+            // Removing the capturer may uncover sliders behind it
+            attackers = self.attackers_to(to, occ) & occ;
+            side = match side {
+                Color::White => Color::Black,
+                Color::Black => Color::White,
+            };
         }
-        // Knight attacks
-        if crate::utils::knight_attacks(sq) & self.piece_bb(PieceKind::Knight, by) != 0 {
-            return true;
+
+        // Each side may decline to continue the exchange
+        while depth > 0 {
+            gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+            depth -= 1;
         }
-        // King attacks
-        if crate::utils::king_attacks(sq) & self.piece_bb(PieceKind::King, by) != 0 {
-            return true;
+        gain[0]
+    }
+
+    // Perft ---------------------------------------------------------
+
+    /// Number of leaf nodes of the legal move tree at `depth`
+    pub fn perft(&mut self, depth: u8) -> u64 {
+        if depth == 0 {
+            return 1;
         }
-        // Bishop/Queen (diagonal sliding)
-        let diagonal_attackers =
-            self.piece_bb(PieceKind::Bishop, by) | self.piece_bb(PieceKind::Queen, by);
-        if diagonal_attackers != 0
-            && crate::magic::bishop_attacks(sq, self.occ) & diagonal_attackers != 0
-        {
-            return true;
+        let moves = self.generate_moves();
+        if depth == 1 {
+            return moves.len() as u64;
         }
-        // Rook/Queen (orthogonal sliding)
-        let orthogonal_attackers =
-            self.piece_bb(PieceKind::Rook, by) | self.piece_bb(PieceKind::Queen, by);
-        if orthogonal_attackers != 0
-            && crate::magic::rook_attacks(sq, self.occ) & orthogonal_attackers != 0
-        {
-            return true;
+        let mut nodes = 0;
+        for mv in moves {
+            self.push_move(mv);
+            nodes += self.perft(depth - 1);
+            self.pop_move();
         }
-        false
+        nodes
     }
 
-    // Generate moves APIs -----------------------------------------
-    pub fn generate_moves(&mut self) -> Vec<Move> {
-        let mut pseudo: Vec<Move> = Vec::with_capacity(256);
-        self.generate_pseudo_moves(&mut pseudo);
-        let mut legal = Vec::with_capacity(pseudo.len());
-        for mv in pseudo {
+    /// Perft with the leaf moves broken down by kind (see [`PerftCounts`]).
+    ///
+    /// Slower than [`Board::perft`]: every leaf move is played to test for
+    /// check and mate.
+    pub fn perft_breakdown(&mut self, depth: u8) -> PerftCounts {
+        if depth == 0 {
+            return PerftCounts {
+                nodes: 1,
+                ..PerftCounts::default()
+            };
+        }
+        let mut counts = PerftCounts::default();
+        for mv in self.generate_moves() {
             let undo = self.make_move(mv);
-            // After make_move, self.side is now the opponent
-            let side_to_move = self.side;
-            let side_that_moved = if side_to_move == Color::White {
-                Color::Black
+            if depth > 1 {
+                counts += self.perft_breakdown(depth - 1);
             } else {
-                Color::White
-            };
-            let own_king_sq = self.king_sq(side_that_moved);
-            let is_attacked = self.is_square_attacked(own_king_sq, side_to_move);
-            if !is_attacked {
-                legal.push(mv);
+                counts.nodes += 1;
+                if mv.captured().is_some() || mv.has_flag(FLAG_EN_PASSANT) {
+                    counts.captures += 1;
+                }
+                if mv.has_flag(FLAG_EN_PASSANT) {
+                    counts.en_passant += 1;
+                }
+                if mv.has_flag(FLAG_CASTLE_KING | FLAG_CASTLE_QUEEN) {
+                    counts.castles += 1;
+                }
+                if mv.promotion().is_some() {
+                    counts.promotions += 1;
+                }
+                if self.is_in_check(self.side) {
+                    counts.checks += 1;
+                    if self.generate_moves().is_empty() {
+                        counts.checkmates += 1;
+                    }
+                }
             }
             self.unmake_move(undo);
         }
-        legal
+        counts
     }
 
-    /// Generate only captures and promotions (for quiescence search)
-    /// This is much faster than generate_moves() when we only need tactical moves
-    pub fn generate_captures(&mut self) -> Vec<Move> {
-        let mut pseudo: Vec<Move> = Vec::with_capacity(64);
-        self.generate_captures_pseudos(&mut pseudo);
-        let mut legal = Vec::with_capacity(pseudo.len());
-        for mv in pseudo {
+    // Generate moves APIs -----------------------------------------
+    pub fn generate_moves(&mut self) -> Vec<Move> {
+        let mut pseudo: Vec<Move> = Vec::with_capacity(256);
+        self.generate_pseudo_moves(&mut pseudo);
+        let mut legal = Vec::with_capacity(pseudo.len());
+        self.push_legal(pseudo, &mut legal);
+        legal
+    }
+
+    /// Append the legal captures and promotions to `out` (for quiescence search)
+    /// This is much faster than generate_moves() when we only need tactical moves
+    pub fn generate_captures(&mut self, out: &mut Vec<Move>) {
+        let mut pseudo: Vec<Move> = Vec::with_capacity(64);
+        self.generate_captures_pseudos(&mut pseudo);
+        self.push_legal(pseudo, out);
+    }
+
+    /// Legal replies to check: king moves, captures of the checker and
+    /// interpositions on its line (only king moves against a double check).
+    /// Same moves, in the same order, as generate_moves() in check; empty
+    /// when the side to move is not in check.
+    pub fn generate_evasions(&mut self) -> Vec<Move> {
+        let mut evasions = Vec::new();
+        if !self.has_king(self.side) {
+            return evasions;
+        }
+        let king_sq = self.king_sq(self.side);
+        let enemy = match self.side {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        };
+        let checkers = self.attackers_to_by(king_sq, self.occ, enemy);
+        if checkers == 0 {
+            return evasions;
+        }
+
+        // Destinations that resolve a single check: the checker's square and,
+        // for a slider, the squares between it and the king
+        let targets = if checkers.count_ones() > 1 {
+            0
+        } else {
+            // Empty for knights, pawns and adjacent pieces
+            checkers | crate::utils::between(king_sq, checkers.trailing_zeros() as usize)
+        };
+
+        let mut pseudo: Vec<Move> = Vec::with_capacity(64);
+        self.generate_pseudo_moves(&mut pseudo);
+        pseudo.retain(|&mv| {
+            if mv.has_flag(FLAG_CASTLE_KING) || mv.has_flag(FLAG_CASTLE_QUEEN) {
+                false
+            } else if mv.piece() == PieceKind::King {
+                true
+            } else {
+                // En passant can remove a checking pawn: left to the legality test
+                targets & (1u64 << mv.to()) != 0 || mv.has_flag(FLAG_EN_PASSANT)
+            }
+        });
+        self.push_legal(pseudo, &mut evasions);
+        evasions
+    }
+
+    /// Append the moves of `pseudo` that do not leave the mover in check
+    fn push_legal(&mut self, pseudo: Vec<Move>, out: &mut Vec<Move>) {
+        for mv in pseudo {
             let undo = self.make_move(mv);
-            let side_to_move = self.side;
-            let side_that_moved = if side_to_move == Color::White {
+            // After make_move, self.side is now the opponent
+            let side_that_moved = if self.side == Color::White {
                 Color::Black
             } else {
                 Color::White
             };
-            let own_king_sq = self.king_sq(side_that_moved);
-            let is_attacked = self.is_square_attacked(own_king_sq, side_to_move);
-            if !is_attacked {
-                legal.push(mv);
+            if !self.is_in_check(side_that_moved) {
+                out.push(mv);
             }
             self.unmake_move(undo);
         }
-        legal
     }
 
     /// Generate pseudo-legal captures and promotions only
@@ -1730,6 +2400,64 @@ pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn test_move_accessors_decode_new_move() {
+        // e7xd8=Q
+        let mv = new_move(
+            52,
+            59,
+            PieceKind::Pawn,
+            Some(PieceKind::Rook),
+            Some(PieceKind::Queen),
+            FLAG_PROMOTION | FLAG_CAPTURE,
+        );
+        assert_eq!((mv.from(), mv.to()), (52, 59));
+        assert_eq!(mv.piece(), PieceKind::Pawn);
+        assert_eq!(mv.captured(), Some(PieceKind::Rook));
+        assert_eq!(mv.promotion(), Some(PieceKind::Queen));
+        assert!(mv.is_capture() && mv.is_promotion());
+        assert_eq!(mv.flags(), FLAG_PROMOTION | FLAG_CAPTURE);
+        assert_eq!(mv.to_string(), "e7d8q");
+
+        let quiet = new_move(12, 28, PieceKind::Pawn, None, None, FLAG_NONE);
+        assert!(!quiet.is_capture() && !quiet.is_promotion());
+        assert_eq!(quiet.promotion(), None);
+        assert_eq!(Move::from_raw(quiet.raw()), quiet);
+        assert!(Move::NONE.is_none() && !quiet.is_none());
+    }
+
+    #[test]
+    fn test_to_fen_round_trips() {
+        for fen in [
+            START_FEN,
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "r3k2r/8/8/8/8/8/8/4K2R b Kq - 7 41",
+            "8/8/8/8/8/8/8/K6k w - - 0 1",
+        ] {
+            let mut board = Board::new();
+            board.set_from_fen(fen).unwrap();
+            assert_eq!(board.to_fen(), fen);
+        }
+    }
+
+    #[test]
+    fn test_mirror_flips_colors() {
+        let mut board = Board::new();
+        board
+            .set_from_fen("r3k2r/8/8/8/2Pp4/8/8/4K2R b Kq c3 0 9")
+            .unwrap();
+        let mirrored = board.mirror();
+        assert_eq!(mirrored.to_fen(), "4k2r/8/8/2pP4/8/8/8/R3K2R w Qk c6 0 9");
+        assert_eq!(mirrored.king_sq(Color::White), 4);
+        assert_eq!(mirrored.king_sq(Color::Black), 60);
+        assert_eq!(mirrored.zobrist, mirrored.recalc_zobrist());
+        assert_eq!(
+            mirrored.psqt_score(Color::White),
+            board.psqt_score(Color::Black)
+        );
+    }
+
     #[test]
     fn test_make_unmake_zobrist_invariant() {
         crate::init();
@@ -1749,6 +2477,472 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_incremental_occupancy_matches_rebuild() {
+        crate::init();
+        // Castling, en passant and promotions with and without capture
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            "r3k2r/1P6/8/8/8/8/6p1/R3K2R b KQkq - 0 1",
+        ] {
+            let mut board = Board::new();
+            board.set_from_fen(fen).unwrap();
+            let (white, black, all) = (board.white_occ, board.black_occ, board.occ);
+            for mv in board.generate_moves() {
+                let undo = board.make_move(mv);
+                let mut rebuilt = board.clone();
+                rebuilt.refresh_occupancy();
+                assert_eq!(
+                    (board.white_occ, board.black_occ, board.occ),
+                    (rebuilt.white_occ, rebuilt.black_occ, rebuilt.occ),
+                    "occupancy out of sync after {} in {}",
+                    mv,
+                    fen
+                );
+                assert!(board.verify_mailbox(), "mailbox out of sync after {}", mv);
+                assert!(board.verify_phase(), "phase out of sync after {}", mv);
+                board.unmake_move(undo);
+                assert_eq!(
+                    (board.white_occ, board.black_occ, board.occ),
+                    (white, black, all)
+                );
+                assert!(board.verify_mailbox());
+            }
+        }
+    }
+
+    fn assert_same_position(a: &Board, b: &Board) {
+        for i in 0..12 {
+            assert_eq!(
+                a.piece_bb_raw(i),
+                b.piece_bb_raw(i),
+                "piece_bb[{}] differs",
+                i
+            );
+        }
+        assert_eq!(a.occ, b.occ);
+        assert_eq!(a.side, b.side);
+        assert_eq!(a.castling, b.castling);
+        assert_eq!(a.ep, b.ep);
+        assert_eq!(a.halfmove, b.halfmove);
+        assert_eq!(a.fullmove, b.fullmove);
+        assert_eq!(a.zobrist, b.zobrist);
+        assert_eq!(a.king_sq(Color::White), b.king_sq(Color::White));
+        assert_eq!(a.king_sq(Color::Black), b.king_sq(Color::Black));
+        assert!(b.verify_psqt());
+        assert!(b.verify_mailbox());
+        assert_eq!(a.phase(), b.phase());
+    }
+
+    #[test]
+    fn test_phase_follows_captures_and_promotions() {
+        crate::init();
+        let mut board = Board::new();
+        board.set_from_fen(START_FEN).unwrap();
+        assert_eq!(board.phase(), PHASE_MAX);
+
+        // Rook and pawn each: 2 + 2
+        board
+            .set_from_fen("r3k3/1P6/8/8/8/8/8/4K2R w - - 0 1")
+            .unwrap();
+        assert_eq!(board.phase(), 4);
+        let promote = parse_uci_move(&mut board, "b7a8q").unwrap();
+        let undo = board.make_move(promote);
+        // The black rook is gone, a queen has appeared
+        assert_eq!(board.phase(), 6);
+        assert!(board.verify_phase());
+        board.unmake_move(undo);
+        assert_eq!(board.phase(), 4);
+
+        // Extra queens count past the starting material, but the phase stops there
+        board
+            .set_from_fen("QQQQkQQQ/8/8/8/8/8/8/4K3 w - - 0 1")
+            .unwrap();
+        assert_eq!(board.recalc_phase(), 28);
+        assert_eq!(board.phase(), PHASE_MAX);
+
+        board
+            .set_from_fen("4k3/pppp4/8/8/8/8/PPPP4/4K3 w - - 0 1")
+            .unwrap();
+        assert_eq!(board.phase(), 0);
+    }
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        crate::init();
+        let fens = [
+            START_FEN,
+            // Tutti i diritti di arrocco + posizione ricca
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            // Arrocchi parziali, nero al tratto
+            "r3k2r/8/8/8/8/8/8/R3K2R b Kq - 5 42",
+            // En-passant per il bianco (casa sulla sesta traversa)
+            "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+            // En-passant per il nero (casa sulla terza traversa)
+            "rnbqkbnr/pppp1ppp/8/8/3PpP2/8/PPP1P1PP/RNBQKBNR b KQkq d3 0 3",
+            // Solo re, contatori alti
+            "8/8/8/4k3/8/8/8/K7 w - - 99 300",
+        ];
+        for fen in fens {
+            let mut board = Board::new();
+            board.set_from_fen(fen).unwrap();
+            let packed = board.encode().unwrap();
+            let decoded = Board::decode(&packed).unwrap();
+            assert_same_position(&board, &decoded);
+            assert_eq!(
+                decoded.encode(),
+                Ok(packed),
+                "re-encode differs for {}",
+                fen
+            );
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_after_moves() {
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+            .unwrap();
+        for mv in board.generate_moves() {
+            let undo = board.make_move(mv);
+            let decoded = Board::decode(&board.encode().unwrap()).unwrap();
+            assert_same_position(&board, &decoded);
+            board.unmake_move(undo);
+        }
+    }
+
+    #[test]
+    fn test_decode_rejects_garbage() {
+        let mut packed = [0u8; PACKED_BOARD_SIZE];
+        packed[0] = 1; // un pezzo su a1
+        packed[8] = 0x07; // codice pezzo non valido
+        assert!(Board::decode(&packed).is_err());
+
+        let mut packed = [0u8; PACKED_BOARD_SIZE];
+        packed[25] = 30; // ep su una traversa impossibile
+        assert!(Board::decode(&packed).is_err());
+    }
+
+    #[test]
+    fn test_encode_rejects_more_than_32_pieces() {
+        crate::init();
+        let mut board = Board::new();
+        // Nove pedoni bianchi: 33 pezzi
+        board
+            .set_from_fen("rnbqkbnr/pppppppp/8/8/8/P7/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+            .unwrap();
+        assert_eq!(board.encode(), Err("too many pieces"));
+    }
+
+    #[test]
+    fn test_castling_uci_notation() {
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1")
+            .unwrap();
+
+        // Arena/CuteChess standard: e1g1; Chess960: king takes rook, e1h1
+        let short = parse_uci_move(&mut board, "e1g1").unwrap();
+        assert!(short.has_flag(FLAG_CASTLE_KING));
+        assert_eq!(move_to_uci(short), "e1g1");
+        assert_eq!(move_to_uci_with(short, true), "e1h1");
+        assert_eq!(parse_uci_move(&mut board, "e1h1"), Ok(short));
+
+        let long = parse_uci_move(&mut board, "e1a1").unwrap();
+        assert!(long.has_flag(FLAG_CASTLE_QUEEN));
+        assert_eq!(move_to_uci(long), "e1c1");
+        assert_eq!(move_to_uci_with(long, true), "e1a1");
+
+        board.make_move(short);
+        let black_long = parse_uci_move(&mut board, "e8a8").unwrap();
+        assert_eq!(move_to_uci(black_long), "e8c8");
+        assert_eq!(move_to_uci_with(black_long, true), "e8a8");
+
+        // Non-castling moves are unaffected by the mode
+        let rook = parse_uci_move(&mut board, "h8h1").unwrap();
+        assert_eq!(move_to_uci_with(rook, true), "h8h1");
+    }
+
+    #[test]
+    fn test_material_key() {
+        crate::init();
+        let mut a = Board::new();
+        a.set_from_fen("8/8/4k3/8/8/2NN4/8/4K3 w - - 0 1").unwrap();
+        let mut b = Board::new();
+        b.set_from_fen("4k3/8/8/1N6/6N1/8/8/K7 b - - 0 1").unwrap();
+        // Stesso materiale su case diverse
+        assert_eq!(a.material_key(), b.material_key());
+        assert_eq!(
+            a.material_key(),
+            material_key_from_counts([0, 2, 0, 0, 0], [0; 5])
+        );
+
+        // Stessi pezzi al colore opposto: firma diversa
+        b.set_from_fen("4k3/8/2nn4/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert_ne!(a.material_key(), b.material_key());
+
+        b.set_from_fen(START_FEN).unwrap();
+        assert_eq!(
+            b.material_key(),
+            material_key_from_counts([8, 2, 2, 2, 1], [8, 2, 2, 2, 1])
+        );
+    }
+
+    #[test]
+    fn test_fen_without_move_counters() {
+        crate::init();
+        let mut full = Board::new();
+        full.set_from_fen("r3k2r/8/8/8/2Pp4/8/8/4K2R b Kq c3 0 1")
+            .unwrap();
+        let mut short = Board::new();
+        short
+            .set_from_fen("  r3k2r/8/8/8/2Pp4/8/8/4K2R \t b  Kq c3 \n")
+            .unwrap();
+        assert_eq!(short.to_fen(), full.to_fen());
+        assert_eq!(short.zobrist, full.zobrist);
+        short
+            .set_from_fen("r3k2r/8/8/8/2Pp4/8/8/4K2R b Kq c3 7")
+            .unwrap();
+        assert_eq!((short.halfmove, short.fullmove), (7, 1));
+
+        let err = |fen: &str| Board::new().set_from_fen(fen).unwrap_err();
+        assert_eq!(
+            err("4k3/8/8/8/8/8/8/4X3 w - -"),
+            FenError::InvalidPiece('X')
+        );
+        // Campi dopo i pezzi di "4k3/8/8/8/8/8/8/4K3"
+        for (fields, expected) in [
+            ("w -", FenError::MissingField("en passant")),
+            ("x - -", FenError::InvalidSide("x".into())),
+            ("w KX -", FenError::InvalidCastling('X')),
+            ("w - e4", FenError::InvalidEnPassant("e4".into())),
+            ("w - - x 1", FenError::InvalidHalfmove("x".into())),
+        ] {
+            assert_eq!(err(&format!("4k3/8/8/8/8/8/8/4K3 {}", fields)), expected);
+        }
+        assert_eq!(err(" ").to_string(), "missing pieces field");
+    }
+
+    #[test]
+    fn test_validate_kings_and_study_positions() {
+        crate::init();
+        let mut board = Board::new();
+        board.set_from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+        assert!(board.validate_kings(false).is_ok());
+
+        board
+            .set_from_fen("4k3/8/8/8/8/8/8/3KK3 w - - 0 1")
+            .unwrap();
+        assert_eq!(
+            board.validate_kings(true),
+            Err(FenError::TooManyKings(Color::White))
+        );
+
+        // Manca il re nero: rifiutato in gioco normale, accettato in modalità studio
+        board
+            .set_from_fen("8/8/8/3b4/8/8/8/K2R4 w - - 0 1")
+            .unwrap();
+        assert_eq!(
+            board.validate_kings(false),
+            Err(FenError::MissingKing(Color::Black))
+        );
+        assert!(board.validate_kings(true).is_ok());
+        assert!(!board.has_king(Color::Black));
+        assert!(!board.is_insufficient_material());
+
+        // Il lato senza re non è mai sotto scacco e può muovere liberamente
+        board.side = Color::Black;
+        assert!(!board.is_in_check(Color::Black));
+        assert_eq!(board.generate_moves().len(), 13);
+        board.side = Color::White;
+        assert_eq!(board.generate_moves().len(), 2 + 10);
+    }
+
+    #[test]
+    fn test_make_unmake_psqt_accumulators() {
+        crate::init();
+        // Kiwipete: castling, captures, promotions-in-reach and ep all exercised
+        let mut board = Board::new();
+        board
+            .set_from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+            .unwrap();
+        assert!(board.verify_psqt(), "Accumulators wrong after FEN setup");
+        let original = [
+            board.psqt_score(Color::White),
+            board.psqt_score(Color::Black),
+        ];
+        for mv in board.generate_moves() {
+            let undo = board.make_move(mv);
+            assert!(
+                board.verify_psqt(),
+                "Accumulators diverged after make_move {}",
+                move_to_uci(mv)
+            );
+            board.unmake_move(undo);
+            assert_eq!(
+                [
+                    board.psqt_score(Color::White),
+                    board.psqt_score(Color::Black)
+                ],
+                original,
+                "Accumulators not restored after unmake_move {}",
+                move_to_uci(mv)
+            );
+        }
+    }
+
+    #[test]
+    fn test_push_pop_moves_use_internal_undo_stack() {
+        crate::init();
+        let mut board = Board::new();
+        board.set_from_fen(START_FEN).unwrap();
+        let start = board.zobrist;
+        assert_eq!(board.ply(), 0);
+        assert_eq!(board.pop_move(), None);
+
+        let mut played = Vec::new();
+        for uci in ["e2e4", "e7e5", "g1f3", "b8c6"] {
+            let mv = parse_uci_move(&mut board, uci).unwrap();
+            board.push_move(mv);
+            played.push(mv);
+        }
+        assert_eq!(board.ply(), 4);
+        assert_eq!(board.history_len(), 4);
+
+        while let Some(mv) = board.pop_move() {
+            assert_eq!(Some(mv), played.pop());
+        }
+        assert_eq!(board.zobrist, start);
+        assert_eq!(board.history_len(), 0);
+
+        // A new position starts with an empty stack
+        let d4 = parse_uci_move(&mut board, "d2d4").unwrap();
+        board.push_move(d4);
+        board.set_from_fen(START_FEN).unwrap();
+        assert_eq!(board.ply(), 0);
+
+        // Pruning the history empties the stack too: its Undos would restore
+        // the unpruned length
+        let nf3 = parse_uci_move(&mut board, "g1f3").unwrap();
+        board.push_move(nf3);
+        board.prune_history();
+        assert_eq!(board.ply(), 0);
+        assert_eq!(board.pop_move(), None);
+    }
+
+    #[test]
+    fn test_incremental_zobrist_matches_full_recompute() {
+        // Walk every line to depth 3 (castling, en passant, promotions,
+        // captures of castling rooks) and compare with the full recompute
+        fn walk(board: &mut Board, depth: u8) {
+            assert!(board.verify_zobrist());
+            if depth == 0 {
+                return;
+            }
+            for mv in board.generate_moves() {
+                let before = board.zobrist;
+                let undo = board.make_move(mv);
+                walk(board, depth - 1);
+                board.unmake_move(undo);
+                assert_eq!(board.zobrist, before);
+            }
+        }
+
+        crate::init();
+        for fen in [
+            START_FEN,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        ] {
+            let mut board = Board::new();
+            board.set_from_fen(fen).unwrap();
+            walk(&mut board, 3);
+        }
+    }
+
+    #[test]
+    fn test_specialized_generators_match_generate_moves() {
+        // Evasions are exactly the legal moves in check (same order), captures
+        // exactly the legal captures and promotions, at every node to depth 3
+        fn walk(board: &mut Board, depth: u8) {
+            let legal = board.generate_moves();
+            let evasions = board.generate_evasions();
+            if board.is_in_check(board.side) {
+                assert_eq!(evasions, legal);
+            } else {
+                assert!(evasions.is_empty());
+            }
+            let mut captures = Vec::new();
+            board.generate_captures(&mut captures);
+            let mut expected: Vec<Move> = legal
+                .iter()
+                .copied()
+                .filter(|&mv| {
+                    mv.captured().is_some() || mv.has_flag(FLAG_EN_PASSANT) || mv.is_promotion()
+                })
+                .collect();
+            captures.sort_unstable();
+            expected.sort_unstable();
+            assert_eq!(captures, expected);
+
+            if depth > 0 {
+                for mv in legal {
+                    board.push_move(mv);
+                    walk(board, depth - 1);
+                    board.pop_move();
+                }
+            }
+        }
+
+        crate::init();
+        for fen in [
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            // The checking pawn can be taken en passant
+            "8/8/8/2k5/3Pp3/8/8/4K3 b - d3 0 1",
+            // Double check: only the king may move
+            "4k3/8/8/8/1b6/8/8/R3K2r w Q - 0 1",
+        ] {
+            let mut board = Board::new();
+            board.set_from_fen(fen).unwrap();
+            walk(&mut board, 3);
+        }
+    }
+
+    #[test]
+    fn test_attackers_to_by_color() {
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1")
+            .unwrap();
+        for sq in 0..64 {
+            let all = board.attackers_to(sq, board.occ);
+            let white = board.attackers_to_by(sq, board.occ, Color::White);
+            let black = board.attackers_to_by(sq, board.occ, Color::Black);
+            assert_eq!(white, all & board.white_occ);
+            assert_eq!(black, all & board.black_occ);
+            assert_eq!(board.is_square_attacked(sq, Color::White), white != 0);
+            assert_eq!(board.is_square_attacked(sq, Color::Black), black != 0);
+        }
+
+        // d5 is hit by the e6 pawn and the b6 and f6 knights
+        let d5 = 35;
+        let e6 = 44;
+        let b6 = 41;
+        let f6 = 45;
+        assert_eq!(
+            board.attackers_to_by(d5, board.occ, Color::Black),
+            (1u64 << e6) | (1u64 << b6) | (1u64 << f6)
+        );
+    }
+
     #[test]
     fn test_zobrist_invariant_after_null_move() {
         // Test that zobrist hash is correctly restored after null-move + unmake
@@ -1795,23 +2989,69 @@ mod tests {
     }
 }
 
+/// Why a FEN was rejected by [`Board::set_from_fen`], or its position by
+/// [`Board::validate_kings`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    /// One of the four required fields is missing: `"pieces"`, `"side"`,
+    /// `"castling"` or `"en passant"`
+    MissingField(&'static str),
+    InvalidPiece(char),
+    InvalidSide(String),
+    InvalidCastling(char),
+    InvalidEnPassant(String),
+    InvalidHalfmove(String),
+    InvalidFullmove(String),
+    /// A side has no king (only accepted in study mode)
+    MissingKing(Color),
+    TooManyKings(Color),
+}
+
+impl std::fmt::Display for FenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FenError::MissingField(field) => write!(f, "missing {} field", field),
+            FenError::InvalidPiece(ch) => write!(f, "invalid piece '{}'", ch),
+            FenError::InvalidSide(side) => write!(f, "invalid side to move '{}'", side),
+            FenError::InvalidCastling(ch) => write!(f, "invalid castling right '{}'", ch),
+            FenError::InvalidEnPassant(ep) => write!(f, "invalid en passant square '{}'", ep),
+            FenError::InvalidHalfmove(n) => write!(f, "invalid halfmove clock '{}'", n),
+            FenError::InvalidFullmove(n) => write!(f, "invalid fullmove number '{}'", n),
+            FenError::MissingKing(color) => write!(f, "missing king for {:?}", color),
+            FenError::TooManyKings(color) => write!(f, "too many kings for {:?}", color),
+        }
+    }
+}
+
+impl std::error::Error for FenError {}
+
 // FEN parsing/setter su Board
 impl Board {
-    pub fn set_from_fen(&mut self, fen: &str) -> Result<(), &'static str> {
+    /// Set up the position of `fen`.
+    ///
+    /// The halfmove clock and fullmove number may be left out, as many tools
+    /// do (they default to 0 and 1); fields may be separated by any amount of
+    /// whitespace.
+    pub fn set_from_fen(&mut self, fen: &str) -> Result<(), FenError> {
         let mut parts = fen.split_whitespace();
-        let piece_part = parts.next().ok_or("missing pieces")?;
-        let side_part = parts.next().ok_or("missing side")?;
-        let castle_part = parts.next().ok_or("missing castling")?;
-        let ep_part = parts.next().ok_or("missing en-passant")?;
-        let halfmove_part = parts.next().ok_or("missing halfmove")?;
-        let fullmove_part = parts.next().ok_or("missing fullmove")?;
+        let piece_part = parts.next().ok_or(FenError::MissingField("pieces"))?;
+        let side_part = parts.next().ok_or(FenError::MissingField("side"))?;
+        let castle_part = parts.next().ok_or(FenError::MissingField("castling"))?;
+        let ep_part = parts.next().ok_or(FenError::MissingField("en passant"))?;
+        let halfmove_part = parts.next().unwrap_or("0");
+        let fullmove_part = parts.next().unwrap_or("1");
 
         // Reset board
         self.piece_bb = [0; 12];
+        self.psqt_mg = [0; 2];
+        self.psqt_eg = [0; 2];
+        self.phase_material = 0;
         self.white_occ = 0;
         self.black_occ = 0;
         self.occ = 0;
+        self.mailbox = [None; 64];
         self.position_history.clear();
+        self.undo_stack.clear();
 
         // Parse pieces: rank8 .. rank1
         let mut rank = 7;
@@ -1834,7 +3074,7 @@ impl Board {
                         'r' => (PieceKind::Rook, Color::Black),
                         'q' => (PieceKind::Queen, Color::Black),
                         'k' => (PieceKind::King, Color::Black),
-                        _ => return Err("invalid piece char"),
+                        _ => return Err(FenError::InvalidPiece(ch)),
                     };
                     let sq = rank * 8 + file;
                     self.set_piece(sq, kind, color);
@@ -1848,13 +3088,11 @@ impl Board {
             }
         }
 
-        self.refresh_occupancy();
-
         // Side to move
         self.side = match side_part {
             "w" => Color::White,
             "b" => Color::Black,
-            _ => return Err("invalid side char"),
+            _ => return Err(FenError::InvalidSide(side_part.to_string())),
         };
 
         // Castling rights: KQkq mapping bits wk wq bk bq
@@ -1866,33 +3104,45 @@ impl Board {
                 'k' => self.castling |= 0b0010u8,
                 'q' => self.castling |= 0b0001u8,
                 '-' => {}
-                _ => return Err("invalid castle char"),
+                _ => return Err(FenError::InvalidCastling(ch)),
             }
         }
 
         // En-passant
+        let invalid_ep = || FenError::InvalidEnPassant(ep_part.to_string());
         self.ep = match ep_part {
             "-" => None,
             s => {
                 if s.len() != 2 {
-                    return Err("invalid ep string");
+                    return Err(invalid_ep());
                 }
                 let file = s.chars().next().unwrap();
                 let rank = s.chars().nth(1).unwrap();
                 let f_idx = match file {
                     'a'..='h' => (file as u8 - b'a') as usize,
-                    _ => return Err("invalid ep file"),
+                    _ => return Err(invalid_ep()),
                 };
                 let r_idx = match rank {
                     '3' | '6' => (rank as u8 - b'1') as usize,
-                    _ => return Err("invalid ep rank"),
+                    _ => return Err(invalid_ep()),
                 };
                 Some((r_idx * 8 + f_idx) as u8)
             }
         };
 
-        self.halfmove = halfmove_part.parse().map_err(|_| "invalid halfmove")?;
-        self.fullmove = fullmove_part.parse().map_err(|_| "invalid fullmove")?;
+        // Same convention as make_move: no ep square without a legal ep capture
+        if let Some(ep_sq) = self.ep {
+            if !self.ep_capture_is_legal(ep_sq as usize, self.side) {
+                self.ep = None;
+            }
+        }
+
+        self.halfmove = halfmove_part
+            .parse()
+            .map_err(|_| FenError::InvalidHalfmove(halfmove_part.to_string()))?;
+        self.fullmove = fullmove_part
+            .parse()
+            .map_err(|_| FenError::InvalidFullmove(fullmove_part.to_string()))?;
 
         // Zobrist placeholder per ora
         self.zobrist = self.recalc_zobrist();
@@ -1900,6 +3150,75 @@ impl Board {
         Ok(())
     }
 
+    /// FEN of the position (inverse of [`Board::set_from_fen`])
+    pub fn to_fen(&self) -> String {
+        let mut fen = String::new();
+        for rank in (0..8).rev() {
+            let mut empty = 0;
+            for file in 0..8 {
+                match self.piece_on(rank * 8 + file) {
+                    Some((kind, color)) => {
+                        if empty > 0 {
+                            fen.push_str(&empty.to_string());
+                            empty = 0;
+                        }
+                        fen.push(piece_char(kind, color));
+                    }
+                    None => empty += 1,
+                }
+            }
+            if empty > 0 {
+                fen.push_str(&empty.to_string());
+            }
+            if rank > 0 {
+                fen.push('/');
+            }
+        }
+        let side = match self.side {
+            Color::White => 'w',
+            Color::Black => 'b',
+        };
+        let castling: String = [(0b1000, 'K'), (0b0100, 'Q'), (0b0010, 'k'), (0b0001, 'q')]
+            .iter()
+            .filter(|(bit, _)| self.castling & bit != 0)
+            .map(|&(_, ch)| ch)
+            .collect();
+        format!(
+            "{} {} {} {} {} {}",
+            fen,
+            side,
+            if castling.is_empty() { "-" } else { &castling },
+            self.ep
+                .map_or("-".to_string(), |sq| square_to_uci(sq as usize)),
+            self.halfmove,
+            self.fullmove
+        )
+    }
+
+    /// Color-flipped position: pieces mirrored top to bottom with colors
+    /// swapped, side to move, castling rights and en-passant square likewise.
+    /// The game history is not carried over.
+    pub fn mirror(&self) -> Board {
+        let flip = |color| match color {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        };
+        let mut mirrored = Board::new();
+        for sq in 0..64 {
+            if let Some((kind, color)) = self.piece_on(sq) {
+                mirrored.set_piece(sq ^ 56, kind, flip(color));
+            }
+        }
+        mirrored.side = flip(self.side);
+        // KQkq -> kqKQ
+        mirrored.castling = (self.castling >> 2) | ((self.castling & 0b0011) << 2);
+        mirrored.ep = self.ep.map(|sq| sq ^ 56);
+        mirrored.halfmove = self.halfmove;
+        mirrored.fullmove = self.fullmove;
+        mirrored.zobrist = mirrored.recalc_zobrist();
+        mirrored
+    }
+
     /// Make a null move (skip turn) - only toggles side and updates Zobrist
     /// Used for null-move pruning in search
     pub fn make_null_move(&mut self) -> Undo {
@@ -1958,6 +3277,124 @@ impl Board {
     }
 }
 
+/// Dimensione in byte di una posizione compressa (vedi [`Board::encode`]).
+pub const PACKED_BOARD_SIZE: usize = 32;
+
+/// Posizione compressa a dimensione fissa, adatta a book e formati su disco.
+pub type PackedBoard = [u8; PACKED_BOARD_SIZE];
+
+// Layout di PackedBoard:
+// Bytes 0-7:   occupancy (u64 little-endian)
+// Bytes 8-23:  un nibble per pezzo, in ordine di casa crescente (max 32 pezzi);
+//              bits 0-2 = PieceKind, bit 3 = colore (1 = nero)
+// Byte 24:     bit 0 = side to move (1 = nero), bits 1-4 = diritti di arrocco
+// Byte 25:     casa en-passant (0xFF = nessuna)
+// Bytes 26-27: halfmove clock (u16 little-endian)
+// Bytes 28-29: fullmove number (u16 little-endian)
+// Bytes 30-31: riservati (zero)
+impl Board {
+    /// Comprime la posizione in [`PACKED_BOARD_SIZE`] byte.
+    ///
+    /// La history delle posizioni (ripetizioni) non viene salvata. Errore se
+    /// ci sono più di 32 pezzi (possibile solo da FEN): non entrano nel
+    /// formato.
+    pub fn encode(&self) -> Result<PackedBoard, &'static str> {
+        if self.occ.count_ones() > 32 {
+            return Err("too many pieces");
+        }
+        let mut out = [0u8; PACKED_BOARD_SIZE];
+        out[0..8].copy_from_slice(&self.occ.to_le_bytes());
+
+        let mut occ = self.occ;
+        let mut idx = 0;
+        while occ != 0 {
+            let sq = occ.trailing_zeros() as usize;
+            occ &= occ - 1;
+            let (kind, color) = self.piece_on(sq).expect("occupancy out of sync");
+            let nibble = (kind as u8) | ((color as u8) << 3);
+            out[8 + idx / 2] |= nibble << ((idx % 2) * 4);
+            idx += 1;
+        }
+
+        out[24] = (self.side as u8) | ((self.castling & 0x0F) << 1);
+        out[25] = self.ep.unwrap_or(0xFF);
+        out[26..28].copy_from_slice(&self.halfmove.to_le_bytes());
+        out[28..30].copy_from_slice(&self.fullmove.to_le_bytes());
+        Ok(out)
+    }
+
+    /// Ricostruisce una posizione da [`Board::encode`].
+    ///
+    /// Zobrist e accumulatori PSQT vengono ricalcolati; la history è vuota.
+    pub fn decode(bytes: &PackedBoard) -> Result<Board, &'static str> {
+        let mut occ_bytes = [0u8; 8];
+        occ_bytes.copy_from_slice(&bytes[0..8]);
+        let mut occ = u64::from_le_bytes(occ_bytes);
+        if occ.count_ones() > 32 {
+            return Err("too many pieces");
+        }
+
+        let mut board = Board::new();
+        let mut idx = 0;
+        while occ != 0 {
+            let sq = occ.trailing_zeros() as usize;
+            occ &= occ - 1;
+            let nibble = (bytes[8 + idx / 2] >> ((idx % 2) * 4)) & 0x0F;
+            let kind = match nibble & 0x07 {
+                0 => PieceKind::Pawn,
+                1 => PieceKind::Knight,
+                2 => PieceKind::Bishop,
+                3 => PieceKind::Rook,
+                4 => PieceKind::Queen,
+                5 => PieceKind::King,
+                _ => return Err("invalid piece code"),
+            };
+            let color = if nibble & 0x08 != 0 {
+                Color::Black
+            } else {
+                Color::White
+            };
+            board.set_piece(sq, kind, color);
+            idx += 1;
+        }
+
+        if bytes[24] & !0x1F != 0 {
+            return Err("invalid flags byte");
+        }
+        board.side = if bytes[24] & 1 != 0 {
+            Color::Black
+        } else {
+            Color::White
+        };
+        board.castling = (bytes[24] >> 1) & 0x0F;
+        board.ep = match bytes[25] {
+            0xFF => None,
+            sq if (16..24).contains(&sq) || (40..48).contains(&sq) => Some(sq),
+            _ => return Err("invalid ep square"),
+        };
+        board.halfmove = u16::from_le_bytes([bytes[26], bytes[27]]);
+        board.fullmove = u16::from_le_bytes([bytes[28], bytes[29]]);
+        board.zobrist = board.recalc_zobrist();
+        Ok(board)
+    }
+}
+
+/// FEN letter of a piece (uppercase for White)
+pub fn piece_char(kind: PieceKind, color: Color) -> char {
+    let ch = match kind {
+        PieceKind::Pawn => 'p',
+        PieceKind::Knight => 'n',
+        PieceKind::Bishop => 'b',
+        PieceKind::Rook => 'r',
+        PieceKind::Queen => 'q',
+        PieceKind::King => 'k',
+    };
+    match color {
+        Color::White => ch.to_ascii_uppercase(),
+        Color::Black => ch,
+    }
+}
+
 // Simple display (fen)
 impl std::fmt::Display for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -1965,21 +3402,7 @@ impl std::fmt::Display for Board {
             for file in 0..8 {
                 let sq = rank * 8 + file;
                 if let Some((p, c)) = self.piece_on(sq) {
-                    let ch = match (c, p) {
-                        (Color::White, PieceKind::Pawn) => 'P',
-                        (Color::White, PieceKind::Knight) => 'N',
-                        (Color::White, PieceKind::Bishop) => 'B',
-                        (Color::White, PieceKind::Rook) => 'R',
-                        (Color::White, PieceKind::Queen) => 'Q',
-                        (Color::White, PieceKind::King) => 'K',
-                        (Color::Black, PieceKind::Pawn) => 'p',
-                        (Color::Black, PieceKind::Knight) => 'n',
-                        (Color::Black, PieceKind::Bishop) => 'b',
-                        (Color::Black, PieceKind::Rook) => 'r',
-                        (Color::Black, PieceKind::Queen) => 'q',
-                        (Color::Black, PieceKind::King) => 'k',
-                    };
-                    write!(f, "{} ", ch)?;
+                    write!(f, "{} ", piece_char(p, c))?;
                 } else {
                     write!(f, ". ")?;
                 }
@@ -2024,6 +3447,22 @@ mod draw_tests {
         assert!(board.is_insufficient_material());
     }
 
+    #[test]
+    fn test_insufficient_material_bishops_on_one_color() {
+        let mut board = Board::new();
+        // Two white bishops on dark squares (c1, e3) vs a black one on g1
+        board
+            .set_from_fen("8/8/8/8/8/4B3/8/K1B3bk w - - 0 1")
+            .unwrap();
+        assert!(board.is_insufficient_material());
+
+        // A light-squared bishop (d1) can help mate
+        board
+            .set_from_fen("8/8/8/8/8/8/8/K1BB3k w - - 0 1")
+            .unwrap();
+        assert!(!board.is_insufficient_material());
+    }
+
     #[test]
     fn test_insufficient_material_king_knight_vs_king() {
         let mut board = Board::new();
@@ -2123,12 +3562,63 @@ mod draw_tests {
             .set_from_fen("4k3/8/8/8/3p4/8/4P3/4K3 w - - 0 1")
             .unwrap();
         let mv = parse_uci_move(&mut board, "e2e4").unwrap();
-        let from = move_from_sq(mv);
-        let to = move_to_sq(mv);
+        let from = mv.from();
+        let to = mv.to();
         board.make_move(mv);
         assert_eq!(board.ep, Some(((from + to) / 2) as u8));
     }
 
+    #[test]
+    fn test_ep_not_set_when_capture_is_pinned() {
+        crate::init();
+        // Rank pin: exd6 would leave a5 open to the rook on h5
+        let mut board = Board::new();
+        board
+            .set_from_fen("7k/3p4/8/K3P2r/8/8/8/8 b - - 0 1")
+            .unwrap();
+        let mv = parse_uci_move(&mut board, "d7d5").unwrap();
+        board.make_move(mv);
+        assert!(board.ep.is_none());
+        assert!(!board
+            .generate_moves()
+            .iter()
+            .any(|mv| mv.has_flag(FLAG_EN_PASSANT)));
+
+        // Diagonal pin: e5 may only capture along the b2-h8 diagonal,
+        // so exf6 is legal and exd6 is not
+        for (push, ep) in [("d7d5", None), ("f7f5", Some(45))] {
+            board
+                .set_from_fen("k6b/3p1p2/8/4P3/8/8/1K6/8 b - - 0 1")
+                .unwrap();
+            let mv = parse_uci_move(&mut board, push).unwrap();
+            board.make_move(mv);
+            assert_eq!(board.ep, ep, "after {}", push);
+            assert_eq!(board.zobrist, board.recalc_zobrist());
+        }
+    }
+
+    #[test]
+    fn test_fen_ep_square_kept_only_if_capturable() {
+        crate::init();
+        // 1.e4 as GUIs write it: e3 set, but no black pawn can take
+        let mut board = Board::new();
+        board
+            .set_from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1")
+            .unwrap();
+        assert!(board.ep.is_none());
+        let mut played = Board::new();
+        played.set_from_fen(START_FEN).unwrap();
+        let mv = parse_uci_move(&mut played, "e2e4").unwrap();
+        played.make_move(mv);
+        assert_eq!(board.zobrist, played.zobrist);
+
+        // Capturable: kept
+        board
+            .set_from_fen("rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3")
+            .unwrap();
+        assert_eq!(board.ep, Some(45));
+    }
+
     #[test]
     fn test_stalemate_detection() {
         let mut board = Board::new();
@@ -2151,6 +3641,24 @@ mod draw_tests {
         assert!(!board.is_stalemate());
     }
 
+    #[test]
+    fn test_king_has_safe_step() {
+        crate::init();
+        let mut board = Board::new();
+        let cases = [
+            ("k7/8/8/8/8/8/8/K7 b - - 0 1", true),
+            // Stallo: ogni casa libera è attaccata dalla donna
+            ("k7/8/1Q6/8/8/8/8/K7 b - - 0 1", false),
+            // Case occupate dai propri pezzi (il cavallo può ancora muovere)
+            ("kn6/pp6/8/8/8/8/8/K7 b - - 0 1", false),
+            ("kn6/p7/8/8/8/8/8/K7 b - - 0 1", true),
+        ];
+        for (fen, expected) in cases {
+            board.set_from_fen(fen).unwrap();
+            assert_eq!(board.king_has_safe_step(board.side), expected, "{}", fen);
+        }
+    }
+
     #[test]
     fn test_checkmate_detection() {
         let mut board = Board::new();
@@ -2173,6 +3681,85 @@ mod draw_tests {
         assert!(!board.is_checkmate());
     }
 
+    #[test]
+    fn test_game_result() {
+        crate::init();
+        let mut board = Board::new();
+        board.set_from_fen(START_FEN).unwrap();
+        assert_eq!(board.game_result(), None);
+
+        // Fool's mate
+        board
+            .set_from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 0 1")
+            .unwrap();
+        let result = board.game_result().unwrap();
+        assert_eq!(
+            result,
+            GameResult::Checkmate {
+                winner: Color::Black
+            }
+        );
+        assert!(result.is_terminal());
+        assert_eq!(result.to_string(), "checkmate 0-1");
+
+        // Black king on h8 has no move and is not in check
+        board
+            .set_from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1")
+            .unwrap();
+        assert!(board.is_stalemate());
+        assert_eq!(board.game_result(), Some(GameResult::Stalemate));
+
+        // Mate on the 100th half-move still wins
+        board
+            .set_from_fen("rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 100 60")
+            .unwrap();
+        assert!(board.game_result().unwrap().is_terminal());
+
+        board.set_from_fen("8/8/8/8/8/8/8/K6k w - - 0 1").unwrap();
+        let result = board.game_result().unwrap();
+        assert_eq!(result, GameResult::InsufficientMaterial);
+        assert!(!result.is_terminal());
+    }
+
+    #[test]
+    fn test_repetition_count() {
+        let mut board = Board::new();
+        board.set_from_fen(START_FEN).unwrap();
+        assert_eq!(board.repetition_count(), 0);
+
+        for uci in ["g1f3", "g8f6", "f3g1", "f6g8"] {
+            let mv = parse_uci_move(&mut board, uci).unwrap();
+            board.make_move(mv);
+        }
+        assert_eq!(board.repetition_count(), 1);
+
+        // An irreversible pawn move hides the older occurrences
+        let mv = parse_uci_move(&mut board, "e2e4").unwrap();
+        board.make_move(mv);
+        assert_eq!(board.repetition_count(), 0);
+    }
+
+    #[test]
+    fn test_prune_history() {
+        let mut board = Board::new();
+        board.set_from_fen(START_FEN).unwrap();
+        for uci in ["e2e4", "e7e5", "g1f3", "g8f6", "f3g1", "f6g8", "g1f3"] {
+            let mv = parse_uci_move(&mut board, uci).unwrap();
+            board.make_move(mv);
+        }
+        assert_eq!(board.history_len(), 7);
+
+        // Only the plies after the last pawn move are kept
+        board.prune_history();
+        assert_eq!(board.history_len(), board.halfmove as usize);
+        assert_eq!(board.history_len(), 5);
+
+        // Repetitions are still detected on the pruned history
+        let mv = parse_uci_move(&mut board, "g8f6").unwrap();
+        board.make_move(mv);
+        assert_eq!(board.repetition_count(), 1);
+    }
+
     #[test]
     fn test_threefold_repetition() {
         crate::zobrist::init_zobrist();
@@ -2196,8 +3783,8 @@ mod draw_tests {
             let ng1_f3 = white_moves
                 .iter()
                 .find(|&&mv| {
-                    let from = move_from_sq(mv);
-                    let to = move_to_sq(mv);
+                    let from = mv.from();
+                    let to = mv.to();
                     let from_str =
                         format!("{}{}", ((from % 8) as u8 + b'a') as char, (from / 8) + 1);
                     let to_str = format!("{}{}", ((to % 8) as u8 + b'a') as char, (to / 8) + 1);
@@ -2211,8 +3798,8 @@ mod draw_tests {
             let ng8_f6 = black_moves
                 .iter()
                 .find(|&&mv| {
-                    let from = move_from_sq(mv);
-                    let to = move_to_sq(mv);
+                    let from = mv.from();
+                    let to = mv.to();
                     let from_str =
                         format!("{}{}", ((from % 8) as u8 + b'a') as char, (from / 8) + 1);
                     let to_str = format!("{}{}", ((to % 8) as u8 + b'a') as char, (to / 8) + 1);
@@ -2226,8 +3813,8 @@ mod draw_tests {
             let nf3_g1 = white_moves2
                 .iter()
                 .find(|&&mv| {
-                    let from = move_from_sq(mv);
-                    let to = move_to_sq(mv);
+                    let from = mv.from();
+                    let to = mv.to();
                     let from_str =
                         format!("{}{}", ((from % 8) as u8 + b'a') as char, (from / 8) + 1);
                     let to_str = format!("{}{}", ((to % 8) as u8 + b'a') as char, (to / 8) + 1);
@@ -2241,8 +3828,8 @@ mod draw_tests {
             let nf6_g8 = black_moves2
                 .iter()
                 .find(|&&mv| {
-                    let from = move_from_sq(mv);
-                    let to = move_to_sq(mv);
+                    let from = mv.from();
+                    let to = mv.to();
                     let from_str =
                         format!("{}{}", ((from % 8) as u8 + b'a') as char, (from / 8) + 1);
                     let to_str = format!("{}{}", ((to % 8) as u8 + b'a') as char, (to / 8) + 1);
@@ -0,0 +1,553 @@
+//! Endgame knowledge
+//!
+//! Specialized evaluators for material configurations the general evaluation
+//! gets wrong:
+//!
+//! - [`kpk`]: king and pawn against king, exact through a bitbase generated
+//!   by retrograde analysis on first use
+//! - [`won_ending`]: known wins (lone king against mating material, KQ vs KR,
+//!   ...) plus a mop-up term: how far the weak king has been driven to the
+//!   edge and how close the strong king is
+//! - [`kbnk`]: bishop and knight mate, driving the king to a corner of the
+//!   bishop's color
+//! - [`scale_factor`]: how much of the evaluation survives in drawish
+//!   endings (opposite-colored bishops, rook endings with all pawns on one
+//!   wing)
+//!
+//! Scores are from the strong side's point of view; [`crate::eval`] turns
+//! them into side-to-move scores.
+
+use crate::board::{Board, Color, PieceKind};
+use std::sync::OnceLock;
+
+/// Score of a won endgame: above any material balance, below mate scores
+pub const KNOWN_WIN: i16 = 10000;
+
+/// Scale factor that keeps the evaluation as it is ([`scale_factor`])
+pub const SCALE_NORMAL: i32 = 64;
+
+/// Opposite-colored bishops and pawns only
+const SCALE_OPPOSITE_BISHOPS: i32 = 24;
+/// Opposite-colored bishops and at most two pawns for the strong side
+const SCALE_OPPOSITE_BISHOPS_FEW_PAWNS: i32 = 12;
+/// Opposite-colored bishops with other pieces on the board
+const SCALE_OPPOSITE_BISHOPS_WITH_PIECES: i32 = 46;
+/// One rook each, at most one extra pawn, every pawn on the weak king's wing
+const SCALE_ROOK_ENDING_ONE_WING: i32 = 40;
+/// No pawns for the strong side and at most a bishop more: with a single
+/// minor piece it cannot mate at all; otherwise it rarely can, against a
+/// minor piece (R vs B) or against more (R+B vs R)
+const SCALE_NO_PAWNS_MINOR_UP: i32 = 0;
+const SCALE_NO_PAWNS_VS_MINOR: i32 = 4;
+const SCALE_NO_PAWNS_VS_PIECES: i32 = 14;
+
+// ============================================================================
+// KPK BITBASE
+// ============================================================================
+
+/// Positions of the bitbase: side to move x weak king x strong king x pawn
+/// (files a-d, ranks 2-7; other pawns are mirrored onto those files)
+const KPK_SIZE: usize = 2 * 64 * 64 * 24;
+
+const INVALID: u8 = 0;
+const UNKNOWN: u8 = 1;
+const DRAW: u8 = 2;
+const WIN: u8 = 4;
+
+fn kpk_index(strong_to_move: bool, weak_king: usize, strong_king: usize, pawn: usize) -> usize {
+    let pawn_idx = (pawn / 8 - 1) * 4 + pawn % 8;
+    strong_king | weak_king << 6 | (strong_to_move as usize) << 12 | pawn_idx << 13
+}
+
+fn distance(a: usize, b: usize) -> usize {
+    (a % 8).abs_diff(b % 8).max((a / 8).abs_diff(b / 8))
+}
+
+/// King moves from each square
+const KING_MOVES: [u64; 64] = {
+    let mut table = [0u64; 64];
+    let mut sq = 0;
+    while sq < 64 {
+        let (file, rank) = ((sq % 8) as i32, (sq / 8) as i32);
+        let mut to = 0;
+        while to < 64 {
+            let (df, dr) = ((to % 8) as i32 - file, (to / 8) as i32 - rank);
+            if to != sq && df.abs() <= 1 && dr.abs() <= 1 {
+                table[sq] |= 1u64 << to;
+            }
+            to += 1;
+        }
+        sq += 1;
+    }
+    table
+};
+
+fn king_moves(sq: usize) -> u64 {
+    KING_MOVES[sq]
+}
+
+/// Squares attacked by a pawn of the strong side (moving up the board)
+fn pawn_attacks(sq: usize) -> u64 {
+    let file = sq % 8;
+    let mut bb = 0;
+    if sq < 56 {
+        if file > 0 {
+            bb |= 1u64 << (sq + 7);
+        }
+        if file < 7 {
+            bb |= 1u64 << (sq + 9);
+        }
+    }
+    bb
+}
+
+fn squares(mut bb: u64) -> impl Iterator<Item = usize> {
+    std::iter::from_fn(move || {
+        (bb != 0).then(|| {
+            let sq = bb.trailing_zeros() as usize;
+            bb &= bb - 1;
+            sq
+        })
+    })
+}
+
+/// Classification that needs no search: illegal positions, immediate
+/// promotions, stalemates and captures of the pawn
+fn kpk_initial(strong_to_move: bool, weak_king: usize, strong_king: usize, pawn: usize) -> u8 {
+    if distance(strong_king, weak_king) <= 1
+        || strong_king == pawn
+        || weak_king == pawn
+        || (strong_to_move && pawn_attacks(pawn) & 1u64 << weak_king != 0)
+    {
+        return INVALID;
+    }
+    let promotion = pawn + 8;
+    if strong_to_move
+        && pawn / 8 == 6
+        && strong_king != promotion
+        && (distance(weak_king, promotion) > 1 || distance(strong_king, promotion) == 1)
+    {
+        return WIN;
+    }
+    let weak_moves = king_moves(weak_king);
+    let guarded = king_moves(strong_king);
+    if !strong_to_move
+        && (weak_moves & !(guarded | pawn_attacks(pawn)) == 0
+            || weak_moves & !guarded & 1u64 << pawn != 0)
+    {
+        return DRAW;
+    }
+    UNKNOWN
+}
+
+/// One retrograde step: the best outcome for the side to move among the
+/// positions it can reach
+fn kpk_classify(
+    db: &[u8],
+    strong_to_move: bool,
+    weak_king: usize,
+    strong_king: usize,
+    pawn: usize,
+) -> u8 {
+    let (good, bad) = if strong_to_move {
+        (WIN, DRAW)
+    } else {
+        (DRAW, WIN)
+    };
+    let mut reachable = INVALID;
+    if strong_to_move {
+        for to in squares(king_moves(strong_king)) {
+            reachable |= db[kpk_index(false, weak_king, to, pawn)];
+        }
+        if pawn / 8 < 6 {
+            let push = pawn + 8;
+            reachable |= db[kpk_index(false, weak_king, strong_king, push)];
+            if pawn / 8 == 1 && push != strong_king && push != weak_king {
+                reachable |= db[kpk_index(false, weak_king, strong_king, push + 8)];
+            }
+        }
+    } else {
+        for to in squares(king_moves(weak_king)) {
+            reachable |= db[kpk_index(true, to, strong_king, pawn)];
+        }
+    }
+    if reachable & good != 0 {
+        good
+    } else if reachable & UNKNOWN != 0 {
+        UNKNOWN
+    } else {
+        bad
+    }
+}
+
+/// Won positions of the bitbase, one bit per index
+fn kpk_bitbase() -> &'static [u64] {
+    static BITBASE: OnceLock<Vec<u64>> = OnceLock::new();
+    BITBASE.get_or_init(|| {
+        let decode = |idx: usize| {
+            let pawn_idx = idx >> 13;
+            (
+                (idx >> 12) & 1 == 1,
+                (idx >> 6) & 63,
+                idx & 63,
+                (pawn_idx / 4 + 1) * 8 + pawn_idx % 4,
+            )
+        };
+        let mut db: Vec<u8> = (0..KPK_SIZE)
+            .map(|idx| {
+                let (stm, weak_king, strong_king, pawn) = decode(idx);
+                kpk_initial(stm, weak_king, strong_king, pawn)
+            })
+            .collect();
+        loop {
+            let mut changed = false;
+            for idx in 0..KPK_SIZE {
+                if db[idx] == UNKNOWN {
+                    let (stm, weak_king, strong_king, pawn) = decode(idx);
+                    let result = kpk_classify(&db, stm, weak_king, strong_king, pawn);
+                    if result != UNKNOWN {
+                        db[idx] = result;
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        // Positions still unknown cannot be forced to a win: draws
+        let mut bits = vec![0u64; KPK_SIZE / 64];
+        for (idx, &result) in db.iter().enumerate() {
+            if result == WIN {
+                bits[idx / 64] |= 1u64 << (idx % 64);
+            }
+        }
+        bits
+    })
+}
+
+/// Whether king and pawn win against the lone king. Squares are from the
+/// strong side's point of view (its pawn moves up the board).
+pub fn kpk_wins(strong_king: usize, pawn: usize, weak_king: usize, strong_to_move: bool) -> bool {
+    // Mirror pawns on files e-h onto files a-d
+    let mirror = if pawn % 8 > 3 { 7 } else { 0 };
+    let idx = kpk_index(
+        strong_to_move,
+        weak_king ^ mirror,
+        strong_king ^ mirror,
+        pawn ^ mirror,
+    );
+    kpk_bitbase()[idx / 64] & 1u64 << (idx % 64) != 0
+}
+
+/// Squares as seen by `strong`: flipped vertically for Black
+fn relative_square(sq: usize, strong: Color) -> usize {
+    match strong {
+        Color::White => sq,
+        Color::Black => sq ^ 56,
+    }
+}
+
+fn other(color: Color) -> Color {
+    match color {
+        Color::White => Color::Black,
+        Color::Black => Color::White,
+    }
+}
+
+/// KP vs K, `strong` having the pawn: a known win plus the pawn's progress
+/// (growing faster as it nears promotion), or 0 for a draw
+pub fn kpk(board: &Board, strong: Color) -> i16 {
+    let weak = other(strong);
+    let pawn = board.piece_bb(PieceKind::Pawn, strong).trailing_zeros() as usize;
+    let pawn = relative_square(pawn, strong);
+    let strong_king = relative_square(board.king_sq(strong), strong);
+    let weak_king = relative_square(board.king_sq(weak), strong);
+    if kpk_wins(strong_king, pawn, weak_king, board.side == strong) {
+        let rank = (pawn / 8) as i16;
+        KNOWN_WIN + 100 + rank * rank * 5
+    } else {
+        0
+    }
+}
+
+// ============================================================================
+// KNOWN WINS AND MOP-UP
+// ============================================================================
+
+/// Light squares (b1, a2, ...)
+const LIGHT_SQUARES: u64 = 0x55AA_55AA_55AA_55AA;
+
+/// Bonus for the weak king's square: 20 per step from the center toward an
+/// edge, up to 120 in the corners
+const fn push_to_edge(sq: usize) -> i16 {
+    let file = sq % 8;
+    let rank = sq / 8;
+    let file_edge = if file < 7 - file { file } else { 7 - file };
+    let rank_edge = if rank < 7 - rank { rank } else { 7 - rank };
+    20 * (6 - file_edge - rank_edge) as i16
+}
+
+/// Bonus for the distance between the kings (index = distance, 2-7), lower
+/// than a step of [`push_to_edge`] so the edge comes first
+const PUSH_CLOSE: [i16; 8] = [0, 70, 60, 50, 40, 30, 20, 10];
+
+/// Whether `color` has material that mates a lone king by force without
+/// pawns: a queen, a rook or bishops on both square colors
+pub fn has_mating_material(board: &Board, color: Color) -> bool {
+    let bishops = board.piece_bb(PieceKind::Bishop, color);
+    board.piece_bb(PieceKind::Queen, color) != 0
+        || board.piece_bb(PieceKind::Rook, color) != 0
+        || (bishops & LIGHT_SQUARES != 0 && bishops & !LIGHT_SQUARES != 0)
+}
+
+/// Mop-up term for `strong`: the weak king's distance from the center and
+/// the kings' proximity, so the search sees progress while it drives the
+/// king to the edge
+pub fn mop_up(board: &Board, strong: Color) -> i16 {
+    let weak_king = board.king_sq(other(strong));
+    push_to_edge(weak_king) + PUSH_CLOSE[distance(board.king_sq(strong), weak_king)]
+}
+
+/// Endgame won by `strong` (a lone king against [`has_mating_material`],
+/// KQ vs KR, ...): a known win plus the material balance and [`mop_up`]
+pub fn won_ending(board: &Board, strong: Color) -> i16 {
+    let material = crate::eval::material_value(board, strong)
+        - crate::eval::material_value(board, other(strong));
+    KNOWN_WIN + material + mop_up(board, strong)
+}
+
+/// Manhattan distance of `sq` from the nearer of two opposite corners: a1
+/// and h8 (the dark ones), or h1 and a8 (the light ones)
+fn corner_distance(sq: usize, light: bool) -> i16 {
+    let (file, rank) = ((sq % 8) as i16, (sq / 8) as i16);
+    let file = if light { 7 - file } else { file };
+    (file + rank).min(14 - file - rank)
+}
+
+/// KBN vs K: mate is only forced in a corner of the bishop's color, so the
+/// weak king is pushed toward the nearer of those two corners (20 per step)
+/// instead of toward any edge
+pub fn kbnk(board: &Board, strong: Color) -> i16 {
+    let light = board.piece_bb(PieceKind::Bishop, strong) & LIGHT_SQUARES != 0;
+    let weak_king = board.king_sq(other(strong));
+    KNOWN_WIN
+        + crate::eval::material_value(board, strong)
+        + 20 * (14 - corner_distance(weak_king, light))
+        + PUSH_CLOSE[distance(board.king_sq(strong), weak_king)]
+}
+
+// ============================================================================
+// SCALING
+// ============================================================================
+
+/// Share (out of [`SCALE_NORMAL`]) of the evaluation kept for `strong`, the
+/// side the evaluation favors, in endings that are hard to win: no pawns
+/// and at most a bishop more (R+B vs R, R vs B, ...), opposite-colored
+/// bishops, and rook endings where the strong side has at most one extra
+/// pawn and every pawn is on the weak king's wing
+pub fn scale_factor(board: &Board, strong: Color) -> i32 {
+    use crate::eval::{BISHOP_VALUE, KNIGHT_VALUE, QUEEN_VALUE, ROOK_VALUE};
+
+    let weak = other(strong);
+    let count = |kind, color| board.piece_bb(kind, color).count_ones();
+    let pieces = |color| {
+        [
+            PieceKind::Knight,
+            PieceKind::Bishop,
+            PieceKind::Rook,
+            PieceKind::Queen,
+        ]
+        .map(|kind| count(kind, color))
+    };
+    let (strong_pieces, weak_pieces) = (pieces(strong), pieces(weak));
+    let strong_pawns = count(PieceKind::Pawn, strong);
+
+    // Without pawns a small material edge does not win
+    let non_pawn = |p: [u32; 4]| {
+        p[0] as i16 * KNIGHT_VALUE
+            + p[1] as i16 * BISHOP_VALUE
+            + p[2] as i16 * ROOK_VALUE
+            + p[3] as i16 * QUEEN_VALUE
+    };
+    let (strong_material, weak_material) = (non_pawn(strong_pieces), non_pawn(weak_pieces));
+    if strong_pawns == 0 && strong_material - weak_material <= BISHOP_VALUE {
+        return if strong_material < ROOK_VALUE {
+            SCALE_NO_PAWNS_MINOR_UP
+        } else if weak_material <= BISHOP_VALUE {
+            SCALE_NO_PAWNS_VS_MINOR
+        } else {
+            SCALE_NO_PAWNS_VS_PIECES
+        };
+    }
+
+    // Opposite-colored bishops
+    if strong_pieces[1] == 1 && weak_pieces[1] == 1 {
+        let on_light = |color| board.piece_bb(PieceKind::Bishop, color) & LIGHT_SQUARES != 0;
+        if on_light(strong) != on_light(weak) {
+            let others = |p: [u32; 4]| p[0] + p[2] + p[3];
+            return if others(strong_pieces) != 0 || others(weak_pieces) != 0 {
+                SCALE_OPPOSITE_BISHOPS_WITH_PIECES
+            } else if strong_pawns <= 2 {
+                SCALE_OPPOSITE_BISHOPS_FEW_PAWNS
+            } else {
+                SCALE_OPPOSITE_BISHOPS
+            };
+        }
+    }
+
+    // Rook endings with every pawn on one wing
+    if strong_pieces == [0, 0, 1, 0] && weak_pieces == [0, 0, 1, 0] {
+        let all_pawns =
+            board.piece_bb(PieceKind::Pawn, strong) | board.piece_bb(PieceKind::Pawn, weak);
+        const QUEEN_SIDE: u64 = 0x0F0F_0F0F_0F0F_0F0F;
+        let weak_king_wing = if board.king_sq(weak) % 8 < 4 {
+            QUEEN_SIDE
+        } else {
+            !QUEEN_SIDE
+        };
+        if strong_pawns <= count(PieceKind::Pawn, weak) + 1 && all_pawns & !weak_king_wing == 0 {
+            return SCALE_ROOK_ENDING_ONE_WING;
+        }
+    }
+
+    SCALE_NORMAL
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(fen: &str) -> Board {
+        let mut board = Board::new();
+        board.set_from_fen(fen).unwrap();
+        board
+    }
+
+    /// Square index from algebraic notation ("e4")
+    fn sq(name: &str) -> usize {
+        let name = name.as_bytes();
+        (name[1] - b'1') as usize * 8 + (name[0] - b'a') as usize
+    }
+
+    #[test]
+    fn test_kpk_bitbase_classics() {
+        // Strong king on the sixth rank in front of the pawn wins either way
+        assert!(kpk_wins(sq("d6"), sq("d5"), sq("d8"), true));
+        assert!(kpk_wins(sq("d6"), sq("d5"), sq("d8"), false));
+        // King in front of the pawn: the opposition decides
+        assert!(!kpk_wins(sq("e5"), sq("e4"), sq("e7"), true));
+        assert!(kpk_wins(sq("e5"), sq("e4"), sq("e7"), false));
+        // Rook pawn with the defender in the corner
+        assert!(!kpk_wins(sq("e1"), sq("a5"), sq("a8"), true));
+        assert!(!kpk_wins(sq("c6"), sq("a6"), sq("a8"), true));
+        // Outside the square of the pawn
+        assert!(kpk_wins(sq("h1"), sq("b5"), sq("h6"), true));
+        assert!(!kpk_wins(sq("h1"), sq("b5"), sq("e6"), false));
+        // Files e-h are mirrored onto files a-d
+        assert!(!kpk_wins(sq("d5"), sq("d4"), sq("d7"), true));
+        assert!(kpk_wins(sq("d5"), sq("d4"), sq("d7"), false));
+    }
+
+    #[test]
+    fn test_kpk_from_board_for_both_colors() {
+        let won = board("8/4k3/8/4K3/4P3/8/8/8 b - - 0 1");
+        assert!(kpk(&won, Color::White) > KNOWN_WIN);
+        let drawn = board("8/4k3/8/4K3/4P3/8/8/8 w - - 0 1");
+        assert_eq!(kpk(&drawn, Color::White), 0);
+
+        // Same positions with the colors swapped
+        let won = board("8/8/8/4p3/4k3/8/4K3/8 w - - 0 1");
+        assert!(kpk(&won, Color::Black) > KNOWN_WIN);
+        let drawn = board("8/8/8/4p3/4k3/8/4K3/8 b - - 0 1");
+        assert_eq!(kpk(&drawn, Color::Black), 0);
+    }
+
+    #[test]
+    fn test_won_ending_rewards_driving_the_king_to_the_edge() {
+        let center = board("8/8/8/4k3/8/8/8/4K2R w - - 0 1");
+        let edge = board("4k3/8/8/8/8/8/8/4K2R w - - 0 1");
+        let corner = board("7k/8/5K2/8/8/8/8/7R w - - 0 1");
+        assert!(won_ending(&center, Color::White) > KNOWN_WIN);
+        assert!(won_ending(&edge, Color::White) > won_ending(&center, Color::White));
+        assert!(won_ending(&corner, Color::White) > won_ending(&edge, Color::White));
+        assert_eq!(push_to_edge(sq("a8")), push_to_edge(sq("h1")));
+
+        assert!(has_mating_material(&edge, Color::White));
+        assert!(!has_mating_material(&edge, Color::Black));
+        let same_colored = board("4k3/8/8/8/8/8/3B1B2/4K3 w - - 0 1");
+        assert!(!has_mating_material(&same_colored, Color::White));
+        let bishop_pair = board("4k3/8/8/8/8/8/4BB2/4K3 w - - 0 1");
+        assert!(has_mating_material(&bishop_pair, Color::White));
+    }
+
+    #[test]
+    fn test_kbnk_drives_the_king_to_the_bishops_corner() {
+        // Light-squared bishop (e2): a8 and h1 are the mating corners
+        let right_corner = board("k7/2K5/8/8/8/8/4BN2/8 w - - 0 1");
+        let wrong_corner = board("7k/5K2/8/8/8/8/4BN2/8 w - - 0 1");
+        let edge = board("3k4/8/3K4/8/8/8/4BN2/8 w - - 0 1");
+        let center = board("8/8/8/3k4/8/3K4/4BN2/8 w - - 0 1");
+        assert!(kbnk(&center, Color::White) > KNOWN_WIN);
+        assert!(kbnk(&right_corner, Color::White) > kbnk(&edge, Color::White));
+        assert!(kbnk(&edge, Color::White) > kbnk(&wrong_corner, Color::White));
+        assert!(kbnk(&edge, Color::White) > kbnk(&center, Color::White));
+
+        // Dark-squared bishop (d2): the other way around
+        let dark = board("k7/2K5/8/8/8/8/3B1N2/8 w - - 0 1");
+        let dark_right = board("7k/5K2/8/8/8/8/3B1N2/8 w - - 0 1");
+        assert!(kbnk(&dark_right, Color::White) > kbnk(&dark, Color::White));
+    }
+
+    #[test]
+    fn test_scale_factor() {
+        // Opposite-colored bishops, a pawn up
+        let ocb = board("4k3/8/8/3b4/2P5/4PP2/8/2B1K3 w - - 0 1");
+        assert_eq!(scale_factor(&ocb, Color::White), SCALE_OPPOSITE_BISHOPS);
+        let ocb_rooks = board("r3k3/8/8/3b4/2P5/4PP2/8/R1B1K3 w - - 0 1");
+        assert_eq!(
+            scale_factor(&ocb_rooks, Color::White),
+            SCALE_OPPOSITE_BISHOPS_WITH_PIECES
+        );
+        let ocb_two_pawns = board("4k3/8/8/3b4/2P5/5P2/8/2B1K3 w - - 0 1");
+        assert_eq!(
+            scale_factor(&ocb_two_pawns, Color::White),
+            SCALE_OPPOSITE_BISHOPS_FEW_PAWNS
+        );
+        // Same-colored bishops play on
+        let scb = board("4k3/8/3b4/8/2P5/4PP2/8/2B1K3 w - - 0 1");
+        assert_eq!(scale_factor(&scb, Color::White), SCALE_NORMAL);
+
+        // Four pawns against three on the king side
+        let one_wing = board("r5k1/5pp1/7p/8/8/5PPP/4P3/R5K1 w - - 0 1");
+        assert_eq!(
+            scale_factor(&one_wing, Color::White),
+            SCALE_ROOK_ENDING_ONE_WING
+        );
+        let two_wings = board("r5k1/p4pp1/7p/8/8/5PPP/P3P3/R5K1 w - - 0 1");
+        assert_eq!(scale_factor(&two_wings, Color::White), SCALE_NORMAL);
+        let two_pawns_up = board("r5k1/6p1/7p/8/8/5PPP/4P3/R5K1 w - - 0 1");
+        assert_eq!(scale_factor(&two_pawns_up, Color::White), SCALE_NORMAL);
+
+        // No pawns for the strong side, at most a bishop up
+        let rb_vs_r = board("4k3/8/8/8/3r4/8/8/R1B1K3 w - - 0 1");
+        assert_eq!(
+            scale_factor(&rb_vs_r, Color::White),
+            SCALE_NO_PAWNS_VS_PIECES
+        );
+        let r_vs_b = board("4k3/8/8/8/3b4/8/8/R3K3 w - - 0 1");
+        assert_eq!(scale_factor(&r_vs_b, Color::White), SCALE_NO_PAWNS_VS_MINOR);
+        let bn_vs_n = board("4k3/8/8/8/3n4/8/8/1NB1K3 w - - 0 1");
+        assert_eq!(
+            scale_factor(&bn_vs_n, Color::White),
+            SCALE_NO_PAWNS_VS_MINOR
+        );
+        let b_vs_p = board("4k3/p7/8/8/8/8/8/2B1K3 w - - 0 1");
+        assert_eq!(scale_factor(&b_vs_p, Color::White), SCALE_NO_PAWNS_MINOR_UP);
+        // A rook up, or a pawn to promote, still wins
+        let r_vs_none = board("4k3/p7/8/8/8/8/8/R3K3 w - - 0 1");
+        assert_eq!(scale_factor(&r_vs_none, Color::White), SCALE_NORMAL);
+        let rp_vs_b = board("4k3/8/8/8/3b4/8/P7/R3K3 w - - 0 1");
+        assert_eq!(scale_factor(&rp_vs_b, Color::White), SCALE_NORMAL);
+    }
+}
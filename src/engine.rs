@@ -0,0 +1,481 @@
+//! Embeddable engine facade
+//!
+//! [`Engine`] bundles a position, the threaded search and its settings behind
+//! a small API, for GUIs and bots that link the crate instead of talking UCI
+//! over pipes:
+//!
+//! ```no_run
+//! use scacchista::{Engine, SearchLimits};
+//!
+//! scacchista::init();
+//! let mut engine = Engine::new(2, 64);
+//! engine.on_info(|info| println!("depth {} score {}", info.depth, info.score));
+//! engine.set_position(None, &["e2e4", "e7e5"]).unwrap();
+//! let result = engine.go(&SearchLimits::new().movetime(500));
+//! println!("bestmove {}", scacchista::move_to_uci(result.best_move));
+//! ```
+//!
+//! [`Engine::go`] blocks until the search ends; [`Engine::stop_handle`] gives
+//! a handle that ends it early from another thread.
+
+use crate::board::{parse_uci_move, Board, Color, FenError, Move, START_FEN};
+use crate::eval::EvalWeights;
+use crate::search::observer::{IterationInfo, SearchObserver};
+use crate::search::thread_mgr::SearchJob;
+use crate::search::{SearchParams, SearchResult, SmpMode, ThreadManager, VarietyRng, MAX_DEPTH};
+use crate::time::{TimeAllocation, TimeManager};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Why a position could not be set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineError {
+    /// The FEN could not be parsed, or the position has no king
+    InvalidFen(FenError),
+    /// A move is malformed or illegal in the position it is played in
+    InvalidMove { mv: String, reason: &'static str },
+    /// The playing style is not one of `Normal`, `Tal`, `Petrosian`
+    UnknownStyle(String),
+}
+
+impl std::fmt::Display for EngineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EngineError::InvalidFen(reason) => write!(f, "invalid FEN: {}", reason),
+            EngineError::InvalidMove { mv, reason } => write!(f, "invalid move {}: {}", mv, reason),
+            EngineError::UnknownStyle(style) => write!(f, "unknown style {}", style),
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+/// Limits of one search; without any the search runs until stopped
+#[derive(Debug, Clone, Default)]
+pub struct SearchLimits {
+    /// Maximum depth in plies
+    pub depth: Option<u8>,
+    /// Exact thinking time in milliseconds
+    pub movetime_ms: Option<u64>,
+    /// Clocks and increments in milliseconds (time allocated as in a game)
+    pub wtime_ms: Option<u64>,
+    pub btime_ms: Option<u64>,
+    pub winc_ms: u64,
+    pub binc_ms: u64,
+    /// Moves to the next time control
+    pub movestogo: Option<u64>,
+    /// Restrict the search to these moves (UCI notation; empty = all)
+    pub search_moves: Vec<String>,
+    /// Stop after this many nodes (reproducible whatever the machine)
+    pub nodes: Option<u64>,
+}
+
+impl SearchLimits {
+    /// No limit
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop after this depth
+    pub fn depth(mut self, depth: u8) -> Self {
+        self.depth = Some(depth);
+        self
+    }
+
+    /// Think exactly this long
+    pub fn movetime(mut self, ms: u64) -> Self {
+        self.movetime_ms = Some(ms);
+        self
+    }
+
+    /// Play on a clock: remaining time and increment of both sides
+    pub fn clock(mut self, wtime_ms: u64, btime_ms: u64, winc_ms: u64, binc_ms: u64) -> Self {
+        self.wtime_ms = Some(wtime_ms);
+        self.btime_ms = Some(btime_ms);
+        self.winc_ms = winc_ms;
+        self.binc_ms = binc_ms;
+        self
+    }
+
+    /// Moves left to the next time control
+    pub fn movestogo(mut self, moves: u64) -> Self {
+        self.movestogo = Some(moves);
+        self
+    }
+
+    /// Stop after this many nodes
+    pub fn nodes(mut self, nodes: u64) -> Self {
+        self.nodes = Some(nodes);
+        self
+    }
+
+    /// Only search these root moves (UCI notation)
+    pub fn search_moves(mut self, moves: &[&str]) -> Self {
+        self.search_moves = moves.iter().map(|mv| mv.to_string()).collect();
+        self
+    }
+
+    /// Deepest iteration to search (`MAX_DEPTH` without a depth limit)
+    pub fn max_depth(&self) -> u8 {
+        self.depth.unwrap_or(MAX_DEPTH).clamp(1, MAX_DEPTH)
+    }
+
+    /// Time budget of the side to move in `board`, from `movetime` or its
+    /// clock; None when neither is given. Every limit applies at once: the
+    /// search ends at whichever of depth, nodes and time is reached first.
+    pub fn time_allocation(&self, board: &Board, move_overhead_ms: u64) -> Option<TimeAllocation> {
+        let white = board.side == Color::White;
+        let clock = if white { self.wtime_ms } else { self.btime_ms };
+        if self.movetime_ms.is_none() && clock.is_none() {
+            return None;
+        }
+        Some(TimeManager::allocate_time(
+            &crate::search::params::TimeManagement::new().with_phase(board.phase()),
+            self.wtime_ms,
+            self.btime_ms,
+            Some(self.winc_ms),
+            Some(self.binc_ms),
+            self.movetime_ms,
+            self.movestogo,
+            white,
+            move_overhead_ms,
+        ))
+    }
+}
+
+/// Stops the search of an [`Engine`] from any thread
+#[derive(Clone)]
+pub struct StopHandle(Arc<AtomicBool>);
+
+impl StopHandle {
+    /// End the running search; `go` returns its best move so far
+    pub fn stop(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Forwards finished iterations to the `on_info` callback
+struct InfoCallback<F>(F);
+
+impl<F: Fn(&IterationInfo) + Send + Sync> SearchObserver for InfoCallback<F> {
+    fn on_iteration(&self, info: &IterationInfo) {
+        (self.0)(info)
+    }
+}
+
+/// A chess engine: position, threaded search and settings
+pub struct Engine {
+    board: Board,
+    /// Search threads (None only while dropping)
+    threads: Option<ThreadManager>,
+    weights: EvalWeights,
+    contempt: i16,
+    smp_mode: SmpMode,
+    move_overhead_ms: u64,
+    engine_color: Option<Color>,
+    move_variety: i16,
+    variety_rng: VarietyRng,
+}
+
+impl Default for Engine {
+    /// One thread and a 16 MB transposition table
+    fn default() -> Self {
+        Self::new(1, 16)
+    }
+}
+
+impl Engine {
+    /// Engine with `threads` search threads and a `hash_mb` transposition
+    /// table, on the starting position. Call [`crate::init`] first.
+    pub fn new(threads: usize, hash_mb: usize) -> Self {
+        let mut board = Board::new();
+        board
+            .set_from_fen(START_FEN)
+            .expect("the start position is valid");
+        Self {
+            board,
+            threads: Some(ThreadManager::new(threads.max(1), hash_mb.max(1))),
+            weights: EvalWeights::NORMAL,
+            contempt: 0,
+            smp_mode: SmpMode::LazySmp,
+            move_overhead_ms: 0,
+            engine_color: None,
+            move_variety: 0,
+            variety_rng: VarietyRng::new(0),
+        }
+    }
+
+    fn threads(&self) -> &ThreadManager {
+        self.threads
+            .as_ref()
+            .expect("search threads live as long as the engine")
+    }
+
+    /// Current position
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Set the position: `fen` (None = starting position) followed by
+    /// `moves` in UCI notation. On error the position is left unchanged.
+    pub fn set_position(&mut self, fen: Option<&str>, moves: &[&str]) -> Result<(), EngineError> {
+        self.board = build_position(fen, moves)?;
+        Ok(())
+    }
+
+    /// Start a new game: the starting position and an empty transposition
+    /// table (which is otherwise kept from one `go` to the next)
+    pub fn new_game(&mut self) {
+        self.board = build_position(None, &[]).expect("the start position is valid");
+        self.threads().clear_tt();
+    }
+
+    /// Call `callback` with every iteration of the main search thread
+    /// (depth, score, PV, nodes, ...), from the search thread
+    pub fn on_info(&mut self, callback: impl Fn(&IterationInfo) + Send + Sync + 'static) {
+        self.threads()
+            .set_observer(Some(Arc::new(InfoCallback(callback))));
+    }
+
+    /// Remove the `on_info` callback
+    pub fn clear_info(&mut self) {
+        self.threads().set_observer(None);
+    }
+
+    /// Playing style: `Normal`, `Tal` or `Petrosian`
+    pub fn set_style(&mut self, style: &str) -> Result<(), EngineError> {
+        self.weights = EvalWeights::for_style(style)
+            .ok_or_else(|| EngineError::UnknownStyle(style.to_string()))?;
+        Ok(())
+    }
+
+    /// Draw score shift in centipawns against `engine_color` (positive =
+    /// avoid draws)
+    pub fn set_contempt(&mut self, cp: i16, engine_color: Option<Color>) {
+        self.contempt = cp;
+        self.engine_color = engine_color;
+    }
+
+    /// How the search threads share the work
+    pub fn set_smp_mode(&mut self, mode: SmpMode) {
+        self.smp_mode = mode;
+    }
+
+    /// Time kept in reserve per move for communication lag
+    pub fn set_move_overhead(&mut self, ms: u64) {
+        self.move_overhead_ms = ms;
+    }
+
+    /// Play any root move within `cp` centipawns of the best one, chosen with
+    /// random bonuses drawn from `seed` (0 = always the best move); the same
+    /// seed replays the same choices
+    pub fn set_move_variety(&mut self, cp: i16, seed: u64) {
+        self.move_variety = cp;
+        self.variety_rng = VarietyRng::new(seed);
+    }
+
+    /// Search the current position within `limits` and return the result;
+    /// blocks until the search ends (see [`Engine::stop_handle`]). A
+    /// finished game returns a null move.
+    pub fn go(&mut self, limits: &SearchLimits) -> SearchResult {
+        let params = self
+            .search_params(limits)
+            .move_variety(self.move_variety, self.variety_rng.next_u64());
+        self.threads().submit_job(SearchJob {
+            board: self.board.clone(),
+            params,
+        })
+    }
+
+    /// Handle that stops the running search from another thread (a stop
+    /// before `go` starts is forgotten)
+    pub fn stop_handle(&self) -> StopHandle {
+        StopHandle(self.threads().get_stop_flag())
+    }
+
+    /// Stop the running search
+    pub fn stop(&self) {
+        self.threads().stop_current_job();
+    }
+
+    fn search_params(&self, limits: &SearchLimits) -> SearchParams {
+        let time = limits
+            .time_allocation(&self.board, self.move_overhead_ms)
+            .unwrap_or(TimeAllocation::fixed(0));
+        let search_moves: Vec<Move> = limits
+            .search_moves
+            .iter()
+            .filter_map(|mv| parse_uci_move(&mut self.board.clone(), mv).ok())
+            .collect();
+        SearchParams::new()
+            .max_depth(limits.max_depth())
+            .time_allocation(time)
+            .node_limit(limits.nodes.unwrap_or(0))
+            .search_moves(search_moves)
+            .eval_weights(self.weights)
+            .contempt(self.contempt)
+            .engine_color(self.engine_color)
+            .smp_mode(self.smp_mode)
+    }
+}
+
+/// Board of `fen` (None = starting position) after `moves` in UCI notation,
+/// keeping the history that can still produce a repetition
+pub(crate) fn build_position(fen: Option<&str>, moves: &[&str]) -> Result<Board, EngineError> {
+    let mut board = Board::new();
+    board
+        .set_from_fen(fen.unwrap_or(START_FEN))
+        .and_then(|_| board.validate_kings(false))
+        .map_err(EngineError::InvalidFen)?;
+    for &mv in moves {
+        let parsed = parse_uci_move(&mut board, mv).map_err(|reason| EngineError::InvalidMove {
+            mv: mv.to_string(),
+            reason,
+        })?;
+        board.make_move(parsed);
+    }
+    board.prune_history();
+    Ok(board)
+}
+
+impl Drop for Engine {
+    /// Stop the search and join the search threads
+    fn drop(&mut self) {
+        if let Some(threads) = self.threads.take() {
+            threads.stop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::move_to_uci;
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_engine_set_position_validates_atomically() {
+        crate::init();
+        let mut engine = Engine::default();
+        engine.set_position(None, &["e2e4", "e7e5"]).unwrap();
+        let after = engine.board().zobrist;
+
+        assert!(matches!(
+            engine.set_position(None, &["e2e4", "e2e4"]),
+            Err(EngineError::InvalidMove { ref mv, .. }) if mv == "e2e4"
+        ));
+        assert!(matches!(
+            engine.set_position(Some("not a fen"), &[]),
+            Err(EngineError::InvalidFen(_))
+        ));
+        assert_eq!(engine.board().zobrist, after);
+        assert!(engine.set_style("Fischer").is_err());
+        assert!(engine.set_style("tal").is_ok());
+    }
+
+    #[test]
+    fn test_engine_go_reports_info_and_finds_mate() {
+        crate::init();
+        let mut engine = Engine::new(2, 16);
+        let depths = Arc::new(Mutex::new(Vec::new()));
+        let seen = depths.clone();
+        engine.on_info(move |info| seen.lock().unwrap().push(info.depth));
+
+        engine.set_position(None, &["e2e4"]).unwrap();
+        let result = engine.go(&SearchLimits::new().depth(3));
+        assert_eq!(result.completed_depth, 3);
+        assert_eq!(*depths.lock().unwrap(), vec![1, 2, 3]);
+
+        engine.clear_info();
+        engine
+            .set_position(Some("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1"), &[])
+            .unwrap();
+        let result = engine.go(&SearchLimits::new().depth(3));
+        assert_eq!(move_to_uci(result.best_move), "a1a8");
+        assert_eq!(result.mate(), Some(1));
+        assert_eq!(depths.lock().unwrap().len(), 3);
+
+        // searchmoves: only the listed move is searched
+        let result = engine.go(&SearchLimits::new().depth(2).search_moves(&["g1f1"]));
+        assert_eq!(move_to_uci(result.best_move), "g1f1");
+    }
+
+    #[test]
+    fn test_engine_fixed_nodes_are_reproducible() {
+        crate::init();
+        let run = || {
+            let mut engine = Engine::new(1, 16);
+            engine.set_position(None, &["e2e4", "c7c5"]).unwrap();
+            engine.go(&SearchLimits::new().nodes(5000))
+        };
+        let (first, second) = (run(), run());
+        assert_ne!(first.best_move, Move::NONE);
+        assert!(first.nodes <= 5000, "{} nodes", first.nodes);
+        assert_eq!(
+            (first.best_move, first.nodes, first.completed_depth),
+            (second.best_move, second.nodes, second.completed_depth)
+        );
+    }
+
+    #[test]
+    fn test_engine_keeps_the_hash_until_a_new_game() {
+        crate::init();
+        let mut engine = Engine::new(2, 16);
+        let start_key = engine.board().zobrist;
+        engine.go(&SearchLimits::new().depth(4));
+        for moves in [["e2e4", "e7e5"], ["d2d4", "d7d5"], ["c2c4", "c7c5"]] {
+            engine.set_position(None, &moves).unwrap();
+            engine.go(&SearchLimits::new().depth(4));
+            assert!(engine.threads().probe_tt(start_key).is_some());
+        }
+
+        engine.new_game();
+        assert_eq!(engine.board().zobrist, start_key);
+        assert!(engine.threads().probe_tt(start_key).is_none());
+    }
+
+    #[test]
+    fn test_engine_depth_and_time_limits_apply_together() {
+        crate::init();
+        let mut engine = Engine::new(1, 16);
+        let board = engine.board().clone();
+        assert_eq!(SearchLimits::new().time_allocation(&board, 0), None);
+        // Only the opponent's clock: no time limit for the side to move
+        let black_clock = SearchLimits {
+            btime_ms: Some(60_000),
+            ..SearchLimits::new()
+        };
+        assert_eq!(black_clock.time_allocation(&board, 0), None);
+        assert_eq!(SearchLimits::new().max_depth(), MAX_DEPTH);
+
+        // The depth is reached long before the time runs out...
+        let start = Instant::now();
+        let result = engine.go(&SearchLimits::new().depth(3).movetime(60_000));
+        assert_eq!(result.completed_depth, 3);
+        assert!(start.elapsed() < Duration::from_secs(30));
+
+        // ...and the time long before the depth
+        let start = Instant::now();
+        let result = engine.go(&SearchLimits::new().depth(60).movetime(300));
+        assert!(result.completed_depth < 60);
+        assert_ne!(result.best_move, Move::NONE);
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_engine_stop_handle_ends_unlimited_search() {
+        crate::init();
+        let mut engine = Engine::default();
+        let handle = engine.stop_handle();
+        let stopper = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            handle.stop();
+        });
+        let start = Instant::now();
+        let result = engine.go(&SearchLimits::new());
+        stopper.join().unwrap();
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert_ne!(result.best_move, Move::NONE);
+    }
+}
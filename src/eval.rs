@@ -4,7 +4,8 @@
 //! ogni casella ha un valore bonus/malus che incentiva posizioni strategicamente
 //! migliori (es: pedoni centrali, cavalieri sviluppati, re protetto dopo arrocco).
 
-use crate::board::{Board, Color, PieceKind};
+use crate::board::{material_key_from_counts, Board, Color, PieceKind};
+use crate::endgame;
 use crate::utils::{
     FILE_A, FILE_B, FILE_C, FILE_D, FILE_E, FILE_F, FILE_G, FILE_H,
 };
@@ -12,15 +13,13 @@ use crate::utils::{
 // ============================================================================
 // VALORI MATERIALI (in centipawn)
 // ============================================================================
-const PAWN_VALUE: i16 = 100;
-const KNIGHT_VALUE: i16 = 320;
-const BISHOP_VALUE: i16 = 330;
-const ROOK_VALUE: i16 = 500;
-const QUEEN_VALUE: i16 = 900;
+pub(crate) const PAWN_VALUE: i16 = 100;
+pub(crate) const KNIGHT_VALUE: i16 = 320;
+pub(crate) const BISHOP_VALUE: i16 = 330;
+pub(crate) const ROOK_VALUE: i16 = 500;
+pub(crate) const QUEEN_VALUE: i16 = 900;
 const KING_VALUE: i16 = 20000;
 
-const SIMPLE_ENDGAME_BONUS: i16 = 10000;
-
 #[derive(Clone, Copy, PartialEq, Eq, Default)]
 struct MaterialCounts {
     pawns: u32,
@@ -40,29 +39,6 @@ impl MaterialCounts {
     }
 }
 
-const SIMPLE_ENDGAME_SIGNATURES: [MaterialCounts; 3] = [
-    MaterialCounts {
-        pawns: 0,
-        knights: 0,
-        bishops: 0,
-        rooks: 0,
-        queens: 1,
-    },
-    MaterialCounts {
-        pawns: 0,
-        knights: 0,
-        bishops: 0,
-        rooks: 1,
-        queens: 0,
-    },
-    MaterialCounts {
-        pawns: 0,
-        knights: 1,
-        bishops: 1,
-        rooks: 0,
-        queens: 0,
-    },
-];
 
 // ============================================================================
 // PIECE-SQUARE TABLES (dal punto di vista del BIANCO)
@@ -217,6 +193,30 @@ const KING_PSQT_EG: [i16; 64] = [
     -50, -30, -20, -10, -10, -20, -30, -50,
 ];
 
+/// Valore materiale + PSQT di un pezzo su una casella, come `(middlegame, endgame)`
+///
+/// Usato dagli accumulatori incrementali della `Board` (aggiornati in make/unmake)
+/// e da `Board::recalc_psqt()` per la verifica completa.
+pub fn piece_square_value(kind: PieceKind, color: Color, sq: usize) -> (i32, i32) {
+    let (material_value, psqt_mg, psqt_eg) = match kind {
+        PieceKind::Pawn => (PAWN_VALUE, &PAWN_PSQT, &PAWN_PSQT_EG),
+        PieceKind::Knight => (KNIGHT_VALUE, &KNIGHT_PSQT, &KNIGHT_PSQT_EG),
+        PieceKind::Bishop => (BISHOP_VALUE, &BISHOP_PSQT, &BISHOP_PSQT_EG),
+        PieceKind::Rook => (ROOK_VALUE, &ROOK_PSQT, &ROOK_PSQT_EG),
+        PieceKind::Queen => (QUEEN_VALUE, &QUEEN_PSQT, &QUEEN_PSQT_EG),
+        PieceKind::King => (KING_VALUE, &KING_PSQT, &KING_PSQT_EG),
+    };
+    // Per il Nero specchiamo verticalmente l'indice PSQT
+    let idx = match color {
+        Color::White => sq,
+        Color::Black => sq ^ 56,
+    };
+    (
+        material_value as i32 + psqt_mg[idx] as i32,
+        material_value as i32 + psqt_eg[idx] as i32,
+    )
+}
+
 // ============================================================================
 // KING SAFETY
 // ============================================================================
@@ -329,6 +329,95 @@ fn has_castling_rights(board: &Board, color: Color) -> bool {
     }
 }
 
+/// Fase (su 24, vedi [`Board::phase`]) da cui la posizione conta come
+/// apertura: almeno due terzi del materiale non-pedone ancora in gioco
+const OPENING_PHASE: i32 = 16;
+
+/// Fase sotto la quale la posizione conta come finale
+const ENDGAME_PHASE: i32 = 8;
+
+/// Riduce `value` (una penalità d'apertura) secondo il materiale rimasto:
+/// piena da [`OPENING_PHASE`] in su, nulla sotto [`ENDGAME_PHASE`], lineare
+/// in mezzo così che cambiare un pezzo non la faccia sparire di colpo.
+///
+/// Dipende solo dalla posizione, non da `board.fullmove`: le FEN possono
+/// avere un numero di mossa qualsiasi e le trasposizioni ne hanno uno diverso
+/// per la stessa chiave Zobrist.
+fn opening_share(board: &Board, value: i16) -> i16 {
+    let phase = (board.phase() as i32).clamp(ENDGAME_PHASE, OPENING_PHASE);
+    (value as i32 * (phase - ENDGAME_PHASE) / (OPENING_PHASE - ENDGAME_PHASE)) as i16
+}
+
+/// Pedoni (dei due colori) sulla casa di partenza da cui la partita conta
+/// come nelle prime mosse
+const EARLY_HOME_PAWNS: i32 = 10;
+
+/// Pedoni sulla casa di partenza sotto i quali le prime mosse sono passate
+const LATE_HOME_PAWNS: i32 = 6;
+
+/// Riduce `value` secondo quanto la partita è ancora nelle prime mosse,
+/// misurato dai pedoni ancora sulla seconda traversa (settima per il Nero):
+/// pieno da [`EARLY_HOME_PAWNS`] in su, nullo sotto [`LATE_HOME_PAWNS`],
+/// lineare in mezzo. Come [`opening_share`] non guarda `board.fullmove`.
+fn early_share(board: &Board, value: i16) -> i16 {
+    const RANK_2_MASK: u64 = 0xFF00;
+    const RANK_7_MASK: u64 = 0x00FF_0000_0000_0000;
+    let home = (board.piece_bb(PieceKind::Pawn, Color::White) & RANK_2_MASK).count_ones()
+        + (board.piece_bb(PieceKind::Pawn, Color::Black) & RANK_7_MASK).count_ones();
+    let home = (home as i32).clamp(LATE_HOME_PAWNS, EARLY_HOME_PAWNS);
+    (value as i32 * (home - LATE_HOME_PAWNS) / (EARLY_HOME_PAWNS - LATE_HOME_PAWNS)) as i16
+}
+
+/// Il Re ha perso i diritti di arrocco senza arroccare: in apertura resta
+/// bloccato al centro.
+///
+/// Re e una Torre ancora sulle case di partenza senza diritti vengono quasi
+/// sempre da una FEN scritta senza diritti d'arrocco, non da una perdita.
+fn lost_castling(board: &Board, color: Color) -> bool {
+    let (king_home, rook_corners) = match color {
+        Color::White => (4, (1u64 << 0) | (1u64 << 7)),
+        Color::Black => (60, (1u64 << 56) | (1u64 << 63)),
+    };
+    let fen_without_rights = board.king_sq(color) == king_home
+        && board.piece_bb(PieceKind::Rook, color) & rook_corners != 0;
+    !has_castled(board, color) && !has_castling_rights(board, color) && !fen_without_rights
+}
+
+/// Pezzi minori di `color` fuori dalla prima traversa (ottava per il Nero)
+fn developed_minors(board: &Board, color: Color) -> i16 {
+    let back_rank: u64 = match color {
+        Color::White => 0xFF,
+        Color::Black => 0xFF00_0000_0000_0000,
+    };
+    let minors =
+        board.piece_bb(PieceKind::Knight, color) | board.piece_bb(PieceKind::Bishop, color);
+    (minors & !back_rank).count_ones() as i16
+}
+
+/// Penalità `value` per l'arrocco perso (vedi [`lost_castling`]), piena solo
+/// in apertura: con il materiale in gioco ([`opening_share`]) e nelle prime
+/// mosse ([`early_share`]). Nel mediogioco un Re che cammina è giudicato dal
+/// resto della sicurezza del Re.
+///
+/// Il Re al centro è in pericolo solo quando l'avversario ha pezzi per
+/// attaccarlo: con tutti i suoi pezzi minori ancora a casa non c'è penalità.
+/// Come il vecchio limite "dalla mossa 5", le prime mosse restano senza
+/// penalità e la ricerca non cambia idea a seconda di chi muove per ultimo il
+/// Re nell'orizzonte.
+fn lost_castling_share(board: &Board, color: Color, value: i16) -> i16 {
+    if !lost_castling(board, color) {
+        return 0;
+    }
+    let enemy = match color {
+        Color::White => Color::Black,
+        Color::Black => Color::White,
+    };
+    if developed_minors(board, enemy) == 0 {
+        return 0;
+    }
+    opening_share(board, early_share(board, value))
+}
+
 /// Verifica se il Re è sotto scacco
 ///
 /// Un Re è sotto scacco se la sua casella è attaccata da pezzi avversari.
@@ -359,24 +448,17 @@ fn is_in_check(board: &Board, color: Color) -> bool {
 ///
 /// # Returns
 /// Score positivo = Bianco controlla centro, negativo = Nero
-fn center_control(board: &Board) -> i16 {
-    let mut score = 0;
+fn center_control(board: &Board, params: &EvalParams) -> i16 {
+    let (white, black) = center_control_by_color(board, params);
+    white - black
+}
 
+/// Punti di controllo del centro di ciascun colore (Bianco, Nero): solo le
+/// caselle attaccate da un colore e non dall'altro contano
+fn center_control_by_color(board: &Board, params: &EvalParams) -> (i16, i16) {
     // Caselle del centro (d4, e4, d5, e5)
     const CENTER: [usize; 4] = [27, 28, 35, 36]; // d4, e4, d5, e5
 
-    for &sq in &CENTER {
-        let white_attacks = board.is_square_attacked(sq, Color::White);
-        let black_attacks = board.is_square_attacked(sq, Color::Black);
-
-        if white_attacks && !black_attacks {
-            score += 10; // Bianco controlla
-        } else if black_attacks && !white_attacks {
-            score -= 10; // Nero controlla
-        }
-        // Se entrambi attaccano, si compensano (score += 0)
-    }
-
     // Centro esteso (c3-f3, c4, f4, c5, f5, c6-f6)
     const EXTENDED: [usize; 12] = [
         18, 19, 20, 21, // c3, d3, e3, f3
@@ -385,18 +467,25 @@ fn center_control(board: &Board) -> i16 {
         42, 43, 44, 45, // c6, d6, e6, f6
     ];
 
-    for &sq in &EXTENDED {
+    let (mut white, mut black) = (0, 0);
+    let squares = CENTER.iter().map(|&sq| (sq, params.center_control)).chain(
+        EXTENDED
+            .iter()
+            .map(|&sq| (sq, params.extended_center_control)),
+    );
+    for (sq, bonus) in squares {
         let white_attacks = board.is_square_attacked(sq, Color::White);
         let black_attacks = board.is_square_attacked(sq, Color::Black);
 
+        // Se entrambi attaccano, si compensano
         if white_attacks && !black_attacks {
-            score += 3; // Bianco controlla
+            white += bonus;
         } else if black_attacks && !white_attacks {
-            score -= 3; // Nero controlla
+            black += bonus;
         }
     }
 
-    score
+    (white, black)
 }
 
 /// Valuta la sicurezza del Re (MIGLIORATO - Fix GrandMaster #1)
@@ -417,7 +506,7 @@ fn center_control(board: &Board) -> i16 {
 ///
 /// # Returns
 /// Score positivo = Re sicuro, negativo = Re in pericolo
-fn king_safety(board: &Board, color: Color) -> i16 {
+fn king_safety(board: &Board, color: Color, params: &EvalParams) -> i16 {
     let mut safety = 0;
 
     // 1. Trova posizione Re
@@ -431,16 +520,14 @@ fn king_safety(board: &Board, color: Color) -> i16 {
     let has_rights = has_castling_rights(board, color);
 
     // 4. NUOVO: Penalità pesante per perdita diritto arrocco in apertura
-    // Se siamo in apertura (fullmove 5-14), re non ha arrocato, e ha perso diritti → GRAVE
-    // Nota: fullmove >= 5 evita falsi positivi su FEN personalizzate nelle prime mosse
-    if board.fullmove >= 5 && board.fullmove < 15 && !castled && !has_rights {
-        // Re ha perso diritto arrocco in apertura senza aver arrocato
-        // Es: 13.Qxe7?? in prova_2.pgn → Qxe7+ e re bloccato in e1
-        // FIX v0.4.1: Aumentata da -120 a -400 cp (approssimazione Desperado mode)
-        // La perdita dell'arrocco in apertura è CATASTROFICA e deve dominare
-        // il vantaggio materiale di +330 cp (Donna vs Alfiere)
-        safety -= 400; // Penalità catastrofica (> valore di un pezzo minore)
-    }
+    // Re non arrocato e senza diritti → GRAVE in apertura (vedi
+    // lost_castling_share), qualunque sia il numero di mossa
+    // Es: 13.Qxe7?? in prova_2.pgn → Qxe7+ e re bloccato in e1
+    // FIX v0.4.1: Aumentata da -120 a -400 cp (approssimazione Desperado mode)
+    // La perdita dell'arrocco in apertura è CATASTROFICA e deve dominare
+    // il vantaggio materiale di +330 cp (Donna vs Alfiere)
+    // Catastrofica (> valore di un pezzo minore)
+    safety -= lost_castling_share(board, color, params.lost_castling_penalty);
 
     // 4.5 NUOVO (Fix Issue #1): Threat evaluation - Re sotto scacco che perderà arrocco
     // Questa è una "penalità preventiva" che si applica PRIMA che il re perda i diritti
@@ -451,7 +538,7 @@ fn king_safety(board: &Board, color: Color) -> i16 {
         // Penalità preventiva: il re è sotto scacco e probabilmente perderà arrocco
         // AUMENTATA a -200 cp (circa 2/3 di pezzo minore) dopo test pratico
         // -80 cp non era sufficiente per prevenire Qxe7
-        safety -= 200;
+        safety -= params.check_castling_penalty;
     }
 
     // 5. MIGLIORATO: Penalità per Re al centro, proporzionale a pezzi avversari
@@ -467,26 +554,109 @@ fn king_safety(board: &Board, color: Color) -> i16 {
         // Formula: -50 * (1 + active_pieces / 6)
         // Es: 4 pezzi avversari → -50 * (1 + 4/6) ≈ -83 cp
         //     8 pezzi avversari → -50 * (1 + 8/6) ≈ -117 cp
-        let base_penalty = 50;
-        let multiplier = 100 + (active_pieces * 16); // 100 = 1.0, 16 ≈ 1/6 scaled to percentage
+        let base_penalty = params.center_king_penalty;
+        // 100 = 1.0, 16 ≈ 1/6 scaled to percentage
+        let multiplier = 100 + (active_pieces * params.center_king_attacker_scale);
         safety -= base_penalty * multiplier / 100;
     }
 
     // 6. Bonus pedoni scudo (invariato)
     let pawn_shield = count_pawn_shield(board, king_sq, color);
-    safety += pawn_shield * 15;
+    safety += pawn_shield * params.pawn_shield_bonus;
 
     safety
 }
 
+/// Peso per pezzo attaccante nel modello "attack units" (N, B, R, Q)
+const KING_ATTACK_WEIGHTS: [i16; 4] = [2, 2, 3, 5];
+
+/// Tabella non lineare unità d'attacco → penalità in centipawn (CPW "King Safety")
+///
+/// Cresce lentamente con pochi attaccanti e rapidamente quando l'attacco si
+/// concentra, saturando a 500 cp.
+const KING_DANGER_TABLE: [i16; 100] = [
+    0, 0, 1, 2, 3, 5, 7, 9, 12, 15, 18, 22, 26, 30, 35, 39, 44, 50, 56, 62, 68, 75, 82, 85, 89, 97,
+    105, 113, 122, 131, 140, 150, 169, 180, 191, 202, 213, 225, 237, 248, 260, 272, 283, 295, 307,
+    319, 330, 342, 354, 366, 377, 389, 401, 412, 424, 436, 448, 459, 471, 483, 494, 500, 500, 500,
+    500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500,
+    500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500,
+];
+
+/// Zona del Re: casa del Re, caselle adiacenti e la traversa successiva verso il nemico
+fn king_zone(king_sq: usize, color: Color) -> u64 {
+    let ring = crate::utils::king_attacks(king_sq) | (1u64 << king_sq);
+    let forward = match color {
+        Color::White => ring << 8,
+        Color::Black => ring >> 8,
+    };
+    ring | forward
+}
+
+/// Pericolo per il Re (modello "attack units", stile CPW/Stockfish)
+///
+/// Per ogni pezzo avversario (N, B, R, Q) che attacca la zona del Re si
+/// sommano `peso * caselle attaccate`; il totale viene mappato tramite
+/// `KING_DANGER_TABLE`. Un singolo attaccante non è considerato un attacco.
+///
+/// # Argomenti
+/// * `board` - La posizione da valutare
+/// * `color` - Colore del Re da valutare
+///
+/// # Returns
+/// Penalità (≥ 0) da sottrarre allo score di `color`
+fn king_danger(board: &Board, color: Color) -> i16 {
+    let enemy = match color {
+        Color::White => Color::Black,
+        Color::Black => Color::White,
+    };
+    let zone = king_zone(board.king_sq(color), color);
+
+    let mut attackers = 0;
+    let mut units = 0i16;
+    let kinds = [
+        PieceKind::Knight,
+        PieceKind::Bishop,
+        PieceKind::Rook,
+        PieceKind::Queen,
+    ];
+    for (i, &kind) in kinds.iter().enumerate() {
+        let mut pieces = board.piece_bb(kind, enemy);
+        while pieces != 0 {
+            let sq = pieces.trailing_zeros() as usize;
+            pieces &= pieces - 1;
+            let attacks = match kind {
+                PieceKind::Knight => crate::utils::knight_attacks(sq),
+                PieceKind::Bishop => crate::magic::bishop_attacks(sq, board.occ),
+                PieceKind::Rook => crate::magic::rook_attacks(sq, board.occ),
+                _ => crate::magic::queen_attacks(sq, board.occ),
+            };
+            let hits = (attacks & zone).count_ones() as i16;
+            if hits > 0 {
+                attackers += 1;
+                units += KING_ATTACK_WEIGHTS[i] * hits;
+            }
+        }
+    }
+
+    if attackers < 2 {
+        return 0;
+    }
+    KING_DANGER_TABLE[(units as usize).min(KING_DANGER_TABLE.len() - 1)]
+}
+
 // ============================================================================
 // PENALITÀ SVILUPPO PEZZI MINORI
 // ============================================================================
 
 /// Calcola penalità per pezzi minori (Cavalieri, Alfieri) non sviluppati
 ///
-/// Dopo la mossa 10, ogni cavaliere o alfiere sulla prima traversa
-/// riceve una penalità di -10 cp.
+/// Ogni cavaliere o alfiere sulla prima traversa riceve una penalità di
+/// -10 cp, ridotta con il materiale come le altre penalità d'apertura
+/// (vedi [`opening_share`]): in finale un alfiere in f1 non è "non sviluppato".
+/// Nelle prime mosse (vedi [`early_share`]) i pezzi a casa sono normali e la
+/// penalità è spenta: altrimenti chi ha l'ultima mossa di sviluppo
+/// nell'orizzonte della ricerca ne guadagna, e il punteggio oscilla tra
+/// profondità pari e dispari.
 ///
 /// Questo incentiva lo sviluppo attivo dei pezzi invece di mosse passive.
 ///
@@ -496,12 +666,7 @@ fn king_safety(board: &Board, color: Color) -> i16 {
 ///
 /// # Returns
 /// Penalità in centipawn per il colore specificato (sempre >= 0)
-fn development_penalty(board: &Board, color: Color) -> i16 {
-    // Prima di mossa 10, non applichiamo penalità (fase di apertura normale)
-    if board.fullmove <= 10 {
-        return 0;
-    }
-
+fn development_penalty(board: &Board, color: Color, params: &EvalParams) -> i16 {
     let mut penalty = 0;
 
     match color {
@@ -511,11 +676,13 @@ fn development_penalty(board: &Board, color: Color) -> i16 {
 
             // Cavalieri bianchi su rank 1
             let white_knights = board.piece_bb(PieceKind::Knight, Color::White);
-            penalty += (white_knights & RANK_1_MASK).count_ones() as i16 * 10;
+            penalty +=
+                (white_knights & RANK_1_MASK).count_ones() as i16 * params.development_penalty;
 
             // Alfieri bianchi su rank 1
             let white_bishops = board.piece_bb(PieceKind::Bishop, Color::White);
-            penalty += (white_bishops & RANK_1_MASK).count_ones() as i16 * 10;
+            penalty +=
+                (white_bishops & RANK_1_MASK).count_ones() as i16 * params.development_penalty;
         }
         Color::Black => {
             // Maschera per rank 8 (squares 56-63): bitboard con bit 56-63 settati
@@ -523,15 +690,330 @@ fn development_penalty(board: &Board, color: Color) -> i16 {
 
             // Cavalieri neri su rank 8
             let black_knights = board.piece_bb(PieceKind::Knight, Color::Black);
-            penalty += (black_knights & RANK_8_MASK).count_ones() as i16 * 10;
+            penalty +=
+                (black_knights & RANK_8_MASK).count_ones() as i16 * params.development_penalty;
 
             // Alfieri neri su rank 8
             let black_bishops = board.piece_bb(PieceKind::Bishop, Color::Black);
-            penalty += (black_bishops & RANK_8_MASK).count_ones() as i16 * 10;
+            penalty +=
+                (black_bishops & RANK_8_MASK).count_ones() as i16 * params.development_penalty;
         }
     }
 
-    penalty
+    opening_share(board, penalty - early_share(board, penalty))
+}
+
+// ============================================================================
+// PARAMETRI DELLA VALUTAZIONE (TUNING)
+// ============================================================================
+
+/// Costanti della valutazione completa, modificabili senza ricompilare.
+///
+/// I valori di default sono quelli storici del motore. Con `scacchista tune`
+/// ogni campo è esposto come opzione UCI spin (vedi [`EVAL_PARAM_SPINS`]), così
+/// tuner esterni come SPSA o CLOP possono variarli partita per partita.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EvalParams {
+    /// Peso della parte posizionale delle PSQT, in percentuale (il materiale
+    /// non viene scalato)
+    pub psqt_scale: i32,
+    /// Perdita del diritto di arrocco in apertura senza aver arroccato
+    pub lost_castling_penalty: i16,
+    /// Re sotto scacco con ancora diritti di arrocco
+    pub check_castling_penalty: i16,
+    /// Re non arroccato sulle colonne centrali (penalità base)
+    pub center_king_penalty: i16,
+    /// Aumento percentuale della penalità per ogni pezzo avversario attivo
+    pub center_king_attacker_scale: i16,
+    /// Bonus per ogni pedone scudo davanti al Re
+    pub pawn_shield_bonus: i16,
+    /// Penalità per ogni pezzo minore non sviluppato (vedi [`opening_share`])
+    pub development_penalty: i16,
+    /// Controllo esclusivo di d4, e4, d5, e5
+    pub center_control: i16,
+    /// Controllo esclusivo di una casella del centro esteso
+    pub extended_center_control: i16,
+}
+
+/// Opzione UCI spin di un parametro della valutazione
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EvalParamSpin {
+    pub name: &'static str,
+    pub min: i32,
+    pub max: i32,
+}
+
+/// Parametri esposti come opzioni UCI spin in modalità tuning
+pub const EVAL_PARAM_SPINS: [EvalParamSpin; 9] = [
+    EvalParamSpin {
+        name: "PsqtScale",
+        min: 0,
+        max: 200,
+    },
+    EvalParamSpin {
+        name: "LostCastlingPenalty",
+        min: 0,
+        max: 1000,
+    },
+    EvalParamSpin {
+        name: "CheckCastlingPenalty",
+        min: 0,
+        max: 1000,
+    },
+    EvalParamSpin {
+        name: "CenterKingPenalty",
+        min: 0,
+        max: 300,
+    },
+    EvalParamSpin {
+        name: "CenterKingAttackerScale",
+        min: 0,
+        max: 100,
+    },
+    EvalParamSpin {
+        name: "PawnShieldBonus",
+        min: 0,
+        max: 100,
+    },
+    EvalParamSpin {
+        name: "DevelopmentPenalty",
+        min: 0,
+        max: 100,
+    },
+    EvalParamSpin {
+        name: "CenterControl",
+        min: 0,
+        max: 100,
+    },
+    EvalParamSpin {
+        name: "ExtendedCenterControl",
+        min: 0,
+        max: 50,
+    },
+];
+
+impl EvalParams {
+    pub const DEFAULT: EvalParams = EvalParams {
+        psqt_scale: 100,
+        lost_castling_penalty: 400,
+        check_castling_penalty: 200,
+        center_king_penalty: 50,
+        center_king_attacker_scale: 16,
+        pawn_shield_bonus: 15,
+        development_penalty: 10,
+        center_control: 10,
+        extended_center_control: 3,
+    };
+
+    /// Valore del parametro con il nome UCI `name`
+    pub fn get(&self, name: &str) -> Option<i32> {
+        Some(match name {
+            "PsqtScale" => self.psqt_scale,
+            "LostCastlingPenalty" => self.lost_castling_penalty as i32,
+            "CheckCastlingPenalty" => self.check_castling_penalty as i32,
+            "CenterKingPenalty" => self.center_king_penalty as i32,
+            "CenterKingAttackerScale" => self.center_king_attacker_scale as i32,
+            "PawnShieldBonus" => self.pawn_shield_bonus as i32,
+            "DevelopmentPenalty" => self.development_penalty as i32,
+            "CenterControl" => self.center_control as i32,
+            "ExtendedCenterControl" => self.extended_center_control as i32,
+            _ => return None,
+        })
+    }
+
+    /// Imposta il parametro `name`, limitato all'intervallo della sua spin.
+    /// Restituisce false se il nome non esiste.
+    pub fn set(&mut self, name: &str, value: i32) -> bool {
+        let Some(spin) = EVAL_PARAM_SPINS.iter().find(|s| s.name == name) else {
+            return false;
+        };
+        let value = value.clamp(spin.min, spin.max);
+        let small = value as i16;
+        match name {
+            "PsqtScale" => self.psqt_scale = value,
+            "LostCastlingPenalty" => self.lost_castling_penalty = small,
+            "CheckCastlingPenalty" => self.check_castling_penalty = small,
+            "CenterKingPenalty" => self.center_king_penalty = small,
+            "CenterKingAttackerScale" => self.center_king_attacker_scale = small,
+            "PawnShieldBonus" => self.pawn_shield_bonus = small,
+            "DevelopmentPenalty" => self.development_penalty = small,
+            "CenterControl" => self.center_control = small,
+            "ExtendedCenterControl" => self.extended_center_control = small,
+            _ => unreachable!("every spin has a field"),
+        }
+        true
+    }
+
+    /// Materiale + PSQT di `color` con la parte posizionale scalata da
+    /// `psqt_scale` (gli accumulatori della Board contengono la somma)
+    fn scaled_psqt(&self, board: &Board, color: Color) -> (i32, i32) {
+        let (mg, eg) = board.psqt_score(color);
+        if self.psqt_scale == 100 {
+            return (mg, eg);
+        }
+        let mut material = material_value(board, color) as i32;
+        if board.has_king(color) {
+            material += KING_VALUE as i32;
+        }
+        let scale = |total: i32| material + (total - material) * self.psqt_scale / 100;
+        (scale(mg), scale(eg))
+    }
+}
+
+impl Default for EvalParams {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+// ============================================================================
+// STILI DI GIOCO (PESI DELLA VALUTAZIONE)
+// ============================================================================
+
+/// Pesi dei termini posizionali, in percentuale (100 = valore base), più il
+/// contempt usato dalla ricerca per le patte.
+///
+/// L'opzione UCI `Style` sceglie uno dei profili predefiniti: `Normal` lascia
+/// la valutazione invariata, `Tal` dà più peso all'attacco al re (così il
+/// materiale sacrificato per l'attacco è compensato più facilmente), alla
+/// mobilità e rifiuta le patte, `Petrosian` privilegia sicurezza del re e
+/// struttura pedonale e rischia meno.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EvalWeights {
+    /// Pericolo per il re dai pezzi avversari nella sua zona (`king_danger`)
+    pub king_attack: i32,
+    /// Arrocco, scudo di pedoni e diritti di arrocco (`king_safety`)
+    pub king_safety: i32,
+    /// Pedoni doppiati, isolati e passati
+    pub pawn_structure: i32,
+    /// Mobilità dei pezzi
+    pub mobility: i32,
+    /// Penalità per i pezzi minori non sviluppati
+    pub development: i32,
+    /// Controllo del centro e del centro allargato
+    pub center_control: i32,
+    /// Centipawn a cui la ricerca rinuncia pur di evitare una patta
+    /// (negativo = la patta è gradita)
+    pub contempt: i16,
+}
+
+impl EvalWeights {
+    pub const NORMAL: EvalWeights = EvalWeights {
+        king_attack: 100,
+        king_safety: 100,
+        pawn_structure: 100,
+        mobility: 100,
+        development: 100,
+        center_control: 100,
+        contempt: 0,
+    };
+
+    pub const TAL: EvalWeights = EvalWeights {
+        king_attack: 150,
+        king_safety: 80,
+        pawn_structure: 80,
+        mobility: 125,
+        development: 100,
+        center_control: 100,
+        contempt: 25,
+    };
+
+    pub const PETROSIAN: EvalWeights = EvalWeights {
+        king_attack: 80,
+        king_safety: 140,
+        pawn_structure: 130,
+        mobility: 90,
+        development: 100,
+        center_control: 100,
+        contempt: -10,
+    };
+
+    /// Profilo dell'opzione `Style` (maiuscole ignorate); None se sconosciuto
+    pub fn for_style(style: &str) -> Option<EvalWeights> {
+        match style.to_ascii_lowercase().as_str() {
+            "normal" => Some(Self::NORMAL),
+            "tal" => Some(Self::TAL),
+            "petrosian" => Some(Self::PETROSIAN),
+            _ => None,
+        }
+    }
+
+    /// Gli stessi pesi con il termine `component` spento (peso 0), per
+    /// misurarne il contributo in self-play senza ricompilare
+    pub fn without(mut self, component: EvalComponent) -> EvalWeights {
+        *self.weight_mut(component) = 0;
+        self
+    }
+
+    /// Falso se il termine `component` è stato spento con [`Self::without`]
+    pub fn is_enabled(&self, component: EvalComponent) -> bool {
+        self.without(component) != *self
+    }
+
+    fn weight_mut(&mut self, component: EvalComponent) -> &mut i32 {
+        match component {
+            EvalComponent::KingSafety => &mut self.king_safety,
+            EvalComponent::KingAttack => &mut self.king_attack,
+            EvalComponent::PawnStructure => &mut self.pawn_structure,
+            EvalComponent::Mobility => &mut self.mobility,
+            EvalComponent::Development => &mut self.development,
+            EvalComponent::CenterControl => &mut self.center_control,
+        }
+    }
+
+    /// Applica il peso `percent` a un termine
+    fn scale(value: i16, percent: i32) -> i32 {
+        value as i32 * percent / 100
+    }
+}
+
+impl Default for EvalWeights {
+    fn default() -> Self {
+        Self::NORMAL
+    }
+}
+
+/// Termini della valutazione che si possono spegnere a runtime (opzioni UCI
+/// `Eval...` in modalità tuning) per i test A/B in self-play
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EvalComponent {
+    /// Sicurezza del re, comprese le penalità critiche (-400 diritti di
+    /// arrocco persi, -200 scacco con i diritti) anche in `evaluate_fast`
+    KingSafety,
+    /// Pericolo dai pezzi avversari nella zona del re
+    KingAttack,
+    PawnStructure,
+    Mobility,
+    Development,
+    CenterControl,
+}
+
+impl EvalComponent {
+    pub const ALL: [EvalComponent; 6] = [
+        EvalComponent::KingSafety,
+        EvalComponent::KingAttack,
+        EvalComponent::PawnStructure,
+        EvalComponent::Mobility,
+        EvalComponent::Development,
+        EvalComponent::CenterControl,
+    ];
+
+    /// Nome dell'opzione UCI (check) che accende o spegne il termine
+    pub fn option_name(self) -> &'static str {
+        match self {
+            EvalComponent::KingSafety => "EvalKingSafety",
+            EvalComponent::KingAttack => "EvalKingAttack",
+            EvalComponent::PawnStructure => "EvalPawnStructure",
+            EvalComponent::Mobility => "EvalMobility",
+            EvalComponent::Development => "EvalDevelopment",
+            EvalComponent::CenterControl => "EvalCenterControl",
+        }
+    }
+
+    /// Termine dell'opzione UCI `name`; None se non è un interruttore
+    pub fn from_option_name(name: &str) -> Option<EvalComponent> {
+        Self::ALL.into_iter().find(|c| c.option_name() == name)
+    }
 }
 
 // ============================================================================
@@ -539,6 +1021,95 @@ fn development_penalty(board: &Board, color: Color) -> i16 {
 // ============================================================================
 
 
+/// Modalità studio: se manca almeno un re non c'è matto né sicurezza del re,
+/// quindi l'obiettivo è puramente materiale (dal punto di vista del side-to-move)
+fn study_score(board: &Board) -> Option<i16> {
+    if board.has_king(Color::White) && board.has_king(Color::Black) {
+        return None;
+    }
+    let relative_score = material_value(board, Color::White) - material_value(board, Color::Black);
+    Some(if board.side == Color::Black {
+        -relative_score
+    } else {
+        relative_score
+    })
+}
+
+/// Valore materiale di un colore (re escluso)
+pub(crate) fn material_value(board: &Board, color: Color) -> i16 {
+    let c = material_counts(board, color);
+    c.pawns as i16 * PAWN_VALUE
+        + c.knights as i16 * KNIGHT_VALUE
+        + c.bishops as i16 * BISHOP_VALUE
+        + c.rooks as i16 * ROOK_VALUE
+        + c.queens as i16 * QUEEN_VALUE
+}
+
+// ============================================================================
+// FINALI PATTI NOTI
+// ============================================================================
+
+/// Riconoscitore di finale patto: `true` se la posizione (con la firma
+/// materiale della tabella) non può essere vinta da nessuno dei due lati
+type DrawRecognizer = fn(&Board) -> bool;
+
+/// Firme materiali (`Board::material_key`) dei finali patti noti
+const DRAWN_ENDGAMES: [(u64, DrawRecognizer); 8] = {
+    // Conteggi [pedoni, cavalli, alfieri, torri, donne]
+    const BARE: [u32; 5] = [0; 5];
+    const N: [u32; 5] = [0, 1, 0, 0, 0];
+    const NN: [u32; 5] = [0, 2, 0, 0, 0];
+    const B: [u32; 5] = [0, 0, 1, 0, 0];
+    [
+        (material_key_from_counts(BARE, BARE), always_drawn), // K vs K
+        (material_key_from_counts(B, BARE), always_drawn),    // KB vs K
+        (material_key_from_counts(BARE, B), always_drawn),    // K vs KB
+        (material_key_from_counts(N, BARE), always_drawn),    // KN vs K
+        (material_key_from_counts(BARE, N), always_drawn),    // K vs KN
+        (material_key_from_counts(NN, BARE), always_drawn),   // KNN vs K (niente matto forzato)
+        (material_key_from_counts(BARE, NN), always_drawn),   // K vs KNN
+        (material_key_from_counts(B, B), same_colored_bishops), // KB vs KB
+    ]
+};
+
+/// Limite del rumore PSQT sommato allo zero nei finali patti
+const DRAWN_ENDGAME_NOISE: i32 = 8;
+
+fn always_drawn(_board: &Board) -> bool {
+    true
+}
+
+/// KB vs KB: patta certa solo con alfieri dello stesso colore
+fn same_colored_bishops(board: &Board) -> bool {
+    let square_color = |color| {
+        let sq = board.piece_bb(PieceKind::Bishop, color).trailing_zeros();
+        (sq / 8 + sq % 8) % 2
+    };
+    square_color(Color::White) == square_color(Color::Black)
+}
+
+/// Valutazione dei finali patti noti: zero più un piccolo termine PSQT
+/// (senza materiale), così la ricerca continua a preferire le case migliori
+/// invece di considerare tutte le mosse identiche
+fn drawn_endgame_score(board: &Board) -> Option<i16> {
+    let key = board.material_key();
+    if !DRAWN_ENDGAMES
+        .iter()
+        .any(|&(drawn_key, recognizer)| drawn_key == key && recognizer(board))
+    {
+        return None;
+    }
+
+    let positional = |color| board.psqt_score(color).1 - material_value(board, color) as i32;
+    let noise = ((positional(Color::White) - positional(Color::Black)) / 8)
+        .clamp(-DRAWN_ENDGAME_NOISE, DRAWN_ENDGAME_NOISE) as i16;
+    Some(if board.side == Color::Black {
+        -noise
+    } else {
+        noise
+    })
+}
+
 fn material_counts(board: &Board, color: Color) -> MaterialCounts {
     MaterialCounts {
         pawns: board.piece_bb(PieceKind::Pawn, color).count_ones(),
@@ -549,11 +1120,13 @@ fn material_counts(board: &Board, color: Color) -> MaterialCounts {
     }
 }
 
-fn simple_endgame_score(attacker: Color, side: Color) -> i16 {
-    if attacker == side {
-        SIMPLE_ENDGAME_BONUS
+/// Finale vinto da `attacker` (con mop-up), dal punto di vista del lato che muove
+fn won_ending_score(board: &Board, attacker: Color) -> i16 {
+    let score = endgame::won_ending(board, attacker);
+    if attacker == board.side {
+        score
     } else {
-        -SIMPLE_ENDGAME_BONUS
+        -score
     }
 }
 
@@ -561,14 +1134,30 @@ fn endgame_score(board: &Board) -> Option<i16> {
     let wc = material_counts(board, Color::White);
     let bc = material_counts(board, Color::Black);
 
-    // Existing simple signatures (KQ/KR/KNB vs K)
-    for signature in &SIMPLE_ENDGAME_SIGNATURES {
-        if wc == *signature && bc.is_empty() {
-            return Some(simple_endgame_score(Color::White, board.side));
-        }
-        if bc == *signature && wc.is_empty() {
-            return Some(simple_endgame_score(Color::Black, board.side));
+    // Specialized evaluators (KP vs K, KBN vs K, lone king vs mating material)
+    let lone_pawn = MaterialCounts {
+        pawns: 1,
+        ..MaterialCounts::default()
+    };
+    let knb = MaterialCounts {
+        knights: 1,
+        bishops: 1,
+        ..MaterialCounts::default()
+    };
+    for (strong, strong_counts, weak_counts) in [(Color::White, wc, bc), (Color::Black, bc, wc)] {
+        if !weak_counts.is_empty() {
+            continue;
         }
+        let score = if strong_counts == lone_pawn {
+            endgame::kpk(board, strong)
+        } else if strong_counts == knb {
+            endgame::kbnk(board, strong)
+        } else if endgame::has_mating_material(board, strong) {
+            endgame::won_ending(board, strong)
+        } else {
+            continue;
+        };
+        return Some(if board.side == strong { score } else { -score });
     }
 
     let k = MaterialCounts {
@@ -592,13 +1181,6 @@ fn endgame_score(board: &Board) -> Option<i16> {
         rooks: 1,
         queens: 0,
     };
-    let kbb = MaterialCounts {
-        pawns: 0,
-        knights: 0,
-        bishops: 2,
-        rooks: 0,
-        queens: 0,
-    };
     let kbp = MaterialCounts {
         pawns: 1,
         knights: 0,
@@ -613,20 +1195,6 @@ fn endgame_score(board: &Board) -> Option<i16> {
         rooks: 1,
         queens: 0,
     };
-    let krn = MaterialCounts {
-        pawns: 0,
-        knights: 1,
-        bishops: 0,
-        rooks: 1,
-        queens: 0,
-    };
-    let krb = MaterialCounts {
-        pawns: 0,
-        knights: 0,
-        bishops: 1,
-        rooks: 1,
-        queens: 0,
-    };
     let kp = MaterialCounts {
         pawns: 1,
         knights: 0,
@@ -635,32 +1203,16 @@ fn endgame_score(board: &Board) -> Option<i16> {
         queens: 0,
     };
 
-    // KBB vs K (theoretical mate)
-    if (wc == kbb && bc == k) || (bc == kbb && wc == k) {
-        let attacker = if wc == kbb { Color::White } else { Color::Black };
-        return Some(simple_endgame_score(attacker, board.side));
-    }
-
     // KQ vs KR (easy win)
     if (wc == kq && bc == kr) || (bc == kq && wc == kr) {
         let attacker = if wc == kq { Color::White } else { Color::Black };
-        return Some(simple_endgame_score(attacker, board.side));
+        return Some(won_ending_score(board, attacker));
     }
 
     // KQ vs KP (easy win)
     if (wc == kq && bc == kp) || (bc == kq && wc == kp) {
         let attacker = if wc == kq { Color::White } else { Color::Black };
-        return Some(simple_endgame_score(attacker, board.side));
-    }
-
-    // KRN vs KR, KRB vs KR (usually winning)
-    if (wc == krn && bc == kr) || (bc == krn && wc == kr) {
-        let attacker = if wc == krn { Color::White } else { Color::Black };
-        return Some(simple_endgame_score(attacker, board.side));
-    }
-    if (wc == krb && bc == kr) || (bc == krb && wc == kr) {
-        let attacker = if wc == krb { Color::White } else { Color::Black };
-        return Some(simple_endgame_score(attacker, board.side));
+        return Some(won_ending_score(board, attacker));
     }
 
     // KRP vs KR: win if pawn is advanced (rank >= 5 for white, rank <= 4 for black)
@@ -673,14 +1225,14 @@ fn endgame_score(board: &Board) -> Option<i16> {
             pawns & (crate::utils::RANK_2 | crate::utils::RANK_3 | crate::utils::RANK_4) != 0
         };
         if advanced {
-            return Some(simple_endgame_score(attacker, board.side));
+            return Some(won_ending_score(board, attacker));
         }
     }
 
     // KBP vs K: generally winning (simplified - ignores wrong-color-rook-pawn corners)
     if (wc == kbp && bc == k) || (bc == kbp && wc == k) {
         let attacker = if wc == kbp { Color::White } else { Color::Black };
-        return Some(simple_endgame_score(attacker, board.side));
+        return Some(won_ending_score(board, attacker));
     }
 
     None
@@ -697,56 +1249,35 @@ fn endgame_score(board: &Board) -> Option<i16> {
 /// NOTE: Include CRITICAL king safety penalties (castling rights loss in opening)
 /// to avoid catastrophic blunders in tactical lines
 pub fn evaluate_fast(board: &Board) -> i16 {
+    evaluate_fast_with(board, &EvalWeights::NORMAL)
+}
+
+/// Come [`evaluate_fast`]; le penalità critiche di sicurezza del re sono
+/// omesse se `weights` spegne [`EvalComponent::KingSafety`]
+pub fn evaluate_fast_with(board: &Board, weights: &EvalWeights) -> i16 {
+    if let Some(score) = study_score(board) {
+        return score;
+    }
+    if let Some(score) = drawn_endgame_score(board) {
+        return score;
+    }
     if let Some(bonus) = endgame_score(board) {
         return bonus;
     }
 
-    let mut white_score: i32 = 0;
-    let mut black_score: i32 = 0;
-
-    // Helper: iterate over set bits in a bitboard, accumulating material + PSQT
-    // For White: PSQT index = sq (a1=0 is bottom-left)
-    // For Black: PSQT index = sq ^ 56 (mirror vertically)
-
-    // --- White pieces ---
-    let pieces_and_tables: [(PieceKind, i16, &[i16; 64]); 6] = [
-        (PieceKind::Pawn, PAWN_VALUE, &PAWN_PSQT),
-        (PieceKind::Knight, KNIGHT_VALUE, &KNIGHT_PSQT),
-        (PieceKind::Bishop, BISHOP_VALUE, &BISHOP_PSQT),
-        (PieceKind::Rook, ROOK_VALUE, &ROOK_PSQT),
-        (PieceKind::Queen, QUEEN_VALUE, &QUEEN_PSQT),
-        (PieceKind::King, KING_VALUE, &KING_PSQT),
-    ];
-
-    for &(kind, material_value, psqt) in &pieces_and_tables {
-        let mut bb = board.piece_bb(kind, Color::White);
-        while bb != 0 {
-            let sq = bb.trailing_zeros() as usize;
-            white_score += material_value as i32 + psqt[sq] as i32;
-            bb &= bb - 1; // Clear lowest set bit
-        }
-    }
-
-    // --- Black pieces ---
-    for &(kind, material_value, psqt) in &pieces_and_tables {
-        let mut bb = board.piece_bb(kind, Color::Black);
-        while bb != 0 {
-            let sq = bb.trailing_zeros() as usize;
-            black_score += material_value as i32 + psqt[sq ^ 56] as i32; // Mirror for Black
-            bb &= bb - 1; // Clear lowest set bit
-        }
-    }
+    // Materiale + PSQT (middlegame) dagli accumulatori incrementali della Board
+    let mut white_score: i32 = board.psqt_score(Color::White).0;
+    let mut black_score: i32 = board.psqt_score(Color::Black).0;
 
     // CRITICAL: Apply catastrophic penalty for losing castling rights in opening
     // This is essential to prevent blunders like Qxe7?? (Issue #1)
     // We only add this penalty, not full king_safety(), to keep evaluate_fast fast
-    let white_king_penalty = king_safety_critical_only(board, Color::White);
-    let black_king_penalty = king_safety_critical_only(board, Color::Black);
-
-    white_score += white_king_penalty as i32;
-    black_score += black_king_penalty as i32;
+    if weights.is_enabled(EvalComponent::KingSafety) {
+        white_score += king_safety_critical_only(board, Color::White) as i32;
+        black_score += king_safety_critical_only(board, Color::Black) as i32;
+    }
 
-    let relative_score = (white_score - black_score) as i16;
+    let relative_score = scale_score(board, white_score - black_score) as i16;
 
     if board.side == Color::Black {
         -relative_score
@@ -755,25 +1286,33 @@ pub fn evaluate_fast(board: &Board) -> i16 {
     }
 }
 
+/// Fattore di scala (su [`endgame::SCALE_NORMAL`]) per uno score dal punto
+/// di vista del Bianco: il lato in vantaggio è quello che deve vincere
+fn scale_factor_for(board: &Board, white_score: i32) -> i32 {
+    let strong = if white_score > 0 {
+        Color::White
+    } else {
+        Color::Black
+    };
+    endgame::scale_factor(board, strong)
+}
+
+/// Riduce verso zero uno score dal punto di vista del Bianco nei finali
+/// difficili da vincere (alfieri di colore contrario, finali di torre)
+fn scale_score(board: &Board, white_score: i32) -> i32 {
+    white_score * scale_factor_for(board, white_score) / endgame::SCALE_NORMAL
+}
+
 /// Fast version of king_safety: only catastrophic penalties (no pawn shield, no dynamic penalties)
 /// Used in evaluate_fast() to keep quiescence search fast but avoid critical blunders
 fn king_safety_critical_only(board: &Board, color: Color) -> i16 {
     let mut safety = 0;
 
-    // Check if king has castled
-    let _king_sq = board.king_sq(color);
-    let castled = has_castled(board, color);
-
-    // Check if king has castling rights
-    let has_rights = has_castling_rights(board, color);
-
     // CRITICAL PENALTY: Lost castling rights in opening without castling
-    if board.fullmove >= 5 && board.fullmove < 15 && !castled && !has_rights {
-        safety -= 400; // Catastrophic penalty
-    }
+    safety -= lost_castling_share(board, color, 400); // Catastrophic penalty
 
     // CRITICAL PENALTY: King in check with castling rights (threat evaluation)
-    if is_in_check(board, color) && has_rights {
+    if is_in_check(board, color) && has_castling_rights(board, color) {
         safety -= 200; // Preventive penalty
     }
 
@@ -833,70 +1372,110 @@ fn isolated_pawns(board: &Board, color: Color) -> i16 {
     penalty
 }
 
-/// Bonus per pedoni passati (progressivo per rank)
-fn passed_pawns(board: &Board, color: Color) -> i16 {
+/// Caselle davanti a un pedone, sulla sua colonna e su quelle adiacenti
+/// (`[colore][casella]`, Bianco = 0): il pedone è passato se nessun pedone
+/// avversario le occupa
+const PASSED_SPANS: [[u64; 64]; 2] = passed_spans();
+
+const fn passed_spans() -> [[u64; 64]; 2] {
+    let mut spans = [[0u64; 64]; 2];
+    let mut sq = 0;
+    while sq < 64 {
+        let file = sq % 8;
+        let rank = sq / 8;
+        let mut files = FILE_MASKS[file];
+        if file > 0 {
+            files |= FILE_MASKS[file - 1];
+        }
+        if file < 7 {
+            files |= FILE_MASKS[file + 1];
+        }
+        let above = if rank < 7 {
+            !0u64 << ((rank + 1) * 8)
+        } else {
+            0
+        };
+        let below = if rank > 0 {
+            !0u64 >> ((8 - rank) * 8)
+        } else {
+            0
+        };
+        spans[0][sq] = files & above;
+        spans[1][sq] = files & below;
+        sq += 1;
+    }
+    spans
+}
+
+/// Bonus del pedone passato per traversa relativa (0 = prima traversa del
+/// colore), middlegame / endgame
+const PASSED_PAWN_MG: [i32; 8] = [0, 0, 0, 20, 40, 80, 150, 0];
+const PASSED_PAWN_EG: [i32; 8] = [0, 10, 10, 30, 55, 95, 165, 0];
+
+/// Per ogni casella di distanza (fino a 5) tra il re avversario e la casella
+/// davanti al pedone (endgame, moltiplicato per traversa relativa - 2)
+const PASSED_PAWN_ENEMY_KING: i32 = 5;
+/// Come sopra, in negativo, per il proprio re
+const PASSED_PAWN_OWN_KING: i32 = 2;
+
+/// Distanza tra due caselle in mosse di re
+fn king_distance(a: usize, b: usize) -> i32 {
+    let files = (a % 8).abs_diff(b % 8);
+    let ranks = (a / 8).abs_diff(b / 8);
+    files.max(ranks) as i32
+}
+
+/// Bonus per pedoni passati, interpolato con la fase di gioco:
+/// - progressivo per traversa, più alto in finale
+/// - dimezzato se un pezzo avversario blocca la casella davanti al pedone
+/// - in finale, re avversario lontano e proprio re vicino alla casella
+///   davanti al pedone contano tanto più quanto il pedone è avanzato
+fn passed_pawns(board: &Board, color: Color, phase: u8) -> i16 {
+    let (us, them) = match color {
+        Color::White => (0, Color::Black),
+        Color::Black => (1, Color::White),
+    };
     let my_pawns = board.piece_bb(PieceKind::Pawn, color);
-    let their_pawns = board.piece_bb(
-        PieceKind::Pawn,
-        match color {
-            Color::White => Color::Black,
-            Color::Black => Color::White,
-        },
-    );
-    let mut bonus = 0i16;
+    let their_pawns = board.piece_bb(PieceKind::Pawn, them);
+    let their_occ = match them {
+        Color::White => board.white_occ,
+        Color::Black => board.black_occ,
+    };
+    let kings = board.has_king(color) && board.has_king(them);
+
+    let (mut mg, mut eg) = (0i32, 0i32);
     let mut bb = my_pawns;
     while bb != 0 {
         let sq = bb.trailing_zeros() as usize;
         bb &= bb - 1;
-        let file = sq % 8;
-        let rank = sq / 8;
+        if their_pawns & PASSED_SPANS[us][sq] != 0 {
+            continue;
+        }
 
-        // Build mask of squares in front (same + adjacent files) that could block/capture
-        let mut front_mask = 0u64;
-        if color == Color::White {
-            for r in (rank + 1)..8 {
-                front_mask |= FILE_MASKS[file] << (r * 8);
-                if file > 0 {
-                    front_mask |= FILE_MASKS[file - 1] << (r * 8);
-                }
-                if file < 7 {
-                    front_mask |= FILE_MASKS[file + 1] << (r * 8);
-                }
-            }
-        } else {
-            for r in 0..rank {
-                front_mask |= FILE_MASKS[file] << (r * 8);
-                if file > 0 {
-                    front_mask |= FILE_MASKS[file - 1] << (r * 8);
-                }
-                if file < 7 {
-                    front_mask |= FILE_MASKS[file + 1] << (r * 8);
-                }
-            }
+        let (rank, stop) = match color {
+            Color::White => (sq / 8, sq + 8),
+            Color::Black => (7 - sq / 8, sq - 8),
+        };
+        let mut pawn_mg = PASSED_PAWN_MG[rank];
+        let mut pawn_eg = PASSED_PAWN_EG[rank];
+
+        if kings && rank > 2 {
+            let weight = rank as i32 - 2;
+            let their_king = king_distance(board.king_sq(them), stop).min(5);
+            let own_king = king_distance(board.king_sq(color), stop).min(5);
+            pawn_eg +=
+                (their_king * PASSED_PAWN_ENEMY_KING - own_king * PASSED_PAWN_OWN_KING) * weight;
         }
 
-        if their_pawns & front_mask == 0 {
-            let rank_bonus = if color == Color::White {
-                match rank {
-                    3 => 20,
-                    4 => 40,
-                    5 => 80,
-                    6 => 150,
-                    _ => 0,
-                }
-            } else {
-                match rank {
-                    1 => 150,
-                    2 => 80,
-                    3 => 40,
-                    4 => 20,
-                    _ => 0,
-                }
-            };
-            bonus += rank_bonus;
+        if their_occ & (1u64 << stop) != 0 {
+            pawn_mg /= 2;
+            pawn_eg /= 2;
         }
+
+        mg += pawn_mg;
+        eg += pawn_eg;
     }
-    bonus
+    taper(mg, eg, phase) as i16
 }
 
 /// Bonus per mobilità dei pezzi (cavallo +4, alfiere +3, torre +2, donna +1 per casella)
@@ -942,23 +1521,6 @@ fn mobility(board: &Board, color: Color) -> i16 {
     bonus
 }
 
-/// Calcola la fase di gioco (24 = apertura iniziale, 0 = finale puro)
-///
-/// Basato sul numero di pezzi rimasti: ogni pezzo contribuisce al "peso" della fase.
-/// Formula PeSTO: phase = sum(piece_phase_weights), max 24.
-fn game_phase(board: &Board) -> u8 {
-    let mut phase = 0u8;
-    phase += board.piece_bb(PieceKind::Queen, Color::White).count_ones() as u8 * 4;
-    phase += board.piece_bb(PieceKind::Queen, Color::Black).count_ones() as u8 * 4;
-    phase += board.piece_bb(PieceKind::Rook, Color::White).count_ones() as u8 * 2;
-    phase += board.piece_bb(PieceKind::Rook, Color::Black).count_ones() as u8 * 2;
-    phase += board.piece_bb(PieceKind::Bishop, Color::White).count_ones() as u8;
-    phase += board.piece_bb(PieceKind::Bishop, Color::Black).count_ones() as u8;
-    phase += board.piece_bb(PieceKind::Knight, Color::White).count_ones() as u8;
-    phase += board.piece_bb(PieceKind::Knight, Color::Black).count_ones() as u8;
-    phase.min(24)
-}
-
 /// Interpola tra score middlegame e endgame in base alla fase di gioco.
 ///
 /// Formula: `(mg * phase + eg * (24 - phase)) / 24`
@@ -978,81 +1540,48 @@ fn taper(mg: i32, eg: i32, phase: u8) -> i32 {
 /// # Returns
 /// Score in centipawn dal punto di vista del side-to-move
 pub fn evaluate(board: &Board) -> i16 {
+    evaluate_with(board, &EvalParams::DEFAULT, &EvalWeights::NORMAL)
+}
+
+/// Come [`evaluate`], con le costanti di `params` e i termini posizionali
+/// pesati secondo `weights` (stile di gioco)
+pub fn evaluate_with(board: &Board, params: &EvalParams, weights: &EvalWeights) -> i16 {
+    if let Some(score) = study_score(board) {
+        return score;
+    }
+    if let Some(score) = drawn_endgame_score(board) {
+        return score;
+    }
     if let Some(bonus) = endgame_score(board) {
         return bonus;
     }
 
-    let mut white_mg: i32 = 0;
-    let mut white_eg: i32 = 0;
-    let mut black_mg: i32 = 0;
-    let mut black_eg: i32 = 0;
-
-    // Iterate directly on bitboards (much faster than piece_on() for each square)
-    let piece_kinds = [
-        PieceKind::Pawn,
-        PieceKind::Knight,
-        PieceKind::Bishop,
-        PieceKind::Rook,
-        PieceKind::Queen,
-        PieceKind::King,
-    ];
-
-    for &kind in &piece_kinds {
-        let material_value = match kind {
-            PieceKind::Pawn => PAWN_VALUE,
-            PieceKind::Knight => KNIGHT_VALUE,
-            PieceKind::Bishop => BISHOP_VALUE,
-            PieceKind::Rook => ROOK_VALUE,
-            PieceKind::Queen => QUEEN_VALUE,
-            PieceKind::King => KING_VALUE,
-        };
-
-        let psqt_mg = match kind {
-            PieceKind::Pawn => &PAWN_PSQT,
-            PieceKind::Knight => &KNIGHT_PSQT,
-            PieceKind::Bishop => &BISHOP_PSQT,
-            PieceKind::Rook => &ROOK_PSQT,
-            PieceKind::Queen => &QUEEN_PSQT,
-            PieceKind::King => &KING_PSQT,
-        };
-        let psqt_eg = match kind {
-            PieceKind::Pawn => &PAWN_PSQT_EG,
-            PieceKind::Knight => &KNIGHT_PSQT_EG,
-            PieceKind::Bishop => &BISHOP_PSQT_EG,
-            PieceKind::Rook => &ROOK_PSQT_EG,
-            PieceKind::Queen => &QUEEN_PSQT_EG,
-            PieceKind::King => &KING_PSQT_EG,
-        };
-
-        // White pieces
-        let mut white_bb = board.piece_bb(kind, Color::White);
-        while white_bb != 0 {
-            let sq = white_bb.trailing_zeros() as usize;
-            white_bb &= white_bb - 1; // Clear LSB
-            white_mg += material_value as i32 + psqt_mg[sq] as i32;
-            white_eg += material_value as i32 + psqt_eg[sq] as i32;
-        }
-
-        // Black pieces (flip vertically for PSQT index)
-        let mut black_bb = board.piece_bb(kind, Color::Black);
-        while black_bb != 0 {
-            let sq = black_bb.trailing_zeros() as usize;
-            black_bb &= black_bb - 1; // Clear LSB
-            let psqt_index = sq ^ 56; // Flip verticale
-            black_mg += material_value as i32 + psqt_mg[psqt_index] as i32;
-            black_eg += material_value as i32 + psqt_eg[psqt_index] as i32;
-        }
-    }
+    // Materiale + PSQT dagli accumulatori incrementali (aggiornati in make/unmake)
+    debug_assert!(
+        board.verify_psqt(),
+        "Incremental PSQT accumulators diverged from full recompute"
+    );
+    debug_assert!(
+        board.verify_phase(),
+        "Incremental game phase diverged from full recompute"
+    );
+    let (mut white_mg, white_eg) = params.scaled_psqt(board, Color::White);
+    let (mut black_mg, black_eg) = params.scaled_psqt(board, Color::Black);
 
     // Positional components are applied to MG only for now
-    white_mg -= development_penalty(board, Color::White) as i32;
-    black_mg -= development_penalty(board, Color::Black) as i32;
+    let w = weights;
+    let development = |color| development_penalty(board, color, params);
+    white_mg -= EvalWeights::scale(development(Color::White), w.development);
+    black_mg -= EvalWeights::scale(development(Color::Black), w.development);
 
-    white_mg += king_safety(board, Color::White) as i32;
-    black_mg += king_safety(board, Color::Black) as i32;
+    let safety = |color| king_safety(board, color, params);
+    white_mg += EvalWeights::scale(safety(Color::White), w.king_safety);
+    black_mg += EvalWeights::scale(safety(Color::Black), w.king_safety);
+    white_mg -= EvalWeights::scale(king_danger(board, Color::White), w.king_attack);
+    black_mg -= EvalWeights::scale(king_danger(board, Color::Black), w.king_attack);
 
     // Taper material + PSQT from MG to EG based on game phase
-    let phase = game_phase(board);
+    let phase = board.phase();
     let mut white_score = taper(white_mg, white_eg, phase);
     let mut black_score = taper(black_mg, black_eg, phase);
 
@@ -1061,22 +1590,23 @@ pub fn evaluate(board: &Board) -> i16 {
     black_score += bishop_pair(board, Color::Black) as i32;
 
     // Pawn structure: doubled / isolated / passed
-    white_score -= doubled_pawns(board, Color::White) as i32;
-    black_score -= doubled_pawns(board, Color::Black) as i32;
-    white_score -= isolated_pawns(board, Color::White) as i32;
-    black_score -= isolated_pawns(board, Color::Black) as i32;
-    white_score += passed_pawns(board, Color::White) as i32;
-    black_score += passed_pawns(board, Color::Black) as i32;
+    let structure = |color| {
+        passed_pawns(board, color, phase)
+            - doubled_pawns(board, color)
+            - isolated_pawns(board, color)
+    };
+    white_score += EvalWeights::scale(structure(Color::White), w.pawn_structure);
+    black_score += EvalWeights::scale(structure(Color::Black), w.pawn_structure);
 
     // Mobility bonus
-    white_score += mobility(board, Color::White) as i32;
-    black_score += mobility(board, Color::Black) as i32;
+    white_score += EvalWeights::scale(mobility(board, Color::White), w.mobility);
+    black_score += EvalWeights::scale(mobility(board, Color::Black), w.mobility);
 
     // Center Control: valuta controllo delle caselle centrali
-    let center = center_control(board);
+    let center = EvalWeights::scale(center_control(board, params), w.center_control);
 
-    // Calcola lo score relativo (Bianco - Nero)
-    let relative_score = (white_score - black_score) as i16 + center;
+    // Calcola lo score relativo (Bianco - Nero), ridotto nei finali difficili da vincere
+    let relative_score = scale_score(board, white_score - black_score + center) as i16;
 
     // CRITICAL: Convenzione negamax - ritorna dal punto di vista del side-to-move
     if board.side == Color::Black {
@@ -1086,6 +1616,173 @@ pub fn evaluate(board: &Board) -> i16 {
     }
 }
 
+// ============================================================================
+// SCOMPOSIZIONE DELLA VALUTAZIONE (comando `eval`)
+// ============================================================================
+
+/// Un termine della valutazione, in centipawn dal punto di vista di ciascun
+/// colore (già interpolato tra middlegame ed endgame)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EvalTerm {
+    pub white: i32,
+    pub black: i32,
+}
+
+impl EvalTerm {
+    fn new(white: i32, black: i32) -> Self {
+        Self { white, black }
+    }
+
+    /// Contributo netto, dal punto di vista del Bianco
+    pub fn net(&self) -> i32 {
+        self.white - self.black
+    }
+}
+
+/// Valutazione statica voce per voce, prodotta da [`evaluate_trace`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EvalTrace {
+    /// Fase di gioco (24 = apertura, 0 = finale)
+    pub phase: u8,
+    /// Score deciso da un riconoscitore (study mode, finale patto o noto):
+    /// in quel caso i termini sotto non vengono calcolati
+    pub recognizer: Option<i16>,
+    pub material: EvalTerm,
+    pub psqt: EvalTerm,
+    pub development: EvalTerm,
+    pub king_safety: EvalTerm,
+    pub king_danger: EvalTerm,
+    pub bishop_pair: EvalTerm,
+    pub pawn_structure: EvalTerm,
+    pub mobility: EvalTerm,
+    pub center_control: EvalTerm,
+    /// Parte della somma dei termini mantenuta (su [`endgame::SCALE_NORMAL`])
+    /// nei finali difficili da vincere
+    pub scale: i32,
+    /// Valutazione dal punto di vista del lato che muove, identica a
+    /// [`evaluate_with`] (la somma dei termini può differire di qualche
+    /// centipawn per l'arrotondamento dell'interpolazione)
+    pub total: i16,
+}
+
+impl EvalTrace {
+    /// Termini con il loro nome, nell'ordine in cui la valutazione li somma
+    pub fn terms(&self) -> [(&'static str, EvalTerm); 9] {
+        [
+            ("Material", self.material),
+            ("PSQT", self.psqt),
+            ("Development", self.development),
+            ("King safety", self.king_safety),
+            ("King danger", self.king_danger),
+            ("Bishop pair", self.bishop_pair),
+            ("Pawn structure", self.pawn_structure),
+            ("Mobility", self.mobility),
+            ("Center control", self.center_control),
+        ]
+    }
+}
+
+impl std::fmt::Display for EvalTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "          Term |  White |  Black |    Net")?;
+        writeln!(f, "---------------+--------+--------+-------")?;
+        for (name, term) in self.terms() {
+            writeln!(
+                f,
+                "{:>14} | {:>6} | {:>6} | {:>6}",
+                name,
+                term.white,
+                term.black,
+                term.net()
+            )?;
+        }
+        writeln!(f, "---------------+--------+--------+-------")?;
+        if let Some(score) = self.recognizer {
+            writeln!(f, "Recognized endgame or study score: {}", score)?;
+        }
+        writeln!(f, "Phase: {}/24", self.phase)?;
+        writeln!(f, "Scale: {}/{}", self.scale, endgame::SCALE_NORMAL)?;
+        write!(f, "Total (side to move): {}", self.total)
+    }
+}
+
+/// Scompone la valutazione di [`evaluate_with`] nei suoi termini per colore.
+///
+/// I termini solo-middlegame (sviluppo, sicurezza e pericolo del Re) sono
+/// interpolati con la fase di gioco come nella valutazione vera; il totale
+/// è quello di [`evaluate_with`].
+pub fn evaluate_trace(board: &Board, params: &EvalParams, weights: &EvalWeights) -> EvalTrace {
+    let phase = board.phase();
+    let total = evaluate_with(board, params, weights);
+    let recognizer = study_score(board)
+        .or_else(|| drawn_endgame_score(board))
+        .or_else(|| endgame_score(board));
+    let mut trace = EvalTrace {
+        phase,
+        recognizer,
+        material: EvalTerm::default(),
+        psqt: EvalTerm::default(),
+        development: EvalTerm::default(),
+        king_safety: EvalTerm::default(),
+        king_danger: EvalTerm::default(),
+        bishop_pair: EvalTerm::default(),
+        pawn_structure: EvalTerm::default(),
+        mobility: EvalTerm::default(),
+        center_control: EvalTerm::default(),
+        scale: endgame::SCALE_NORMAL,
+        total,
+    };
+    if recognizer.is_some() {
+        return trace;
+    }
+
+    let w = weights;
+    let mg_only = |f: &dyn Fn(Color) -> i32| {
+        EvalTerm::new(
+            taper(f(Color::White), 0, phase),
+            taper(f(Color::Black), 0, phase),
+        )
+    };
+    let per_color = |f: &dyn Fn(Color) -> i32| EvalTerm::new(f(Color::White), f(Color::Black));
+
+    let material = |color| material_value(board, color) as i32;
+    trace.material = per_color(&material);
+    trace.psqt = per_color(&|color| {
+        // Gli accumulatori contengono anche materiale e re
+        let (mg, eg) = params.scaled_psqt(board, color);
+        let base = material(color)
+            + if board.has_king(color) {
+                KING_VALUE as i32
+            } else {
+                0
+            };
+        taper(mg - base, eg - base, phase)
+    });
+    trace.development = mg_only(&|color| {
+        -EvalWeights::scale(development_penalty(board, color, params), w.development)
+    });
+    trace.king_safety =
+        mg_only(&|color| EvalWeights::scale(king_safety(board, color, params), w.king_safety));
+    trace.king_danger =
+        mg_only(&|color| -EvalWeights::scale(king_danger(board, color), w.king_attack));
+    trace.bishop_pair = per_color(&|color| bishop_pair(board, color) as i32);
+    trace.pawn_structure = per_color(&|color| {
+        let structure = passed_pawns(board, color, phase)
+            - doubled_pawns(board, color)
+            - isolated_pawns(board, color);
+        EvalWeights::scale(structure, w.pawn_structure)
+    });
+    trace.mobility = per_color(&|color| EvalWeights::scale(mobility(board, color), w.mobility));
+    let (white_center, black_center) = center_control_by_color(board, params);
+    trace.center_control = EvalTerm::new(
+        EvalWeights::scale(white_center, w.center_control),
+        EvalWeights::scale(black_center, w.center_control),
+    );
+    let net: i32 = trace.terms().iter().map(|(_, term)| term.net()).sum();
+    trace.scale = scale_factor_for(board, net);
+    trace
+}
+
 // ============================================================================
 // TESTS
 // ============================================================================
@@ -1136,14 +1833,15 @@ mod tests {
     #[test]
     fn test_evaluate_central_pawn_bonus() {
         // Testa che un pedone centrale (e4) abbia bonus rispetto a un pedone laterale
+        // (con una coppia di pedoni in più, altrimenti decide la bitbase KPK)
         let mut board_central = Board::new();
         board_central
-            .set_from_fen("4k3/8/8/8/4P3/8/8/4K3 w - - 0 1")
+            .set_from_fen("4k3/7p/8/8/4P3/8/7P/4K3 w - - 0 1")
             .unwrap();
 
         let mut board_edge = Board::new();
         board_edge
-            .set_from_fen("4k3/8/8/8/P7/8/8/4K3 w - - 0 1")
+            .set_from_fen("4k3/7p/8/8/P7/8/7P/4K3 w - - 0 1")
             .unwrap();
 
         let score_central = evaluate(&board_central);
@@ -1198,36 +1896,84 @@ mod tests {
     }
 
     #[test]
-    fn test_simple_endgame_kq_vs_k() {
+    fn test_endgame_kq_vs_k() {
         let mut board = Board::new();
         board
             .set_from_fen("4k3/8/8/8/8/8/8/4K2Q w - - 0 1")
             .unwrap();
-        assert_eq!(evaluate(&board), SIMPLE_ENDGAME_BONUS);
+        assert!(evaluate(&board) > endgame::KNOWN_WIN);
 
         let mut board_black_to_move = Board::new();
         board_black_to_move
             .set_from_fen("4k3/8/8/8/8/8/8/4K2Q b - - 0 1")
             .unwrap();
-        assert_eq!(evaluate(&board_black_to_move), -SIMPLE_ENDGAME_BONUS);
+        assert_eq!(evaluate(&board_black_to_move), -evaluate(&board));
+    }
+
+    #[test]
+    fn test_endgame_kr_vs_k_drives_king_to_edge() {
+        let mut center = Board::new();
+        center
+            .set_from_fen("8/8/8/3k4/8/8/8/4K2R w - - 0 1")
+            .unwrap();
+        let mut edge = Board::new();
+        edge.set_from_fen("4k3/8/8/8/8/8/8/4K2R w - - 0 1").unwrap();
+        assert!(evaluate(&center) > endgame::KNOWN_WIN);
+        assert!(evaluate(&edge) > evaluate(&center));
     }
 
     #[test]
-    fn test_simple_endgame_kr_vs_k() {
+    fn test_endgame_kp_vs_k_uses_bitbase() {
+        // Re davanti al pedone: decide l'opposizione
         let mut board = Board::new();
         board
-            .set_from_fen("4k3/8/8/8/8/8/8/4K2R w - - 0 1")
+            .set_from_fen("8/4k3/8/4K3/4P3/8/8/8 b - - 0 1")
+            .unwrap();
+        assert!(evaluate(&board) < -endgame::KNOWN_WIN);
+        board
+            .set_from_fen("8/4k3/8/4K3/4P3/8/8/8 w - - 0 1")
             .unwrap();
-        assert_eq!(evaluate(&board), SIMPLE_ENDGAME_BONUS);
+        assert_eq!(evaluate(&board), 0);
     }
 
     #[test]
-    fn test_simple_endgame_knb_vs_k() {
+    fn test_endgame_knb_vs_k() {
         let mut board = Board::new();
         board
             .set_from_fen("4k3/8/8/8/8/8/4B1N1/4K3 w - - 0 1")
             .unwrap();
-        assert_eq!(evaluate(&board), SIMPLE_ENDGAME_BONUS);
+        assert!(evaluate(&board) > endgame::KNOWN_WIN);
+    }
+
+    #[test]
+    fn test_drawish_material_is_scaled_toward_zero() {
+        // R+A contro T senza pedoni: patta quasi sempre
+        let mut board = Board::new();
+        board
+            .set_from_fen("4k3/8/8/8/3r4/8/8/R1B1K3 w - - 0 1")
+            .unwrap();
+        let score = evaluate(&board);
+        assert!(score > 0 && score < BISHOP_VALUE / 4, "{score}");
+
+        // Alfieri di colore contrario con un pedone in più
+        board
+            .set_from_fen("4k3/8/8/3b4/2P5/5P2/8/2B1K3 w - - 0 1")
+            .unwrap();
+        assert!(evaluate(&board) < PAWN_VALUE / 2);
+    }
+
+    #[test]
+    fn test_endgame_kq_vs_kr_has_mop_up_gradient() {
+        let mut center = Board::new();
+        center
+            .set_from_fen("8/8/3r4/3k4/8/8/8/4K2Q w - - 0 1")
+            .unwrap();
+        let mut corner = Board::new();
+        corner
+            .set_from_fen("k7/1r6/2K5/8/8/8/8/7Q w - - 0 1")
+            .unwrap();
+        assert!(evaluate(&center) > endgame::KNOWN_WIN);
+        assert!(evaluate(&corner) > evaluate(&center));
     }
 
     #[test]
@@ -1236,8 +1982,8 @@ mod tests {
         let mut board = Board::new();
         board.set_from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
 
-        let white_safety = king_safety(&board, Color::White);
-        let black_safety = king_safety(&board, Color::Black);
+        let white_safety = king_safety(&board, Color::White, &EvalParams::DEFAULT);
+        let black_safety = king_safety(&board, Color::Black, &EvalParams::DEFAULT);
 
         // Entrambi i Re sono al centro (e1, e8) e non arrocati: -50 cp
         assert_eq!(
@@ -1258,7 +2004,7 @@ mod tests {
             .set_from_fen("4k3/8/8/8/8/8/5PPP/6K1 w - - 0 1")
             .unwrap();
 
-        let white_safety = king_safety(&board, Color::White);
+        let white_safety = king_safety(&board, Color::White, &EvalParams::DEFAULT);
 
         // Re in g1 (arrocato), 3 pedoni in f2,g2,h2: +45 cp
         assert_eq!(
@@ -1273,7 +2019,7 @@ mod tests {
         let mut board = Board::new();
         board.set_from_fen("4k3/8/8/8/8/8/8/6K1 w - - 0 1").unwrap();
 
-        let white_safety = king_safety(&board, Color::White);
+        let white_safety = king_safety(&board, Color::White, &EvalParams::DEFAULT);
 
         // Re in g1 (arrocato), nessun pedone scudo: 0 cp
         assert_eq!(
@@ -1290,7 +2036,7 @@ mod tests {
             .set_from_fen("4k3/8/8/8/8/8/5PP1/6K1 w - - 0 1")
             .unwrap();
 
-        let white_safety = king_safety(&board, Color::White);
+        let white_safety = king_safety(&board, Color::White, &EvalParams::DEFAULT);
 
         // Re in g1 (arrocato), 2 pedoni in f2,g2: +30 cp (2×15)
         assert_eq!(
@@ -1307,7 +2053,7 @@ mod tests {
             .set_from_fen("4k3/8/8/8/8/8/1PPP4/2K5 w - - 0 1")
             .unwrap();
 
-        let white_safety = king_safety(&board, Color::White);
+        let white_safety = king_safety(&board, Color::White, &EvalParams::DEFAULT);
 
         // Re in c1 (arrocato lungo), 3 pedoni in b2,c2,d2: +45 cp
         assert_eq!(
@@ -1325,7 +2071,7 @@ mod tests {
             .set_from_fen("r5k1/pp2qppp/1n1p4/2pPb3/2P1P3/2N2N2/PP2BPPP/R1B2K1R b - - 1 14")
             .unwrap();
 
-        let white_safety = king_safety(&board, Color::White);
+        let white_safety = king_safety(&board, Color::White, &EvalParams::DEFAULT);
 
         // Re in f1 (file 5, non centro), fullmove=14, no castling rights, not castled
         // - Penalità -120 per perdita diritto arrocco in apertura (FIX v0.4.0: era -70)
@@ -1343,7 +2089,8 @@ mod tests {
             .set_from_fen("r5k1/pp2qppp/1n1p4/2pPb3/2P1P3/2N2N2/PP2BPPP/R1B2K1R b KQ - 1 14")
             .unwrap();
 
-        let white_safety_with_rights = king_safety(&board_with_rights, Color::White);
+        let white_safety_with_rights =
+            king_safety(&board_with_rights, Color::White, &EvalParams::DEFAULT);
 
         // Con diritti di arrocco, non si applica la penalità -70
         assert!(
@@ -1352,6 +2099,70 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_opening_penalties_ignore_the_move_counter() {
+        crate::init();
+        // La stessa posizione con un numero di mossa qualsiasi (FEN arbitraria
+        // o trasposizione) ha la stessa valutazione
+        let fens = [
+            "r5k1/pp2qppp/1n1p4/2pPb3/2P1P3/2N2N2/PP2BPPP/R1B2K1R b - - 1 {}",
+            "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPPKPPP/RNBQ1BNR b kq - 1 {}",
+            "r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 {}",
+        ];
+        for fen in fens {
+            let mut early = Board::new();
+            early.set_from_fen(&fen.replace("{}", "2")).unwrap();
+            let mut late = Board::new();
+            late.set_from_fen(&fen.replace("{}", "250")).unwrap();
+            assert_eq!(evaluate(&early), evaluate(&late), "{}", fen);
+            assert_eq!(evaluate_fast(&early), evaluate_fast(&late), "{}", fen);
+        }
+
+        // Re che ha perso l'arrocco alla mossa 2 (2.Ke2): finché i pezzi
+        // neri sono a casa nessuno lo attacca
+        let mut board = Board::new();
+        board.set_from_fen(&fens[1].replace("{}", "2")).unwrap();
+        assert_eq!(king_safety_critical_only(&board, Color::White), 0);
+
+        // Con Cc6 fuori: penalità piena, anche nella valutazione veloce
+        board
+            .set_from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/8/PPPPKPPP/RNBQ1BNR w kq - 2 3")
+            .unwrap();
+        let params = EvalParams::DEFAULT;
+        assert!(king_safety(&board, Color::White, &params) < -params.lost_castling_penalty);
+        assert!(king_safety(&board, Color::Black, &params) > -200);
+        assert_eq!(king_safety_critical_only(&board, Color::White), -400);
+
+        // Mediogioco: il Re bianco è andato in f2 a partita inoltrata, con
+        // tutti i pezzi in gioco ma la struttura pedonale ormai cambiata
+        board
+            .set_from_fen("r2q1rk1/1b2bppp/p2p1n2/1p2p3/P2nP1P1/1BN1BP2/1PPQ1K1P/R6R w - - 0 18")
+            .unwrap();
+        assert!(board.phase() as i32 >= OPENING_PHASE);
+        assert_eq!(king_safety_critical_only(&board, Color::White), 0);
+
+        // FEN senza diritti d'arrocco con Re e Torri a casa: nessuna perdita
+        board
+            .set_from_fen("r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w - - 4 4")
+            .unwrap();
+        assert_eq!(king_safety_critical_only(&board, Color::White), 0);
+        assert_eq!(king_safety_critical_only(&board, Color::Black), 0);
+
+        // Finale: il re al centro senza diritti non è un difetto
+        board
+            .set_from_fen("4k3/pp3ppp/8/8/8/8/PP3PPP/4K2R w - - 0 3")
+            .unwrap();
+        assert_eq!(king_safety_critical_only(&board, Color::White), 0);
+        assert_eq!(opening_share(&board, 400), 0);
+
+        // In mezzo la penalità cala con il materiale
+        board
+            .set_from_fen("r3k2r/pp3ppp/2n5/8/8/2N5/PP3PPP/R3K2R w - - 0 3")
+            .unwrap();
+        assert_eq!(board.phase(), 10);
+        assert_eq!(opening_share(&board, 400), 100);
+    }
+
     #[test]
     fn test_king_safety_center_with_active_pieces() {
         // Re al centro (e1) con molti pezzi avversari attivi → penalità aumentata
@@ -1360,7 +2171,7 @@ mod tests {
             .set_from_fen("rnbq1rk1/pppppppp/8/8/8/8/PPPPPPPP/RNBQK2R w KQ - 0 10")
             .unwrap();
 
-        let white_safety = king_safety(&board, Color::White);
+        let white_safety = king_safety(&board, Color::White, &EvalParams::DEFAULT);
 
         // Re in e1 (centro), non arrocato, fullmove=10, ha diritti di arrocco (KQ)
         // Pezzi neri attivi dalla FEN: 1N + 1B + 2R + 1Q = 5 pezzi
@@ -1379,7 +2190,7 @@ mod tests {
             .set_from_fen("4k3/8/8/8/8/8/PPPPPPPP/RNBQK2R w KQ - 0 10")
             .unwrap();
 
-        let white_safety_few = king_safety(&board_few_pieces, Color::White);
+        let white_safety_few = king_safety(&board_few_pieces, Color::White, &EvalParams::DEFAULT);
 
         // Con 0 pezzi avversari: penalità -50 * (1 + 0/6) = -50, bonus +45 pedoni = -5 cp
         assert!(
@@ -1455,6 +2266,91 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_king_danger_no_attackers() {
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+            .unwrap();
+        assert_eq!(king_danger(&board, Color::White), 0);
+        assert_eq!(king_danger(&board, Color::Black), 0);
+    }
+
+    #[test]
+    fn test_king_danger_grows_with_attackers() {
+        crate::init();
+        // Re nero arroccato, solo la donna bianca punta sull'arrocco: nessun attacco
+        let mut one = Board::new();
+        one.set_from_fen("r4rk1/ppp2ppp/8/8/8/3B4/PPP2PPP/4RRK1 w - - 0 1")
+            .unwrap();
+        // Donna, alfiere e cavallo bianchi contro l'arrocco nero
+        let mut many = Board::new();
+        many.set_from_fen("r4rk1/ppp2ppp/8/6N1/7Q/3B4/PPP2PPP/4RRK1 w - - 0 1")
+            .unwrap();
+        let d_one = king_danger(&one, Color::Black);
+        let d_many = king_danger(&many, Color::Black);
+        assert_eq!(d_one, 0, "A single attacker should not count as an attack");
+        assert!(
+            d_many > 50,
+            "Concentrated attack should be dangerous, got {}",
+            d_many
+        );
+        assert!(evaluate(&many) > evaluate(&one));
+    }
+
+    #[test]
+    fn test_study_position_material_only() {
+        crate::init();
+        // Nessun re nero: valutazione solo materiale (torre contro alfiere)
+        let mut board = Board::new();
+        board
+            .set_from_fen("8/8/8/3b4/8/8/8/K2R4 w - - 0 1")
+            .unwrap();
+        assert_eq!(evaluate(&board), ROOK_VALUE - BISHOP_VALUE);
+        assert_eq!(evaluate_fast(&board), ROOK_VALUE - BISHOP_VALUE);
+
+        board
+            .set_from_fen("8/8/8/3b4/8/8/8/K2R4 b - - 0 1")
+            .unwrap();
+        assert_eq!(evaluate(&board), BISHOP_VALUE - ROOK_VALUE);
+    }
+
+    #[test]
+    fn test_drawn_endgames_score_near_zero() {
+        crate::init();
+        let near_zero = |fen: &str| {
+            let mut board = Board::new();
+            board.set_from_fen(fen).unwrap();
+            let (full, fast) = (evaluate(&board), evaluate_fast(&board));
+            assert!(
+                full.abs() <= DRAWN_ENDGAME_NOISE as i16 && fast == full,
+                "{}: evaluate {} evaluate_fast {}",
+                fen,
+                full,
+                fast
+            );
+        };
+        near_zero("8/8/4k3/8/8/2NN4/8/4K3 w - - 0 1"); // KNN vs K
+        near_zero("4k3/8/2nn4/8/8/8/8/4K3 b - - 0 1"); // K vs KNN
+        near_zero("8/8/4k3/8/8/2N5/8/4K3 b - - 0 1"); // KN vs K
+        near_zero("8/8/4k3/8/8/8/8/4K3 w - - 0 1"); // K vs K
+        near_zero("8/8/4k3/8/8/2B5/8/4K3 w - - 0 1"); // KB vs K
+        near_zero("4k3/8/8/3b4/8/8/8/4K3 w - - 0 1"); // K vs KB
+        near_zero("4k3/8/8/3b4/8/8/2B5/4K3 w - - 0 1"); // KB vs KB, stesso colore
+
+        // Alfieri di colore opposto e KNN vs KP non sono riconosciuti
+        let mut board = Board::new();
+        board
+            .set_from_fen("4k3/8/8/3b4/8/8/3B4/4K3 w - - 0 1")
+            .unwrap();
+        assert_eq!(drawn_endgame_score(&board), None);
+        board
+            .set_from_fen("8/4p3/4k3/8/8/2NN4/8/4K3 w - - 0 1")
+            .unwrap();
+        assert_eq!(drawn_endgame_score(&board), None);
+    }
+
     #[test]
     fn test_bishop_pair_bonus() {
         let mut board_pair = Board::new();
@@ -1491,7 +2387,7 @@ mod tests {
     fn test_isolated_pawns_penalty() {
         let mut board_isolated = Board::new();
         board_isolated
-            .set_from_fen("4k3/8/8/8/4P3/8/8/4K3 w - - 0 1")
+            .set_from_fen("4k3/7p/8/8/4P3/8/7P/4K3 w - - 0 1")
             .unwrap();
         let mut board_supported = Board::new();
         board_supported
@@ -1519,6 +2415,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_passed_pawns_use_front_spans_for_both_colors() {
+        let board = |fen: &str| {
+            let mut board = Board::new();
+            board.set_from_fen(fen).unwrap();
+            board
+        };
+        // Il pedone bianco in e6 è dietro al pedone nero in d4: entrambi passati
+        let b = board("4k3/8/4P3/8/3p4/8/8/4K3 b - - 0 1");
+        assert!(passed_pawns(&b, Color::Black, 0) > 0);
+        assert!(passed_pawns(&b, Color::White, 0) > 0);
+        // Un pedone bianco in c2 (colonna adiacente, davanti) ferma il nero
+        let b = board("4k3/8/8/8/3p4/8/2P5/4K3 b - - 0 1");
+        assert_eq!(passed_pawns(&b, Color::Black, 0), 0);
+        // Stesso pedone, con i colori scambiati: stesso bonus
+        let white = board("8/4k3/8/3P4/8/8/8/4K3 w - - 0 1");
+        let black = board("4k3/8/8/8/3p4/8/4K3/8 b - - 0 1");
+        assert_eq!(
+            passed_pawns(&white, Color::White, 0),
+            passed_pawns(&black, Color::Black, 0)
+        );
+    }
+
+    #[test]
+    fn test_passed_pawns_blockade_and_king_distance() {
+        let passed = |fen: &str| {
+            let mut board = Board::new();
+            board.set_from_fen(fen).unwrap();
+            passed_pawns(&board, Color::White, board.phase())
+        };
+        // Cavallo nero davanti al pedone: bonus dimezzato
+        let free = passed("7k/8/8/3P4/8/8/8/K5n1 w - - 0 1");
+        let blocked = passed("7k/8/3n4/3P4/8/8/8/K7 w - - 0 1");
+        assert!(blocked < free, "blocked={blocked}, free={free}");
+
+        // In finale conta dove sono i re
+        let kings_favor = passed("k7/8/8/3P4/3K4/8/8/8 w - - 0 1");
+        let kings_against = passed("8/8/3k4/3P4/8/8/8/7K w - - 0 1");
+        assert!(kings_favor > kings_against + 50);
+
+        // Più avanzato = più prezioso
+        assert!(passed("7k/3P4/8/8/8/8/8/K7 w - - 0 1") > passed("7k/8/8/3P4/8/8/8/K7 w - - 0 1"));
+    }
+
     #[test]
     fn test_mobility_bonus() {
         let mut board_center = Board::new();
@@ -1538,9 +2478,194 @@ mod tests {
     #[test]
     fn test_endgame_kbb_vs_k() {
         let mut board = Board::new();
+        board
+            .set_from_fen("4k3/8/8/8/8/8/4BB2/4K3 w - - 0 1")
+            .unwrap();
+        assert!(evaluate(&board) > endgame::KNOWN_WIN);
+
+        // Alfieri dello stesso colore: niente matto
         board
             .set_from_fen("4k3/8/8/8/8/8/4B1B1/4K3 w - - 0 1")
             .unwrap();
-        assert_eq!(evaluate(&board), SIMPLE_ENDGAME_BONUS);
+        assert!(evaluate(&board) < endgame::KNOWN_WIN);
+    }
+
+    #[test]
+    fn test_eval_params_change_evaluation() {
+        let mut board = Board::new();
+        board
+            .set_from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4")
+            .unwrap();
+        let normal = EvalWeights::NORMAL;
+        let base = evaluate_with(&board, &EvalParams::DEFAULT, &normal);
+        assert_eq!(base, evaluate(&board));
+
+        // Ogni spin corrisponde a un campo; i valori vengono limitati all'intervallo
+        let mut params = EvalParams::DEFAULT;
+        for spin in &EVAL_PARAM_SPINS {
+            assert!(params.get(spin.name).is_some(), "{}", spin.name);
+            assert!(params.set(spin.name, spin.max + 1));
+            assert_eq!(params.get(spin.name), Some(spin.max));
+        }
+        assert!(!params.set("Inesistente", 1));
+
+        // Senza la parte posizionale delle PSQT la valutazione cambia
+        let mut flat = EvalParams::DEFAULT;
+        flat.set("PsqtScale", 0);
+        assert_ne!(evaluate_with(&board, &flat, &normal), base);
+        let mut no_center = EvalParams::DEFAULT;
+        no_center.set("CenterControl", 0);
+        no_center.set("ExtendedCenterControl", 0);
+        assert_eq!(center_control(&board, &no_center), 0);
+    }
+
+    #[test]
+    fn test_style_weights() {
+        assert_eq!(EvalWeights::for_style("tal"), Some(EvalWeights::TAL));
+        assert_eq!(
+            EvalWeights::for_style("Petrosian"),
+            Some(EvalWeights::PETROSIAN)
+        );
+        assert_eq!(EvalWeights::for_style("Karpov"), None);
+
+        // Normal non cambia nulla; ogni stile rispetta la convenzione negamax
+        let fens = [
+            "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4",
+            "2r3k1/pp3ppp/2n1b3/q2pP3/3P4/P1PB1N2/5PPP/R2Q2K1 b - - 0 18",
+        ];
+        for fen in fens {
+            let mut board = Board::new();
+            board.set_from_fen(fen).unwrap();
+            assert_eq!(
+                evaluate_with(&board, &EvalParams::DEFAULT, &EvalWeights::NORMAL),
+                evaluate(&board)
+            );
+            for weights in [EvalWeights::TAL, EvalWeights::PETROSIAN] {
+                let mut flipped = board.clone();
+                flipped.side = match board.side {
+                    Color::White => Color::Black,
+                    Color::Black => Color::White,
+                };
+                assert_eq!(
+                    evaluate_with(&board, &EvalParams::DEFAULT, &weights),
+                    -evaluate_with(&flipped, &EvalParams::DEFAULT, &weights)
+                );
+            }
+        }
+
+        // Tal premia l'attacco al re più di Petrosian
+        let mut board = Board::new();
+        board
+            .set_from_fen("r4rk1/ppp2p1p/6pQ/3N4/8/8/PPP2PPP/2KR3R w - - 0 1")
+            .unwrap();
+        assert!(
+            evaluate_with(&board, &EvalParams::DEFAULT, &EvalWeights::TAL)
+                > evaluate_with(&board, &EvalParams::DEFAULT, &EvalWeights::PETROSIAN)
+        );
+    }
+
+    #[test]
+    fn test_disabled_components_drop_out_of_the_evaluation() {
+        crate::init();
+        let params = EvalParams::DEFAULT;
+        // Re bianco in e2 alla mossa 5: diritti di arrocco persi senza arroccare
+        let mut board = Board::new();
+        board
+            .set_from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPPKPPP/RNBQ1B1R b kq - 3 5")
+            .unwrap();
+
+        for component in EvalComponent::ALL {
+            let weights = EvalWeights::NORMAL.without(component);
+            assert!(!weights.is_enabled(component));
+            assert!(EvalWeights::NORMAL.is_enabled(component));
+            assert_eq!(
+                EvalComponent::from_option_name(component.option_name()),
+                Some(component)
+            );
+
+            let trace = evaluate_trace(&board, &params, &weights);
+            let term = match component {
+                EvalComponent::KingSafety => trace.king_safety,
+                EvalComponent::KingAttack => trace.king_danger,
+                EvalComponent::PawnStructure => trace.pawn_structure,
+                EvalComponent::Mobility => trace.mobility,
+                EvalComponent::Development => trace.development,
+                EvalComponent::CenterControl => trace.center_control,
+            };
+            assert_eq!(term, EvalTerm::default(), "{:?}", component);
+        }
+
+        // Senza sicurezza del re sparisce anche la penalità critica di
+        // evaluate_fast (-400 al Bianco, quindi +400 per il Nero al tratto)
+        let no_safety = EvalWeights::NORMAL.without(EvalComponent::KingSafety);
+        assert_eq!(
+            evaluate_fast(&board) - evaluate_fast_with(&board, &no_safety),
+            400
+        );
+        assert!(
+            evaluate_with(&board, &params, &EvalWeights::NORMAL)
+                > evaluate_with(&board, &params, &no_safety)
+        );
+    }
+
+    #[test]
+    fn test_evaluate_trace_adds_up_to_the_evaluation() {
+        crate::init();
+        let params = EvalParams::DEFAULT;
+        for (fen, weights) in [
+            (
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1",
+                EvalWeights::NORMAL,
+            ),
+            (
+                "r4rk1/ppp2p1p/6pQ/3N4/8/8/PPP2PPP/2KR3R w - - 0 1",
+                EvalWeights::TAL,
+            ),
+            (
+                "8/5k2/3p4/1p1P4/1P6/2K5/8/8 b - - 0 40",
+                EvalWeights::PETROSIAN,
+            ),
+        ] {
+            let mut board = Board::new();
+            board.set_from_fen(fen).unwrap();
+            let trace = evaluate_trace(&board, &params, &weights);
+            assert_eq!(trace.total, evaluate_with(&board, &params, &weights));
+            assert_eq!(trace.phase, board.phase());
+            assert!(trace.recognizer.is_none());
+
+            // Tapering each term apart only changes the rounding
+            let net: i32 = trace.terms().iter().map(|(_, t)| t.net()).sum();
+            let white_view = net * trace.scale / endgame::SCALE_NORMAL;
+            let sum = if board.side == Color::White {
+                white_view
+            } else {
+                -white_view
+            };
+            assert!((sum - trace.total as i32).abs() <= 4, "{}: {}", fen, trace);
+        }
+
+        // Posizione iniziale: ogni termine è simmetrico
+        let mut board = Board::new();
+        board
+            .set_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+            .unwrap();
+        let trace = evaluate_trace(&board, &params, &EvalWeights::NORMAL);
+        assert_eq!(trace.material, EvalTerm::new(4000, 4000));
+        for (name, term) in trace.terms() {
+            assert_eq!(term.net(), 0, "{}", name);
+        }
+        assert!(trace.to_string().contains("Total (side to move): "));
+    }
+
+    #[test]
+    fn test_evaluate_trace_reports_recognized_endgames() {
+        let mut board = Board::new();
+        board
+            .set_from_fen("8/8/8/4k3/8/8/8/3QK3 w - - 0 1")
+            .unwrap();
+        let trace = evaluate_trace(&board, &EvalParams::DEFAULT, &EvalWeights::NORMAL);
+        assert_eq!(trace.recognizer, Some(evaluate(&board)));
+        assert_eq!(trace.material, EvalTerm::default());
+        assert!(trace.to_string().contains("Recognized endgame"));
     }
 }
@@ -0,0 +1,383 @@
+//! Experience book: what the engine learned from its own games.
+//!
+//! After every search the UCI loop records the root position (Zobrist key),
+//! the move played, its score and depth. When the game ends the result is
+//! attached to each of those records from the point of view of the side that
+//! moved, and the records are merged into the book. In later games the book
+//! move of a position is searched first, and a move that was searched deeply
+//! in a game that was not lost is played almost instantly (book selection).
+//!
+//! On disk the book is a magic header followed by fixed-size little-endian
+//! records: position ([`Board::encode`]), move (u32), score (i16), depth (u8),
+//! outcome (u8). The Zobrist key is recomputed from the position on load, so
+//! a book does not depend on the hashing scheme of the engine that wrote it.
+
+use crate::board::{Board, Color, GameResult, Move, PackedBoard, PACKED_BOARD_SIZE};
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+/// File header: format name and version
+pub const EXPERIENCE_MAGIC: [u8; 8] = *b"SCXPBK02";
+
+/// Size in bytes of one record on disk
+pub const EXPERIENCE_ENTRY_SIZE: usize = PACKED_BOARD_SIZE + 8;
+
+/// Minimum depth of a record to be played as a book move without searching
+pub const EXPERIENCE_BOOK_DEPTH: u8 = 12;
+
+/// Game result for the side to move in the recorded position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Win,
+    Draw,
+    Loss,
+    /// The game was abandoned or its result never reached the engine
+    Unknown,
+}
+
+impl Outcome {
+    fn to_byte(self) -> u8 {
+        match self {
+            Outcome::Win => 0,
+            Outcome::Draw => 1,
+            Outcome::Loss => 2,
+            Outcome::Unknown => 3,
+        }
+    }
+
+    fn from_byte(b: u8) -> io::Result<Self> {
+        match b {
+            0 => Ok(Outcome::Win),
+            1 => Ok(Outcome::Draw),
+            2 => Ok(Outcome::Loss),
+            3 => Ok(Outcome::Unknown),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "invalid experience outcome",
+            )),
+        }
+    }
+
+    /// Outcome for `side` of a game that ended with `result` (None = unknown)
+    pub fn for_side(result: Option<GameResult>, side: Color) -> Self {
+        match result {
+            Some(GameResult::Checkmate { winner }) if winner == side => Outcome::Win,
+            Some(GameResult::Checkmate { .. }) => Outcome::Loss,
+            Some(_) => Outcome::Draw,
+            None => Outcome::Unknown,
+        }
+    }
+}
+
+/// One learned (position, move) pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExperienceEntry {
+    pub key: u64,
+    /// The recorded position, stored on disk instead of the key
+    pub position: PackedBoard,
+    pub mv: Move,
+    /// Search score for the side to move
+    pub score: i16,
+    pub depth: u8,
+    pub outcome: Outcome,
+}
+
+impl ExperienceEntry {
+    fn to_bytes(self) -> [u8; EXPERIENCE_ENTRY_SIZE] {
+        const P: usize = PACKED_BOARD_SIZE;
+        let mut out = [0u8; EXPERIENCE_ENTRY_SIZE];
+        out[..P].copy_from_slice(&self.position);
+        out[P..P + 4].copy_from_slice(&self.mv.raw().to_le_bytes());
+        out[P + 4..P + 6].copy_from_slice(&self.score.to_le_bytes());
+        out[P + 6] = self.depth;
+        out[P + 7] = self.outcome.to_byte();
+        out
+    }
+
+    fn from_bytes(bytes: &[u8; EXPERIENCE_ENTRY_SIZE]) -> io::Result<Self> {
+        const P: usize = PACKED_BOARD_SIZE;
+        let position: PackedBoard = bytes[..P].try_into().expect("exact slice");
+        let board = Board::decode(&position)
+            .map_err(|msg| io::Error::new(io::ErrorKind::InvalidData, msg))?;
+        Ok(Self {
+            key: board.zobrist,
+            position,
+            mv: Move::from_raw(u32::from_le_bytes([
+                bytes[P],
+                bytes[P + 1],
+                bytes[P + 2],
+                bytes[P + 3],
+            ])),
+            score: i16::from_le_bytes([bytes[P + 4], bytes[P + 5]]),
+            depth: bytes[P + 6],
+            outcome: Outcome::from_byte(bytes[P + 7])?,
+        })
+    }
+
+    /// Preference among the moves of one position: results first, then score
+    fn rank(&self) -> (u8, i16) {
+        let result = match self.outcome {
+            Outcome::Win => 3,
+            Outcome::Draw => 2,
+            Outcome::Unknown => 1,
+            Outcome::Loss => 0,
+        };
+        (result, self.score)
+    }
+}
+
+/// Learned moves by position, plus the searches of the game in progress
+#[derive(Debug, Clone, Default)]
+pub struct ExperienceBook {
+    entries: HashMap<u64, Vec<ExperienceEntry>>,
+    /// Searches of the current game, with the side that moved
+    game: Vec<(ExperienceEntry, Color)>,
+}
+
+impl ExperienceBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a book; a missing file is an empty book
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut data = Vec::new();
+        match std::fs::File::open(path) {
+            Ok(mut file) => file.read_to_end(&mut data)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::new()),
+            Err(e) => return Err(e),
+        };
+        let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+        let body = data
+            .strip_prefix(&EXPERIENCE_MAGIC)
+            .ok_or_else(|| invalid("not an experience book"))?;
+        if body.len() % EXPERIENCE_ENTRY_SIZE != 0 {
+            return Err(invalid("truncated experience book"));
+        }
+
+        let mut book = Self::new();
+        for chunk in body.chunks_exact(EXPERIENCE_ENTRY_SIZE) {
+            let entry = ExperienceEntry::from_bytes(chunk.try_into().expect("exact chunk"))?;
+            book.merge(entry);
+        }
+        Ok(book)
+    }
+
+    /// Write the learned entries (not the game in progress)
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut data =
+            Vec::with_capacity(EXPERIENCE_MAGIC.len() + self.len() * EXPERIENCE_ENTRY_SIZE);
+        data.extend_from_slice(&EXPERIENCE_MAGIC);
+        let mut keys: Vec<u64> = self.entries.keys().copied().collect();
+        keys.sort_unstable();
+        for key in keys {
+            for entry in &self.entries[&key] {
+                data.extend_from_slice(&entry.to_bytes());
+            }
+        }
+        std::fs::File::create(path)?.write_all(&data)
+    }
+
+    /// Number of learned (position, move) pairs
+    pub fn len(&self) -> usize {
+        self.entries.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Learned moves of the position with Zobrist key `key`
+    pub fn probe(&self, key: u64) -> &[ExperienceEntry] {
+        self.entries.get(&key).map_or(&[], Vec::as_slice)
+    }
+
+    /// Remember the move chosen by a search of `board` for the current game
+    /// (not for positions [`Board::encode`] cannot store)
+    pub fn record(&mut self, board: &Board, mv: Move, score: i16, depth: u8) {
+        if mv.is_none() {
+            return;
+        }
+        let Ok(position) = board.encode() else {
+            return;
+        };
+        let entry = ExperienceEntry {
+            key: board.zobrist,
+            position,
+            mv,
+            score,
+            depth,
+            outcome: Outcome::Unknown,
+        };
+        self.game.push((entry, board.side));
+    }
+
+    /// Close the current game with `result` and learn from its searches.
+    /// Returns the number of records learned.
+    pub fn finish_game(&mut self, result: Option<GameResult>) -> usize {
+        let game = std::mem::take(&mut self.game);
+        let learned = game.len();
+        for (mut entry, side) in game {
+            entry.outcome = Outcome::for_side(result, side);
+            self.merge(entry);
+        }
+        learned
+    }
+
+    /// Close the current game at its last known position `board`. If our own
+    /// last move was never answered (e.g. it mated), it is played first so
+    /// the result is seen.
+    pub fn finish_game_from(&mut self, board: &Board) -> usize {
+        let mut last = board.clone();
+        if let Some((entry, _)) = self.game.last() {
+            if entry.key == last.zobrist && last.game_result().is_none() {
+                last.push_move(entry.mv);
+            }
+        }
+        let result = last.game_result();
+        self.finish_game(result)
+    }
+
+    /// Add `entry`, or update the record of the same move: the deeper search
+    /// gives the score, a known outcome replaces the older one
+    fn merge(&mut self, entry: ExperienceEntry) {
+        let moves = self.entries.entry(entry.key).or_default();
+        match moves.iter_mut().find(|e| e.mv == entry.mv) {
+            Some(known) => {
+                if entry.depth >= known.depth {
+                    known.score = entry.score;
+                    known.depth = entry.depth;
+                }
+                if entry.outcome != Outcome::Unknown {
+                    known.outcome = entry.outcome;
+                }
+            }
+            None => moves.push(entry),
+        }
+    }
+
+    /// Best legal learned move of `board` that was not part of a lost game
+    pub fn preferred_move(&self, board: &Board) -> Option<&ExperienceEntry> {
+        let legal = board.clone().generate_moves();
+        self.probe(board.zobrist)
+            .iter()
+            .filter(|e| e.outcome != Outcome::Loss && legal.contains(&e.mv))
+            .max_by_key(|e| e.rank())
+    }
+
+    /// Preferred move when it was searched deeply enough to be played
+    /// without a new search
+    pub fn book_move(&self, board: &Board) -> Option<Move> {
+        self.preferred_move(board)
+            .filter(|e| e.depth >= EXPERIENCE_BOOK_DEPTH)
+            .map(|e| e.mv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{parse_uci_move, START_FEN};
+
+    fn start() -> Board {
+        crate::init();
+        let mut board = Board::new();
+        board.set_from_fen(START_FEN).unwrap();
+        board
+    }
+
+    #[test]
+    fn test_finished_game_is_learned_and_survives_a_round_trip() {
+        let mut board = start();
+        let e4 = parse_uci_move(&mut board, "e2e4").unwrap();
+        let mut book = ExperienceBook::new();
+        book.record(&board, e4, 30, 14);
+        let undo = board.make_move(e4);
+        let e5 = parse_uci_move(&mut board, "e7e5").unwrap();
+        book.record(&board, e5, -25, 13);
+        board.unmake_move(undo);
+        assert!(book.is_empty());
+
+        let result = Some(GameResult::Checkmate {
+            winner: Color::White,
+        });
+        assert_eq!(book.finish_game(result), 2);
+        assert_eq!(book.probe(board.zobrist)[0].outcome, Outcome::Win);
+        assert_eq!(book.book_move(&board), Some(e4));
+
+        let path = std::env::temp_dir().join(format!("scacchista-exp-{}.bin", std::process::id()));
+        book.save(&path).unwrap();
+        let loaded = ExperienceBook::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded.probe(board.zobrist), book.probe(board.zobrist));
+        // Black lost with e7e5: never preferred
+        let undo = board.make_move(e4);
+        assert_eq!(loaded.probe(board.zobrist)[0].outcome, Outcome::Loss);
+        assert_eq!(loaded.preferred_move(&board), None);
+        board.unmake_move(undo);
+    }
+
+    #[test]
+    fn test_merge_keeps_deepest_score_and_shallow_moves_are_not_book() {
+        let mut board = start();
+        let d4 = parse_uci_move(&mut board, "d2d4").unwrap();
+        let mut book = ExperienceBook::new();
+        book.record(&board, d4, 40, 16);
+        book.finish_game(None);
+        book.record(&board, d4, 10, 6);
+        book.finish_game(Some(GameResult::Stalemate));
+
+        let entry = book.probe(board.zobrist)[0];
+        assert_eq!((entry.score, entry.depth), (40, 16));
+        assert_eq!(entry.outcome, Outcome::Draw);
+
+        let mut shallow = ExperienceBook::new();
+        shallow.record(&board, d4, 40, EXPERIENCE_BOOK_DEPTH - 1);
+        shallow.finish_game(None);
+        assert_eq!(shallow.preferred_move(&board).map(|e| e.mv), Some(d4));
+        assert_eq!(shallow.book_move(&board), None);
+    }
+
+    #[test]
+    fn test_load_rejects_foreign_files() {
+        let path = std::env::temp_dir().join(format!("scacchista-bad-{}.bin", std::process::id()));
+        std::fs::write(&path, b"not a book").unwrap();
+        let err = ExperienceBook::load(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let missing = std::env::temp_dir().join("scacchista-no-such-book.bin");
+        assert!(ExperienceBook::load(&missing).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_records_keep_their_position_and_load_checks_it() {
+        let mut board = start();
+        let e4 = parse_uci_move(&mut board, "e2e4").unwrap();
+        let mut book = ExperienceBook::new();
+        book.record(&board, e4, 30, 14);
+        // Nine white pawns do not fit the packed format: not recorded
+        let mut crowded = Board::new();
+        crowded
+            .set_from_fen("rnbqkbnr/pppppppp/8/8/8/P7/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+            .unwrap();
+        book.record(&crowded, e4, 30, 14);
+        assert_eq!(book.finish_game(None), 1);
+        assert_eq!(
+            book.probe(board.zobrist)[0].position,
+            board.encode().unwrap()
+        );
+
+        let path = std::env::temp_dir().join(format!("scacchista-pos-{}.bin", std::process::id()));
+        book.save(&path).unwrap();
+        let mut data = std::fs::read(&path).unwrap();
+        // A piece code no board can have
+        data[EXPERIENCE_MAGIC.len() + 8] |= 0x07;
+        std::fs::write(&path, &data).unwrap();
+        let err = ExperienceBook::load(&path).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
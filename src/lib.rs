@@ -2,20 +2,34 @@
 //!
 //! This crate provides a complete bitboard-based chess engine with
 //! alpha-beta search, transposition tables, and hand-crafted evaluation.
+//!
+//! The UCI protocol layer is behind the `uci` feature (on by default); with
+//! `default-features = false` the crate is a chess library without it.
 
+pub mod analysis;
+pub mod bench;
 pub mod board;
+pub mod endgame;
+pub mod engine;
 pub mod eval;
+pub mod experience;
 pub mod magic;
+pub mod perft;
 pub mod search;
+pub mod selfplay;
 pub mod time;
+#[cfg(feature = "uci")]
 pub mod uci;
 pub mod utils;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 pub mod zobrist;
 
+pub use engine::{Engine, EngineError, SearchLimits, StopHandle};
+
 // Re-export move utilities for the perft binary
 pub use board::{
-    move_captured, move_flag, move_from_sq, move_piece, move_to_sq, move_to_uci, parse_uci_move,
-    Board, Color, PieceKind, FLAG_PROMOTION,
+    move_to_uci, parse_uci_move, Board, Color, GameResult, Move, PieceKind, FLAG_PROMOTION,
 };
 
 /// Initialize global lookup tables (attack tables, Zobrist keys, etc.).
@@ -1,10 +1,32 @@
 //! Scacchista UCI chess engine main entry point.
+//!
+//! `scacchista bench [depth]` runs the node-count benchmark and exits instead
+//! of starting the UCI loop. `scacchista tune` starts the UCI loop with the
+//! evaluation constants exposed as spin options, for SPSA/CLOP tuners.
 
 fn main() {
     scacchista::init();
 
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("bench") {
+        let depth = args
+            .get(2)
+            .and_then(|d| d.parse::<u8>().ok())
+            .unwrap_or(scacchista::bench::BENCH_DEPTH);
+        let result = scacchista::bench::run_bench(depth);
+        for line in scacchista::uci::bench_info_lines(&result) {
+            println!("{}", line);
+        }
+        return;
+    }
+
+    let mut engine = scacchista::uci::UciEngine::new();
+    if args.get(1).map(String::as_str) == Some("tune") {
+        engine.enable_tuning();
+    }
+
     // Run UCI main loop
-    if let Err(e) = scacchista::uci::run_uci_loop() {
+    if let Err(e) = scacchista::uci::run_uci_engine(engine) {
         eprintln!("UCI loop failed: {:?}", e);
     }
 }
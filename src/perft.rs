@@ -0,0 +1,157 @@
+//! Fast perft: bulk counting, a hash table of subtree counts and root splitting
+//!
+//! [`Board::perft`] already bulk-counts (the legal moves at depth 1 are counted,
+//! not played). On top of it [`PerftTable`] remembers the node count of every
+//! (position, depth) pair, so transpositions are counted once, and
+//! [`perft_parallel`] splits the root moves across threads sharing one table.
+//! Used by `src/bin/perft.rs` for depth 6+ runs.
+//!
+//! With the `perft-oracle` feature, [`oracle`] checks the counts against
+//! shakmaty and reduces a mismatch to the first position where the two
+//! generators disagree.
+
+use crate::board::{Board, Move};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+#[cfg(feature = "perft-oracle")]
+pub mod oracle;
+
+/// Subtree counts keyed by (Zobrist key, depth)
+///
+/// Same lockless scheme as the search TT: each bucket is `key ^ count` plus
+/// `count`, so an entry torn by two racing writers fails the key check and is
+/// a miss, never a wrong count.
+pub struct PerftTable {
+    buckets: Vec<[AtomicU64; 2]>,
+}
+
+impl PerftTable {
+    /// Table of `size_mb` megabytes (at least one bucket)
+    pub fn new(size_mb: usize) -> Self {
+        let count = (size_mb * 1024 * 1024 / std::mem::size_of::<[AtomicU64; 2]>()).max(1);
+        Self {
+            buckets: (0..count)
+                .map(|_| [AtomicU64::new(0), AtomicU64::new(0)])
+                .collect(),
+        }
+    }
+
+    /// Depth is mixed into the key so that one position at several depths
+    /// never aliases
+    fn key(zobrist: u64, depth: u8) -> u64 {
+        zobrist ^ (depth as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+    }
+
+    pub fn probe(&self, zobrist: u64, depth: u8) -> Option<u64> {
+        let key = Self::key(zobrist, depth);
+        let bucket = &self.buckets[(key % self.buckets.len() as u64) as usize];
+        let key_xor = bucket[0].load(Ordering::Relaxed);
+        let nodes = bucket[1].load(Ordering::Relaxed);
+        (nodes != 0 && key_xor ^ nodes == key).then_some(nodes)
+    }
+
+    pub fn store(&self, zobrist: u64, depth: u8, nodes: u64) {
+        let key = Self::key(zobrist, depth);
+        let bucket = &self.buckets[(key % self.buckets.len() as u64) as usize];
+        bucket[0].store(key ^ nodes, Ordering::Relaxed);
+        bucket[1].store(nodes, Ordering::Relaxed);
+    }
+}
+
+/// Perft with bulk counting, reusing and filling `table`
+pub fn perft_hashed(board: &mut Board, depth: u8, table: &PerftTable) -> u64 {
+    if depth <= 1 {
+        return board.perft(depth);
+    }
+    if let Some(nodes) = table.probe(board.zobrist, depth) {
+        return nodes;
+    }
+    let mut nodes = 0;
+    for mv in board.generate_moves() {
+        board.push_move(mv);
+        nodes += perft_hashed(board, depth - 1, table);
+        board.pop_move();
+    }
+    table.store(board.zobrist, depth, nodes);
+    nodes
+}
+
+/// Perft of every root move ("divide"), root moves shared out over `threads`
+/// threads; with a table the threads share it. Results are in move
+/// generation order.
+pub fn perft_parallel(
+    board: &Board,
+    depth: u8,
+    threads: usize,
+    table: Option<&PerftTable>,
+) -> Vec<(Move, u64)> {
+    if depth == 0 {
+        return Vec::new();
+    }
+    let moves = board.clone().generate_moves();
+    let next = AtomicUsize::new(0);
+    let counts: Vec<AtomicU64> = moves.iter().map(|_| AtomicU64::new(0)).collect();
+
+    std::thread::scope(|scope| {
+        for _ in 0..threads.clamp(1, moves.len().max(1)) {
+            scope.spawn(|| {
+                let mut board = board.clone();
+                // Work stealing: subtrees differ a lot in size
+                loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(&mv) = moves.get(i) else { break };
+                    board.push_move(mv);
+                    let nodes = match table {
+                        Some(table) => perft_hashed(&mut board, depth - 1, table),
+                        None => board.perft(depth - 1),
+                    };
+                    board.pop_move();
+                    counts[i].store(nodes, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+
+    moves
+        .into_iter()
+        .zip(counts)
+        .map(|(mv, nodes)| (mv, nodes.into_inner()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::START_FEN;
+
+    const KIWIPETE: &str = "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1";
+
+    fn board(fen: &str) -> Board {
+        crate::init();
+        let mut board = Board::new();
+        board.set_from_fen(fen).unwrap();
+        board
+    }
+
+    #[test]
+    fn test_hashed_perft_matches_plain_perft() {
+        let table = PerftTable::new(1);
+        let mut b = board(KIWIPETE);
+        assert_eq!(perft_hashed(&mut b, 3, &table), 97862);
+        // Second run is served from the table
+        assert_eq!(perft_hashed(&mut b, 3, &table), 97862);
+        assert_eq!(table.probe(b.zobrist, 3), Some(97862));
+        assert_eq!(table.probe(b.zobrist, 2), None);
+    }
+
+    #[test]
+    fn test_parallel_perft_matches_divide() {
+        let b = board(START_FEN);
+        let table = PerftTable::new(1);
+        for (threads, table) in [(1, None), (4, None), (4, Some(&table))] {
+            let divide = perft_parallel(&b, 4, threads, table);
+            assert_eq!(divide.len(), 20);
+            assert_eq!(divide.iter().map(|&(_, n)| n).sum::<u64>(), 197281);
+        }
+    }
+}
@@ -0,0 +1,457 @@
+//! Perft against shakmaty with automatic reduction of a mismatch
+//!
+//! When the node counts differ, [`find_divergence`] divides both generators
+//! and descends into the first root move whose subtree counts disagree, then
+//! repeats one ply lower, until it reaches a position where the two
+//! generators produce different moves. On the way down every position is
+//! also compared with the oracle's (FEN) and its incremental Zobrist key with
+//! a full recompute, so a make/unmake bug that leaves the counts right for a
+//! while is reported where the state first goes wrong.
+//!
+//! [`check_random_positions`] runs the same comparison on positions reached by
+//! random games from [`RANDOM_START_FENS`], on several threads, and shrinks
+//! each failing position by removing pieces while the counts still differ.
+
+use crate::board::{move_to_uci, Board, Color, PieceKind};
+use crate::search::VarietyRng;
+use shakmaty::fen::Fen;
+use shakmaty::{CastlingMode, Chess, EnPassantMode, Position};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// What differs at the divergence point
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DivergenceKind {
+    /// Legal moves that Scacchista does not generate
+    Missing(Vec<String>),
+    /// Moves Scacchista generates that are not legal
+    Extra(Vec<String>),
+    /// Both generators played the same moves but reached different positions
+    /// (FEN), or the incremental Zobrist key differs from a recompute
+    State { ours: String, oracle: String },
+}
+
+/// Smallest failing case found: the position, how to reach it from the root
+/// and what differs there
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// Moves (UCI) from the root position
+    pub path: Vec<String>,
+    /// FEN of the position reached by `path` (Scacchista's view of it)
+    pub fen: String,
+    /// Zobrist key of that position
+    pub zobrist: u64,
+    pub kind: DivergenceKind,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            writeln!(f, "at the root position")?;
+        } else {
+            writeln!(f, "after moves: {}", self.path.join(" "))?;
+        }
+        writeln!(f, "fen: {}", self.fen)?;
+        writeln!(f, "zobrist: {:016x}", self.zobrist)?;
+        match &self.kind {
+            DivergenceKind::Missing(moves) => write!(f, "missing moves: {}", moves.join(" ")),
+            DivergenceKind::Extra(moves) => write!(f, "illegal moves: {}", moves.join(" ")),
+            DivergenceKind::State { ours, oracle } => {
+                write!(
+                    f,
+                    "state differs:\n  ours:   {}\n  oracle: {}",
+                    ours, oracle
+                )
+            }
+        }
+    }
+}
+
+/// Perft of `fen` to `depth` with shakmaty: Scacchista's count, the oracle's
+/// count and, if they differ, the first divergence
+pub fn compare(fen: &str, depth: u8) -> Result<(u64, u64, Option<Divergence>), String> {
+    let (mut board, pos) = setup(fen)?;
+    let ours = board.perft(depth);
+    let oracle = shakmaty::perft(&pos, depth as u32);
+    let divergence = if ours != oracle {
+        find_divergence(fen, depth)?
+    } else {
+        None
+    };
+    Ok((ours, oracle, divergence))
+}
+
+/// Walk down from `fen` to the first position where Scacchista and shakmaty
+/// disagree within `depth` plies (None if the whole tree agrees)
+pub fn find_divergence(fen: &str, depth: u8) -> Result<Option<Divergence>, String> {
+    let (mut board, pos) = setup(fen)?;
+    let mut path = Vec::new();
+    Ok(descend(&mut board, &pos, depth, &mut path))
+}
+
+/// Start positions of the random games: the standard perft positions, where
+/// castling, en passant, promotions and pins are all within a few moves
+pub const RANDOM_START_FENS: [&str; 6] = [
+    crate::board::START_FEN,
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+];
+
+/// Random positions compared by [`check_random_positions`]
+#[derive(Debug, Clone)]
+pub struct RandomPerft {
+    /// Positions to generate
+    pub positions: usize,
+    /// Each position is reached by 0..=max_plies random legal moves
+    pub max_plies: u32,
+    /// Perft depth compared on every position
+    pub depth: u8,
+    pub threads: usize,
+    /// Same seed, same positions
+    pub seed: u64,
+}
+
+impl Default for RandomPerft {
+    fn default() -> Self {
+        Self {
+            positions: 1000,
+            max_plies: 40,
+            depth: 2,
+            threads: 1,
+            seed: 1,
+        }
+    }
+}
+
+/// A random position whose counts differ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RandomFailure {
+    /// Position reached by the random game
+    pub fen: String,
+    /// Same failure with as few pieces as possible
+    pub shrunk_fen: String,
+    /// Node counts of the shrunk position: Scacchista's and the oracle's
+    pub nodes: (u64, u64),
+    /// First divergence below the shrunk position (None when the walk down
+    /// could not locate it: the counts still differ)
+    pub divergence: Option<Divergence>,
+}
+
+impl fmt::Display for RandomFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "random position: {}", self.fen)?;
+        writeln!(f, "shrunk to: {}", self.shrunk_fen)?;
+        writeln!(f, "nodes: {} (oracle {})", self.nodes.0, self.nodes.1)?;
+        match &self.divergence {
+            Some(divergence) => write!(f, "{}", divergence),
+            None => write!(f, "divergence not located"),
+        }
+    }
+}
+
+/// The `index`-th random position of `config`: a random game of up to
+/// `max_plies` moves from one of [`RANDOM_START_FENS`]. It only depends on
+/// the seed and the index, whichever thread generates it.
+pub fn random_position(config: &RandomPerft, index: usize) -> String {
+    let mut rng = VarietyRng::new(config.seed ^ (index as u64).wrapping_mul(0x2545_F491_4F6C_DD1D));
+    let start = RANDOM_START_FENS[rng.next_u64() as usize % RANDOM_START_FENS.len()];
+    let mut board = Board::new();
+    board
+        .set_from_fen(start)
+        .expect("random start positions are valid FENs");
+    let plies = rng.next_u64() % (config.max_plies as u64 + 1);
+    for _ in 0..plies {
+        let moves = board.generate_moves();
+        if moves.is_empty() {
+            break;
+        }
+        board.make_move(moves[rng.next_u64() as usize % moves.len()]);
+    }
+    board.to_fen()
+}
+
+/// Compare perft with shakmaty on `config.positions` random positions split
+/// over `config.threads` threads. Failures come back shrunk, in position
+/// order; an empty list means every count agreed.
+pub fn check_random_positions(config: &RandomPerft) -> Vec<RandomFailure> {
+    let next = AtomicUsize::new(0);
+    let failures = Mutex::new(Vec::new());
+    std::thread::scope(|scope| {
+        for _ in 0..config.threads.clamp(1, config.positions.max(1)) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                if index >= config.positions {
+                    break;
+                }
+                let fen = random_position(config, index);
+                if let Some(failure) = check_position(&fen, config.depth) {
+                    failures.lock().unwrap().push((index, failure));
+                }
+            });
+        }
+    });
+    let mut failures = failures.into_inner().unwrap();
+    failures.sort_by_key(|(index, _)| *index);
+    failures.into_iter().map(|(_, failure)| failure).collect()
+}
+
+fn check_position(fen: &str, depth: u8) -> Option<RandomFailure> {
+    if !counts_differ(fen, depth) {
+        return None;
+    }
+    let shrunk_fen = shrink(fen, |candidate| counts_differ(candidate, depth));
+    // The shrunk position passed setup, so only the divergence can be missing
+    let (ours, oracle, divergence) =
+        compare(&shrunk_fen, depth).expect("shrunk positions are valid for both generators");
+    Some(RandomFailure {
+        fen: fen.to_string(),
+        shrunk_fen,
+        nodes: (ours, oracle),
+        divergence,
+    })
+}
+
+/// Whether the two generators count differently (positions either side
+/// rejects count as agreeing: there is nothing to compare)
+fn counts_differ(fen: &str, depth: u8) -> bool {
+    match setup(fen) {
+        Ok((mut board, pos)) => board.perft(depth) != shakmaty::perft(&pos, depth as u32),
+        Err(_) => false,
+    }
+}
+
+/// Remove pieces other than the kings one at a time, keeping each removal
+/// after which `fails` still holds, until no piece can go
+pub fn shrink(fen: &str, fails: impl Fn(&str) -> bool) -> String {
+    let mut current = fen.to_string();
+    'shrinking: loop {
+        let mut board = Board::new();
+        if board.set_from_fen(&current).is_err() {
+            return current;
+        }
+        for sq in 0..64 {
+            let Some((kind, color)) = board.piece_on(sq) else {
+                continue;
+            };
+            if kind == PieceKind::King {
+                continue;
+            }
+            let mut smaller = board.clone();
+            smaller.remove_piece(sq, kind, color);
+            let mut candidate = smaller.to_fen();
+            if setup(&candidate).is_err() {
+                // A castling rook or the pawn behind the en-passant square
+                // is gone: drop the rights that came with it
+                smaller.castling &= match color {
+                    Color::White => !0b1100,
+                    Color::Black => !0b0011,
+                };
+                smaller.ep = None;
+                candidate = smaller.to_fen();
+            }
+            if setup(&candidate).is_ok() && fails(&candidate) {
+                current = candidate;
+                continue 'shrinking;
+            }
+        }
+        return current;
+    }
+}
+
+fn setup(fen: &str) -> Result<(Board, Chess), String> {
+    let mut board = Board::new();
+    board.set_from_fen(fen).map_err(|e| e.to_string())?;
+    let pos: Chess = fen
+        .parse::<Fen>()
+        .map_err(|e| e.to_string())?
+        .into_position(CastlingMode::Standard)
+        .map_err(|e| e.to_string())?;
+    Ok((board, pos))
+}
+
+fn descend(
+    board: &mut Board,
+    pos: &Chess,
+    depth: u8,
+    path: &mut Vec<String>,
+) -> Option<Divergence> {
+    let divergence = |board: &Board, kind| Divergence {
+        path: path.clone(),
+        fen: board.to_fen(),
+        zobrist: board.zobrist,
+        kind,
+    };
+
+    let oracle_fen = Fen::from_position(pos.clone(), EnPassantMode::Legal).to_string();
+    if board.to_fen() != oracle_fen {
+        let ours = board.to_fen();
+        return Some(divergence(
+            board,
+            DivergenceKind::State {
+                ours,
+                oracle: oracle_fen,
+            },
+        ));
+    }
+    if board.zobrist != board.recalc_zobrist() {
+        return Some(divergence(
+            board,
+            DivergenceKind::State {
+                ours: format!("zobrist {:016x}", board.zobrist),
+                oracle: format!("zobrist {:016x} (recomputed)", board.recalc_zobrist()),
+            },
+        ));
+    }
+    if depth == 0 {
+        return None;
+    }
+
+    let ours = board.generate_moves();
+    let oracle: Vec<(String, shakmaty::Move)> = pos
+        .legal_moves()
+        .into_iter()
+        .map(|m| (m.to_uci(CastlingMode::Standard).to_string(), m))
+        .collect();
+    let ours_uci: Vec<String> = ours.iter().map(|&mv| move_to_uci(mv)).collect();
+    let oracle_uci: Vec<String> = oracle.iter().map(|(uci, _)| uci.clone()).collect();
+    if let Some(kind) = diff_moves(&ours_uci, &oracle_uci) {
+        return Some(divergence(board, kind));
+    }
+
+    // Same moves: follow the first one whose subtree count differs
+    for (mv, uci) in ours.into_iter().zip(ours_uci) {
+        let oracle_move = &oracle.iter().find(|(u, _)| *u == uci)?.1;
+        let mut child = pos.clone();
+        child.play_unchecked(oracle_move);
+        board.push_move(mv);
+        let differs = board.perft(depth - 1) != shakmaty::perft(&child, depth as u32 - 1);
+        if differs {
+            path.push(uci);
+            let found = descend(board, &child, depth - 1, path);
+            board.pop_move();
+            return found;
+        }
+        board.pop_move();
+    }
+    None
+}
+
+/// Moves only one of the two generators produced (Missing wins over Extra);
+/// a move Scacchista generates twice counts as Extra
+fn diff_moves(ours: &[String], oracle: &[String]) -> Option<DivergenceKind> {
+    let mut seen = BTreeSet::new();
+    let duplicated: Vec<String> = ours.iter().filter(|m| !seen.insert(*m)).cloned().collect();
+    let ours: BTreeSet<&String> = ours.iter().collect();
+    let oracle: BTreeSet<&String> = oracle.iter().collect();
+    let missing: Vec<String> = oracle.difference(&ours).map(|m| m.to_string()).collect();
+    if !missing.is_empty() {
+        return Some(DivergenceKind::Missing(missing));
+    }
+    let mut extra: Vec<String> = ours.difference(&oracle).map(|m| m.to_string()).collect();
+    extra.extend(duplicated);
+    if !extra.is_empty() {
+        return Some(DivergenceKind::Extra(extra));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_positions_agree() {
+        crate::init();
+        for (fen, depth) in [
+            (crate::board::START_FEN, 3),
+            (
+                "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+                2,
+            ),
+            ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 3),
+            (
+                "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+                2,
+            ),
+        ] {
+            let (ours, oracle, divergence) = compare(fen, depth).unwrap();
+            assert_eq!(ours, oracle, "{}", fen);
+            assert_eq!(divergence, None);
+            assert_eq!(find_divergence(fen, depth).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn test_diff_moves_reports_missing_then_extra() {
+        let moves = |list: &[&str]| list.iter().map(|m| m.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            diff_moves(&moves(&["e2e4", "d2d4"]), &moves(&["d2d4", "e2e4"])),
+            None
+        );
+        assert_eq!(
+            diff_moves(&moves(&["e2e4", "a1a8"]), &moves(&["e2e4", "e1g1"])),
+            Some(DivergenceKind::Missing(moves(&["e1g1"])))
+        );
+        assert_eq!(
+            diff_moves(&moves(&["e2e4", "a1a8"]), &moves(&["e2e4"])),
+            Some(DivergenceKind::Extra(moves(&["a1a8"])))
+        );
+        assert_eq!(
+            diff_moves(&moves(&["e2e4", "d2d4", "e2e4"]), &moves(&["d2d4", "e2e4"])),
+            Some(DivergenceKind::Extra(moves(&["e2e4"])))
+        );
+    }
+
+    #[test]
+    fn test_random_positions_are_reproducible_and_agree() {
+        crate::init();
+        let config = RandomPerft {
+            positions: 40,
+            threads: 2,
+            ..RandomPerft::default()
+        };
+        assert_eq!(random_position(&config, 7), random_position(&config, 7));
+        assert_ne!(random_position(&config, 7), random_position(&config, 8));
+        assert_eq!(check_random_positions(&config), Vec::new());
+    }
+
+    #[test]
+    fn test_shrink_removes_every_piece_the_failure_does_not_need() {
+        crate::init();
+        // Pretend the bug needs a white knight on the board
+        let needs_knight = |fen: &str| fen.split(' ').next().unwrap().contains('N');
+        let shrunk = shrink(crate::board::START_FEN, needs_knight);
+        assert_eq!(shrunk.split(' ').next().unwrap(), "4k3/8/8/8/8/8/8/4K1N1");
+    }
+
+    #[test]
+    fn test_divergent_state_is_reported_with_its_position() {
+        crate::init();
+        let (mut board, _) = setup(crate::board::START_FEN).unwrap();
+        // The oracle thinks White has already lost the right to castle
+        let (_, oracle) = setup("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w kq - 0 1").unwrap();
+        let found = descend(&mut board, &oracle, 2, &mut Vec::new()).unwrap();
+        assert!(found.path.is_empty());
+        assert_eq!(found.fen, crate::board::START_FEN);
+        assert!(matches!(found.kind, DivergenceKind::State { .. }));
+        assert!(found.to_string().contains("state differs"));
+    }
+
+    #[test]
+    fn test_failure_without_divergence_keeps_the_counts() {
+        let failure = RandomFailure {
+            fen: crate::board::START_FEN.to_string(),
+            shrunk_fen: "4k3/8/8/8/8/8/8/4K1N1 w - - 0 1".to_string(),
+            nodes: (40, 41),
+            divergence: None,
+        };
+        let report = failure.to_string();
+        assert!(report.contains("nodes: 40 (oracle 41)"));
+        assert!(report.ends_with("divergence not located"));
+    }
+}
@@ -0,0 +1,162 @@
+//! CPU affinity of the search workers (UCI `ThreadAffinity`)
+//!
+//! On machines with many cores the scheduler moves threads between cores,
+//! and across sockets, which costs cache warmth and turns transposition
+//! table accesses into remote memory traffic. With affinity enabled, worker
+//! `i` is pinned to the `i`-th CPU of [`worker_cpus`]: the CPUs the process
+//! may run on, grouped by NUMA node, so a search with fewer threads than a
+//! node has CPUs stays on one node.
+//!
+//! Only Linux is supported; elsewhere pinning reports failure and the
+//! workers keep running wherever the scheduler puts them.
+
+/// Parse a Linux CPU list such as `0-3,8,10-11`; malformed parts are skipped
+pub fn parse_cpu_list(list: &str) -> Vec<usize> {
+    let mut cpus = Vec::new();
+    for part in list.trim().split(',').filter(|p| !p.is_empty()) {
+        match part.split_once('-') {
+            Some((first, last)) => {
+                if let (Ok(first), Ok(last)) = (first.parse::<usize>(), last.parse::<usize>()) {
+                    cpus.extend(first..=last);
+                }
+            }
+            None => cpus.extend(part.parse::<usize>().ok()),
+        }
+    }
+    cpus
+}
+
+/// `allowed` CPUs ordered node by node (`nodes` lists the CPUs of each NUMA
+/// node); CPUs of no known node come last
+pub fn numa_order(allowed: &[usize], nodes: &[Vec<usize>]) -> Vec<usize> {
+    let mut order: Vec<usize> = nodes
+        .iter()
+        .flatten()
+        .copied()
+        .filter(|cpu| allowed.contains(cpu))
+        .collect();
+    order.dedup();
+    let rest: Vec<usize> = allowed
+        .iter()
+        .copied()
+        .filter(|cpu| !order.contains(cpu))
+        .collect();
+    order.extend(rest);
+    order
+}
+
+/// CPUs the workers are pinned to, in worker order (empty when affinity is
+/// not supported)
+pub fn worker_cpus() -> Vec<usize> {
+    numa_order(&sys::allowed_cpus(), &sys::numa_nodes())
+}
+
+/// Restrict the calling thread to `cpus`; false if that is not supported or
+/// the system refused
+pub fn pin_current_thread(cpus: &[usize]) -> bool {
+    !cpus.is_empty() && sys::set_affinity(cpus)
+}
+
+#[cfg(target_os = "linux")]
+mod sys {
+    #![allow(unsafe_code)]
+
+    use std::mem::{size_of, zeroed};
+
+    /// CPUs in the affinity mask of the calling thread
+    pub fn allowed_cpus() -> Vec<usize> {
+        // SAFETY: cpu_set_t is a plain bit array, all zeroes is the empty set
+        let mut set: libc::cpu_set_t = unsafe { zeroed() };
+        // SAFETY: pid 0 is the calling thread and `set` is a valid, writable
+        // cpu_set_t of the size passed
+        if unsafe { libc::sched_getaffinity(0, size_of::<libc::cpu_set_t>(), &mut set) } != 0 {
+            return Vec::new();
+        }
+        (0..libc::CPU_SETSIZE as usize)
+            // SAFETY: `cpu` is below CPU_SETSIZE, inside the set
+            .filter(|&cpu| unsafe { libc::CPU_ISSET(cpu, &set) })
+            .collect()
+    }
+
+    /// CPUs of each NUMA node, by node number (empty without sysfs)
+    pub fn numa_nodes() -> Vec<Vec<usize>> {
+        let Ok(entries) = std::fs::read_dir("/sys/devices/system/node") else {
+            return Vec::new();
+        };
+        let mut nodes: Vec<(usize, Vec<usize>)> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().into_string().ok()?;
+                let id = name.strip_prefix("node")?.parse::<usize>().ok()?;
+                let list = std::fs::read_to_string(entry.path().join("cpulist")).ok()?;
+                Some((id, super::parse_cpu_list(&list)))
+            })
+            .collect();
+        nodes.sort_by_key(|(id, _)| *id);
+        nodes.into_iter().map(|(_, cpus)| cpus).collect()
+    }
+
+    pub fn set_affinity(cpus: &[usize]) -> bool {
+        // SAFETY: as above, the zeroed set is empty
+        let mut set: libc::cpu_set_t = unsafe { zeroed() };
+        for &cpu in cpus.iter().filter(|&&cpu| cpu < libc::CPU_SETSIZE as usize) {
+            // SAFETY: `cpu` is below CPU_SETSIZE, inside the set
+            unsafe { libc::CPU_SET(cpu, &mut set) };
+        }
+        // SAFETY: pid 0 is the calling thread and `set` is a valid cpu_set_t
+        // of the size passed
+        unsafe { libc::sched_setaffinity(0, size_of::<libc::cpu_set_t>(), &set) == 0 }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod sys {
+    pub fn allowed_cpus() -> Vec<usize> {
+        Vec::new()
+    }
+
+    pub fn numa_nodes() -> Vec<Vec<usize>> {
+        Vec::new()
+    }
+
+    pub fn set_affinity(_cpus: &[usize]) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_list() {
+        assert_eq!(parse_cpu_list("0-3,8,10-11\n"), vec![0, 1, 2, 3, 8, 10, 11]);
+        assert_eq!(parse_cpu_list("5"), vec![5]);
+        assert_eq!(parse_cpu_list(""), Vec::<usize>::new());
+        assert_eq!(parse_cpu_list("x,2,3-y"), vec![2]);
+    }
+
+    #[test]
+    fn test_numa_order_groups_cpus_by_node() {
+        // Two sockets with interleaved numbering, CPU 3 not allowed, CPU 9 on no node
+        let nodes = vec![vec![0, 2, 4, 6], vec![1, 3, 5, 7]];
+        let allowed = [0, 1, 2, 4, 5, 6, 7, 9];
+        assert_eq!(numa_order(&allowed, &nodes), vec![0, 2, 4, 6, 1, 5, 7, 9]);
+        assert_eq!(numa_order(&allowed, &[]), allowed.to_vec());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_pin_current_thread_on_linux() {
+        let cpus = worker_cpus();
+        assert!(!cpus.is_empty());
+        std::thread::spawn(move || {
+            assert!(pin_current_thread(&cpus[..1]));
+            assert_eq!(sys::allowed_cpus(), vec![cpus[0]]);
+            assert!(pin_current_thread(&cpus));
+        })
+        .join()
+        .unwrap();
+        assert!(!pin_current_thread(&[]));
+    }
+}
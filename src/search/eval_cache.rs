@@ -0,0 +1,255 @@
+//! Static evaluation cache for Scacchista
+//!
+//! Positions reached through transpositions are evaluated again every time
+//! the search meets them. The cache keeps the static evaluation per Zobrist
+//! key so the second visit is a table lookup.
+//!
+//! Each search owns its cache (no sharing between lazy-SMP workers, so no
+//! atomics): one `u64` per slot holding the high 32 bits of the key next to
+//! the full evaluation (main search) and the fast one (quiescence), either
+//! of which may be missing. A lazy-SMP worker keeps its cache from one job
+//! to the next; [`EvalCache::prepare`] empties it when the evaluation
+//! weights or constants change, so a new style never reads a stale score.
+
+use crate::board::Board;
+use crate::eval::{EvalParams, EvalWeights};
+
+/// Marks an evaluation slot of an entry as not computed yet
+const NO_EVAL: i16 = i16::MIN;
+
+/// An entry with neither evaluation: all zeroes, so a new cache is a zeroed
+/// allocation
+const EMPTY: u64 = 0;
+
+/// Default cache size in MB (UCI `EvalCache`)
+pub const DEFAULT_EVAL_CACHE_MB: usize = 4;
+
+/// Largest accepted cache size in MB
+pub const MAX_EVAL_CACHE_MB: usize = 256;
+
+// ---------------------------------------------------------------------------
+// Entry layout (single u64)
+//   bits 0-15  : full evaluation
+//   bits 16-31 : fast evaluation
+//   bits 32-63 : high 32 bits of the Zobrist key
+// Evaluations are stored with their sign bit flipped, so NO_EVAL is 0.
+// ---------------------------------------------------------------------------
+
+#[inline]
+fn encode(eval: i16) -> u64 {
+    ((eval as u16) ^ 0x8000) as u64
+}
+
+#[inline]
+fn decode(bits: u64) -> i16 {
+    ((bits as u16) ^ 0x8000) as i16
+}
+
+#[inline]
+fn pack(key: u64, full: i16, fast: i16) -> u64 {
+    (key & 0xFFFF_FFFF_0000_0000) | (encode(fast) << 16) | encode(full)
+}
+
+/// Slots of a `size_mb` cache, rounded down to a power of two so the cache
+/// never exceeds the requested size
+fn slots(size_mb: usize) -> usize {
+    let wanted = size_mb.min(MAX_EVAL_CACHE_MB) * 1024 * 1024 / std::mem::size_of::<u64>();
+    if wanted == 0 {
+        0
+    } else {
+        1 << wanted.ilog2()
+    }
+}
+
+/// Per-position static evaluation cache
+pub struct EvalCache {
+    entries: Vec<u64>,
+    mask: usize,
+    /// Evaluation the entries were computed with (None: not known yet)
+    settings: Option<(EvalParams, EvalWeights)>,
+}
+
+impl EvalCache {
+    /// Create a cache with approximately `size_mb` megabytes (0 = disabled)
+    pub fn new(size_mb: usize) -> Self {
+        let len = slots(size_mb);
+        Self {
+            entries: vec![EMPTY; len],
+            mask: len.saturating_sub(1),
+            settings: None,
+        }
+    }
+
+    /// Get ready for a search with a `size_mb` cache evaluating with
+    /// `params` and `weights`: the entries are kept when both match the
+    /// previous search, emptied when the evaluation differs, and the table
+    /// is only reallocated when the size changes
+    pub fn prepare(&mut self, size_mb: usize, params: &EvalParams, weights: &EvalWeights) {
+        if slots(size_mb) != self.size() {
+            *self = Self::new(size_mb);
+        } else if self.settings != Some((*params, *weights)) {
+            self.clear();
+        }
+        self.settings = Some((*params, *weights));
+    }
+
+    /// Whether the cache has any slots
+    pub fn is_enabled(&self) -> bool {
+        !self.entries.is_empty()
+    }
+
+    /// Number of slots
+    pub fn size(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Entry for `key` as `(full, fast)`, or None if the slot holds another key
+    #[inline]
+    fn lookup(&self, key: u64) -> Option<(i16, i16)> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let entry = self.entries[(key as usize) & self.mask];
+        if (entry ^ key) >> 32 != 0 {
+            return None;
+        }
+        Some((decode(entry), decode(entry >> 16)))
+    }
+
+    /// Cached full evaluation of `key`
+    #[inline]
+    pub fn probe(&self, key: u64) -> Option<i16> {
+        self.lookup(key)
+            .map(|(full, _)| full)
+            .filter(|&e| e != NO_EVAL)
+    }
+
+    /// Cached fast (quiescence) evaluation of `key`
+    #[inline]
+    pub fn probe_fast(&self, key: u64) -> Option<i16> {
+        self.lookup(key)
+            .map(|(_, fast)| fast)
+            .filter(|&e| e != NO_EVAL)
+    }
+
+    /// Store the full evaluation of `key`, keeping its fast one if cached
+    #[inline]
+    pub fn store(&mut self, key: u64, eval: i16) {
+        let fast = self.lookup(key).map_or(NO_EVAL, |(_, fast)| fast);
+        self.write(key, eval, fast);
+    }
+
+    /// Store the fast evaluation of `key`, keeping its full one if cached
+    #[inline]
+    pub fn store_fast(&mut self, key: u64, eval: i16) {
+        let full = self.lookup(key).map_or(NO_EVAL, |(full, _)| full);
+        self.write(key, full, eval);
+    }
+
+    #[inline]
+    fn write(&mut self, key: u64, full: i16, fast: i16) {
+        if self.is_enabled() {
+            self.entries[(key as usize) & self.mask] = pack(key, full, fast);
+        }
+    }
+
+    /// Full evaluation of `board`, computed with `eval` on a miss
+    #[inline]
+    pub fn get_or_insert(&mut self, board: &Board, eval: impl FnOnce(&Board) -> i16) -> i16 {
+        if let Some(score) = self.probe(board.zobrist) {
+            return score;
+        }
+        let score = eval(board);
+        self.store(board.zobrist, score);
+        score
+    }
+
+    /// Forget every entry
+    pub fn clear(&mut self) {
+        self.entries.fill(EMPTY);
+    }
+}
+
+impl Default for EvalCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVAL_CACHE_MB)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eval_cache_keeps_full_and_fast_evals_apart() {
+        let mut cache = EvalCache::new(1);
+        let key = 0x1234_5678_9ABC_DEF0;
+        assert_eq!(cache.probe(key), None);
+
+        cache.store_fast(key, -42);
+        assert_eq!(cache.probe(key), None);
+        assert_eq!(cache.probe_fast(key), Some(-42));
+
+        cache.store(key, 17);
+        assert_eq!(cache.probe(key), Some(17));
+        assert_eq!(cache.probe_fast(key), Some(-42));
+
+        // Same slot, other key: replaces the entry and never matches the old key
+        let other = key ^ (1 << 40);
+        cache.store(other, 5);
+        assert_eq!(cache.probe(other), Some(5));
+        assert_eq!(cache.probe_fast(other), None);
+        assert_eq!(cache.probe(key), None);
+
+        cache.clear();
+        assert_eq!(cache.probe(other), None);
+    }
+
+    #[test]
+    fn test_eval_cache_size_zero_disables_it() {
+        let mut cache = EvalCache::new(0);
+        assert!(!cache.is_enabled());
+        cache.store(7, 100);
+        assert_eq!(cache.probe(7), None);
+
+        let cache = EvalCache::new(1);
+        assert_eq!(cache.size(), 1024 * 1024 / 8);
+    }
+
+    #[test]
+    fn test_prepare_keeps_entries_only_for_the_same_evaluation() {
+        let params = EvalParams::default();
+        let weights = EvalWeights::default();
+        let mut cache = EvalCache::new(0);
+        cache.prepare(1, &params, &weights);
+        assert_eq!(cache.size(), 1024 * 1024 / 8);
+        cache.store(7, 100);
+
+        // Next search, same evaluation: still there
+        cache.prepare(1, &params, &weights);
+        assert_eq!(cache.probe(7), Some(100));
+
+        // Another style: emptied
+        cache.prepare(1, &params, &EvalWeights::TAL);
+        assert_eq!(cache.probe(7), None);
+
+        cache.store(7, 100);
+        cache.prepare(2, &params, &EvalWeights::TAL);
+        assert_eq!(cache.size(), 2 * 1024 * 1024 / 8);
+        assert_eq!(cache.probe(7), None);
+    }
+
+    #[test]
+    fn test_eval_cache_matches_evaluation() {
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4")
+            .unwrap();
+        let mut cache = EvalCache::new(1);
+        let expected = crate::eval::evaluate(&board);
+        assert_eq!(cache.get_or_insert(&board, crate::eval::evaluate), expected);
+        // Served from the cache: the evaluator is not called again
+        assert_eq!(cache.get_or_insert(&board, |_| unreachable!()), expected);
+    }
+}
@@ -3,20 +3,38 @@
 //!
 //!
 
+pub mod affinity;
+pub mod eval_cache;
+pub mod observer;
 pub mod params;
+pub mod root;
+pub mod root_split;
+pub mod score;
 // The module name matches its parent directory (`search/search.rs`), which is
 // a standard Rust pattern for the primary module file in a directory.
 #[allow(clippy::module_inception)]
 pub mod search;
+pub mod stack;
 pub mod stats;
 pub mod thread_mgr;
+pub mod trace;
 pub mod tt;
+pub mod variety;
 
+pub use self::eval_cache::EvalCache;
+pub use self::observer::{
+    AspirationStats, IterationInfo, NoopObserver, ObserverSet, ScoreBound, SearchObserver, TtStats,
+};
 pub use self::params::SearchParams;
-pub use self::search::Search;
+pub use self::root::{GameScores, RootMove};
+pub use self::root_split::{RootSplit, SmpMode};
+pub use self::score::mate_in;
+pub use self::search::{Search, MAX_DEPTH, MAX_PLY};
 pub use self::stats::SearchStats;
-pub use self::thread_mgr::ThreadManager;
+pub use self::thread_mgr::{JobHandle, ThreadManager};
+pub use self::trace::{SearchTrace, TraceDecision, TraceNode};
 pub use self::tt::TranspositionTable;
+pub use self::variety::VarietyRng;
 pub use crate::board::Move;
 
 /// Result of a completed search job, including move, score, PV and stats.
@@ -29,5 +47,15 @@ pub struct SearchResult {
     pub nodes: u64,
     pub nps: u64,
     pub seldepth: u8,
-    pub hashfull: u8,
+    /// Transposition table occupancy in permille
+    pub hashfull: u16,
+    /// Root moves of the last completed iteration (score, bound, PV, effort)
+    pub root_moves: Vec<RootMove>,
+}
+
+impl SearchResult {
+    /// Moves to mate when the score is a forced mate (see [`mate_in`])
+    pub fn mate(&self) -> Option<i16> {
+        mate_in(self.score)
+    }
 }
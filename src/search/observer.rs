@@ -0,0 +1,151 @@
+//! Search telemetry hooks
+//!
+//! A [`SearchObserver`] receives structured events from a running search, so
+//! integrations (metrics exporters, loggers, servers) can monitor the engine
+//! without parsing UCI text. Every method has an empty default body; the hooks
+//! fire per root move, per iteration or per search, never per node, so an
+//! engine without an observer pays only an `Option` check at those points.
+
+use crate::board::Move;
+use crate::search::stats::SearchStats;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Summary of a completed iterative-deepening iteration
+#[derive(Debug, Clone)]
+pub struct IterationInfo {
+    /// Depth just completed
+    pub depth: u8,
+
+    /// Selective depth reached so far
+    pub seldepth: u8,
+
+    /// Score from the side to move's point of view
+    pub score: i16,
+
+    /// Nodes searched so far (same count as the final UCI `info nodes`)
+    pub nodes: u64,
+
+    /// Time since the search started
+    pub elapsed: Duration,
+
+    /// Transposition table occupancy in permille (UCI `hashfull`)
+    pub hashfull: u16,
+
+    /// Principal variation of this iteration
+    pub pv: Vec<Move>,
+
+    /// Effective branching factor of the iterations so far (None early on)
+    pub branching_factor: Option<f64>,
+
+    /// Predicted duration of the next iteration (None early on)
+    pub next_iteration: Option<Duration>,
+}
+
+/// Which side of the aspiration window a search failed on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScoreBound {
+    /// Failed high: the score is at least the reported one (UCI `lowerbound`)
+    Lower,
+    /// Failed low: the score is at most the reported one (UCI `upperbound`)
+    Upper,
+}
+
+/// Transposition table usage sampled after an iteration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TtStats {
+    /// Table occupancy in permille (UCI `hashfull`)
+    pub hashfull: u16,
+
+    /// Probes that found an entry during this search so far
+    pub hits: u64,
+}
+
+/// Aspiration window failures of the search so far, reported after each
+/// iteration to tune `SearchParams::aspiration_window`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AspirationStats {
+    /// Searches that failed high (UCI `lowerbound`)
+    pub fail_highs: u64,
+
+    /// Searches that failed low (UCI `upperbound`)
+    pub fail_lows: u64,
+
+    /// Nodes spent re-searching after those failures
+    pub research_nodes: u64,
+
+    /// All nodes searched so far
+    pub nodes: u64,
+}
+
+/// Callbacks invoked by [`crate::search::Search`] while it runs.
+///
+/// Implementations must be cheap and non-blocking: they are called from the
+/// search thread.
+pub trait SearchObserver: Send + Sync {
+    /// An iteration of iterative deepening finished
+    fn on_iteration(&self, _info: &IterationInfo) {}
+
+    /// An aspiration search at `info.depth` fell outside its window and will
+    /// be repeated with a wider one; `info.score` is only a bound
+    fn on_aspiration_fail(&self, _info: &IterationInfo, _bound: ScoreBound) {}
+
+    /// Transposition table statistics, reported after each iteration
+    fn on_tt_stats(&self, _stats: &TtStats) {}
+
+    /// Aspiration window statistics, reported after each iteration
+    fn on_aspiration_stats(&self, _stats: &AspirationStats) {}
+
+    /// The root move numbered `number` (1-based) starts being searched at `depth`
+    fn on_currmove(&self, _depth: u8, _mv: Move, _number: u32, _elapsed: Duration) {}
+
+    /// The search picked its final move
+    fn on_bestmove(&self, _best_move: Move, _score: i16) {}
+
+    /// The search stopped (depth reached, time out or stop request)
+    fn on_stop(&self, _stats: &SearchStats) {}
+}
+
+/// Forwards every event to several observers, in order
+#[derive(Default, Clone)]
+pub struct ObserverSet(pub Vec<Arc<dyn SearchObserver>>);
+
+impl SearchObserver for ObserverSet {
+    fn on_iteration(&self, info: &IterationInfo) {
+        self.0.iter().for_each(|o| o.on_iteration(info));
+    }
+
+    fn on_aspiration_fail(&self, info: &IterationInfo, bound: ScoreBound) {
+        self.0
+            .iter()
+            .for_each(|o| o.on_aspiration_fail(info, bound));
+    }
+
+    fn on_tt_stats(&self, stats: &TtStats) {
+        self.0.iter().for_each(|o| o.on_tt_stats(stats));
+    }
+
+    fn on_aspiration_stats(&self, stats: &AspirationStats) {
+        self.0.iter().for_each(|o| o.on_aspiration_stats(stats));
+    }
+
+    fn on_currmove(&self, depth: u8, mv: Move, number: u32, elapsed: Duration) {
+        self.0
+            .iter()
+            .for_each(|o| o.on_currmove(depth, mv, number, elapsed));
+    }
+
+    fn on_bestmove(&self, best_move: Move, score: i16) {
+        self.0.iter().for_each(|o| o.on_bestmove(best_move, score));
+    }
+
+    fn on_stop(&self, stats: &SearchStats) {
+        self.0.iter().for_each(|o| o.on_stop(stats));
+    }
+}
+
+/// Observer that ignores every event
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoopObserver;
+
+impl SearchObserver for NoopObserver {}
@@ -9,12 +9,23 @@ pub struct SearchParams {
     /// Maximum search depth in plies
     pub max_depth: u8,
 
-    /// Time limit in milliseconds (0 = unlimited)
+    /// Time limit in milliseconds (0 = unlimited); the soft limit when
+    /// `hard_time_limit_ms` is larger
     pub time_limit_ms: u64,
 
-    /// Node limit (0 = unlimited)
+    /// Hard time limit in milliseconds: how far the search may run past
+    /// `time_limit_ms` while the best move is unstable (0 = no extension)
+    pub hard_time_limit_ms: u64,
+
+    /// Node limit (0 = unlimited): the search stops as soon as it has
+    /// searched this many nodes, and plays the best move found so far
     pub node_limit: u64,
 
+    /// Nodes per second the search may not exceed (0 = unlimited): it sleeps
+    /// whenever it gets ahead, so it plays like a slower engine. A job of the
+    /// thread manager shares it out between the workers.
+    pub nps_limit: u64,
+
     /// Alpha-beta aspiration window size in centipawns
     pub aspiration_window: i16,
 
@@ -40,6 +51,30 @@ pub struct SearchParams {
     /// Minimum depth for futility pruning
     pub futility_min_depth: u8,
 
+    /// Prune and reduce harder in nodes whose static eval is not better than
+    /// two plies earlier ("improving", see `search::stack`)
+    pub enable_improving: bool,
+
+    /// Futility margin given up in nodes that are not improving
+    pub improving_margin: i16,
+
+    /// Enable late move pruning: at low depths, once enough moves have been
+    /// searched, the remaining quiet moves of a non-PV node are skipped
+    pub enable_lmp: bool,
+
+    /// Late move pruning thresholds by depth (`lmp_move_counts[depth - 1]`
+    /// moves are searched before quiet moves are skipped; deeper nodes are
+    /// never pruned). Nodes that are not improving use two thirds of them.
+    pub lmp_move_counts: Vec<u32>,
+
+    /// Enable internal iterative reduction: PV nodes without a TT move are
+    /// searched one ply shallower (the shallow search then provides the TT
+    /// move for the next iteration)
+    pub enable_iir: bool,
+
+    /// Minimum depth for internal iterative reduction
+    pub iir_min_depth: u8,
+
     /// Number of killer move slots
     pub killer_moves_count: usize,
 
@@ -55,6 +90,61 @@ pub struct SearchParams {
 
     /// Razoring margin in centipawns (very conservative to avoid missing tactics)
     pub razoring_margin: i16,
+
+    /// Root penalty for moves that recreate a position already seen in the game
+    /// (shuffle loops), applied to exact scores only, when ahead by at least
+    /// `shuffle_min_advantage` and not better than the last search of the root
+    pub shuffle_penalty: i16,
+
+    /// Minimum root score (centipawns) before shuffle loops are penalized
+    pub shuffle_min_advantage: i16,
+
+    /// Restrict the root search to these moves (UCI `go searchmoves`; empty = all)
+    pub search_moves: Vec<crate::board::Move>,
+
+    /// Root move learned in earlier games (experience book), searched first
+    /// when the transposition table has no move for the root
+    pub experience_move: Option<crate::board::Move>,
+
+    /// Evaluation weights and contempt of the playing style (UCI `Style`)
+    pub eval_weights: crate::eval::EvalWeights,
+
+    /// Evaluation constants (tunable through UCI in `scacchista tune`)
+    pub eval_params: crate::eval::EvalParams,
+
+    /// Draw score shift (centipawns) against `engine_color`, on top of the
+    /// style's own contempt (UCI `Contempt`)
+    pub contempt: i16,
+
+    /// Color the engine is playing; contempt is measured from its point of
+    /// view (None = the side to move at the root)
+    pub engine_color: Option<crate::board::Color>,
+
+    /// Seed for move-ordering jitter among quiet moves (0 = off). Lazy-SMP
+    /// helpers use distinct seeds so they explore the tree in different orders.
+    pub ordering_jitter: u32,
+
+    /// Root moves within this many centipawns of the best one may be played
+    /// instead (0 = always the best move, see `search::variety`)
+    pub move_variety: i16,
+
+    /// Seed of the random bonuses of `move_variety`
+    pub variety_seed: u64,
+
+    /// Static evaluation cache size in MB per search thread (0 = disabled)
+    pub eval_cache_mb: usize,
+
+    /// Record the tree of the last root search (see `search::trace`)
+    pub trace: bool,
+
+    /// Most nodes recorded per root search when tracing
+    pub trace_max_nodes: usize,
+
+    /// Collect move ordering telemetry in `SearchStats::ordering`
+    pub ordering_telemetry: bool,
+
+    /// How the threads of a `ThreadManager` share the search (UCI `SMPMode`)
+    pub smp_mode: crate::search::root_split::SmpMode,
 }
 
 impl Default for SearchParams {
@@ -62,7 +152,9 @@ impl Default for SearchParams {
         Self {
             max_depth: 8,
             time_limit_ms: 5000,
+            hard_time_limit_ms: 0,
             node_limit: 0,
+            nps_limit: 0,
             aspiration_window: 50,          // 0.5 pawn
             enable_null_move_pruning: true, // Re-enabled after perft fix
             null_move_min_depth: 2,
@@ -72,11 +164,33 @@ impl Default for SearchParams {
             enable_futility_pruning: true, // Re-enabled after LMR tested
             futility_margin: 150,          // 2.0 pawns (conservative to avoid missing tactics)
             futility_min_depth: 3,
+            enable_improving: true,
+            improving_margin: 50,
+            enable_lmp: true,
+            lmp_move_counts: vec![5, 8, 13, 20],
+            enable_iir: true,
+            iir_min_depth: 4,
             killer_moves_count: 2,
             qsearch_depth: 4,
             enable_qsearch_optimizations: false, // FIX v0.5.1: disabilitato - Delta pruning troppo aggressivo
             enable_razoring: true,               // v0.5.3: Abilitato con margine ultra-conservativo
             razoring_margin: 50,                 // 0.5 pawns (molto conservativo)
+            shuffle_penalty: 20,
+            shuffle_min_advantage: 150,
+            search_moves: Vec::new(),
+            experience_move: None,
+            eval_weights: crate::eval::EvalWeights::NORMAL,
+            eval_params: crate::eval::EvalParams::DEFAULT,
+            contempt: 0,
+            engine_color: None,
+            ordering_jitter: 0,
+            move_variety: 0,
+            variety_seed: 0,
+            eval_cache_mb: crate::search::eval_cache::DEFAULT_EVAL_CACHE_MB,
+            trace: false,
+            trace_max_nodes: 100_000,
+            ordering_telemetry: false,
+            smp_mode: crate::search::root_split::SmpMode::LazySmp,
         }
     }
 }
@@ -87,9 +201,9 @@ impl SearchParams {
         Self::default()
     }
 
-    /// Set maximum depth in plies
+    /// Set maximum depth in plies (at most [`crate::search::MAX_DEPTH`])
     pub fn max_depth(mut self, depth: u8) -> Self {
-        self.max_depth = depth;
+        self.max_depth = depth.min(crate::search::MAX_DEPTH);
         self
     }
 
@@ -99,18 +213,50 @@ impl SearchParams {
         self
     }
 
+    /// Set the hard time limit in milliseconds (0 = no extension)
+    pub fn hard_time_limit(mut self, ms: u64) -> Self {
+        self.hard_time_limit_ms = ms;
+        self
+    }
+
+    /// Set soft and hard time limits from a time allocation
+    pub fn time_allocation(self, alloc: crate::time::TimeAllocation) -> Self {
+        self.time_limit(alloc.soft_ms)
+            .hard_time_limit(alloc.hard_ms)
+    }
+
     /// Set aspiration window size in centipawns
     pub fn aspiration_window(mut self, window: i16) -> Self {
         self.aspiration_window = window;
         self
     }
 
+    /// Set the move-ordering jitter seed (0 disables jitter)
+    pub fn ordering_jitter(mut self, seed: u32) -> Self {
+        self.ordering_jitter = seed;
+        self
+    }
+
+    /// Set the move variety margin in centipawns and the seed of its random
+    /// bonuses (a margin of 0 always plays the best move)
+    pub fn move_variety(mut self, cp: i16, seed: u64) -> Self {
+        self.move_variety = cp.max(0);
+        self.variety_seed = seed;
+        self
+    }
+
     /// Set node limit
     pub fn node_limit(mut self, limit: u64) -> Self {
         self.node_limit = limit;
         self
     }
 
+    /// Set the nodes per second limit
+    pub fn nps_limit(mut self, nps: u64) -> Self {
+        self.nps_limit = nps;
+        self
+    }
+
     /// Enable or disable null-move pruning
     pub fn enable_null_move_pruning(mut self, enable: bool) -> Self {
         self.enable_null_move_pruning = enable;
@@ -159,6 +305,42 @@ impl SearchParams {
         self
     }
 
+    /// Enable or disable the improving adjustments to pruning and reductions
+    pub fn enable_improving(mut self, enable: bool) -> Self {
+        self.enable_improving = enable;
+        self
+    }
+
+    /// Set the futility margin given up in nodes that are not improving
+    pub fn improving_margin(mut self, margin: i16) -> Self {
+        self.improving_margin = margin;
+        self
+    }
+
+    /// Enable or disable late move pruning
+    pub fn enable_lmp(mut self, enable: bool) -> Self {
+        self.enable_lmp = enable;
+        self
+    }
+
+    /// Set the late move pruning thresholds, indexed by depth - 1
+    pub fn lmp_move_counts(mut self, counts: Vec<u32>) -> Self {
+        self.lmp_move_counts = counts;
+        self
+    }
+
+    /// Enable or disable internal iterative reduction
+    pub fn enable_iir(mut self, enable: bool) -> Self {
+        self.enable_iir = enable;
+        self
+    }
+
+    /// Set minimum depth for internal iterative reduction
+    pub fn iir_min_depth(mut self, depth: u8) -> Self {
+        self.iir_min_depth = depth;
+        self
+    }
+
     /// Set killer moves count
     pub fn killer_moves_count(mut self, count: usize) -> Self {
         self.killer_moves_count = count;
@@ -176,6 +358,84 @@ impl SearchParams {
         self.enable_qsearch_optimizations = enable;
         self
     }
+
+    /// Set root shuffle-loop penalty (0 disables it)
+    pub fn shuffle_penalty(mut self, penalty: i16) -> Self {
+        self.shuffle_penalty = penalty;
+        self
+    }
+
+    /// Set minimum advantage before shuffle loops are penalized
+    pub fn shuffle_min_advantage(mut self, cp: i16) -> Self {
+        self.shuffle_min_advantage = cp;
+        self
+    }
+
+    /// Restrict the root search to the given moves (empty = all legal moves)
+    pub fn search_moves(mut self, moves: Vec<crate::board::Move>) -> Self {
+        self.search_moves = moves;
+        self
+    }
+
+    /// Set the experience-book move searched first at the root
+    pub fn experience_move(mut self, mv: Option<crate::board::Move>) -> Self {
+        self.experience_move = mv;
+        self
+    }
+
+    /// Set the evaluation weights (playing style)
+    pub fn eval_weights(mut self, weights: crate::eval::EvalWeights) -> Self {
+        self.eval_weights = weights;
+        self
+    }
+
+    /// Set the evaluation constants
+    pub fn eval_params(mut self, params: crate::eval::EvalParams) -> Self {
+        self.eval_params = params;
+        self
+    }
+
+    /// Set the contempt in centipawns (positive = avoid draws)
+    pub fn contempt(mut self, cp: i16) -> Self {
+        self.contempt = cp;
+        self
+    }
+
+    /// Set the color the engine is playing (contempt point of view)
+    pub fn engine_color(mut self, color: Option<crate::board::Color>) -> Self {
+        self.engine_color = color;
+        self
+    }
+
+    /// Set the evaluation cache size in MB (0 disables the cache)
+    pub fn eval_cache_mb(mut self, mb: usize) -> Self {
+        self.eval_cache_mb = mb;
+        self
+    }
+
+    /// Enable or disable search tree tracing
+    pub fn trace(mut self, enable: bool) -> Self {
+        self.trace = enable;
+        self
+    }
+
+    /// Set the most nodes recorded per root search when tracing
+    pub fn trace_max_nodes(mut self, max_nodes: usize) -> Self {
+        self.trace_max_nodes = max_nodes;
+        self
+    }
+
+    /// Enable or disable move ordering telemetry
+    pub fn ordering_telemetry(mut self, enable: bool) -> Self {
+        self.ordering_telemetry = enable;
+        self
+    }
+
+    /// Set how the threads share the search
+    pub fn smp_mode(mut self, mode: crate::search::root_split::SmpMode) -> Self {
+        self.smp_mode = mode;
+        self
+    }
 }
 
 /// Search time management parameters
@@ -186,6 +446,9 @@ pub struct TimeManagement {
     pub moves_to_go_left: u8,
     pub inc_ms: u64,
     pub msec_per_move: u64,
+    /// Game phase of the position to move in (24 = all pieces, 0 = pawn
+    /// endgame, see `Board::phase`); None = no phase scaling
+    pub game_phase: Option<u8>,
 }
 
 impl Default for TimeManagement {
@@ -202,9 +465,16 @@ impl TimeManagement {
             moves_to_go_left: 40,
             inc_ms: 0,
             msec_per_move: 5000, // Default 5 seconds per move
+            game_phase: None,
         }
     }
 
+    /// Time management for a position of the given game phase
+    pub fn with_phase(mut self, phase: u8) -> Self {
+        self.game_phase = Some(phase);
+        self
+    }
+
     /// Calculate time to allocate for current move
     pub fn allocate_time(&self) -> u64 {
         if self.time_left_ms == 0 {
@@ -0,0 +1,161 @@
+//! Root move list
+//!
+//! The root moves of a search live in a list that persists across iterative
+//! deepening iterations. Each [`RootMove`] keeps its latest score (with the
+//! bound it represents), the score of the previous iteration, its own PV and
+//! the effort spent on it, which is what MultiPV, easy-move detection, result
+//! aggregation and `rootmove` reporting need.
+
+use crate::board::{Board, Move};
+use crate::search::score::INFINITE;
+
+/// A root move and what the search learned about it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootMove {
+    pub mv: Move,
+
+    /// Score of the latest search of this move (-INFINITE until searched)
+    pub score: i16,
+
+    /// Score at the end of the previous iteration (-INFINITE before that)
+    pub prev_score: i16,
+
+    /// Principal variation starting with `mv`; just `[mv]` unless the move
+    /// raised alpha (a bound has no reliable continuation)
+    pub pv: Vec<Move>,
+
+    /// Nodes searched in this move's subtree (including qsearch)
+    pub nodes: u64,
+
+    /// Transposition table hits inside this move's subtree
+    pub tt_hits: u64,
+
+    /// `score` failed high: the true score is at least `score`
+    pub lower_bound: bool,
+
+    /// `score` failed low: the true score is at most `score`
+    pub upper_bound: bool,
+
+    /// Searched in the current root search (not skipped by a cutoff or a stop)
+    pub searched: bool,
+
+    /// `score` is a draw by repetition of a position played before the search
+    /// or by the 50-move rule: it depends on the game history, not just on
+    /// the root position
+    pub path_draw: bool,
+
+    /// `score` was lowered by the shuffle penalty, which depends on the
+    /// game's earlier positions and scores as well
+    pub shuffle_penalized: bool,
+}
+
+impl RootMove {
+    pub fn new(mv: Move) -> Self {
+        Self {
+            mv,
+            score: -INFINITE,
+            prev_score: -INFINITE,
+            pv: vec![mv],
+            nodes: 0,
+            tt_hits: 0,
+            lower_bound: false,
+            upper_bound: false,
+            searched: false,
+            path_draw: false,
+            shuffle_penalized: false,
+        }
+    }
+
+    /// Neither bound: `score` is exact
+    pub fn is_exact(&self) -> bool {
+        !self.lower_bound && !self.upper_bound
+    }
+
+    /// `score` is not the value of the root position alone (a path draw or
+    /// a shuffle penalty), so it must not be stored for the root key
+    pub fn depends_on_history(&self) -> bool {
+        self.path_draw || self.shuffle_penalized
+    }
+}
+
+/// Reorder `list` to follow `order`, keeping what is known about each move and
+/// adding entries for moves not in the list yet; moves missing from `order`
+/// are dropped
+pub fn sync_root_moves(list: &mut Vec<RootMove>, order: &[Move]) {
+    let mut old = std::mem::take(list);
+    list.extend(
+        order
+            .iter()
+            .map(|&mv| match old.iter().position(|rm| rm.mv == mv) {
+                Some(i) => old.swap_remove(i),
+                None => RootMove::new(mv),
+            }),
+    );
+}
+
+/// Root scores of the positions searched earlier in the game (score for the
+/// side to move), kept across searches: a shuffle loop that comes back to a
+/// root without a better score makes no progress
+#[derive(Debug, Clone, Default)]
+pub struct GameScores(Vec<(u64, i16)>);
+
+impl GameScores {
+    /// Score of the last search of the position `key`
+    pub fn get(&self, key: u64) -> Option<i16> {
+        self.0
+            .iter()
+            .find(|&&(k, _)| k == key)
+            .map(|&(_, score)| score)
+    }
+
+    /// Record the result of a search of the position `key`
+    pub fn record(&mut self, key: u64, score: i16) {
+        match self.0.iter_mut().find(|(k, _)| *k == key) {
+            Some(entry) => entry.1 = score,
+            None => self.0.push((key, score)),
+        }
+    }
+
+    /// Forget the positions that are not in the history of `board` (another
+    /// game, or before an irreversible move)
+    pub fn retain_history(&mut self, board: &Board) {
+        self.0.retain(|&(key, _)| board.in_history(key));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sync_keeps_scores_and_follows_order() {
+        let mut list: Vec<RootMove> = [1, 2, 3].map(|n| RootMove::new(Move::from_raw(n))).into();
+        list[1].score = 42;
+        list[1].prev_score = 17;
+
+        sync_root_moves(&mut list, &[2, 4, 1].map(Move::from_raw));
+        let moves: Vec<u32> = list.iter().map(|rm| rm.mv.raw()).collect();
+        assert_eq!(moves, vec![2, 4, 1]);
+        assert_eq!((list[0].score, list[0].prev_score), (42, 17));
+        assert_eq!(list[1], RootMove::new(Move::from_raw(4)));
+        assert!(list[1].is_exact());
+    }
+
+    #[test]
+    fn test_game_scores_follow_the_board_history() {
+        crate::init();
+        let mut board = Board::new();
+        board.set_from_fen(crate::board::START_FEN).unwrap();
+        let start = board.zobrist;
+        let mut scores = GameScores::default();
+        scores.record(start, 20);
+        scores.record(start, 35);
+        scores.record(12345, 99);
+
+        let mv = crate::board::parse_uci_move(&mut board, "g1f3").unwrap();
+        board.make_move(mv);
+        scores.retain_history(&board);
+        assert_eq!(scores.get(start), Some(35));
+        assert_eq!(scores.get(12345), None);
+    }
+}
@@ -0,0 +1,292 @@
+//! Root splitting: an alternative to lazy SMP for small thread counts
+//!
+//! In [`SmpMode::RootSplit`] the workers of a job do not each search the whole
+//! tree. Every iteration, the first worker to reach a depth fixes the order of
+//! the root moves, and move `i` goes to the partition of worker
+//! `i % workers`. A worker searches its own partition first, in order, then
+//! steals the last unclaimed moves of the others. Every move is searched
+//! with the best score found so far at that depth, by any worker, as alpha
+//! (the shared alpha), so the rest of the moves only have to be refuted.
+//!
+//! A worker that runs out of moves waits until the depth is complete and then
+//! takes over the combined result (best move, score, PV and every root move),
+//! so all workers start the next iteration from the same root. The moves of
+//! a worker that panicked are released for the others to claim.
+
+use crate::board::Move;
+use crate::search::root::RootMove;
+use crate::search::score::INFINITE;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// How the workers of a [`crate::search::ThreadManager`] share a search
+/// (UCI `SMPMode`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SmpMode {
+    /// Every worker searches the whole tree, sharing only the TT
+    #[default]
+    LazySmp,
+    /// The root moves are split among the workers (see [`RootSplit`])
+    RootSplit,
+}
+
+impl SmpMode {
+    /// Every mode, in the order of the UCI combo
+    pub const ALL: [SmpMode; 2] = [SmpMode::LazySmp, SmpMode::RootSplit];
+
+    /// Name of the mode in the UCI `SMPMode` combo
+    pub fn name(self) -> &'static str {
+        match self {
+            SmpMode::LazySmp => "LazySMP",
+            SmpMode::RootSplit => "RootSplit",
+        }
+    }
+
+    /// Mode by its UCI name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// One root move of a split depth
+struct SplitMove {
+    mv: Move,
+    /// Worker searching (or having searched) the move
+    owner: Option<usize>,
+    /// Result, once searched
+    result: Option<RootMove>,
+}
+
+/// Root moves of one iteration and the shared alpha
+struct SplitDepth {
+    moves: Vec<SplitMove>,
+    alpha: i16,
+}
+
+impl SplitDepth {
+    fn is_complete(&self) -> bool {
+        self.moves.iter().all(|m| m.result.is_some())
+    }
+}
+
+/// Root moves of a job shared by its workers, iteration by iteration
+pub struct RootSplit {
+    workers: usize,
+    /// Split state per depth (index = depth)
+    depths: Mutex<Vec<Option<SplitDepth>>>,
+    /// Notified when a move is searched or released
+    changed: Condvar,
+}
+
+impl RootSplit {
+    /// Split among `workers` workers (ids `0..workers`)
+    pub fn new(workers: usize) -> Self {
+        Self {
+            workers: workers.max(1),
+            depths: Mutex::new(Vec::new()),
+            changed: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Option<SplitDepth>>> {
+        self.depths
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Join the iteration of `depth`; the first worker to join sets the
+    /// order of the root moves
+    pub fn begin(&self, depth: u8, order: &[Move]) {
+        let mut depths = self.lock();
+        let idx = depth as usize;
+        if depths.len() <= idx {
+            depths.resize_with(idx + 1, || None);
+        }
+        depths[idx].get_or_insert_with(|| SplitDepth {
+            moves: order
+                .iter()
+                .map(|&mv| SplitMove {
+                    mv,
+                    owner: None,
+                    result: None,
+                })
+                .collect(),
+            alpha: -INFINITE,
+        });
+    }
+
+    /// Next move of `depth` for `worker` to search: the first unclaimed move
+    /// of its own partition, else the last unclaimed move of any other one
+    pub fn claim(&self, depth: u8, worker: usize) -> Option<Move> {
+        let mut depths = self.lock();
+        let split = depths.get_mut(depth as usize)?.as_mut()?;
+        let partition = worker % self.workers;
+        let idx = split
+            .moves
+            .iter()
+            .enumerate()
+            .find(|(i, m)| m.owner.is_none() && i % self.workers == partition)
+            .or_else(|| {
+                split
+                    .moves
+                    .iter()
+                    .enumerate()
+                    .rfind(|(_, m)| m.owner.is_none())
+            })
+            .map(|(i, _)| i)?;
+        split.moves[idx].owner = Some(worker);
+        Some(split.moves[idx].mv)
+    }
+
+    /// Best score found so far at `depth`, the alpha to search with
+    pub fn alpha(&self, depth: u8) -> i16 {
+        self.lock()
+            .get(depth as usize)
+            .and_then(|split| split.as_ref())
+            .map_or(-INFINITE, |split| split.alpha)
+    }
+
+    /// Record the result of a claimed move
+    pub fn complete(&self, depth: u8, result: RootMove) {
+        let mut depths = self.lock();
+        if let Some(split) = depths.get_mut(depth as usize).and_then(|s| s.as_mut()) {
+            if let Some(m) = split.moves.iter_mut().find(|m| m.mv == result.mv) {
+                split.alpha = split.alpha.max(result.score);
+                m.result = Some(result);
+            }
+        }
+        self.changed.notify_all();
+    }
+
+    /// Give back the moves `worker` claimed but did not finish (its search
+    /// panicked), at every depth
+    pub fn release(&self, worker: usize) {
+        let mut depths = self.lock();
+        for split in depths.iter_mut().flatten() {
+            for m in &mut split.moves {
+                if m.owner == Some(worker) && m.result.is_none() {
+                    m.owner = None;
+                }
+            }
+        }
+        self.changed.notify_all();
+    }
+
+    /// Root moves of a complete `depth` in split order, and the index of the
+    /// best one (the first of the highest score); None while moves are left
+    pub fn results(&self, depth: u8) -> Option<(Vec<RootMove>, usize)> {
+        let depths = self.lock();
+        let split = depths.get(depth as usize)?.as_ref()?;
+        if !split.is_complete() {
+            return None;
+        }
+        let moves: Vec<RootMove> = split.moves.iter().flat_map(|m| m.result.clone()).collect();
+        let best = moves.iter().enumerate().fold(0, |best, (i, rm)| {
+            if rm.score > moves[best].score {
+                i
+            } else {
+                best
+            }
+        });
+        Some((moves, best))
+    }
+
+    /// Block until a move of `depth` is searched or released, at most
+    /// `timeout` (the caller polls its stop flag and clock in between)
+    pub fn wait(&self, depth: u8, timeout: Duration) {
+        let depths = self.lock();
+        let pending = |depths: &mut Vec<Option<SplitDepth>>| {
+            depths
+                .get(depth as usize)
+                .and_then(|s| s.as_ref())
+                .is_some_and(|split| {
+                    !split.is_complete() && split.moves.iter().all(|m| m.owner.is_some())
+                })
+        };
+        let _ = self.changed.wait_timeout_while(depths, timeout, pending);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn searched(mv: Move, score: i16) -> RootMove {
+        RootMove {
+            score,
+            searched: true,
+            ..RootMove::new(mv)
+        }
+    }
+
+    #[test]
+    fn test_smp_mode_names() {
+        assert_eq!(SmpMode::default(), SmpMode::LazySmp);
+        for mode in SmpMode::ALL {
+            assert_eq!(SmpMode::from_name(mode.name()), Some(mode));
+        }
+        assert_eq!(SmpMode::from_name("rootsplit"), Some(SmpMode::RootSplit));
+        assert_eq!(SmpMode::from_name("YBWC"), None);
+    }
+
+    #[test]
+    fn test_root_split_partitions_then_steals() {
+        let split = RootSplit::new(2);
+        split.begin(3, &[10, 11, 12, 13, 14].map(Move::from_raw));
+        // A late joiner does not change the order
+        split.begin(3, &[14, 13, 12, 11, 10].map(Move::from_raw));
+
+        // Worker 0 owns 10, 12, 14 and worker 1 owns 11, 13
+        assert_eq!(split.claim(3, 0), Some(Move::from_raw(10)));
+        assert_eq!(split.claim(3, 1), Some(Move::from_raw(11)));
+        assert_eq!(split.claim(3, 1), Some(Move::from_raw(13)));
+        // Worker 1 is done with its partition: it steals from the tail
+        assert_eq!(split.claim(3, 1), Some(Move::from_raw(14)));
+        assert_eq!(split.claim(3, 0), Some(Move::from_raw(12)));
+        assert_eq!(split.claim(3, 0), None);
+        assert_eq!(split.claim(4, 0), None);
+    }
+
+    #[test]
+    fn test_root_split_shares_alpha_and_combines_results() {
+        let split = RootSplit::new(2);
+        split.begin(1, &[10, 11, 12].map(Move::from_raw));
+        assert_eq!(split.alpha(1), -INFINITE);
+        for worker in [0, 1, 0] {
+            split.claim(1, worker).unwrap();
+        }
+
+        split.complete(1, searched(Move::from_raw(11), 40));
+        assert_eq!(split.alpha(1), 40);
+        split.complete(1, searched(Move::from_raw(10), 15));
+        assert_eq!(split.alpha(1), 40);
+        assert!(split.results(1).is_none());
+
+        split.complete(1, searched(Move::from_raw(12), 40));
+        let (moves, best) = split.results(1).unwrap();
+        assert_eq!(
+            moves.iter().map(|rm| rm.mv.raw()).collect::<Vec<_>>(),
+            vec![10, 11, 12]
+        );
+        // Ties go to the move earlier in the order
+        assert_eq!(moves[best].mv, Move::from_raw(11));
+    }
+
+    #[test]
+    fn test_root_split_release_returns_unfinished_moves() {
+        let split = RootSplit::new(2);
+        split.begin(2, &[10, 11].map(Move::from_raw));
+        assert_eq!(split.claim(2, 0), Some(Move::from_raw(10)));
+        assert_eq!(split.claim(2, 1), Some(Move::from_raw(11)));
+        split.complete(2, searched(Move::from_raw(10), 5));
+
+        split.release(1);
+        assert_eq!(split.claim(2, 0), Some(Move::from_raw(11)));
+        split.complete(2, searched(Move::from_raw(11), 7));
+        assert_eq!(split.results(2).map(|(_, best)| best), Some(1));
+        // Nothing left to wait for
+        split.wait(2, Duration::from_secs(60));
+    }
+}
@@ -0,0 +1,157 @@
+//! Score constants and arithmetic
+//!
+//! Scores are centipawns from the side to move's point of view, stored as
+//! `i16` everywhere (search, TT, eval). Every score the search produces lies
+//! strictly inside `(-INFINITE, INFINITE)`:
+//!
+//! - ordinary evaluations stay below [`MATE_THRESHOLD`] ([`clamp_eval`])
+//! - forced mates are `MATE - plies` (winning) or `-(MATE - plies)` (losing),
+//!   with `MATE < INFINITE`
+//! - `±INFINITE` is only a window bound or the "nothing searched yet"
+//!   sentinel, never a result
+//!
+//! so negating a score never overflows and no result can be mistaken for a
+//! window bound. [`negate`] and [`clamp`] check this in debug builds.
+
+use super::search::MAX_PLY;
+
+/// Window bound beyond any score
+pub const INFINITE: i16 = 30000;
+
+/// Score of delivering mate right away (mates further away score less)
+pub const MATE: i16 = INFINITE - 1;
+
+/// Scores at or beyond this magnitude are forced mates (`MATE - plies to mate`)
+pub const MATE_THRESHOLD: i16 = MATE - MAX_PLY as i16;
+
+const _: () = assert!(MATE < INFINITE && MATE_THRESHOLD > 0);
+
+/// Whether `score` is a score or window bound the search can work with
+#[inline]
+pub const fn is_valid(score: i16) -> bool {
+    -INFINITE <= score && score <= INFINITE
+}
+
+/// Whether `score` is a forced mate, for either side
+#[inline]
+pub const fn is_mate(score: i16) -> bool {
+    score >= MATE_THRESHOLD || score <= -MATE_THRESHOLD
+}
+
+/// `score` from the other side's point of view
+#[inline]
+pub fn negate(score: i16) -> i16 {
+    debug_assert!(is_valid(score), "score {} out of range", score);
+    -score
+}
+
+/// Bring a score computed in wider arithmetic (window arithmetic, bonuses)
+/// back into the window bounds
+#[inline]
+pub fn clamp(score: i32) -> i16 {
+    score.clamp(-INFINITE as i32, INFINITE as i32) as i16
+}
+
+/// Keep a static evaluation out of the mate range, so no evaluation is ever
+/// reported or stored as a forced mate
+#[inline]
+pub fn clamp_eval(eval: i16) -> i16 {
+    eval.clamp(-MATE_THRESHOLD + 1, MATE_THRESHOLD - 1)
+}
+
+/// Moves to mate encoded in a root score: positive when the side to move
+/// mates, negative when it gets mated, None for an ordinary score.
+///
+/// The children of the root are searched at ply 0, so mating on the `n`-th
+/// move scores `MATE - (2n - 2)` and getting mated on the opponent's `n`-th
+/// move scores `-(MATE - (2n - 1))`.
+pub fn mate_in(score: i16) -> Option<i16> {
+    if score >= MATE_THRESHOLD {
+        Some((MATE - score) / 2 + 1)
+    } else if score <= -MATE_THRESHOLD {
+        Some(-((MATE + score + 1) / 2))
+    } else {
+        None
+    }
+}
+
+/// Convert a mate score from "distance from the root" to "distance from this
+/// node" before storing it, so a TT hit at another ply (or in another worker)
+/// reports the right mate distance.
+pub fn to_tt(score: i16, ply: u8) -> i16 {
+    if score >= MATE_THRESHOLD {
+        score + ply as i16
+    } else if score <= -MATE_THRESHOLD {
+        score - ply as i16
+    } else {
+        score
+    }
+}
+
+/// Inverse of [`to_tt`] for a TT hit found at `ply`
+pub fn from_tt(score: i16, ply: u8) -> i16 {
+    if score >= MATE_THRESHOLD {
+        score - ply as i16
+    } else if score <= -MATE_THRESHOLD {
+        score + ply as i16
+    } else {
+        score
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mate_scores_stay_inside_the_window() {
+        for ply in 0..MAX_PLY as i16 {
+            let mated = -(MATE - ply);
+            assert!(is_mate(mated) && is_mate(negate(mated)));
+            assert!(mated > -INFINITE && negate(mated) < INFINITE);
+        }
+        assert_eq!(negate(-INFINITE), INFINITE);
+    }
+
+    #[test]
+    fn test_clamps_keep_evaluations_and_windows_in_range() {
+        assert_eq!(clamp(i16::MAX as i32 * 2), INFINITE);
+        assert_eq!(clamp(-(i16::MAX as i32) * 2), -INFINITE);
+        assert_eq!(clamp(123), 123);
+        assert!(!is_mate(clamp_eval(i16::MAX)));
+        assert!(!is_mate(clamp_eval(-i16::MAX)));
+        assert_eq!(clamp_eval(-250), -250);
+    }
+
+    #[test]
+    fn test_tt_mate_scores_are_ply_relative() {
+        // Mate in 3 plies from the root, stored at ply 2: one ply from that node
+        let stored = to_tt(MATE - 3, 2);
+        assert_eq!(stored, MATE - 1);
+        // Read back at ply 4 (same position reached later): mate is 5 plies from the root
+        assert_eq!(from_tt(stored, 4), MATE - 5);
+        assert_eq!(from_tt(to_tt(-(MATE - 6), 3), 3), -(MATE - 6));
+        // Ordinary scores are untouched
+        assert_eq!(to_tt(250, 7), 250);
+        assert_eq!(from_tt(-250, 7), -250);
+    }
+
+    #[test]
+    fn test_mate_in_counts_moves_from_the_root() {
+        assert_eq!(mate_in(MATE), Some(1));
+        assert_eq!(mate_in(MATE - 2), Some(2));
+        assert_eq!(mate_in(MATE - 4), Some(3));
+        assert_eq!(mate_in(-(MATE - 1)), Some(-1));
+        assert_eq!(mate_in(-(MATE - 3)), Some(-2));
+        assert_eq!(mate_in(MATE_THRESHOLD - 1), None);
+        assert_eq!(mate_in(0), None);
+        assert_eq!(mate_in(-950), None);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "out of range")]
+    fn test_negating_an_invalid_score_is_caught() {
+        negate(i16::MIN);
+    }
+}
@@ -3,25 +3,95 @@
 //! Implements iterative deepening alpha-beta search with transposition table
 //! and basic move ordering capabilities.
 
+use super::eval_cache::EvalCache;
+use super::observer::{AspirationStats, IterationInfo, ScoreBound, SearchObserver, TtStats};
 use super::params::{SearchParams, TimeManagement};
-use super::stats::SearchStats;
+use super::root::{sync_root_moves, GameScores, RootMove};
+use super::root_split::RootSplit;
+use super::score::{self, INFINITE, MATE, MATE_THRESHOLD};
+use super::stack::SearchStackEntry;
+use super::stats::{MoveSource, SearchStats};
+use super::trace::{SearchTrace, TraceDecision};
 use super::tt::{NodeType, TranspositionTable};
-use crate::board::{
-    Board, Color, Move, PieceKind, FLAG_CASTLE_KING, FLAG_CASTLE_QUEEN, FLAG_PROMOTION,
-};
-use crate::{move_captured, move_flag, move_piece, move_to_sq};
-use std::collections::HashMap;
+use super::variety;
+use crate::board::{Board, Color, Move, PieceKind, FLAG_CASTLE_KING, FLAG_CASTLE_QUEEN};
+use crate::time::{IterationForecast, TimeManager, TimeState};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
 };
+use std::time::Duration;
+
+/// Deepest ply the search reaches (stack, PV table and mate range size).
+/// Nodes at the last ply return their static evaluation, however far
+/// extensions and quiescence push the line
+pub const MAX_PLY: usize = 128;
+
+/// Deepest iteration: `SearchParams::max_depth`, `go depth` and
+/// `Search::search` are clamped to it
+pub const MAX_DEPTH: u8 = (MAX_PLY - 1) as u8;
+
+/// Upper bound (exclusive) of the helper-thread move-ordering jitter, small
+/// next to the history scores so it only reorders moves of similar merit
+const ORDERING_JITTER_MAX: u32 = 32;
+
+/// Once an aspiration window has been widened past this half-width the next
+/// re-search uses a fully open window
+const ASPIRATION_MAX_WINDOW: i32 = 1000;
+
+/// Nodes between two reads of the stop flag (a power of two): cheap enough
+/// to stay out of the profile, frequent enough to stop within microseconds
+const STOP_POLL_NODES: u64 = 64;
+
+/// Nodes between two reads of the clock (a power of two)
+const TIME_POLL_NODES: u64 = 1024;
+
+/// Longest single sleep of the nodes-per-second throttle, so that a stop
+/// raised while it waits is seen within this many milliseconds
+const THROTTLE_SLICE_MS: u64 = 10;
+
+/// How often a root-split worker waiting for the others checks its stop flag
+/// and clock
+const ROOT_SPLIT_POLL: Duration = Duration::from_millis(5);
 
-/// Search engine configurations
-pub const INFINITE: i16 = 30000;
-pub const MATE: i16 = 30001;
-pub const MATE_THRESHOLD: i16 = 29999;
+/// Upper bound for history-style ordering scores
+const HISTORY_MAX: i16 = 1000;
 
-const MAX_PLY: usize = 128;
+/// Entries in a continuation history table: (piece, to) x (piece, to)
+const CONTINUATION_HISTORY_SIZE: usize = 6 * 64 * 6 * 64;
+
+/// Index of the (previous move, move) pair in a continuation history table
+fn continuation_index(prev: Move, mv: Move) -> Option<usize> {
+    if prev.is_none() {
+        return None;
+    }
+    let prev_key = prev.piece() as usize * 64 + prev.to();
+    Some((prev_key * 6 + mv.piece() as usize) * 64 + mv.to())
+}
+
+/// Entries in the capture history table: (piece, to) x captured piece
+const CAPTURE_HISTORY_SIZE: usize = 6 * 64 * 6;
+
+/// The capture history counts for 1/CAPTURE_HISTORY_DIVISOR of its value in
+/// the capture ordering key (at most ~125 cp): enough to reorder captures of
+/// similar SEE, not to put a losing capture ahead of a winning one
+const CAPTURE_HISTORY_DIVISOR: i16 = 8;
+
+/// Index of a capture in the capture history table
+fn capture_index(mv: Move) -> usize {
+    let captured = mv.captured().map_or(0, |k| k as usize);
+    (mv.piece() as usize * 64 + mv.to()) * 6 + captured
+}
+
+/// Capture ordering: higher key (SEE blended with the capture history)
+/// first, then most valuable victim
+fn compare_captures(a: Move, a_see: i16, b: Move, b_see: i16) -> std::cmp::Ordering {
+    b_see.cmp(&a_see).then_with(|| {
+        let a_victim = a.captured().map_or(0, |k| k as u8);
+        let b_victim = b.captured().map_or(0, |k| k as u8);
+        b_victim.cmp(&a_victim)
+    })
+}
 
 /// Calculate LMR reduction using formula instead of lookup table
 /// Reduction based on depth and move count (quiet moves only)
@@ -55,19 +125,32 @@ pub struct Search {
     /// Time management
     time_mgmt: TimeManagement,
 
-    /// Killer moves table [ply][index][slot]
-    killer_moves: Vec<Vec<Move>>,
-
     /// History heuristic table [color][piece][from_sq][to_sq]
     history: [[[i16; 64]; 6]; 2], // [color][piece][square]
 
-    /// SEE cache for current position [square] -> score
-    /// Clear cache between nodes to avoid invalid results
-    see_cache: HashMap<usize, i16>,
+    /// Countermove table [side to move][previous piece][previous to_sq]
+    countermoves: [[[Move; 64]; 6]; 2],
+
+    /// Counter-move history [(prev piece, prev to) x (piece, to)], see `continuation_index`
+    counter_history: Vec<i16>,
+
+    /// Follow-up history: like `counter_history` but keyed by our own previous move
+    followup_history: Vec<i16>,
+
+    /// Capture history [(piece, to) x captured piece], see `capture_index`
+    capture_history: Vec<i16>,
+
+    /// Per-ply node state (move that led to it, static eval, killers...),
+    /// indexed by the ply of the node; root children are at ply 0
+    stack: [SearchStackEntry; MAX_PLY + 1],
 
     /// Stop flag for cooperative cancellation of search
     stop_flag: Option<Arc<AtomicBool>>,
 
+    /// Start a new TT age at every search (false when the owner of a shared
+    /// table ages it, see [`Self::without_tt_aging`])
+    ages_tt: bool,
+
     /// Flag indicating time has expired during search
     /// Used for intra-depth time checking to exit search early
     time_expired: bool,
@@ -75,11 +158,67 @@ pub struct Search {
     /// Counter for time check sampling (check every N nodes to avoid overhead)
     time_check_counter: u64,
 
+    /// Time limit in force, fed with the root result of every iteration
+    time_state: TimeState,
+
+    /// The root failed low (aspiration re-search) in the current iteration
+    root_failed_low: bool,
+
+    /// Static evaluations of positions already met (see [`EvalCache`])
+    eval_cache: EvalCache,
+
+    /// Tree of the last root search (only with `SearchParams::trace`)
+    trace: Option<SearchTrace>,
+
+    /// Path-dependent draws (repetition, 50-move rule) found so far: a node
+    /// whose search found one and returns the draw score is not stored
+    path_draws: u64,
+
+    /// Path-dependent draws that also depend on the game before the root (a
+    /// repetition of a position played before the search, the 50-move rule):
+    /// only these make a root score a property of the path
+    game_draws: u64,
+
+    /// Length of the board history at the root of the current search
+    root_history_len: usize,
+
+    /// Root scores of the earlier searches of this game (see
+    /// [`Self::with_game_scores`])
+    game_scores: GameScores,
+
+    /// Score of the last search of the current root in this game (None =
+    /// first visit): a shuffle loop that does not beat it makes no progress
+    shuffle_baseline: Option<i16>,
+
     /// Principal Variation table [ply][move_idx]
     pv: [[Move; MAX_PLY]; MAX_PLY],
 
     /// Length of PV at each ply
     pv_length: [usize; MAX_PLY],
+
+    /// PV from the root: the root move followed by the PV of its subtree
+    /// (root children search at ply 0, so the root cannot use `pv[0]`)
+    root_pv: Vec<Move>,
+
+    /// Root move list, kept across the iterations of one search
+    root_list: Vec<RootMove>,
+
+    /// Root moves searched by the last completed iteration
+    completed_root_moves: Vec<RootMove>,
+
+    /// Telemetry hooks (None = no monitoring)
+    observer: Option<Arc<dyn SearchObserver>>,
+
+    /// Root moves shared with the other workers of a root-split job, and this
+    /// worker's id (None = search every root move)
+    root_split: Option<(Arc<RootSplit>, usize)>,
+
+    /// Node growth of the completed iterations, predicts the next one
+    forecast: IterationForecast,
+
+    /// Side the contempt is applied for: the engine's color, or the side to
+    /// move at the root when it is not known
+    contempt_side: Color,
 }
 
 impl Search {
@@ -93,76 +232,219 @@ impl Search {
     /// # Returns
     /// New search engine
     pub fn new(board: Board, tt_size_mb: usize, params: SearchParams) -> Self {
-        let killer_moves_count = params.killer_moves_count;
-        let max_ply = params.max_depth as usize + 1; // +1 for array indexing
+        let contempt_side = params.engine_color.unwrap_or(board.side);
+        // Allocated when the search starts (see `EvalCache::prepare`)
+        let eval_cache = EvalCache::new(0);
+        let trace = params
+            .trace
+            .then(|| SearchTrace::new(params.trace_max_nodes));
+        let mut stats = SearchStats::new();
+        if params.ordering_telemetry {
+            stats.enable_ordering_telemetry();
+        }
         Self {
             board,
             tt: Arc::new(TranspositionTable::new(tt_size_mb)),
             params,
-            stats: SearchStats::new(),
+            stats,
             time_mgmt: TimeManagement::new(),
-            killer_moves: vec![vec![0; killer_moves_count]; max_ply], // [ply][slot]
             history: [[[0; 64]; 6]; 2],
-            see_cache: HashMap::new(),
+            countermoves: [[[Move::NONE; 64]; 6]; 2],
+            counter_history: vec![0; CONTINUATION_HISTORY_SIZE],
+            followup_history: vec![0; CONTINUATION_HISTORY_SIZE],
+            capture_history: vec![0; CAPTURE_HISTORY_SIZE],
+            stack: [SearchStackEntry::EMPTY; MAX_PLY + 1],
             stop_flag: None,
+            ages_tt: true,
             time_expired: false,
             time_check_counter: 0,
-            pv: [[0; MAX_PLY]; MAX_PLY],
+            time_state: TimeState::default(),
+            root_failed_low: false,
+            eval_cache,
+            trace,
+            path_draws: 0,
+            game_draws: 0,
+            root_history_len: 0,
+            game_scores: GameScores::default(),
+            shuffle_baseline: None,
+            pv: [[Move::NONE; MAX_PLY]; MAX_PLY],
             pv_length: [0; MAX_PLY],
+            root_pv: Vec::new(),
+            root_list: Vec::new(),
+            completed_root_moves: Vec::new(),
+            observer: None,
+            root_split: None,
+            forecast: IterationForecast::default(),
+            contempt_side,
         }
     }
 
     /// Check if time has expired, with sampling to avoid overhead
     /// Returns true if search should stop immediately
-    /// Only checks actual time every 1024 nodes to minimize syscall overhead
+    /// Only reads the stop flag every `STOP_POLL_NODES` nodes and the clock
+    /// every `TIME_POLL_NODES` nodes to keep the check off the hot path
     fn check_time_expired(&mut self) -> bool {
         // If already expired, return immediately
         if self.time_expired {
             return true;
         }
 
-        // Check stop flag
-        if let Some(ref stop) = self.stop_flag {
-            if stop.load(Ordering::Relaxed) {
-                self.time_expired = true;
-                return true;
-            }
+        self.time_check_counter += 1;
+        if self.time_check_counter & (STOP_POLL_NODES - 1) != 0 {
+            return false;
+        }
+        if self.stop_requested() {
+            self.time_expired = true;
+            return true;
         }
 
         // Sample time check every 1024 nodes to avoid syscall overhead
-        self.time_check_counter += 1;
-        if self.time_check_counter & 0x3FF != 0 {
-            // Not time to check yet (every 1024 nodes)
+        if self.time_check_counter & (TIME_POLL_NODES - 1) != 0 {
             return false;
         }
 
         // Actually check time
-        if self.params.time_limit_ms > 0 {
-            if let Some(start) = self.stats.start_time {
-                let elapsed = start.elapsed().as_millis() as u64;
-                if elapsed >= self.params.time_limit_ms {
-                    self.time_expired = true;
-                    return true;
-                }
-            }
+        if self.time_limit_reached() {
+            self.time_expired = true;
+            return true;
         }
 
         false
     }
 
-    /// Set stop flag for cooperative cancellation
+    /// Whether the stop flag (see [`Self::with_stop_flag`]) is raised
+    fn stop_requested(&self) -> bool {
+        self.stop_flag
+            .as_ref()
+            .is_some_and(|stop| stop.load(Ordering::Relaxed))
+    }
+
+    /// Whether `SearchParams::node_limit` is used up. Checked before counting
+    /// a node, so the search never goes past the limit.
+    fn node_limit_reached(&mut self) -> bool {
+        if self.params.node_limit > 0 && self.stats.nodes >= self.params.node_limit {
+            self.time_expired = true;
+        }
+        self.time_expired
+    }
+
+    /// Sleep until the search is back under `SearchParams::nps_limit`. The
+    /// clock is read about once per millisecond worth of nodes. A low limit
+    /// can ask for long sleeps: they are taken in `THROTTLE_SLICE_MS` slices
+    /// that end on a stop and never outlast the time limit.
+    fn throttle_nps(&mut self) {
+        let nps = self.params.nps_limit;
+        let nodes = self.stats.nodes;
+        if nodes % (nps / 1000).clamp(1, 1024) != 0 {
+            return;
+        }
+        let Some(start) = self.stats.start_time else {
+            return;
+        };
+        let due_ms = nodes.saturating_mul(1000) / nps;
+        let limit_ms = self.time_state.limit_ms();
+        loop {
+            let elapsed_ms = start.elapsed().as_millis() as u64;
+            if self.stop_requested() || (limit_ms > 0 && elapsed_ms >= limit_ms) {
+                self.time_expired = true;
+                return;
+            }
+            if due_ms <= elapsed_ms {
+                return;
+            }
+            let mut wait_ms = (due_ms - elapsed_ms).min(THROTTLE_SLICE_MS);
+            if limit_ms > 0 {
+                wait_ms = wait_ms.min(limit_ms - elapsed_ms);
+            }
+            std::thread::sleep(Duration::from_millis(wait_ms));
+        }
+    }
+
+    /// Whether the time limit in force has passed (reads the clock)
+    fn time_limit_reached(&self) -> bool {
+        let limit_ms = self.time_state.limit_ms();
+        limit_ms > 0
+            && self
+                .stats
+                .start_time
+                .is_some_and(|start| start.elapsed().as_millis() as u64 >= limit_ms)
+    }
+
+    /// Stop cooperatively once `flag` is raised.
+    ///
+    /// The flag is polled every `STOP_POLL_NODES` nodes and before each
+    /// iteration. A stopped search returns the result of its last completed
+    /// iteration (the unfinished one is discarded), or what the first
+    /// iteration found before the stop when none completed. The flag is never
+    /// reset by the search: clear it before reusing it.
     pub fn with_stop_flag(mut self, flag: Arc<AtomicBool>) -> Self {
         self.stop_flag = Some(flag);
         self
     }
 
-    /// Use a shared transposition table (for multi-threaded search)
-    /// This allows multiple search instances to share the same TT
+    /// Probe and store into `tt` instead of the search's own table.
+    ///
+    /// Lazy-SMP workers share one table this way, and a table kept across
+    /// searches carries their results over. The table given to
+    /// [`Self::new`] is dropped, so pass a hash size of 0 there.
     pub fn with_shared_tt(mut self, tt: Arc<TranspositionTable>) -> Self {
         self.tt = tt;
         self
     }
 
+    /// Evaluate through `cache` (e.g. the one of the previous search on this
+    /// thread, see [`Self::take_eval_cache`]). It is resized to
+    /// `eval_cache_mb` and emptied if the evaluation changed when the search
+    /// starts, and kept as it is otherwise.
+    pub fn with_eval_cache(mut self, cache: EvalCache) -> Self {
+        self.eval_cache = cache;
+        self
+    }
+
+    /// Hand the evaluation cache over to the next search, leaving an empty
+    /// one behind
+    pub fn take_eval_cache(&mut self) -> EvalCache {
+        std::mem::replace(&mut self.eval_cache, EvalCache::new(0))
+    }
+
+    /// Start from the root scores of the earlier searches of the game (see
+    /// [`Self::take_game_scores`]), which tell a shuffle loop that makes no
+    /// progress from one that does
+    pub fn with_game_scores(mut self, scores: GameScores) -> Self {
+        self.game_scores = scores;
+        self
+    }
+
+    /// Hand the root scores, including this search's, over to the next search
+    pub fn take_game_scores(&mut self) -> GameScores {
+        std::mem::take(&mut self.game_scores)
+    }
+
+    /// Leave the TT age alone when the search starts.
+    ///
+    /// Every search normally calls [`TranspositionTable::new_search`], which
+    /// is right for a table with one searcher. Lazy-SMP workers share one
+    /// table per move, so the thread manager ages it once per job instead:
+    /// one age per worker would make the entries of the other workers (and,
+    /// after a few moves, of the previous ones) look stale.
+    pub fn without_tt_aging(mut self) -> Self {
+        self.ages_tt = false;
+        self
+    }
+
+    /// Attach an observer that receives per-iteration telemetry
+    pub fn with_observer(mut self, observer: Arc<dyn SearchObserver>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Search only the root moves `split` hands to worker `worker_id`, with
+    /// the other workers of a root-split job
+    pub fn with_root_split(mut self, split: Arc<RootSplit>, worker_id: usize) -> Self {
+        self.root_split = Some((split, worker_id));
+        self
+    }
+
     /// Create search with reasonable defaults
     pub fn with_board(board: Board) -> Self {
         let params = SearchParams::new().max_depth(8).time_limit(5000);
@@ -191,14 +473,36 @@ impl Search {
 
     /// Return the Principal Variation from the last search.
     pub fn get_pv(&self) -> Vec<Move> {
-        self.pv[0][..self.pv_length[0]].to_vec()
+        self.root_pv.clone()
+    }
+
+    /// Recorded tree of the last root search (None unless
+    /// `SearchParams::trace` is enabled)
+    pub fn trace(&self) -> Option<&SearchTrace> {
+        self.trace.as_ref()
+    }
+
+    /// Root moves searched by the last completed iteration, with their score,
+    /// bound, PV and effort.
+    pub fn root_moves(&self) -> &[RootMove] {
+        &self.completed_root_moves
+    }
+
+    /// Start a new iterative deepening iteration: the current root scores
+    /// become the previous ones
+    fn begin_root_iteration(&mut self) {
+        for rm in &mut self.root_list {
+            rm.prev_score = rm.score;
+        }
     }
 
     fn reset_pv(&mut self) {
         self.pv_length.fill(0);
+        self.root_pv.clear();
     }
 
-    fn update_pv(&mut self, ply: usize, mv: Move) {
+    fn update_pv(&mut self, ply: u8, mv: Move) {
+        let ply = ply as usize;
         self.pv[ply][0] = mv;
         let child_len = self.pv_length[ply + 1];
         self.pv_length[ply] = 1 + child_len;
@@ -215,29 +519,45 @@ impl Search {
     /// # Returns
     /// (best_move, score) found
     pub fn search(&mut self, max_depth: Option<u8>) -> (Move, i16) {
-        let max_depth = max_depth.unwrap_or(self.params.max_depth);
+        let max_depth = max_depth.unwrap_or(self.params.max_depth).min(MAX_DEPTH);
 
         self.stats.reset();
         self.stats.start_timing();
-        self.tt.new_search();
+        if self.ages_tt {
+            self.tt.new_search();
+        }
+        self.eval_cache.prepare(
+            self.params.eval_cache_mb,
+            &self.params.eval_params,
+            &self.params.eval_weights,
+        );
+        self.root_list.clear();
+        self.completed_root_moves.clear();
+        self.contempt_side = self.params.engine_color.unwrap_or(self.board.side);
+        self.game_scores.retain_history(&self.board);
+        self.shuffle_baseline = self.game_scores.get(self.board.zobrist);
+        self.forecast.clear();
 
         // Reset time management state for new search
         self.time_expired = false;
         self.time_check_counter = 0;
+        self.time_state = TimeState::new(self.params.time_limit_ms, self.params.hard_time_limit_ms);
 
-        let mut best_move = 0;
+        let mut best_move = Move::NONE;
         let mut best_score = -INFINITE;
+        // Result of the last fully completed iteration
+        let (mut completed_move, mut completed_score) = (Move::NONE, -INFINITE);
+        let mut completed_pv: Vec<Move> = Vec::new();
 
         // Iterative deepening with aspiration windows
         for depth in 1..=max_depth {
-            let _nodes_before = self.stats.nodes;
+            let nodes_before = self.stats.nodes;
+            let iteration_start = crate::time::Instant::now();
 
             // Check stop flag before starting new depth
-            if let Some(ref stop) = self.stop_flag {
-                if stop.load(Ordering::Relaxed) {
-                    // Stop requested, return best move found so far
-                    break;
-                }
+            if self.stop_requested() {
+                // Stop requested, return best move found so far
+                break;
             }
 
             // Check time limit before starting new depth (fast path)
@@ -246,70 +566,142 @@ impl Search {
             }
 
             // Check time limit before starting new depth
-            if self.params.time_limit_ms > 0 {
+            let limit_ms = self.time_state.limit_ms();
+            if limit_ms > 0 {
                 if let Some(start) = self.stats.start_time {
-                    if start.elapsed() > std::time::Duration::from_millis(self.params.time_limit_ms)
-                    {
+                    if start.elapsed() > std::time::Duration::from_millis(limit_ms) {
                         // Time expired, return best move found so far
                         self.time_expired = true;
                         break;
                     }
+                    // Do not start an iteration that is predicted not to finish
+                    if !TimeManager::should_start_iteration(
+                        &self.forecast,
+                        start.elapsed(),
+                        limit_ms,
+                    ) {
+                        break;
+                    }
                 }
             }
 
-            // Use aspiration window after depth 1 (we need a baseline score)
-            if depth <= 1 {
+            self.begin_root_iteration();
+            self.root_failed_low = false;
+
+            // Use aspiration window after depth 1 (we need a baseline score).
+            // A root split shares alpha between workers instead.
+            if depth <= 1 || self.root_split.is_some() {
                 // First depth: full window search
                 let (mv, score) = self.iddfs(depth, best_move, -INFINITE, INFINITE);
                 best_move = mv;
                 best_score = score;
             } else {
-                // Use aspiration window around previous best score
-                let window = self.params.aspiration_window;
-                let mut alpha = best_score.saturating_sub(window);
-                let mut beta = best_score.saturating_add(window);
-                let mut search_result = self.iddfs(depth, best_move, alpha, beta);
-                let (mut mv, mut score) = search_result;
-
-                // If score fell outside window, we need to re-search with wider window
-                if score <= alpha {
-                    // Failed low - re-search with lower bound
-                    alpha = -INFINITE;
-                    search_result = self.iddfs(depth, best_move, alpha, beta);
-                    (mv, score) = search_result;
-                } else if score >= beta {
-                    // Failed high - re-search with upper bound
-                    beta = INFINITE;
-                    search_result = self.iddfs(depth, best_move, alpha, beta);
-                    (mv, score) = search_result;
-                }
-
-                // Update best move and score
+                let (mv, score) = self.aspiration_search(depth, best_move, best_score);
                 best_move = mv;
                 best_score = score;
             }
 
+            // An iteration cut short by the stop flag or the time limit is not
+            // completed: it must not raise `completed_depth` (lazy-SMP result
+            // selection trusts it) nor replace the last complete result, unless
+            // nothing was completed at all
+            if self.time_expired {
+                if !completed_move.is_none() {
+                    best_move = completed_move;
+                    best_score = completed_score;
+                    self.root_pv = std::mem::take(&mut completed_pv);
+                }
+                break;
+            }
+            let second_score = self
+                .completed_root_moves
+                .iter()
+                .filter(|rm| rm.mv != best_move)
+                .map(|rm| rm.score)
+                .max();
+            self.time_state.record_iteration(
+                best_move,
+                best_score,
+                second_score,
+                self.root_failed_low,
+            );
+            completed_move = best_move;
+            completed_score = best_score;
+            completed_pv = self.root_pv.clone();
+            self.forecast
+                .record(self.stats.nodes - nodes_before, iteration_start.elapsed());
+
             // FIX Bug #3: Track last completed depth
             self.stats.completed_depth = depth;
+            self.notify_iteration(depth, best_score);
 
             // If we found mate, we can stop searching for deeper mates
             if best_score >= MATE {
                 break;
             }
+
+            // Easy move: the answer is clear, keep the rest of the time
+            if let Some(start) = self.stats.start_time {
+                if self.time_state.stop_early(start.elapsed()) {
+                    break;
+                }
+            }
+        }
+
+        if self.params.move_variety > 0 {
+            (best_move, best_score) = self.vary_best_move(best_move, best_score);
         }
 
         self.stats.update_timing();
+        self.record_game_score(best_move, best_score);
+        self.notify_finished(best_move, best_score);
         (best_move, best_score)
     }
 
+    /// Remember the result of this search for the later searches of the game
+    fn record_game_score(&mut self, best_move: Move, score: i16) {
+        if !best_move.is_none() {
+            self.game_scores.record(self.board.zobrist, score);
+        }
+    }
+
+    /// Move variety: maybe play another root move of the last completed
+    /// iteration close to the best one (see `search::variety`)
+    fn vary_best_move(&mut self, best_move: Move, best_score: i16) -> (Move, i16) {
+        let moves = &self.completed_root_moves;
+        let Some(best) = moves.iter().find(|rm| rm.mv == best_move) else {
+            return (best_move, best_score);
+        };
+        let (margin, seed) = (self.params.move_variety, self.params.variety_seed);
+        let choice = variety::pick(moves, best, margin, seed);
+        if choice.mv == best_move {
+            return (best_move, best_score);
+        }
+        let (mv, score) = (choice.mv, choice.score);
+        self.root_pv.clone_from(&choice.pv);
+        (mv, score)
+    }
+
     /// Iterative deepening search with time management
     pub fn search_timed(&mut self) -> (Move, i16) {
         let time_limit = self.time_mgmt.allocate_time();
-        let max_depth = self.params.max_depth;
+        let max_depth = self.params.max_depth.min(MAX_DEPTH);
 
         self.stats.reset();
         self.stats.start_timing();
-        self.tt.new_search();
+        if self.ages_tt {
+            self.tt.new_search();
+        }
+        self.eval_cache.prepare(
+            self.params.eval_cache_mb,
+            &self.params.eval_params,
+            &self.params.eval_weights,
+        );
+        self.root_list.clear();
+        self.completed_root_moves.clear();
+        self.contempt_side = self.params.engine_color.unwrap_or(self.board.side);
+        self.game_scores.retain_history(&self.board);
+        self.shuffle_baseline = self.game_scores.get(self.board.zobrist);
 
         // Reset time management state for new search
         self.time_expired = false;
@@ -319,8 +711,9 @@ impl Search {
         // (save original and restore later if needed)
         let orig_time_limit = self.params.time_limit_ms;
         self.params.time_limit_ms = time_limit;
+        self.time_state = TimeState::new(time_limit, time_limit);
 
-        let mut best_move = 0;
+        let mut best_move = Move::NONE;
         let mut best_score = -INFINITE;
 
         // Iterative deepening with time control
@@ -342,6 +735,7 @@ impl Search {
                 break;
             }
 
+            self.begin_root_iteration();
             let (mv, score) = self.iddfs(depth, best_move, -INFINITE, INFINITE);
 
             // If time expired during search, don't use partial results
@@ -353,6 +747,8 @@ impl Search {
             if score >= MATE {
                 self.params.time_limit_ms = orig_time_limit;
                 self.stats.update_timing();
+                self.record_game_score(mv, score);
+                self.notify_finished(mv, score);
                 return (mv, score);
             }
 
@@ -365,23 +761,157 @@ impl Search {
 
             // FIX Bug #3: Track last completed depth
             self.stats.completed_depth = depth;
+            self.notify_iteration(depth, best_score);
         }
 
         self.params.time_limit_ms = orig_time_limit;
         self.stats.update_timing();
+        self.record_game_score(best_move, best_score);
+        self.notify_finished(best_move, best_score);
         (best_move, best_score)
     }
 
+    /// Snapshot of the search at the end of a root search of `depth`
+    fn iteration_info(&self, depth: u8, score: i16) -> IterationInfo {
+        IterationInfo {
+            depth,
+            seldepth: self.stats.seldepth,
+            score,
+            nodes: self.stats.nodes,
+            elapsed: self
+                .stats
+                .start_time
+                .map(|start| start.elapsed())
+                .unwrap_or_default(),
+            hashfull: self.tt.hashfull(),
+            pv: self.get_pv(),
+            branching_factor: self.forecast.branching_factor(),
+            next_iteration: self.forecast.next_iteration_time(),
+        }
+    }
+
+    /// Report a finished iteration (and TT usage) to the observer, if any
+    fn notify_iteration(&self, depth: u8, score: i16) {
+        let Some(observer) = &self.observer else {
+            return;
+        };
+        let info = self.iteration_info(depth, score);
+        observer.on_iteration(&info);
+        observer.on_tt_stats(&TtStats {
+            hashfull: info.hashfull,
+            hits: self.stats.tt_hits,
+        });
+        observer.on_aspiration_stats(&AspirationStats {
+            fail_highs: self.stats.aspiration_fail_highs,
+            fail_lows: self.stats.aspiration_fail_lows,
+            research_nodes: self.stats.aspiration_research_nodes,
+            nodes: self.stats.nodes,
+        });
+    }
+
+    /// Report an aspiration window failure to the observer, if any
+    fn notify_aspiration_fail(&self, depth: u8, score: i16, bound: ScoreBound) {
+        if let Some(observer) = &self.observer {
+            observer.on_aspiration_fail(&self.iteration_info(depth, score), bound);
+        }
+    }
+
+    /// Report the final move and statistics to the observer, if any
+    fn notify_finished(&self, best_move: Move, score: i16) {
+        if let Some(observer) = &self.observer {
+            observer.on_bestmove(best_move, score);
+            observer.on_stop(&self.stats);
+        }
+    }
+
+    /// Search one iteration with an aspiration window around `prev_score`.
+    ///
+    /// The window starts `aspiration_window` wide on each side. When the
+    /// (fail-soft) score falls outside it, only the failing side moves: to the
+    /// returned score minus (fail low) or plus (fail high) the current
+    /// half-width, which doubles on every failure, until it exceeds
+    /// [`ASPIRATION_MAX_WINDOW`] and that side is opened completely. Each
+    /// failure is reported to the observer as a bound and counted in the
+    /// stats, together with the nodes of the re-searches it caused.
+    fn aspiration_search(
+        &mut self,
+        depth: u8,
+        mut best_move: Move,
+        prev_score: i16,
+    ) -> (Move, i16) {
+        let clamp = score::clamp;
+        let mut delta = (self.params.aspiration_window as i32).max(1);
+        let mut alpha = clamp(prev_score as i32 - delta);
+        let mut beta = clamp(prev_score as i32 + delta);
+        let mut researching = false;
+        loop {
+            let start_nodes = self.stats.nodes;
+            let (mv, score) = self.iddfs(depth, best_move, alpha, beta);
+            if researching {
+                let nodes = self.stats.nodes - start_nodes;
+                self.stats.add_aspiration_research_nodes(nodes);
+            }
+            if self.time_expired {
+                return (mv, score);
+            }
+            let bound = if score <= alpha && alpha > -INFINITE {
+                ScoreBound::Upper
+            } else if score >= beta && beta < INFINITE {
+                ScoreBound::Lower
+            } else {
+                return (mv, score);
+            };
+            match bound {
+                ScoreBound::Lower => self.stats.inc_aspiration_fail_high(),
+                ScoreBound::Upper => {
+                    self.stats.inc_aspiration_fail_low();
+                    self.root_failed_low = true;
+                }
+            }
+            self.notify_aspiration_fail(depth, score, bound);
+            researching = true;
+
+            delta = delta.saturating_mul(2);
+            let open = delta > ASPIRATION_MAX_WINDOW;
+            match bound {
+                ScoreBound::Upper => {
+                    alpha = if open {
+                        -INFINITE
+                    } else {
+                        clamp(score as i32 - delta)
+                    };
+                }
+                ScoreBound::Lower => {
+                    // The refutation-proof move is the one to search first
+                    best_move = mv;
+                    beta = if open {
+                        INFINITE
+                    } else {
+                        clamp(score as i32 + delta)
+                    };
+                }
+            }
+        }
+    }
+
     /// Iterative deepening framework (phase 1)
     fn iddfs(&mut self, depth: u8, best_move: Move, mut alpha: i16, beta: i16) -> (Move, i16) {
+        if let Some(trace) = self.trace.as_mut() {
+            trace.clear();
+        }
         // Root search with move ordering
         let mut best_root_move = best_move;
         let mut best_score = -INFINITE;
-        let root_moves = self.generate_root_moves();
+        let orig_alpha = alpha;
+        let order = self.generate_root_moves();
+        sync_root_moves(&mut self.root_list, &order);
+        for rm in &mut self.root_list {
+            rm.searched = false;
+        }
         self.reset_pv();
 
         // If no root moves (e.g., empty/invalid position), record a node and store a TT entry
-        if root_moves.is_empty() {
+        if order.is_empty() {
             let sc = if depth <= self.params.qsearch_depth {
                 self.qsearch(-INFINITE, INFINITE, self.params.qsearch_depth, 0)
             } else {
@@ -389,37 +919,23 @@ impl Search {
             };
             // record a node and TT entry so stats/tests consider this position handled
             self.stats.inc_node();
-            let key = self.board.recalc_zobrist();
-            self.tt.store(key, sc, depth, NodeType::Exact, 0);
+            let key = self.board.zobrist;
+            self.tt.store(key, sc, depth, NodeType::Exact, Move::NONE);
             self.stats.inc_tt_entry();
-            return (0, sc);
+            return (Move::NONE, sc);
         }
 
-        for (move_idx, mv) in root_moves.into_iter().enumerate() {
-            // Increment node count for root moves
-            self.stats.inc_node();
-            self.stats.inc_root_node();
-            self.stats.currmove = mv;
-            self.stats.currmovenumber = (move_idx + 1) as u32;
-
-            let undo = self.board.make_move(mv);
-            // Always do full negamax search from root
-            let score = -self.negamax_pv(depth - 1, -beta, -alpha, 0);
-            let _node_type = if score >= beta {
-                NodeType::LowerBound
-            } else if score <= alpha {
-                NodeType::UpperBound
-            } else {
-                NodeType::Exact
-            };
-            self.board.unmake_move(undo);
+        if let Some((split, worker_id)) = self.root_split.clone() {
+            return self.iddfs_split(depth, &split, worker_id, &order);
+        }
 
-            // FIX Bug #1: Check if time expired during search
-            // If so, discard this score (it's from incomplete search, likely 0 from timeout)
-            // and return best move found so far
-            if self.time_expired {
+        for (move_idx, mv) in order.into_iter().enumerate() {
+            let root_alpha = self.root_move_alpha(alpha);
+            let Some(score) = self.search_root_move(depth, move_idx, mv, root_alpha, beta) else {
+                // FIX Bug #1: time expired during the search of this move:
+                // its score is from an incomplete search, keep the best so far
                 break;
-            }
+            };
 
             // Update best
             if score > best_score {
@@ -428,7 +944,7 @@ impl Search {
                 // Update alpha for subsequent moves
                 if score > alpha {
                     alpha = score;
-                    self.update_pv(0, mv);
+                    self.root_pv.clone_from(&self.root_list[move_idx].pv);
                 }
             }
 
@@ -438,12 +954,37 @@ impl Search {
             }
         }
 
-        // Store in transposition table
-        let key = self.board.recalc_zobrist();
-        self.tt
-            .store(key, best_score, depth, NodeType::Exact, best_root_move);
-        self.stats.inc_tt_entry();
-        // Store recorded in stats above.
+        // Keep root move results only for iterations that were not cut short
+        if !self.time_expired {
+            self.completed_root_moves = self
+                .root_list
+                .iter()
+                .filter(|rm| rm.searched)
+                .cloned()
+                .collect();
+        }
+
+        // Store in transposition table. Interrupted iterations are not stored and
+        // aspiration failures keep their bound, so an Exact root entry can be
+        // trusted by the UCI layer for instant moves. A `searchmoves`-restricted
+        // root is not the real root value, and neither is a path draw or a
+        // shuffle penalty, so they are never stored.
+        if !self.time_expired
+            && self.params.search_moves.is_empty()
+            && !self.root_score_depends_on_history(best_root_move)
+        {
+            let node_type = if best_score >= beta {
+                NodeType::LowerBound
+            } else if best_score <= orig_alpha {
+                NodeType::UpperBound
+            } else {
+                NodeType::Exact
+            };
+            let key = self.board.zobrist;
+            self.tt
+                .store(key, best_score, depth, node_type, best_root_move);
+            self.stats.inc_tt_entry();
+        }
 
         // FIX Bug #1: If time expired before completing any move evaluation,
         // best_score will still be -INFINITE. Return 0 (draw) instead to avoid
@@ -455,11 +996,208 @@ impl Search {
         (best_root_move, best_score)
     }
 
-    /// Principal variation search (alpha-beta)
-    fn negamax_pv(&mut self, depth: u8, mut alpha: i16, beta: i16, ply: u8) -> i16 {
+    /// Lower bound of the window a root move is searched with: with move
+    /// variety the moves close to the best one need exact scores too
+    fn root_move_alpha(&self, alpha: i16) -> i16 {
+        alpha
+            .saturating_sub(self.params.move_variety)
+            .max(-INFINITE)
+    }
+
+    /// Search root move `mv` (entry `move_idx` of the root list) with the
+    /// window (`alpha`, `beta`) and record the result in the root list.
+    /// Returns None when the time ran out or a stop was requested: the score
+    /// of an interrupted search is meaningless.
+    fn search_root_move(
+        &mut self,
+        depth: u8,
+        move_idx: usize,
+        mv: Move,
+        alpha: i16,
+        beta: i16,
+    ) -> Option<i16> {
+        if self.node_limit_reached() {
+            return None;
+        }
+        // Increment node count for root moves
+        self.stats.inc_node();
+        self.stats.inc_root_node();
+        self.stats.currmove = mv;
+        self.stats.currmovenumber = (move_idx + 1) as u32;
+        if let Some(observer) = &self.observer {
+            let elapsed = self
+                .stats
+                .start_time
+                .map(|start| start.elapsed())
+                .unwrap_or_default();
+            observer.on_currmove(depth, mv, self.stats.currmovenumber, elapsed);
+        }
+        let nodes_before = self.stats.nodes + self.stats.qsearch_nodes;
+        let tt_hits_before = self.stats.tt_hits;
+        let game_draws_before = self.game_draws;
+        self.root_history_len = self.board.history_len();
+
+        self.board.push_move(mv);
+        self.tt.prefetch(self.board.zobrist);
+        // Shuffle loop: this move goes back to a position already reached in the game
+        let repeats_position = self.board.repetition_count() > 0;
+        self.set_stack_move(0, mv);
+        // Always do full negamax search from root
+        let mut score = score::negate(self.negamax_pv(depth - 1, -beta, -alpha, 0));
+        self.board.pop_move();
+
+        if self.time_expired {
+            return None;
+        }
+
+        let path_draw = self.game_draws != game_draws_before && score == self.draw_score();
+
+        // When winning but not mating, going round in circles without improving
+        // on the last visit of the root makes no progress and drifts towards a
+        // repetition draw: bias towards the alternatives. Bounds are left alone,
+        // they only order the move against the window.
+        let exact = score > alpha && score < beta;
+        let stagnant = self.shuffle_baseline.is_some_and(|prev| score <= prev);
+        let shuffle_penalized = repeats_position
+            && exact
+            && stagnant
+            && score >= self.params.shuffle_min_advantage
+            && score < MATE_THRESHOLD
+            && self.params.shuffle_penalty != 0;
+        if shuffle_penalized {
+            score -= self.params.shuffle_penalty;
+        }
+
+        let rm = &mut self.root_list[move_idx];
+        rm.score = score;
+        rm.nodes = self.stats.nodes + self.stats.qsearch_nodes - nodes_before;
+        rm.tt_hits = self.stats.tt_hits - tt_hits_before;
+        rm.lower_bound = score >= beta;
+        rm.upper_bound = score <= alpha;
+        rm.searched = true;
+        rm.path_draw = path_draw;
+        rm.shuffle_penalized = shuffle_penalized;
+        rm.pv.clear();
+        rm.pv.push(mv);
+        if score > alpha {
+            rm.pv.extend_from_slice(&self.pv[0][..self.pv_length[0]]);
+        }
+        Some(score)
+    }
+
+    /// Root search of `depth` shared with the other workers of a root split:
+    /// search the moves `split` hands out with its shared alpha, then wait for
+    /// the depth to be complete and take over the combined result
+    fn iddfs_split(
+        &mut self,
+        depth: u8,
+        split: &RootSplit,
+        worker_id: usize,
+        order: &[Move],
+    ) -> (Move, i16) {
+        split.begin(depth, order);
+        let (mut own_move, mut own_score) = (order[0], -INFINITE);
+        let results = loop {
+            if let Some(mv) = split.claim(depth, worker_id) {
+                let move_idx = order
+                    .iter()
+                    .position(|&m| m == mv)
+                    .expect("every worker has the same root moves");
+                let alpha = self.root_move_alpha(split.alpha(depth));
+                let Some(score) = self.search_root_move(depth, move_idx, mv, alpha, INFINITE)
+                else {
+                    break None;
+                };
+                if score > own_score {
+                    (own_move, own_score) = (mv, score);
+                }
+                split.complete(depth, self.root_list[move_idx].clone());
+            } else if let Some(results) = split.results(depth) {
+                break Some(results);
+            } else if self.stop_requested()
+                || self.check_time_expired()
+                || self.time_limit_reached()
+            {
+                self.time_expired = true;
+                break None;
+            } else {
+                split.wait(depth, ROOT_SPLIT_POLL);
+            }
+        };
+        let Some((moves, best)) = results else {
+            // Interrupted: only this worker's own moves are known
+            return (own_move, if own_score == -INFINITE { 0 } else { own_score });
+        };
+
+        for result in &moves {
+            if let Some(rm) = self.root_list.iter_mut().find(|rm| rm.mv == result.mv) {
+                let prev_score = rm.prev_score;
+                *rm = RootMove {
+                    prev_score,
+                    ..result.clone()
+                };
+            }
+        }
+        let (best_move, best_score) = (moves[best].mv, moves[best].score);
+        self.root_pv.clone_from(&moves[best].pv);
+        let depends_on_history = moves[best].depends_on_history();
+        self.completed_root_moves = moves;
+        if self.params.search_moves.is_empty() && !depends_on_history {
+            let key = self.board.zobrist;
+            self.tt
+                .store(key, best_score, depth, NodeType::Exact, best_move);
+            self.stats.inc_tt_entry();
+        }
+        (best_move, best_score)
+    }
+
+    /// Principal variation search (alpha-beta), recorded in the trace when
+    /// tracing is enabled
+    #[inline]
+    fn negamax_pv(&mut self, depth: u8, alpha: i16, beta: i16, ply: u8) -> i16 {
+        if self.trace.is_none() {
+            return self.negamax_node(depth, alpha, beta, ply);
+        }
+        let (key, mv) = (self.board.zobrist, self.stack_move(ply, 0));
+        if let Some(trace) = self.trace.as_mut() {
+            trace.enter(key, ply, depth, alpha, beta, mv);
+        }
+        let score = self.negamax_node(depth, alpha, beta, ply);
+        let aborted = self.time_expired;
+        if let Some(trace) = self.trace.as_mut() {
+            trace.exit(score, aborted);
+        }
+        score
+    }
+
+    /// Record why the current node returns (no-op unless tracing)
+    #[inline]
+    fn trace_decide(&mut self, decision: TraceDecision) {
+        if let Some(trace) = self.trace.as_mut() {
+            trace.decide(decision);
+        }
+    }
+
+    /// Body of [`Self::negamax_pv`]
+    fn negamax_node(&mut self, mut depth: u8, mut alpha: i16, beta: i16, ply: u8) -> i16 {
+        if ply as usize >= MAX_PLY - 1 {
+            return self.static_eval();
+        }
+        // No PV below this node until a move raises alpha
+        self.pv_length[ply as usize] = 0;
+        self.enter_node(ply);
+        // Move skipped by a singular extension verification of this node
+        let excluded_move = self.ss(ply).excluded_move;
+
+        if self.node_limit_reached() {
+            return 0;
+        }
         // Increment node counter
         self.stats.inc_node();
         self.stats.update_seldepth(ply);
+        if self.params.nps_limit > 0 {
+            self.throttle_nps();
+        }
 
         // Check time periodically (every 1024 nodes) to allow early exit
         // This prevents massive time overshoots during deep searches
@@ -471,60 +1209,83 @@ impl Search {
             return 0;
         }
 
-        // Clear SEE cache for this node position
-        self.clear_see_cache();
-
-        // Check transposition table
-        let key = self.board.recalc_zobrist();
+        // Check transposition table (incremental key, verified in debug builds)
+        debug_assert!(
+            self.board.verify_zobrist(),
+            "Incremental zobrist hash diverged from full recompute"
+        );
+        let key = self.board.zobrist;
         // FIX: Use i32 to avoid overflow when computing window size
         // (beta - alpha can overflow i16 when beta=30000, alpha=-30000)
         let is_pv_node = (beta as i32) - (alpha as i32) > 1; // PV node has open window
-                                                             // Probe TT
+
+        // Draw detection - before the TT probe: a repetition or the 50-move
+        // rule depends on the path to this node, which the key does not
+        // cover, so a TT entry must not overrule it
+        // Note: We check for checkmate/stalemate after move generation
+        //
+        // OPTIMIZATION (v0.5.3): Skip threefold check in non-PV nodes - rare and expensive
+        if self.board.is_insufficient_material() {
+            self.trace_decide(TraceDecision::Draw);
+            return self.draw_score(); // Draw
+        }
+        if self.board.is_50_move_draw() || (is_pv_node && self.board.is_threefold_repetition()) {
+            self.record_path_draw();
+            self.trace_decide(TraceDecision::Draw);
+            return self.draw_score(); // Draw
+        }
+        let path_draws_before = self.path_draws;
+
+        // Probe TT
+        let mut has_tt_move = false;
         if let Some(entry) = self.tt.probe(key) {
             self.stats.inc_tt_hit();
+            has_tt_move = !entry.best_move.is_none();
             // In PV nodes, only use TT for move ordering, not for cutoffs
             // This prevents score instability from aspiration window re-searches
-            if !is_pv_node && entry.depth >= depth {
-                let (entry_alpha, entry_beta) = entry.bound();
-                if entry_beta <= alpha {
-                    return entry_alpha; // Upper bound cutoff
-                }
-                if entry_alpha >= beta {
-                    return entry_beta; // Lower bound cutoff
+            if !is_pv_node && entry.depth >= depth && excluded_move.is_none() {
+                let tt_score = score::from_tt(entry.score, ply);
+                let (entry_alpha, entry_beta) = match entry.node_type {
+                    NodeType::Exact => (tt_score, tt_score),
+                    NodeType::LowerBound => (tt_score, INFINITE),
+                    NodeType::UpperBound => (-INFINITE, tt_score),
+                };
+                if entry_beta <= alpha || entry_alpha >= beta {
+                    self.trace_decide(TraceDecision::TtCutoff);
+                    return tt_score; // Upper / lower bound cutoff
                 }
             }
         }
 
+        // Internal iterative reduction: without a TT move the ordering of a PV
+        // node is poor, so search it one ply shallower
+        if self.params.enable_iir
+            && is_pv_node
+            && !has_tt_move
+            && depth >= self.params.iir_min_depth
+        {
+            depth -= 1;
+            self.stats.inc_iir_reduction();
+        }
+
         // Terminal check - use depth-based quiescence switching
         if depth == 0 {
             // When at leaf, always use quiescence search
+            self.trace_decide(TraceDecision::Quiescence);
             return self.qsearch(alpha, beta, self.params.qsearch_depth, ply);
         }
 
-        // Draw detection - only for terminal positions
-        // Note: We check for checkmate/stalemate after move generation
-        // 50-move rule and threefold repetition should be handled by game controller when possible,
-        // but we can still exit early here for draw states
-        //
-        // OPTIMIZATION (v0.5.3): Skip threefold check in non-PV nodes - rare and expensive
-        let draw_check = if is_pv_node {
-            // In PV nodes, check all draw conditions thoroughly
-            self.board.is_insufficient_material()
-                || self.board.is_50_move_draw()
-                || self.board.is_threefold_repetition()
-        } else {
-            // In non-PV nodes, only check cheap conditions
-            // Threefold repetition is rare and expensive to check
-            self.board.is_insufficient_material() || self.board.is_50_move_draw()
-        };
-
-        if draw_check {
-            return 0; // Draw
-        }
-
         // OPTIMIZATION: Cache is_in_check() result to avoid duplicate expensive calls
         let parent_in_check = self.is_in_check();
 
+        // Improving: the static eval is better than the last time this side
+        // was to move. Nodes that are not get pruned and reduced harder.
+        let improving = self.params.enable_improving && !parent_in_check && {
+            self.node_static_eval(ply);
+            self.improving(ply)
+        };
+        let futility_margin = self.futility_margin(improving);
+
         // Razoring (v0.5.3): ultra-conservative pruning at depth 1
         // Only prune if position is clearly hopeless (static_eval << alpha)
         if self.params.enable_razoring
@@ -535,11 +1296,12 @@ impl Search {
             && alpha > -MATE_THRESHOLD
         // Not in mate search
         {
-            let static_eval = self.static_eval();
+            let static_eval = self.node_static_eval(ply);
             // Position is hopeless if eval + margin < alpha
             let threshold = alpha - self.params.razoring_margin;
             if static_eval < threshold {
                 self.stats.inc_razoring_pruned();
+                self.trace_decide(TraceDecision::Razored);
                 return static_eval; // Fail low
             }
         }
@@ -552,9 +1314,10 @@ impl Search {
             && alpha < beta - 1
         // Not in PV node
         {
-            let static_eval = self.static_eval();
-            if static_eval + self.params.futility_margin < beta {
+            let static_eval = self.node_static_eval(ply);
+            if static_eval + futility_margin < beta {
                 self.stats.inc_futility_pruned();
+                self.trace_decide(TraceDecision::Futility);
                 return static_eval; // Return eval since it can't beat beta
             }
         }
@@ -566,6 +1329,7 @@ impl Search {
             && depth >= self.params.null_move_min_depth
             && ply > 0  // Not at root
             && !parent_in_check
+            && excluded_move.is_none()
         // Reuse cached check state
         {
             // Null-move reduction: typically R = 2 or 3, we'll use R = 2
@@ -579,20 +1343,18 @@ impl Search {
 
             // Make null move (skip turn)
             let undo = self.board.make_null_move();
+            self.tt.prefetch(self.board.zobrist);
+            self.set_stack_move(ply + 1, Move::NONE);
 
             // Perform reduced-depth search with a null window
             // After null move, the side to move has changed, so we search from opponent's perspective
             // Null window is [-beta, -beta+1] to verify fail-high
-            let null_alpha = if beta > i16::MIN { -beta } else { i16::MAX };
-            let null_beta = if beta < i16::MAX { -beta + 1 } else { i16::MIN };
-            let null_search_score = self.negamax_pv(null_depth, null_alpha, null_beta, ply + 1);
-
-            // Handle overflow when negating
-            let null_score = if null_search_score == i16::MIN {
-                i16::MAX
-            } else {
-                -null_search_score
-            };
+            let null_score = score::negate(self.negamax_pv(
+                null_depth,
+                score::negate(beta),
+                score::negate(beta) + 1,
+                ply + 1,
+            ));
 
             // Unmake null move
             self.board.unmake_null_move(undo);
@@ -646,19 +1408,33 @@ impl Search {
                 if total_pieces > 6 {
                     // Only with enough pieces on board
                     self.stats.inc_null_move_cutoff();
-                    return beta;
+                    self.trace_decide(TraceDecision::NullMove);
+                    // Fail soft, but never claim an unproven mate from a null move
+                    return if null_score >= MATE_THRESHOLD {
+                        beta
+                    } else {
+                        null_score
+                    };
                 }
             }
         }
 
-        // Generate and order moves
-        let mut moves = self.board.generate_moves();
+        // Generate and order moves (only the replies to a check when in check)
+        let moves = if parent_in_check {
+            self.board.generate_evasions()
+        } else {
+            self.board.generate_moves()
+        };
         if moves.is_empty() {
             // In checkmate or stalemate - reuse parent_in_check
             if parent_in_check {
+                self.trace_decide(TraceDecision::Checkmate);
                 return -(MATE - ply as i16); // Checkmate, add distance-to-mate
+            } else if !self.board.has_king(self.board.side) {
+                return self.static_eval(); // Study mode: no king, material decides
             } else {
-                return 0; // Stalemate
+                self.trace_decide(TraceDecision::Stalemate);
+                return self.draw_score(); // Stalemate
             }
         }
 
@@ -667,20 +1443,32 @@ impl Search {
         // Use incremental zobrist hash instead of recalculating
         // After null-move + unmake, zobrist should be identical to original
         // Validate in debug mode that incremental hashing is correct
-        debug_assert_eq!(
-            self.board.zobrist,
-            self.board.recalc_zobrist(),
+        debug_assert!(
+            self.board.verify_zobrist(),
             "Incremental zobrist hash diverged from recalculated hash"
         );
         let key = self.board.zobrist;
         // Probe TT for cached result
         if let Some(entry) = self.tt.probe(key) {
-            if entry.best_move != 0 {
+            if !entry.best_move.is_none() {
                 tt_move = Some(entry.best_move);
             }
         }
 
-        moves.sort_by(|&a, &b| {
+        // SEE computed once per capture and used as its ordering key;
+        // quiet moves get history + counter-move history + follow-up history
+        let countermove = self.countermove(ply);
+        let mut scored: Vec<(Move, i16, i32)> = moves
+            .iter()
+            .map(|&mv| {
+                if mv.captured().is_some() {
+                    (mv, self.capture_see(mv), 0)
+                } else {
+                    (mv, 0, self.quiet_move_score(mv, ply))
+                }
+            })
+            .collect();
+        scored.sort_by(|&(a, a_see, a_score), &(b, b_see, b_score)| {
             // TT move first
             if let Some(tt_mv) = tt_move {
                 if a == tt_mv && b != tt_mv {
@@ -691,66 +1479,62 @@ impl Search {
                 }
             }
 
-            let a_capture = move_flag(a, 0x40);
-            let b_capture = move_flag(b, 0x40);
+            let a_capture = a.captured().is_some();
+            let b_capture = b.captured().is_some();
 
             match (a_capture, b_capture) {
                 (true, false) => std::cmp::Ordering::Less,
                 (false, true) => std::cmp::Ordering::Greater,
                 (true, true) => {
-                    // MVV-LVA + SEE for captures (original)
-                    let a_to = move_to_sq(a);
-                    let b_to = move_to_sq(b);
-
-                    let a_victim_value = if let Some((kind, _)) = self.board.piece_on(a_to) {
-                        self.piece_value(&kind)
-                    } else {
-                        0
-                    };
-
-                    let b_victim_value = if let Some((kind, _)) = self.board.piece_on(b_to) {
-                        self.piece_value(&kind)
-                    } else {
-                        0
-                    };
-
-                    let mvv_lva_cmp = b_victim_value.cmp(&a_victim_value);
-
-                    // If MVV-LVA is equal, use SEE as tiebreaker
-                    if mvv_lva_cmp == std::cmp::Ordering::Equal {
-                        let a_see = self.see(a_to, self.board.side);
-                        let b_see = self.see(b_to, self.board.side);
-                        b_see.cmp(&a_see) // Higher SEE first
-                    } else {
-                        mvv_lva_cmp
-                    }
+                    compare_captures(a, self.capture_key(a, a_see), b, self.capture_key(b, b_see))
                 }
                 (false, false) => {
                     // Quiet moves - killer moves first
-                    let a_is_killer = self.is_killer_move(ply as usize, a);
-                    let b_is_killer = self.is_killer_move(ply as usize, b);
+                    let a_is_killer = self.is_killer_move(ply, a);
+                    let b_is_killer = self.is_killer_move(ply, b);
 
                     match (a_is_killer, b_is_killer) {
                         (true, false) => std::cmp::Ordering::Less,
                         (false, true) => std::cmp::Ordering::Greater,
-                        (true, true) | (false, false) => {
-                            // History heuristic
-                            let a_history = self.get_history_score(a);
-                            let b_history = self.get_history_score(b);
-                            b_history.cmp(&a_history)
+                        (true, true) => b_score.cmp(&a_score),
+                        (false, false) => {
+                            // Then the refutation of the previous move
+                            if a == countermove && b != countermove {
+                                return std::cmp::Ordering::Less;
+                            }
+                            if b == countermove && a != countermove {
+                                return std::cmp::Ordering::Greater;
+                            }
+                            b_score.cmp(&a_score)
                         }
                     }
                 }
             }
         });
+        let moves: Vec<Move> = scored.into_iter().map(|(mv, _, _)| mv).collect();
 
         let mut best = -INFINITE;
-        let mut best_move = 0;
+        let mut best_move = Move::NONE;
+        let lmp_threshold = self.lmp_threshold(depth, is_pv_node, parent_in_check, improving);
 
         for (move_idx, mv) in moves.into_iter().enumerate() {
+            if mv == excluded_move {
+                continue;
+            }
             // Determine move characteristics for LMR
-            let is_quiet = move_captured(mv).is_none() && !move_flag(mv, FLAG_PROMOTION);
+            let is_quiet = mv.captured().is_none() && !mv.is_promotion();
             let move_count = (move_idx + 1) as u32;
+            self.ss_mut(ply).move_count = move_count;
+
+            // Late move pruning: enough moves tried, the quiet ones left are
+            // not worth a search (once a move has shown we are not mated)
+            if is_quiet
+                && lmp_threshold.is_some_and(|threshold| move_count > threshold)
+                && best > -MATE_THRESHOLD
+            {
+                self.stats.inc_lmp_pruned();
+                continue;
+            }
 
             // Check if move gives check (only for quiet moves that might be reduced)
             let gives_check = if is_quiet
@@ -763,7 +1547,15 @@ impl Search {
                 false
             };
 
-            let undo = self.board.make_move(mv);
+            let source = self
+                .stats
+                .ordering
+                .is_some()
+                .then(|| self.move_source(mv, tt_move, countermove, ply));
+
+            self.board.push_move(mv);
+            self.tt.prefetch(self.board.zobrist);
+            self.set_stack_move(ply + 1, mv);
 
             // Check extension: extend search by 1 ply if move gives check
             // MIGLIORATO (Fix GrandMaster #3): Limite aumentato da ply<10 a ply<16
@@ -776,26 +1568,30 @@ impl Search {
             };
 
             // Futility pruning for individual nodes (only for quiet moves)
-            let is_quiet_move = move_captured(mv).is_none() && !move_flag(mv, FLAG_PROMOTION);
+            let is_quiet_move = mv.captured().is_none() && !mv.is_promotion();
             let should_futility_prune = is_quiet_move
                 && self.params.enable_futility_pruning
                 && depth <= self.params.futility_min_depth
                 && !in_check  // Don't prune if in check
                 && !self.is_endgame()
-                && alpha > -INFINITE + self.params.futility_margin;
+                && alpha > -INFINITE + futility_margin;
 
             if should_futility_prune {
                 let static_eval = self.static_eval();
-                if static_eval + self.params.futility_margin <= alpha {
+                if static_eval + futility_margin <= alpha {
                     self.stats.inc_futility_pruned();
-                    self.board.unmake_move(undo);
+                    self.board.pop_move();
                     continue; // Skip this move
                 }
             }
 
+            if let (Some(ordering), Some(source)) = (self.stats.ordering.as_mut(), source) {
+                ordering.record_searched(source);
+            }
+
             // Late Move Reductions logic
             let lmr_reduction = if is_quiet && move_count > 3 {
-                self.get_lmr_reduction(depth, move_count, is_quiet, gives_check)
+                self.get_lmr_reduction(depth, move_count, is_quiet, gives_check, improving)
             } else {
                 0
             };
@@ -808,51 +1604,50 @@ impl Search {
 
             // First try reduced depth if LMR applies
             let score = if lmr_reduction > 0 {
-                let reduced_score = self.negamax_pv(search_depth, -alpha - 1, -alpha, ply + 1);
+                let reduced_score =
+                    score::negate(self.negamax_pv(search_depth, -alpha - 1, -alpha, ply + 1));
 
                 // Research at full depth if reduced search fails high
                 if reduced_score > alpha {
                     self.stats.inc_lmr_reduction();
-                    let full_score = self.negamax_pv(depth - 1 + extension, -beta, -alpha, ply + 1);
-                    if full_score == i16::MIN {
-                        i16::MAX
-                    } else {
-                        -full_score
-                    }
-                } else if reduced_score == i16::MIN {
-                    i16::MAX
+                    score::negate(self.negamax_pv(depth - 1 + extension, -beta, -alpha, ply + 1))
                 } else {
-                    -reduced_score
+                    reduced_score
                 }
             } else {
                 // Normal search without reduction
-                let child_score = self.negamax_pv(search_depth, -beta, -alpha, ply + 1);
-                if child_score == i16::MIN {
-                    i16::MAX
-                } else {
-                    -child_score
-                }
+                score::negate(self.negamax_pv(search_depth, -beta, -alpha, ply + 1))
             };
 
-            self.board.unmake_move(undo);
+            self.board.pop_move();
 
             if score > best {
                 best = score;
                 best_move = mv;
                 if best > alpha {
                     alpha = best;
-                    self.update_pv(ply as usize, mv);
+                    self.update_pv(ply, mv);
                     // Update history for quiet moves that improve alpha
-                    if move_captured(mv).is_none() && !move_flag(mv, FLAG_PROMOTION) {
+                    if mv.captured().is_none() && !mv.is_promotion() {
                         self.update_history(mv, depth);
+                        self.update_continuation_history(mv, depth, ply);
                     }
                     if alpha >= beta {
                         // Beta cutoff - store killer move if it's a non-capture and not TT move
-                        if move_captured(mv).is_none() {
+                        if mv.captured().is_none() {
                             // Check if this move is not already stored as killer
-                            self.store_killer_move(ply as usize, mv);
+                            self.store_killer_move(ply, mv);
+                            self.store_countermove(ply, mv);
+                        } else {
+                            self.update_capture_history(mv, depth);
                         }
                         self.stats.inc_cutoff();
+                        if let (Some(ordering), Some(source)) =
+                            (self.stats.ordering.as_mut(), source)
+                        {
+                            ordering.record_cutoff(source, move_count);
+                        }
+                        self.trace_decide(TraceDecision::BetaCutoff);
                         break; // Beta cutoff
                     }
                 }
@@ -868,20 +1663,81 @@ impl Search {
             NodeType::Exact
         };
 
-        self.tt.store(key, best, depth, node_type, best_move);
+        // A search cut short by the clock or a stop returns meaningless
+        // scores, and a draw score that may come from a repetition below this
+        // node only holds for the current path: neither is stored
+        // A search without the excluded move is not a search of this
+        // position: its result must not replace the real one
+        let path_draw = self.path_draws != path_draws_before && best == self.draw_score();
+        if !self.time_expired && !path_draw && excluded_move.is_none() {
+            self.tt
+                .store(key, score::to_tt(best, ply), depth, node_type, best_move);
+        }
 
         best
     }
 
     /// Static evaluation with PSQT (piece-square tables)
-    fn static_eval(&self) -> i16 {
-        // Use full evaluation with material + PSQT + king safety + development + center
-        crate::eval::evaluate(&self.board)
+    fn static_eval(&mut self) -> i16 {
+        // Use full evaluation with material + PSQT + king safety + development + center,
+        // weighted by the playing style
+        let (params, weights) = (&self.params.eval_params, &self.params.eval_weights);
+        self.eval_cache.get_or_insert(&self.board, |board| {
+            score::clamp_eval(crate::eval::evaluate_with(board, params, weights))
+        })
+    }
+
+    /// Count a repetition or 50-move draw found at the current node (see
+    /// `path_draws` and `game_draws`)
+    fn record_path_draw(&mut self) {
+        self.path_draws += 1;
+        if self.board.is_50_move_draw() || self.board.occurred_before(self.root_history_len) {
+            self.game_draws += 1;
+        }
+    }
+
+    /// The score of root move `mv` is not the value of the root position
+    /// alone (see [`RootMove::depends_on_history`])
+    fn root_score_depends_on_history(&self, mv: Move) -> bool {
+        self.root_list
+            .iter()
+            .any(|rm| rm.mv == mv && rm.depends_on_history())
+    }
+
+    /// Score of a drawn position for the side to move: with a positive
+    /// contempt the engine avoids draws, its opponent is assumed to seek them
+    fn draw_score(&self) -> i16 {
+        let contempt = self.params.contempt + self.params.eval_weights.contempt;
+        if self.board.side == self.contempt_side {
+            -contempt
+        } else {
+            contempt
+        }
+    }
+
+    /// Stalemate of a side to move that has only its king and pawns. Only
+    /// such a side is checked: with a piece left, a stalemate is rare
+    /// enough not to pay for move generation at every quiescence node. A
+    /// king with a free square settles most positions before it as well.
+    fn is_stalemate_without_pieces(&mut self) -> bool {
+        let side = self.board.side;
+        self.board.has_king(side)
+            && !self.board.has_non_pawn_material(side)
+            && !self.is_in_check()
+            && !self.board.king_has_safe_step(side)
+            && self.board.generate_moves().is_empty()
     }
 
     /// Fast static evaluation (material + PSQT only) for quiescence search
-    fn static_eval_fast(&self) -> i16 {
-        crate::eval::evaluate_fast(&self.board)
+    fn static_eval_fast(&mut self) -> i16 {
+        let key = self.board.zobrist;
+        if let Some(score) = self.eval_cache.probe_fast(key) {
+            return score;
+        }
+        let weights = &self.params.eval_weights;
+        let score = score::clamp_eval(crate::eval::evaluate_fast_with(&self.board, weights));
+        self.eval_cache.store_fast(key, score);
+        score
     }
 
     /// Quiescence search - searches only noisy moves (captures, promotions, checks)
@@ -898,6 +1754,9 @@ impl Search {
     /// # Returns
     /// Score for the position after quiescence search
     fn qsearch(&mut self, mut alpha: i16, beta: i16, depth: u8, ply: u8) -> i16 {
+        if ply as usize >= MAX_PLY - 1 {
+            return self.static_eval_fast();
+        }
         // Increment quiescence node counter
         self.stats.inc_qsearch_node();
         self.stats.update_seldepth(ply);
@@ -908,15 +1767,20 @@ impl Search {
             return self.static_eval_fast();
         }
 
-        // Clear SEE cache for this node position
-        self.clear_see_cache();
-
         // Draw detection - can cover insufficient material, 50-move rule, and threefold repetition
-        if self.board.is_insufficient_material()
-            || self.board.is_50_move_draw()
-            || self.board.is_threefold_repetition()
-        {
-            return 0; // Draw by insufficient material, 50-move, or threefold
+        if self.board.is_insufficient_material() {
+            return self.draw_score();
+        }
+        if self.board.is_50_move_draw() || self.board.is_threefold_repetition() {
+            // Path-dependent: the parent must not store the resulting score
+            self.record_path_draw();
+            return self.draw_score();
+        }
+
+        // A side left with king and pawns may have no move at all: stand pat
+        // would score that stalemate as the lost ending the evaluation sees
+        if self.is_stalemate_without_pieces() {
+            return self.draw_score();
         }
 
         // Stand pat: use fast eval (material + PSQT only) for speed
@@ -942,24 +1806,21 @@ impl Search {
         let in_check = self.is_in_check();
 
         // Generate moves based on check status:
-        // - If in check: generate ALL moves (evasions might be quiet)
+        // - If in check: generate all evasions (they might be quiet)
         // - Otherwise: only generate captures and promotions if optimizations enabled
         let moves_to_search = if in_check {
             // In check: must search all evasions
-            let all_moves = self.board.generate_moves();
+            let evasions = self.board.generate_evasions();
 
-            // Check for mate or stalemate (no legal moves)
-            if all_moves.is_empty() {
-                if self.is_in_check() {
-                    return -(MATE - ply as i16); // Checkmate
-                } else {
-                    return 0; // Stalemate
-                }
+            // No legal reply to the check: mate
+            if evasions.is_empty() {
+                return -(MATE - ply as i16);
             }
-            all_moves
+            evasions
         } else if self.params.enable_qsearch_optimizations {
             // Optimized path: generate only captures/promotions
-            let captures = self.board.generate_captures();
+            let mut captures = Vec::with_capacity(32);
+            self.board.generate_captures(&mut captures);
             if captures.is_empty() {
                 return stand_pat;
             }
@@ -969,10 +1830,10 @@ impl Search {
             let all_moves = self.board.generate_moves();
             let mut noisy_moves = Vec::new();
             for &mv in &all_moves {
-                let is_noisy = move_captured(mv).is_some()            // captures
-                    || move_flag(mv, FLAG_PROMOTION)                // promotions
-                    || move_flag(mv, FLAG_CASTLE_KING)               // castling
-                    || move_flag(mv, FLAG_CASTLE_QUEEN)              // castling
+                let is_noisy = mv.captured().is_some()            // captures
+                    || mv.is_promotion()                // promotions
+                    || mv.has_flag(FLAG_CASTLE_KING)               // castling
+                    || mv.has_flag(FLAG_CASTLE_QUEEN)              // castling
                     || self.move_gives_check(mv); // gives check
 
                 if is_noisy {
@@ -985,43 +1846,21 @@ impl Search {
             noisy_moves
         };
 
-        // Order moves: use MVV-LVA for better pruning (captures first)
-        let mut moves_to_search = moves_to_search; // Make mutable
-        moves_to_search.sort_by(|&a, &b| {
-            let a_capture = move_captured(a).is_some();
-            let b_capture = move_captured(b).is_some();
+        // Order moves: captures first, by SEE (MVV as tie-breaker)
+        let mut moves_to_search: Vec<(Move, i16)> = moves_to_search
+            .into_iter()
+            .map(|mv| (mv, self.capture_see(mv)))
+            .collect();
+        moves_to_search.sort_by(|&(a, a_see), &(b, b_see)| {
+            let a_capture = a.captured().is_some();
+            let b_capture = b.captured().is_some();
 
             // Captures first
             match (a_capture, b_capture) {
                 (true, false) => std::cmp::Ordering::Less,
                 (false, true) => std::cmp::Ordering::Greater,
                 (true, true) => {
-                    // Both captures - MVV-LVA + SEE ordering
-                    let a_to = move_to_sq(a);
-                    let b_to = move_to_sq(b);
-
-                    let a_victim_value = if let Some((kind, _)) = self.board.piece_on(a_to) {
-                        self.piece_value(&kind)
-                    } else {
-                        0
-                    };
-
-                    let b_victim_value = if let Some((kind, _)) = self.board.piece_on(b_to) {
-                        self.piece_value(&kind)
-                    } else {
-                        0
-                    };
-
-                    let mvv_lva_cmp = b_victim_value.cmp(&a_victim_value);
-
-                    // If MVV-LVA is equal, use SEE as tiebreaker
-                    if mvv_lva_cmp == std::cmp::Ordering::Equal {
-                        let a_see = self.see(a_to, self.board.side);
-                        let b_see = self.see(b_to, self.board.side);
-                        b_see.cmp(&a_see) // Higher SEE first
-                    } else {
-                        mvv_lva_cmp
-                    }
+                    compare_captures(a, self.capture_key(a, a_see), b, self.capture_key(b, b_see))
                 }
                 (false, false) => std::cmp::Ordering::Equal, // Both non-captures, keep original order
             }
@@ -1029,14 +1868,20 @@ impl Search {
 
         // Search moves (captures or all evasions if in check)
         let mut best_score = stand_pat;
-        for &mv in &moves_to_search {
+        for &(mv, see) in &moves_to_search {
+            // SEE pruning: a capture that loses material cannot raise the stand-pat
+            // score, so it is skipped (promotions and check evasions are kept)
+            if !in_check && see < 0 && mv.captured().is_some() && !mv.is_promotion() {
+                continue;
+            }
+
             // Delta pruning: skip captures that can't improve alpha even in best case
             // Only apply when optimizations are enabled and not in check
             if self.params.enable_qsearch_optimizations && !in_check {
                 // Get the value of the captured piece (if any)
-                let victim_value = if let Some(captured) = move_captured(mv) {
+                let victim_value = if let Some(captured) = mv.captured() {
                     self.piece_value(&captured)
-                } else if move_flag(mv, FLAG_PROMOTION) {
+                } else if mv.is_promotion() {
                     // Promotion to queen adds ~800 cp
                     800
                 } else {
@@ -1053,12 +1898,13 @@ impl Search {
                 }
             }
 
-            let undo = self.board.make_move(mv);
+            self.board.push_move(mv);
+            self.tt.prefetch(self.board.zobrist);
 
             // Recursive quiescence search with negated bounds
-            let score = -self.qsearch(-beta, -alpha, depth - 1, ply + 1);
+            let score = score::negate(self.qsearch(-beta, -alpha, depth - 1, ply + 1));
 
-            self.board.unmake_move(undo);
+            self.board.pop_move();
 
             // Beta cutoff
             if score >= beta {
@@ -1125,13 +1971,16 @@ impl Search {
     /// Generate root moves with enhanced ordering including killer moves and history
     fn generate_root_moves(&mut self) -> Vec<Move> {
         let mut moves = self.board.generate_moves();
+        if !self.params.search_moves.is_empty() {
+            moves.retain(|mv| self.params.search_moves.contains(mv));
+        }
 
         // Try TT move first if available
-        let key = self.board.recalc_zobrist();
+        let key = self.board.zobrist;
         let mut tt_move = None;
         // Probe TT
         if let Some(entry) = self.tt.probe(key) {
-            if entry.best_move != 0 {
+            if !entry.best_move.is_none() {
                 tt_move = Some(entry.best_move);
                 self.stats.inc_tt_hit();
                 // Move TT-best move to front
@@ -1140,10 +1989,16 @@ impl Search {
                 }
             }
         }
+        // Without a TT move, start from what earlier games taught
+        if tt_move.is_none() {
+            tt_move = self.params.experience_move.filter(|mv| moves.contains(mv));
+        }
 
         // Enhanced move ordering
-        let root_ply = 0; // Root moves are at ply 0
-        moves.sort_by(|&a, &b| {
+        let root_ply = 0u8; // Root moves are at ply 0
+        let mut scored: Vec<(Move, i16)> =
+            moves.iter().map(|&mv| (mv, self.capture_see(mv))).collect();
+        scored.sort_by(|&(a, a_see), &(b, b_see)| {
             // Check for TT move first (highest priority)
             if let Some(tt_mv) = tt_move {
                 if a == tt_mv && b != tt_mv {
@@ -1155,39 +2010,14 @@ impl Search {
             }
 
             // Check for capture moves
-            let a_capture = move_flag(a, 0x40); // Capture flag
-            let b_capture = move_flag(b, 0x40);
+            let a_capture = a.captured().is_some();
+            let b_capture = b.captured().is_some();
 
             match (a_capture, b_capture) {
                 (true, false) => std::cmp::Ordering::Less,
                 (false, true) => std::cmp::Ordering::Greater,
                 (true, true) => {
-                    // Both captures - order by victim value (MVV-LVA + SEE)
-                    let a_to = move_to_sq(a);
-                    let b_to = move_to_sq(b);
-
-                    let a_victim_value = if let Some((kind, _)) = self.board.piece_on(a_to) {
-                        self.piece_value(&kind)
-                    } else {
-                        0
-                    };
-
-                    let b_victim_value = if let Some((kind, _)) = self.board.piece_on(b_to) {
-                        self.piece_value(&kind)
-                    } else {
-                        0
-                    };
-
-                    let mvv_lva_cmp = b_victim_value.cmp(&a_victim_value);
-
-                    // If MVV-LVA is equal, use SEE as tiebreaker
-                    if mvv_lva_cmp == std::cmp::Ordering::Equal {
-                        let a_see = self.see(a_to, self.board.side);
-                        let b_see = self.see(b_to, self.board.side);
-                        b_see.cmp(&a_see) // Higher SEE first
-                    } else {
-                        mvv_lva_cmp
-                    }
+                    compare_captures(a, self.capture_key(a, a_see), b, self.capture_key(b, b_see))
                 }
                 (false, false) => {
                     // Both quiet moves - check for killer moves
@@ -1207,6 +2037,7 @@ impl Search {
                 }
             }
         });
+        let moves: Vec<Move> = scored.into_iter().map(|(mv, _)| mv).collect();
 
         moves
     }
@@ -1224,35 +2055,42 @@ impl Search {
     }
 
     /// Store a killer move at the given ply
-    fn store_killer_move(&mut self, ply: usize, mv: Move) {
-        if ply < self.killer_moves.len() {
-            let killers = &mut self.killer_moves[ply];
-
-            // If move is already stored, don't store again
-            if killers.contains(&mv) {
-                return;
-            }
-
-            // Shift existing killers and insert new one at front
-            killers.pop(); // Remove oldest if slots are full
-            killers.insert(0, mv);
-        }
+    fn store_killer_move(&mut self, ply: u8, mv: Move) {
+        let slots = self.params.killer_moves_count;
+        self.ss_mut(ply).store_killer(mv, slots);
     }
 
     /// Check if a move is a killer move at the current ply
-    fn is_killer_move(&self, ply: usize, mv: Move) -> bool {
-        if ply < self.killer_moves.len() {
-            self.killer_moves[ply].contains(&mv)
+    fn is_killer_move(&self, ply: u8, mv: Move) -> bool {
+        self.ss(ply).is_killer(mv, self.params.killer_moves_count)
+    }
+
+    /// Which part of the move ordering placed `mv` (ordering telemetry)
+    fn move_source(
+        &self,
+        mv: Move,
+        tt_move: Option<Move>,
+        countermove: Move,
+        ply: u8,
+    ) -> MoveSource {
+        if tt_move == Some(mv) {
+            MoveSource::Tt
+        } else if mv.captured().is_some() {
+            MoveSource::Capture
+        } else if self.is_killer_move(ply, mv) {
+            MoveSource::Killer
+        } else if mv == countermove {
+            MoveSource::Countermove
         } else {
-            false
+            MoveSource::History
         }
     }
 
     /// Get history score for a move
     fn get_history_score(&self, mv: Move) -> i16 {
         let color = self.board.side;
-        let piece = move_piece(mv);
-        let to_sq = move_to_sq(mv);
+        let piece = mv.piece();
+        let to_sq = mv.to();
 
         self.history[color as usize][piece as usize][to_sq]
     }
@@ -1260,422 +2098,235 @@ impl Search {
     /// Update history heuristic for a quiet move that improved alpha
     fn update_history(&mut self, mv: Move, depth: u8) {
         let color = self.board.side;
-        let piece = move_piece(mv);
-        let to_sq = move_to_sq(mv);
+        let piece = mv.piece();
+        let to_sq = mv.to();
 
         // Increment history by depth*depth (common weighting)
         let bonus = (depth as i16) * (depth as i16);
         self.history[color as usize][piece as usize][to_sq] += bonus;
 
         // Clamp to avoid overflow
-        const HISTORY_MAX: i16 = 1000;
         if self.history[color as usize][piece as usize][to_sq] > HISTORY_MAX {
             self.history[color as usize][piece as usize][to_sq] = HISTORY_MAX;
         }
     }
 
-    /// Get LMR reduction for specific depth and move count
-    /// Only applies to quiet moves, returns 0 for captures/promotions
-    fn get_lmr_reduction(
-        &mut self,
-        depth: u8,
-        move_count: u32,
-        is_quiet: bool,
-        gives_check: bool,
-    ) -> u8 {
-        // Don't reduce if LMR is disabled or move is not quiet
-        if !self.params.enable_lmr || !is_quiet || depth < self.params.lmr_min_depth {
-            return 0;
-        }
-
-        // No reduction for moves that give check
-        if gives_check {
-            return 0;
-        }
-
-        // Calculate base reduction using formula
-        let base_reduction = calculate_lmr_reduction(depth, move_count);
-
-        // Apply additional parameters
-        let reduction = base_reduction.saturating_add(self.params.lmr_base_reduction);
+    /// Search stack entry of the node at `ply`
+    #[inline]
+    fn ss(&self, ply: u8) -> &SearchStackEntry {
+        &self.stack[ply as usize]
+    }
 
-        // Ensure we don't reduce more than depth-1
-        if reduction >= depth {
-            depth - 1
-        } else {
-            reduction
-        }
+    #[inline]
+    fn ss_mut(&mut self, ply: u8) -> &mut SearchStackEntry {
+        &mut self.stack[ply as usize]
     }
 
-    /// Clear SEE cache (call at each node position)
-    fn clear_see_cache(&mut self) {
-        self.see_cache.clear();
+    /// Reset the per-node part of the stack entry at `ply`; the move that led
+    /// here and the killers of the ply are kept, the excluded move is the
+    /// caller's to set and clear
+    fn enter_node(&mut self, ply: u8) {
+        let double_extensions = match ply.checked_sub(1) {
+            Some(parent) => self.ss(parent).double_extensions,
+            None => 0,
+        };
+        let ss = self.ss_mut(ply);
+        ss.static_eval = None;
+        ss.move_count = 0;
+        ss.double_extensions = double_extensions;
     }
 
-    /// Static Exchange Evaluation (SEE) - compute net material gain of capture sequence
-    ///
-    /// SEE is like simulating a "trade" on a target square. We calculate:
-    /// - Initial capture value (what we gain)
-    /// - Potential recaptures from both sides
-    /// - Net gain/loss after all exchanges complete
-    ///
-    /// # Arguments
-    /// * `target_sq` - square where the capture occurs
-    /// * `attacker_color` - color making the capture
-    ///
-    /// # Returns
-    /// Net material gain/loss (positive = winning capture, negative = losing)
-    #[allow(clippy::needless_range_loop)]
-    fn see(&mut self, target_sq: usize, attacker_color: Color) -> i16 {
-        // Check cache first
-        if let Some(&cached_score) = self.see_cache.get(&target_sq) {
-            return cached_score;
+    /// Static evaluation of the node at `ply`, computed once and kept in its
+    /// stack entry
+    fn node_static_eval(&mut self, ply: u8) -> i16 {
+        if let Some(eval) = self.ss(ply).static_eval {
+            return eval;
         }
+        let eval = self.static_eval();
+        self.ss_mut(ply).static_eval = Some(eval);
+        eval
+    }
 
-        // Increment expensive SEE evaluation counter
-        self.stats.inc_see_eval();
-
-        // Get piece on target square (victim)
-        let victim_value =
-            if let Some((victim_kind, _victim_color)) = self.board.piece_on(target_sq) {
-                self.piece_value(&victim_kind)
-            } else {
-                // Empty square - no capture
-                self.see_cache.insert(target_sq, 0);
-                return 0;
-            };
-
-        // Get all attackers for both sides (including current attacker)
-        let mut white_attackers = self.get_attackers_to_square(target_sq, Color::White);
-        let mut black_attackers = self.get_attackers_to_square(target_sq, Color::Black);
-
-        // Implementation standard SEE utilizzando la swap-off logic
-        // side = attacker_color, gain = victim_value
-        let mut gain_list = [0i16; 32]; // max 32 capture sequence
-        let mut idx = 0;
-        gain_list[idx] = victim_value;
-        idx += 1;
-
-        let mut side = attacker_color;
-        let mut from_set = white_attackers | black_attackers;
-
-        // Sim alternate captures: if missing attackers for a side, break
-        loop {
-            // Choose least valuable attacker of current side
-            let (attackers, lva_square) = if side == Color::White {
-                (
-                    white_attackers,
-                    self.find_least_valuable_attacker(white_attackers, Color::White),
-                )
-            } else {
-                (
-                    black_attackers,
-                    self.find_least_valuable_attacker(black_attackers, Color::Black),
-                )
-            };
-
-            // No more attackers for this side -> sequence ends
-            if lva_square.is_none() || attackers == 0 {
-                break;
-            }
-
-            let lva_sq = lva_square.unwrap();
-
-            // Remove this attacker from both attack sets (make capture simulation)
-            white_attackers &= !(1u64 << lva_sq);
-            black_attackers &= !(1u64 << lva_sq);
-            from_set &= !(1u64 << lva_sq);
-
-            // Add X-ray attackers revealed by removing this piece
-            let revealed_white =
-                self.add_xray_attackers(target_sq, lva_sq, Color::White) & from_set;
-            let revealed_black =
-                self.add_xray_attackers(target_sq, lva_sq, Color::Black) & from_set;
-
-            white_attackers |= revealed_white;
-            black_attackers |= revealed_black;
-            from_set |= revealed_white | revealed_black;
-
-            // The "value" we get/give on this exchange: value of captured piece
-            let capture_value = if let Some((captured_kind, _)) = self.board.piece_on(lva_sq) {
-                self.piece_value(&captured_kind)
-            } else {
-                0
-            };
-
-            // Next gain/lux
-            if idx < gain_list.len() {
-                gain_list[idx] = capture_value.saturating_sub(gain_list[idx - 1]);
-                idx += 1;
-            }
-
-            // Switch sides
-            side = if side == Color::White {
-                Color::Black
-            } else {
-                Color::White
-            };
-        }
+    /// Whether the static eval of the node at `ply` is better than two plies
+    /// earlier (four when that node was in check, true when neither has one,
+    /// e.g. near the root)
+    fn improving(&self, ply: u8) -> bool {
+        let Some(eval) = self.ss(ply).static_eval else {
+            return false;
+        };
+        [2, 4]
+            .iter()
+            .filter_map(|&back| ply.checked_sub(back))
+            .find_map(|earlier| self.ss(earlier).static_eval)
+            .map_or(true, |earlier| eval > earlier)
+    }
 
-        // Compute net gain using swap-off logic: sum of even-index gains - sum of odd-index gains
-        let mut see_acc = 0i32;
-        for i in 0..idx {
-            if i % 2 == 0 {
-                see_acc = see_acc.saturating_add(gain_list[i] as i32);
-            } else {
-                see_acc = see_acc.saturating_sub(gain_list[i] as i32);
-            }
+    /// Moves searched before late move pruning skips the remaining quiet
+    /// moves of a node (None = no pruning at this node)
+    fn lmp_threshold(
+        &self,
+        depth: u8,
+        is_pv_node: bool,
+        in_check: bool,
+        improving: bool,
+    ) -> Option<u32> {
+        if !self.params.enable_lmp || is_pv_node || in_check || depth == 0 {
+            return None;
         }
-
-        // Clamp to i16 range and cache result
-        let see_score = if see_acc > i16::MAX as i32 {
-            i16::MAX
-        } else if see_acc < i16::MIN as i32 {
-            i16::MIN
+        let count = *self.params.lmp_move_counts.get(depth as usize - 1)?;
+        Some(if improving || !self.params.enable_improving {
+            count
         } else {
-            see_acc as i16
-        };
-
-        self.see_cache.insert(target_sq, see_score);
-        see_score
+            count * 2 / 3
+        })
     }
 
-    /// Get all pieces that attack the target square from the given color
-    fn get_attackers_to_square(&self, target_sq: usize, color: Color) -> u64 {
-        let mut attackers = 0u64;
-        let _opp_color = match color {
-            Color::White => Color::Black,
-            Color::Black => Color::White,
-        };
-
-        // Pawn attacks (special case: pawns attack differently from where they move)
-        attackers |= if color == Color::White {
-            let white_pawns = self.board.piece_bb(PieceKind::Pawn, Color::White);
-            ((white_pawns & crate::utils::NOT_FILE_A) << 7) & (1u64 << target_sq)
-                | ((white_pawns & crate::utils::NOT_FILE_H) << 9) & (1u64 << target_sq)
+    /// Futility margin of a node: narrower (pruning more) when not improving
+    fn futility_margin(&self, improving: bool) -> i16 {
+        if improving || !self.params.enable_improving {
+            self.params.futility_margin
         } else {
-            let black_pawns = self.board.piece_bb(PieceKind::Pawn, Color::Black);
-            ((black_pawns & crate::utils::NOT_FILE_A) >> 9) & (1u64 << target_sq)
-                | ((black_pawns & crate::utils::NOT_FILE_H) >> 7) & (1u64 << target_sq)
-        };
+            (self.params.futility_margin - self.params.improving_margin).max(0)
+        }
+    }
 
-        // Knight attacks
-        attackers |=
-            crate::utils::knight_attacks(target_sq) & self.board.piece_bb(PieceKind::Knight, color);
+    /// Record the move that led to the node at `ply`
+    fn set_stack_move(&mut self, ply: u8, mv: Move) {
+        self.ss_mut(ply).current_move = mv;
+    }
 
-        // King attacks (adjacent squares)
-        attackers |=
-            crate::utils::king_attacks(target_sq) & self.board.piece_bb(PieceKind::King, color);
+    /// Move played `back` plies before the one that led to the node at `ply`
+    /// (0 if there is none, e.g. above the root or after a null move)
+    fn stack_move(&self, ply: u8, back: u8) -> Move {
+        ply.checked_sub(back)
+            .map(|idx| self.ss(idx).current_move)
+            .unwrap_or(Move::NONE)
+    }
 
-        // Diagonal sliding attacks (bishops, queens)
-        let diagonal_sliders = self.board.piece_bb(PieceKind::Bishop, color)
-            | self.board.piece_bb(PieceKind::Queen, color);
-        if diagonal_sliders != 0 {
-            attackers |= self.get_sliding_attackers(target_sq, diagonal_sliders, true);
+    /// Stored refutation of the opponent's last move (0 if none)
+    fn countermove(&self, ply: u8) -> Move {
+        let prev = self.stack_move(ply, 0);
+        if prev.is_none() {
+            return Move::NONE;
         }
+        self.countermoves[self.board.side as usize][prev.piece() as usize][prev.to()]
+    }
 
-        // Orthogonal sliding attacks (rooks, queens)
-        let orthogonal_sliders = self.board.piece_bb(PieceKind::Rook, color)
-            | self.board.piece_bb(PieceKind::Queen, color);
-        if orthogonal_sliders != 0 {
-            attackers |= self.get_sliding_attackers(target_sq, orthogonal_sliders, false);
+    /// Remember `mv` as the refutation of the opponent's last move
+    fn store_countermove(&mut self, ply: u8, mv: Move) {
+        let prev = self.stack_move(ply, 0);
+        if !prev.is_none() {
+            self.countermoves[self.board.side as usize][prev.piece() as usize][prev.to()] = mv;
         }
-
-        attackers
     }
 
-    /// Get sliding attackers in specified direction (diagonal or orthogonal)
-    fn get_sliding_attackers(&self, target_sq: usize, sliders: u64, diagonal: bool) -> u64 {
-        let mut attackers = 0u64;
-        let occ = self.board.occ;
-
-        // Precomputed directions: diagonal = [-9, -7, +7, +9], orthogonal = [-8, +8, -1, +1]
-        let directions = if diagonal {
-            [-9, -7, 7, 9]
-        } else {
-            [-8, 8, -1, 1]
-        };
-
-        for &dir in &directions {
-            let mut sq = target_sq as i8 + dir;
-
-            // Continue scanning until we hit board edges or a piece
-            while (0..64).contains(&sq) {
-                // Check board boundaries for horizontal directions
-                if !diagonal && (dir == -1 && (sq + 1) % 8 == 0 || dir == 1 && sq % 8 == 0) {
-                    break;
-                }
-                // Check board boundaries for diagonal directions
-                if diagonal
-                    && ((dir == -9 && (sq + 1) % 8 == 0)
-                        || (dir == -7 && sq % 8 == 0)
-                        || (dir == 7 && (sq + 1) % 8 == 0)
-                        || (dir == 9 && sq % 8 == 0))
-                {
-                    break;
-                }
-
-                let square_mask = 1u64 << sq;
-
-                // If we hit a piece
-                if (square_mask & occ) != 0 {
-                    // If this piece is one of our sliders, it's an attacker
-                    if (square_mask & sliders) != 0 {
-                        attackers |= square_mask;
-                    }
-                    break; // Stop scanning in this direction
-                }
-
-                sq += dir;
-            }
+    /// Ordering score of a quiet move: history plus the continuation histories
+    /// keyed by the opponent's last move and by our own previous move
+    fn quiet_move_score(&self, mv: Move, ply: u8) -> i32 {
+        let mut score = self.get_history_score(mv) as i32 + self.ordering_jitter(mv);
+        if let Some(idx) = continuation_index(self.stack_move(ply, 0), mv) {
+            score += self.counter_history[idx] as i32;
         }
-
-        attackers
+        if let Some(idx) = continuation_index(self.stack_move(ply, 1), mv) {
+            score += self.followup_history[idx] as i32;
+        }
+        score
     }
 
-    /// Find the least valuable attacker (lowest piece value) from attacker bitboard
-    fn find_least_valuable_attacker(&self, attackers: u64, color: Color) -> Option<usize> {
-        if attackers == 0 {
-            return None;
+    /// Small deterministic per-move offset (0..ORDERING_JITTER_MAX) that reshuffles
+    /// quiet moves with similar history; 0 unless `params.ordering_jitter` is set
+    fn ordering_jitter(&self, mv: Move) -> i32 {
+        let seed = self.params.ordering_jitter;
+        if seed == 0 {
+            return 0;
         }
+        let hash = (mv.raw() ^ seed.wrapping_mul(0x9E37_79B9)).wrapping_mul(0x85EB_CA6B);
+        ((hash >> 16) % ORDERING_JITTER_MAX) as i32
+    }
 
-        // Check pieces in value order: Pawn, Knight, Bishop, Rook, Queen, King
-        let pieces = [
-            (PieceKind::Pawn, self.board.piece_bb(PieceKind::Pawn, color)),
-            (
-                PieceKind::Knight,
-                self.board.piece_bb(PieceKind::Knight, color),
-            ),
-            (
-                PieceKind::Bishop,
-                self.board.piece_bb(PieceKind::Bishop, color),
-            ),
-            (PieceKind::Rook, self.board.piece_bb(PieceKind::Rook, color)),
-            (
-                PieceKind::Queen,
-                self.board.piece_bb(PieceKind::Queen, color),
-            ),
-            (PieceKind::King, self.board.piece_bb(PieceKind::King, color)),
-        ];
-
-        for (_kind, piece_bb) in pieces {
-            let attackers_of_kind = attackers & piece_bb;
-            if attackers_of_kind != 0 {
-                // Get least significant bit (lowest square index)
-                let lsb = attackers_of_kind.trailing_zeros();
-                return Some(lsb as usize);
-            }
+    /// Update counter-move and follow-up history for a quiet move that improved alpha
+    fn update_continuation_history(&mut self, mv: Move, depth: u8, ply: u8) {
+        let bonus = (depth as i16) * (depth as i16);
+        if let Some(idx) = continuation_index(self.stack_move(ply, 0), mv) {
+            self.counter_history[idx] = (self.counter_history[idx] + bonus).min(HISTORY_MAX);
         }
+        if let Some(idx) = continuation_index(self.stack_move(ply, 1), mv) {
+            self.followup_history[idx] = (self.followup_history[idx] + bonus).min(HISTORY_MAX);
+        }
+    }
 
-        None
+    /// Capture ordering key: SEE plus a share of the capture history, so
+    /// captures of similar SEE are tried in the order that produced cutoffs
+    fn capture_key(&self, mv: Move, see: i16) -> i16 {
+        see + self.capture_history[capture_index(mv)] / CAPTURE_HISTORY_DIVISOR
     }
 
-    /// Add X-ray attackers revealed when a piece is removed from square
-    fn add_xray_attackers(&self, target_sq: usize, removed_sq: usize, color: Color) -> u64 {
-        let mut xray_attackers = 0u64;
+    /// Update the capture history for a capture that caused a beta cutoff
+    fn update_capture_history(&mut self, mv: Move, depth: u8) {
+        let bonus = (depth as i16) * (depth as i16);
+        let entry = &mut self.capture_history[capture_index(mv)];
+        *entry = (*entry + bonus).min(HISTORY_MAX);
+    }
 
-        // Diagonal X-rays (bishops, queens)
-        let diagonal_sliders = self.board.piece_bb(PieceKind::Bishop, color)
-            | self.board.piece_bb(PieceKind::Queen, color);
-        if diagonal_sliders != 0 && self.is_on_same_diagonal(target_sq, removed_sq) {
-            xray_attackers |=
-                self.get_sliding_attackers_target_squares(target_sq, diagonal_sliders, true);
+    /// Get LMR reduction for specific depth and move count
+    /// Only applies to quiet moves, returns 0 for captures/promotions
+    fn get_lmr_reduction(
+        &mut self,
+        depth: u8,
+        move_count: u32,
+        is_quiet: bool,
+        gives_check: bool,
+        improving: bool,
+    ) -> u8 {
+        // Don't reduce if LMR is disabled or move is not quiet
+        if !self.params.enable_lmr || !is_quiet || depth < self.params.lmr_min_depth {
+            return 0;
         }
 
-        // Orthogonal X-rays (rooks, queens)
-        let orthogonal_sliders = self.board.piece_bb(PieceKind::Rook, color)
-            | self.board.piece_bb(PieceKind::Queen, color);
-        if orthogonal_sliders != 0 && self.is_on_same_rank_file(target_sq, removed_sq) {
-            xray_attackers |=
-                self.get_sliding_attackers_target_squares(target_sq, orthogonal_sliders, false);
+        // No reduction for moves that give check
+        if gives_check {
+            return 0;
         }
 
-        xray_attackers
-    }
+        // Calculate base reduction using formula
+        let base_reduction = calculate_lmr_reduction(depth, move_count);
 
-    /// Check if two squares are on same diagonal
-    fn is_on_same_diagonal(&self, sq1: usize, sq2: usize) -> bool {
-        let file1 = sq1 % 8;
-        let rank1 = sq1 / 8;
-        let file2 = sq2 % 8;
-        let rank2 = sq2 / 8;
+        // Apply additional parameters; one ply more when the position is not
+        // getting better
+        let mut reduction = base_reduction.saturating_add(self.params.lmr_base_reduction);
+        if self.params.enable_improving && !improving {
+            reduction = reduction.saturating_add(1);
+        }
 
-        // Same diagonal if absolute difference in file equals absolute difference in rank
-        file1.abs_diff(file2) == rank1.abs_diff(rank2)
+        // Ensure we don't reduce more than depth-1
+        if reduction >= depth {
+            depth - 1
+        } else {
+            reduction
+        }
     }
 
-    /// Check if two squares are on same rank or file
-    fn is_on_same_rank_file(&self, sq1: usize, sq2: usize) -> bool {
-        let file1 = sq1 % 8;
-        let rank1 = sq1 / 8;
-        let file2 = sq2 % 8;
-        let rank2 = sq2 / 8;
-
-        file1 == file2 || rank1 == rank2
+    /// Static Exchange Evaluation of a move (see [`Board::see`]), counted in stats
+    fn see(&mut self, mv: Move) -> i16 {
+        self.stats.inc_see_eval();
+        self.board.see(mv)
     }
 
-    /// Get sliding attackers that can reach target through specific squares
-    fn get_sliding_attackers_target_squares(
-        &self,
-        target_sq: usize,
-        sliders: u64,
-        diagonal: bool,
-    ) -> u64 {
-        let mut attackers = 0u64;
-        let occ = self.board.occ;
-
-        let directions = if diagonal {
-            [-9, -7, 7, 9]
+    /// SEE for captures, 0 for anything else (ordering key)
+    fn capture_see(&mut self, mv: Move) -> i16 {
+        if mv.captured().is_some() {
+            self.see(mv)
         } else {
-            [-8, 8, -1, 1]
-        };
-
-        for &dir in &directions {
-            let mut sq = target_sq as i8 + dir;
-            let mut found_blocker = false;
-
-            while (0..64).contains(&sq) {
-                // Boundary checks
-                if !diagonal && (dir == -1 && (sq + 1) % 8 == 0 || dir == 1 && sq % 8 == 0) {
-                    break;
-                }
-                if diagonal
-                    && ((dir == -9 && (sq + 1) % 8 == 0)
-                        || (dir == -7 && sq % 8 == 0)
-                        || (dir == 7 && (sq + 1) % 8 == 0)
-                        || (dir == 9 && sq % 8 == 0))
-                {
-                    break;
-                }
-
-                let square_mask = 1u64 << sq;
-
-                if (square_mask & occ) != 0 {
-                    if !found_blocker {
-                        found_blocker = true;
-                    } else {
-                        // Second piece - if it's our slider, it's an X-ray attacker
-                        if (square_mask & sliders) != 0 {
-                            attackers |= square_mask;
-                        }
-                        break;
-                    }
-                }
-
-                sq += dir;
-            }
+            0
         }
-
-        attackers
     }
 
-    /// Clear SEE cache (call at each node position) - already defined above
     /// Check if a move gives check (simplified check)
     fn move_gives_check(&mut self, mv: Move) -> bool {
         // Make the move and check if opponent is in check
-        let undo = self.board.make_move(mv);
+        self.board.push_move(mv);
         let in_check = self.is_in_check();
-        self.board.unmake_move(undo);
+        self.board.pop_move();
         in_check
     }
 
@@ -1689,8 +2340,7 @@ impl Search {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::board::FLAG_PROMOTION;
-    use crate::board::{move_captured, move_flag, Board};
+    use crate::board::{parse_uci_move, Board};
 
     #[test]
     fn test_search_creation() {
@@ -1735,7 +2385,7 @@ mod tests {
         let (best_move, score) = search.search(Some(1));
 
         // Should find some move (even with static eval)
-        assert!(best_move != 0 || score != -INFINITE);
+        assert!(best_move != Move::NONE || score != -INFINITE);
 
         // Stats should be recorded
         assert!(search.stats.nodes > 0);
@@ -1774,22 +2424,30 @@ mod tests {
         // Perform a search to generate some killer moves
         let (_best_move, _score) = search.search(Some(3));
 
-        // Check that killer moves table is initialized properly
-        assert!(!search.killer_moves.is_empty());
-        assert!(search.killer_moves[0].len() >= 2); // Should have 2 slots as per params
+        // Check that the stack has room for the killers of every ply
+        assert!(search.stack.len() > 4);
+        assert!(search.params.killer_moves_count >= 2); // Should have 2 slots as per params
 
         // Test that we can store a killer move directly
         let quiet_move = board
             .generate_moves()
             .iter()
-            .find(|&&m| move_captured(m).is_none() && !move_flag(m, FLAG_PROMOTION))
+            .find(|&&m| m.captured().is_none() && !m.is_promotion())
             .copied()
-            .unwrap_or(0);
-
-        if quiet_move != 0 {
-            let initial_len = search.killer_moves[1].iter().filter(|&&m| m != 0).count();
+            .unwrap_or(Move::NONE);
+
+        if !quiet_move.is_none() {
+            let initial_len = search.stack[1]
+                .killers
+                .iter()
+                .filter(|m| !m.is_none())
+                .count();
             search.store_killer_move(1, quiet_move);
-            let new_len = search.killer_moves[1].iter().filter(|&&m| m != 0).count();
+            let new_len = search.stack[1]
+                .killers
+                .iter()
+                .filter(|m| !m.is_none())
+                .count();
 
             // Should have stored the move
             assert!(new_len >= initial_len);
@@ -1810,11 +2468,11 @@ mod tests {
         let moves = board.generate_moves();
         let quiet_move = moves
             .iter()
-            .find(|&&m| move_captured(m).is_none() && !move_flag(m, FLAG_PROMOTION))
+            .find(|&&m| m.captured().is_none() && !m.is_promotion())
             .copied()
-            .unwrap_or(0);
+            .unwrap_or(Move::NONE);
 
-        if quiet_move != 0 {
+        if quiet_move != Move::NONE {
             let initial_score = search.get_history_score(quiet_move);
 
             // Update history
@@ -1822,11 +2480,59 @@ mod tests {
 
             let updated_score = search.get_history_score(quiet_move);
 
-            // Should have increased
-            assert!(updated_score > initial_score);
-            // Should be depth*depth = 9 bonus
-            assert_eq!(updated_score - initial_score, 9);
+            // Should have increased
+            assert!(updated_score > initial_score);
+            // Should be depth*depth = 9 bonus
+            assert_eq!(updated_score - initial_score, 9);
+        }
+    }
+
+    #[test]
+    fn test_countermove_and_continuation_history() {
+        let mut board = Board::new();
+        board
+            .set_from_fen("rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2")
+            .unwrap();
+        let find = |board: &mut Board, uci: &str| {
+            board
+                .generate_moves()
+                .into_iter()
+                .find(|&m| crate::board::move_to_uci(m) == uci)
+                .unwrap()
+        };
+
+        // White played Nf3 (ply 0 of the tree), black now looks for a reply
+        let nf3 = find(&mut board, "g1f3");
+        board.make_move(nf3);
+        let nc6 = find(&mut board, "b8c6");
+        let mut search = Search::with_board(board.clone());
+        search.set_stack_move(0, nf3);
+
+        assert_eq!(search.countermove(0), Move::NONE);
+        assert_eq!(search.quiet_move_score(nc6, 0), 0);
+
+        search.store_countermove(0, nc6);
+        search.update_continuation_history(nc6, 3, 0);
+        assert_eq!(search.countermove(0), nc6);
+        assert_eq!(search.quiet_move_score(nc6, 0), 9);
+
+        // No previous move (e.g. after a null move): no countermove, no bonus
+        search.set_stack_move(0, Move::NONE);
+        assert_eq!(search.countermove(0), Move::NONE);
+        assert_eq!(search.quiet_move_score(nc6, 0), 0);
+
+        // Follow-up history is keyed by the move two plies back
+        search.set_stack_move(0, nf3);
+        search.set_stack_move(1, Move::NONE);
+        assert_eq!(search.stack_move(1, 1), nf3);
+        search.update_continuation_history(nc6, 2, 1);
+        assert_eq!(search.quiet_move_score(nc6, 1), 4);
+
+        // Scores stay clamped
+        for _ in 0..200 {
+            search.update_continuation_history(nc6, 10, 0);
         }
+        assert_eq!(search.quiet_move_score(nc6, 0), HISTORY_MAX as i32);
     }
 
     #[test]
@@ -1842,7 +2548,7 @@ mod tests {
         let moves = board.generate_moves();
         let quiet_moves: Vec<Move> = moves
             .iter()
-            .filter(|&&m| move_captured(m).is_none() && !move_flag(m, FLAG_PROMOTION))
+            .filter(|&&m| m.captured().is_none() && !m.is_promotion())
             .copied()
             .collect();
 
@@ -1867,7 +2573,7 @@ mod tests {
                             || ordered_moves
                                 .iter()
                                 .take(pos)
-                                .all(|&m| move_captured(m).is_some())
+                                .all(|&m| m.captured().is_some())
                     );
                 }
             }
@@ -1892,7 +2598,7 @@ mod tests {
         let (best_move, score) = search.search(Some(3));
 
         // Should find some move with a reasonable score
-        assert!(best_move != 0 || score != -INFINITE);
+        assert!(best_move != Move::NONE || score != -INFINITE);
         println!(
             "Aspiration windows test: best_move={}, score={}",
             best_move, score
@@ -1916,13 +2622,58 @@ mod tests {
         let (best_move, score) = search.search(Some(4));
 
         // Should complete without crashing
-        assert!(best_move != 0 || score != -INFINITE);
+        assert!(best_move != Move::NONE || score != -INFINITE);
         println!(
             "Aspiration windows complex test: best_move={}, score={}",
             best_move, score
         );
     }
 
+    #[test]
+    fn test_aspiration_failures_widen_and_are_reported() {
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct Bounds(Mutex<Vec<(u8, i16, ScoreBound)>>);
+
+        impl SearchObserver for Bounds {
+            fn on_aspiration_fail(&self, info: &IterationInfo, bound: ScoreBound) {
+                self.0.lock().unwrap().push((info.depth, info.score, bound));
+            }
+        }
+
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("r3k2r/ppp2ppp/2n1q3/2b1p3/2B1P3/3P1N2/PPP2PPP/RN2K2R w KQkq - 0 8")
+            .unwrap();
+        let bounds = Arc::new(Bounds::default());
+        // A 1 cp window fails at almost every depth
+        let params = SearchParams::new()
+            .max_depth(5)
+            .time_limit(0)
+            .aspiration_window(1);
+        let mut search = Search::new(board.clone(), 4, params).with_observer(bounds.clone());
+        let (mv, score) = search.search(Some(5));
+
+        let fails = bounds.0.lock().unwrap().clone();
+        assert!(!fails.is_empty());
+        let stats = search.stats();
+        assert_eq!(stats.aspiration_researches, fails.len() as u64);
+        let highs = fails.iter().filter(|f| f.2 == ScoreBound::Lower).count();
+        assert_eq!(stats.aspiration_fail_highs, highs as u64);
+        assert_eq!(stats.aspiration_fail_lows, (fails.len() - highs) as u64);
+        assert!(stats.aspiration_research_nodes > 0);
+        assert!(stats.aspiration_research_nodes < stats.nodes);
+        assert!(fails.iter().all(|&(depth, _, _)| (2..=5).contains(&depth)));
+        // The widened re-searches still end with an exact root score
+        assert!(board.generate_moves().contains(&mv));
+        assert!(search
+            .root_moves()
+            .iter()
+            .any(|rm| rm.mv == mv && rm.score == score && rm.is_exact()));
+    }
+
     #[test]
     fn test_null_move_pruning_basic() {
         let mut board = Board::new();
@@ -2213,7 +2964,7 @@ mod tests {
 
         let _ = search1.search(Some(4));
         let lmr_first_search = search1.stats().lmr_reductions;
-        let _history_after_first = search1.get_history_score(12345); // Dummy move for testing
+        let _history_after_first = search1.get_history_score(Move::from_raw(12345)); // Dummy move for testing
 
         // Second search might have different behavior due to updated history
         // Note: LMR doesn't directly use history in current implementation, but this tests integration
@@ -2270,7 +3021,7 @@ mod tests {
 
         // The best move could be a capture or quiet move depending on position
         // LMR only applies to quiet moves, so we test that system works
-        assert!(best_move != 0); // Should find at least some move
+        assert!(best_move != Move::NONE); // Should find at least some move
     }
 
     #[test]
@@ -2330,6 +3081,185 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_improving_compares_with_two_plies_earlier() {
+        let mut search = Search::with_board(Board::new());
+        // Near the root there is nothing to compare with
+        search.stack[0].static_eval = Some(10);
+        assert!(search.improving(0));
+        search.stack[1].static_eval = Some(-300);
+        assert!(search.improving(1));
+
+        search.stack[2].static_eval = Some(20);
+        assert!(search.improving(2));
+        search.stack[2].static_eval = Some(10);
+        assert!(!search.improving(2));
+
+        // In check two plies earlier: four plies back decides
+        search.stack[4].static_eval = Some(0);
+        search.stack[2].static_eval = None;
+        search.stack[0].static_eval = Some(-50);
+        assert!(search.improving(4));
+        search.stack[0].static_eval = Some(50);
+        assert!(!search.improving(4));
+
+        // No static eval (in check): not improving
+        search.stack[4].static_eval = None;
+        assert!(!search.improving(4));
+    }
+
+    #[test]
+    fn test_not_improving_prunes_and_reduces_more() {
+        let mut search = Search::with_board(Board::new());
+        assert_eq!(search.futility_margin(true), 150);
+        assert_eq!(search.futility_margin(false), 100);
+        let improving = search.get_lmr_reduction(8, 10, true, false, true);
+        assert_eq!(
+            search.get_lmr_reduction(8, 10, true, false, false),
+            improving + 1
+        );
+
+        let mut search = Search::new(Board::new(), 1, SearchParams::new().enable_improving(false));
+        assert_eq!(search.futility_margin(false), 150);
+        assert_eq!(
+            search.get_lmr_reduction(8, 10, true, false, false),
+            search.get_lmr_reduction(8, 10, true, false, true)
+        );
+    }
+
+    #[test]
+    fn test_lmp_thresholds_by_depth() {
+        let search = Search::with_board(Board::new());
+        assert_eq!(search.lmp_threshold(1, false, false, true), Some(5));
+        assert_eq!(search.lmp_threshold(4, false, false, true), Some(20));
+        assert_eq!(search.lmp_threshold(4, false, false, false), Some(13));
+        // Never in PV nodes, in check or beyond the table
+        assert_eq!(search.lmp_threshold(2, true, false, true), None);
+        assert_eq!(search.lmp_threshold(2, false, true, true), None);
+        assert_eq!(search.lmp_threshold(5, false, false, true), None);
+
+        let search = Search::new(Board::new(), 1, SearchParams::new().enable_lmp(false));
+        assert_eq!(search.lmp_threshold(1, false, false, true), None);
+    }
+
+    #[test]
+    fn test_lmp_skips_late_quiet_moves() {
+        crate::init();
+        let fen = "2r3k1/pp3ppp/2n1b3/q2pP3/3P4/P1PB1N2/5PPP/R2Q2K1 b - - 0 18";
+        let pruned = |params: SearchParams| {
+            let mut board = Board::new();
+            board.set_from_fen(fen).unwrap();
+            let mut search = Search::new(board, 16, params.max_depth(6).time_limit(0));
+            let (mv, _) = search.search(Some(6));
+            assert!(!mv.is_none());
+            search.stats().lmp_pruned
+        };
+        assert!(pruned(SearchParams::new()) > 0);
+        assert_eq!(pruned(SearchParams::new().enable_lmp(false)), 0);
+    }
+
+    #[test]
+    fn test_ordering_telemetry_is_opt_in_and_neutral() {
+        crate::init();
+        let fen = "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4";
+        let run = |params: SearchParams| {
+            let mut board = Board::new();
+            board.set_from_fen(fen).unwrap();
+            let mut search = Search::new(board, 16, params.max_depth(5).time_limit(0));
+            search.search(Some(5));
+            search.stats().clone()
+        };
+
+        let plain = run(SearchParams::new());
+        assert!(plain.ordering.is_none());
+
+        let stats = run(SearchParams::new().ordering_telemetry(true));
+        assert_eq!(stats.nodes, plain.nodes, "telemetry changed the search");
+        let ordering = stats.ordering.unwrap();
+        assert_eq!(ordering.cutoff_nodes, stats.cutoffs);
+        assert!(ordering.first_move_cutoffs > 0);
+        assert!(ordering.average_cutoff_move_number() >= 1.0);
+        let searched: u64 = ordering.sources.iter().map(|s| s.searched).sum();
+        let cutoffs: u64 = ordering.sources.iter().map(|s| s.cutoffs).sum();
+        assert!(searched >= cutoffs && cutoffs == ordering.cutoff_nodes);
+        assert!(ordering.source(MoveSource::Tt).searched > 0);
+    }
+
+    #[test]
+    fn test_depth_and_ply_are_bounded_by_max_ply() {
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3")
+            .unwrap();
+        let mut search = Search::new(board, 1, SearchParams::new());
+
+        // A node at the last ply stops there, whatever depth is left
+        let eval = search.static_eval();
+        let last = (MAX_PLY - 1) as u8;
+        assert_eq!(search.negamax_pv(6, -INFINITE, INFINITE, last), eval);
+        let fast = search.static_eval_fast();
+        assert_eq!(search.qsearch(-INFINITE, INFINITE, 4, u8::MAX), fast);
+
+        // Depths past MAX_DEPTH are clamped, not rejected
+        assert_eq!(SearchParams::new().max_depth(u8::MAX).max_depth, MAX_DEPTH);
+        let mut board = Board::new();
+        board.set_from_fen("8/8/4k3/8/8/3K4/8/8 w - - 0 1").unwrap();
+        let mut search = Search::new(board, 1, SearchParams::new().time_limit(0));
+        search.search(Some(u8::MAX));
+        assert_eq!(search.stats().completed_depth, MAX_DEPTH);
+    }
+
+    #[test]
+    fn test_node_and_nps_limits() {
+        crate::init();
+        let fen = "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4";
+        let run = |params: SearchParams| {
+            let mut board = Board::new();
+            board.set_from_fen(fen).unwrap();
+            let mut search = Search::new(board, 16, params.max_depth(99).time_limit(0));
+            let start = std::time::Instant::now();
+            let (best, _) = search.search(None);
+            (best, search.stats().nodes, start.elapsed())
+        };
+
+        let (best, nodes, _) = run(SearchParams::new().node_limit(4000));
+        assert_ne!(best, Move::NONE);
+        assert_eq!(nodes, 4000);
+        assert_eq!(run(SearchParams::new().node_limit(4000)).0, best);
+
+        // 2000 nodes at 10000 nodes per second take at least 200 ms
+        let (_, nodes, elapsed) = run(SearchParams::new().node_limit(2000).nps_limit(10_000));
+        assert_eq!(nodes, 2000);
+        assert!(elapsed >= Duration::from_millis(190), "{:?}", elapsed);
+
+        // A limit of one node per second keeps to the time limit, and to a
+        // stop raised during a sleep
+        let throttled = |time_limit: u64, stop: Arc<AtomicBool>| {
+            let mut board = Board::new();
+            board.set_from_fen(fen).unwrap();
+            let params = SearchParams::new()
+                .nps_limit(1)
+                .max_depth(99)
+                .time_limit(time_limit);
+            let mut search = Search::new(board, 16, params).with_stop_flag(stop);
+            let start = std::time::Instant::now();
+            search.search(None);
+            start.elapsed()
+        };
+        let elapsed = throttled(200, Arc::new(AtomicBool::new(false)));
+        assert!(elapsed < Duration::from_millis(600), "{:?}", elapsed);
+        let stop = Arc::new(AtomicBool::new(false));
+        let raise = stop.clone();
+        let stopper = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            raise.store(true, Ordering::Relaxed);
+        });
+        let elapsed = throttled(0, stop);
+        assert!(elapsed < Duration::from_millis(500), "{:?}", elapsed);
+        stopper.join().unwrap();
+    }
+
     #[test]
     fn test_lmr_parameters() {
         let mut board = Board::new();
@@ -2623,6 +3553,55 @@ mod tests {
         assert_eq!(search_low_depth.params.futility_min_depth, 2);
     }
 
+    #[test]
+    fn test_contempt_scores_draws_against_engine_side() {
+        crate::init();
+        let mut board = Board::new();
+        board.set_from_fen("8/8/4k3/8/8/3K4/8/8 w - - 0 1").unwrap();
+
+        // Every move of a bare-kings position reaches a dead draw
+        let tal = SearchParams::new().eval_weights(crate::eval::EvalWeights::TAL);
+        let (_, score) = Search::new(board.clone(), 1, tal).search(Some(2));
+        assert_eq!(score, -crate::eval::EvalWeights::TAL.contempt);
+
+        let (_, score) = Search::new(board.clone(), 1, SearchParams::new()).search(Some(2));
+        assert_eq!(score, 0);
+
+        // UCI Contempt adds to the style; playing Black, a draw is bad for Black
+        let params = SearchParams::new()
+            .eval_weights(crate::eval::EvalWeights::TAL)
+            .contempt(15)
+            .engine_color(Some(Color::Black));
+        let (_, score) = Search::new(board, 1, params).search(Some(2));
+        assert_eq!(score, crate::eval::EvalWeights::TAL.contempt + 15);
+    }
+
+    #[test]
+    fn test_iir_reduces_pv_nodes_without_tt_move() {
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 0 1")
+            .unwrap();
+
+        // A fresh table has no TT move for the PV node searched directly
+        let params = SearchParams::new().enable_iir(false);
+        let mut search_disabled = Search::new(board.clone(), 1, params);
+        search_disabled.negamax_pv(5, -INFINITE, INFINITE, 1);
+        assert_eq!(search_disabled.stats().iir_reductions, 0);
+
+        let params = SearchParams::new().enable_iir(true).iir_min_depth(4);
+        let mut search_enabled = Search::new(board.clone(), 1, params);
+        search_enabled.negamax_pv(5, -INFINITE, INFINITE, 1);
+        assert!(search_enabled.stats().iir_reductions > 0);
+
+        // Non-PV (null window) nodes are never reduced
+        let params = SearchParams::new().enable_iir(true).iir_min_depth(4);
+        let mut search_null_window = Search::new(board, 1, params);
+        search_null_window.negamax_pv(5, 0, 1, 1);
+        assert_eq!(search_null_window.stats().iir_reductions, 0);
+    }
+
     #[test]
     fn test_quiescence_basic() {
         // Test basic quiescence search functionality
@@ -2840,110 +3819,120 @@ mod tests {
     }
 
     #[test]
-    fn test_see_basic() {
-        // Test basic SEE functionality with simple positions
-        let mut board = Board::new();
-
-        // Test 1: Simple winning capture - white pawn captures black knight
-        board.set_from_fen("8/8/8/3n4/8/4P3/8/8 w - - 0 1").unwrap();
-        let mut search = Search::new(board.clone(), 1, SearchParams::new());
+    fn test_quiescence_scores_bare_king_stalemate_as_draw() {
+        crate::init();
+        // Black to move has a lone king (and a blocked pawn) and no move:
+        // the material says lost, the position is a draw
+        for fen in [
+            "k7/8/1K1Q4/8/8/8/8/8 b - - 0 1",
+            "7k/7p/7P/8/8/8/6Q1/7K b - - 0 1",
+        ] {
+            let mut board = Board::new();
+            board.set_from_fen(fen).unwrap();
+            let mut search = Search::new(board, 1, SearchParams::new().contempt(0));
+            assert_eq!(search.qsearch(-INFINITE, INFINITE, 3, 0), 0, "{}", fen);
+        }
 
-        // White pawn on e4 (square 36) can capture black knight on d5 (square 35)
-        let see_score = search.see(35, Color::White);
+        // With a move left the lost ending is scored as such
+        let mut board = Board::new();
+        board
+            .set_from_fen("k7/8/1K6/8/3Q4/8/8/8 b - - 0 1")
+            .unwrap();
+        let mut search = Search::new(board, 1, SearchParams::new().contempt(0));
+        assert!(search.qsearch(-INFINITE, INFINITE, 3, 0) < -500);
+    }
 
-        // Pawn (100) captures Knight (320) = good trade for white
-        // But since knight can recapture the pawn, SEE should be ≤ 0
-        // For now, just check it's not wildly positive (allowing implementation flexibility)
-        assert!(see_score < 500, "SEE should be reasonable: {}", see_score);
+    #[test]
+    fn test_see_basic() {
+        crate::init();
+        let find = |board: &mut Board, uci: &str| crate::board::parse_uci_move(board, uci).unwrap();
 
-        // Test 2: Simple losing capture - white pawn captures black queen but gets recaptured
-        board.set_from_fen("8/8/8/8/3q4/8/4P3/8 w - - 0 1").unwrap();
+        // Pawn takes an undefended knight: wins the knight
+        let mut board = Board::new();
+        board
+            .set_from_fen("4k3/8/8/3n4/4P3/8/8/4K3 w - - 0 1")
+            .unwrap();
+        let mv = find(&mut board, "e4d5");
         let mut search = Search::new(board.clone(), 1, SearchParams::new());
+        assert_eq!(search.see(mv), 320);
 
-        // White pawn captures queen - should be reasonable (likely negative but allow flexibility)
-        let see_score = search.see(35, Color::White);
-        println!("SEE score for pawn captures queen: {}", see_score);
-        // SEE should give reasonable result even if implementation is simplified
-        assert!(see_score < 1000, "SEE should be reasonable: {}", see_score);
-
-        // Test 3: Empty square should return 0
-        board.set_from_fen("8/8/8/8/8/8/8/8 w - - 0 1").unwrap();
+        // Queen takes a pawn defended by a pawn: loses queen for pawn
+        board
+            .set_from_fen("4k3/8/2p5/3p4/8/8/8/3QK3 w - - 0 1")
+            .unwrap();
+        let mv = find(&mut board, "d1d5");
         let mut search = Search::new(board.clone(), 1, SearchParams::new());
+        assert_eq!(search.see(mv), 100 - 900);
 
-        let see_score = search.see(0, Color::White);
-        assert_eq!(see_score, 0, "Empty square should return 0");
-
-        println!("SEE basic test passed");
+        // Quiet move: nothing to exchange
+        board
+            .set_from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1")
+            .unwrap();
+        let mv = find(&mut board, "a1a2");
+        let mut search = Search::new(board.clone(), 1, SearchParams::new());
+        assert_eq!(search.see(mv), 0);
+        assert_eq!(search.stats().see_evals, 1);
     }
 
     #[test]
     fn test_see_capture_ordering() {
-        // Test that SEE improves capture ordering compared to pure MVV-LVA
+        crate::init();
+        // White can take a defended rook with the queen (bad) or a hanging knight
+        // with a pawn (good): SEE must put the knight capture first even though
+        // MVV-LVA prefers the rook.
         let mut board = Board::new();
-
-        // Simple tactical position with clear captures
-        // Starting position has clear capture opportunities
         board
-            .set_from_fen("rnbqkbnr/ppp1pppp/3p4/3P4/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+            .set_from_fen("4k3/1p6/r7/4n3/3P4/8/8/Q3K3 w - - 0 1")
             .unwrap();
         let mut search = Search::new(board.clone(), 1, SearchParams::new());
 
-        let moves = board.generate_moves();
-        let capture_moves: Vec<Move> = moves
+        let root_moves = search.generate_root_moves();
+        let captures: Vec<String> = root_moves
             .iter()
-            .filter(|&&mv| move_captured(mv).is_some())
-            .copied()
+            .filter(|&&mv| mv.captured().is_some())
+            .map(|&mv| crate::board::move_to_uci(mv))
             .collect();
 
-        // Sort captures using our SEE-enhanced ordering (same logic as in search)
-        let mut sorted_moves = capture_moves.clone();
-        sorted_moves.sort_by(|&a, &b| {
-            let a_to = move_to_sq(a);
-            let b_to = move_to_sq(b);
-
-            let a_victim_value = if let Some((kind, _)) = board.piece_on(a_to) {
-                search.piece_value(&kind)
-            } else {
-                0
-            };
-
-            let b_victim_value = if let Some((kind, _)) = board.piece_on(b_to) {
-                search.piece_value(&kind)
-            } else {
-                0
-            };
-
-            let mvv_lva_cmp = b_victim_value.cmp(&a_victim_value);
-
-            if mvv_lva_cmp == std::cmp::Ordering::Equal {
-                let a_see = search.see(a_to, board.side);
-                let b_see = search.see(b_to, board.side);
-                b_see.cmp(&a_see)
-            } else {
-                mvv_lva_cmp
-            }
-        });
+        assert_eq!(captures, vec!["d4e5", "a1a6"]);
+        assert!(
+            search.stats().see_evals > 0,
+            "Should have performed SEE evaluations"
+        );
+    }
 
-        // Debug: Check what we found
-        println!("Total moves generated: {}", moves.len());
-        println!("Capture moves found: {}", capture_moves.len());
+    #[test]
+    fn test_capture_history_reorders_equal_captures() {
+        crate::init();
+        // Two winning pawn captures 10 cp apart: the bishop first, until
+        // taking the knight has produced enough cutoffs
+        let mut board = Board::new();
+        board
+            .set_from_fen("4k3/8/b1n5/1P6/8/8/8/4K3 w - - 0 1")
+            .unwrap();
+        let captures = |search: &mut Search| -> Vec<String> {
+            search
+                .generate_root_moves()
+                .iter()
+                .filter(|&&mv| mv.captured().is_some())
+                .map(|&mv| crate::board::move_to_uci(mv))
+                .collect()
+        };
+        let mut search = Search::new(board.clone(), 1, SearchParams::new());
+        assert_eq!(captures(&mut search), vec!["b5a6", "b5c6"]);
 
-        // Should have found some moves (not necessarily captures)
-        assert!(!moves.is_empty(), "Should find any moves");
+        let bxc6 = crate::board::parse_uci_move(&mut board, "b5c6").unwrap();
+        search.update_capture_history(bxc6, 10);
+        assert_eq!(
+            search.capture_key(bxc6, 320),
+            320 + 100 / CAPTURE_HISTORY_DIVISOR
+        );
+        assert_eq!(captures(&mut search), vec!["b5c6", "b5a6"]);
 
-        // Only require SEE evaluations if we found captures
-        if !capture_moves.is_empty() {
-            assert!(
-                search.stats().see_evals > 0,
-                "Should have performed SEE evaluations"
-            );
+        // Clamped like the other histories
+        for _ in 0..100 {
+            search.update_capture_history(bxc6, 10);
         }
-
-        println!(
-            "SEE capture ordering test passed - {} total moves, {} captures",
-            moves.len(),
-            capture_moves.len()
-        );
+        assert_eq!(search.capture_history[capture_index(bxc6)], HISTORY_MAX);
     }
 
     #[test]
@@ -2982,31 +3971,505 @@ mod tests {
     }
 
     #[test]
-    fn test_see_cache_functionality() {
-        // Test that SEE caching works correctly
+    fn test_see_xray_recapture() {
+        crate::init();
+        // Rook takes a rook defended by a rook; the second white rook behind
+        // (x-ray) wins the exchange back: R(500) - R(500) + R(500) = +500
         let mut board = Board::new();
+        board
+            .set_from_fen("3r2k1/3r4/8/8/8/8/3R4/3RK3 w - - 0 1")
+            .unwrap();
+        let mv = crate::board::parse_uci_move(&mut board, "d2d7").unwrap();
+        assert_eq!(board.see(mv), 500);
 
-        // Simple position for testing
-        board.set_from_fen("4r3/8/8/8/8/8/8/3R4 w - - 0 1").unwrap();
-        let mut search = Search::new(board.clone(), 1, SearchParams::new());
-
-        // First SEE calculation
-        let initial_evals = search.stats().see_evals;
-        let see1 = search.see(60, Color::White); // rook captures rook
-        let evals_after_first = search.stats().see_evals;
+        // Without the x-ray backup the same capture just trades rooks
+        board
+            .set_from_fen("3r2k1/3r4/8/8/8/8/3R4/4K3 w - - 0 1")
+            .unwrap();
+        let mv = crate::board::parse_uci_move(&mut board, "d2d7").unwrap();
+        assert_eq!(board.see(mv), 0);
+    }
 
-        // Second SEE calculation on same position should use cache
-        let see2 = search.see(60, Color::White);
-        let evals_after_second = search.stats().see_evals;
+    #[test]
+    fn test_root_shuffle_penalty() {
+        crate::init();
+        // Bianco in vantaggio in una posizione chiusa; il cavallo (che esce solo
+        // da b3, c2 è occupata) ha appena fatto a1-b3-a1 mentre il nero muoveva
+        // il re avanti e indietro. `last_score` è il punteggio della ricerca
+        // precedente di questa stessa posizione.
+        let setup = |penalty: i16, last_score: i16| {
+            let mut board = Board::new();
+            board
+                .set_from_fen("6k1/8/3p1p2/2pPpPp1/2P1P1P1/8/2P5/N6K w - - 0 1")
+                .unwrap();
+            for uci in ["a1b3", "g8h8", "b3a1", "h8g8"] {
+                let mv = crate::board::parse_uci_move(&mut board, uci).unwrap();
+                board.make_move(mv);
+            }
+            let mut scores = GameScores::default();
+            scores.record(board.zobrist, last_score);
+            let params = SearchParams::new()
+                .max_depth(5)
+                .time_limit(0)
+                .shuffle_penalty(penalty);
+            Search::new(board, 4, params).with_game_scores(scores)
+        };
 
-        // Results should be identical
-        assert_eq!(see1, see2, "Cached SEE should return same result");
+        // Senza penalità Nb3 è la mossa naturale, ma ripete una posizione già vista
+        let (mv, _) = setup(0, MATE_THRESHOLD).search(Some(5));
+        assert_eq!(crate::board::move_to_uci(mv), "a1b3");
 
-        // Second call should not have incremented count if cache worked
-        // (though our cache implementation might be cleared between calls)
-        println!(
-            "SEE cache test passed - SEE1: {}, SEE2: {}, Eval calls: {}->{}->{}",
-            see1, see2, initial_evals, evals_after_first, evals_after_second
+        let (mv, _) = setup(300, MATE_THRESHOLD).search(Some(5));
+        assert_ne!(
+            crate::board::move_to_uci(mv),
+            "a1b3",
+            "Shuffle penalty should steer the root away from repeating moves"
         );
+
+        // Il punteggio è migliorato dall'ultima volta: il giro fa progressi
+        let (mv, _) = setup(300, 0).search(Some(5));
+        assert_eq!(crate::board::move_to_uci(mv), "a1b3");
+    }
+
+    #[test]
+    fn test_tt_keeps_mate_distance_across_searches() {
+        // Mate in 2 (1. Rb7 Kg8 2. Ra8#): the mating move is at ply 2 (root
+        // children search at ply 0), so MATE - 2 at every depth that sees it,
+        // also when the second search is served by the warm TT
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("7k/8/8/8/8/8/R7/1R4K1 w - - 0 1")
+            .unwrap();
+        let params = SearchParams::new().max_depth(6).time_limit(0);
+        let mut search = Search::new(board, 16, params);
+        for depth in 3..=6 {
+            let (_, score) = search.search(Some(depth));
+            assert_eq!(score, MATE - 2, "depth {}", depth);
+        }
+        let (_, score) = search.search(Some(6));
+        assert_eq!(score, MATE - 2);
+    }
+
+    #[test]
+    fn test_eval_cache_does_not_change_search_result() {
+        // Cached evaluations are exact: a fixed-depth search finds the same
+        // move, score and tree size with or without the cache
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4")
+            .unwrap();
+        let run = |cache_mb: usize| {
+            let params = SearchParams::new()
+                .max_depth(5)
+                .time_limit(0)
+                .eval_cache_mb(cache_mb);
+            let mut search = Search::new(board.clone(), 4, params);
+            let (mv, score) = search.search(Some(5));
+            (mv, score, search.stats().nodes)
+        };
+        assert_eq!(run(0), run(1));
+    }
+
+    #[test]
+    fn test_eval_cache_is_handed_to_the_next_search() {
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4")
+            .unwrap();
+        let params = SearchParams::new()
+            .max_depth(4)
+            .time_limit(0)
+            .eval_cache_mb(1);
+        let mut first = Search::new(board.clone(), 4, params.clone());
+        let (mv, score) = first.search(Some(4));
+        let cache = first.take_eval_cache();
+        assert_eq!(cache.size(), 1024 * 1024 / 8);
+        let mut child = board.clone();
+        child.make_move(mv);
+        assert!(cache.probe_fast(child.zobrist).is_some());
+
+        // Same evaluation: the entries survive into the next search, whose
+        // result does not change
+        let mut second = Search::new(board.clone(), 4, params.clone()).with_eval_cache(cache);
+        assert_eq!(second.search(Some(4)), (mv, score));
+        assert!(second.take_eval_cache().probe_fast(child.zobrist).is_some());
+    }
+
+    #[test]
+    fn test_trace_records_last_root_search() {
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4")
+            .unwrap();
+        let legal = board.generate_moves().len();
+
+        let params = SearchParams::new().max_depth(4).time_limit(0);
+        let mut search = Search::new(board.clone(), 4, params.clone());
+        search.search(Some(4));
+        assert!(search.trace().is_none());
+
+        let mut search = Search::new(board.clone(), 4, params.trace(true));
+        search.search(Some(4));
+        let trace = search.trace().expect("tracing enabled");
+        // One top-level node per root move, entered at depth 3 with the
+        // root move as the move leading to it
+        let roots: Vec<usize> = trace.roots().collect();
+        assert_eq!(roots.len(), legal);
+        for &idx in &roots {
+            let node = &trace.nodes()[idx];
+            assert_eq!((node.ply, node.depth), (0, 3));
+            assert!(board.generate_moves().contains(&node.mv));
+        }
+        assert!(trace
+            .nodes()
+            .iter()
+            .any(|n| n.decision == TraceDecision::BetaCutoff));
+        assert!(trace
+            .nodes()
+            .iter()
+            .all(|n| n.decision != TraceDecision::Aborted));
+        let json = trace.roots_to_json(1);
+        assert!(json.starts_with("[{\"key\":"));
+
+        // The limit bounds the recording
+        let params = SearchParams::new()
+            .max_depth(4)
+            .time_limit(0)
+            .trace(true)
+            .trace_max_nodes(10);
+        let mut search = Search::new(board, 4, params);
+        search.search(Some(4));
+        let trace = search.trace().unwrap();
+        assert_eq!(trace.nodes().len(), 10);
+        assert!(trace.dropped() > 0);
+    }
+
+    #[test]
+    fn test_path_dependent_draw_overrules_tt_entry() {
+        // 50-move rule reached: the TT entry for this key (stored from
+        // another path) must not produce a cutoff
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 100 80")
+            .unwrap();
+        let params = SearchParams::new().max_depth(4).time_limit(0);
+        let mut search = Search::new(board, 1, params);
+        let key = search.board.zobrist;
+        search.tt.store(key, 500, 20, NodeType::Exact, Move::NONE);
+
+        assert_eq!(search.negamax_pv(3, -1, 0, 1), 0);
+        assert_eq!(search.negamax_pv(3, 400, 401, 1), 0);
+        assert_eq!(search.path_draws, 2);
+    }
+
+    #[test]
+    fn test_root_repetition_draw_is_flagged() {
+        // Qb3-b2 repeats the starting position for the third time: winning
+        // with the queen, the draw belongs to this game history only
+        crate::init();
+        let mut board = Board::new();
+        board.set_from_fen("7k/8/8/8/8/8/1Q6/K7 b - - 0 1").unwrap();
+        for uci in ["h8g8", "b2b3", "g8h8", "b3b2", "h8g8", "b2b3", "g8h8"] {
+            let mv = parse_uci_move(&mut board, uci).unwrap();
+            board.make_move(mv);
+        }
+        let repeat = parse_uci_move(&mut board, "b3b2").unwrap();
+
+        let params = SearchParams::new()
+            .max_depth(2)
+            .time_limit(0)
+            .search_moves(vec![repeat]);
+        let mut search = Search::new(board, 1, params);
+        let (mv, score) = search.iddfs(2, Move::NONE, -INFINITE, INFINITE);
+        assert_eq!((mv, score), (repeat, search.draw_score()));
+        assert!(search.root_list[0].path_draw);
+        assert!(search.root_score_depends_on_history(repeat));
+
+        // Qsearch counts the repetition it stops at
+        let (path_draws, game_draws) = (search.path_draws, search.game_draws);
+        search.board.push_move(repeat);
+        search.qsearch(-INFINITE, INFINITE, 4, 1);
+        search.board.pop_move();
+        assert_eq!(search.path_draws, path_draws + 1);
+        assert_eq!(search.game_draws, game_draws + 1);
+    }
+
+    #[test]
+    fn test_path_draw_root_is_not_stored_in_tt() {
+        // Kg8-h8 repeats the starting position for the third time: the lone
+        // king's best move is a draw that only this game history allows, so
+        // a normal search must not leave it in the TT for the root key
+        crate::init();
+        let mut board = Board::new();
+        board.set_from_fen("7k/8/8/8/8/8/2Q5/K7 w - - 0 1").unwrap();
+        for uci in ["c2d2", "h8g8", "d2c2", "g8h8", "c2d2", "h8g8", "d2c2"] {
+            let mv = parse_uci_move(&mut board, uci).unwrap();
+            board.make_move(mv);
+        }
+        let repeat = parse_uci_move(&mut board, "g8h8").unwrap();
+        let key = board.zobrist;
+
+        let params = SearchParams::new().max_depth(3).time_limit(0);
+        let mut search = Search::new(board, 1, params);
+        let (mv, score) = search.search(Some(3));
+        assert_eq!((mv, score), (repeat, search.draw_score()));
+        assert!(search.tt.probe(key).is_none());
+        assert!(search.path_draws > 0);
+    }
+
+    #[test]
+    fn test_shuffle_penalized_root_is_not_stored_in_tt() {
+        // Ogni mossa del bianco (re o cavallo) torna a una posizione già vista
+        // nella partita: con la penalità anche la mossa scelta ne è colpita, e
+        // quel punteggio dipende dalla storia, non dalla sola radice
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("6k1/8/3p1p2/2pPpPp1/2P1P1P1/2P5/8/N6K w - - 0 1")
+            .unwrap();
+        for (there, back) in [
+            ("h1g1", "g1h1"),
+            ("h1h2", "h2h1"),
+            ("h1g2", "g2h1"),
+            ("a1b3", "b3a1"),
+            ("a1c2", "c2a1"),
+        ] {
+            for uci in [there, "g8h8", back, "h8g8"] {
+                let mv = parse_uci_move(&mut board, uci).unwrap();
+                board.make_move(mv);
+            }
+        }
+        let key = board.zobrist;
+        let mut scores = GameScores::default();
+        scores.record(key, MATE_THRESHOLD);
+
+        let params = SearchParams::new()
+            .max_depth(5)
+            .time_limit(0)
+            .shuffle_penalty(300);
+        let mut search = Search::new(board, 4, params).with_game_scores(scores);
+        let (mv, _) = search.search(Some(5));
+        assert!(search.root_score_depends_on_history(mv));
+        // Le ricerche con finestra di aspirazione fallita lasciano al più un limite
+        let root_entry = search.tt.probe(key).map(|e| e.node_type);
+        assert_ne!(root_entry, Some(NodeType::Exact));
+    }
+
+    #[test]
+    fn test_move_variety_is_reproducible_and_within_margin() {
+        crate::init();
+        let run = |seed| {
+            let mut board = Board::new();
+            board.set_from_fen(crate::board::START_FEN).unwrap();
+            let params = SearchParams::new()
+                .max_depth(3)
+                .time_limit(0)
+                .move_variety(30, seed);
+            let mut search = Search::new(board, 1, params);
+            let (mv, score) = search.search(Some(3));
+            let best = search.root_moves().iter().map(|rm| rm.score).max().unwrap();
+            assert!(best - score <= 30);
+            assert_eq!(search.get_pv()[0], mv);
+            mv
+        };
+
+        let mut played = std::collections::HashSet::new();
+        for seed in 1..=8 {
+            let mv = run(seed);
+            assert_eq!(run(seed), mv);
+            played.insert(mv);
+        }
+        assert!(played.len() > 1);
+    }
+
+    #[test]
+    fn test_ordering_jitter_only_with_seed() {
+        crate::init();
+        let mut board = Board::new();
+        board.set_from_fen(crate::board::START_FEN).unwrap();
+        let moves = board.generate_moves();
+
+        let plain = Search::new(board.clone(), 1, SearchParams::new());
+        assert!(moves.iter().all(|&mv| plain.ordering_jitter(mv) == 0));
+
+        let helper = Search::new(board.clone(), 1, SearchParams::new().ordering_jitter(3));
+        let jitter: Vec<i32> = moves.iter().map(|&mv| helper.ordering_jitter(mv)).collect();
+        let max = ORDERING_JITTER_MAX as i32;
+        assert!(jitter.iter().all(|&j| (0..max).contains(&j)));
+        assert!(jitter.iter().any(|&j| j != jitter[0]));
+    }
+
+    #[test]
+    fn test_root_moves_keep_pv_bounds_and_previous_score() {
+        crate::init();
+        let mut board = Board::new();
+        board.set_from_fen(crate::board::START_FEN).unwrap();
+        let params = SearchParams::new().max_depth(4).time_limit(0);
+        let mut search = Search::new(board.clone(), 4, params);
+        let (mv, score) = search.search(Some(4));
+
+        let root_moves = search.root_moves();
+        assert_eq!(root_moves.len(), board.generate_moves().len());
+        let best = root_moves.iter().find(|rm| rm.mv == mv).unwrap();
+        assert!(best.is_exact());
+        assert_eq!(best.score, score);
+        assert_eq!(best.pv, search.get_pv());
+        assert!(best.prev_score > -INFINITE);
+        for rm in root_moves {
+            assert_eq!(rm.pv[0], rm.mv);
+            assert!(!(rm.lower_bound && rm.upper_bound));
+            assert!(rm.nodes > 0);
+        }
+    }
+
+    #[test]
+    fn test_pv_starts_with_best_move_and_is_legal() {
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 0 1")
+            .unwrap();
+        let params = SearchParams::new().max_depth(5).time_limit(0);
+        let mut search = Search::new(board.clone(), 4, params);
+        let (mv, _) = search.search(Some(5));
+
+        let pv = search.get_pv();
+        assert!(!pv.is_empty());
+        assert_eq!(pv[0], mv);
+        for &pv_move in &pv {
+            assert!(
+                board.generate_moves().contains(&pv_move),
+                "Illegal PV move {}",
+                crate::board::move_to_uci(pv_move)
+            );
+            board.make_move(pv_move);
+        }
+    }
+
+    #[test]
+    fn test_observer_receives_events() {
+        use std::sync::atomic::AtomicUsize;
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct Recorder {
+            depths: Mutex<Vec<u8>>,
+            tt_reports: AtomicUsize,
+            bestmove: Mutex<Option<Move>>,
+            stops: AtomicUsize,
+        }
+
+        impl SearchObserver for Recorder {
+            fn on_iteration(&self, info: &IterationInfo) {
+                assert!(!info.pv.is_empty());
+                assert!(info.nodes > 0);
+                self.depths.lock().unwrap().push(info.depth);
+            }
+            fn on_tt_stats(&self, stats: &TtStats) {
+                assert!(stats.hashfull <= 1000);
+                self.tt_reports.fetch_add(1, Ordering::Relaxed);
+            }
+            fn on_bestmove(&self, best_move: Move, _score: i16) {
+                *self.bestmove.lock().unwrap() = Some(best_move);
+            }
+            fn on_stop(&self, _stats: &SearchStats) {
+                self.stops.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+            .unwrap();
+        let recorder = Arc::new(Recorder::default());
+        let params = SearchParams::new().max_depth(3).time_limit(0);
+        let mut search = Search::new(board, 1, params).with_observer(recorder.clone());
+        let (mv, _) = search.search(Some(3));
+
+        assert_eq!(*recorder.depths.lock().unwrap(), vec![1, 2, 3]);
+        assert_eq!(recorder.tt_reports.load(Ordering::Relaxed), 3);
+        assert_eq!(*recorder.bestmove.lock().unwrap(), Some(mv));
+        assert_eq!(recorder.stops.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_stopped_search_returns_last_completed_iteration() {
+        use std::sync::Mutex;
+
+        /// Raises the stop flag once the second root move of `stop_depth`
+        /// starts, i.e. in the middle of that iteration
+        struct StopMidIteration {
+            stop: Arc<AtomicBool>,
+            stop_depth: u8,
+            completed: Mutex<Vec<(u8, Move)>>,
+        }
+
+        impl SearchObserver for StopMidIteration {
+            fn on_iteration(&self, info: &IterationInfo) {
+                let best = (info.depth, info.pv[0]);
+                self.completed.lock().unwrap().push(best);
+            }
+            fn on_currmove(&self, depth: u8, _mv: Move, number: u32, _elapsed: Duration) {
+                if depth == self.stop_depth && number == 2 {
+                    self.stop.store(true, Ordering::Relaxed);
+                }
+            }
+        }
+
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4")
+            .unwrap();
+        let stop = Arc::new(AtomicBool::new(false));
+        let observer = Arc::new(StopMidIteration {
+            stop: stop.clone(),
+            stop_depth: 6,
+            completed: Mutex::new(Vec::new()),
+        });
+        let params = SearchParams::new().max_depth(30).time_limit(0);
+        let mut search = Search::new(board.clone(), 16, params.clone())
+            .with_stop_flag(stop.clone())
+            .with_observer(observer.clone());
+        let (mv, _) = search.search(None);
+
+        let completed = observer.completed.lock().unwrap().clone();
+        assert_eq!(completed.last().map(|&(depth, _)| depth), Some(5));
+        assert_eq!(mv, completed[4].1);
+        assert_eq!(search.stats().completed_depth, 5);
+        assert_eq!(search.get_pv().first(), Some(&mv));
+
+        // Raised before the search: nothing is searched
+        let mut search = Search::new(board, 16, params).with_stop_flag(stop);
+        assert_eq!(search.search(None).0, Move::NONE);
+        assert_eq!(search.stats().nodes, 0);
+    }
+
+    #[test]
+    fn test_shared_tt_carries_results_between_searches() {
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4")
+            .unwrap();
+        let params = SearchParams::new().max_depth(5).time_limit(0);
+        let tt = Arc::new(TranspositionTable::new(16));
+        let run = |tt: &Arc<TranspositionTable>| {
+            let mut search =
+                Search::new(board.clone(), 0, params.clone()).with_shared_tt(tt.clone());
+            search.search(None);
+            search.stats().nodes
+        };
+
+        let cold = run(&tt);
+        assert!(tt.hashfull() > 0);
+        let warm = run(&tt);
+        assert!(warm < cold, "{} nodes warm, {} cold", warm, cold);
     }
 }
@@ -0,0 +1,95 @@
+//! Search stack
+//!
+//! One [`SearchStackEntry`] per ply holds what the search knows about the
+//! node at that ply: how it was reached, its static evaluation, the moves
+//! searched so far, its killer moves, the move excluded from it (singular
+//! extension verification) and the double extensions spent on the path.
+//! Pruning and extension decisions that look at the parent or grandparent
+//! (improving, countermoves, follow-up history) read their entries instead
+//! of keeping tables of their own.
+
+use crate::board::Move;
+
+/// Killer slots per ply (`SearchParams::killer_moves_count` is capped to it)
+pub const MAX_KILLERS: usize = 4;
+
+/// State of the node at one ply
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchStackEntry {
+    /// Move that led to this node (NONE at the root and after a null move)
+    pub current_move: Move,
+
+    /// Static evaluation of this node, once computed (None in check or
+    /// before anything needed it)
+    pub static_eval: Option<i16>,
+
+    /// Moves searched so far at this node
+    pub move_count: u32,
+
+    /// Quiet moves that caused a beta cutoff at this ply, most recent first;
+    /// they survive from one node to the next at the same ply
+    pub killers: [Move; MAX_KILLERS],
+
+    /// Move skipped by this node (NONE = search every move): a singular
+    /// extension verifies the TT move by searching the node without it
+    pub excluded_move: Move,
+
+    /// Double extensions spent on the path from the root to this node
+    pub double_extensions: u8,
+}
+
+impl SearchStackEntry {
+    pub const EMPTY: SearchStackEntry = SearchStackEntry {
+        current_move: Move::NONE,
+        static_eval: None,
+        move_count: 0,
+        killers: [Move::NONE; MAX_KILLERS],
+        excluded_move: Move::NONE,
+        double_extensions: 0,
+    };
+
+    /// Whether `mv` is one of the first `slots` killers of this ply
+    pub fn is_killer(&self, mv: Move, slots: usize) -> bool {
+        self.killers[..slots.min(MAX_KILLERS)].contains(&mv)
+    }
+
+    /// Remember `mv` as the most recent killer of this ply, keeping `slots`
+    /// killers (a move already stored is not duplicated)
+    pub fn store_killer(&mut self, mv: Move, slots: usize) {
+        let slots = slots.min(MAX_KILLERS);
+        if slots == 0 || self.killers[..slots].contains(&mv) {
+            return;
+        }
+        self.killers.copy_within(..slots - 1, 1);
+        self.killers[0] = mv;
+    }
+}
+
+impl Default for SearchStackEntry {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{new_move, PieceKind};
+
+    #[test]
+    fn killers_are_most_recent_first_without_duplicates() {
+        let moves: Vec<Move> = (0..3)
+            .map(|i| new_move(8 + i, 16 + i, PieceKind::Pawn, None, None, 0))
+            .collect();
+        let mut entry = SearchStackEntry::EMPTY;
+        entry.store_killer(moves[0], 2);
+        entry.store_killer(moves[1], 2);
+        entry.store_killer(moves[1], 2);
+        assert_eq!(entry.killers[..2], [moves[1], moves[0]]);
+        entry.store_killer(moves[2], 2);
+        assert_eq!(entry.killers[..2], [moves[2], moves[1]]);
+        assert!(entry.is_killer(moves[1], 2));
+        assert!(!entry.is_killer(moves[0], 2));
+        assert!(!entry.is_killer(moves[2], 0));
+    }
+}
@@ -3,7 +3,148 @@
 //! Tracks search performance including nodes searched, cutoffs,
 //! hash table hits, and timing information.
 
-use std::time::{Duration, Instant};
+use crate::time::Instant;
+use std::fmt;
+use std::time::Duration;
+
+/// Where the move ordering placed a move from, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveSource {
+    /// Best move stored in the transposition table
+    Tt,
+    /// Capture, ordered by SEE
+    Capture,
+    /// Killer move of the ply
+    Killer,
+    /// Refutation of the opponent's last move
+    Countermove,
+    /// Any other quiet move, ordered by history
+    History,
+}
+
+impl MoveSource {
+    pub const ALL: [MoveSource; 5] = [
+        MoveSource::Tt,
+        MoveSource::Capture,
+        MoveSource::Killer,
+        MoveSource::Countermove,
+        MoveSource::History,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MoveSource::Tt => "TT",
+            MoveSource::Capture => "capture",
+            MoveSource::Killer => "killer",
+            MoveSource::Countermove => "countermove",
+            MoveSource::History => "history",
+        }
+    }
+}
+
+/// Moves searched and beta cutoffs produced by one [`MoveSource`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SourceStats {
+    pub searched: u64,
+    pub cutoffs: u64,
+}
+
+impl SourceStats {
+    /// Share of the moves of this source that cut off (0.0 - 1.0)
+    pub fn cutoff_rate(&self) -> f64 {
+        if self.searched > 0 {
+            self.cutoffs as f64 / self.searched as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Move ordering telemetry of the main search (`SearchParams::ordering_telemetry`)
+///
+/// A well ordered node that cuts off does so with its first move, so the
+/// first-move cutoff rate and the average position of the cutoff move in the
+/// ordered list measure the ordering as a whole, and the cutoff rate of each
+/// source shows which part of it earns its place.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct OrderingStats {
+    /// Nodes that ended with a beta cutoff
+    pub cutoff_nodes: u64,
+    /// Cutoffs produced by the first move of the ordered list
+    pub first_move_cutoffs: u64,
+    /// Sum of the (1-based) positions of the cutoff moves in the ordered list
+    pub cutoff_move_number_sum: u64,
+    /// Per source, in [`MoveSource::ALL`] order
+    pub sources: [SourceStats; 5],
+}
+
+impl OrderingStats {
+    /// A move of `source` was searched
+    pub fn record_searched(&mut self, source: MoveSource) {
+        self.sources[source as usize].searched += 1;
+    }
+
+    /// The `move_number`-th move of the ordered list, of `source`, cut off
+    pub fn record_cutoff(&mut self, source: MoveSource, move_number: u32) {
+        self.cutoff_nodes += 1;
+        if move_number == 1 {
+            self.first_move_cutoffs += 1;
+        }
+        self.cutoff_move_number_sum += move_number as u64;
+        self.sources[source as usize].cutoffs += 1;
+    }
+
+    pub fn source(&self, source: MoveSource) -> SourceStats {
+        self.sources[source as usize]
+    }
+
+    /// Share of the cutoffs produced by the first move (0.0 - 1.0)
+    pub fn first_move_cutoff_rate(&self) -> f64 {
+        if self.cutoff_nodes > 0 {
+            self.first_move_cutoffs as f64 / self.cutoff_nodes as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// Average position of the cutoff move in the ordered list (1 = first)
+    pub fn average_cutoff_move_number(&self) -> f64 {
+        if self.cutoff_nodes > 0 {
+            self.cutoff_move_number_sum as f64 / self.cutoff_nodes as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+impl fmt::Display for OrderingStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "=== Move Ordering ===")?;
+        writeln!(
+            f,
+            "Cutoff nodes: {} (first move {:.1}%)",
+            self.cutoff_nodes,
+            self.first_move_cutoff_rate() * 100.0
+        )?;
+        writeln!(
+            f,
+            "Average cutoff move: {:.2}",
+            self.average_cutoff_move_number()
+        )?;
+        for source in MoveSource::ALL {
+            let stats = self.source(source);
+            writeln!(
+                f,
+                "{:<12} searched {:>10}  cutoffs {:>9} ({:.1}%)",
+                source.name(),
+                stats.searched,
+                stats.cutoffs,
+                stats.cutoff_rate() * 100.0
+            )?;
+        }
+        Ok(())
+    }
+}
 
 /// Search statistics
 #[derive(Debug, Default, Clone)]
@@ -67,6 +208,28 @@ pub struct SearchStats {
 
     /// Razoring pruned nodes
     pub razoring_pruned: u64,
+
+    /// PV nodes searched one ply shallower for lack of a TT move
+    pub iir_reductions: u64,
+
+    /// Quiet moves skipped by late move pruning
+    pub lmp_pruned: u64,
+
+    /// Root re-searches after an aspiration window failed (high plus low)
+    pub aspiration_researches: u64,
+
+    /// Aspiration searches that failed high (score at or above beta)
+    pub aspiration_fail_highs: u64,
+
+    /// Aspiration searches that failed low (score at or below alpha)
+    pub aspiration_fail_lows: u64,
+
+    /// Nodes spent re-searching the root after aspiration failures
+    pub aspiration_research_nodes: u64,
+
+    /// Move ordering telemetry, None unless enabled (it costs a
+    /// classification of every searched move)
+    pub ordering: Option<OrderingStats>,
 }
 
 impl SearchStats {
@@ -89,8 +252,8 @@ impl SearchStats {
         if let Some(start) = self.start_time {
             self.search_time = now.duration_since(start);
             let elapsed_ms = self.search_time.as_millis() as u64;
-            if elapsed_ms > 0 {
-                self.nps = (self.nodes * 1000) / elapsed_ms;
+            if let Some(nps) = (self.nodes * 1000).checked_div(elapsed_ms) {
+                self.nps = nps;
             }
         }
     }
@@ -155,9 +318,52 @@ impl SearchStats {
         self.razoring_pruned += 1;
     }
 
-    /// Reset all statistics
+    /// Increment internal iterative reduction count
+    pub fn inc_iir_reduction(&mut self) {
+        self.iir_reductions += 1;
+    }
+
+    /// Increment late move pruning count
+    pub fn inc_lmp_pruned(&mut self) {
+        self.lmp_pruned += 1;
+    }
+
+    /// Count an aspiration window that failed high (and its re-search)
+    pub fn inc_aspiration_fail_high(&mut self) {
+        self.aspiration_fail_highs += 1;
+        self.aspiration_researches += 1;
+    }
+
+    /// Count an aspiration window that failed low (and its re-search)
+    pub fn inc_aspiration_fail_low(&mut self) {
+        self.aspiration_fail_lows += 1;
+        self.aspiration_researches += 1;
+    }
+
+    /// Add the nodes of one aspiration re-search
+    pub fn add_aspiration_research_nodes(&mut self, nodes: u64) {
+        self.aspiration_research_nodes += nodes;
+    }
+
+    /// Share of all nodes spent on aspiration re-searches, in percent
+    pub fn aspiration_research_share(&self) -> f64 {
+        if self.nodes > 0 {
+            self.aspiration_research_nodes as f64 / self.nodes as f64 * 100.0
+        } else {
+            0.0
+        }
+    }
+
+    /// Start collecting move ordering telemetry
+    pub fn enable_ordering_telemetry(&mut self) {
+        self.ordering.get_or_insert_with(OrderingStats::default);
+    }
+
+    /// Reset all statistics (ordering telemetry stays enabled if it was)
     pub fn reset(&mut self) {
+        let ordering = self.ordering.as_ref().map(|_| OrderingStats::default());
         *self = Self::new();
+        self.ordering = ordering;
     }
 
     pub fn update_seldepth(&mut self, ply: u8) {
@@ -185,7 +391,18 @@ impl SearchStats {
         println!("Null-move cutoffs: {}", self.null_move_cutoffs);
         println!("LMR reductions: {}", self.lmr_reductions);
         println!("Futility pruned: {}", self.futility_pruned);
+        println!("IIR reductions: {}", self.iir_reductions);
+        println!("LMP pruned: {}", self.lmp_pruned);
         println!("SEE evaluations: {}", self.see_evals);
+        println!(
+            "Aspiration re-searches: {} (fail high {}, fail low {})",
+            self.aspiration_researches, self.aspiration_fail_highs, self.aspiration_fail_lows
+        );
+        println!(
+            "Aspiration re-search nodes: {} ({:.1}%)",
+            self.aspiration_research_nodes,
+            self.aspiration_research_share()
+        );
         println!("Search time: {} ms", self.search_time.as_millis());
         println!("Nodes per second: {}", self.nps);
 
@@ -195,5 +412,49 @@ impl SearchStats {
             0.0
         };
         println!("Average depth: {:.2}", avg_depth);
+
+        if let Some(ordering) = &self.ordering {
+            print!("{}", ordering);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ordering_telemetry_rates() {
+        let mut ordering = OrderingStats::default();
+        for _ in 0..3 {
+            ordering.record_searched(MoveSource::Tt);
+        }
+        ordering.record_searched(MoveSource::Killer);
+        ordering.record_cutoff(MoveSource::Tt, 1);
+        ordering.record_cutoff(MoveSource::Tt, 1);
+        ordering.record_cutoff(MoveSource::Killer, 4);
+
+        assert_eq!(ordering.cutoff_nodes, 3);
+        assert!((ordering.first_move_cutoff_rate() - 2.0 / 3.0).abs() < 1e-9);
+        assert!((ordering.average_cutoff_move_number() - 2.0).abs() < 1e-9);
+        assert!((ordering.source(MoveSource::Tt).cutoff_rate() - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(ordering.source(MoveSource::History), SourceStats::default());
+        assert!(ordering.to_string().contains("killer"));
+    }
+
+    #[test]
+    fn test_reset_keeps_ordering_telemetry_enabled() {
+        let mut stats = SearchStats::new();
+        stats.reset();
+        assert!(stats.ordering.is_none());
+
+        stats.enable_ordering_telemetry();
+        stats
+            .ordering
+            .as_mut()
+            .unwrap()
+            .record_cutoff(MoveSource::Capture, 2);
+        stats.reset();
+        assert_eq!(stats.ordering, Some(OrderingStats::default()));
     }
 }
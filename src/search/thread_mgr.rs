@@ -3,16 +3,30 @@
 //! True lazy-SMP implementation: all workers search the same position in parallel,
 //! sharing a global transposition table. Workers naturally explore different parts
 //! of the search tree due to timing differences in TT hits/misses.
+//!
+//! Job dispatch is event driven: idle workers block on a condition variable until
+//! a job (or shutdown) is posted, and the caller blocks on a second one until the
+//! workers report completion. Nothing sleeps or polls; the only flag checked in a
+//! loop is the job stop flag, which the search itself polls between nodes.
+//!
+//! With [`SmpMode::RootSplit`] in the job parameters the workers split the root
+//! moves of every iteration instead (see [`crate::search::root_split`]).
 
-use crate::board::Board;
-use crate::search::tt::TranspositionTable;
-use crate::search::{Search, SearchParams, SearchResult};
+use crate::board::{Board, Move};
+use crate::search::affinity;
+use crate::search::observer::{IterationInfo, ObserverSet, SearchObserver};
+use crate::search::root_split::{RootSplit, SmpMode};
+use crate::search::score::MATE_THRESHOLD;
+use crate::search::tt::{TTEntry, TranspositionTable};
+use crate::search::{EvalCache, GameScores, Search, SearchParams, SearchResult};
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::{
-    atomic::{AtomicBool, AtomicUsize, Ordering},
-    Arc, Mutex,
+    atomic::{AtomicBool, Ordering},
+    Arc, Condvar, Mutex, MutexGuard,
 };
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// Job to broadcast to all workers
 #[derive(Clone)]
@@ -21,224 +35,388 @@ pub struct SearchJob {
     pub params: SearchParams,
 }
 
+/// Dispatch state shared by the manager and its workers (guarded by one mutex)
+struct JobState {
+    /// Current job broadcasted to all workers (None = idle)
+    job: Option<SearchJob>,
+    /// Root moves shared by the workers of a root-split job
+    root_split: Option<Arc<RootSplit>>,
+    /// Incremented for every submitted job, so workers never re-run a finished
+    /// job and results of an abandoned job are never mixed into the next one
+    generation: usize,
+    /// Set once when the manager shuts down
+    shutdown: bool,
+    /// Results from each worker [worker_id] for the current generation
+    results: Vec<Option<SearchResult>>,
+    /// Workers whose search of the current generation panicked
+    failed: Vec<bool>,
+    /// Number of workers that have completed (or failed) the current generation
+    workers_done: usize,
+    /// Workers searching a job of any generation, including one the caller
+    /// gave up on after a timeout
+    workers_running: usize,
+    /// Panics not yet collected by `take_worker_panics`
+    panics: Vec<WorkerPanic>,
+}
+
+struct Shared {
+    state: Mutex<JobState>,
+    /// Notified when a job is posted or on shutdown (workers wait on it)
+    job_posted: Condvar,
+    /// Notified when a worker stores its result (the caller waits on it)
+    job_done: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, JobState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Observer slot shared with the workers; replaced between jobs
+type SharedObserver = Arc<Mutex<Option<Arc<dyn SearchObserver>>>>;
+
+/// Transposition table slot shared with the workers; replaced between jobs
+/// when the table is resized, each job searching the table current at its start
+type SharedTT = Arc<Mutex<Arc<TranspositionTable>>>;
+
+/// Last completed iteration of the main worker, tagged with its job generation
+type BestSoFar = Arc<Mutex<Option<(usize, SearchResult)>>>;
+
+/// Observer of the main worker that keeps [`BestSoFar`] up to date, so the
+/// best move is known at any time while the search runs
+struct BestSoFarRecorder {
+    slot: BestSoFar,
+    generation: usize,
+}
+
+impl SearchObserver for BestSoFarRecorder {
+    fn on_iteration(&self, info: &IterationInfo) {
+        let Some(&best_move) = info.pv.first() else {
+            return;
+        };
+        let elapsed_ms = info.elapsed.as_millis() as u64;
+        let result = SearchResult {
+            best_move,
+            score: info.score,
+            completed_depth: info.depth,
+            pv: info.pv.clone(),
+            nodes: info.nodes,
+            nps: (info.nodes * 1000).checked_div(elapsed_ms).unwrap_or(0),
+            seldepth: info.seldepth,
+            hashfull: info.hashfull,
+            root_moves: Vec::new(),
+        };
+        *self
+            .slot
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some((self.generation, result));
+    }
+}
+
+/// A worker whose search panicked, reported once by
+/// [`ThreadManager::take_worker_panics`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkerPanic {
+    pub worker_id: usize,
+    pub message: String,
+}
+
+/// What a worker thread shares with the manager
+#[derive(Clone)]
+struct WorkerContext {
+    shared: Arc<Shared>,
+    tt: SharedTT,
+    job_stop_flag: Arc<AtomicBool>,
+    observer: SharedObserver,
+    best_so_far: BestSoFar,
+    /// Whether workers should be pinned to their CPU (checked before each job)
+    affinity: Arc<AtomicBool>,
+    /// CPUs to pin workers to, in worker order (empty = not supported)
+    cpus: Arc<Vec<usize>>,
+}
+
 /// Thread manager implementing true lazy-SMP parallel search
-#[allow(clippy::type_complexity)]
 pub struct ThreadManager {
-    workers: Vec<thread::JoinHandle<()>>,
-    stop_flag: Arc<AtomicBool>,
-    /// Current job broadcasted to all workers (None = idle)
-    current_job: Arc<Mutex<Option<SearchJob>>>,
-    /// Signal that a new job is available
-    job_available: Arc<AtomicBool>,
+    /// Worker threads by id; a thread found dead is respawned on the next job
+    workers: Mutex<Vec<thread::JoinHandle<()>>>,
+    shared: Arc<Shared>,
     /// Stop flag for current search job
     job_stop_flag: Arc<AtomicBool>,
-    /// Results from each worker [worker_id]
-    results: Arc<Mutex<Vec<Option<SearchResult>>>>,
-    /// Counter for workers that have completed current job
-    workers_done: Arc<AtomicUsize>,
+    /// Transposition table shared by all workers
+    tt: SharedTT,
+    /// Telemetry observer, attached to the main worker's searches
+    observer: SharedObserver,
+    /// Main worker's last completed iteration of the current job
+    best_so_far: BestSoFar,
+    /// Pin workers to CPUs (UCI `ThreadAffinity`)
+    affinity: Arc<AtomicBool>,
+    /// CPUs available for pinning, grouped by NUMA node
+    cpus: Arc<Vec<usize>>,
 }
 
 impl ThreadManager {
     pub fn new(num_threads: usize, tt_mb: usize) -> Self {
-        let stop_flag = Arc::new(AtomicBool::new(false));
-        let tt = Arc::new(TranspositionTable::new(tt_mb));
-        let current_job = Arc::new(Mutex::new(None));
-        let job_available = Arc::new(AtomicBool::new(false));
-        let job_stop_flag = Arc::new(AtomicBool::new(false));
-        let results: Arc<Mutex<Vec<Option<SearchResult>>>> = Arc::new(Mutex::new(vec![None; num_threads]));
-        let workers_done = Arc::new(AtomicUsize::new(0));
-
-        let mut workers = Vec::new();
-        for worker_id in 0..num_threads {
-            let stop_clone = stop_flag.clone();
-            let tt_clone = tt.clone();
-            let job_clone = current_job.clone();
-            let job_avail_clone = job_available.clone();
-            let job_stop_clone = job_stop_flag.clone();
-            let results_clone = results.clone();
-            let workers_done_clone = workers_done.clone();
-
-            let handle = thread::spawn(move || {
-                loop {
-                    // Check global stop flag
-                    if stop_clone.load(Ordering::Relaxed) {
-                        break;
-                    }
-
-                    // Wait for job to become available
-                    if !job_avail_clone.load(Ordering::Acquire) {
-                        thread::sleep(Duration::from_millis(10));
-                        continue;
-                    }
-
-                    // Get current job (if any)
-                    let job = {
-                        let guard = job_clone
-                            .lock()
-                            .unwrap_or_else(|poisoned| poisoned.into_inner());
-                        guard.clone()
-                    };
-
-                    if let Some(SearchJob { board, params }) = job {
-                        let max_depth = params.max_depth;
-
-                        // Lazy-SMP diversity: helper threads search at slightly
-                        // different depths and with wider aspiration windows.
-                        let worker_depth = if worker_id == 0 {
-                            max_depth
-                        } else {
-                            max_depth.saturating_sub((worker_id as u8) % 3)
-                        };
-                        let mut worker_params = params.clone();
-                        worker_params.max_depth = worker_depth;
-                        if worker_id > 0 {
-                            worker_params.aspiration_window +=
-                                (worker_id as i16) * 10;
-                        }
-
-                        // Create search with shared TT and job stop flag
-                        let mut search = Search::new(board, 16, worker_params)
-                            .with_shared_tt(tt_clone.clone())
-                            .with_stop_flag(job_stop_clone.clone());
-
-                        // Execute search
-                        let (mv, score) = search.search(Some(worker_depth));
-
-                        {
-                            let mut results_guard = results_clone
-                                .lock()
-                                .unwrap_or_else(|poisoned| poisoned.into_inner());
-                            let stats = search.stats();
-                            let hashfull = (tt_clone.fill_percentage() * 1000.0) as u8;
-                            results_guard[worker_id] = Some(SearchResult {
-                                best_move: mv,
-                                score,
-                                completed_depth: stats.completed_depth,
-                                pv: search.get_pv(),
-                                nodes: stats.nodes,
-                                nps: stats.nps,
-                                seldepth: stats.seldepth,
-                                hashfull,
-                            });
-                        }
-
-                        // Signal completion
-                        workers_done_clone.fetch_add(1, Ordering::Release);
-
-                        // Wait for job to be cleared
-                        while job_avail_clone.load(Ordering::Acquire)
-                            && !stop_clone.load(Ordering::Relaxed)
-                        {
-                            thread::sleep(Duration::from_millis(10));
-                        }
-                    }
+        let tt = Arc::new(Mutex::new(Arc::new(TranspositionTable::new(tt_mb))));
+        let shared = Arc::new(Shared {
+            state: Mutex::new(JobState {
+                job: None,
+                root_split: None,
+                generation: 0,
+                shutdown: false,
+                results: vec![None; num_threads],
+                failed: vec![false; num_threads],
+                workers_done: 0,
+                workers_running: 0,
+                panics: Vec::new(),
+            }),
+            job_posted: Condvar::new(),
+            job_done: Condvar::new(),
+        });
+        let tm = ThreadManager {
+            workers: Mutex::new(Vec::new()),
+            shared,
+            job_stop_flag: Arc::new(AtomicBool::new(false)),
+            tt,
+            observer: Arc::new(Mutex::new(None)),
+            best_so_far: Arc::new(Mutex::new(None)),
+            affinity: Arc::new(AtomicBool::new(false)),
+            cpus: Arc::new(affinity::worker_cpus()),
+        };
+        let workers = (0..num_threads)
+            .map(|worker_id| spawn_worker(tm.context(), worker_id))
+            .collect();
+        *tm.lock_workers() = workers;
+        tm
+    }
+
+    fn context(&self) -> WorkerContext {
+        WorkerContext {
+            shared: self.shared.clone(),
+            tt: self.tt.clone(),
+            job_stop_flag: self.job_stop_flag.clone(),
+            observer: self.observer.clone(),
+            best_so_far: self.best_so_far.clone(),
+            affinity: self.affinity.clone(),
+            cpus: self.cpus.clone(),
+        }
+    }
+
+    fn lock_workers(&self) -> MutexGuard<'_, Vec<thread::JoinHandle<()>>> {
+        self.workers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Replace worker threads that died (a panic outside the search, which
+    /// catches its own) with fresh ones
+    fn respawn_dead_workers(&self) {
+        let mut workers = self.lock_workers();
+        for (worker_id, handle) in workers.iter_mut().enumerate() {
+            if handle.is_finished() {
+                let dead = std::mem::replace(handle, spawn_worker(self.context(), worker_id));
+                if let Err(payload) = dead.join() {
+                    self.shared.lock().panics.push(WorkerPanic {
+                        worker_id,
+                        message: panic_message(payload.as_ref()),
+                    });
                 }
-            });
-            workers.push(handle);
+            }
         }
+    }
+
+    /// Worker panics since the last call (the UCI loop reports them as
+    /// `info string`)
+    pub fn take_worker_panics(&self) -> Vec<WorkerPanic> {
+        std::mem::take(&mut self.shared.lock().panics)
+    }
+
+    /// Install (or remove) the telemetry observer used by subsequent jobs
+    pub fn set_observer(&self, observer: Option<Arc<dyn SearchObserver>>) {
+        *self
+            .observer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = observer;
+    }
+
+    /// Pin each worker to its own CPU (or release them) from the next job on.
+    /// Worker `i` gets the `i`-th CPU of [`affinity::worker_cpus`], wrapping
+    /// around when there are more workers than CPUs.
+    pub fn set_thread_affinity(&self, enabled: bool) {
+        self.affinity.store(enabled, Ordering::Relaxed);
+    }
+
+    /// CPUs workers are pinned to, in worker order (empty when the platform
+    /// does not support affinity)
+    pub fn affinity_cpus(&self) -> &[usize] {
+        &self.cpus
+    }
+
+    /// Result of the main worker's last completed iteration of the current
+    /// (or last) job, available while the search is still running
+    pub fn best_so_far(&self) -> Option<SearchResult> {
+        self.job_handle().best_so_far()
+    }
 
-        ThreadManager {
-            workers,
-            stop_flag,
-            current_job,
-            job_available,
-            job_stop_flag,
-            results,
-            workers_done,
+    /// Handle on the current job for another thread (see [`JobHandle`])
+    pub fn job_handle(&self) -> JobHandle {
+        JobHandle {
+            shared: self.shared.clone(),
+            job_stop_flag: self.job_stop_flag.clone(),
+            tt: self.tt.clone(),
+            best_so_far: self.best_so_far.clone(),
         }
     }
 
-    /// Submit a job and wait for result (synchronous from caller perspective)
-    pub fn submit_job(&self, job: SearchJob) -> SearchResult {
-        // Reset state for new job
-        self.workers_done.store(0, Ordering::Release);
-        self.job_stop_flag.store(false, Ordering::Release);
+    /// Probe the shared transposition table (e.g. for the root position before a search)
+    pub fn probe_tt(&self, key: u64) -> Option<TTEntry> {
+        current_tt(&self.tt).probe(key)
+    }
+
+    /// Number of entries of the shared transposition table
+    pub fn tt_size(&self) -> usize {
+        current_tt(&self.tt).size()
+    }
+
+    /// Reallocate the shared transposition table with about `tt_mb` megabytes
+    /// (UCI `Hash`), keeping the workers. A running job is stopped first; the
+    /// entries are lost, as with any resize.
+    pub fn resize_tt(&self, tt_mb: usize) {
+        self.abandon_job();
+        *self
+            .tt
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) =
+            Arc::new(TranspositionTable::new(tt_mb));
+    }
+
+    /// Empty the shared transposition table (UCI `Clear Hash`)
+    pub fn clear_tt(&self) {
+        current_tt(&self.tt).clear();
+    }
+
+    /// Number of worker threads
+    pub fn num_threads(&self) -> usize {
+        self.lock_workers().len()
+    }
+
+    /// Grow or shrink the worker pool to `num_threads` (at least one) without
+    /// touching the transposition table or the installed observer. A running
+    /// job is stopped first; surplus workers are joined, new ones spawned.
+    pub fn set_threads(&self, num_threads: usize) {
+        let num_threads = num_threads.max(1);
+        self.abandon_job();
+        // Workers before state, the order `respawn_dead_workers` uses
+        let mut workers = self.lock_workers();
+        if workers.len() == num_threads {
+            return;
+        }
         {
-            let mut results_guard = self
-                .results
-                .lock()
-                .unwrap_or_else(|poisoned| poisoned.into_inner());
-            for r in results_guard.iter_mut() {
-                *r = None;
+            let mut state = self.shared.lock();
+            self.job_stop_flag.store(true, Ordering::Release);
+            // A new generation: results of the interrupted job are dropped
+            state.generation += 1;
+            state.job = None;
+            state.root_split = None;
+            state.results = vec![None; num_threads];
+            state.failed = vec![false; num_threads];
+            state.workers_done = 0;
+            // Wake the surplus workers so they see they are no longer needed
+            self.shared.job_posted.notify_all();
+        }
+        let kept = num_threads.min(workers.len());
+        let retired = workers.split_off(kept);
+        for (worker_id, handle) in (num_threads..).zip(retired) {
+            if let Err(payload) = handle.join() {
+                self.shared.lock().panics.push(WorkerPanic {
+                    worker_id,
+                    message: panic_message(payload.as_ref()),
+                });
             }
         }
-
-        // Set current job (broadcast to all workers)
-        {
-            let mut job_guard = self
-                .current_job
-                .lock()
-                .unwrap_or_else(|poisoned| poisoned.into_inner());
-            *job_guard = Some(job);
+        for worker_id in workers.len()..num_threads {
+            workers.push(spawn_worker(self.context(), worker_id));
         }
+    }
 
-        // Signal job available (all workers will start searching)
-        self.job_available.store(true, Ordering::Release);
-
-        // Wait for at least one worker to complete (or timeout after 10 minutes)
-        let start = std::time::Instant::now();
-        let timeout = Duration::from_secs(600);
-
-        while self.workers_done.load(Ordering::Acquire) == 0 {
-            if start.elapsed() > timeout {
-                // Timeout - stop all workers and return draw score
-                // FIX Bug #2A: Return 0 (draw) instead of -30000 to avoid score corruption
-                // FIX Bug #3: Return depth 0 on timeout
-                self.job_stop_flag.store(true, Ordering::Release);
-                self.job_available.store(false, Ordering::Release);
-                return SearchResult {
-                    best_move: 0,
-                    score: 0,
-                    completed_depth: 0,
-                    pv: Vec::new(),
-                    nodes: 0,
-                    nps: 0,
-                    seldepth: 0,
-                    hashfull: 0,
-                };
-            }
-            thread::sleep(Duration::from_millis(10));
+    /// Stop the running job (if any) and wait for its workers to finish, so
+    /// the next job starts from a quiet pool. This includes the workers of a
+    /// job whose result was already taken after a timeout: they would keep
+    /// searching once the stop flag is reset.
+    fn abandon_job(&self) {
+        let state = self.shared.lock();
+        if state.job.is_none() && state.workers_running == 0 {
+            return;
         }
+        self.job_stop_flag.store(true, Ordering::Release);
+        let mut state = self.wait_done(state, Instant::now() + Duration::from_secs(10), |st| {
+            st.workers_running > 0
+        });
+        state.job = None;
+    }
 
-        // Collect results: use worker 0 result (main thread authority)
-        let best_result = {
-            let results_guard = self.results.lock().unwrap_or_else(|poisoned| {
-                poisoned.into_inner()
-            });
-            results_guard
-                .iter()
-                .filter_map(|r| r.clone())
-                .next()
-                .unwrap_or_else(|| SearchResult {
-                    best_move: 0,
-                    score: 0,
-                    completed_depth: 0,
-                    pv: Vec::new(),
-                    nodes: 0,
-                    nps: 0,
-                    seldepth: 0,
-                    hashfull: 0,
-                })
-        };
+    /// Stop what is left of the previous job, then reset per-job state and
+    /// wake every worker on `job`
+    fn post_job(&self, mut job: SearchJob) {
+        self.abandon_job();
+        self.respawn_dead_workers();
+        let mut state = self.shared.lock();
+        // Every worker throttles its own nodes: share the limit out
+        job.params.nps_limit = job.params.nps_limit.div_ceil(state.results.len() as u64);
+        self.job_stop_flag.store(false, Ordering::Release);
+        state.results.iter_mut().for_each(|r| *r = None);
+        state.failed.iter_mut().for_each(|f| *f = false);
+        state.workers_done = 0;
+        state.generation += 1;
+        // One TT age per move for all workers (they skip their own aging)
+        current_tt(&self.tt).new_search();
+        state.root_split = (job.params.smp_mode == SmpMode::RootSplit)
+            .then(|| Arc::new(RootSplit::new(state.results.len())));
+        state.job = Some(job);
+        self.shared.job_posted.notify_all();
+    }
 
-        // Clear job (stop workers)
-        self.job_available.store(false, Ordering::Release);
-        {
-            let mut job_guard = self
-                .current_job
-                .lock()
-                .unwrap_or_else(|poisoned| poisoned.into_inner());
-            *job_guard = None;
-        }
+    /// Block on `job_done` while `pending` holds, at most until `deadline`
+    fn wait_done<'a>(
+        &self,
+        state: MutexGuard<'a, JobState>,
+        deadline: Instant,
+        pending: impl FnMut(&mut JobState) -> bool,
+    ) -> MutexGuard<'a, JobState> {
+        wait_done(&self.shared, state, deadline, pending)
+    }
 
-        best_result
+    fn collect_job(&self, timeout: Duration) -> Option<SearchResult> {
+        self.job_handle().collect_job(timeout)
+    }
+
+    /// Submit a job and wait for result (synchronous from caller perspective)
+    pub fn submit_job(&self, job: SearchJob) -> SearchResult {
+        self.post_job(job);
+
+        // Wait for the main worker to complete (or timeout after 10 minutes).
+        // FIX Bug #2A: on timeout return 0 (draw) instead of -30000 to avoid score corruption
+        // FIX Bug #3: Return depth 0 on timeout
+        self.collect_job(Duration::from_secs(600))
+            .unwrap_or_else(empty_result)
     }
 
     /// Signal workers to stop and join
     pub fn stop(self) {
-        self.stop_flag.store(true, Ordering::Relaxed);
-        self.job_available.store(false, Ordering::Relaxed);
-        for w in self.workers {
+        self.job_stop_flag.store(true, Ordering::Relaxed);
+        {
+            let mut state = self.shared.lock();
+            state.shutdown = true;
+            state.job = None;
+            self.shared.job_posted.notify_all();
+        }
+        let workers = self
+            .workers
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        for w in workers {
             let _ = w.join();
         }
     }
@@ -256,30 +434,7 @@ impl ThreadManager {
     /// Start an async search (non-blocking). Used for "go infinite" mode.
     /// Starts all workers searching and returns immediately.
     pub fn start_async_search(&self, job: SearchJob) -> Arc<AtomicBool> {
-        // Reset state for new job
-        self.workers_done.store(0, Ordering::Release);
-        self.job_stop_flag.store(false, Ordering::Release);
-        {
-            let mut results_guard = self
-                .results
-                .lock()
-                .unwrap_or_else(|poisoned| poisoned.into_inner());
-            for r in results_guard.iter_mut() {
-                *r = None;
-            }
-        }
-
-        // Set current job (broadcast to all workers)
-        {
-            let mut job_guard = self
-                .current_job
-                .lock()
-                .unwrap_or_else(|poisoned| poisoned.into_inner());
-            *job_guard = Some(job);
-        }
-
-        // Signal job available (all workers will start searching)
-        self.job_available.store(true, Ordering::Release);
+        self.post_job(job);
 
         // Return job stop flag for caller to stop search when needed
         self.job_stop_flag.clone()
@@ -287,40 +442,348 @@ impl ThreadManager {
 
     /// Wait for async search result with timeout (blocking call).
     pub fn wait_async_result(&self, timeout_ms: u64) -> Option<SearchResult> {
-        // Wait for at least one worker to complete
-        let start = std::time::Instant::now();
-        let timeout = Duration::from_millis(timeout_ms);
+        self.collect_job(Duration::from_millis(timeout_ms))
+    }
+}
 
-        while self.workers_done.load(Ordering::Acquire) == 0 {
-            if start.elapsed() > timeout {
-                return None;
-            }
-            thread::sleep(Duration::from_millis(10));
-        }
+/// Handle on the jobs of a [`ThreadManager`] for another thread, e.g. a
+/// ponderhit timer that ends a search started with
+/// [`ThreadManager::start_async_search`] and reports its result
+#[derive(Clone)]
+pub struct JobHandle {
+    shared: Arc<Shared>,
+    job_stop_flag: Arc<AtomicBool>,
+    tt: SharedTT,
+    best_so_far: BestSoFar,
+}
+
+impl JobHandle {
+    /// Signal the running job (if any) to stop
+    pub fn stop_job(&self) {
+        self.job_stop_flag.store(true, Ordering::Relaxed);
+    }
+
+    /// Wait for the result of the job, like [`ThreadManager::wait_async_result`]
+    pub fn wait_result(&self, timeout_ms: u64) -> Option<SearchResult> {
+        self.collect_job(Duration::from_millis(timeout_ms))
+    }
+
+    /// Probe the shared transposition table
+    pub fn probe_tt(&self, key: u64) -> Option<TTEntry> {
+        current_tt(&self.tt).probe(key)
+    }
+
+    /// Main worker's last completed iteration of the current (or last) job
+    fn best_so_far(&self) -> Option<SearchResult> {
+        let generation = self.shared.lock().generation;
+        let slot = self
+            .best_so_far
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        slot.as_ref()
+            .filter(|(g, _)| *g == generation)
+            .map(|(_, result)| result.clone())
+    }
 
-        // Collect best result (use first available)
-        let best_result = {
-            let results_guard = self
-                .results
-                .lock()
+    /// Wait for the main worker, then stop the helpers and pick the best result.
+    ///
+    /// If the main worker panicked the helpers' results are used instead, and
+    /// if no worker has one, the first legal move. If the main worker does not
+    /// finish within `timeout` it is stopped and its last completed iteration
+    /// is returned (or the first legal move before one completed). Returns
+    /// `None` only without a job; the job is cleared either way.
+    fn collect_job(&self, timeout: Duration) -> Option<SearchResult> {
+        let deadline = Instant::now() + timeout;
+        let mut state = wait_done(&self.shared, self.shared.lock(), deadline, |st| {
+            st.results[0].is_none() && !st.failed[0]
+        });
+        let best_result = if state.results[0].is_some() || state.failed[0] {
+            // Stop the helpers and collect their last completed iteration too,
+            // so a faster mate found by a helper is not lost
+            self.job_stop_flag.store(true, Ordering::Release);
+            let num_workers = state.results.len();
+            state = wait_done(&self.shared, state, deadline, |st| {
+                st.workers_done < num_workers
+            });
+            state.job.as_ref().map(|job| {
+                let terminal = job.board.clone().generate_moves().is_empty();
+                select_best_result(&state.results, terminal)
+                    .unwrap_or_else(|| fallback_result(&job.board))
+            })
+        } else {
+            // Timed out: the search keeps its best move so far
+            self.job_stop_flag.store(true, Ordering::Release);
+            drop(state);
+            let best = self.best_so_far();
+            state = self.shared.lock();
+            best.or_else(|| state.job.as_ref().map(|job| fallback_result(&job.board)))
+        };
+
+        state.job = None;
+        best_result
+    }
+}
+
+/// Block on `job_done` of `shared` while `pending` holds, at most until `deadline`
+fn wait_done<'a>(
+    shared: &Shared,
+    state: MutexGuard<'a, JobState>,
+    deadline: Instant,
+    pending: impl FnMut(&mut JobState) -> bool,
+) -> MutexGuard<'a, JobState> {
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    shared
+        .job_done
+        .wait_timeout_while(state, remaining, pending)
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .0
+}
+
+/// Start the thread of worker `worker_id`
+fn spawn_worker(ctx: WorkerContext, worker_id: usize) -> thread::JoinHandle<()> {
+    thread::spawn(move || worker_loop(ctx, worker_id))
+}
+
+/// Search every job posted until shutdown. A panic in a search is caught:
+/// the worker reports it, is counted as done for that job and waits for the
+/// next one with fresh search state.
+fn worker_loop(ctx: WorkerContext, worker_id: usize) {
+    let mut last_generation = 0usize;
+    let mut pinned = false;
+    // Kept from one job to the next (lost with the search if it panics)
+    let mut eval_cache = EvalCache::new(0);
+    let mut game_scores = GameScores::default();
+    loop {
+        // Block until there is a job this worker has not searched yet
+        let (job, generation, split) = {
+            let mut state = ctx
+                .shared
+                .job_posted
+                .wait_while(ctx.shared.lock(), |st| {
+                    !st.shutdown
+                        && worker_id < st.results.len()
+                        && (st.job.is_none() || st.generation == last_generation)
+                })
                 .unwrap_or_else(|poisoned| poisoned.into_inner());
-            results_guard
-                .iter()
-                .filter_map(|r| r.clone())
-                .next()
+            // Shut down, or retired by `set_threads`
+            if state.shutdown || worker_id >= state.results.len() {
+                break;
+            }
+            let Some(job) = state.job.clone() else {
+                continue;
+            };
+            state.workers_running += 1;
+            (job, state.generation, state.root_split.clone())
         };
+        last_generation = generation;
 
-        // Clear job
-        self.job_available.store(false, Ordering::Release);
-        {
-            let mut job_guard = self.current_job.lock().unwrap();
-            *job_guard = None;
+        let pin = ctx.affinity.load(Ordering::Relaxed);
+        if pin != pinned && !ctx.cpus.is_empty() {
+            let cpus = if pin {
+                &ctx.cpus[worker_id % ctx.cpus.len()..][..1]
+            } else {
+                &ctx.cpus[..]
+            };
+            affinity::pin_current_thread(cpus);
+            pinned = pin;
         }
 
-        best_result
+        let outcome = panic::catch_unwind(AssertUnwindSafe(|| {
+            run_job(
+                &ctx,
+                worker_id,
+                job,
+                generation,
+                split.clone(),
+                &mut eval_cache,
+                &mut game_scores,
+            )
+        }));
+        // The other workers of a root split must not wait for moves this one
+        // will never finish
+        if let (Err(_), Some(split)) = (&outcome, &split) {
+            split.release(worker_id);
+        }
+
+        // Publish the result and signal completion, unless the caller
+        // gave up on this job and already posted another one
+        let mut state = ctx.shared.lock();
+        if let Err(payload) = &outcome {
+            state.panics.push(WorkerPanic {
+                worker_id,
+                message: panic_message(payload.as_ref()),
+            });
+        }
+        state.workers_running -= 1;
+        if state.generation == generation {
+            match outcome {
+                Ok(result) => state.results[worker_id] = Some(result),
+                Err(_) => state.failed[worker_id] = true,
+            }
+            state.workers_done += 1;
+        }
+        ctx.shared.job_done.notify_all();
+    }
+}
+
+/// Search one job (of `generation`) on worker `worker_id`, sharing the root
+/// moves through `split` in a root-split job
+fn run_job(
+    ctx: &WorkerContext,
+    worker_id: usize,
+    job: SearchJob,
+    generation: usize,
+    split: Option<Arc<RootSplit>>,
+    eval_cache: &mut EvalCache,
+    game_scores: &mut GameScores,
+) -> SearchResult {
+    let SearchJob { board, params } = job;
+    let worker_params = worker_params(&params, worker_id);
+    let worker_depth = worker_params.max_depth;
+
+    // Create search with shared TT and job stop flag (the
+    // minimal private table from `new` is replaced right away); the table
+    // was aged once for the whole job in `post_job`
+    let tt = current_tt(&ctx.tt);
+    let mut search = Search::new(board, 0, worker_params)
+        .with_shared_tt(tt.clone())
+        .without_tt_aging()
+        .with_stop_flag(ctx.job_stop_flag.clone())
+        .with_eval_cache(std::mem::replace(eval_cache, EvalCache::new(0)))
+        .with_game_scores(std::mem::take(game_scores));
+    if let Some(split) = split {
+        search = search.with_root_split(split, worker_id);
+    }
+    // Only the main worker reports telemetry, and records its best move so far
+    if worker_id == 0 {
+        let recorder: Arc<dyn SearchObserver> = Arc::new(BestSoFarRecorder {
+            slot: ctx.best_so_far.clone(),
+            generation,
+        });
+        let observer = ctx
+            .observer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone();
+        search = search.with_observer(match observer {
+            Some(observer) => Arc::new(ObserverSet(vec![recorder, observer])),
+            None => recorder,
+        });
+    }
+
+    // Execute search
+    let (mv, score) = search.search(Some(worker_depth));
+    *eval_cache = search.take_eval_cache();
+    *game_scores = search.take_game_scores();
+    let stats = search.stats();
+    SearchResult {
+        best_move: mv,
+        score,
+        completed_depth: stats.completed_depth,
+        pv: search.get_pv(),
+        nodes: stats.nodes,
+        nps: stats.nps,
+        seldepth: stats.seldepth,
+        hashfull: tt.hashfull(),
+        root_moves: search.root_moves().to_vec(),
+    }
+}
+
+/// The table currently in the shared slot
+fn current_tt(slot: &SharedTT) -> Arc<TranspositionTable> {
+    slot.lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+}
+
+/// Text of a panic payload (`panic!` with a literal or a formatted message)
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
     }
 }
 
+/// Lazy-SMP diversification: the main worker (0) runs the job as given, helpers
+/// get a depth offset, a wider aspiration window and their own move-ordering
+/// jitter, so that they explore different parts of the tree and fill the shared
+/// TT with entries the main worker would not have produced itself. Root-split
+/// workers search distinct moves already and must agree on the depth, so they
+/// all get the job as given.
+fn worker_params(params: &SearchParams, worker_id: usize) -> SearchParams {
+    let mut worker_params = params.clone();
+    if worker_id > 0 && params.smp_mode == SmpMode::LazySmp {
+        worker_params.max_depth = params.max_depth.saturating_sub((worker_id % 3) as u8);
+        worker_params.aspiration_window += (worker_id as i16) * 10;
+        worker_params.ordering_jitter = worker_id as u32;
+    }
+    worker_params
+}
+
+/// Last resort when every worker panicked: the first legal move, unscored
+fn fallback_result(board: &Board) -> SearchResult {
+    SearchResult {
+        best_move: board
+            .clone()
+            .generate_moves()
+            .first()
+            .copied()
+            .unwrap_or(Move::NONE),
+        ..empty_result()
+    }
+}
+
+/// Result returned when no worker produced one (timeout or terminal position)
+fn empty_result() -> SearchResult {
+    SearchResult {
+        best_move: Move::NONE,
+        score: 0,
+        completed_depth: 0,
+        pv: Vec::new(),
+        nodes: 0,
+        nps: 0,
+        seldepth: 0,
+        hashfull: 0,
+        root_moves: Vec::new(),
+    }
+}
+
+/// Choose the result to play from the per-worker results (indexed by worker id).
+///
+/// Mate scores are already stored as distance from the root (`MATE - plies`),
+/// so among workers that found a forced mate the highest score is the fastest
+/// mate, whatever depth each worker reached. Without a mate the deepest completed
+/// iteration wins, so a helper's shallow result never overrides the main worker;
+/// ties go to the lowest worker id (main thread authority).
+///
+/// A result without a move (a worker stopped before completing depth 1, or a
+/// root without legal moves) is only returned when the root is `terminal`:
+/// otherwise None, and the caller falls back to a legal move.
+fn select_best_result(results: &[Option<SearchResult>], terminal: bool) -> Option<SearchResult> {
+    let mut best: Option<&SearchResult> = None;
+    for r in results
+        .iter()
+        .flatten()
+        .filter(|r| r.best_move != Move::NONE)
+    {
+        let better = match best {
+            None => true,
+            // A mate beats any non-mate score, and a faster mate beats a slower one
+            Some(b) if b.score >= MATE_THRESHOLD || r.score >= MATE_THRESHOLD => r.score > b.score,
+            Some(b) => r.completed_depth > b.completed_depth,
+        };
+        if better {
+            best = Some(r);
+        }
+    }
+
+    best.or_else(|| results.iter().flatten().find(|_| terminal))
+        .cloned()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -338,4 +801,378 @@ mod tests {
         assert!(res.completed_depth >= 1);
         tm.stop();
     }
+
+    fn start_board() -> Board {
+        let mut board = Board::new();
+        board
+            .set_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+            .unwrap();
+        board
+    }
+
+    #[test]
+    fn consecutive_jobs_and_shutdown_during_search() {
+        crate::init();
+        let tm = ThreadManager::new(3, 16);
+        for depth in 1..=3 {
+            let res = tm.submit_job(SearchJob {
+                board: start_board(),
+                params: SearchParams::new().max_depth(depth),
+            });
+            assert_ne!(res.best_move, Move::NONE);
+            assert!(res.completed_depth >= 1 && res.completed_depth <= depth);
+        }
+
+        // An infinite search ends on stop() instead of keeping the join waiting
+        tm.start_async_search(SearchJob {
+            board: start_board(),
+            params: SearchParams::new().max_depth(99).time_limit(0),
+        });
+        let start = Instant::now();
+        tm.stop();
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn async_search_returns_result_after_stop() {
+        crate::init();
+        let tm = ThreadManager::new(2, 16);
+        tm.start_async_search(SearchJob {
+            board: start_board(),
+            params: SearchParams::new().max_depth(99).time_limit(0),
+        });
+        thread::sleep(Duration::from_millis(50));
+        tm.stop_current_job();
+        let res = tm
+            .wait_async_result(2000)
+            .expect("search should finish after stop");
+        assert_ne!(res.best_move, Move::NONE);
+        tm.stop();
+    }
+
+    #[test]
+    fn nps_limit_is_shared_between_workers() {
+        crate::init();
+        let tm = ThreadManager::new(3, 16);
+        tm.start_async_search(SearchJob {
+            board: start_board(),
+            params: SearchParams::new()
+                .max_depth(99)
+                .time_limit(0)
+                .nps_limit(1000),
+        });
+        let worker_nps = tm.shared.lock().job.as_ref().map(|j| j.params.nps_limit);
+        assert_eq!(worker_nps, Some(334));
+        tm.stop();
+    }
+
+    #[test]
+    fn best_so_far_survives_a_stop_timeout() {
+        crate::init();
+        let legal = start_board().generate_moves();
+        let tm = ThreadManager::new(1, 16);
+        assert!(tm.best_so_far().is_none());
+        tm.start_async_search(SearchJob {
+            board: start_board(),
+            params: SearchParams::new().max_depth(99).time_limit(0),
+        });
+
+        let start = Instant::now();
+        let best = loop {
+            if let Some(best) = tm.best_so_far() {
+                break best;
+            }
+            assert!(start.elapsed() < Duration::from_secs(5));
+            thread::sleep(Duration::from_millis(5));
+        };
+        assert!(legal.contains(&best.best_move));
+        assert!(best.completed_depth >= 1);
+
+        // The search is not done: a zero timeout still yields the best move so far
+        let res = tm.wait_async_result(0).expect("best move so far");
+        assert!(legal.contains(&res.best_move));
+        assert!(res.completed_depth >= best.completed_depth);
+
+        // The next job stops the abandoned search first instead of resetting
+        // its stop flag, which would leave the worker busy with it for good
+        let start = Instant::now();
+        let res = tm.submit_job(SearchJob {
+            board: start_board(),
+            params: SearchParams::new().max_depth(1),
+        });
+        assert!(legal.contains(&res.best_move));
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(tm.shared.lock().workers_running, 0);
+        tm.stop();
+    }
+
+    #[test]
+    fn helpers_get_diversified_params() {
+        let params = SearchParams::new().max_depth(10);
+        let main = worker_params(&params, 0);
+        assert_eq!(main.max_depth, 10);
+        assert_eq!(main.aspiration_window, params.aspiration_window);
+        assert_eq!(main.ordering_jitter, 0);
+
+        let helpers: Vec<SearchParams> = (1..4).map(|id| worker_params(&params, id)).collect();
+        assert_eq!(
+            helpers.iter().map(|p| p.max_depth).collect::<Vec<_>>(),
+            vec![9, 8, 10]
+        );
+        for (i, helper) in helpers.iter().enumerate() {
+            assert!(helper.aspiration_window > params.aspiration_window);
+            assert_eq!(helper.ordering_jitter, i as u32 + 1);
+        }
+    }
+
+    #[test]
+    fn root_split_workers_agree_on_every_iteration() {
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1")
+            .unwrap();
+        let legal = board.generate_moves();
+
+        let tm = ThreadManager::new(3, 16);
+        let res = tm.submit_job(SearchJob {
+            board,
+            params: SearchParams::new()
+                .max_depth(3)
+                .time_limit(0)
+                .smp_mode(SmpMode::RootSplit),
+        });
+        assert_eq!(crate::board::move_to_uci(res.best_move), "a1a8");
+        assert!(res.score >= MATE_THRESHOLD);
+        // The combined iteration holds every root move, each searched once
+        assert_eq!(res.root_moves.len(), legal.len());
+        assert!(legal
+            .iter()
+            .all(|mv| res.root_moves.iter().any(|rm| rm.mv == *mv)));
+        assert_eq!(res.pv.first(), Some(&res.best_move));
+
+        // The quiet start position runs every depth to the end as well
+        let res = tm.submit_job(SearchJob {
+            board: start_board(),
+            params: SearchParams::new()
+                .max_depth(4)
+                .time_limit(0)
+                .smp_mode(SmpMode::RootSplit),
+        });
+        assert_eq!(res.completed_depth, 4);
+        assert_eq!(res.root_moves.len(), 20);
+        tm.stop();
+    }
+
+    #[test]
+    fn root_split_helpers_are_not_diversified() {
+        let params = SearchParams::new()
+            .max_depth(10)
+            .smp_mode(SmpMode::RootSplit);
+        for id in 0..4 {
+            let worker = worker_params(&params, id);
+            assert_eq!(worker.max_depth, 10);
+            assert_eq!(worker.aspiration_window, params.aspiration_window);
+            assert_eq!(worker.ordering_jitter, 0);
+        }
+    }
+
+    fn fake_result(best_move: crate::board::Move, score: i16, depth: u8) -> SearchResult {
+        SearchResult {
+            best_move,
+            score,
+            completed_depth: depth,
+            pv: vec![best_move],
+            ..empty_result()
+        }
+    }
+
+    #[test]
+    fn aggregation_prefers_faster_mate() {
+        use crate::search::score::MATE;
+
+        // Main worker: mate in 5 (9 plies) at depth 10; helper: mate in 3 (5 plies) at depth 8
+        let m5 = fake_result(Move::from_raw(101), MATE - 9, 10);
+        let m3 = fake_result(Move::from_raw(202), MATE - 5, 8);
+        let results = vec![Some(m5.clone()), Some(m3.clone())];
+        let best = select_best_result(&results, false).unwrap();
+        assert_eq!(best.best_move, Move::from_raw(202));
+        assert_eq!(best.score, MATE - 5);
+
+        // Order of workers does not matter
+        let results = vec![Some(m3), None, Some(m5)];
+        assert_eq!(
+            select_best_result(&results, false).unwrap().best_move,
+            Move::from_raw(202)
+        );
+    }
+
+    #[test]
+    fn aggregation_ignores_stale_shallow_results() {
+        let main = fake_result(Move::from_raw(101), 20, 9);
+        let shallow = fake_result(Move::from_raw(202), 150, 7);
+        let deeper = fake_result(Move::from_raw(303), 35, 10);
+
+        let results = vec![Some(main.clone()), Some(shallow.clone())];
+        assert_eq!(
+            select_best_result(&results, false).unwrap().best_move,
+            Move::from_raw(101)
+        );
+
+        let results = vec![Some(main), Some(shallow), Some(deeper)];
+        assert_eq!(
+            select_best_result(&results, false).unwrap().best_move,
+            Move::from_raw(303)
+        );
+
+        // Terminal position: no worker has a move, keep the main worker's result
+        let results = vec![Some(fake_result(Move::NONE, -30000, 0)), None];
+        assert_eq!(select_best_result(&results, true).unwrap().score, -30000);
+        assert!(select_best_result(&[None, None], true).is_none());
+
+        // Workers stopped before depth 1 of a position with moves: no result,
+        // the caller plays a legal move instead
+        let results = vec![None, Some(fake_result(Move::NONE, 0, 0))];
+        assert!(select_best_result(&results, false).is_none());
+    }
+
+    #[test]
+    fn panicking_worker_is_reported_and_recovers() {
+        struct Crash;
+        impl SearchObserver for Crash {
+            fn on_iteration(&self, info: &crate::search::IterationInfo) {
+                if info.depth == 2 {
+                    panic!("crash at depth {}", info.depth);
+                }
+            }
+        }
+
+        crate::init();
+        let mut board = start_board();
+        let legal = board.generate_moves();
+
+        // Only the main worker reports telemetry: it dies, helpers answer
+        let tm = ThreadManager::new(2, 16);
+        tm.set_observer(Some(Arc::new(Crash)));
+        let job = SearchJob {
+            board: start_board(),
+            params: SearchParams::new().max_depth(4).time_limit(0),
+        };
+        let res = tm.submit_job(job.clone());
+        assert!(legal.contains(&res.best_move));
+        let panics = tm.take_worker_panics();
+        assert_eq!(panics.len(), 1);
+        assert_eq!(panics[0].worker_id, 0);
+        assert_eq!(panics[0].message, "crash at depth 2");
+        assert!(tm.take_worker_panics().is_empty());
+
+        // Single worker: no helper result, still a legal move
+        let single = ThreadManager::new(1, 16);
+        single.set_observer(Some(Arc::new(Crash)));
+        let res = single.submit_job(job.clone());
+        assert!(legal.contains(&res.best_move));
+
+        // The worker keeps serving jobs afterwards
+        single.set_observer(None);
+        let res = single.submit_job(job);
+        assert!(legal.contains(&res.best_move));
+        assert_eq!(res.completed_depth, 4);
+        assert_eq!(single.take_worker_panics().len(), 1);
+        single.stop();
+        tm.stop();
+    }
+
+    #[test]
+    fn observer_reports_bestmove() {
+        struct BestMoves(Mutex<Vec<crate::board::Move>>);
+        impl SearchObserver for BestMoves {
+            fn on_bestmove(&self, best_move: crate::board::Move, _score: i16) {
+                self.0.lock().unwrap().push(best_move);
+            }
+        }
+
+        crate::init();
+        let tm = ThreadManager::new(1, 16);
+        let observer = Arc::new(BestMoves(Mutex::new(Vec::new())));
+        tm.set_observer(Some(observer.clone()));
+        let mut board = Board::new();
+        board
+            .set_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1")
+            .unwrap();
+        let res = tm.submit_job(SearchJob {
+            board,
+            params: SearchParams::new().max_depth(2),
+        });
+        assert_eq!(*observer.0.lock().unwrap(), vec![res.best_move]);
+        tm.stop();
+    }
+
+    #[test]
+    fn reconfigure_keeps_table_and_observer() {
+        struct BestMoves(Mutex<usize>);
+        impl SearchObserver for BestMoves {
+            fn on_bestmove(&self, _best_move: crate::board::Move, _score: i16) {
+                *self.0.lock().unwrap() += 1;
+            }
+        }
+
+        crate::init();
+        let tm = ThreadManager::new(1, 1);
+        let observer = Arc::new(BestMoves(Mutex::new(0)));
+        tm.set_observer(Some(observer.clone()));
+        let job = SearchJob {
+            board: start_board(),
+            params: SearchParams::new().max_depth(3),
+        };
+        tm.submit_job(job.clone());
+        let key = start_board().zobrist;
+        assert!(tm.probe_tt(key).is_some());
+
+        // More and then fewer threads: the table survives, the workers serve jobs
+        tm.set_threads(3);
+        assert_eq!(tm.num_threads(), 3);
+        assert!(tm.probe_tt(key).is_some());
+        assert!(tm.submit_job(job.clone()).completed_depth >= 1);
+        tm.set_threads(1);
+        assert_eq!(tm.num_threads(), 1);
+        assert!(tm.probe_tt(key).is_some());
+        assert!(tm.submit_job(job.clone()).completed_depth >= 1);
+        assert_eq!(*observer.0.lock().unwrap(), 3);
+
+        tm.clear_tt();
+        assert!(tm.probe_tt(key).is_none());
+
+        let small = tm.tt_size();
+        tm.resize_tt(4);
+        assert!(tm.tt_size() > small);
+        assert!(tm.submit_job(job).completed_depth >= 1);
+        assert!(tm.probe_tt(key).is_some());
+        assert!(tm.take_worker_panics().is_empty());
+        tm.stop();
+    }
+
+    #[test]
+    fn test_tt_is_aged_once_per_job() {
+        crate::init();
+        let tm = ThreadManager::new(4, 1);
+        let job = |board: Board| SearchJob {
+            board,
+            params: SearchParams::new().max_depth(3).time_limit(0),
+        };
+        let start = start_board();
+        tm.submit_job(job(start.clone()));
+        assert_eq!(current_tt(&tm.tt).age(), 1);
+
+        // Later moves of the game still see the first one's entries, however
+        // many workers share the table
+        let mut board = start.clone();
+        for uci in ["e2e4", "e7e5", "g1f3"] {
+            let mv = crate::board::parse_uci_move(&mut board, uci).unwrap();
+            board.make_move(mv);
+            tm.submit_job(job(board.clone()));
+        }
+        assert_eq!(current_tt(&tm.tt).age(), 4);
+        assert!(tm.probe_tt(start.zobrist).is_some());
+        tm.stop();
+    }
 }
@@ -0,0 +1,268 @@
+//! Search tree tracer for offline analysis
+//!
+//! With [`crate::search::SearchParams::trace`] enabled, every main-search
+//! node of the last root search is recorded with its window, the move that
+//! led to it, the score it returned and why it returned (cutoff, pruning,
+//! draw, ...). Quiescence nodes are not recorded: a depth-0 node shows up as
+//! a single [`TraceDecision::Quiescence`] leaf. The recording is bounded by
+//! `SearchParams::trace_max_nodes`; nodes beyond it are counted but dropped.
+//!
+//! [`SearchTrace::to_json`] dumps a subtree (bounded in depth) as JSON, e.g.
+//! to inspect why a line was pruned:
+//!
+//! ```text
+//! {"key":"0x...","ply":1,"depth":3,"alpha":-30000,"beta":30000,
+//!  "move":"e7e5","score":-12,"decision":"searched","children":[...]}
+//! ```
+
+use crate::board::{move_to_uci, Move};
+
+/// Why a traced node returned its score
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceDecision {
+    /// All moves searched, no cutoff
+    Searched,
+    /// A move reached beta
+    BetaCutoff,
+    /// Transposition table bound outside the window
+    TtCutoff,
+    /// Insufficient material, 50-move rule or repetition
+    Draw,
+    /// Depth 0: the score comes from quiescence search
+    Quiescence,
+    /// Razoring at depth 1
+    Razored,
+    /// Static evaluation plus margin cannot reach beta
+    Futility,
+    /// Null-move search failed high
+    NullMove,
+    /// No legal move, side to move in check
+    Checkmate,
+    /// No legal move, side to move not in check
+    Stalemate,
+    /// Time ran out or a stop was requested below this node: the score is
+    /// meaningless
+    Aborted,
+}
+
+impl TraceDecision {
+    /// Name used in the JSON dump
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TraceDecision::Searched => "searched",
+            TraceDecision::BetaCutoff => "beta_cutoff",
+            TraceDecision::TtCutoff => "tt_cutoff",
+            TraceDecision::Draw => "draw",
+            TraceDecision::Quiescence => "qsearch",
+            TraceDecision::Razored => "razored",
+            TraceDecision::Futility => "futility",
+            TraceDecision::NullMove => "null_move",
+            TraceDecision::Checkmate => "checkmate",
+            TraceDecision::Stalemate => "stalemate",
+            TraceDecision::Aborted => "aborted",
+        }
+    }
+}
+
+/// One recorded search node
+#[derive(Debug, Clone)]
+pub struct TraceNode {
+    /// Zobrist key of the position
+    pub key: u64,
+    pub ply: u8,
+    /// Remaining depth when the node was entered
+    pub depth: u8,
+    /// Window the node was searched with
+    pub alpha: i16,
+    pub beta: i16,
+    /// Move that led to this node (0 after a null move)
+    pub mv: Move,
+    /// Score returned (meaningless while the node is still open)
+    pub score: i16,
+    pub decision: TraceDecision,
+    /// Index of the parent node (None for the root's children)
+    pub parent: Option<usize>,
+    /// Indices of the children, in search order
+    pub children: Vec<usize>,
+}
+
+/// Recorded tree of the last root search
+#[derive(Debug, Clone)]
+pub struct SearchTrace {
+    nodes: Vec<TraceNode>,
+    /// Open nodes from the root down (None = entered but not recorded)
+    stack: Vec<Option<usize>>,
+    max_nodes: usize,
+    dropped: u64,
+}
+
+impl SearchTrace {
+    /// Trace recording at most `max_nodes` nodes per root search
+    pub fn new(max_nodes: usize) -> Self {
+        Self {
+            nodes: Vec::new(),
+            stack: Vec::new(),
+            max_nodes,
+            dropped: 0,
+        }
+    }
+
+    /// Forget the recorded tree (start of a root search)
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+        self.stack.clear();
+        self.dropped = 0;
+    }
+
+    /// Open a node below the current one
+    pub fn enter(&mut self, key: u64, ply: u8, depth: u8, alpha: i16, beta: i16, mv: Move) {
+        if self.nodes.len() >= self.max_nodes {
+            self.dropped += 1;
+            self.stack.push(None);
+            return;
+        }
+        let idx = self.nodes.len();
+        let parent = self.stack.last().copied().flatten();
+        if let Some(parent) = parent {
+            self.nodes[parent].children.push(idx);
+        }
+        self.nodes.push(TraceNode {
+            key,
+            ply,
+            depth,
+            alpha,
+            beta,
+            mv,
+            score: 0,
+            decision: TraceDecision::Searched,
+            parent,
+            children: Vec::new(),
+        });
+        self.stack.push(Some(idx));
+    }
+
+    /// Record why the current node is returning
+    pub fn decide(&mut self, decision: TraceDecision) {
+        if let Some(Some(idx)) = self.stack.last() {
+            self.nodes[*idx].decision = decision;
+        }
+    }
+
+    /// Close the current node with its score
+    pub fn exit(&mut self, score: i16, aborted: bool) {
+        if let Some(Some(idx)) = self.stack.pop() {
+            let node = &mut self.nodes[idx];
+            node.score = score;
+            if aborted {
+                node.decision = TraceDecision::Aborted;
+            }
+        }
+    }
+
+    /// All recorded nodes, in the order they were entered
+    pub fn nodes(&self) -> &[TraceNode] {
+        &self.nodes
+    }
+
+    /// Nodes entered after the limit was reached
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Indices of the top-level nodes (one per root move searched)
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| n.parent.is_none())
+            .map(|(i, _)| i)
+    }
+
+    /// Index of the first recorded node of the position `key`
+    pub fn find(&self, key: u64) -> Option<usize> {
+        self.nodes.iter().position(|n| n.key == key)
+    }
+
+    /// JSON dump of the subtree at `idx`, `max_depth` levels deep (0 = the
+    /// node alone); cut-off levels report their size as `"truncated"`
+    pub fn to_json(&self, idx: usize, max_depth: usize) -> String {
+        let mut out = String::new();
+        self.write_json(&mut out, idx, max_depth);
+        out
+    }
+
+    /// JSON array with the subtree of every root move
+    pub fn roots_to_json(&self, max_depth: usize) -> String {
+        let roots: Vec<String> = self.roots().map(|i| self.to_json(i, max_depth)).collect();
+        format!("[{}]", roots.join(","))
+    }
+
+    fn write_json(&self, out: &mut String, idx: usize, max_depth: usize) {
+        let node = &self.nodes[idx];
+        let mv = if node.mv == Move::NONE {
+            "null".to_string()
+        } else {
+            format!("\"{}\"", move_to_uci(node.mv))
+        };
+        out.push_str(&format!(
+            "{{\"key\":\"{:#018x}\",\"ply\":{},\"depth\":{},\"alpha\":{},\"beta\":{},\
+             \"move\":{},\"score\":{},\"decision\":\"{}\"",
+            node.key,
+            node.ply,
+            node.depth,
+            node.alpha,
+            node.beta,
+            mv,
+            node.score,
+            node.decision.as_str()
+        ));
+        if max_depth == 0 {
+            if !node.children.is_empty() {
+                out.push_str(&format!(",\"truncated\":{}", node.children.len()));
+            }
+        } else {
+            out.push_str(",\"children\":[");
+            for (i, &child) in node.children.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                self.write_json(out, child, max_depth - 1);
+            }
+            out.push(']');
+        }
+        out.push('}');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_builds_tree_and_respects_limit() {
+        let mut trace = SearchTrace::new(3);
+        trace.enter(1, 0, 2, -100, 100, Move::NONE);
+        trace.enter(2, 1, 1, -100, 100, Move::NONE);
+        trace.decide(TraceDecision::TtCutoff);
+        trace.exit(40, false);
+        trace.enter(3, 1, 1, -100, 100, Move::NONE);
+        trace.enter(4, 2, 0, -100, 100, Move::NONE); // over the limit
+        trace.exit(7, false);
+        trace.exit(-5, true);
+        trace.exit(40, false);
+
+        assert_eq!(trace.nodes().len(), 3);
+        assert_eq!(trace.dropped(), 1);
+        assert_eq!(trace.roots().collect::<Vec<_>>(), vec![0]);
+        assert_eq!(trace.nodes()[0].children, vec![1, 2]);
+        assert_eq!(trace.nodes()[1].decision, TraceDecision::TtCutoff);
+        assert_eq!(trace.nodes()[2].decision, TraceDecision::Aborted);
+        assert_eq!(trace.find(3), Some(2));
+
+        let json = trace.to_json(0, 0);
+        assert!(json.ends_with("\"decision\":\"searched\",\"truncated\":2}"));
+        let json = trace.to_json(0, 1);
+        assert!(json.contains("\"decision\":\"tt_cutoff\"}"));
+        assert!(json.contains("\"key\":\"0x0000000000000003\""));
+    }
+}
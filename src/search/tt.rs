@@ -1,11 +1,22 @@
 //! Lock-free Transposition Table for Scacchista
 //!
-//! Each bucket holds a single entry composed of three `AtomicU64` fields:
-//! `key`, packed `data` (score/depth/age/node_type), and `best_move`.
-//! Writes are performed with `Ordering::Relaxed`; readers may observe
-//! slightly stale data, which is harmless for a lossy cache.
+//! Each bucket holds a single 16-byte entry made of two `AtomicU64` words:
+//! the packed `data` (score/depth/age/node_type/best_move) and `key ^ data`
+//! (Hyatt's lockless hashing). Writers store both words with `Ordering::Relaxed`
+//! and no lock; if two threads race on the same bucket a reader may see the
+//! key word of one write and the data word of another, but then
+//! `key_xor ^ data` no longer equals the probed key and the torn entry is
+//! treated as a miss instead of returning another position's score or move.
+//!
+//! The full key is kept (rather than a 16-bit partial key) because the XOR
+//! check needs it, and the move keeps its 29 bits because a `Move` carries
+//! the moving/captured pieces and flags that a 16-bit from/to/promo move
+//! would lose; both still fit in one 16-byte entry, four per cache line.
+//! [`TranspositionTable::prefetch`] starts loading a bucket as soon as the
+//! child key is known, so the line is usually cached by the time it is probed.
 
 use crate::board::Move;
+use crate::search::score::INFINITE;
 use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
 
 /// Node type for transposition table entries
@@ -34,7 +45,7 @@ impl TTEntry {
             score: 0,
             depth: 0,
             node_type: NodeType::Exact,
-            best_move: 0,
+            best_move: Move::NONE,
             age: 0,
         }
     }
@@ -65,8 +76,8 @@ impl TTEntry {
     pub fn bound(&self) -> (i16, i16) {
         match self.node_type {
             NodeType::Exact => (self.score, self.score),
-            NodeType::LowerBound => (self.score, i16::MAX),
-            NodeType::UpperBound => (i16::MIN, self.score),
+            NodeType::LowerBound => (self.score, INFINITE),
+            NodeType::UpperBound => (-INFINITE, self.score),
         }
     }
 }
@@ -77,18 +88,23 @@ impl TTEntry {
 //   bits 16-23 : depth
 //   bits 24-31 : age
 //   bits 32-33 : node_type (0=Exact, 1=LowerBound, 2=UpperBound)
+//   bits 34-62 : best_move (the 29 low bits of `Move`, flags included)
 // ---------------------------------------------------------------------------
 
+/// Bits of a `Move` kept in the entry (squares, pieces, promotion and flags)
+const MOVE_MASK: u64 = 0x1FFF_FFFF;
+
 #[inline]
-fn pack_data(score: i16, depth: u8, age: u8, node_type: NodeType) -> u64 {
+fn pack_data(score: i16, depth: u8, age: u8, node_type: NodeType, best_move: Move) -> u64 {
     ((score as u16) as u64)
         | ((depth as u64) << 16)
         | ((age as u64) << 24)
         | ((node_type as u64) << 32)
+        | (((best_move.raw() as u64) & MOVE_MASK) << 34)
 }
 
 #[inline]
-fn unpack_data(data: u64) -> (i16, u8, u8, NodeType) {
+fn unpack_data(data: u64) -> (i16, u8, u8, NodeType, Move) {
     let score = (data & 0xFFFF) as u16 as i16;
     let depth = ((data >> 16) & 0xFF) as u8;
     let age = ((data >> 24) & 0xFF) as u8;
@@ -98,24 +114,37 @@ fn unpack_data(data: u64) -> (i16, u8, u8, NodeType) {
         2 => NodeType::UpperBound,
         _ => NodeType::Exact,
     };
-    (score, depth, age, node_type)
+    let best_move = Move::from_raw(((data >> 34) & MOVE_MASK) as u32);
+    (score, depth, age, node_type, best_move)
 }
 
 #[repr(C)]
 struct AtomicTTEntry {
-    key: AtomicU64,
+    /// Zobrist key XOR `data`; an all-zero entry is empty
+    key_xor: AtomicU64,
     data: AtomicU64,
-    best_move: AtomicU64,
 }
 
 impl AtomicTTEntry {
     fn empty() -> Self {
         Self {
-            key: AtomicU64::new(0),
+            key_xor: AtomicU64::new(0),
             data: AtomicU64::new(0),
-            best_move: AtomicU64::new(0),
         }
     }
+
+    /// Load both words; returns `(key, data)`, with `key == 0` for an empty slot
+    #[inline]
+    fn load(&self) -> (u64, u64) {
+        let key_xor = self.key_xor.load(Ordering::Relaxed);
+        let data = self.data.load(Ordering::Relaxed);
+        (key_xor ^ data, data)
+    }
+
+    #[inline]
+    fn is_used(&self) -> bool {
+        self.key_xor.load(Ordering::Relaxed) | self.data.load(Ordering::Relaxed) != 0
+    }
 }
 
 /// Lock-free transposition table.
@@ -148,53 +177,96 @@ impl TranspositionTable {
     }
 
     /// Probe returns an entry if the stored key matches and the entry is recent enough.
+    ///
+    /// Same as [`Self::probe_atomic`]; kept as the name used throughout the search.
+    #[inline]
     pub fn probe(&self, key: u64) -> Option<TTEntry> {
+        self.probe_atomic(key)
+    }
+
+    /// Store an entry using replacement policy.
+    ///
+    /// Same as [`Self::store_atomic`]; kept as the name used throughout the search.
+    #[inline]
+    pub fn store(&self, key: u64, score: i16, depth: u8, node_type: NodeType, best_move: Move) {
+        self.store_atomic(key, score, depth, node_type, best_move)
+    }
+
+    /// Hint the CPU to start loading the bucket for `key` into cache.
+    ///
+    /// Called right after a move is made, before the child node gets to its
+    /// TT probe. A no-op on targets without a prefetch intrinsic.
+    #[inline]
+    pub fn prefetch(&self, key: u64) {
+        #[cfg(target_arch = "x86_64")]
+        self.prefetch_x86_64(key);
+        #[cfg(not(target_arch = "x86_64"))]
+        let _ = key;
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    #[inline]
+    #[allow(unsafe_code)]
+    fn prefetch_x86_64(&self, key: u64) {
+        use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+        let entry: *const AtomicTTEntry = &self.entries[(key as usize) & self.mask];
+        // SAFETY: SSE is part of the x86_64 baseline, and a prefetch never
+        // dereferences the pointer (it cannot fault); it points into
+        // `self.entries` anyway.
+        unsafe { _mm_prefetch::<_MM_HINT_T0>(entry as *const i8) };
+    }
+
+    /// Lock-free probe: an entry is returned only if `key_xor ^ data == key`, so
+    /// a bucket torn by a concurrent store is reported as a miss.
+    pub fn probe_atomic(&self, key: u64) -> Option<TTEntry> {
         let index = (key as usize) & self.mask;
-        let entry = &self.entries[index];
-        let k = entry.key.load(Ordering::Relaxed);
-        if k == key {
-            let table_age = self.age.load(Ordering::Relaxed);
-            let data = entry.data.load(Ordering::Relaxed);
-            let best_move = entry.best_move.load(Ordering::Relaxed) as Move;
-            let (score, depth, entry_age, node_type) = unpack_data(data);
-            if table_age.wrapping_sub(entry_age) < 8 {
-                return Some(TTEntry {
-                    key,
-                    score,
-                    depth,
-                    node_type,
-                    best_move,
-                    age: entry_age,
-                });
-            }
+        let (entry_key, data) = self.entries[index].load();
+        if entry_key != key {
+            return None;
+        }
+        let table_age = self.age.load(Ordering::Relaxed);
+        let (score, depth, entry_age, node_type, best_move) = unpack_data(data);
+        if table_age.wrapping_sub(entry_age) < 8 {
+            return Some(TTEntry {
+                key,
+                score,
+                depth,
+                node_type,
+                best_move,
+                age: entry_age,
+            });
         }
         None
     }
 
-    /// Store an entry using replacement policy.
-    pub fn store(&self, key: u64, score: i16, depth: u8, node_type: NodeType, best_move: Move) {
+    /// Lock-free store using the replacement policy (empty, stale, deeper or
+    /// exact-at-equal-depth entries are overwritten).
+    pub fn store_atomic(
+        &self,
+        key: u64,
+        score: i16,
+        depth: u8,
+        node_type: NodeType,
+        best_move: Move,
+    ) {
         let index = (key as usize) & self.mask;
         let entry = &self.entries[index];
         let current_age = self.age.load(Ordering::Relaxed);
 
-        let existing_key = entry.key.load(Ordering::Relaxed);
-
-        let replace = if existing_key == 0 {
+        let replace = if !entry.is_used() {
             true
         } else {
-            let existing_data = entry.data.load(Ordering::Relaxed);
-            let (_, existing_depth, existing_age, _) = unpack_data(existing_data);
-            existing_key == 0
-                || (current_age != existing_age && current_age.wrapping_sub(existing_age) >= 2)
+            let (_, existing_data) = entry.load();
+            let (_, existing_depth, existing_age, _, _) = unpack_data(existing_data);
+            (current_age != existing_age && current_age.wrapping_sub(existing_age) >= 2)
                 || (depth >= existing_depth && node_type == NodeType::Exact)
                 || depth > existing_depth
         };
 
         if replace {
-            let packed = pack_data(score, depth, current_age, node_type);
-            entry.data.store(packed, Ordering::Relaxed);
-            entry.best_move.store(best_move as u64, Ordering::Relaxed);
-            entry.key.store(key, Ordering::Relaxed);
+            let data = pack_data(score, depth, current_age, node_type, best_move);
+            entry.data.store(data, Ordering::Relaxed);
+            entry.key_xor.store(key ^ data, Ordering::Relaxed);
         }
     }
 
@@ -203,22 +275,35 @@ impl TranspositionTable {
         self.age.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Current search age (see [`Self::new_search`]).
+    pub fn age(&self) -> u8 {
+        self.age.load(Ordering::Relaxed)
+    }
+
     /// Approximate fill percentage.
     pub fn fill_percentage(&self) -> f64 {
-        let filled = self
-            .entries
+        let filled = self.entries.iter().filter(|e| e.is_used()).count();
+        (filled as f64 / self.entries.len() as f64) * 100.0
+    }
+
+    /// Occupancy in permille, sampled over the first 1000 entries (UCI `hashfull`).
+    pub fn hashfull(&self) -> u16 {
+        let sample = self.entries.len().min(1000);
+        if sample == 0 {
+            return 0;
+        }
+        let filled = self.entries[..sample]
             .iter()
-            .filter(|e| e.key.load(Ordering::Relaxed) != 0)
+            .filter(|e| e.is_used())
             .count();
-        (filled as f64 / self.entries.len() as f64) * 100.0
+        (filled * 1000 / sample) as u16
     }
 
     /// Clear all entries.
     pub fn clear(&self) {
         for entry in &self.entries {
-            entry.key.store(0, Ordering::Relaxed);
+            entry.key_xor.store(0, Ordering::Relaxed);
             entry.data.store(0, Ordering::Relaxed);
-            entry.best_move.store(0, Ordering::Relaxed);
         }
         self.age.store(0, Ordering::Relaxed);
     }
@@ -254,7 +339,7 @@ mod tests {
         tt.set_age(254);
 
         // Store an entry at age 254
-        tt.store(0x1234, 100, 5, NodeType::Exact, 0x1111);
+        tt.store(0x1234, 100, 5, NodeType::Exact, Move::from_raw(0x1111));
 
         // Verify entry was stored
         let entry = tt.probe(0x1234).expect("Entry should exist");
@@ -276,7 +361,7 @@ mod tests {
         let key1 = 0x00001_12345;
         let key2 = 0x00002_12345;
 
-        tt.store(key1, 100, 5, NodeType::Exact, 0x1111);
+        tt.store(key1, 100, 5, NodeType::Exact, Move::from_raw(0x1111));
 
         // key2 should be detected as collision
         let entry = tt.probe(key2);
@@ -301,7 +386,7 @@ mod tests {
             handles.push(thread::spawn(move || {
                 for j in 0..100 {
                     let key = (i * 100 + j) as u64;
-                    tt_clone.store(key, j as i16, 5, NodeType::Exact, 0);
+                    tt_clone.store(key, j as i16, 5, NodeType::Exact, Move::NONE);
                     let _ = tt_clone.probe(key);
                 }
             }));
@@ -314,4 +399,67 @@ mod tests {
         // If we get here without panic, the lock-free table is thread-safe
         assert!(tt.fill_percentage() > 0.0);
     }
+
+    #[test]
+    fn test_tt_entry_is_16_bytes_and_keeps_move_flags() {
+        assert_eq!(std::mem::size_of::<AtomicTTEntry>(), 16);
+
+        // Promotion capture e7xd8=Q with CAPTURE and PROMOTION flags (bit 28 set)
+        let mv = Move::from_raw(
+            52 | (59 << 6) | (1 << 12) | (3 << 16) | (4 << 20) | (1 << 27) | (1 << 28),
+        );
+        let tt = TranspositionTable::new(1);
+        tt.store(0xDEAD_BEEF_0000_0042, -321, 9, NodeType::LowerBound, mv);
+        let entry = tt.probe(0xDEAD_BEEF_0000_0042).expect("entry stored");
+        assert_eq!(entry.best_move, mv);
+        assert_eq!(entry.score, -321);
+        assert_eq!(entry.depth, 9);
+        assert_eq!(entry.node_type, NodeType::LowerBound);
+    }
+
+    #[test]
+    fn test_tt_prefetch_does_not_change_entries() {
+        let tt = TranspositionTable::new(1);
+        tt.store(0x4242, 77, 3, NodeType::UpperBound, Move::from_raw(0x2222));
+        tt.prefetch(0x4242);
+        tt.prefetch(u64::MAX);
+        let entry = tt.probe(0x4242).expect("entry stored");
+        assert_eq!(entry.score, 77);
+        assert_eq!(entry.best_move, Move::from_raw(0x2222));
+        assert!(tt.probe(u64::MAX).is_none());
+    }
+
+    #[test]
+    fn test_tt_concurrent_writes_never_return_torn_entries() {
+        // Every writer targets the same bucket with keys that differ only in the
+        // high bits; each entry's payload is derived from its key, so a torn read
+        // (key of one store, data of another) would be detectable.
+        let tt = Arc::new(TranspositionTable::new(1));
+        let payload = |key: u64| ((key >> 40) as i16, Move::from_raw((key >> 40) as u32));
+
+        let handles: Vec<_> = (0..4u64)
+            .map(|t| {
+                let tt = Arc::clone(&tt);
+                thread::spawn(move || {
+                    for i in 0..20_000u64 {
+                        let key = ((t * 20_000 + i + 1) << 40) | 0x77;
+                        let (score, mv) = payload(key);
+                        tt.store(key, score, (i % 64) as u8, NodeType::Exact, mv);
+                        for probe_t in 0..4u64 {
+                            let probe_key = ((probe_t * 20_000 + i + 1) << 40) | 0x77;
+                            if let Some(entry) = tt.probe(probe_key) {
+                                let (score, mv) = payload(probe_key);
+                                assert_eq!(entry.score, score);
+                                assert_eq!(entry.best_move, mv);
+                            }
+                        }
+                    }
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
 }
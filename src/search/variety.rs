@@ -0,0 +1,135 @@
+//! Move variety (UCI `MoveVariety`)
+//!
+//! With a margin of N centipawns the root moves whose exact score is within N
+//! of the best one all get a random bonus between 0 and N, and the highest
+//! adjusted score is played. The closer a move is to the best, the more
+//! likely it is picked: the margin acts as a temperature. The randomness comes
+//! from a seedable generator, so a given seed replays the same games.
+
+use crate::search::root::RootMove;
+use crate::search::score::MATE_THRESHOLD;
+
+/// Seedable pseudo-random generator (SplitMix64)
+#[derive(Debug, Clone)]
+pub struct VarietyRng {
+    state: u64,
+}
+
+impl VarietyRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Generator for a configured seed; 0 seeds it from the clock instead
+    pub fn seeded_or_clock(seed: u64) -> Self {
+        if seed != 0 {
+            return Self::new(seed);
+        }
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        Self::new(nanos)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `0..=max`
+    pub fn below_or_equal(&mut self, max: u16) -> u16 {
+        (self.next_u64() % (max as u64 + 1)) as u16
+    }
+}
+
+/// Root move to play among `root_moves` when the best one is `best` and the
+/// variety margin is `margin` centipawns, drawing the bonuses from `seed`.
+///
+/// Only exact scores within the margin compete, and a mate score is never
+/// traded for variety; without a candidate other than the best move, the
+/// best move is returned.
+pub fn pick<'a>(
+    root_moves: &'a [RootMove],
+    best: &'a RootMove,
+    margin: i16,
+    seed: u64,
+) -> &'a RootMove {
+    if margin <= 0 || best.score.abs() >= MATE_THRESHOLD {
+        return best;
+    }
+    let mut rng = VarietyRng::new(seed);
+    let mut choice = best;
+    let mut choice_score = i32::MIN;
+    // Root list order, so the draws do not depend on how the moves are sorted
+    for rm in root_moves {
+        let candidate = rm.mv == best.mv
+            || (rm.searched
+                && rm.is_exact()
+                && best.score as i32 - rm.score as i32 <= margin as i32);
+        if !candidate {
+            continue;
+        }
+        let adjusted = rm.score as i32 + rng.below_or_equal(margin as u16) as i32;
+        if adjusted > choice_score {
+            (choice, choice_score) = (rm, adjusted);
+        }
+    }
+    choice
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Move;
+
+    fn scored(n: u32, score: i16) -> RootMove {
+        RootMove {
+            score,
+            searched: true,
+            ..RootMove::new(Move::from_raw(n))
+        }
+    }
+
+    #[test]
+    fn test_rng_is_reproducible() {
+        let draws = |seed| {
+            let mut rng = VarietyRng::new(seed);
+            (0..8).map(|_| rng.next_u64()).collect::<Vec<_>>()
+        };
+        assert_eq!(draws(42), draws(42));
+        assert_ne!(draws(42), draws(43));
+    }
+
+    #[test]
+    fn test_pick_stays_within_the_margin() {
+        let moves = [scored(1, 50), scored(2, 40), scored(3, 10), scored(4, 45)];
+        let mut picked = [false; 5];
+        for seed in 0..200 {
+            let rm = pick(&moves, &moves[0], 20, seed);
+            picked[rm.mv.raw() as usize] = true;
+            assert_eq!(rm, pick(&moves, &moves[0], 20, seed));
+        }
+        // 40 cp worse is never played; the close alternatives sometimes are
+        assert!(picked[1] && picked[2] && picked[4]);
+        assert!(!picked[3]);
+
+        // Off, or a forced mate: always the best move
+        assert_eq!(pick(&moves, &moves[0], 0, 7).mv, moves[0].mv);
+        let mate = [scored(1, MATE_THRESHOLD + 5), scored(2, MATE_THRESHOLD + 1)];
+        assert_eq!(pick(&mate, &mate[0], 50, 7).mv, mate[0].mv);
+    }
+
+    #[test]
+    fn test_pick_ignores_bounds() {
+        let mut bound = scored(2, 48);
+        bound.upper_bound = true;
+        let moves = [scored(1, 50), bound];
+        for seed in 0..50 {
+            assert_eq!(pick(&moves, &moves[0], 20, seed).mv, moves[0].mv);
+        }
+    }
+}
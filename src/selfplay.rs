@@ -0,0 +1,515 @@
+//! Match infrastructure for self-play and engine-vs-engine testing
+//!
+//! Opening suites keep the games of a match apart: each opening is played
+//! twice with the colors swapped, so neither engine profits from a lopsided
+//! start. Suites are EPD files (one position per line, opcodes ignored) or
+//! PGN files (the movetext of each game, from its `FEN` tag if any), as
+//! distributed for 2-move or 8-move books.
+//!
+//! Adjudication ends games whose result is clear from the engines' own
+//! scores, like the `-resign` and `-draw` rules of testing frameworks.
+
+use crate::board::{
+    move_to_uci, parse_uci_move, Board, Color, Move, PieceKind, FLAG_CASTLE_KING,
+    FLAG_CASTLE_QUEEN, START_FEN,
+};
+use std::path::Path;
+
+/// Starting point of a pair of games
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Opening {
+    /// Initial position (None = standard starting position)
+    pub fen: Option<String>,
+
+    /// Moves played from it, in UCI notation
+    pub moves: Vec<String>,
+
+    /// Side to move once the opening moves are played
+    pub side_to_move: Color,
+}
+
+impl Opening {
+    /// Opening from `fen` (None = starting position) followed by `moves` in
+    /// SAN or UCI notation; fails on an invalid position or move
+    pub fn new(fen: Option<&str>, moves: &[&str]) -> Result<Self, String> {
+        let mut board = Board::new();
+        board
+            .set_from_fen(fen.unwrap_or(START_FEN))
+            .and_then(|_| board.validate_kings(false))
+            .map_err(|e| format!("invalid FEN: {}", e))?;
+        let mut uci_moves = Vec::with_capacity(moves.len());
+        for &text in moves {
+            let mv = parse_uci_move(&mut board, text)
+                .or_else(|_| parse_san_move(&mut board, text))
+                .map_err(|e| format!("move {}: {}", text, e))?;
+            uci_moves.push(move_to_uci(mv));
+            board.make_move(mv);
+        }
+        Ok(Self {
+            fen: fen.map(str::to_string),
+            moves: uci_moves,
+            side_to_move: board.side,
+        })
+    }
+
+    /// UCI `position` command for the game after the opening and `game_moves`
+    pub fn position_command(&self, game_moves: &[String]) -> String {
+        let mut cmd = match &self.fen {
+            Some(fen) => format!("position fen {}", fen),
+            None => "position startpos".to_string(),
+        };
+        let mut moves = self.moves.iter().chain(game_moves).peekable();
+        if moves.peek().is_some() {
+            cmd.push_str(" moves");
+            for mv in moves {
+                cmd.push(' ');
+                cmd.push_str(mv);
+            }
+        }
+        cmd
+    }
+}
+
+/// Load an opening suite: PGN for a `.pgn` file, EPD otherwise
+pub fn load_openings(path: &Path) -> Result<Vec<Opening>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let is_pgn = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pgn"));
+    let openings = if is_pgn {
+        parse_pgn(&text)
+    } else {
+        parse_epd(&text)
+    }
+    .map_err(|e| format!("{}: {}", path.display(), e))?;
+    if openings.is_empty() {
+        return Err(format!("{}: no openings", path.display()));
+    }
+    Ok(openings)
+}
+
+/// Openings of an EPD suite: one position per line (4-field EPD or full FEN),
+/// opcodes ignored; blank lines and `#` comments are skipped
+pub fn parse_epd(text: &str) -> Result<Vec<Opening>, String> {
+    let mut openings = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line
+            .split(';')
+            .next()
+            .unwrap_or("")
+            .split_whitespace()
+            .collect();
+        if fields.len() < 4 {
+            return Err(format!("line {}: not an EPD position", n + 1));
+        }
+        let is_counter = |i: usize| fields.get(i).is_some_and(|t| t.parse::<u32>().is_ok());
+        let fen = if is_counter(4) && is_counter(5) {
+            fields[..6].join(" ")
+        } else {
+            format!("{} 0 1", fields[..4].join(" "))
+        };
+        openings.push(Opening::new(Some(&fen), &[]).map_err(|e| format!("line {}: {}", n + 1, e))?);
+    }
+    Ok(openings)
+}
+
+/// Openings of a PGN suite: the movetext of every game (comments, variations,
+/// move numbers, NAGs and results skipped), starting from its `FEN` tag if any
+pub fn parse_pgn(text: &str) -> Result<Vec<Opening>, String> {
+    let mut openings = Vec::new();
+    let mut fen: Option<String> = None;
+    let mut moves: Vec<String> = Vec::new();
+    let mut in_movetext = false;
+    let mut game = 1;
+
+    let mut finish = |fen: &mut Option<String>, moves: &mut Vec<String>, game: &mut usize| {
+        let list: Vec<&str> = moves.iter().map(String::as_str).collect();
+        let opening =
+            Opening::new(fen.as_deref(), &list).map_err(|e| format!("game {}: {}", game, e))?;
+        openings.push(opening);
+        *fen = None;
+        moves.clear();
+        *game += 1;
+        Ok::<(), String>(())
+    };
+
+    for line in strip_pgn_comments(text).lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            if in_movetext {
+                // A new game without a result token after the previous one
+                finish(&mut fen, &mut moves, &mut game)?;
+                in_movetext = false;
+            }
+            if let Some(value) = line.strip_prefix("[FEN ") {
+                fen = Some(
+                    value
+                        .trim_end_matches(']')
+                        .trim()
+                        .trim_matches('"')
+                        .to_string(),
+                );
+            }
+            continue;
+        }
+        for token in line.split_whitespace() {
+            in_movetext = true;
+            if matches!(token, "1-0" | "0-1" | "1/2-1/2" | "*") {
+                finish(&mut fen, &mut moves, &mut game)?;
+                in_movetext = false;
+                continue;
+            }
+            // "12." / "12..." / "12.e4": drop the move number
+            let token = if token.contains('.') {
+                token.trim_start_matches(|c: char| c.is_ascii_digit() || c == '.')
+            } else {
+                token
+            };
+            if token.is_empty() || token.starts_with('$') {
+                continue;
+            }
+            moves.push(token.to_string());
+        }
+    }
+    if in_movetext || !moves.is_empty() {
+        finish(&mut fen, &mut moves, &mut game)?;
+    }
+    Ok(openings)
+}
+
+/// PGN text without `{...}` and `;` comments and `(...)` variations
+fn strip_pgn_comments(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let (mut brace, mut paren, mut line_comment) = (false, 0usize, false);
+    for c in text.chars() {
+        match c {
+            '\n' => {
+                line_comment = false;
+                out.push(c);
+            }
+            _ if line_comment => {}
+            '{' if !brace => brace = true,
+            '}' if brace => brace = false,
+            _ if brace => {}
+            ';' => line_comment = true,
+            '(' => paren += 1,
+            ')' if paren > 0 => paren -= 1,
+            _ if paren > 0 => {}
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Parse a move in Standard Algebraic Notation (`e4`, `Nbd7`, `exd8=Q+`,
+/// `O-O-O`) among the legal moves of `board`
+pub fn parse_san_move(board: &mut Board, san: &str) -> Result<Move, &'static str> {
+    let san = san.trim_end_matches(['+', '#', '!', '?']);
+    let legal = board.generate_moves();
+
+    let castle = match san {
+        "O-O" | "0-0" => Some(FLAG_CASTLE_KING),
+        "O-O-O" | "0-0-0" => Some(FLAG_CASTLE_QUEEN),
+        _ => None,
+    };
+    if let Some(flag) = castle {
+        return legal
+            .into_iter()
+            .find(|mv| mv.has_flag(flag))
+            .ok_or("castling is not legal");
+    }
+
+    // Promotion: "e8=Q", also written "e8Q"
+    let (body, promotion) = if let Some((body, promo)) = san.split_once('=') {
+        (body, promo.chars().next())
+    } else if san.starts_with(|c: char| c.is_ascii_lowercase())
+        && san.ends_with(['Q', 'R', 'B', 'N'])
+    {
+        (&san[..san.len() - 1], san.chars().last())
+    } else {
+        (san, None)
+    };
+    let promotion = match promotion {
+        None => None,
+        Some(c) => Some(
+            piece_from_letter(c)
+                .filter(|&p| p != PieceKind::King)
+                .ok_or("invalid promotion piece")?,
+        ),
+    };
+
+    let piece = body
+        .chars()
+        .next()
+        .and_then(piece_from_letter)
+        .unwrap_or(PieceKind::Pawn);
+    let body = if piece == PieceKind::Pawn {
+        body
+    } else {
+        &body[1..]
+    };
+    let body: String = body.chars().filter(|&c| c != 'x' && c != '-').collect();
+    if body.len() < 2 || !body.is_ascii() {
+        return Err("invalid SAN move");
+    }
+    let (hint, dest) = body.split_at(body.len() - 2);
+    let to = square_from_name(dest).ok_or("invalid destination square")?;
+    let mut from_file = None;
+    let mut from_rank = None;
+    for c in hint.chars() {
+        match c {
+            'a'..='h' => from_file = Some(c as usize - 'a' as usize),
+            '1'..='8' => from_rank = Some(c as usize - '1' as usize),
+            _ => return Err("invalid SAN move"),
+        }
+    }
+
+    let mut matches = legal.into_iter().filter(|mv| {
+        mv.piece() == piece
+            && mv.to() == to
+            && mv.promotion() == promotion
+            && !mv.has_flag(FLAG_CASTLE_KING | FLAG_CASTLE_QUEEN)
+            && from_file.map_or(true, |f| mv.from() % 8 == f)
+            && from_rank.map_or(true, |r| mv.from() / 8 == r)
+    });
+    match (matches.next(), matches.next()) {
+        (Some(mv), None) => Ok(mv),
+        (Some(_), Some(_)) => Err("ambiguous SAN move"),
+        (None, _) => Err("move not found in legal moves"),
+    }
+}
+
+fn piece_from_letter(c: char) -> Option<PieceKind> {
+    match c {
+        'N' => Some(PieceKind::Knight),
+        'B' => Some(PieceKind::Bishop),
+        'R' => Some(PieceKind::Rook),
+        'Q' => Some(PieceKind::Queen),
+        'K' => Some(PieceKind::King),
+        _ => None,
+    }
+}
+
+fn square_from_name(name: &str) -> Option<usize> {
+    let bytes = name.as_bytes();
+    let (file, rank) = (*bytes.first()?, *bytes.get(1)?);
+    if !(b'a'..=b'h').contains(&file) || !(b'1'..=b'8').contains(&rank) {
+        return None;
+    }
+    Some((rank - b'1') as usize * 8 + (file - b'a') as usize)
+}
+
+/// Opening index and colors of game `game` (0-based) of a match over
+/// `openings` openings: consecutive games form a pair on the same opening,
+/// the first engine has White in the first game of the pair
+pub fn schedule(game: usize, openings: usize) -> (usize, bool) {
+    ((game / 2) % openings.max(1), game % 2 == 0)
+}
+
+/// When a game is decided from the engines' scores (0 moves = rule off;
+/// the default adjudicates nothing)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AdjudicationRules {
+    /// A side whose own score is at most -`resign_score` centipawns for
+    /// `resign_moves` consecutive moves loses
+    pub resign_score: i32,
+    pub resign_moves: u32,
+
+    /// After `draw_min_ply` plies, a game where both sides' scores stay
+    /// within `draw_score` centipawns of zero for `draw_moves` consecutive
+    /// moves each is drawn
+    pub draw_score: i32,
+    pub draw_moves: u32,
+    pub draw_min_ply: u32,
+}
+
+/// Outcome decided by the adjudicator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    WhiteWins,
+    BlackWins,
+    Draw,
+}
+
+/// Follows the scores of a game and applies the [`AdjudicationRules`]
+#[derive(Debug, Clone)]
+pub struct Adjudicator {
+    rules: AdjudicationRules,
+    /// Consecutive losing scores per side [white, black]
+    resign_streak: [u32; 2],
+    /// Consecutive drawish scores (plies, both sides)
+    draw_streak: u32,
+}
+
+impl Adjudicator {
+    pub fn new(rules: AdjudicationRules) -> Self {
+        Self {
+            rules,
+            resign_streak: [0; 2],
+            draw_streak: 0,
+        }
+    }
+
+    /// Record the score `score` (centipawns, from the mover's point of view)
+    /// that `mover` reported for its move at ply `ply` of the game
+    pub fn record(&mut self, ply: u32, mover: Color, score: i32) -> Option<Verdict> {
+        let rules = self.rules;
+        let streak = &mut self.resign_streak[mover as usize];
+        *streak = if score <= -rules.resign_score {
+            *streak + 1
+        } else {
+            0
+        };
+        if rules.resign_moves > 0 && *streak >= rules.resign_moves {
+            return Some(match mover {
+                Color::White => Verdict::BlackWins,
+                Color::Black => Verdict::WhiteWins,
+            });
+        }
+
+        self.draw_streak = if score.abs() <= rules.draw_score {
+            self.draw_streak + 1
+        } else {
+            0
+        };
+        if rules.draw_moves > 0
+            && ply >= rules.draw_min_ply
+            && self.draw_streak >= 2 * rules.draw_moves
+        {
+            return Some(Verdict::Draw);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_epd_suite_loads_positions() {
+        crate::init();
+        let text = "\
+# 2-move book
+rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - id \"e4 e5\";
+
+rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2
+";
+        let openings = parse_epd(text).unwrap();
+        assert_eq!(openings.len(), 2);
+        assert_eq!(openings[0].side_to_move, Color::White);
+        assert_eq!(openings[1].side_to_move, Color::Black);
+        assert_eq!(
+            openings[1].position_command(&["d7d6".to_string()]),
+            "position fen rnbqkbnr/pp1ppppp/8/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2 moves d7d6"
+        );
+        assert!(parse_epd("8/8/8/8 w - -").is_err());
+    }
+
+    #[test]
+    fn test_pgn_suite_converts_san_to_uci() {
+        crate::init();
+        let text = r#"[Event "book"]
+[Result "*"]
+
+1. e4 {main line} c5 2. Nf3 (2. c3 d5) d6 $1 3. d4 cxd4 4. Nxd4 Nf6 *
+
+[Event "from a FEN"]
+[FEN "4k3/P7/8/8/8/8/8/R3K2R w KQ - 0 1"]
+
+1. a8=Q+ Kd7 2. O-O-O+ 1-0
+"#;
+        let openings = parse_pgn(text).unwrap();
+        assert_eq!(openings.len(), 2);
+        assert_eq!(
+            openings[0].moves,
+            ["e2e4", "c7c5", "g1f3", "d7d6", "d2d4", "c5d4", "f3d4", "g8f6"]
+        );
+        assert_eq!(openings[0].fen, None);
+        assert_eq!(openings[0].side_to_move, Color::White);
+        assert_eq!(openings[1].moves, ["a7a8q", "e8d7", "e1c1"]);
+        assert_eq!(openings[1].side_to_move, Color::Black);
+        assert_eq!(
+            openings[0].position_command(&[]),
+            "position startpos moves e2e4 c7c5 g1f3 d7d6 d2d4 c5d4 f3d4 g8f6"
+        );
+
+        assert!(parse_pgn("1. e4 e5 2. Ke3 *").is_err());
+    }
+
+    #[test]
+    fn test_san_disambiguation() {
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("4k3/8/8/8/8/8/4K3/R6R w - - 0 1")
+            .unwrap();
+        assert_eq!(parse_san_move(&mut board, "Rd1"), Err("ambiguous SAN move"));
+        let mv = parse_san_move(&mut board, "Rad1").unwrap();
+        assert_eq!(move_to_uci(mv), "a1d1");
+        let mv = parse_san_move(&mut board, "Rhf1").unwrap();
+        assert_eq!(move_to_uci(mv), "h1f1");
+        assert!(parse_san_move(&mut board, "O-O").is_err());
+        assert!(parse_san_move(&mut board, "Nf3").is_err());
+    }
+
+    #[test]
+    fn test_schedule_pairs_openings_with_swapped_colors() {
+        let games: Vec<_> = (0..6).map(|g| schedule(g, 2)).collect();
+        assert_eq!(
+            games,
+            [
+                (0, true),
+                (0, false),
+                (1, true),
+                (1, false),
+                (0, true),
+                (0, false)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_adjudication_resign_and_draw() {
+        let rules = AdjudicationRules {
+            resign_score: 500,
+            resign_moves: 3,
+            draw_score: 10,
+            draw_moves: 2,
+            draw_min_ply: 40,
+        };
+
+        // Black reports a lost score for three moves in a row; White's own
+        // scores do not reset Black's count
+        let mut adj = Adjudicator::new(rules);
+        assert_eq!(adj.record(1, Color::White, 600), None);
+        assert_eq!(adj.record(2, Color::Black, -600), None);
+        assert_eq!(adj.record(3, Color::White, 650), None);
+        assert_eq!(adj.record(4, Color::Black, -550), None);
+        assert_eq!(adj.record(5, Color::White, 700), None);
+        assert_eq!(adj.record(6, Color::Black, -800), Some(Verdict::WhiteWins));
+
+        // Drawish scores only count once the minimum ply is reached, and a
+        // single larger score restarts the count
+        let mut adj = Adjudicator::new(rules);
+        for ply in 30..34 {
+            assert_eq!(adj.record(ply, Color::White, 0), None);
+        }
+        assert_eq!(adj.record(40, Color::White, 5), Some(Verdict::Draw));
+        let mut adj = Adjudicator::new(rules);
+        for (ply, score) in [(41, 0), (42, -3), (43, 40), (44, 0), (45, 2), (46, 0)] {
+            assert_eq!(adj.record(ply, Color::White, score), None);
+        }
+        assert_eq!(adj.record(47, Color::White, 0), Some(Verdict::Draw));
+
+        // Rules off
+        let mut adj = Adjudicator::new(AdjudicationRules::default());
+        for ply in 0..100 {
+            assert_eq!(adj.record(ply, Color::Black, -30000), None);
+        }
+    }
+}
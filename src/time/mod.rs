@@ -1,14 +1,289 @@
 //! Time management helper for Scacchista
 
+use crate::board::Move;
 use crate::search::params::TimeManagement as TM;
+use std::time::Duration;
+
+/// Monotonic clock of the search. `std::time::Instant` panics in the browser,
+/// so the `wasm` build reads `performance.now()` through `web-time` instead.
+#[cfg(not(all(target_arch = "wasm32", feature = "wasm")))]
+pub use std::time::Instant;
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub use web_time::Instant;
+
+/// Quality of a root move already known before the search starts.
+///
+/// Lets the time manager spend almost nothing on positions whose answer is
+/// already available (book hit, deep exact TT entry left by pondering).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrecomputedMove {
+    /// Nothing known in advance: use the normal allocation
+    None,
+    /// Move taken from an opening/experience book
+    Book,
+    /// Exact TT entry for the root position, searched to `depth`
+    TtExact { depth: u8 },
+}
+
+/// Minimum depth of an exact root TT entry to be trusted for an instant move
+pub const INSTANT_MOVE_TT_DEPTH: u8 = 12;
+
+/// With `movestogo 1` this fraction of the clock (1/8) is kept as a safety margin
+pub const LAST_MOVE_RESERVE_DIVISOR: u64 = 8;
+/// Minimum safety margin kept on the clock for the last move before a time control
+pub const LAST_MOVE_MIN_RESERVE_MS: u64 = 200;
+
+/// The hard limit is at most this many times the soft limit
+pub const HARD_LIMIT_MULTIPLIER: u64 = 3;
+/// ...and never more than this fraction (1/4) of the clock
+pub const HARD_LIMIT_CLOCK_DIVISOR: u64 = 4;
+
+/// Share of the normal allocation (percent) at full middlegame complexity...
+pub const MIDDLEGAME_TIME_PERCENT: u64 = 120;
+/// ...and in a bare endgame; in between it is interpolated on the game phase
+pub const ENDGAME_TIME_PERCENT: u64 = 75;
+/// Game phase from which a position counts as a full middlegame
+pub const MIDDLEGAME_PHASE: u8 = 16;
+
+/// Time budget of one move.
+///
+/// The search aims at `soft_ms` (ideal time) and may run on up to `hard_ms`
+/// (panic maximum) while the best move keeps changing between iterations;
+/// the hard limit is never exceeded. Both already exclude the move overhead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeAllocation {
+    pub soft_ms: u64,
+    pub hard_ms: u64,
+}
+
+impl TimeAllocation {
+    /// Same soft and hard limit: no extension possible
+    pub fn fixed(ms: u64) -> Self {
+        Self {
+            soft_ms: ms,
+            hard_ms: ms,
+        }
+    }
+}
+
+/// Completed iterations needed before the tree-growth forecast is trusted
+pub const FORECAST_MIN_ITERATIONS: usize = 3;
+
+/// The branching factor is averaged over at most this many iteration ratios
+const FORECAST_WINDOW: usize = 3;
+
+/// Tree-growth model of iterative deepening.
+///
+/// Records the nodes and time of every completed iteration; the effective
+/// branching factor (geometric mean of the last node ratios) then predicts the
+/// cost of the next iteration.
+#[derive(Debug, Clone, Default)]
+pub struct IterationForecast {
+    iterations: Vec<(u64, Duration)>,
+}
+
+impl IterationForecast {
+    pub fn clear(&mut self) {
+        self.iterations.clear();
+    }
+
+    /// Record the nodes searched and the time spent by one completed iteration
+    pub fn record(&mut self, nodes: u64, time: Duration) {
+        self.iterations.push((nodes, time));
+    }
+
+    /// Effective branching factor, None until `FORECAST_MIN_ITERATIONS` iterations
+    pub fn branching_factor(&self) -> Option<f64> {
+        if self.iterations.len() < FORECAST_MIN_ITERATIONS {
+            return None;
+        }
+        let ratios: Vec<f64> = self
+            .iterations
+            .windows(2)
+            .rev()
+            .take(FORECAST_WINDOW)
+            .filter(|w| w[0].0 > 0)
+            .map(|w| w[1].0 as f64 / w[0].0 as f64)
+            .collect();
+        if ratios.is_empty() {
+            return None;
+        }
+        let log_mean = ratios.iter().map(|r| r.max(1.0).ln()).sum::<f64>() / ratios.len() as f64;
+        Some(log_mean.exp())
+    }
+
+    /// Predicted node count of the next iteration
+    pub fn next_iteration_nodes(&self) -> Option<u64> {
+        let (nodes, _) = self.iterations.last()?;
+        Some((*nodes as f64 * self.branching_factor()?) as u64)
+    }
+
+    /// Predicted duration of the next iteration
+    pub fn next_iteration_time(&self) -> Option<Duration> {
+        let (_, time) = self.iterations.last()?;
+        Some(time.mul_f64(self.branching_factor()?))
+    }
+}
+
+/// Iterations the best move must survive unchanged to be an easy move
+pub const EASY_MOVE_STABLE_ITERATIONS: u32 = 4;
+/// An easy move beats every other root move by at least this many centipawns
+pub const EASY_MOVE_MARGIN: i16 = 150;
+/// An easy move is played once this share (percent) of the soft limit is used
+pub const EASY_MOVE_TIME_PERCENT: u64 = 30;
+/// A root score drop of more than this (centipawns) counts as a fail-low
+pub const ROOT_FAIL_LOW_MARGIN: i16 = 30;
+
+/// Time limit of a running search, driven by the root result of each
+/// completed iteration.
+///
+/// The limit is the soft one while the best move holds, the hard one after
+/// the best move changed or the root failed low. A best move that held for
+/// `EASY_MOVE_STABLE_ITERATIONS` iterations and dominates the other root
+/// moves by `EASY_MOVE_MARGIN` is an easy move: the search may stop well
+/// before the soft limit.
+#[derive(Debug, Clone, Default)]
+pub struct TimeState {
+    soft_ms: u64,
+    hard_ms: u64,
+    limit_ms: u64,
+    best_move: Move,
+    best_score: i16,
+    stable_iterations: u32,
+    easy_move: bool,
+}
+
+impl TimeState {
+    /// State for a search with these limits (0 = no time limit)
+    pub fn new(soft_ms: u64, hard_ms: u64) -> Self {
+        Self {
+            soft_ms,
+            hard_ms: hard_ms.max(soft_ms),
+            limit_ms: soft_ms,
+            ..Self::default()
+        }
+    }
+
+    /// Time limit in force (0 = unlimited)
+    pub fn limit_ms(&self) -> u64 {
+        self.limit_ms
+    }
+
+    /// Completed iterations in a row that kept the current best move
+    pub fn stable_iterations(&self) -> u32 {
+        self.stable_iterations
+    }
+
+    pub fn is_easy_move(&self) -> bool {
+        self.easy_move
+    }
+
+    /// Feed the root result of a completed iteration: its best move and
+    /// score, the best score of the other root moves (None when there are
+    /// none) and whether the root failed low (aspiration window re-search)
+    pub fn record_iteration(
+        &mut self,
+        best_move: Move,
+        score: i16,
+        second_score: Option<i16>,
+        failed_low: bool,
+    ) {
+        let first = self.best_move == Move::NONE;
+        let changed = !first && best_move != self.best_move;
+        let dropped = !first && score < self.best_score.saturating_sub(ROOT_FAIL_LOW_MARGIN);
+        if first || changed {
+            self.stable_iterations = 0;
+        } else {
+            self.stable_iterations += 1;
+        }
+        self.limit_ms = if changed || failed_low || dropped {
+            self.hard_ms
+        } else {
+            self.soft_ms
+        };
+        self.easy_move = self.stable_iterations >= EASY_MOVE_STABLE_ITERATIONS
+            && !failed_low
+            && second_score.map_or(true, |s| s.saturating_add(EASY_MOVE_MARGIN) <= score);
+        self.best_move = best_move;
+        self.best_score = score;
+    }
+
+    /// Whether to stop after `elapsed` although the limit is not reached:
+    /// an easy move once `EASY_MOVE_TIME_PERCENT` of the soft limit is used
+    pub fn stop_early(&self, elapsed: Duration) -> bool {
+        self.soft_ms > 0
+            && self.easy_move
+            && elapsed >= Duration::from_millis(self.soft_ms * EASY_MOVE_TIME_PERCENT / 100)
+    }
+}
+
+/// Clock of a `go ponder` search.
+///
+/// Pondering runs on the opponent's time, but the tree it builds is kept
+/// when the expected move is played. At `ponderhit` the time already spent
+/// pondering therefore counts towards the move's budget: the deadline is
+/// what is left of the soft limit, not a full allocation started over.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PonderClock {
+    /// No ponder search running
+    #[default]
+    Idle,
+    /// `go ponder` received at `started`
+    Pondering { started: Instant },
+    /// `ponderhit` received: the search runs on the engine's own clock
+    Hit,
+}
+
+impl PonderClock {
+    /// A ponder search starts at `now`
+    pub fn start(&mut self, now: Instant) {
+        *self = Self::Pondering { started: now };
+    }
+
+    /// The search ended (`stop`, a new `go`)
+    pub fn stop(&mut self) {
+        *self = Self::Idle;
+    }
+
+    pub fn is_pondering(&self) -> bool {
+        matches!(self, Self::Pondering { .. })
+    }
+
+    /// Switch to the engine's clock at `now`: the milliseconds left of the
+    /// soft limit of `budget` after the pondering (see
+    /// [`TimeManager::after_ponder`]), or None when no ponder search was
+    /// running
+    pub fn ponderhit(&mut self, budget: TimeAllocation, now: Instant) -> Option<u64> {
+        let Self::Pondering { started } = *self else {
+            return None;
+        };
+        *self = Self::Hit;
+        Some(TimeManager::after_ponder(
+            budget,
+            now.saturating_duration_since(started),
+        ))
+    }
+}
 
 pub struct TimeManager;
 
 impl TimeManager {
-    /// Compute milliseconds to allocate given TimeManagement and go parameters.
+    /// Compute the soft and hard limits for the given TimeManagement and go
+    /// parameters.
+    ///
+    /// The clock is shared over `movestogo` moves (40 when not given) and the
+    /// share is scaled by the game phase of `time_mgmt` (see
+    /// [`TimeManager::phase_time_percent`]). Applies emergency logic for very
+    /// low time reserves and subtracts `move_overhead_ms` to compensate for
+    /// network/GUI lag. The hard limit is `HARD_LIMIT_MULTIPLIER` times the
+    /// soft one; neither limit exceeds 1/`HARD_LIMIT_CLOCK_DIVISOR` of the
+    /// clock (half of it in an emergency with an increment). `movetime`, the
+    /// last move before the control and the emergency allocation are fixed.
     ///
-    /// Applies emergency logic for very low time reserves and subtracts
-    /// `move_overhead_ms` to compensate for network/GUI lag.
+    /// `movestogo 1` is the last move before the time control: the clock is
+    /// refilled right after it, so everything but a safety margin may be spent
+    /// (see [`TimeManager::last_move_allocation`]). Allocation is stateless, so
+    /// the next `go` (new clock, new `movestogo`) starts a new time control.
     #[allow(clippy::too_many_arguments)]
     pub fn allocate_time(
         time_mgmt: &TM,
@@ -20,37 +295,130 @@ impl TimeManager {
         movestogo: Option<u64>,
         side_is_white: bool,
         move_overhead_ms: u64,
-    ) -> u64 {
+    ) -> TimeAllocation {
         if let Some(mt) = movetime {
-            return mt.saturating_sub(move_overhead_ms).max(1);
+            return TimeAllocation::fixed(mt.saturating_sub(move_overhead_ms).max(1));
         }
 
         let time_left = if side_is_white { wtime } else { btime };
         let inc = if side_is_white { winc } else { binc };
 
         if let Some(t) = time_left {
-            // Emergency: less than 1 second on the clock
-            if t < 1000 {
-                return t.saturating_sub(move_overhead_ms).max(1);
+            if Self::is_last_move_before_control(movestogo) {
+                return TimeAllocation::fixed(Self::last_move_allocation(t, move_overhead_ms));
             }
 
-            // Conservative: less than 5 seconds
-            if t < 5000 {
-                let alloc = (t / 10).max(10);
-                return alloc.saturating_sub(move_overhead_ms).max(1);
+            // Emergency: less than 1 second on the clock. The same share of
+            // it as the hard limit, plus half the increment, but never more
+            // than half the clock: the increment only comes after the move
+            if t < 1000 {
+                let alloc = (t / HARD_LIMIT_CLOCK_DIVISOR + inc.unwrap_or(0) / 2).min(t / 2);
+                return TimeAllocation::fixed(alloc.saturating_sub(move_overhead_ms).max(1));
             }
 
-            // Normal allocation
-            let moves_to_go = movestogo.unwrap_or(40).max(2);
-            let base_time = (t / moves_to_go).max(10);
-            let increment_bonus = inc.map(|i| (i * 8) / 10).unwrap_or(0);
-            let alloc = base_time + increment_bonus;
-            return alloc.saturating_sub(move_overhead_ms).max(1);
+            // Conservative: less than 5 seconds
+            let alloc = if t < 5000 {
+                (t / 10).max(10)
+            } else {
+                // Normal allocation
+                let moves_to_go = movestogo.unwrap_or(40).max(2);
+                let base_time = (t / moves_to_go).max(10);
+                let increment_bonus = inc.map(|i| (i * 8) / 10).unwrap_or(0);
+                base_time + increment_bonus
+            };
+            let alloc = match time_mgmt.game_phase {
+                Some(phase) => alloc * Self::phase_time_percent(phase) / 100,
+                None => alloc,
+            };
+            let max_ms = t / HARD_LIMIT_CLOCK_DIVISOR;
+            let soft_ms = alloc.min(max_ms).saturating_sub(move_overhead_ms).max(1);
+            let hard_ms = (alloc * HARD_LIMIT_MULTIPLIER)
+                .min(max_ms)
+                .saturating_sub(move_overhead_ms)
+                .max(soft_ms);
+            return TimeAllocation { soft_ms, hard_ms };
         }
 
         // Fallback when no clock info is provided
-        time_mgmt.msec_per_move
+        TimeAllocation::fixed(
+            time_mgmt
+                .msec_per_move
+                .saturating_sub(move_overhead_ms)
+                .max(1),
+        )
+    }
+
+    /// Share of the normal allocation for a position of game phase `phase`
+    /// (percent): `MIDDLEGAME_TIME_PERCENT` from `MIDDLEGAME_PHASE` up,
+    /// falling linearly to `ENDGAME_TIME_PERCENT` at phase 0
+    pub fn phase_time_percent(phase: u8) -> u64 {
+        let phase = phase.min(MIDDLEGAME_PHASE) as u64;
+        let mid = MIDDLEGAME_PHASE as u64;
+        (MIDDLEGAME_TIME_PERCENT * phase + ENDGAME_TIME_PERCENT * (mid - phase)) / mid
+    }
+
+    /// True when `movestogo` says the current move is the last one of the control
+    pub fn is_last_move_before_control(movestogo: Option<u64>) -> bool {
+        movestogo == Some(1)
+    }
+
+    /// Time for the last move before a time control with `time_left_ms` on the
+    /// clock: all of it except max(1/8 of the clock, `LAST_MOVE_MIN_RESERVE_MS`)
+    /// and the move overhead
+    pub fn last_move_allocation(time_left_ms: u64, move_overhead_ms: u64) -> u64 {
+        let reserve = (time_left_ms / LAST_MOVE_RESERVE_DIVISOR).max(LAST_MOVE_MIN_RESERVE_MS);
+        time_left_ms
+            .saturating_sub(reserve)
             .saturating_sub(move_overhead_ms)
             .max(1)
     }
+
+    /// Whether to start another iteration with `elapsed` of `limit_ms` used.
+    ///
+    /// An iteration that is predicted not to finish is skipped: an interrupted
+    /// iteration's result is discarded, so its time would be wasted. Without a
+    /// forecast (too few iterations) or a limit the answer is always yes.
+    pub fn should_start_iteration(
+        forecast: &IterationForecast,
+        elapsed: Duration,
+        limit_ms: u64,
+    ) -> bool {
+        if limit_ms == 0 {
+            return true;
+        }
+        match forecast.next_iteration_time() {
+            Some(next) => elapsed + next <= Duration::from_millis(limit_ms),
+            None => true,
+        }
+    }
+
+    /// Time left of the soft limit of `budget` after `pondered` of pondering
+    /// on the same search, counted from the `ponderhit`, so a search that
+    /// pondered past its soft limit stops right away.
+    ///
+    /// The ponder search runs without a time limit of its own and is stopped
+    /// from outside once this time is used, so the hard limit does not apply.
+    pub fn after_ponder(budget: TimeAllocation, pondered: Duration) -> u64 {
+        budget
+            .soft_ms
+            .saturating_sub(pondered.as_millis() as u64)
+            .max(1)
+    }
+
+    /// Shrink an allocation when the root move is already known.
+    ///
+    /// Book moves and sufficiently deep exact TT entries are capped at
+    /// `min_think_ms`; anything else keeps `alloc_ms` unchanged.
+    pub fn adjust_for_precomputed(alloc_ms: u64, hint: PrecomputedMove, min_think_ms: u64) -> u64 {
+        let instant = match hint {
+            PrecomputedMove::None => false,
+            PrecomputedMove::Book => true,
+            PrecomputedMove::TtExact { depth } => depth >= INSTANT_MOVE_TT_DEPTH,
+        };
+        if instant {
+            alloc_ms.min(min_think_ms).max(1)
+        } else {
+            alloc_ms
+        }
+    }
 }
@@ -0,0 +1,147 @@
+//! Live UCI `info` output while the engine is thinking
+//!
+//! [`InfoStreamer`] is a [`SearchObserver`] that turns search events into
+//! `info depth ... seldepth ... nodes ... nps ... hashfull ... pv ...` lines
+//! after every iteration (followed by an `info string depth N eta ...` forecast
+//! of the next iteration once the branching factor is known, and an
+//! `info string aspiration ...` summary once a window has failed), the same
+//! line with `lowerbound`/`upperbound` when an aspiration window fails, plus
+//! `currmove`/`currmovenumber` lines on long searches, and hands them to a sink
+//! (stdout in the UCI loop).
+
+use crate::board::{move_to_uci_with, Move};
+use crate::search::score::mate_in;
+use crate::search::{AspirationStats, IterationInfo, ScoreBound, SearchObserver};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Destination of streamed `info` lines (called from the search thread)
+pub type InfoSink = Arc<dyn Fn(String) + Send + Sync>;
+
+/// `currmove` lines are only sent after this much thinking time, so short
+/// searches do not flood the GUI
+const CURRMOVE_DELAY: Duration = Duration::from_millis(1000);
+
+/// Observer that streams UCI `info` lines to a sink
+pub struct InfoStreamer {
+    sink: InfoSink,
+    chess960: bool,
+}
+
+impl InfoStreamer {
+    pub fn new(sink: InfoSink, chess960: bool) -> Self {
+        Self { sink, chess960 }
+    }
+
+    /// `info depth ...` line of an iteration, its score marked as a bound if any
+    fn info_line(&self, info: &IterationInfo, bound: Option<ScoreBound>) -> String {
+        let time_ms = info.elapsed.as_millis() as u64;
+        let mut score = format_score(info.score);
+        match bound {
+            Some(ScoreBound::Lower) => score.push_str(" lowerbound"),
+            Some(ScoreBound::Upper) => score.push_str(" upperbound"),
+            None => {}
+        }
+        let mut parts = vec![
+            format!("depth {}", info.depth),
+            format!("seldepth {}", info.seldepth),
+            score,
+            format!("nodes {}", info.nodes),
+        ];
+        if let Some(nps) = (info.nodes * 1000).checked_div(time_ms) {
+            parts.push(format!("nps {}", nps));
+        }
+        parts.push(format!("time {}", time_ms));
+        parts.push(format!("hashfull {}", info.hashfull));
+        if !info.pv.is_empty() {
+            let pv = info
+                .pv
+                .iter()
+                .map(|&m| move_to_uci_with(m, self.chess960))
+                .collect::<Vec<_>>()
+                .join(" ");
+            parts.push(format!("pv {}", pv));
+        }
+        format!("info {}", parts.join(" "))
+    }
+}
+
+impl SearchObserver for InfoStreamer {
+    fn on_iteration(&self, info: &IterationInfo) {
+        (self.sink)(self.info_line(info, None));
+        if let (Some(ebf), Some(next)) = (info.branching_factor, info.next_iteration) {
+            (self.sink)(format!(
+                "info string depth {} eta {} ms ebf {:.2}",
+                info.depth + 1,
+                next.as_millis(),
+                ebf
+            ));
+        }
+    }
+
+    fn on_aspiration_fail(&self, info: &IterationInfo, bound: ScoreBound) {
+        (self.sink)(self.info_line(info, Some(bound)));
+    }
+
+    fn on_aspiration_stats(&self, stats: &AspirationStats) {
+        if let Some(line) = aspiration_line(stats) {
+            (self.sink)(line);
+        }
+    }
+
+    fn on_currmove(&self, depth: u8, mv: Move, number: u32, elapsed: Duration) {
+        if elapsed >= CURRMOVE_DELAY {
+            (self.sink)(format!(
+                "info depth {} currmove {} currmovenumber {}",
+                depth,
+                move_to_uci_with(mv, self.chess960),
+                number
+            ));
+        }
+    }
+}
+
+/// `info string aspiration ...` summary of the window failures so far; None
+/// while every window has held
+fn aspiration_line(stats: &AspirationStats) -> Option<String> {
+    if stats.fail_highs + stats.fail_lows == 0 {
+        return None;
+    }
+    let share = stats.research_nodes as f64 * 100.0 / stats.nodes.max(1) as f64;
+    Some(format!(
+        "info string aspiration failhigh {} faillow {} researchnodes {} ({:.1}%)",
+        stats.fail_highs, stats.fail_lows, stats.research_nodes, share
+    ))
+}
+
+/// Format a search score as a UCI `score cp N` / `score mate N` token.
+pub(crate) fn format_score(score: i16) -> String {
+    match mate_in(score) {
+        Some(moves) => format!("score mate {}", moves),
+        None => format!("score cp {}", score),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aspiration_line_only_after_a_failure() {
+        let mut stats = AspirationStats {
+            fail_highs: 0,
+            fail_lows: 0,
+            research_nodes: 0,
+            nodes: 1000,
+        };
+        assert_eq!(aspiration_line(&stats), None);
+
+        stats.fail_highs = 2;
+        stats.fail_lows = 1;
+        stats.research_nodes = 250;
+        assert_eq!(
+            aspiration_line(&stats).unwrap(),
+            "info string aspiration failhigh 2 faillow 1 researchnodes 250 (25.0%)"
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! Debug log of the UCI conversation
+//!
+//! With `debug on`, every line received from the GUI, every line sent back
+//! (including `info` lines streamed by the search threads) and internal
+//! diagnostics are appended to the `Debug Log File`, one per line:
+//!
+//! ```text
+//! 1760601234.567 >> position startpos moves e2e4
+//! 1760601234.568 << readyok
+//! 1760601234.569 ## go: soft 950 ms, hard 2850 ms
+//! ```
+//!
+//! The timestamp is Unix time with milliseconds. The log is shared with the
+//! info sink, so lines written from search threads are never interleaved.
+//!
+//! The file never grows past [`DEBUG_LOG_MAX_BYTES`]: when it is full it is
+//! renamed with a `.1` suffix (replacing the previous one) and a new file is
+//! started, so at most twice that much is kept on disk.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, LineWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Log file used when `Debug Log File` is not set
+pub const DEFAULT_DEBUG_LOG_FILE: &str = "scacchista-debug.log";
+
+/// Size at which the log file is rotated
+pub const DEBUG_LOG_MAX_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Handle to the debug log; clones write to the same file
+#[derive(Clone, Default)]
+pub struct DebugLog {
+    file: Arc<Mutex<Option<LogFile>>>,
+}
+
+/// The open log file and how much of it is used
+struct LogFile {
+    /// None only while rotating: the file is closed before it is renamed
+    writer: Option<LineWriter<File>>,
+    path: PathBuf,
+    len: u64,
+    max_len: u64,
+}
+
+impl LogFile {
+    /// Append to `path`, rotating it first if it is already full
+    fn open(path: &Path, max_len: u64) -> io::Result<Self> {
+        let mut log = Self {
+            writer: None,
+            path: path.to_path_buf(),
+            len: std::fs::metadata(path).map_or(0, |meta| meta.len()),
+            max_len,
+        };
+        if log.len >= max_len {
+            log.rotate()?;
+        } else {
+            log.writer = Some(LineWriter::new(Self::append(path)?));
+        }
+        Ok(log)
+    }
+
+    fn append(path: &Path) -> io::Result<File> {
+        OpenOptions::new().create(true).append(true).open(path)
+    }
+
+    /// Keep the full file as `<path>.1` and start an empty one
+    fn rotate(&mut self) -> io::Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+        }
+        let mut old = self.path.clone().into_os_string();
+        old.push(".1");
+        std::fs::rename(&self.path, old)?;
+        self.writer = Some(LineWriter::new(Self::append(&self.path)?));
+        self.len = 0;
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let size = line.len() as u64 + 1;
+        if self.len > 0 && self.len + size > self.max_len {
+            self.rotate()?;
+        }
+        let Some(writer) = self.writer.as_mut() else {
+            return Ok(());
+        };
+        writeln!(writer, "{}", line)?;
+        self.len += size;
+        Ok(())
+    }
+}
+
+impl DebugLog {
+    /// Log with no file open (nothing is written)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start appending to `path`, replacing any file already open
+    pub fn open(&self, path: &Path) -> io::Result<()> {
+        self.open_with_limit(path, DEBUG_LOG_MAX_BYTES)
+    }
+
+    fn open_with_limit(&self, path: &Path, max_len: u64) -> io::Result<()> {
+        *self.lock() = Some(LogFile::open(path, max_len)?);
+        Ok(())
+    }
+
+    /// Stop logging
+    pub fn close(&self) {
+        *self.lock() = None;
+    }
+
+    /// Whether a log file is open
+    pub fn is_enabled(&self) -> bool {
+        self.lock().is_some()
+    }
+
+    /// Line received from the GUI
+    pub fn input(&self, line: &str) {
+        self.write(">>", line);
+    }
+
+    /// Line sent to the GUI
+    pub fn output(&self, line: &str) {
+        self.write("<<", line);
+    }
+
+    /// Internal diagnostic (never sent to the GUI)
+    pub fn note(&self, message: &str) {
+        self.write("##", message);
+    }
+
+    fn write(&self, direction: &str, line: &str) {
+        let mut file = self.lock();
+        if let Some(file) = file.as_mut() {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+            // A failing log must not disturb the engine
+            let _ = file.write_line(&format!(
+                "{}.{:03} {} {}",
+                now.as_secs(),
+                now.subsec_millis(),
+                direction,
+                line
+            ));
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<LogFile>> {
+        self.file
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debug_log_writes_timestamped_lines_only_when_open() {
+        let path = std::env::temp_dir().join(format!("scacchista-log-{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let log = DebugLog::new();
+        log.input("ignored");
+        assert!(!log.is_enabled());
+
+        log.open(&path).unwrap();
+        log.input("isready");
+        log.clone().output("readyok");
+        log.note("diagnostic");
+        log.close();
+        log.output("ignored too");
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with(" >> isready"));
+        assert!(lines[1].ends_with(" << readyok"));
+        assert!(lines[2].ends_with(" ## diagnostic"));
+        let stamp = lines[0].split(' ').next().unwrap();
+        let (secs, millis) = stamp.split_once('.').unwrap();
+        assert!(secs.parse::<u64>().is_ok());
+        assert_eq!(millis.len(), 3);
+    }
+
+    #[test]
+    fn test_debug_log_rotates_when_full() {
+        let path = std::env::temp_dir().join(format!("scacchista-rot-{}.txt", std::process::id()));
+        let rotated = path.with_extension("txt.1");
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&rotated);
+
+        // About 25 bytes per line: a few lines fit in each file
+        let log = DebugLog::new();
+        log.open_with_limit(&path, 100).unwrap();
+        for i in 0..10 {
+            log.note(&format!("line {}", i));
+        }
+        log.close();
+        let current = std::fs::read_to_string(&path).unwrap();
+        let previous = std::fs::read_to_string(&rotated).unwrap();
+        assert!(current.len() <= 100 && previous.len() <= 100);
+        assert!(current.ends_with(" ## line 9\n"));
+        assert!(!previous.contains("line 9"));
+
+        // A file already over the limit is rotated when it is opened again
+        std::fs::write(&path, "x".repeat(200)).unwrap();
+        log.open_with_limit(&path, 100).unwrap();
+        log.note("fresh");
+        log.close();
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .ends_with(" ## fresh\n"));
+        assert_eq!(std::fs::read_to_string(&rotated).unwrap().len(), 200);
+
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&rotated);
+    }
+}
@@ -1,10 +1,21 @@
 //! Minimal UCI event loop and state machine for Scacchista
 
 use super::parser::{parse_uci_command, UciCommand};
-use crate::board::{move_to_uci, parse_uci_move, Board};
-use crate::search::search::{MATE, MATE_THRESHOLD};
+use crate::board::{
+    move_to_uci_with, parse_uci_move, piece_char, square_to_uci, Board, Color, Move, PieceKind,
+};
+use crate::eval::EvalWeights;
+use crate::experience::ExperienceBook;
+use crate::search::{
+    JobHandle, ObserverSet, SearchObserver, SearchResult, ThreadManager, VarietyRng,
+};
+use crate::uci::info::{format_score, InfoSink, InfoStreamer};
+use crate::uci::log::{DebugLog, DEFAULT_DEBUG_LOG_FILE};
 use std::io::{self, BufRead, Write};
-use std::sync::{atomic::AtomicBool, atomic::Ordering, Arc};
+use std::path::Path;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
 use std::time::Instant;
 
 use crate::uci::options::UciOptions;
@@ -25,15 +36,29 @@ pub struct UciEngine {
     thread_mgr: Option<crate::search::ThreadManager>,
     /// Flag indicating if an async search (go infinite) is currently active
     async_search_active: bool,
-    /// Cancel flag for an active ponder timer thread
-    ponder_timer_cancel: Option<Arc<AtomicBool>>,
-    /// Last clock parameters from go command (used by ponderhit)
-    last_wtime: Option<u64>,
-    last_btime: Option<u64>,
-    last_winc: Option<u64>,
-    last_binc: Option<u64>,
-    last_movetime: Option<u64>,
-    last_movestogo: Option<u64>,
+    /// Timer ending the search after a ponderhit
+    ponder_timer: Option<PonderTimer>,
+    /// Start of the current ponder search, credited to the move at ponderhit
+    ponder_clock: crate::time::PonderClock,
+    /// Limits of the last go command (ponderhit allocates time from its clock)
+    last_limits: crate::engine::SearchLimits,
+    /// Telemetry observer, kept across thread manager re-creation
+    observer: Option<Arc<dyn SearchObserver>>,
+    /// Where `info` lines are streamed during a search (None = only a final line)
+    info_sink: Option<InfoSink>,
+    /// FEN and move list of the last accepted `position` command: a command
+    /// that extends it only plays the new moves on the current board
+    game_fen: Option<String>,
+    game_moves: Vec<String>,
+    /// Experience book, loaded from `ExperienceFile` on first use
+    experience: Option<ExperienceBook>,
+    /// Color the engine plays in the current game (from the last clock-based
+    /// `go`); contempt is applied from its point of view
+    engine_color: Option<Color>,
+    /// Log of the conversation while `debug on` is active
+    log: DebugLog,
+    /// Draws the seed of each search's `MoveVariety` bonuses
+    variety_rng: VarietyRng,
 }
 
 impl Default for UciEngine {
@@ -53,18 +78,254 @@ impl UciEngine {
             options: opts,
             thread_mgr: Some(tm),
             async_search_active: false,
-            ponder_timer_cancel: None,
-            last_wtime: None,
-            last_btime: None,
-            last_winc: None,
-            last_binc: None,
-            last_movetime: None,
-            last_movestogo: None,
+            ponder_timer: None,
+            ponder_clock: crate::time::PonderClock::Idle,
+            last_limits: crate::engine::SearchLimits::default(),
+            observer: None,
+            info_sink: None,
+            game_fen: None,
+            game_moves: Vec::new(),
+            experience: None,
+            engine_color: None,
+            log: DebugLog::new(),
+            variety_rng: VarietyRng::seeded_or_clock(0),
         }
     }
 
+    /// Forget the game followed by `position` commands, so the next one
+    /// rebuilds the board from its FEN
+    fn reset_game(&mut self) {
+        self.game_fen = None;
+        self.game_moves.clear();
+    }
+
+    /// Load the experience book from `ExperienceFile` unless it is disabled
+    /// or already loaded; an unreadable file starts an empty book
+    fn load_experience(&mut self) -> Option<String> {
+        if !self.options.use_experience_book || self.experience.is_some() {
+            return None;
+        }
+        let Some(path) = self.options.experience_book_path.clone() else {
+            self.experience = Some(ExperienceBook::new());
+            return None;
+        };
+        match ExperienceBook::load(Path::new(&path)) {
+            Ok(book) => {
+                self.experience = Some(book);
+                None
+            }
+            Err(e) => {
+                self.experience = Some(ExperienceBook::new());
+                Some(format!("info string experience book {}: {}", path, e))
+            }
+        }
+    }
+
+    /// Learn from the game that just ended and write the book to
+    /// `ExperienceFile`
+    fn save_experience(&mut self) -> Option<String> {
+        let book = self.experience.as_mut()?;
+        let learned = book.finish_game_from(&self.board);
+        let path = self.options.experience_book_path.as_ref()?;
+        if learned == 0 {
+            return None;
+        }
+        book.save(Path::new(path))
+            .err()
+            .map(|e| format!("info string experience book {}: {}", path, e))
+    }
+
+    /// Remember the move chosen for the current position
+    fn record_experience(&mut self, result: &SearchResult) {
+        if let Some(book) = self.experience.as_mut() {
+            book.record(
+                &self.board,
+                result.best_move,
+                result.score,
+                result.completed_depth,
+            );
+        }
+    }
+
+    /// Learned move deep enough to be played without thinking
+    fn book_move(&self) -> Option<Move> {
+        self.experience.as_ref()?.book_move(&self.board)
+    }
+
+    /// Learned move searched first at the root
+    fn experience_move(&self) -> Option<Move> {
+        self.experience
+            .as_ref()?
+            .preferred_move(&self.board)
+            .map(|e| e.mv)
+    }
+
+    /// Evaluation profile of the `Style` option, without the terms switched
+    /// off in tuning mode
+    fn eval_weights(&self) -> EvalWeights {
+        let style = EvalWeights::for_style(&self.options.chess_style).unwrap_or_default();
+        self.options
+            .disabled_eval
+            .iter()
+            .fold(style, |weights, &component| weights.without(component))
+    }
+
+    /// Install a telemetry observer that receives structured events from
+    /// every subsequent search (see [`SearchObserver`])
+    pub fn set_observer(&mut self, observer: Arc<dyn SearchObserver>) {
+        self.observer = Some(observer);
+        self.install_observer();
+    }
+
+    /// Stream `info` lines to `sink` while searching, instead of only
+    /// reporting once the search is over
+    pub fn set_info_sink(&mut self, sink: InfoSink) {
+        self.info_sink = Some(sink);
+        self.install_observer();
+    }
+
+    /// Open the debug log at `Debug Log File`; an error is reported as an
+    /// `info string`
+    fn open_debug_log(&self) -> Option<String> {
+        let path = self
+            .options
+            .debug_log_path
+            .as_deref()
+            .unwrap_or(DEFAULT_DEBUG_LOG_FILE);
+        match self.log.open(Path::new(path)) {
+            Ok(()) => {
+                self.log.note(&format!(
+                    "debug log opened, Scacchista {}",
+                    env!("CARGO_PKG_VERSION")
+                ));
+                None
+            }
+            Err(e) => Some(format!("info string debug log {}: {}", path, e)),
+        }
+    }
+
+    /// Handle one line from the GUI, mirroring it and the replies to the
+    /// debug log
+    pub fn process_line(&mut self, line: &str) -> Vec<String> {
+        self.log.input(line);
+        let responses = self.handle_command(parse_uci_command(line));
+        for r in &responses {
+            self.log.output(r);
+        }
+        responses
+    }
+
+    /// Observer for the next search: the telemetry observer and/or the info streamer
+    fn search_observer(&self) -> Option<Arc<dyn SearchObserver>> {
+        let streamer = self.info_sink.clone().map(|sink| {
+            // Streamed lines bypass `process_line`: mirror them here
+            let log = self.log.clone();
+            let sink: InfoSink = Arc::new(move |line: String| {
+                log.output(&line);
+                sink(line)
+            });
+            Arc::new(InfoStreamer::new(sink, self.options.chess960)) as Arc<dyn SearchObserver>
+        });
+        match (self.observer.clone(), streamer) {
+            (Some(observer), Some(streamer)) => {
+                Some(Arc::new(ObserverSet(vec![observer, streamer])))
+            }
+            (observer, streamer) => observer.or(streamer),
+        }
+    }
+
+    fn install_observer(&self) {
+        if let Some(tm) = &self.thread_mgr {
+            tm.set_observer(self.search_observer());
+        }
+    }
+
+    /// `info string` lines for search workers that panicked since the last
+    /// call (the thread manager has already recovered from them)
+    fn worker_panic_lines(&self) -> Vec<String> {
+        let Some(tm) = &self.thread_mgr else {
+            return Vec::new();
+        };
+        tm.take_worker_panics()
+            .into_iter()
+            .map(|p| {
+                let line = format!(
+                    "info string search worker {} panicked: {}",
+                    p.worker_id, p.message
+                );
+                self.log.note(&line);
+                line
+            })
+            .collect()
+    }
+
+    /// Check the search's best move against the legal moves before it is
+    /// played (see [`legal_best_move`]); the diagnostic goes to the GUI and
+    /// the debug log
+    fn verify_best_move(&mut self, result: &mut SearchResult) -> Option<String> {
+        self.report().verify_best_move(result)
+    }
+
+    /// How results are reported in the current position
+    fn report(&self) -> ResultReport {
+        ResultReport {
+            board: self.board.clone(),
+            chess960: self.options.chess960,
+            report_root_moves: self.options.report_root_moves,
+            log: self.log.clone(),
+            jobs: self.thread_mgr.as_ref().map(ThreadManager::job_handle),
+        }
+    }
+
+    /// Cancel the ponderhit timer, or collect what it reported if its
+    /// deadline already ended the search: the lines it could not send
+    /// through the info sink, None if the search is still to be answered
+    fn end_ponder_timer(&mut self) -> Option<Vec<String>> {
+        let timer = self.ponder_timer.take()?;
+        // The timer may be gone already: a failed send is fine
+        let _ = timer.cancel.send(());
+        let (result, lines) = timer.thread.join().ok().flatten()?;
+        if let Some(result) = result {
+            self.record_experience(&result);
+        }
+        self.ponder_clock.stop();
+        self.async_search_active = false;
+        self.state = UciState::Ready;
+        Some(lines)
+    }
+
+    /// Time for a move when `go` gives neither `movetime` nor a clock
+    fn default_time_allocation(&self) -> crate::time::TimeAllocation {
+        crate::time::TimeManager::allocate_time(
+            &crate::search::params::TimeManagement::new().with_phase(self.board.phase()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            self.board.side == Color::White,
+            self.options.move_overhead_ms,
+        )
+    }
+
+    /// Create a thread manager carrying over the installed observer
+    fn new_thread_manager(&self, threads: usize, hash_mb: usize) -> ThreadManager {
+        let tm = ThreadManager::new(threads, hash_mb);
+        tm.set_observer(self.search_observer());
+        tm.set_thread_affinity(self.options.thread_affinity);
+        tm
+    }
+
     pub fn handle_command(&mut self, cmd: UciCommand) -> Vec<String> {
         let mut res = Vec::new();
+        if self
+            .ponder_timer
+            .as_ref()
+            .is_some_and(|timer| timer.thread.is_finished())
+        {
+            res.extend(self.end_ponder_timer().into_iter().flatten());
+        }
         match cmd {
             UciCommand::Uci => {
                 res.push("id name Scacchista".to_string());
@@ -73,12 +334,67 @@ impl UciEngine {
                 // Send UCI options
                 res.push("option name Hash type spin default 16 min 1 max 4096".to_string());
                 res.push("option name Threads type spin default 1 min 1 max 256".to_string());
+                res.push("option name Clear Hash type button".to_string());
+                res.push("option name Ponder type check default false".to_string());
+                res.push(format!(
+                    "option name EvalCache type spin default {} min 0 max {}",
+                    crate::search::eval_cache::DEFAULT_EVAL_CACHE_MB,
+                    crate::search::eval_cache::MAX_EVAL_CACHE_MB
+                ));
+                res.push(
+                    "option name SMPMode type combo default LazySMP var LazySMP var RootSplit"
+                        .to_string(),
+                );
+                res.push("option name ThreadAffinity type check default false".to_string());
+                res.push(format!(
+                    "option name Move Overhead type spin default {} min 0 max {}",
+                    crate::uci::options::DEFAULT_MOVE_OVERHEAD_MS,
+                    crate::uci::options::MAX_MOVE_OVERHEAD_MS
+                ));
+                res.push("option name Debug Log File type string default <empty>".to_string());
                 res.push("option name SyzygyPath type string default <empty>".to_string());
                 res.push("option name UseExperienceBook type check default true".to_string());
+                res.push("option name ExperienceFile type string default <empty>".to_string());
                 res.push(
                     "option name Style type combo default Normal var Normal var Tal var Petrosian"
                         .to_string(),
                 );
+                res.push("option name Contempt type spin default 0 min -100 max 100".to_string());
+                res.push(format!(
+                    "option name MoveVariety type spin default 0 min 0 max {}",
+                    crate::uci::options::MAX_MOVE_VARIETY
+                ));
+                res.push(
+                    "option name VarietySeed type spin default 0 min 0 max 2147483647".to_string(),
+                );
+                res.push("option name ReportRootMoves type check default false".to_string());
+                res.push("option name InstantMoves type check default true".to_string());
+                res.push(
+                    "option name MinThinkTime type spin default 30 min 0 max 5000".to_string(),
+                );
+                res.push("option name StudyMode type check default false".to_string());
+                res.push(
+                    "option name NodesLimit type spin default 0 min 0 max 2147483647".to_string(),
+                );
+                res.push(
+                    "option name NPS Limit type spin default 0 min 0 max 2147483647".to_string(),
+                );
+                if self.options.tune {
+                    for spin in &crate::eval::EVAL_PARAM_SPINS {
+                        let default = crate::eval::EvalParams::DEFAULT.get(spin.name).unwrap_or(0);
+                        res.push(format!(
+                            "option name {} type spin default {} min {} max {}",
+                            spin.name, default, spin.min, spin.max
+                        ));
+                    }
+                    for component in crate::eval::EvalComponent::ALL {
+                        res.push(format!(
+                            "option name {} type check default true",
+                            component.option_name()
+                        ));
+                    }
+                }
+                res.push("option name UCI_Chess960 type check default false".to_string());
 
                 res.push("uciok".to_string());
                 self.state = UciState::Ready;
@@ -87,8 +403,6 @@ impl UciEngine {
                 res.push("readyok".to_string());
             }
             UciCommand::Position { fen, moves } => {
-                // Create temporary board to validate all moves atomically
-                let mut temp_board = Board::new();
                 let fen_str = if let Some(f) = fen {
                     f
                 } else {
@@ -96,19 +410,34 @@ impl UciEngine {
                     "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string()
                 };
 
-                // Set position from FEN
-                if let Err(e) = temp_board.set_from_fen(&fen_str) {
-                    res.push(format!("info string FEN parse error: {}", e));
-                    // Don't update self.board if FEN is invalid
-                    self.state = UciState::Ready;
-                    return res;
-                }
+                // Work on a temporary board to validate all moves atomically.
+                // The same game with more moves (the usual GUI command after
+                // every move) continues from the current board: only the new
+                // moves are played and the repetition history is kept.
+                let continues_game =
+                    self.game_fen.as_ref() == Some(&fen_str) && moves.starts_with(&self.game_moves);
+                let (mut temp_board, new_moves) = if continues_game {
+                    (self.board.clone(), &moves[self.game_moves.len()..])
+                } else {
+                    let mut board = Board::new();
+                    // Set position from FEN; kings may only be missing in study mode
+                    if let Err(e) = board
+                        .set_from_fen(&fen_str)
+                        .and_then(|_| board.validate_kings(self.options.study_mode))
+                    {
+                        res.push(format!("info string FEN parse error: {}", e));
+                        // Don't update self.board if FEN is invalid
+                        self.state = UciState::Ready;
+                        return res;
+                    }
+                    (board, &moves[..])
+                };
 
-                // Apply all moves to temporary board first
-                for move_str in &moves {
+                // Apply the moves to the temporary board first
+                for move_str in new_moves {
                     match parse_uci_move(&mut temp_board, move_str) {
                         Ok(mv) => {
-                            let _undo = temp_board.make_move(mv);
+                            temp_board.make_move(mv);
                         }
                         Err(e) => {
                             res.push(format!("info string invalid move {}: {}", move_str, e));
@@ -119,8 +448,12 @@ impl UciEngine {
                     }
                 }
 
-                // All moves valid: commit the new position
+                // All moves valid: commit the new position, keeping only the
+                // history that can still produce a repetition
+                temp_board.prune_history();
                 self.board = temp_board;
+                self.game_fen = Some(fen_str);
+                self.game_moves = moves;
                 self.state = UciState::Ready;
             }
             UciCommand::Go {
@@ -130,44 +463,117 @@ impl UciEngine {
                 binc, // FIX Bug #4
                 movetime,
                 depth,
-                nodes: _nodes,
+                nodes,
                 mate: _mate,
-                movestogo: _movestogo,
+                movestogo,
                 infinite,
-                ponder: _ponder,
+                ponder,
+                searchmoves,
             } => {
-                // Compute time budget
+                // Checkmate or stalemate: there is no move to search for
+                if let Some(result) = self.board.game_result().filter(|r| r.is_terminal()) {
+                    res.push(format!("info string game over {}", result));
+                    res.push("bestmove 0000".to_string());
+                    self.state = UciState::Ready;
+                    return res;
+                }
+
+                // Root move restriction: unknown or illegal moves are reported and ignored
+                let mut search_moves = Vec::new();
+                for move_str in &searchmoves {
+                    match parse_uci_move(&mut self.board.clone(), move_str) {
+                        Ok(mv) => search_moves.push(mv),
+                        Err(e) => res.push(format!(
+                            "info string ignoring searchmove {}: {}",
+                            move_str, e
+                        )),
+                    }
+                }
+
+                // Playing on a clock: the engine is the side to move
+                if wtime.is_some() || btime.is_some() {
+                    self.engine_color = Some(self.board.side);
+                }
+
+                // Every limit of the command applies at once: the search ends
+                // at whichever of depth, nodes and time is reached first
+                let limits = crate::engine::SearchLimits {
+                    depth,
+                    movetime_ms: movetime,
+                    wtime_ms: wtime,
+                    btime_ms: btime,
+                    winc_ms: winc.unwrap_or(0),
+                    binc_ms: binc.unwrap_or(0),
+                    movestogo: movestogo.map(|x| x as u64),
+                    search_moves: Vec::new(),
+                    nodes,
+                };
+
+                // Compute time budget (the default time per move without
+                // movetime or a clock)
                 let side_white = self.board.side == crate::board::Color::White;
-                let time_alloc = crate::time::TimeManager::allocate_time(
-                    &crate::search::params::TimeManagement::new(),
-                    wtime,
-                    btime,
-                    winc,
-                    binc,
-                    movetime,
-                    _movestogo.map(|x| x as u64),
-                    side_white,
-                    self.options.move_overhead_ms,
-                );
+                let game_phase = self.board.phase();
+                let timed_alloc =
+                    limits.time_allocation(&self.board, self.options.move_overhead_ms);
+                let time_alloc = timed_alloc.unwrap_or_else(|| self.default_time_allocation());
+                self.log.note(&format!(
+                    "go: phase {}, soft {} ms, hard {} ms",
+                    game_phase, time_alloc.soft_ms, time_alloc.hard_ms
+                ));
+                let clock = if side_white { wtime } else { btime };
+                if clock.is_some()
+                    && movetime.is_none()
+                    && crate::time::TimeManager::is_last_move_before_control(
+                        movestogo.map(|x| x as u64),
+                    )
+                {
+                    res.push(format!(
+                        "info string movestogo 1: last move before time control, allocating {} ms",
+                        time_alloc.soft_ms
+                    ));
+                }
 
                 // Cancel any pending ponder timer before starting a new search
-                if let Some(cancel) = self.ponder_timer_cancel.take() {
-                    cancel.store(true, Ordering::Relaxed);
+                res.extend(self.end_ponder_timer().into_iter().flatten());
+
+                // Options (e.g. UCI_Chess960) may have changed since the last search
+                self.install_observer();
+                res.extend(self.load_experience());
+                let experience_move = self.experience_move();
+
+                // Save the limits for a potential ponderhit later
+                self.last_limits = limits.clone();
+
+                if ponder {
+                    self.ponder_clock.start(crate::time::Instant::now());
+                } else {
+                    self.ponder_clock.stop();
                 }
 
-                // Save clock parameters for potential ponderhit later
-                self.last_wtime = wtime;
-                self.last_btime = btime;
-                self.last_winc = winc;
-                self.last_binc = binc;
-                self.last_movetime = movetime;
-                self.last_movestogo = _movestogo.map(|x| x as u64);
+                // Fixed nodes: `go nodes` and the NodesLimit option, the
+                // smaller one wins
+                let node_limit = [nodes.unwrap_or(0), self.options.nodes_limit]
+                    .into_iter()
+                    .filter(|&n| n > 0)
+                    .min()
+                    .unwrap_or(0);
 
-                if infinite || _ponder {
+                if infinite || ponder {
                     // ASYNC MODE: go infinite / ponder - start search in background
                     let params = crate::search::SearchParams::new()
-                        .max_depth(99)
-                        .time_limit(0); // No time limit; wait for stop/ponderhit
+                        .max_depth(crate::search::MAX_DEPTH)
+                        .time_limit(0) // No time limit; wait for stop/ponderhit
+                        .node_limit(node_limit)
+                        .nps_limit(self.options.nps_limit)
+                        .search_moves(search_moves)
+                        .experience_move(experience_move)
+                        .eval_weights(self.eval_weights())
+                        .eval_params(self.options.eval_params)
+                        .contempt(self.options.contempt)
+                        .move_variety(self.options.move_variety, self.variety_rng.next_u64())
+                        .engine_color(self.engine_color)
+                        .eval_cache_mb(self.options.eval_cache_mb)
+                        .smp_mode(self.options.smp_mode);
 
                     if let Some(ref tm) = self.thread_mgr {
                         let job = crate::search::thread_mgr::SearchJob {
@@ -176,7 +582,7 @@ impl UciEngine {
                         };
                         tm.start_async_search(job);
                         self.async_search_active = true;
-                        self.state = if _ponder {
+                        self.state = if ponder {
                             UciState::Pondering
                         } else {
                             UciState::Thinking
@@ -194,23 +600,53 @@ impl UciEngine {
                     // UCI semantics: depth is always a maximum depth limit.
                     // Time limits (wtime/btime/movetime) are additional constraints.
                     // When only depth is specified, search runs until depth is reached.
-                    let max_search_depth = depth.map(|d| d.clamp(1, 99)).unwrap_or(99);
-
-                    // If depth is specified WITHOUT time limits, use unlimited time
-                    // (depth will control the search). Otherwise, use time allocation.
-                    let effective_time = if depth.is_some()
-                        && movetime.is_none()
-                        && wtime.is_none()
-                        && btime.is_none()
+                    let max_search_depth = limits.max_depth();
+
+                    // If depth or nodes is specified WITHOUT time limits, use
+                    // unlimited time (they control the search). Otherwise, use
+                    // time allocation.
+                    let mut search_moves = search_moves;
+                    let effective_time = if timed_alloc.is_none()
+                        && (depth.is_some() || nodes.is_some())
+                    {
+                        // 0 = no time limit, depth controls search
+                        crate::time::TimeAllocation::fixed(0)
+                    } else if movetime.is_none() && timed_alloc.is_some() && search_moves.is_empty()
                     {
-                        0 // 0 = no time limit, depth controls search
+                        // Clock-based game: skip thinking if the answer is already known
+                        let precomputed = self.precomputed_root_move();
+                        if precomputed == crate::time::PrecomputedMove::Book {
+                            // Only the book move is searched, for its score and PV
+                            search_moves.extend(self.book_move());
+                        }
+                        let instant = crate::time::TimeManager::adjust_for_precomputed(
+                            time_alloc.soft_ms,
+                            precomputed,
+                            self.options.min_think_ms,
+                        );
+                        if instant < time_alloc.soft_ms {
+                            crate::time::TimeAllocation::fixed(instant)
+                        } else {
+                            time_alloc
+                        }
                     } else {
                         time_alloc
                     };
 
                     let params = crate::search::SearchParams::new()
                         .max_depth(max_search_depth)
-                        .time_limit(effective_time);
+                        .time_allocation(effective_time)
+                        .node_limit(node_limit)
+                        .nps_limit(self.options.nps_limit)
+                        .search_moves(search_moves)
+                        .experience_move(experience_move)
+                        .eval_weights(self.eval_weights())
+                        .eval_params(self.options.eval_params)
+                        .contempt(self.options.contempt)
+                        .move_variety(self.options.move_variety, self.variety_rng.next_u64())
+                        .engine_color(self.engine_color)
+                        .eval_cache_mb(self.options.eval_cache_mb)
+                        .smp_mode(self.options.smp_mode);
 
                     // Submit job to persistent thread manager
                     if let Some(tm) = &self.thread_mgr {
@@ -219,8 +655,18 @@ impl UciEngine {
                             params,
                         };
                         let search_start = Instant::now();
-                        let result = tm.submit_job(job);
+                        let mut result = tm.submit_job(job);
                         let search_time_ms = search_start.elapsed().as_millis() as u64;
+                        res.extend(self.worker_panic_lines());
+                        res.extend(self.verify_best_move(&mut result));
+                        self.log.note(&format!(
+                            "search done: depth {}, nodes {}, {} ms (allocated {} ms)",
+                            result.completed_depth,
+                            result.nodes,
+                            search_time_ms,
+                            effective_time.soft_ms
+                        ));
+                        self.record_experience(&result);
 
                         // Build UCI info line with full search data
                         let mut info_parts = vec![
@@ -229,24 +675,11 @@ impl UciEngine {
                         ];
 
                         // Score: cp or mate
-                        if result.score >= MATE_THRESHOLD {
-                            let mate_plies = MATE - result.score;
-                            let mate_moves = (mate_plies + 1) / 2;
-                            info_parts.push(format!("score mate {}", mate_moves));
-                        } else if result.score <= -MATE_THRESHOLD {
-                            let mate_plies = MATE + result.score;
-                            let mate_moves = -(mate_plies / 2);
-                            info_parts.push(format!("score mate {}", mate_moves));
-                        } else {
-                            info_parts.push(format!("score cp {}", result.score));
-                        }
+                        info_parts.push(format_score(result.score));
 
                         info_parts.push(format!("nodes {}", result.nodes));
-                        if search_time_ms > 0 {
-                            info_parts.push(format!(
-                                "nps {}",
-                                result.nodes * 1000 / search_time_ms
-                            ));
+                        if let Some(nps) = (result.nodes * 1000).checked_div(search_time_ms) {
+                            info_parts.push(format!("nps {}", nps));
                         }
                         info_parts.push(format!("time {}", search_time_ms));
                         info_parts.push(format!("hashfull {}", result.hashfull));
@@ -255,22 +688,25 @@ impl UciEngine {
                             let pv_str = result
                                 .pv
                                 .iter()
-                                .map(|&m| move_to_uci(m))
+                                .map(|&m| move_to_uci_with(m, self.options.chess960))
                                 .collect::<Vec<_>>()
                                 .join(" ");
                             info_parts.push(format!("pv {}", pv_str));
                         }
 
                         res.push(format!("info {}", info_parts.join(" ")));
+                        if self.options.report_root_moves {
+                            res.extend(root_move_info_lines(&result, self.options.chess960));
+                        }
 
-                        if result.best_move == 0 {
+                        if result.best_move == Move::NONE {
                             res.push(
                                 "info string position is terminal (checkmate or stalemate)"
                                     .to_string(),
                             );
                         }
 
-                        res.push(format!("bestmove {}", move_to_uci(result.best_move)));
+                        res.push(self.bestmove_line(&result));
                     } else {
                         res.push("info string no thread manager available".to_string());
                         res.push("bestmove 0000".to_string());
@@ -280,53 +716,23 @@ impl UciEngine {
                 }
             }
             UciCommand::Stop => {
-                // Cancel any pending ponder timer
-                if let Some(cancel) = self.ponder_timer_cancel.take() {
-                    cancel.store(true, Ordering::Relaxed);
-                }
+                // Cancel any pending ponder timer; if it already stopped the
+                // search, it has also answered it
+                res.extend(self.end_ponder_timer().into_iter().flatten());
+                self.ponder_clock.stop();
 
                 if self.async_search_active {
                     // Stop async search (go infinite mode) and send bestmove
                     if let Some(ref tm) = self.thread_mgr {
                         tm.stop_current_job();
 
-                        // Wait for result with timeout (500ms should be enough for graceful stop)
-                        if let Some(result) = tm.wait_async_result(500) {
-                            let mut info_parts = vec![
-                                format!("depth {}", result.completed_depth),
-                                format!("seldepth {}", result.seldepth),
-                            ];
-                            if result.score >= MATE_THRESHOLD {
-                                let mate_plies = MATE - result.score;
-                                let mate_moves = (mate_plies + 1) / 2;
-                                info_parts.push(format!("score mate {}", mate_moves));
-                            } else if result.score <= -MATE_THRESHOLD {
-                                let mate_plies = MATE + result.score;
-                                let mate_moves = -(mate_plies / 2);
-                                info_parts.push(format!("score mate {}", mate_moves));
-                            } else {
-                                info_parts.push(format!("score cp {}", result.score));
-                            }
-                            info_parts.push(format!("nodes {}", result.nodes));
-                            if result.nps > 0 {
-                                info_parts.push(format!("nps {}", result.nps));
-                            }
-                            info_parts.push(format!("hashfull {}", result.hashfull));
-                            if !result.pv.is_empty() {
-                                let pv_str = result
-                                    .pv
-                                    .iter()
-                                    .map(|&m| move_to_uci(m))
-                                    .collect::<Vec<_>>()
-                                    .join(" ");
-                                info_parts.push(format!("pv {}", pv_str));
-                            }
-                            res.push(format!("info {}", info_parts.join(" ")));
-                            res.push(format!("bestmove {}", move_to_uci(result.best_move)));
-                        } else {
-                            // Timeout: search didn't complete in time, return null move
-                            res.push("info string search timeout on stop".to_string());
-                            res.push("bestmove 0000".to_string());
+                        // Wait for result with timeout (500ms should be enough for graceful
+                        // stop); past it the main worker's best move so far is played
+                        let mut result = tm.wait_async_result(500);
+                        res.extend(self.worker_panic_lines());
+                        res.extend(self.report().stopped_search_lines(result.as_mut()));
+                        if let Some(result) = result {
+                            self.record_experience(&result);
                         }
                     }
 
@@ -341,7 +747,16 @@ impl UciEngine {
                 self.state = UciState::Ready;
             }
             UciCommand::UciNewGame => {
+                // The previous game is over: learn from it before forgetting it
+                res.extend(self.save_experience());
+                res.extend(self.load_experience());
+                // The hash is kept between the moves of a game, not across games
+                if let Some(tm) = &self.thread_mgr {
+                    tm.clear_tt();
+                }
                 // Reset to starting position
+                self.reset_game();
+                self.engine_color = None;
                 self.board = Board::new();
                 let _ = self
                     .board
@@ -349,19 +764,24 @@ impl UciEngine {
                 self.state = UciState::Ready;
             }
             UciCommand::SetOption { name, value } => {
+                // Options such as StudyMode change how positions are validated
+                self.reset_game();
                 // Dynamic setoption: reconfigure thread manager if Threads/Hash changed
                 match name.as_str() {
                     "Threads" => {
                         if let Some(v) = value {
                             if let Ok(n) = v.parse::<usize>() {
                                 if n > 0 && n <= 256 {
-                                    // Stop old thread manager and create new one with updated thread count
-                                    if let Some(old_tm) = self.thread_mgr.take() {
-                                        old_tm.stop();
+                                    // Resize the worker pool in place: the TT and
+                                    // the observer are kept
+                                    match &self.thread_mgr {
+                                        Some(tm) => tm.set_threads(n),
+                                        None => {
+                                            let hash_mb = self.options.hash as usize;
+                                            self.thread_mgr =
+                                                Some(self.new_thread_manager(n, hash_mb));
+                                        }
                                     }
-                                    let hash_mb = self.options.hash as usize;
-                                    self.thread_mgr =
-                                        Some(crate::search::ThreadManager::new(n, hash_mb));
                                     self.options.threads = n as u8;
                                     res.push(format!("info string Threads set to {}", n));
                                 } else {
@@ -379,13 +799,15 @@ impl UciEngine {
                         if let Some(v) = value {
                             if let Ok(mb) = v.parse::<usize>() {
                                 if (1..=4096).contains(&mb) {
-                                    // Stop old thread manager and create new one with updated TT size
-                                    if let Some(old_tm) = self.thread_mgr.take() {
-                                        old_tm.stop();
+                                    // Reallocate the TT in place: the workers are kept
+                                    match &self.thread_mgr {
+                                        Some(tm) => tm.resize_tt(mb),
+                                        None => {
+                                            let threads = self.options.threads as usize;
+                                            self.thread_mgr =
+                                                Some(self.new_thread_manager(threads, mb));
+                                        }
                                     }
-                                    let threads = self.options.threads as usize;
-                                    self.thread_mgr =
-                                        Some(crate::search::ThreadManager::new(threads, mb));
                                     self.options.hash = mb as u64;
                                     res.push(format!("info string Hash set to {} MB", mb));
                                 } else {
@@ -399,16 +821,75 @@ impl UciEngine {
                             }
                         }
                     }
-                    "MoveOverhead" => {
-                        if let Some(v) = value {
-                            if let Ok(ms) = v.parse::<u64>() {
-                                self.options.move_overhead_ms = ms;
-                                res.push(format!("info string MoveOverhead set to {} ms", ms));
+                    "Clear Hash" => {
+                        if let Some(tm) = &self.thread_mgr {
+                            tm.clear_tt();
+                        }
+                        res.push("info string Hash cleared".to_string());
+                    }
+                    "Move Overhead" | "MoveOverhead" => {
+                        match self.options.set_option(&name, value.as_deref()) {
+                            Ok(()) => res.push(format!(
+                                "info string Move Overhead set to {} ms",
+                                self.options.move_overhead_ms
+                            )),
+                            Err(e) => res.push(format!("info string error: {}", e)),
+                        }
+                    }
+                    "ThreadAffinity" => {
+                        let _ = self.options.set_option(&name, value.as_deref());
+                        let enabled = self.options.thread_affinity;
+                        if let Some(tm) = &self.thread_mgr {
+                            tm.set_thread_affinity(enabled);
+                            let cpus = tm.affinity_cpus().len();
+                            res.push(if !enabled {
+                                "info string ThreadAffinity off".to_string()
+                            } else if cpus == 0 {
+                                "info string ThreadAffinity not supported on this platform"
+                                    .to_string()
                             } else {
-                                res.push("info string error: invalid MoveOverhead value".to_string());
+                                format!(
+                                    "info string ThreadAffinity on: threads pinned to {} CPUs",
+                                    cpus.min(self.options.threads as usize)
+                                )
+                            });
+                        }
+                    }
+                    "Debug Log File" => {
+                        let _ = self.options.set_option(&name, value.as_deref());
+                        if self.options.debug_log {
+                            res.extend(self.open_debug_log());
+                        }
+                        res.push(format!("info string setoption {} = {:?}", name, value));
+                    }
+                    "VarietySeed" => {
+                        match self.options.set_option(&name, value.as_deref()) {
+                            Ok(()) => {
+                                // A new seed replays the same sequence of choices
+                                self.variety_rng =
+                                    VarietyRng::seeded_or_clock(self.options.variety_seed);
+                                res.push(format!("info string setoption {} = {:?}", name, value));
                             }
+                            Err(e) => res.push(format!("info string error: {}", e)),
                         }
                     }
+                    _ if self.options.tune
+                        && crate::eval::EvalComponent::from_option_name(&name).is_some() =>
+                    {
+                        let _ = self.options.set_option(&name, value.as_deref());
+                        // Stored scores were searched with the old weights
+                        if let Some(tm) = &self.thread_mgr {
+                            tm.clear_tt();
+                        }
+                        res.push(format!("info string setoption {} = {:?}", name, value));
+                    }
+                    "ExperienceFile" => {
+                        // Keep what was learned so far, then switch books on the next go
+                        res.extend(self.save_experience());
+                        self.experience = None;
+                        let _ = self.options.set_option(&name, value.as_deref());
+                        res.push(format!("info string setoption {} = {:?}", name, value));
+                    }
                     _ => {
                         // Other options: use existing set_option method
                         let _ = self.options.set_option(&name, value.clone().as_deref());
@@ -416,50 +897,72 @@ impl UciEngine {
                     }
                 }
             }
+            UciCommand::Debug { on } => {
+                self.options.debug_log = on;
+                if on {
+                    res.extend(self.open_debug_log());
+                } else {
+                    self.log.note("debug log closed");
+                    self.log.close();
+                }
+            }
             UciCommand::PonderHit => {
                 if self.state == UciState::Pondering {
                     self.state = UciState::Thinking;
 
-                    // Schedule a stop after the allocated thinking time.
+                    // Schedule a stop, answered with `bestmove`, once the
+                    // allocated thinking time is used up, pondering included.
                     // Use the same clock parameters from the preceding go command.
-                    if let Some(ref tm) = self.thread_mgr {
-                        let side_white = self.board.side == crate::board::Color::White;
-                        let time_alloc = crate::time::TimeManager::allocate_time(
-                            &crate::search::params::TimeManagement::new(),
-                            self.last_wtime,
-                            self.last_btime,
-                            self.last_winc,
-                            self.last_binc,
-                            self.last_movetime,
-                            self.last_movestogo,
-                            side_white,
-                            self.options.move_overhead_ms,
-                        );
+                    if self.thread_mgr.is_some() {
+                        let time_alloc = self
+                            .last_limits
+                            .time_allocation(&self.board, self.options.move_overhead_ms)
+                            .unwrap_or_else(|| self.default_time_allocation());
+                        let time_left = self
+                            .ponder_clock
+                            .ponderhit(time_alloc, crate::time::Instant::now())
+                            .unwrap_or(time_alloc.soft_ms);
 
-                        // Cancel any previous timer
-                        if let Some(cancel) = self.ponder_timer_cancel.take() {
-                            cancel.store(true, Ordering::Relaxed);
-                        }
-
-                        let cancel = Arc::new(AtomicBool::new(false));
-                        self.ponder_timer_cancel = Some(cancel.clone());
-                        let flag = tm.get_stop_flag();
-                        std::thread::spawn(move || {
-                            std::thread::sleep(std::time::Duration::from_millis(time_alloc));
-                            if !cancel.load(Ordering::Relaxed) {
-                                flag.store(true, Ordering::Relaxed);
-                            }
-                        });
+                        res.extend(self.end_ponder_timer().into_iter().flatten());
+                        self.ponder_timer = Some(PonderTimer::start(
+                            time_left,
+                            self.report(),
+                            self.info_sink.clone(),
+                        ));
                     }
                 }
             }
             UciCommand::Quit => {
+                self.end_ponder_timer();
                 // Stop and join worker threads if present
                 if let Some(tm) = self.thread_mgr.take() {
                     tm.stop();
                 }
+                res.extend(self.save_experience());
                 self.running = false;
             }
+            UciCommand::Display => {
+                res.extend(display_lines(&self.board));
+            }
+            UciCommand::Eval => {
+                let params = self.options.eval_params;
+                let trace = crate::eval::evaluate_trace(&self.board, &params, &self.eval_weights());
+                res.extend(trace.to_string().lines().map(str::to_string));
+            }
+            UciCommand::Bench { depth } => {
+                let result = crate::bench::run_bench(depth.unwrap_or(crate::bench::BENCH_DEPTH));
+                res.extend(bench_info_lines(&result));
+            }
+            UciCommand::Hotspots { count } => {
+                let hotspots = crate::analysis::hotspots(&self.board, count);
+                for color in [Color::White, Color::Black] {
+                    res.push(hotspots_info_line(
+                        color,
+                        hotspots.side(color),
+                        self.options.chess960,
+                    ));
+                }
+            }
             UciCommand::Unknown(s) => {
                 res.push(format!("info string unknown command: {}", s));
             }
@@ -467,18 +970,339 @@ impl UciEngine {
         res
     }
 
+    /// Look up what is already known about the current root position.
+    ///
+    /// Only an exact TT entry whose best move is legal here counts (e.g. one
+    /// left behind by pondering); book lookups plug in here as well.
+    fn precomputed_root_move(&mut self) -> crate::time::PrecomputedMove {
+        use crate::search::tt::NodeType;
+        use crate::time::PrecomputedMove;
+
+        if !self.options.instant_moves {
+            return PrecomputedMove::None;
+        }
+        if self.book_move().is_some() {
+            return PrecomputedMove::Book;
+        }
+        let entry = match self
+            .thread_mgr
+            .as_ref()
+            .and_then(|tm| tm.probe_tt(self.board.zobrist))
+        {
+            Some(e) => e,
+            None => return PrecomputedMove::None,
+        };
+        if entry.node_type != NodeType::Exact || entry.best_move == Move::NONE {
+            return PrecomputedMove::None;
+        }
+        if !self.board.generate_moves().contains(&entry.best_move) {
+            return PrecomputedMove::None;
+        }
+        PrecomputedMove::TtExact { depth: entry.depth }
+    }
+
+    fn bestmove_line(&self, result: &SearchResult) -> String {
+        self.report().bestmove_line(result)
+    }
+
     pub fn is_running(&self) -> bool {
         self.running
     }
+
+    /// Tuning mode: advertise and accept the evaluation constants
+    /// ([`crate::eval::EvalParams`]) as UCI spin options, and the `Eval...`
+    /// check options that switch single terms off
+    pub fn enable_tuning(&mut self) {
+        self.options.tune = true;
+    }
+
+    /// Current position, as set by the last `position` command
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+}
+
+/// Position and options a search result is reported with, detached from the
+/// engine so the ponderhit timer can answer a search on its own
+struct ResultReport {
+    board: Board,
+    chess960: bool,
+    report_root_moves: bool,
+    log: DebugLog,
+    jobs: Option<JobHandle>,
+}
+
+impl ResultReport {
+    /// Check the search's best move against the legal moves before it is
+    /// played (see [`legal_best_move`]); the diagnostic also goes to the debug log
+    fn verify_best_move(&self, result: &mut SearchResult) -> Option<String> {
+        let line = legal_best_move(&self.board, result, self.chess960)?;
+        self.log.note(&line);
+        Some(line)
+    }
+
+    /// Lines answering a stopped search: the final `info` line and
+    /// `bestmove`, or `bestmove 0000` when no job was running
+    fn stopped_search_lines(&self, result: Option<&mut SearchResult>) -> Vec<String> {
+        let Some(result) = result else {
+            // No job was running: nothing to play
+            return vec![
+                "info string no search to stop".to_string(),
+                "bestmove 0000".to_string(),
+            ];
+        };
+        let mut lines: Vec<String> = self.verify_best_move(result).into_iter().collect();
+        let mut info_parts = vec![
+            format!("depth {}", result.completed_depth),
+            format!("seldepth {}", result.seldepth),
+        ];
+        info_parts.push(format_score(result.score));
+        info_parts.push(format!("nodes {}", result.nodes));
+        if result.nps > 0 {
+            info_parts.push(format!("nps {}", result.nps));
+        }
+        info_parts.push(format!("hashfull {}", result.hashfull));
+        if !result.pv.is_empty() {
+            let pv_str = result
+                .pv
+                .iter()
+                .map(|&m| move_to_uci_with(m, self.chess960))
+                .collect::<Vec<_>>()
+                .join(" ");
+            info_parts.push(format!("pv {}", pv_str));
+        }
+        lines.push(format!("info {}", info_parts.join(" ")));
+        if self.report_root_moves {
+            lines.extend(root_move_info_lines(result, self.chess960));
+        }
+        lines.push(self.bestmove_line(result));
+        lines
+    }
+
+    /// `bestmove X ponder Y`, Y being the expected reply: the second PV move,
+    /// or the TT move after X when the PV stops at X. Y is only sent if it is
+    /// legal after X.
+    fn bestmove_line(&self, result: &SearchResult) -> String {
+        let best = move_to_uci_with(result.best_move, self.chess960);
+        if result.best_move == Move::NONE {
+            return format!("bestmove {}", best);
+        }
+        let mut board = self.board.clone();
+        board.make_move(result.best_move);
+        let ponder = match result.pv.get(1) {
+            Some(&mv) if result.pv[0] == result.best_move => Some(mv),
+            _ => self
+                .jobs
+                .as_ref()
+                .and_then(|jobs| jobs.probe_tt(board.zobrist))
+                .map(|entry| entry.best_move),
+        }
+        .filter(|&mv| mv != Move::NONE && board.generate_moves().contains(&mv));
+        match ponder {
+            Some(mv) => format!(
+                "bestmove {} ponder {}",
+                best,
+                move_to_uci_with(mv, self.chess960)
+            ),
+            None => format!("bestmove {}", best),
+        }
+    }
+}
+
+/// Thread that ends the search once the time left at ponderhit is used up,
+/// then answers it through the info sink like `stop` would
+struct PonderTimer {
+    cancel: mpsc::Sender<()>,
+    /// The stopped search's result and the lines not sent through the info
+    /// sink; None when cancelled before the deadline
+    thread: JoinHandle<Option<(Option<SearchResult>, Vec<String>)>>,
+}
+
+impl PonderTimer {
+    fn start(time_left_ms: u64, report: ResultReport, sink: Option<InfoSink>) -> Self {
+        let (cancel, cancelled) = mpsc::channel();
+        let thread = std::thread::spawn(move || {
+            match cancelled.recv_timeout(std::time::Duration::from_millis(time_left_ms)) {
+                Err(RecvTimeoutError::Timeout) => {}
+                Ok(()) | Err(RecvTimeoutError::Disconnected) => return None,
+            }
+            let jobs = report.jobs.as_ref()?;
+            jobs.stop_job();
+            let mut result = jobs.wait_result(500);
+            let mut lines = report.stopped_search_lines(result.as_mut());
+            if let Some(sink) = sink {
+                for line in lines.drain(..) {
+                    // Like streamed lines, these bypass `process_line`
+                    report.log.output(&line);
+                    sink(line);
+                }
+            }
+            Some((result, lines))
+        });
+        Self { cancel, thread }
+    }
+}
+
+/// One `info string bench` line per position, then the totals (node signature,
+/// time, NPS)
+pub fn bench_info_lines(result: &crate::bench::BenchResult) -> Vec<String> {
+    let mut lines: Vec<String> = result
+        .positions
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            format!(
+                "info string bench position {} nodes {} fen {}",
+                i + 1,
+                p.nodes,
+                p.fen
+            )
+        })
+        .collect();
+    lines.push(format!(
+        "info string bench depth {} nodes {} time {} nps {}",
+        result.depth,
+        result.nodes(),
+        result.elapsed.as_millis(),
+        result.nps()
+    ));
+    lines
+}
+
+/// Output of `d`: the board as a diagram (White at the bottom), then its FEN,
+/// Zobrist key, castling rights, en-passant square and legal move count
+fn display_lines(board: &Board) -> Vec<String> {
+    const SEPARATOR: &str = " +---+---+---+---+---+---+---+---+";
+    let mut lines = vec![SEPARATOR.to_string()];
+    for rank in (0..8).rev() {
+        let squares: String = (0..8)
+            .map(|file| match board.piece_on(rank * 8 + file) {
+                Some((kind, color)) => format!(" {} |", piece_char(kind, color)),
+                None => "   |".to_string(),
+            })
+            .collect();
+        lines.push(format!(" |{} {}", squares, rank + 1));
+        lines.push(SEPARATOR.to_string());
+    }
+    lines.push("   a   b   c   d   e   f   g   h".to_string());
+    lines.push(String::new());
+
+    let fen = board.to_fen();
+    let fields: Vec<&str> = fen.split(' ').collect();
+    lines.push(format!("Fen: {}", fen));
+    lines.push(format!("Key: {:016X}", board.zobrist));
+    lines.push(format!("Castling: {}", fields[2]));
+    lines.push(format!("En passant: {}", fields[3]));
+    lines.push(format!(
+        "Legal moves: {}",
+        board.clone().generate_moves().len()
+    ));
+    lines
+}
+
+/// `info string hotspots <side> constrained <piece><square>:<safe squares> ...
+/// threats <move>:<SEE gain> ...` for one side
+fn hotspots_info_line(
+    color: Color,
+    side: &crate::analysis::SideHotspots,
+    chess960: bool,
+) -> String {
+    let constrained: Vec<String> = side
+        .constrained
+        .iter()
+        .map(|p| {
+            let letter = match p.kind {
+                PieceKind::Pawn => 'P',
+                PieceKind::Knight => 'N',
+                PieceKind::Bishop => 'B',
+                PieceKind::Rook => 'R',
+                PieceKind::Queen => 'Q',
+                PieceKind::King => 'K',
+            };
+            format!("{}{}:{}", letter, square_to_uci(p.square), p.safe_mobility)
+        })
+        .collect();
+    let threats: Vec<String> = side
+        .threats
+        .iter()
+        .map(|t| format!("{}:{}", move_to_uci_with(t.mv, chess960), t.gain))
+        .collect();
+    let side_name = match color {
+        Color::White => "white",
+        Color::Black => "black",
+    };
+    format!(
+        "info string hotspots {} constrained {} threats {}",
+        side_name,
+        constrained.join(" "),
+        threats.join(" ")
+    )
+}
+
+/// Last line of defence against a search or TT bug reaching the GUI: a best
+/// move that is not legal in `board` is replaced by the first legal move and
+/// the PV built on it is dropped. Returns the `info string` diagnostic, None
+/// when the move is legal (or there is no legal move and none was chosen).
+fn legal_best_move(board: &Board, result: &mut SearchResult, chess960: bool) -> Option<String> {
+    let legal = board.clone().generate_moves();
+    if legal.contains(&result.best_move) || (legal.is_empty() && result.best_move == Move::NONE) {
+        return None;
+    }
+    let fallback = legal.first().copied().unwrap_or(Move::NONE);
+    let line = format!(
+        "info string bestmove {} is not legal here, playing {} instead",
+        move_to_uci_with(result.best_move, chess960),
+        move_to_uci_with(fallback, chess960)
+    );
+    result.best_move = fallback;
+    result.pv.clear();
+    Some(line)
+}
+
+/// Build one `info string rootmove ...` line per root move, most searched first.
+fn root_move_info_lines(result: &crate::search::SearchResult, chess960: bool) -> Vec<String> {
+    let mut root_moves = result.root_moves.clone();
+    root_moves.sort_by_key(|rm| std::cmp::Reverse(rm.nodes));
+    root_moves
+        .iter()
+        .map(|rm| {
+            let bound = if rm.lower_bound {
+                " lowerbound"
+            } else if rm.upper_bound {
+                " upperbound"
+            } else {
+                ""
+            };
+            format!(
+                "info string rootmove {} nodes {} tthits {} {}{}",
+                move_to_uci_with(rm.mv, chess960),
+                rm.nodes,
+                rm.tt_hits,
+                format_score(rm.score),
+                bound
+            )
+        })
+        .collect()
 }
 
 pub fn run_uci_loop() -> io::Result<()> {
+    run_uci_engine(UciEngine::new())
+}
+
+/// Run the UCI loop on stdin/stdout with a preconfigured engine (e.g. in
+/// tuning mode)
+pub fn run_uci_engine(mut engine: UciEngine) -> io::Result<()> {
     let stdin = io::stdin();
     let mut reader = stdin.lock();
     let stdout = io::stdout();
-    let mut writer = stdout.lock();
 
-    let mut engine = UciEngine::new();
+    // Search threads print their info lines as they go; stdout is locked per
+    // line there and per command here, so lines never interleave
+    engine.set_info_sink(Arc::new(|line: String| {
+        let mut out = io::stdout().lock();
+        let _ = writeln!(out, "{}", line);
+        let _ = out.flush();
+    }));
     let mut buf = String::new();
 
     while engine.is_running() {
@@ -493,10 +1317,10 @@ pub fn run_uci_loop() -> io::Result<()> {
             continue;
         }
 
-        let cmd = parse_uci_command(line);
-        let responses = engine.handle_command(cmd);
+        let responses = engine.process_line(line);
 
         // Write all responses
+        let mut writer = stdout.lock();
         for r in responses {
             writeln!(writer, "{}", r)?;
         }
@@ -509,6 +1333,141 @@ pub fn run_uci_loop() -> io::Result<()> {
 }
 
 pub fn process_uci_line(line: &str, engine: &mut UciEngine) -> Vec<String> {
-    let cmd = parse_uci_command(line);
-    engine.handle_command(cmd)
+    engine.process_line(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result_with(best_move: Move) -> SearchResult {
+        SearchResult {
+            best_move,
+            score: 0,
+            completed_depth: 1,
+            pv: vec![best_move],
+            nodes: 1,
+            nps: 0,
+            seldepth: 1,
+            hashfull: 0,
+            root_moves: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_illegal_best_move_is_replaced_by_a_legal_one() {
+        crate::init();
+        let mut board = Board::new();
+        board
+            .set_from_fen("4k3/8/8/8/8/8/3r4/4K3 w - - 0 1")
+            .unwrap();
+        let legal = board.generate_moves();
+        let mut result = result_with(legal[0]);
+        assert_eq!(legal_best_move(&board, &mut result, false), None);
+        assert_eq!(result.pv, vec![legal[0]]);
+
+        // Ke1-d1 walks into the rook; no move at all in a position with moves
+        let into_check = crate::board::new_move(4, 3, PieceKind::King, None, None, 0);
+        for bad in [into_check, Move::NONE] {
+            let mut result = result_with(bad);
+            let line = legal_best_move(&board, &mut result, false).unwrap();
+            assert!(line.starts_with("info string bestmove "), "{}", line);
+            assert!(legal.contains(&result.best_move));
+            assert!(result.pv.is_empty());
+        }
+
+        // Checkmated: no move is the right answer
+        board
+            .set_from_fen("6Qk/6Q1/8/8/8/8/8/K7 b - - 0 1")
+            .unwrap();
+        let mut result = result_with(Move::NONE);
+        assert_eq!(legal_best_move(&board, &mut result, false), None);
+    }
+
+    #[test]
+    fn test_hash_survives_moves_until_ucinewgame() {
+        crate::init();
+        let mut engine = UciEngine::new();
+        engine.handle_command(crate::uci::parse_uci_command("position startpos"));
+        let start_key = engine.board.zobrist;
+        let probe = |engine: &UciEngine| engine.thread_mgr.as_ref().unwrap().probe_tt(start_key);
+        for cmd in [
+            "go depth 4",
+            "position startpos moves e2e4 e7e5",
+            "go depth 4",
+        ] {
+            engine.handle_command(crate::uci::parse_uci_command(cmd));
+        }
+        assert!(probe(&engine).is_some());
+
+        engine.handle_command(UciCommand::UciNewGame);
+        assert!(probe(&engine).is_none());
+    }
+
+    #[test]
+    fn test_go_infinite_keeps_to_the_node_limit() {
+        crate::init();
+        let mut engine = UciEngine::new();
+        engine.process_line("position startpos");
+        assert!(engine.process_line("go infinite nodes 1000").is_empty());
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        let res = engine.process_line("stop");
+        let nodes: u64 = res
+            .iter()
+            .find_map(|s| s.strip_prefix("info depth "))
+            .and_then(|info| info.split(" nodes ").nth(1))
+            .and_then(|rest| rest.split_whitespace().next())
+            .and_then(|n| n.parse().ok())
+            .unwrap_or_else(|| panic!("{:?}", res));
+        assert!(nodes <= 1000, "{:?}", res);
+        assert!(res.iter().any(|s| s.starts_with("bestmove ")));
+    }
+
+    #[test]
+    fn test_ponderhit_stops_on_the_time_left_after_pondering() {
+        crate::init();
+        let mut engine = UciEngine::new();
+        let sent = Arc::new(std::sync::Mutex::new(Vec::<String>::new()));
+        let sink = sent.clone();
+        engine.set_info_sink(Arc::new(move |line: String| {
+            sink.lock().unwrap().push(line)
+        }));
+        let bestmove = || {
+            sent.lock()
+                .unwrap()
+                .iter()
+                .find(|s| s.starts_with("bestmove "))
+                .cloned()
+        };
+        engine.process_line("position startpos moves e2e4");
+        engine.process_line("go ponder wtime 2000 btime 2000");
+        assert_eq!(engine.state, UciState::Pondering);
+
+        // Pondering has no deadline of its own, but it uses up the move's
+        // budget: past it, ponderhit ends the search at once and answers it
+        // without waiting for `stop`
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        assert!(bestmove().is_none());
+        let hit = std::time::Instant::now();
+        assert!(engine.process_line("ponderhit").is_empty());
+        assert_eq!(engine.state, UciState::Thinking);
+        let best = loop {
+            if let Some(best) = bestmove() {
+                break best;
+            }
+            assert!(hit.elapsed() < std::time::Duration::from_millis(1000));
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        };
+        let mv = best.split_whitespace().nth(1).unwrap();
+        assert!(
+            parse_uci_move(&mut engine.board.clone(), mv).is_ok(),
+            "{}",
+            best
+        );
+
+        // The search is over: `stop` has nothing left to answer
+        let res = engine.process_line("stop");
+        assert!(!res.iter().any(|s| s.starts_with("bestmove ")), "{:?}", res);
+        assert_eq!(engine.state, UciState::Ready);
+    }
 }
@@ -1,9 +1,15 @@
 //! UCI protocol implementation for Scacchista.
 
+pub mod info;
+pub mod log;
 pub mod r#loop;
 pub mod options;
 pub mod parser;
 
+pub use info::{InfoSink, InfoStreamer};
+pub use log::DebugLog;
 pub use options::UciOptions;
 pub use parser::{parse_uci_command, UciCommand};
-pub use r#loop::{process_uci_line, run_uci_loop, UciEngine, UciState};
+pub use r#loop::{
+    bench_info_lines, process_uci_line, run_uci_engine, run_uci_loop, UciEngine, UciState,
+};
@@ -1,5 +1,12 @@
 //! UCI options configuration system for Scacchista
 
+/// Default `Move Overhead` in milliseconds
+pub const DEFAULT_MOVE_OVERHEAD_MS: u64 = 10;
+/// Largest accepted `Move Overhead` in milliseconds
+pub const MAX_MOVE_OVERHEAD_MS: u64 = 5000;
+/// Largest accepted `MoveVariety` in centipawns
+pub const MAX_MOVE_VARIETY: i16 = 100;
+
 /// UCI option types
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum OptionType {
@@ -24,6 +31,15 @@ pub struct UciOptions {
     /// Number of search threads
     pub threads: u8,
 
+    /// Static evaluation cache size in MB per search thread (0 = disabled)
+    pub eval_cache_mb: usize,
+
+    /// How the search threads share the work: lazy SMP or root splitting
+    pub smp_mode: crate::search::SmpMode,
+
+    /// Pin each search thread to its own CPU, NUMA node by node
+    pub thread_affinity: bool,
+
     /// Syzygy tablebase path
     pub syzygy_path: Option<String>,
 
@@ -36,12 +52,36 @@ pub struct UciOptions {
     /// Chess style: Normal, Tal, Petrosian
     pub chess_style: String,
 
+    /// Draw score shift in centipawns against the engine's own color
+    /// (positive = avoid draws), added to the style's contempt
+    pub contempt: i16,
+
+    /// Root moves within this many centipawns of the best one may be played
+    /// instead (0 = always the best move)
+    pub move_variety: i16,
+
+    /// Seed of the `MoveVariety` randomness (0 = seeded from the clock)
+    pub variety_seed: u64,
+
+    /// Tuning mode (`scacchista tune`): evaluation constants are UCI options
+    pub tune: bool,
+
+    /// Evaluation constants, set through UCI only in tuning mode
+    pub eval_params: crate::eval::EvalParams,
+
+    /// Evaluation terms switched off through their `Eval...` check options
+    /// (tuning mode only), for A/B self-play of each term
+    pub disabled_eval: Vec<crate::eval::EvalComponent>,
+
     /// Whether to enable UCI_AnalysisMode by default
     pub analyze_mode: bool,
 
-    /// Debug logging enabled
+    /// Debug logging enabled (`debug on`)
     pub debug_log: bool,
 
+    /// File the debug log is appended to (None = `DEFAULT_DEBUG_LOG_FILE`)
+    pub debug_log_path: Option<String>,
+
     /// Engine name
     pub engine_name: String,
 
@@ -50,6 +90,27 @@ pub struct UciOptions {
 
     /// Move overhead in milliseconds (network/GUI lag compensation)
     pub move_overhead_ms: u64,
+
+    /// Report per-root-move nodes/TT hits/score after each search
+    pub report_root_moves: bool,
+
+    /// Move almost instantly when the root move is already known (book / deep TT)
+    pub instant_moves: bool,
+
+    /// Think time in milliseconds used for such instant moves
+    pub min_think_ms: u64,
+
+    /// Study/puzzle mode: accept positions where one or both kings are missing
+    pub study_mode: bool,
+
+    /// UCI_Chess960: castling is written king-takes-rook (e1h1) instead of e1g1
+    pub chess960: bool,
+
+    /// Most nodes searched per move (0 = unlimited); `go nodes` may lower it
+    pub nodes_limit: u64,
+
+    /// Most nodes per second per search thread (0 = unlimited)
+    pub nps_limit: u64,
 }
 
 impl Default for UciOptions {
@@ -57,15 +118,32 @@ impl Default for UciOptions {
         Self {
             hash: 16,
             threads: 1,
+            eval_cache_mb: crate::search::eval_cache::DEFAULT_EVAL_CACHE_MB,
+            smp_mode: crate::search::SmpMode::LazySmp,
+            thread_affinity: false,
             syzygy_path: None,
             use_experience_book: true,
             experience_book_path: None,
             chess_style: "Normal".to_string(),
+            contempt: 0,
+            move_variety: 0,
+            variety_seed: 0,
+            tune: false,
+            eval_params: crate::eval::EvalParams::DEFAULT,
+            disabled_eval: Vec::new(),
             analyze_mode: false,
             debug_log: false,
+            debug_log_path: None,
             engine_name: "Scacchista".to_string(),
             author: "Claude Code".to_string(),
-            move_overhead_ms: 10,
+            move_overhead_ms: DEFAULT_MOVE_OVERHEAD_MS,
+            report_root_moves: false,
+            instant_moves: true,
+            min_think_ms: 30,
+            study_mode: false,
+            chess960: false,
+            nodes_limit: 0,
+            nps_limit: 0,
         }
     }
 }
@@ -97,6 +175,20 @@ impl UciOptions {
                     }
                 }
             }
+            "EvalCache" => {
+                if let Some(v_str) = value {
+                    if let Ok(mb) = v_str.parse::<usize>() {
+                        self.eval_cache_mb = mb.min(crate::search::eval_cache::MAX_EVAL_CACHE_MB);
+                    } else {
+                        return Err(format!("Invalid numeric value for EvalCache: {}", v_str));
+                    }
+                }
+            }
+            "Debug Log File" => {
+                self.debug_log_path = value
+                    .filter(|s| !s.is_empty() && *s != "<empty>")
+                    .map(|s| s.to_string());
+            }
             "SyzygyPath" => {
                 self.syzygy_path = value.map(|s| s.to_string());
             }
@@ -106,11 +198,146 @@ impl UciOptions {
                     self.use_experience_book = v;
                 }
             }
+            "ExperienceFile" => {
+                self.experience_book_path = value
+                    .filter(|s| !s.is_empty() && *s != "<empty>")
+                    .map(|s| s.to_string());
+            }
+            "ReportRootMoves" => {
+                if let Some(v_str) = value {
+                    self.report_root_moves =
+                        matches!(v_str.to_lowercase().as_str(), "true" | "1" | "yes");
+                }
+            }
+            "InstantMoves" => {
+                if let Some(v_str) = value {
+                    self.instant_moves =
+                        matches!(v_str.to_lowercase().as_str(), "true" | "1" | "yes");
+                }
+            }
+            "MinThinkTime" => {
+                if let Some(v_str) = value {
+                    if let Ok(v) = v_str.parse::<u64>() {
+                        self.min_think_ms = v.min(5000);
+                    }
+                }
+            }
+            "ThreadAffinity" => {
+                if let Some(v_str) = value {
+                    self.thread_affinity =
+                        matches!(v_str.to_lowercase().as_str(), "true" | "1" | "yes");
+                }
+            }
+            "Ponder" => {
+                // Only tells the engine the GUI may send `go ponder`, which
+                // always works: nothing to configure
+            }
+            "StudyMode" => {
+                if let Some(v_str) = value {
+                    self.study_mode = matches!(v_str.to_lowercase().as_str(), "true" | "1" | "yes");
+                }
+            }
+            "UCI_Chess960" => {
+                if let Some(v_str) = value {
+                    self.chess960 = matches!(v_str.to_lowercase().as_str(), "true" | "1" | "yes");
+                }
+            }
+            "Contempt" => {
+                if let Some(v_str) = value {
+                    if let Ok(v) = v_str.parse::<i16>() {
+                        self.contempt = v.clamp(-100, 100);
+                    } else {
+                        return Err(format!("Invalid numeric value for Contempt: {}", v_str));
+                    }
+                }
+            }
+            "MoveVariety" => {
+                if let Some(v_str) = value {
+                    if let Ok(v) = v_str.parse::<i16>() {
+                        self.move_variety = v.clamp(0, MAX_MOVE_VARIETY);
+                    } else {
+                        return Err(format!("Invalid numeric value for MoveVariety: {}", v_str));
+                    }
+                }
+            }
+            "VarietySeed" => {
+                if let Some(v_str) = value {
+                    if let Ok(v) = v_str.parse::<u64>() {
+                        self.variety_seed = v;
+                    } else {
+                        return Err(format!("Invalid numeric value for VarietySeed: {}", v_str));
+                    }
+                }
+            }
+            "NodesLimit" => {
+                if let Some(v_str) = value {
+                    if let Ok(v) = v_str.parse::<u64>() {
+                        self.nodes_limit = v;
+                    } else {
+                        return Err(format!("Invalid numeric value for NodesLimit: {}", v_str));
+                    }
+                }
+            }
+            "NPS Limit" => {
+                if let Some(v_str) = value {
+                    if let Ok(v) = v_str.parse::<u64>() {
+                        self.nps_limit = v;
+                    } else {
+                        return Err(format!("Invalid numeric value for NPS Limit: {}", v_str));
+                    }
+                }
+            }
+            // "MoveOverhead" is the name used by older versions
+            "Move Overhead" | "MoveOverhead" => {
+                if let Some(v_str) = value {
+                    if let Ok(ms) = v_str.parse::<u64>() {
+                        self.move_overhead_ms = ms.min(MAX_MOVE_OVERHEAD_MS);
+                    } else {
+                        return Err(format!(
+                            "Invalid numeric value for Move Overhead: {}",
+                            v_str
+                        ));
+                    }
+                }
+            }
+            "SMPMode" => {
+                if let Some(v_str) = value {
+                    match crate::search::SmpMode::from_name(v_str) {
+                        Some(mode) => self.smp_mode = mode,
+                        None => return Err(format!("Invalid SMPMode value: {}", v_str)),
+                    }
+                }
+            }
             "Style" => {
                 if let Some(v_str) = value {
+                    if crate::eval::EvalWeights::for_style(v_str).is_none() {
+                        return Err(format!("Invalid Style value: {}", v_str));
+                    }
                     self.chess_style = v_str.to_string();
                 }
             }
+            _ if self.tune && self.eval_params.get(name).is_some() => {
+                if let Some(v_str) = value {
+                    match v_str.parse::<i32>() {
+                        Ok(v) => {
+                            self.eval_params.set(name, v);
+                        }
+                        Err(_) => {
+                            return Err(format!("Invalid numeric value for {}: {}", name, v_str))
+                        }
+                    }
+                }
+            }
+            _ if self.tune && crate::eval::EvalComponent::from_option_name(name).is_some() => {
+                let component = crate::eval::EvalComponent::from_option_name(name);
+                if let (Some(component), Some(v_str)) = (component, value) {
+                    let enabled = matches!(v_str.to_lowercase().as_str(), "true" | "1" | "yes");
+                    self.disabled_eval.retain(|&c| c != component);
+                    if !enabled {
+                        self.disabled_eval.push(component);
+                    }
+                }
+            }
             _ => {
                 return Err(format!("Unknown option: {}", name));
             }
@@ -133,4 +360,131 @@ mod tests {
         assert_eq!(options.chess_style, "Normal");
         assert!(!options.analyze_mode);
     }
+
+    #[test]
+    fn test_style_accepts_only_known_profiles() {
+        let mut options = UciOptions::new();
+        assert!(options.set_option("Style", Some("Tal")).is_ok());
+        assert!(options.set_option("Style", Some("Fischer")).is_err());
+        assert_eq!(options.chess_style, "Tal");
+    }
+
+    #[test]
+    fn test_eval_params_are_options_only_when_tuning() {
+        let mut options = UciOptions::new();
+        assert!(options.set_option("PawnShieldBonus", Some("20")).is_err());
+
+        options.tune = true;
+        options.set_option("PawnShieldBonus", Some("20")).unwrap();
+        options.set_option("PsqtScale", Some("999")).unwrap();
+        assert_eq!(options.eval_params.pawn_shield_bonus, 20);
+        assert_eq!(options.eval_params.psqt_scale, 200);
+        assert!(options.set_option("PawnShieldBonus", Some("x")).is_err());
+    }
+
+    #[test]
+    fn test_eval_toggles_are_options_only_when_tuning() {
+        let mut options = UciOptions::new();
+        assert!(options.set_option("EvalKingSafety", Some("false")).is_err());
+
+        options.tune = true;
+        for (name, value) in [
+            ("EvalKingSafety", "false"),
+            ("EvalKingSafety", "0"),
+            ("EvalDevelopment", "false"),
+            ("EvalDevelopment", "true"),
+        ] {
+            options.set_option(name, Some(value)).unwrap();
+        }
+        assert_eq!(
+            options.disabled_eval,
+            vec![crate::eval::EvalComponent::KingSafety]
+        );
+    }
+
+    #[test]
+    fn test_contempt_is_clamped_to_spin_range() {
+        let mut options = UciOptions::new();
+        assert_eq!(options.contempt, 0);
+        options.set_option("Contempt", Some("-30")).unwrap();
+        assert_eq!(options.contempt, -30);
+        options.set_option("Contempt", Some("500")).unwrap();
+        assert_eq!(options.contempt, 100);
+        assert!(options.set_option("Contempt", Some("lots")).is_err());
+    }
+
+    #[test]
+    fn test_move_variety_options() {
+        let mut options = UciOptions::new();
+        assert_eq!((options.move_variety, options.variety_seed), (0, 0));
+        options.set_option("MoveVariety", Some("15")).unwrap();
+        options.set_option("VarietySeed", Some("1234")).unwrap();
+        assert_eq!((options.move_variety, options.variety_seed), (15, 1234));
+        options.set_option("MoveVariety", Some("900")).unwrap();
+        assert_eq!(options.move_variety, MAX_MOVE_VARIETY);
+        assert!(options.set_option("VarietySeed", Some("-1")).is_err());
+    }
+
+    #[test]
+    fn test_eval_cache_option() {
+        let mut options = UciOptions::new();
+        assert_eq!(
+            options.eval_cache_mb,
+            crate::search::eval_cache::DEFAULT_EVAL_CACHE_MB
+        );
+        options.set_option("EvalCache", Some("0")).unwrap();
+        assert_eq!(options.eval_cache_mb, 0);
+        options.set_option("EvalCache", Some("100000")).unwrap();
+        assert_eq!(
+            options.eval_cache_mb,
+            crate::search::eval_cache::MAX_EVAL_CACHE_MB
+        );
+        assert!(options.set_option("EvalCache", Some("big")).is_err());
+    }
+
+    #[test]
+    fn test_smp_mode_option() {
+        use crate::search::SmpMode;
+        let mut options = UciOptions::new();
+        assert_eq!(options.smp_mode, SmpMode::LazySmp);
+        options.set_option("SMPMode", Some("RootSplit")).unwrap();
+        assert_eq!(options.smp_mode, SmpMode::RootSplit);
+        assert!(options.set_option("SMPMode", Some("YBWC")).is_err());
+        assert_eq!(options.smp_mode, SmpMode::RootSplit);
+    }
+
+    #[test]
+    fn test_thread_affinity_option() {
+        let mut options = UciOptions::new();
+        assert!(!options.thread_affinity);
+        options.set_option("ThreadAffinity", Some("true")).unwrap();
+        assert!(options.thread_affinity);
+        options.set_option("ThreadAffinity", Some("false")).unwrap();
+        assert!(!options.thread_affinity);
+    }
+
+    #[test]
+    fn test_move_overhead_option() {
+        let mut options = UciOptions::new();
+        assert_eq!(options.move_overhead_ms, DEFAULT_MOVE_OVERHEAD_MS);
+        options.set_option("Move Overhead", Some("150")).unwrap();
+        assert_eq!(options.move_overhead_ms, 150);
+        options.set_option("MoveOverhead", Some("80")).unwrap();
+        assert_eq!(options.move_overhead_ms, 80);
+        options.set_option("Move Overhead", Some("99999")).unwrap();
+        assert_eq!(options.move_overhead_ms, MAX_MOVE_OVERHEAD_MS);
+        assert!(options.set_option("Move Overhead", Some("-5")).is_err());
+    }
+
+    #[test]
+    fn test_node_and_nps_limit_options() {
+        let mut options = UciOptions::new();
+        assert_eq!((options.nodes_limit, options.nps_limit), (0, 0));
+        options.set_option("NodesLimit", Some("20000")).unwrap();
+        options.set_option("NPS Limit", Some("5000")).unwrap();
+        assert_eq!((options.nodes_limit, options.nps_limit), (20000, 5000));
+        assert!(options.set_option("NodesLimit", Some("many")).is_err());
+        assert!(options.set_option("NPS Limit", Some("-1")).is_err());
+        assert_eq!(options.nps_limit, 5000);
+    }
 }
@@ -27,13 +27,48 @@ pub enum UciCommand {
         movestogo: Option<u8>,
         infinite: bool,
         ponder: bool,
+        /// Root moves to restrict the search to (empty = all moves)
+        searchmoves: Vec<String>,
     },
     Stop,
     PonderHit,
+    /// `debug on` / `debug off`: log the conversation to the debug log file
+    Debug {
+        on: bool,
+    },
+    /// Non-standard: constrained pieces and SEE-winning captures of both sides
+    /// (`hotspots [n]`, default 3 per side)
+    Hotspots {
+        count: usize,
+    },
+    /// Non-standard: term-by-term static evaluation of the current position
+    Eval,
+    /// Non-standard: board diagram, FEN, hash key and legal move count (`d`)
+    Display,
+    /// Non-standard: fixed-depth node-count benchmark (`bench [depth]`)
+    Bench {
+        depth: Option<u8>,
+    },
     Quit,
     Unknown(String),
 }
 
+/// Tokens that start a new parameter of the `go` command
+const GO_KEYWORDS: [&str; 12] = [
+    "searchmoves",
+    "ponder",
+    "wtime",
+    "btime",
+    "winc",
+    "binc",
+    "movestogo",
+    "depth",
+    "nodes",
+    "mate",
+    "movetime",
+    "infinite",
+];
+
 /// Parse a UCI command from a string (simple tokenizer)
 pub fn parse_uci_command(line: &str) -> UciCommand {
     let trimmed = line.trim();
@@ -49,6 +84,22 @@ pub fn parse_uci_command(line: &str) -> UciCommand {
         "stop" => UciCommand::Stop,
         "ponderhit" => UciCommand::PonderHit,
         "quit" => UciCommand::Quit,
+        "debug" => match parts.get(1) {
+            Some(&"on") => UciCommand::Debug { on: true },
+            Some(&"off") => UciCommand::Debug { on: false },
+            _ => UciCommand::Unknown(trimmed.to_string()),
+        },
+        "eval" => UciCommand::Eval,
+        "d" => UciCommand::Display,
+        "bench" => UciCommand::Bench {
+            depth: parts.get(1).and_then(|v| parse_depth(v)),
+        },
+        "hotspots" => UciCommand::Hotspots {
+            count: parts
+                .get(1)
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(3),
+        },
         "setoption" => {
             // expected: setoption name <name> [value <val>]
             let mut name = String::new();
@@ -119,6 +170,7 @@ pub fn parse_uci_command(line: &str) -> UciCommand {
             let mut movestogo: Option<u8> = None;
             let mut infinite = false;
             let mut ponder = false;
+            let mut searchmoves: Vec<String> = Vec::new();
 
             let mut i = 1usize;
             while i < parts.len() {
@@ -167,7 +219,7 @@ pub fn parse_uci_command(line: &str) -> UciCommand {
                     }
                     "depth" => {
                         if let Some(v) = parts.get(i + 1) {
-                            if let Ok(x) = v.parse::<u8>() {
+                            if let Some(x) = parse_depth(v) {
                                 depth = Some(x);
                             }
                         }
@@ -205,6 +257,14 @@ pub fn parse_uci_command(line: &str) -> UciCommand {
                         ponder = true;
                         i += 1;
                     }
+                    "searchmoves" => {
+                        // Moves run until the next go keyword
+                        i += 1;
+                        while i < parts.len() && !GO_KEYWORDS.contains(&parts[i]) {
+                            searchmoves.push(parts[i].to_string());
+                            i += 1;
+                        }
+                    }
                     _ => {
                         i += 1;
                     }
@@ -223,8 +283,17 @@ pub fn parse_uci_command(line: &str) -> UciCommand {
                 movestogo,
                 infinite,
                 ponder,
+                searchmoves,
             }
         }
         other => UciCommand::Unknown(other.to_string()),
     }
 }
+
+/// Depth argument of `go depth` / `bench`: any non-negative number, clamped
+/// to [`crate::search::MAX_DEPTH`] (so `go depth 500` searches as deep as
+/// the engine can instead of being ignored)
+fn parse_depth(value: &str) -> Option<u8> {
+    let depth = value.parse::<u64>().ok()?;
+    Some(depth.min(crate::search::MAX_DEPTH as u64) as u8)
+}
@@ -76,7 +76,6 @@ use std::sync::OnceLock;
 static KNIGHT_ATTACKS: OnceLock<[u64; 64]> = OnceLock::new();
 static KING_ATTACKS: OnceLock<[u64; 64]> = OnceLock::new();
 
-#[allow(clippy::needless_range_loop)]
 fn init_knight_attacks() -> [u64; 64] {
     const KNIGHT_OFFSETS: [(i8, i8); 8] = [
         (-2, -1),
@@ -90,25 +89,22 @@ fn init_knight_attacks() -> [u64; 64] {
     ];
     let mut attacks = [0u64; 64];
 
-    for sq in 0..64 {
+    for (sq, attack_mask) in attacks.iter_mut().enumerate() {
         let file = sq % 8;
         let rank = sq / 8;
-        let mut attack_mask = 0u64;
 
         for (dx, dy) in &KNIGHT_OFFSETS {
             let new_file = file as i8 + dx;
             let new_rank = rank as i8 + dy;
             if (0..8).contains(&new_file) && (0..8).contains(&new_rank) {
                 let target_sq = (new_rank as usize) * 8 + (new_file as usize);
-                attack_mask |= 1u64 << target_sq;
+                *attack_mask |= 1u64 << target_sq;
             }
         }
-        attacks[sq] = attack_mask;
     }
     attacks
 }
 
-#[allow(clippy::needless_range_loop)]
 fn init_king_attacks() -> [u64; 64] {
     const KING_OFFSETS: [(i8, i8); 8] = [
         (-1, -1),
@@ -122,29 +118,82 @@ fn init_king_attacks() -> [u64; 64] {
     ];
     let mut attacks = [0u64; 64];
 
-    for sq in 0..64 {
+    for (sq, attack_mask) in attacks.iter_mut().enumerate() {
         let file = sq % 8;
         let rank = sq / 8;
-        let mut attack_mask = 0u64;
 
         for (dx, dy) in &KING_OFFSETS {
             let new_file = file as i8 + dx;
             let new_rank = rank as i8 + dy;
             if (0..8).contains(&new_file) && (0..8).contains(&new_rank) {
                 let target_sq = (new_rank as usize) * 8 + (new_file as usize);
-                attack_mask |= 1u64 << target_sq;
+                *attack_mask |= 1u64 << target_sq;
             }
         }
-        attacks[sq] = attack_mask;
     }
     attacks
 }
 
+/// Squares strictly between two aligned squares, by square pair
+static BETWEEN: OnceLock<[[u64; 64]; 64]> = OnceLock::new();
+/// Whole rank, file or diagonal through two aligned squares, by square pair
+static LINE: OnceLock<[[u64; 64]; 64]> = OnceLock::new();
+
+/// Walk the eight ray directions from every square; 0 for unaligned pairs
+fn init_line_tables() -> ([[u64; 64]; 64], [[u64; 64]; 64]) {
+    const DIRECTIONS: [(i8, i8); 8] = [
+        (-1, -1),
+        (-1, 0),
+        (-1, 1),
+        (0, -1),
+        (0, 1),
+        (1, -1),
+        (1, 0),
+        (1, 1),
+    ];
+    let ray = |sq: usize, (dx, dy): (i8, i8)| -> Vec<usize> {
+        let (mut file, mut rank) = ((sq % 8) as i8, (sq / 8) as i8);
+        let mut squares = Vec::new();
+        loop {
+            file += dx;
+            rank += dy;
+            if !(0..8).contains(&file) || !(0..8).contains(&rank) {
+                return squares;
+            }
+            squares.push((rank as usize) * 8 + file as usize);
+        }
+    };
+
+    let mut between = [[0u64; 64]; 64];
+    let mut line = [[0u64; 64]; 64];
+    for from in 0..64 {
+        for &(dx, dy) in &DIRECTIONS {
+            let forward = ray(from, (dx, dy));
+            let full = forward
+                .iter()
+                .chain(ray(from, (-dx, -dy)).iter())
+                .fold(1u64 << from, |bb, &sq| bb | (1u64 << sq));
+            let mut gap = 0u64;
+            for &to in &forward {
+                between[from][to] = gap;
+                line[from][to] = full;
+                gap |= 1u64 << to;
+            }
+        }
+    }
+    (between, line)
+}
+
 #[inline]
 pub fn init_attack_tables() {
-    // Initialize both tables if not already done
+    // Initialize all tables if not already done
     KNIGHT_ATTACKS.get_or_init(init_knight_attacks);
     KING_ATTACKS.get_or_init(init_king_attacks);
+    if BETWEEN.get().is_none() {
+        let (between, line) = init_line_tables();
+        BETWEEN.get_or_init(|| between);
+        LINE.get_or_init(|| line);
+    }
 }
 
 #[inline]
@@ -164,3 +213,49 @@ pub fn king_attacks(sq: usize) -> u64 {
     });
     table[sq]
 }
+
+/// Squares strictly between `a` and `b` on their rank, file or diagonal
+/// (0 when they are not aligned or adjacent)
+#[inline]
+pub fn between(a: usize, b: usize) -> u64 {
+    let table = BETWEEN.get().unwrap_or_else(|| {
+        init_attack_tables();
+        BETWEEN.get().unwrap()
+    });
+    table[a][b]
+}
+
+/// The full rank, file or diagonal through `a` and `b`, both included
+/// (0 when they are not aligned)
+#[inline]
+pub fn line(a: usize, b: usize) -> u64 {
+    let table = LINE.get().unwrap_or_else(|| {
+        init_attack_tables();
+        LINE.get().unwrap()
+    });
+    table[a][b]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_between_and_line_tables() {
+        // a1 (0) and h8 (63): the long diagonal
+        assert_eq!(between(0, 63), 0x0040201008040200);
+        assert_eq!(line(0, 63), 0x8040201008040201);
+        assert_eq!(between(63, 0), between(0, 63));
+        // e1 (4) and e8 (60): the e-file
+        assert_eq!(between(4, 60), FILE_E & !RANK_1 & !RANK_8);
+        assert_eq!(line(4, 60), FILE_E);
+        assert_eq!(line(60, 4), FILE_E);
+        // Adjacent squares: nothing in between, still on a line
+        assert_eq!(between(0, 1), 0);
+        assert_eq!(line(0, 1), RANK_1);
+        // Not aligned (knight jump) or identical
+        assert_eq!(between(0, 10), 0);
+        assert_eq!(line(0, 10), 0);
+        assert_eq!(line(0, 0), 0);
+    }
+}
@@ -0,0 +1,208 @@
+//! WebAssembly bindings (feature `wasm`)
+//!
+//! Browser GUIs load the package built by `wasm-pack build wasm` (the
+//! `wasm/` crate, a cdylib around this module) and call:
+//!
+//! ```js
+//! import load, { init, setPosition, search } from "./wasm/pkg/scacchista_wasm.js";
+//! await load();
+//! init();
+//! setPosition("", "e2e4 e7e5");
+//! const result = search(12, 1000); // depth 12 or one second, whichever first
+//! console.log(result.bestMove, result.score, result.mate, result.pv);
+//! search(0, 0); // no limit given: one second
+//! ```
+//!
+//! The browser has no threads without extra setup, so the search runs on the
+//! calling thread (run the module in a Web Worker to keep the page
+//! responsive) with a single [`Search`] whose transposition table and
+//! evaluation cache are kept from one search to the next.
+
+use crate::board::{move_to_uci, Board};
+use crate::engine::build_position;
+use crate::search::{
+    mate_in, EvalCache, GameScores, Search, SearchParams, TranspositionTable, MAX_DEPTH,
+};
+use std::cell::RefCell;
+use std::sync::Arc;
+use wasm_bindgen::prelude::*;
+
+/// Transposition table size in MB
+const WASM_HASH_MB: usize = 16;
+
+/// Search time when `search` is given neither a depth nor a time: the search
+/// runs on the caller's thread, so it must end on its own
+const WASM_DEFAULT_MOVETIME_MS: u32 = 1000;
+
+/// Position, transposition table, evaluation cache and root scores between
+/// calls
+struct WasmState {
+    board: Board,
+    tt: Arc<TranspositionTable>,
+    eval_cache: EvalCache,
+    game_scores: GameScores,
+}
+
+thread_local! {
+    static STATE: RefCell<Option<WasmState>> = const { RefCell::new(None) };
+}
+
+/// Initialize the lookup tables and start from the initial position with an
+/// empty transposition table; call once before anything else
+#[wasm_bindgen]
+pub fn init() {
+    crate::init();
+    let board = build_position(None, &[]).expect("the start position is valid");
+    STATE.with(|state| {
+        *state.borrow_mut() = Some(WasmState {
+            board,
+            tt: Arc::new(TranspositionTable::new(WASM_HASH_MB)),
+            eval_cache: EvalCache::new(0),
+            game_scores: GameScores::default(),
+        })
+    });
+}
+
+/// Set the position: `fen` (empty = initial position) followed by `moves`,
+/// space-separated in UCI notation. Throws on an invalid FEN or move, leaving
+/// the position unchanged.
+#[wasm_bindgen(js_name = setPosition)]
+pub fn set_position(fen: &str, moves: &str) -> Result<(), JsError> {
+    let fen = Some(fen.trim()).filter(|f| !f.is_empty());
+    let moves: Vec<&str> = moves.split_whitespace().collect();
+    let board = build_position(fen, &moves)?;
+    with_state(|state| state.board = board)
+}
+
+/// Search the current position to `depth` plies and/or for `movetime_ms`
+/// milliseconds (0 = no limit). With neither limit the search takes
+/// `WASM_DEFAULT_MOVETIME_MS`.
+#[wasm_bindgen]
+pub fn search(depth: u8, movetime_ms: u32) -> Result<WasmSearchResult, JsError> {
+    with_state(|state| {
+        let (max_depth, movetime_ms) = search_limits(depth, movetime_ms);
+        let params = SearchParams::new()
+            .max_depth(max_depth)
+            .time_limit(movetime_ms as u64);
+        let mut search = Search::new(state.board.clone(), 0, params)
+            .with_shared_tt(state.tt.clone())
+            .with_eval_cache(std::mem::replace(&mut state.eval_cache, EvalCache::new(0)))
+            .with_game_scores(std::mem::take(&mut state.game_scores));
+        let (best_move, score) = search.search(Some(max_depth));
+        state.eval_cache = search.take_eval_cache();
+        state.game_scores = search.take_game_scores();
+        let stats = search.stats();
+        WasmSearchResult {
+            best_move: if best_move.is_none() {
+                String::new()
+            } else {
+                move_to_uci(best_move)
+            },
+            score,
+            depth: stats.completed_depth,
+            pv: search
+                .get_pv()
+                .iter()
+                .map(|&mv| move_to_uci(mv))
+                .collect::<Vec<_>>()
+                .join(" "),
+            nodes: stats.nodes as f64,
+        }
+    })
+}
+
+/// Depth and time of a `search` call: 0 means no limit, but one of the two
+/// always is
+fn search_limits(depth: u8, movetime_ms: u32) -> (u8, u32) {
+    match (depth, movetime_ms) {
+        (0, 0) => (MAX_DEPTH, WASM_DEFAULT_MOVETIME_MS),
+        (0, ms) => (MAX_DEPTH, ms),
+        (depth, ms) => (depth.min(MAX_DEPTH), ms),
+    }
+}
+
+fn with_state<T>(f: impl FnOnce(&mut WasmState) -> T) -> Result<T, JsError> {
+    STATE.with(|state| match state.borrow_mut().as_mut() {
+        Some(state) => Ok(f(state)),
+        None => Err(JsError::new("init() has not been called")),
+    })
+}
+
+/// Outcome of [`search`]
+#[wasm_bindgen]
+pub struct WasmSearchResult {
+    best_move: String,
+    score: i16,
+    depth: u8,
+    pv: String,
+    nodes: f64,
+}
+
+#[wasm_bindgen]
+impl WasmSearchResult {
+    /// Best move in UCI notation (empty when the game is over)
+    #[wasm_bindgen(getter, js_name = bestMove)]
+    pub fn best_move(&self) -> String {
+        self.best_move.clone()
+    }
+
+    /// Score in centipawns from the side to move's point of view
+    #[wasm_bindgen(getter)]
+    pub fn score(&self) -> i16 {
+        self.score
+    }
+
+    /// Moves to mate: positive when the side to move mates, negative when it
+    /// gets mated, 0 when the score is not a forced mate
+    #[wasm_bindgen(getter)]
+    pub fn mate(&self) -> i16 {
+        mate_in(self.score).unwrap_or(0)
+    }
+
+    /// Last completed depth
+    #[wasm_bindgen(getter)]
+    pub fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    /// Principal variation, space-separated UCI moves
+    #[wasm_bindgen(getter)]
+    pub fn pv(&self) -> String {
+        self.pv.clone()
+    }
+
+    /// Nodes searched
+    #[wasm_bindgen(getter)]
+    pub fn nodes(&self) -> f64 {
+        self.nodes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Only the success paths: building a JsError needs a JavaScript host
+    #[test]
+    fn test_wasm_bindings_search_the_set_position() {
+        init();
+        set_position("", "e2e4 e7e5").unwrap();
+        let result = search(3, 0).unwrap();
+        assert_eq!(result.depth(), 3);
+        assert!(result.pv().starts_with(&result.best_move()));
+        assert!(result.nodes() > 0.0);
+
+        set_position("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1", "").unwrap();
+        let result = search(3, 0).unwrap();
+        assert_eq!(result.best_move(), "a1a8");
+        assert_eq!(result.mate(), 1);
+    }
+
+    #[test]
+    fn test_search_without_limits_is_timed() {
+        assert_eq!(search_limits(0, 0), (MAX_DEPTH, WASM_DEFAULT_MOVETIME_MS));
+        assert_eq!(search_limits(0, 500), (MAX_DEPTH, 500));
+        assert_eq!(search_limits(6, 0), (6, 0));
+        assert_eq!(search_limits(u8::MAX, 0), (MAX_DEPTH, 0));
+    }
+}
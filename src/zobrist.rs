@@ -2,6 +2,10 @@
 //!
 //! Tables are initialized lazily via [`std::sync::OnceLock`] and are
 //! immutable after initialization, making all lookups completely safe.
+//!
+//! `Board` keeps its key up to date incrementally in `make_move`; the full
+//! recompute ([`recalc_zobrist_full`]) is the reference it is checked against
+//! (`Board::verify_zobrist`, asserted on every search node in debug builds).
 
 use std::sync::OnceLock;
 
@@ -24,50 +28,49 @@ fn split_mix64(x: u64) -> u64 {
     z ^ (z >> 31)
 }
 
-/// Initialize the global Zobrist tables.
-///
-/// This function is idempotent; subsequent calls are no-ops.
-#[allow(clippy::needless_range_loop)]
-pub fn init_zobrist() {
-    let _ = ZOBRIST.get_or_init(|| {
-        let mut piece = [[0u64; 64]; 12];
-        for i in 0..12 {
-            for j in 0..64 {
-                piece[i][j] =
-                    split_mix64((i as u64).wrapping_mul(0xad3) + (j as u64).wrapping_mul(0x47a1));
-            }
+fn build_tables() -> ZobristTables {
+    let mut piece = [[0u64; 64]; 12];
+    for (i, squares) in piece.iter_mut().enumerate() {
+        for (j, key) in squares.iter_mut().enumerate() {
+            *key = split_mix64((i as u64).wrapping_mul(0xad3) + (j as u64).wrapping_mul(0x47a1));
         }
+    }
 
-        let side = split_mix64(0xdeadbeefdeadbeef);
+    let side = split_mix64(0xdeadbeefdeadbeef);
 
-        let mut castling = [0u64; 16];
-        for i in 0..16 {
-            castling[i] = split_mix64((i as u64).wrapping_mul(0x1234_abcd));
-        }
+    let mut castling = [0u64; 16];
+    for (i, key) in castling.iter_mut().enumerate() {
+        *key = split_mix64((i as u64).wrapping_mul(0x1234_abcd));
+    }
 
-        let mut ep_file = [0u64; 8];
-        for i in 0..8 {
-            ep_file[i] = split_mix64((i as u64).wrapping_mul(0x3333_5555));
-        }
+    let mut ep_file = [0u64; 8];
+    for (i, key) in ep_file.iter_mut().enumerate() {
+        *key = split_mix64((i as u64).wrapping_mul(0x3333_5555));
+    }
 
-        ZobristTables {
-            piece,
-            side,
-            castling,
-            ep_file,
-        }
-    });
+    ZobristTables {
+        piece,
+        side,
+        castling,
+        ep_file,
+    }
+}
+
+/// Initialize the global Zobrist tables.
+///
+/// This function is idempotent; subsequent calls are no-ops. Calling it is
+/// optional: the first lookup initializes the tables as well.
+pub fn init_zobrist() {
+    get();
 }
 
 fn piece_index(kind: PieceKind, color: Color) -> usize {
     (color as usize) * 6 + (kind as usize)
 }
 
+#[inline]
 fn get() -> &'static ZobristTables {
-    init_zobrist();
-    ZOBRIST
-        .get()
-        .expect("Zobrist tables not initialized. Call init_zobrist() first.")
+    ZOBRIST.get_or_init(build_tables)
 }
 
 /// Return the Zobrist key for a piece on a given square.
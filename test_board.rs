@@ -55,9 +55,9 @@ fn main() {
     println!("\nGenerated {} moves", moves.len());
 
     for (i, &mv) in moves.iter().take(5).enumerate() {
-        let from = scacchista::board::move_from_sq(mv);
-        let to = scacchista::board::move_to_sq(mv);
-        let piece = scacchista::board::move_piece(mv);
+        let from = mv.from();
+        let to = mv.to();
+        let piece = mv.piece();
 
         let from_file = (from % 8) as u8;
         let from_rank = (7 - (from / 8)) as u8;
@@ -93,3 +93,66 @@ fn test_insufficient_material() {
         "KB vs KB (opposite color: f2/f3) is NOT insufficient material"
     );
 }
+
+#[test]
+fn test_search_scores_dead_positions_as_draw() {
+    use scacchista::search::{Search, SearchParams};
+
+    scacchista::init();
+    // A bishop up, but no mate is possible: no reason to play for a win
+    for fen in [
+        "8/8/4k3/8/8/2B5/8/4K3 w - - 0 1",
+        "4k3/8/8/3b4/8/8/2B5/4K3 w - - 0 1",
+    ] {
+        let mut board = Board::new();
+        board.set_from_fen(fen).unwrap();
+        let params = SearchParams::new().max_depth(4).time_limit(0);
+        let mut search = Search::new(board, 1, params);
+        let (_, score) = search.search(Some(4));
+        assert_eq!(score, 0, "{}", fen);
+    }
+}
+
+#[test]
+fn test_winning_side_avoids_stalemate_in_one() {
+    use scacchista::search::{Search, SearchParams};
+
+    scacchista::init();
+    // (position, move that stalemates): the lone king is in a corner and
+    // one natural-looking move takes its last squares without giving check
+    for (fen, trap) in [
+        ("7k/8/5K2/8/8/8/8/6Q1 w - - 0 1", "g1g6"),
+        ("k7/8/1K6/8/8/8/8/2Q5 w - - 0 1", "c1c7"),
+        ("7k/8/7K/8/8/8/8/1R6 w - - 0 1", "b1g1"),
+        // Taking the last piece leaves the king without a move
+        ("k7/8/1K1n4/8/8/8/8/3Q4 w - - 0 1", "d1d6"),
+        // A blocked pawn is no way out
+        ("7k/7p/7P/8/8/8/Q7/7K w - - 0 1", "a2g2"),
+    ] {
+        let mut board = Board::new();
+        board.set_from_fen(fen).unwrap();
+        let mv = parse_move(&board, trap);
+        let mut stalemated = board.clone();
+        stalemated.make_move(mv);
+        assert!(
+            stalemated.is_stalemate(),
+            "{} {} is no stalemate",
+            fen,
+            trap
+        );
+
+        for depth in 1..=4 {
+            let params = SearchParams::new().max_depth(depth).time_limit(0);
+            let mut search = Search::new(board.clone(), 1, params);
+            let (best, score) = search.search(Some(depth));
+            assert_ne!(
+                scacchista::board::move_to_uci(best),
+                trap,
+                "{} depth {}",
+                fen,
+                depth
+            );
+            assert!(score > 500, "{} depth {}: score {}", fen, depth, score);
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Evaluation symmetry: a position and its color-flipped mirror must get the
+//! same score with the colors swapped, i.e. `evaluate(b) == -evaluate(b.mirror())`
+//! from White's point of view. Any term that treats the two colors
+//! differently (a wrong square flip, a White-only bonus) shows up here.
+
+use scacchista::board::{Board, Color};
+use scacchista::eval::{evaluate_with, EvalParams, EvalWeights};
+
+/// Seed positions: openings, middlegames with castling and en-passant
+/// rights, and endgames handled by the specialized evaluators
+const SEED_FENS: &[&str] = &[
+    scacchista::board::START_FEN,
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4",
+    "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    "2r3k1/pp3ppp/2n1b3/q2pP3/3P4/P1PB1N2/5PPP/R2Q2K1 b - - 0 18",
+    "r4rk1/ppp2p1p/6pQ/3N4/8/8/PPP2PPP/2KR3R w - - 0 1",
+    "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "8/5pk1/6p1/8/2P5/1P3K2/8/8 w - - 0 40",
+    "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+    "8/8/8/4k3/8/8/8/KQ6 w - - 0 1",
+    "8/8/4k3/8/8/2B5/2N5/4K3 b - - 0 1",
+    "6k1/5ppp/8/8/8/8/r4PPP/3R2K1 w - - 0 30",
+];
+
+/// Random plies played from each seed position
+const PLIES_PER_SEED: usize = 400;
+
+/// Score from White's point of view (`evaluate` is side-to-move relative)
+fn white_score(board: &Board, weights: &EvalWeights) -> i32 {
+    let score = evaluate_with(board, &EvalParams::DEFAULT, weights) as i32;
+    match board.side {
+        Color::White => score,
+        Color::Black => -score,
+    }
+}
+
+/// Positions reached by random games from every seed FEN (deterministic LCG,
+/// restarting from the seed after a game ends or drags on)
+fn sample_positions() -> Vec<Board> {
+    let mut positions = Vec::new();
+    let mut rng = 0x2545_F491_4F6C_DD1Du64;
+    for fen in SEED_FENS {
+        let mut board = Board::new();
+        board.set_from_fen(fen).unwrap();
+        positions.push(board.clone());
+        for _ in 0..PLIES_PER_SEED {
+            let moves = board.generate_moves();
+            if moves.is_empty() || board.halfmove >= 40 {
+                board.set_from_fen(fen).unwrap();
+                continue;
+            }
+            rng = rng
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            board.make_move(moves[(rng >> 33) as usize % moves.len()]);
+            positions.push(board.clone());
+        }
+    }
+    positions
+}
+
+#[test]
+fn test_mirror_round_trip() {
+    scacchista::init();
+    for board in sample_positions() {
+        let mirrored = board.mirror();
+        assert_eq!(mirrored.mirror().to_fen(), board.to_fen());
+        assert_eq!(mirrored.zobrist, mirrored.recalc_zobrist());
+        assert_eq!(
+            mirrored.clone().generate_moves().len(),
+            board.clone().generate_moves().len(),
+            "legal move count differs for {}",
+            board.to_fen()
+        );
+    }
+}
+
+#[test]
+fn test_evaluation_is_color_symmetric() {
+    scacchista::init();
+    let positions = sample_positions();
+    assert!(positions.len() > 5000);
+    for weights in [
+        EvalWeights::NORMAL,
+        EvalWeights::TAL,
+        EvalWeights::PETROSIAN,
+    ] {
+        let asymmetric: Vec<String> = positions
+            .iter()
+            .filter_map(|board| {
+                let (score, mirrored) = (
+                    white_score(board, &weights),
+                    white_score(&board.mirror(), &weights),
+                );
+                (score != -mirrored)
+                    .then(|| format!("{} ({} vs {})", board.to_fen(), score, -mirrored))
+            })
+            .collect();
+        assert!(
+            asymmetric.is_empty(),
+            "{} of {} positions evaluate differently when mirrored, e.g.:\n{}",
+            asymmetric.len(),
+            positions.len(),
+            asymmetric[..asymmetric.len().min(10)].join("\n")
+        );
+    }
+}
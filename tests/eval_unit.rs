@@ -1,5 +1,5 @@
 use scacchista::board::Board;
-use scacchista::eval::evaluate;
+use scacchista::eval::{evaluate, evaluate_trace, EvalParams, EvalWeights};
 
 #[test]
 fn test_material_difference() {
@@ -58,13 +58,32 @@ fn test_passed_pawn_bonus() {
     scacchista::init();
     let mut board = Board::new();
 
+    // A knight each keeps the position out of the endgame recognizers (KPK),
+    // so the passed-pawn term decides
+    let trace =
+        |board: &Board| evaluate_trace(board, &EvalParams::default(), &EvalWeights::default());
+
     // Passed pawn on 2nd rank
-    board.set_from_fen("8/8/8/k7/K7/8/1P6/8 w - - 0 1").unwrap();
+    board
+        .set_from_fen("6n1/8/8/7k/K7/8/1P6/6N1 w - - 0 1")
+        .unwrap();
     let score_low = evaluate(&board);
+    let low = trace(&board);
 
     // Passed pawn on 7th rank
-    board.set_from_fen("8/1P6/8/k7/K7/8/8/8 w - - 0 1").unwrap();
+    board
+        .set_from_fen("6n1/1P6/8/7k/K7/8/8/6N1 w - - 0 1")
+        .unwrap();
     let score_high = evaluate(&board);
+    let high = trace(&board);
+
+    assert_eq!((low.recognizer, high.recognizer), (None, None));
+    assert!(
+        high.pawn_structure.white > low.pawn_structure.white + 100,
+        "pawn structure {:?} vs {:?}",
+        high.pawn_structure,
+        low.pawn_structure
+    );
 
     assert!(
         score_high > score_low + 100,
@@ -73,3 +73,76 @@ fn perft_regression_kiwipete() {
         );
     }
 }
+
+/// Published perft tables (chessprogramming.org "Perft Results"):
+/// nodes, captures, e.p., castles, promotions, checks, checkmates per depth
+fn assert_breakdown(fen: &str, expected: &[[u64; 7]]) {
+    scacchista::init();
+    let mut board = Board::new();
+    board.set_from_fen(fen).expect("set_from_fen");
+    for (i, row) in expected.iter().enumerate() {
+        let depth = i as u8 + 1;
+        let c = board.perft_breakdown(depth);
+        let got = [
+            c.nodes,
+            c.captures,
+            c.en_passant,
+            c.castles,
+            c.promotions,
+            c.checks,
+            c.checkmates,
+        ];
+        assert_eq!(&got, row, "perft breakdown mismatch at depth {}", depth);
+        assert_eq!(board.perft(depth), c.nodes);
+    }
+}
+
+#[test]
+fn perft_breakdown_starting_pos() {
+    assert_breakdown(
+        START_FEN,
+        &[
+            [20, 0, 0, 0, 0, 0, 0],
+            [400, 0, 0, 0, 0, 0, 0],
+            [8902, 34, 0, 0, 0, 12, 0],
+            [197281, 1576, 0, 0, 0, 469, 8],
+        ],
+    );
+}
+
+#[test]
+fn perft_breakdown_kiwipete() {
+    assert_breakdown(
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        &[
+            [48, 8, 0, 2, 0, 0, 0],
+            [2039, 351, 1, 91, 0, 3, 0],
+            [97862, 17102, 45, 3162, 0, 993, 1],
+        ],
+    );
+}
+
+#[test]
+fn perft_breakdown_position_3() {
+    assert_breakdown(
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        &[
+            [14, 1, 0, 0, 0, 2, 0],
+            [191, 14, 0, 0, 0, 10, 0],
+            [2812, 209, 2, 0, 0, 267, 0],
+            [43238, 3348, 123, 0, 0, 1680, 17],
+        ],
+    );
+}
+
+#[test]
+fn perft_breakdown_position_4() {
+    assert_breakdown(
+        "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+        &[
+            [6, 0, 0, 0, 0, 0, 0],
+            [264, 87, 0, 6, 48, 10, 0],
+            [9467, 1021, 4, 0, 120, 38, 22],
+        ],
+    );
+}
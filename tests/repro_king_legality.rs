@@ -26,7 +26,7 @@ fn test_king_cannot_move_into_check() {
     for &mv in &scacchista_moves {
         let uci = move_to_uci(mv);
         // println!("  {}", uci);
-        if scacchista::board::move_piece(mv) == PieceKind::King {
+        if mv.piece() == PieceKind::King {
             println!("  King move: {}", uci);
             // Shakmaty uses slightly different UCI for castling (e1g1) vs checks?
             // Scacchista move_to_uci returns standard UCI (e1g1).
@@ -142,8 +142,8 @@ fn test_captures_hanging_piece() {
             .search(Some(5));
 
     // Should capture the hanging knight
-    let from = move_from_sq(best_move);
-    let to = move_to_sq(best_move);
+    let from = best_move.from();
+    let to = best_move.to();
 
     // c3 pawn (from square 18) captures d4 knight (to square 27)
     // Or any other piece captures on d4
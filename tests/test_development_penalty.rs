@@ -1,43 +1,52 @@
 //! Test per la penalità di sviluppo dei pezzi minori
 //!
 //! Verifica che:
-//! 1. Prima di mossa 10, nessuna penalità viene applicata
-//! 2. Dopo mossa 10, pezzi minori sulla prima traversa ricevono -10 cp
+//! 1. La penalità non dipende dal numero di mossa (FEN arbitrarie, trasposizioni)
+//! 2. Pezzi minori sulla prima traversa ricevono -10 cp finché c'è materiale
 //! 3. Pezzi minori sviluppati non ricevono penalità
 
 use scacchista::board::Board;
 use scacchista::eval::evaluate;
 
 #[test]
-fn test_no_penalty_before_move_10() {
-    // Posizione a mossa 5 con cavaliere su b1
+fn test_penalty_ignores_move_number() {
+    // Posizione iniziale: penalità simmetrica, score bilanciato a ogni mossa
     let mut board = Board::new();
     board
         .set_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 5")
         .unwrap();
-
     let score = evaluate(&board);
-
-    // A mossa 5, non dovrebbe esserci penalità, quindi score bilanciato
     assert!(
         score.abs() < 100,
-        "Prima di mossa 10, non dovrebbe esserci penalità: score = {score}"
+        "Posizione iniziale sbilanciata: score = {score}"
     );
+
+    // Stessa posizione (1.Nf3 Nf6 2.Ng1 Ng8 o una FEN qualsiasi) a mosse
+    // diverse: stessa valutazione
+    board
+        .set_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 4 3")
+        .unwrap();
+    assert_eq!(evaluate(&board), score);
+    board
+        .set_from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 80")
+        .unwrap();
+    assert_eq!(evaluate(&board), score);
 }
 
 #[test]
 fn test_penalty_for_undeveloped_knight() {
     // Posizione a mossa 15 con cavaliere bianco su b1
-    // (Tolgo gli altri pezzi per isolare il test)
+    // (Tolgo gli altri pezzi per isolare il test; un pedone per parte evita
+    // che KN vs K venga riconosciuto come finale patto)
     let mut board_undeveloped = Board::new();
     board_undeveloped
-        .set_from_fen("4k3/8/8/8/8/8/8/1N2K3 w - - 0 15")
+        .set_from_fen("4k3/7p/8/8/8/8/7P/1N2K3 w - - 0 15")
         .unwrap();
 
     // Posizione a mossa 15 con cavaliere bianco sviluppato (c3)
     let mut board_developed = Board::new();
     board_developed
-        .set_from_fen("4k3/8/8/8/8/2N5/8/4K3 w - - 0 15")
+        .set_from_fen("4k3/7p/8/8/8/2N5/7P/4K3 w - - 0 15")
         .unwrap();
 
     let score_undeveloped = evaluate(&board_undeveloped);
@@ -60,16 +69,17 @@ fn test_penalty_for_undeveloped_knight() {
 
 #[test]
 fn test_penalty_for_undeveloped_bishop() {
-    // Posizione a mossa 15 con alfiere bianco su c1
+    // Posizione a mossa 15 con alfiere bianco su c1 (i pedoni a2 e h7 evitano
+    // i finali patti o vinti noti)
     let mut board_undeveloped = Board::new();
     board_undeveloped
-        .set_from_fen("4k3/8/8/8/8/8/8/2B1K3 w - - 0 15")
+        .set_from_fen("4k3/7p/8/8/8/8/P7/2B1K3 w - - 0 15")
         .unwrap();
 
     // Posizione a mossa 15 con alfiere bianco sviluppato (c4)
     let mut board_developed = Board::new();
     board_developed
-        .set_from_fen("4k3/8/8/8/2B5/8/8/4K3 w - - 0 15")
+        .set_from_fen("4k3/7p/8/8/2B5/8/P7/4K3 w - - 0 15")
         .unwrap();
 
     let score_undeveloped = evaluate(&board_undeveloped);
@@ -93,13 +103,13 @@ fn test_penalty_for_black_undeveloped() {
     // Posizione a mossa 15 con cavaliere nero su b8 (non sviluppato)
     let mut board_undeveloped = Board::new();
     board_undeveloped
-        .set_from_fen("1n2k3/8/8/8/8/8/8/4K3 b - - 0 15")
+        .set_from_fen("1n2k3/7p/8/8/8/8/7P/4K3 b - - 0 15")
         .unwrap();
 
     // Posizione a mossa 15 con cavaliere nero sviluppato (c6)
     let mut board_developed = Board::new();
     board_developed
-        .set_from_fen("4k3/8/2n5/8/8/8/8/4K3 b - - 0 15")
+        .set_from_fen("4k3/7p/2n5/8/8/8/7P/4K3 b - - 0 15")
         .unwrap();
 
     let score_undeveloped = evaluate(&board_undeveloped);
@@ -121,15 +131,16 @@ fn test_penalty_for_black_undeveloped() {
 #[test]
 fn test_penalty_cumulative() {
     // Posizione a mossa 15 con 2 cavalieri e 2 alfieri bianchi sulla prima traversa
+    // (il pedone nero evita la valutazione da finale KX vs K)
     let mut board_all_undeveloped = Board::new();
     board_all_undeveloped
-        .set_from_fen("4k3/8/8/8/8/8/8/1NBBNK2 w - - 0 15")
+        .set_from_fen("4k3/4p3/8/8/8/8/8/1NBBNK2 w - - 0 15")
         .unwrap();
 
     // Posizione a mossa 15 con tutti i pezzi sviluppati
     let mut board_all_developed = Board::new();
     board_all_developed
-        .set_from_fen("4k3/8/8/2NBBN2/8/8/8/4K3 w - - 0 15")
+        .set_from_fen("4k3/4p3/8/2NBBN2/8/8/8/4K3 w - - 0 15")
         .unwrap();
 
     let score_undeveloped = evaluate(&board_all_undeveloped);
@@ -62,3 +62,37 @@ fn test_multithreaded_correctness() {
     assert!(best_st.starts_with("bestmove"), "ST failed");
     assert!(best_mt.starts_with("bestmove"), "MT failed");
 }
+
+#[test]
+fn test_root_split_and_thread_affinity_options() {
+    scacchista::init();
+    let mut engine = UciEngine::new();
+    let res = process_uci_line("uci", &mut engine);
+    assert!(res
+        .iter()
+        .any(|s| s.starts_with("option name SMPMode type combo")));
+    assert!(res
+        .iter()
+        .any(|s| s == "option name ThreadAffinity type check default false"));
+
+    process_uci_line("setoption name Threads value 2", &mut engine);
+    process_uci_line("setoption name SMPMode value RootSplit", &mut engine);
+    let res = process_uci_line("setoption name ThreadAffinity value true", &mut engine);
+    assert!(res
+        .iter()
+        .any(|s| s.starts_with("info string ThreadAffinity")));
+    // Changing Threads keeps the affinity setting
+    process_uci_line("setoption name Threads value 3", &mut engine);
+    process_uci_line("position startpos moves e2e4 e7e5", &mut engine);
+    let res = process_uci_line("go depth 4", &mut engine);
+    assert!(res
+        .iter()
+        .any(|s| s.starts_with("bestmove") && s != "bestmove 0000"));
+
+    let res = process_uci_line("setoption name ThreadAffinity value false", &mut engine);
+    assert!(res.iter().any(|s| s == "info string ThreadAffinity off"));
+    let res = process_uci_line("go depth 3", &mut engine);
+    assert!(res
+        .iter()
+        .any(|s| s.starts_with("bestmove") && s != "bestmove 0000"));
+}
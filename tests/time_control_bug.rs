@@ -10,7 +10,7 @@
 // 4. Engine thinks it found mate and saves that move as best
 // 5. Move is actually a blunder (e.g., losing queen for pawn)
 
-use scacchista::board::Board;
+use scacchista::board::{Board, Move};
 use scacchista::search::{Search, SearchParams};
 
 #[test]
@@ -45,7 +45,7 @@ fn test_time_expiration_no_fake_mate() {
     );
 
     // Additional check: move should not be 0 (null move)
-    assert_ne!(_mv, 0, "Engine returned null move");
+    assert_ne!(_mv, Move::NONE, "Engine returned null move");
 }
 
 #[test]
@@ -94,7 +94,7 @@ fn test_depth_based_no_corruption() {
         "Depth-based search returned bad score: {}",
         score
     );
-    assert_ne!(mv, 0, "Depth-based search returned null move");
+    assert_ne!(mv, Move::NONE, "Depth-based search returned null move");
 
     // Score should be negative (black is losing - queen hanging)
     // or at least not a huge positive (fake mate)
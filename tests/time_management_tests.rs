@@ -1,11 +1,19 @@
+use scacchista::board::Move;
 use scacchista::search::params::TimeManagement;
-use scacchista::time::TimeManager;
+use scacchista::time::{
+    IterationForecast, PonderClock, PrecomputedMove, TimeAllocation, TimeManager, TimeState,
+    EASY_MOVE_MARGIN, EASY_MOVE_STABLE_ITERATIONS, ENDGAME_TIME_PERCENT, HARD_LIMIT_CLOCK_DIVISOR,
+    HARD_LIMIT_MULTIPLIER, INSTANT_MOVE_TT_DEPTH, LAST_MOVE_MIN_RESERVE_MS, MIDDLEGAME_PHASE,
+    MIDDLEGAME_TIME_PERCENT, ROOT_FAIL_LOW_MARGIN,
+};
+use std::time::Duration;
 
 #[test]
 fn test_allocate_normal() {
     let tm = TimeManagement::new();
     // 60s left, no inc, white to move
-    let time = TimeManager::allocate_time(&tm, Some(60000), None, None, None, None, None, true, 0);
+    let time =
+        TimeManager::allocate_time(&tm, Some(60000), None, None, None, None, None, true, 0).soft_ms;
     // Heuristic: usually ~1/20 to 1/30 of remaining time.
     // 60000 / 30 = 2000. 60000 / 20 = 3000.
     assert!(
@@ -19,8 +27,18 @@ fn test_allocate_normal() {
 fn test_allocate_increment() {
     let tm = TimeManagement::new();
     // 10s left, 1s increment
-    let time =
-        TimeManager::allocate_time(&tm, Some(10000), None, Some(1000), None, None, None, true, 0);
+    let time = TimeManager::allocate_time(
+        &tm,
+        Some(10000),
+        None,
+        Some(1000),
+        None,
+        None,
+        None,
+        true,
+        0,
+    )
+    .soft_ms;
     // Should use some of the time + increment.
     // Base: 10000/20 = 500. + Inc/2 = 500? Total ~1000.
     assert!(time > 500, "Should use feasible time with increment");
@@ -32,7 +50,8 @@ fn test_allocate_increment() {
 fn test_movetime_exact() {
     let tm = TimeManagement::new();
     // Fixed movetime 5000ms
-    let time = TimeManager::allocate_time(&tm, None, None, None, None, Some(5000), None, true, 0);
+    let time =
+        TimeManager::allocate_time(&tm, None, None, None, None, Some(5000), None, true, 0).soft_ms;
     // Should be exactly 5000 minus overhead? Or roughly 5000.
     // Implementation: `if let Some(mt) = movetime { return mt - overhead; }`
     assert!(
@@ -49,7 +68,9 @@ fn test_moves_to_go() {
 
     // 60s left, 5 moves to go.
     // Should use ~ 60s / 5 = 12s.
-    let time = TimeManager::allocate_time(&tm, Some(60000), None, None, None, None, Some(5), true, 0);
+    let time =
+        TimeManager::allocate_time(&tm, Some(60000), None, None, None, None, Some(5), true, 0)
+            .soft_ms;
 
     assert!(
         time > 8000 && time < 14000,
@@ -57,3 +78,267 @@ fn test_moves_to_go() {
         time
     );
 }
+
+#[test]
+fn test_last_move_before_time_control() {
+    let tm = TimeManagement::new();
+
+    // 60s left, movestogo 1: the clock is refilled after this move, so most of
+    // it is spent, but 1/8 stays on the clock
+    let time =
+        TimeManager::allocate_time(&tm, Some(60000), None, None, None, None, Some(1), true, 0)
+            .soft_ms;
+    assert_eq!(time, 52500);
+    let two_to_go =
+        TimeManager::allocate_time(&tm, Some(60000), None, None, None, None, Some(2), true, 0)
+            .soft_ms;
+    assert!(time > two_to_go, "{} vs {} with 2 to go", time, two_to_go);
+
+    // Low clock: the minimum reserve and the move overhead are kept
+    let time =
+        TimeManager::allocate_time(&tm, Some(800), None, None, None, None, Some(1), true, 50)
+            .soft_ms;
+    assert_eq!(time, 800 - LAST_MOVE_MIN_RESERVE_MS - 50);
+    assert!(time < 800);
+
+    // Next position: new time control, normal allocation again
+    let time =
+        TimeManager::allocate_time(&tm, Some(60000), None, None, None, None, Some(40), true, 0)
+            .soft_ms;
+    assert_eq!(time, 1500);
+}
+
+#[test]
+fn test_soft_and_hard_limits() {
+    let tm = TimeManagement::new();
+
+    // 60s left: 1500 ms ideal, up to three times that when unstable
+    let alloc = TimeManager::allocate_time(&tm, Some(60000), None, None, None, None, None, true, 0);
+    assert_eq!(alloc.soft_ms, 1500);
+    assert_eq!(alloc.hard_ms, 1500 * HARD_LIMIT_MULTIPLIER);
+
+    // Few moves to go: the hard limit is capped at a quarter of the clock
+    let alloc =
+        TimeManager::allocate_time(&tm, Some(60000), None, None, None, None, Some(5), true, 0);
+    assert_eq!(alloc.soft_ms, 12000);
+    assert_eq!(alloc.hard_ms, 60000 / HARD_LIMIT_CLOCK_DIVISOR);
+
+    // The move overhead comes off both limits
+    let alloc =
+        TimeManager::allocate_time(&tm, Some(60000), None, None, None, None, None, true, 100);
+    assert_eq!(alloc.soft_ms, 1400);
+    assert_eq!(alloc.hard_ms, 1500 * HARD_LIMIT_MULTIPLIER - 100);
+
+    // Fixed budgets: movetime, last move before the control, emergency
+    let fixed = [
+        TimeManager::allocate_time(&tm, None, None, None, None, Some(5000), None, true, 30),
+        TimeManager::allocate_time(&tm, Some(60000), None, None, None, None, Some(1), true, 0),
+        TimeManager::allocate_time(&tm, Some(900), None, None, None, None, None, true, 50),
+    ];
+    for alloc in fixed {
+        assert_eq!(alloc, TimeAllocation::fixed(alloc.soft_ms));
+    }
+    assert_eq!(fixed[0].soft_ms, 4970);
+
+    // The emergency budget is capped like the hard limit, plus half the
+    // increment, and never more than half the clock
+    assert_eq!(fixed[2].soft_ms, 900 / HARD_LIMIT_CLOCK_DIVISOR - 50);
+    let emergency = |inc: u64| {
+        TimeManager::allocate_time(&tm, Some(900), None, Some(inc), None, None, None, true, 0)
+    };
+    assert_eq!(emergency(100).soft_ms, 900 / HARD_LIMIT_CLOCK_DIVISOR + 50);
+    assert_eq!(emergency(2000), TimeAllocation::fixed(450));
+}
+
+#[test]
+fn test_phase_aware_allocation() {
+    assert_eq!(TimeManager::phase_time_percent(24), MIDDLEGAME_TIME_PERCENT);
+    assert_eq!(
+        TimeManager::phase_time_percent(MIDDLEGAME_PHASE),
+        MIDDLEGAME_TIME_PERCENT
+    );
+    assert_eq!(TimeManager::phase_time_percent(0), ENDGAME_TIME_PERCENT);
+
+    // Same clock: a middlegame gets more time than a bare endgame, and both
+    // scale the unscaled 1500 ms
+    let alloc = |phase: Option<u8>| {
+        let mut tm = TimeManagement::new();
+        tm.game_phase = phase;
+        TimeManager::allocate_time(&tm, Some(60000), None, None, None, None, None, true, 0)
+    };
+    assert_eq!(alloc(None).soft_ms, 1500);
+    assert_eq!(
+        alloc(Some(20)).soft_ms,
+        1500 * MIDDLEGAME_TIME_PERCENT / 100
+    );
+    assert_eq!(alloc(Some(0)).soft_ms, 1500 * ENDGAME_TIME_PERCENT / 100);
+    assert!(alloc(Some(8)).soft_ms < alloc(Some(12)).soft_ms);
+
+    // Never more than a quarter of the clock, even with 2 moves to go
+    let tm = TimeManagement::new().with_phase(24);
+    let alloc =
+        TimeManager::allocate_time(&tm, Some(60000), None, None, None, None, Some(2), true, 0);
+    assert_eq!(alloc.soft_ms, 60000 / HARD_LIMIT_CLOCK_DIVISOR);
+    assert_eq!(alloc.hard_ms, 60000 / HARD_LIMIT_CLOCK_DIVISOR);
+}
+
+#[test]
+fn test_time_state_extends_on_instability_and_fail_low() {
+    let mut state = TimeState::new(1000, 3000);
+    assert_eq!(state.limit_ms(), 1000);
+
+    state.record_iteration(Move::from_raw(1), 20, Some(10), false);
+    assert_eq!(state.limit_ms(), 1000);
+    // New best move: up to the hard limit
+    state.record_iteration(Move::from_raw(2), 25, Some(20), false);
+    assert_eq!(state.limit_ms(), 3000);
+    assert_eq!(state.stable_iterations(), 0);
+    // Stable again: back to the soft limit
+    state.record_iteration(Move::from_raw(2), 25, Some(20), false);
+    assert_eq!(state.limit_ms(), 1000);
+    // Aspiration fail-low, or a score drop, at the root: extend
+    state.record_iteration(Move::from_raw(2), 20, Some(10), true);
+    assert_eq!(state.limit_ms(), 3000);
+    state.record_iteration(
+        Move::from_raw(2),
+        20 - ROOT_FAIL_LOW_MARGIN - 1,
+        Some(10),
+        false,
+    );
+    assert_eq!(state.limit_ms(), 3000);
+
+    // No time limit stays unlimited
+    let mut unlimited = TimeState::new(0, 0);
+    unlimited.record_iteration(Move::from_raw(1), 0, None, false);
+    unlimited.record_iteration(Move::from_raw(2), 0, None, true);
+    assert_eq!(unlimited.limit_ms(), 0);
+    assert!(!unlimited.stop_early(Duration::from_secs(60)));
+}
+
+#[test]
+fn test_time_state_easy_move() {
+    let mut state = TimeState::new(1000, 3000);
+    state.record_iteration(Move::from_raw(7), 300, Some(300 - EASY_MOVE_MARGIN), false);
+    for _ in 0..EASY_MOVE_STABLE_ITERATIONS {
+        assert!(!state.is_easy_move());
+        state.record_iteration(Move::from_raw(7), 300, Some(300 - EASY_MOVE_MARGIN), false);
+    }
+    assert!(state.is_easy_move());
+    assert!(!state.stop_early(Duration::from_millis(100)));
+    assert!(state.stop_early(Duration::from_millis(300)));
+
+    // A close second move is not easy
+    state.record_iteration(
+        Move::from_raw(7),
+        300,
+        Some(300 - EASY_MOVE_MARGIN + 1),
+        false,
+    );
+    assert!(!state.is_easy_move());
+
+    // The only legal move is easy once stable
+    let mut only = TimeState::new(1000, 1000);
+    for _ in 0..=EASY_MOVE_STABLE_ITERATIONS {
+        only.record_iteration(Move::from_raw(3), 0, None, false);
+    }
+    assert!(only.is_easy_move());
+}
+
+#[test]
+fn test_precomputed_move_shortcuts_allocation() {
+    // Nothing known: allocation unchanged
+    assert_eq!(
+        TimeManager::adjust_for_precomputed(3000, PrecomputedMove::None, 50),
+        3000
+    );
+    // Book move: capped at the minimum think time
+    assert_eq!(
+        TimeManager::adjust_for_precomputed(3000, PrecomputedMove::Book, 50),
+        50
+    );
+    // Deep exact TT entry: instant
+    let deep = PrecomputedMove::TtExact {
+        depth: INSTANT_MOVE_TT_DEPTH,
+    };
+    assert_eq!(TimeManager::adjust_for_precomputed(3000, deep, 50), 50);
+    // Shallow TT entry is not trusted
+    let shallow = PrecomputedMove::TtExact {
+        depth: INSTANT_MOVE_TT_DEPTH - 1,
+    };
+    assert_eq!(TimeManager::adjust_for_precomputed(3000, shallow, 50), 3000);
+    // Never extends an already shorter allocation
+    assert_eq!(
+        TimeManager::adjust_for_precomputed(20, PrecomputedMove::Book, 50),
+        20
+    );
+}
+
+#[test]
+fn test_iteration_forecast_predicts_next_depth() {
+    let mut forecast = IterationForecast::default();
+    forecast.record(100, Duration::from_millis(1));
+    forecast.record(400, Duration::from_millis(4));
+    // Not enough iterations yet
+    assert_eq!(forecast.branching_factor(), None);
+    assert!(TimeManager::should_start_iteration(
+        &forecast,
+        Duration::from_millis(990),
+        1000
+    ));
+
+    forecast.record(1600, Duration::from_millis(16));
+    let ebf = forecast.branching_factor().unwrap();
+    assert!((ebf - 4.0).abs() < 1e-9, "ebf {}", ebf);
+    assert_eq!(forecast.next_iteration_nodes(), Some(6400));
+    assert_eq!(
+        forecast.next_iteration_time(),
+        Some(Duration::from_millis(64))
+    );
+
+    // 64 ms predicted: fits after 900 ms of 1000, not after 950
+    assert!(TimeManager::should_start_iteration(
+        &forecast,
+        Duration::from_millis(900),
+        1000
+    ));
+    assert!(!TimeManager::should_start_iteration(
+        &forecast,
+        Duration::from_millis(950),
+        1000
+    ));
+    // No time limit: always start
+    assert!(TimeManager::should_start_iteration(
+        &forecast,
+        Duration::from_secs(60),
+        0
+    ));
+
+    forecast.clear();
+    assert_eq!(forecast.next_iteration_time(), None);
+}
+
+#[test]
+fn test_ponderhit_credits_pondering_time() {
+    let budget = TimeAllocation {
+        soft_ms: 2000,
+        hard_ms: 6000,
+    };
+    let left = TimeManager::after_ponder(budget, Duration::from_millis(500));
+    assert_eq!(left, 1500);
+    // Pondered past the soft limit: stop right away
+    let left = TimeManager::after_ponder(budget, Duration::from_secs(10));
+    assert_eq!(left, 1);
+
+    let start = scacchista::time::Instant::now();
+    let mut clock = PonderClock::default();
+    assert_eq!(clock.ponderhit(budget, start), None);
+    clock.start(start);
+    assert!(clock.is_pondering());
+    let left = clock.ponderhit(budget, start + Duration::from_millis(1200));
+    assert_eq!(left, Some(800));
+    assert_eq!(clock, PonderClock::Hit);
+    // A second ponderhit has nothing left to convert
+    assert_eq!(clock.ponderhit(budget, start), None);
+    clock.stop();
+    assert_eq!(clock, PonderClock::Idle);
+}
@@ -1,5 +1,15 @@
+use scacchista::board::{move_to_uci, Board};
+use scacchista::experience::{ExperienceBook, Outcome};
 use scacchista::uci::{process_uci_line, UciEngine};
 
+/// Move of the `bestmove` line in `res` (without any ponder move)
+fn best_move(res: &[String]) -> &str {
+    res.iter()
+        .find_map(|s| s.strip_prefix("bestmove "))
+        .and_then(|s| s.split_whitespace().next())
+        .expect("bestmove line")
+}
+
 #[test]
 fn test_uci_handshake() {
     scacchista::init();
@@ -42,3 +52,530 @@ fn test_go_infinite_stop() {
     let best = res_stop.iter().find(|s| s.starts_with("bestmove")).unwrap();
     assert_ne!(best, "bestmove 0000", "Should return a valid move");
 }
+
+#[test]
+fn test_report_root_moves() {
+    scacchista::init();
+    let mut engine = UciEngine::new();
+
+    let res = process_uci_line("uci", &mut engine);
+    assert!(res
+        .iter()
+        .any(|s| s.contains("option name ReportRootMoves")));
+
+    process_uci_line("setoption name ReportRootMoves value true", &mut engine);
+    process_uci_line("position startpos", &mut engine);
+    let res = process_uci_line("go depth 3", &mut engine);
+
+    let root_lines: Vec<&String> = res
+        .iter()
+        .filter(|s| s.starts_with("info string rootmove"))
+        .collect();
+    // Every legal move from the start position is a root move
+    assert_eq!(root_lines.len(), 20, "Expected one line per root move");
+    assert!(root_lines
+        .iter()
+        .all(|s| s.contains(" nodes ") && s.contains(" tthits ") && s.contains(" score ")));
+}
+
+#[test]
+fn test_instant_move_from_deep_tt_entry() {
+    scacchista::init();
+    let mut engine = UciEngine::new();
+    process_uci_line("uci", &mut engine);
+    process_uci_line("position fen k7/p7/P7/8/8/8/8/7K w - - 0 1", &mut engine);
+
+    // Fill the TT with a deep exact root entry (as pondering would)
+    process_uci_line("go depth 12", &mut engine);
+
+    // Same position on a long clock: the known move is played almost at once
+    let start = std::time::Instant::now();
+    let res = process_uci_line("go wtime 600000 btime 600000", &mut engine);
+    let elapsed = start.elapsed().as_millis();
+    assert!(res.iter().any(|s| s.starts_with("bestmove")));
+    assert!(
+        elapsed < 2000,
+        "Expected an instant move from TT, took {} ms",
+        elapsed
+    );
+}
+
+#[test]
+fn test_study_mode_allows_missing_king() {
+    scacchista::init();
+    let mut engine = UciEngine::new();
+    let res = process_uci_line("uci", &mut engine);
+    assert!(res.iter().any(|s| s.contains("option name StudyMode")));
+
+    // Normal play: a position without the black king is rejected
+    let fen = "position fen 8/8/8/3b4/8/8/8/K2R4 w - - 0 1";
+    let res = process_uci_line(fen, &mut engine);
+    assert!(res.iter().any(|s| s.contains("missing king")));
+
+    // Study mode: loaded and searched on material, the rook wins the bishop
+    process_uci_line("setoption name StudyMode value true", &mut engine);
+    let res = process_uci_line(fen, &mut engine);
+    assert!(res.is_empty(), "Unexpected output: {:?}", res);
+    let res = process_uci_line("go depth 3", &mut engine);
+    assert_eq!(best_move(&res), "d1d5", "{:?}", res);
+}
+
+#[test]
+fn test_position_fen_without_move_counters() {
+    scacchista::init();
+    let mut engine = UciEngine::new();
+
+    // Four fields, as many tools send them
+    let cmd = "position fen rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - moves e2e4";
+    let res = process_uci_line(cmd, &mut engine);
+    assert!(res.is_empty(), "Unexpected output: {:?}", res);
+    let res = process_uci_line("d", &mut engine);
+    let fen = "Fen: rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1";
+    assert!(res.contains(&fen.to_string()), "{:?}", res);
+
+    let res = process_uci_line("position fen 4k3/8/8/8/8/8/8/4K3 w KQ e9", &mut engine);
+    let error = "info string FEN parse error: invalid en passant square 'e9'";
+    assert!(res.contains(&error.to_string()), "{:?}", res);
+}
+
+#[test]
+fn test_go_searchmoves_restricts_root() {
+    scacchista::init();
+    let mut engine = UciEngine::new();
+    process_uci_line("uci", &mut engine);
+    process_uci_line("position startpos", &mut engine);
+
+    let res = process_uci_line("go depth 3 searchmoves a2a3 h2h3", &mut engine);
+    let best = best_move(&res);
+    assert!(best == "a2a3" || best == "h2h3", "Unexpected {}", best);
+
+    // Illegal entries are reported and ignored
+    let res = process_uci_line("go depth 2 searchmoves e2e5 b1c3", &mut engine);
+    assert!(res.iter().any(|s| s.contains("ignoring searchmove e2e5")));
+    assert_eq!(best_move(&res), "b1c3");
+}
+
+#[test]
+fn test_chess960_castling_notation() {
+    scacchista::init();
+    let mut engine = UciEngine::new();
+    let res = process_uci_line("uci", &mut engine);
+    assert!(res.iter().any(|s| s.contains("option name UCI_Chess960")));
+
+    let fen = "position fen r3k2r/pppppppp/8/8/8/8/PPPPPPPP/R3K2R w KQkq - 0 1";
+    process_uci_line(fen, &mut engine);
+    let res = process_uci_line("go depth 1 searchmoves e1g1", &mut engine);
+    assert_eq!(best_move(&res), "e1g1", "{:?}", res);
+
+    process_uci_line("setoption name UCI_Chess960 value true", &mut engine);
+    process_uci_line(fen, &mut engine);
+    let res = process_uci_line("go depth 1 searchmoves e1h1", &mut engine);
+    assert_eq!(best_move(&res), "e1h1", "{:?}", res);
+
+    // Either form is accepted in position commands
+    let res = process_uci_line(&format!("{} moves e1h1 e8c8", fen), &mut engine);
+    assert!(res.is_empty(), "{:?}", res);
+}
+
+#[test]
+fn test_info_lines_streamed_during_search() {
+    use std::sync::{Arc, Mutex};
+
+    scacchista::init();
+    let mut engine = UciEngine::new();
+    let streamed = Arc::new(Mutex::new(Vec::new()));
+    let sink = streamed.clone();
+    engine.set_info_sink(Arc::new(move |line: String| {
+        sink.lock().unwrap().push(line)
+    }));
+
+    process_uci_line("uci", &mut engine);
+    process_uci_line("position startpos", &mut engine);
+    let res = process_uci_line("go depth 4", &mut engine);
+    assert!(res.iter().any(|s| s.starts_with("bestmove")));
+
+    // One line per completed iteration, sent before the search returned
+    // (aspiration failures add `lowerbound`/`upperbound` lines before it)
+    let streamed = streamed.lock().unwrap();
+    for depth in 1..=4 {
+        let prefix = format!("info depth {} seldepth ", depth);
+        let line = streamed
+            .iter()
+            .find(|s| s.starts_with(&prefix) && !s.contains("bound "))
+            .unwrap_or_else(|| panic!("No info line for depth {}: {:?}", depth, streamed));
+        for field in [" score ", " nodes ", " time ", " hashfull ", " pv "] {
+            assert!(line.contains(field), "Missing {} in {}", field, line);
+        }
+    }
+}
+
+#[test]
+fn test_go_infinite_streams_info_before_stop() {
+    use std::sync::{Arc, Mutex};
+
+    scacchista::init();
+    let mut engine = UciEngine::new();
+    let streamed = Arc::new(Mutex::new(Vec::new()));
+    let sink = streamed.clone();
+    engine.set_info_sink(Arc::new(move |line: String| {
+        sink.lock().unwrap().push(line)
+    }));
+
+    process_uci_line("position startpos", &mut engine);
+    process_uci_line("go infinite", &mut engine);
+    let start = std::time::Instant::now();
+    while !streamed
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|s| s.starts_with("info depth 2 "))
+    {
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+
+    let res = process_uci_line("stop", &mut engine);
+    let best = res.iter().find(|s| s.starts_with("bestmove")).unwrap();
+    assert_ne!(best, "bestmove 0000");
+}
+
+#[test]
+fn test_go_on_finished_game_reports_game_over() {
+    scacchista::init();
+    let mut engine = UciEngine::new();
+    process_uci_line("uci", &mut engine);
+
+    // Fool's mate reached through the move list
+    process_uci_line("position startpos moves f2f3 e7e5 g2g4 d8h4", &mut engine);
+    let res = process_uci_line("go depth 5", &mut engine);
+    assert_eq!(
+        res,
+        vec!["info string game over checkmate 0-1", "bestmove 0000"]
+    );
+
+    // Answered at once even for an open-ended search
+    process_uci_line("position fen 7k/5Q2/6K1/8/8/8/8/8 b - - 0 1", &mut engine);
+    let res = process_uci_line("go infinite", &mut engine);
+    assert_eq!(
+        res,
+        vec!["info string game over stalemate 1/2-1/2", "bestmove 0000"]
+    );
+
+    // Claimable draws are still played on
+    process_uci_line("position fen 8/8/8/8/8/8/8/K6k w - - 0 1", &mut engine);
+    let res = process_uci_line("go depth 2", &mut engine);
+    assert!(!res.iter().any(|s| s.contains("game over")), "{:?}", res);
+    assert_ne!(best_move(&res), "0000", "{:?}", res);
+}
+
+#[test]
+fn test_position_continues_game_incrementally() {
+    scacchista::init();
+    let shuffle = [
+        "g1f3", "g8f6", "f3g1", "f6g8", "g1f3", "g8f6", "f3g1", "f6g8",
+    ];
+
+    // GUI style: the whole game again after every move
+    let mut engine = UciEngine::new();
+    for ply in 0..=shuffle.len() {
+        let cmd = format!("position startpos moves {}", shuffle[..ply].join(" "));
+        assert!(process_uci_line(cmd.trim_end(), &mut engine).is_empty());
+    }
+    let mut fresh = UciEngine::new();
+    let cmd = format!("position startpos moves {}", shuffle.join(" "));
+    process_uci_line(&cmd, &mut fresh);
+
+    // Same position and repetition history as a rebuild from scratch
+    assert_eq!(engine.board().zobrist, fresh.board().zobrist);
+    assert_eq!(engine.board().history_len(), fresh.board().history_len());
+    assert_eq!(engine.board().ply(), 0);
+    assert!(engine.board().is_threefold_repetition());
+
+    // A takeback is not a continuation: the board is rebuilt
+    process_uci_line("position startpos moves g1f3", &mut engine);
+    process_uci_line("position startpos moves g1f3", &mut fresh);
+    assert_eq!(engine.board().zobrist, fresh.board().zobrist);
+    assert_eq!(engine.board().history_len(), 1);
+
+    // An illegal new move leaves the game where it was
+    let res = process_uci_line("position startpos moves g1f3 e2e4", &mut engine);
+    assert!(res.iter().any(|s| s.contains("invalid move e2e4")));
+    assert_eq!(engine.board().zobrist, fresh.board().zobrist);
+    process_uci_line("position startpos moves g1f3 g8f6", &mut engine);
+    process_uci_line("position startpos moves g1f3 g8f6", &mut fresh);
+    assert_eq!(engine.board().zobrist, fresh.board().zobrist);
+}
+
+#[test]
+fn test_long_game_keeps_only_the_reversible_history() {
+    scacchista::init();
+    // 400 plies of knight shuffles, then 1.e4 e5: the GUI sends the whole
+    // game after every move
+    let mut moves: Vec<&str> = Vec::new();
+    for _ in 0..100 {
+        moves.extend(["g1f3", "g8f6", "f3g1", "f6g8"]);
+    }
+    moves.extend(["e2e4", "e7e5"]);
+
+    let mut engine = UciEngine::new();
+    for ply in 1..=moves.len() {
+        let cmd = format!("position startpos moves {}", moves[..ply].join(" "));
+        assert!(process_uci_line(&cmd, &mut engine).is_empty());
+        // Game moves cannot be taken back: the board carries no undo stack
+        assert_eq!(engine.board().ply(), 0);
+        assert!(engine.board().history_len() <= 400);
+    }
+    assert_eq!(engine.board().history_len(), 0);
+
+    // Continuing from the pruned history still finds repetitions and searches
+    for ply in 1..=8 {
+        let shuffle = ["g1f3", "b8c6", "f3g1", "c6b8"].repeat(2);
+        let cmd = format!(
+            "position startpos moves {} {}",
+            moves.join(" "),
+            shuffle[..ply].join(" ")
+        );
+        process_uci_line(&cmd, &mut engine);
+        assert_eq!(engine.board().history_len(), ply);
+    }
+    assert!(engine.board().is_threefold_repetition());
+    let res = process_uci_line("go depth 3", &mut engine);
+    assert!(!best_move(&res).is_empty());
+    assert_eq!(engine.board().history_len(), 8);
+}
+
+#[test]
+fn test_experience_book_learns_from_finished_games() {
+    scacchista::init();
+    let path = std::env::temp_dir().join(format!("scacchista-uci-exp-{}.bin", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut engine = UciEngine::new();
+    let cmd = format!("setoption name ExperienceFile value {}", path.display());
+    process_uci_line(&cmd, &mut engine);
+    process_uci_line(
+        "position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1",
+        &mut engine,
+    );
+    let res = process_uci_line("go depth 3", &mut engine);
+    assert_eq!(best_move(&res), "a1a8");
+    // The game ends (the GUI never sends the mated position): learned on ucinewgame
+    assert!(!path.exists());
+    process_uci_line("ucinewgame", &mut engine);
+
+    let book = ExperienceBook::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let mut board = Board::new();
+    board
+        .set_from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1")
+        .unwrap();
+    let entry = book.preferred_move(&board).expect("mating move learned");
+    assert_eq!(move_to_uci(entry.mv), "a1a8");
+    assert_eq!(entry.outcome, Outcome::Win);
+}
+
+#[test]
+fn test_tuning_mode_exposes_eval_params() {
+    scacchista::init();
+    let mut engine = UciEngine::new();
+    let res = process_uci_line("uci", &mut engine);
+    let spin = "option name PawnShieldBonus type spin default 15 min 0 max 100".to_string();
+    assert!(!res.contains(&spin));
+
+    engine.enable_tuning();
+    let res = process_uci_line("uci", &mut engine);
+    assert!(res.contains(&spin));
+    process_uci_line("setoption name PawnShieldBonus value 25", &mut engine);
+    process_uci_line("position startpos", &mut engine);
+    let res = process_uci_line("go depth 2", &mut engine);
+    assert!(res.iter().any(|s| s.starts_with("bestmove")));
+}
+
+#[test]
+fn test_move_overhead_option() {
+    scacchista::init();
+    let mut engine = UciEngine::new();
+    let res = process_uci_line("uci", &mut engine);
+    let spin = "option name Move Overhead type spin default 10 min 0 max 5000".to_string();
+    assert!(res.contains(&spin));
+
+    let res = process_uci_line("setoption name Move Overhead value 200", &mut engine);
+    assert!(res.contains(&"info string Move Overhead set to 200 ms".to_string()));
+    let res = process_uci_line("setoption name Move Overhead value soon", &mut engine);
+    assert!(res.iter().any(|s| s.starts_with("info string error")));
+
+    // The overhead comes off the clock allocation: still a legal move in time
+    process_uci_line("position startpos", &mut engine);
+    let res = process_uci_line("go wtime 1000 btime 1000", &mut engine);
+    let best = res.iter().find(|s| s.starts_with("bestmove")).unwrap();
+    assert_ne!(best, "bestmove 0000");
+}
+
+#[test]
+fn test_go_nodes_and_nodes_limit_option() {
+    scacchista::init();
+    let mut engine = UciEngine::new();
+    let res = process_uci_line("uci", &mut engine);
+    for name in ["NodesLimit", "NPS Limit"] {
+        let spin = format!(
+            "option name {} type spin default 0 min 0 max 2147483647",
+            name
+        );
+        assert!(res.contains(&spin));
+    }
+
+    // Fixed nodes: the same move every time
+    process_uci_line("position startpos moves d2d4", &mut engine);
+    let first = process_uci_line("go nodes 3000", &mut engine);
+    process_uci_line("ucinewgame", &mut engine);
+    process_uci_line("position startpos moves d2d4", &mut engine);
+    let second = process_uci_line("go nodes 3000", &mut engine);
+    assert_ne!(best_move(&first), "0000");
+    assert_eq!(best_move(&first), best_move(&second));
+
+    // The option caps searches that carry a clock
+    process_uci_line("setoption name NodesLimit value 2000", &mut engine);
+    let res = process_uci_line("go wtime 60000 btime 60000", &mut engine);
+    assert_ne!(best_move(&res), "0000");
+    let nodes = res
+        .iter()
+        .filter_map(|s| s.split(" nodes ").nth(1))
+        .filter_map(|s| s.split_whitespace().next()?.parse::<u64>().ok())
+        .max()
+        .expect("info line with nodes");
+    assert!(nodes <= 2000, "{} nodes", nodes);
+}
+
+#[test]
+fn test_go_depth_and_movetime_whichever_comes_first() {
+    scacchista::init();
+    let mut engine = UciEngine::new();
+    process_uci_line("position startpos", &mut engine);
+    let depth_of = |res: &[String]| {
+        res.iter()
+            .rev()
+            .find_map(|s| s.strip_prefix("info depth "))
+            .and_then(|s| s.split_whitespace().next()?.parse::<u8>().ok())
+            .expect("info line with depth")
+    };
+
+    let start = std::time::Instant::now();
+    let res = process_uci_line("go depth 3 movetime 60000", &mut engine);
+    assert_eq!(depth_of(&res), 3);
+    assert!(start.elapsed() < std::time::Duration::from_secs(30));
+
+    let start = std::time::Instant::now();
+    let res = process_uci_line("go depth 60 movetime 300", &mut engine);
+    assert!(depth_of(&res) < 60);
+    assert_ne!(best_move(&res), "0000");
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+}
+
+#[test]
+fn test_hash_threads_and_clear_hash_reconfigure_in_place() {
+    scacchista::init();
+    let mut engine = UciEngine::new();
+    let res = process_uci_line("uci", &mut engine);
+    assert!(res.contains(&"option name Clear Hash type button".to_string()));
+
+    let res = process_uci_line("setoption name Threads value 2", &mut engine);
+    assert!(res.contains(&"info string Threads set to 2".to_string()));
+    let res = process_uci_line("setoption name Hash value 4", &mut engine);
+    assert!(res.contains(&"info string Hash set to 4 MB".to_string()));
+    process_uci_line("position startpos", &mut engine);
+    let res = process_uci_line("go depth 3", &mut engine);
+    assert_ne!(best_move(&res), "0000");
+
+    let res = process_uci_line("setoption name Clear Hash", &mut engine);
+    assert!(res.contains(&"info string Hash cleared".to_string()));
+    let res = process_uci_line("setoption name Threads value 1", &mut engine);
+    assert!(res.contains(&"info string Threads set to 1".to_string()));
+    let res = process_uci_line("go depth 3", &mut engine);
+    assert_ne!(best_move(&res), "0000");
+}
+
+#[test]
+fn test_debug_mode_logs_conversation() {
+    scacchista::init();
+    let path = std::env::temp_dir().join(format!("scacchista-uci-log-{}.txt", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let mut engine = UciEngine::new();
+    process_uci_line(
+        &format!("setoption name Debug Log File value {}", path.display()),
+        &mut engine,
+    );
+    process_uci_line("isready", &mut engine); // not logged yet
+    assert!(process_uci_line("debug on", &mut engine).is_empty());
+    process_uci_line("position startpos", &mut engine);
+    let res = process_uci_line("go depth 3", &mut engine);
+    let best = res.iter().find(|s| s.starts_with("bestmove")).unwrap();
+    process_uci_line("debug off", &mut engine);
+    process_uci_line("isready", &mut engine);
+
+    let log = std::fs::read_to_string(&path).unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert!(log.contains(">> position startpos\n"));
+    assert!(log.contains(">> go depth 3\n"));
+    assert!(log.contains(&format!("<< {}\n", best)));
+    assert!(log.contains(" ## go: "));
+    assert!(log.contains(" ## search done: depth 3"));
+    assert!(log.contains(">> debug off\n"));
+    assert_eq!(log.matches("isready").count(), 0);
+}
+
+#[test]
+fn test_mate_scores_reported_in_moves() {
+    scacchista::init();
+    let mut engine = UciEngine::new();
+    process_uci_line("uci", &mut engine);
+
+    // Back-rank mate in 1, then mate in 2 (1. Rb7 Kg8 2. Ra8#)
+    for (fen, depth, mate) in [
+        ("6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1", 3, "score mate 1"),
+        ("7k/8/8/8/8/8/R7/1R4K1 w - - 0 1", 5, "score mate 2"),
+    ] {
+        process_uci_line(&format!("position fen {}", fen), &mut engine);
+        let res = process_uci_line(&format!("go depth {}", depth), &mut engine);
+        let info = res
+            .iter()
+            .rev()
+            .find(|s| s.starts_with("info depth"))
+            .expect("final info line");
+        assert!(info.contains(mate), "{}: {}", fen, info);
+    }
+}
+
+#[test]
+fn test_bestmove_carries_legal_ponder_move() {
+    scacchista::init();
+    let mut engine = UciEngine::new();
+    let res = process_uci_line("uci", &mut engine);
+    assert!(res.contains(&"option name Ponder type check default false".to_string()));
+    process_uci_line("setoption name Ponder value true", &mut engine);
+
+    process_uci_line("position startpos moves e2e4", &mut engine);
+    let res = process_uci_line("go depth 4", &mut engine);
+    let line = res.iter().find(|s| s.starts_with("bestmove")).unwrap();
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    assert_eq!(parts.len(), 4, "{}", line);
+    assert_eq!(parts[2], "ponder");
+
+    // The ponder move (from the PV or, past its end, the TT) is a legal
+    // reply to the best move
+    let mut board = engine.board().clone();
+    let find = |board: &mut Board, uci: &str| {
+        board
+            .generate_moves()
+            .into_iter()
+            .find(|&m| move_to_uci(m) == uci)
+    };
+    let best = find(&mut board, parts[1]).unwrap();
+    board.make_move(best);
+    assert!(find(&mut board, parts[3]).is_some());
+
+    // Mate in 1: there is no reply to ponder on
+    let fen = "position fen 6k1/5ppp/8/8/8/8/5PPP/R5K1 w - - 0 1";
+    process_uci_line(fen, &mut engine);
+    let res = process_uci_line("go depth 3", &mut engine);
+    assert!(res.contains(&"bestmove a1a8".to_string()), "{:?}", res);
+}
@@ -133,3 +133,97 @@ fn test_go_depth_zero() {
     // Should have a bestmove
     assert!(responses.iter().any(|s| s.starts_with("bestmove")));
 }
+
+#[test]
+fn test_hotspots_command() {
+    let mut engine = UciEngine::new();
+    process_uci_line(
+        "position fen 4k3/8/4p3/3pn3/6b1/5N2/3Q4/4K3 w - - 0 1",
+        &mut engine,
+    );
+    let responses = process_uci_line("hotspots 2", &mut engine);
+    assert_eq!(responses.len(), 2);
+    assert!(responses[0].starts_with("info string hotspots white constrained "));
+    assert!(responses[0].ends_with("threats f3e5:320"));
+    assert!(responses[1].starts_with("info string hotspots black constrained "));
+    assert!(responses[1].contains("g4f3:320"));
+}
+
+#[test]
+fn test_eval_command_prints_breakdown() {
+    scacchista::init();
+    let mut engine = UciEngine::new();
+    process_uci_line("position startpos moves e2e4 d7d5", &mut engine);
+    let responses = process_uci_line("eval", &mut engine);
+    assert!(responses
+        .iter()
+        .any(|s| s.trim_start().starts_with("Material |")));
+    assert!(responses.iter().any(|s| s.contains("Center control")));
+    let total = scacchista::eval::evaluate(engine.board());
+    assert_eq!(
+        responses.last().unwrap(),
+        &format!("Total (side to move): {}", total)
+    );
+}
+
+#[test]
+fn test_eval_toggles_switch_terms_off_in_tuning_mode() {
+    use scacchista::eval::{evaluate_with, EvalComponent, EvalParams, EvalWeights};
+
+    scacchista::init();
+    let mut engine = UciEngine::new();
+    engine.enable_tuning();
+    let res = process_uci_line("uci", &mut engine);
+    assert!(res.contains(&"option name EvalKingSafety type check default true".to_string()));
+
+    process_uci_line("setoption name EvalKingSafety value false", &mut engine);
+    process_uci_line("setoption name EvalCenterControl value false", &mut engine);
+    process_uci_line("position startpos moves e2e4 e7e5 e1e2", &mut engine);
+    let responses = process_uci_line("eval", &mut engine);
+    let weights = EvalWeights::NORMAL
+        .without(EvalComponent::KingSafety)
+        .without(EvalComponent::CenterControl);
+    let total = evaluate_with(engine.board(), &EvalParams::DEFAULT, &weights);
+    assert_ne!(total, scacchista::eval::evaluate(engine.board()));
+    assert_eq!(
+        responses.last().unwrap(),
+        &format!("Total (side to move): {}", total)
+    );
+
+    // Without `tune` the toggles are unknown options
+    let mut engine = UciEngine::new();
+    let res = process_uci_line("uci", &mut engine);
+    assert!(!res.iter().any(|s| s.contains("EvalKingSafety")));
+}
+
+#[test]
+fn test_display_command() {
+    scacchista::init();
+    let mut engine = UciEngine::new();
+    process_uci_line("position startpos moves e2e4 c7c5 e4e5 d7d5", &mut engine);
+    let responses = process_uci_line("d", &mut engine);
+    assert_eq!(responses[0], " +---+---+---+---+---+---+---+---+");
+    assert_eq!(responses[1], " | r | n | b | q | k | b | n | r | 8");
+    assert_eq!(responses[7], " |   |   | p | p | P |   |   |   | 5");
+    assert_eq!(responses[17], "   a   b   c   d   e   f   g   h");
+    assert!(responses.contains(
+        &"Fen: rnbqkbnr/pp2pppp/8/2ppP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3".to_string()
+    ));
+    let key = format!("Key: {:016X}", engine.board().zobrist);
+    assert!(responses.contains(&key));
+    assert!(responses.contains(&"Castling: KQkq".to_string()));
+    assert!(responses.contains(&"En passant: d6".to_string()));
+    // 30 moves plus the en-passant capture e5d6
+    assert_eq!(responses.last().unwrap(), "Legal moves: 31");
+}
+
+#[test]
+fn test_go_movestogo_one_is_reported() {
+    let mut engine = UciEngine::new();
+    process_uci_line("position startpos", &mut engine);
+    let responses = process_uci_line("go wtime 1000 btime 1000 movestogo 1", &mut engine);
+    assert!(responses
+        .iter()
+        .any(|s| s == "info string movestogo 1: last move before time control, allocating 790 ms"));
+    assert!(responses.iter().any(|s| s.starts_with("bestmove")));
+}
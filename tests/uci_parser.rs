@@ -1,3 +1,4 @@
+use scacchista::search::MAX_DEPTH;
 use scacchista::uci::{parse_uci_command, UciCommand};
 
 #[test]
@@ -29,3 +30,80 @@ fn test_parse_full_go_command() {
         other => panic!("Expected Go command, got: {:?}", other),
     }
 }
+
+#[test]
+fn test_parse_go_searchmoves() {
+    match parse_uci_command("go searchmoves e2e4 d2d4 g1f3 depth 5") {
+        UciCommand::Go {
+            depth, searchmoves, ..
+        } => {
+            assert_eq!(searchmoves, vec!["e2e4", "d2d4", "g1f3"]);
+            assert_eq!(depth, Some(5));
+        }
+        other => panic!("Expected Go command, got: {:?}", other),
+    }
+
+    match parse_uci_command("go depth 5") {
+        UciCommand::Go { searchmoves, .. } => assert!(searchmoves.is_empty()),
+        other => panic!("Expected Go command, got: {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_go_depth_beyond_u8_is_clamped() {
+    for line in ["go depth 300", "go depth 18446744073709551615"] {
+        match parse_uci_command(line) {
+            UciCommand::Go { depth, .. } => assert_eq!(depth, Some(MAX_DEPTH), "{}", line),
+            other => panic!("Expected Go command, got: {:?}", other),
+        }
+    }
+    assert_eq!(
+        parse_uci_command("bench 1000"),
+        UciCommand::Bench {
+            depth: Some(MAX_DEPTH)
+        }
+    );
+}
+
+#[test]
+fn test_parse_hotspots() {
+    assert_eq!(
+        parse_uci_command("hotspots 5"),
+        UciCommand::Hotspots { count: 5 }
+    );
+    assert_eq!(
+        parse_uci_command("hotspots"),
+        UciCommand::Hotspots { count: 3 }
+    );
+}
+
+#[test]
+fn test_parse_eval_and_display() {
+    assert_eq!(parse_uci_command("eval"), UciCommand::Eval);
+    assert_eq!(parse_uci_command("d"), UciCommand::Display);
+}
+
+#[test]
+fn test_parse_bench() {
+    assert_eq!(
+        parse_uci_command("bench 6"),
+        UciCommand::Bench { depth: Some(6) }
+    );
+    assert_eq!(
+        parse_uci_command("bench"),
+        UciCommand::Bench { depth: None }
+    );
+}
+
+#[test]
+fn test_parse_debug() {
+    assert_eq!(
+        parse_uci_command("debug on"),
+        UciCommand::Debug { on: true }
+    );
+    assert_eq!(
+        parse_uci_command("debug off"),
+        UciCommand::Debug { on: false }
+    );
+    assert!(matches!(parse_uci_command("debug"), UciCommand::Unknown(_)));
+}
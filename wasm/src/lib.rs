@@ -0,0 +1,4 @@
+//! WebAssembly package of Scacchista: a cdylib around `scacchista::wasm`,
+//! built with `wasm-pack build wasm --target web`
+
+pub use scacchista::wasm::*;